@@ -0,0 +1,13 @@
+#!/usr/bin/env bash
+# Regenerates ../starknet_reference_vectors.json by running the Cairo implementation of the shared algorithms.
+# Requires scarb and jq. The ekubo revision that was used is printed so it can be recorded in README.md.
+set -euo pipefail
+
+cd "$(dirname "$0")"
+
+scarb build
+scarb cairo-run --function main --available-gas 1000000000 \
+    | grep '^{' \
+    | jq --slurp --indent 2 . >../starknet_reference_vectors.json
+
+echo "ekubo revision: $(grep -A2 'name = "ekubo"' Scarb.lock | sed -n 's/.*#\([0-9a-f]*\)".*/\1/p')"
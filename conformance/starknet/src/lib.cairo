@@ -0,0 +1,125 @@
+// Prints one reference vector per line in the format of `parse_reference_vectors` in src/conformance/differential.rs.
+// The inputs mirror conformance/twamm_next_sqrt_ratio.json with the fees converted to 0.128 fixed point numbers.
+use ekubo::math::ticks::{sqrt_ratio_to_tick, tick_to_sqrt_ratio};
+use ekubo::math::twamm::calculate_next_sqrt_ratio;
+use ekubo::types::i129::i129;
+
+fn print_tick(tick: i129) -> ByteArray {
+    if tick.sign && tick.mag != 0 {
+        format!("-{}", tick.mag)
+    } else {
+        format!("{}", tick.mag)
+    }
+}
+
+fn tick_vectors(tick: i129) {
+    let sqrt_ratio = tick_to_sqrt_ratio(tick);
+    println!(
+        "{{\"function\":\"tick_to_sqrt_ratio\",\"tick\":{},\"sqrt_ratio\":\"{}\"}}",
+        print_tick(tick),
+        sqrt_ratio,
+    );
+    println!(
+        "{{\"function\":\"sqrt_ratio_to_tick\",\"sqrt_ratio\":\"{}\",\"tick\":{}}}",
+        sqrt_ratio,
+        print_tick(sqrt_ratio_to_tick(sqrt_ratio)),
+    );
+}
+
+fn twamm_vector(
+    description: ByteArray,
+    sqrt_ratio: u256,
+    liquidity: u128,
+    token0_sale_rate: u128,
+    token1_sale_rate: u128,
+    time_elapsed: u32,
+    fee: u128,
+) {
+    let expected_sqrt_ratio = calculate_next_sqrt_ratio(
+        sqrt_ratio, liquidity, token0_sale_rate, token1_sale_rate, time_elapsed, fee,
+    );
+    println!(
+        "{{\"function\":\"twamm_next_sqrt_ratio\",\"description\":\"{}\",\"sqrt_ratio\":\"{}\",\"liquidity\":\"{}\",\"token0_sale_rate\":\"{}\",\"token1_sale_rate\":\"{}\",\"time_elapsed\":{},\"fee\":\"{}\",\"expected_sqrt_ratio\":\"{}\"}}",
+        description,
+        sqrt_ratio,
+        liquidity,
+        token0_sale_rate,
+        token1_sale_rate,
+        time_elapsed,
+        fee,
+        expected_sqrt_ratio,
+    );
+}
+
+fn main() {
+    let ticks: Array<i129> = array![
+        i129 { mag: 0, sign: false },
+        i129 { mag: 1, sign: false },
+        i129 { mag: 1, sign: true },
+        i129 { mag: 100, sign: false },
+        i129 { mag: 100, sign: true },
+        i129 { mag: 1000000, sign: false },
+        i129 { mag: 1000000, sign: true },
+        i129 { mag: 88722883, sign: false },
+        i129 { mag: 88722883, sign: true },
+    ];
+    for tick in ticks {
+        tick_vectors(tick);
+    };
+
+    twamm_vector(
+        "zero_liquidity_price_eq_sale_ratio",
+        0,
+        0,
+        4294967296000000000000000000,
+        4294967296000000000000000000,
+        0,
+        0,
+    );
+    twamm_vector(
+        "price_at_sale_ratio_does_not_move",
+        340282366920938463463374607431768211456,
+        1000000000000000000,
+        4294967296000000000000000000,
+        4294967296000000000000000000,
+        3600,
+        0,
+    );
+    twamm_vector(
+        "low_liquidity_token0_gt_token1",
+        340282366920938463463374607431768211456,
+        1,
+        8589934592000000000000000000,
+        4294967296000000000000000000,
+        16,
+        0,
+    );
+    twamm_vector(
+        "high_liquidity_token1_gt_token0",
+        340282366920938463463374607431768211456,
+        1000000000000000000000000,
+        4294967296000000000000000000,
+        8589934592000000000000000000,
+        1,
+        0,
+    );
+    twamm_vector(
+        "round_in_direction_of_price",
+        481231811499356508086519009265716982182,
+        70710696755630728101718334,
+        10526880627450980392156862745,
+        10526880627450980392156862745,
+        2040,
+        0,
+    );
+    // 9223372036854775 as a 0.64 fixed point number, i.e. 0.05%, shifted by 64 bits
+    twamm_vector(
+        "example_solidity_upper",
+        2738179289227384381927918250491904,
+        4472135213867,
+        3728260255814876407785,
+        1597830095238095,
+        2688,
+        170141183460469216826718092158566400,
+    );
+}
@@ -1,5 +1,5 @@
-use crate::math::muldiv::{muldiv, MuldivError};
-use crate::math::uint::U256;
+use crate::muldiv::{muldiv, MuldivError};
+use crate::uint::U256;
 use num_traits::Zero;
 
 fn sort_ratios(sqrt_ratio_a: U256, sqrt_ratio_b: U256) -> Option<(U256, U256)> {
@@ -57,7 +57,7 @@ pub fn amount0_delta(
 #[cfg(test)]
 mod amount0_delta_tests {
     use super::*;
-    use crate::math::uint::U256;
+    use crate::uint::U256;
 
     #[test]
     fn price_down() {
@@ -143,8 +143,8 @@ pub fn amount1_delta(
 #[cfg(test)]
 mod amount1_delta_tests {
     use super::*;
-    use crate::math::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
-    use crate::math::uint::U256;
+    use crate::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+    use crate::uint::U256;
 
     #[test]
     fn price_down() {
@@ -0,0 +1,189 @@
+use crate::tick::to_sqrt_ratio;
+use crate::uint::{u256_to_float_base_x128, U256};
+
+// Sensitivities of the value of a liquidity position, denominated in token1, to the price of token0 in token1.
+// Prices are in raw token units, i.e. not adjusted for decimals.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PositionGreeks {
+    // The first derivative of the position value with respect to price, which equals the amount of token0 held
+    pub delta: f64,
+    // The second derivative of the position value with respect to price, which is never positive
+    pub gamma: f64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PositionGreeksError {
+    /// The lower sqrt ratio must be non-zero and less than the upper sqrt ratio.
+    InvalidSqrtRatioRange,
+    /// One of the bounds is not a valid tick.
+    InvalidTick(i32),
+}
+
+// Computes the delta and gamma of a position with the given liquidity between the lower and upper sqrt ratio,
+// at the current sqrt ratio of the pool
+pub fn position_greeks(
+    sqrt_ratio: U256,
+    sqrt_ratio_lower: U256,
+    sqrt_ratio_upper: U256,
+    liquidity: u128,
+) -> Result<PositionGreeks, PositionGreeksError> {
+    if sqrt_ratio_lower.is_zero() || sqrt_ratio_lower >= sqrt_ratio_upper {
+        return Err(PositionGreeksError::InvalidSqrtRatioRange);
+    }
+
+    let liquidity = liquidity as f64;
+    let sqrt_price = u256_to_float_base_x128(sqrt_ratio);
+    let sqrt_price_lower = u256_to_float_base_x128(sqrt_ratio_lower);
+    let sqrt_price_upper = u256_to_float_base_x128(sqrt_ratio_upper);
+
+    Ok(if sqrt_ratio <= sqrt_ratio_lower {
+        // entirely in token0, so the value is linear in price
+        PositionGreeks {
+            delta: liquidity * (1f64 / sqrt_price_lower - 1f64 / sqrt_price_upper),
+            gamma: 0f64,
+        }
+    } else if sqrt_ratio >= sqrt_ratio_upper {
+        // entirely in token1, so the value does not depend on price
+        PositionGreeks {
+            delta: 0f64,
+            gamma: 0f64,
+        }
+    } else {
+        // V(P) = L * (2 * sqrt(P) - sqrt(P_lower) - P / sqrt(P_upper))
+        PositionGreeks {
+            delta: liquidity * (1f64 / sqrt_price - 1f64 / sqrt_price_upper),
+            gamma: -liquidity / (2f64 * sqrt_price * sqrt_price * sqrt_price),
+        }
+    })
+}
+
+// Same as position_greeks, with the bounds of the position given as ticks
+pub fn position_greeks_for_ticks(
+    sqrt_ratio: U256,
+    tick_lower: i32,
+    tick_upper: i32,
+    liquidity: u128,
+) -> Result<PositionGreeks, PositionGreeksError> {
+    position_greeks(
+        sqrt_ratio,
+        to_sqrt_ratio(tick_lower).ok_or(PositionGreeksError::InvalidTick(tick_lower))?,
+        to_sqrt_ratio(tick_upper).ok_or(PositionGreeksError::InvalidTick(tick_upper))?,
+        liquidity,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::delta::amount0_delta;
+
+    const ONE: U256 = U256([0, 0, 1, 0]);
+
+    // The value of the position in token1 at the given sqrt price
+    fn position_value(sqrt_price: f64, lower: f64, upper: f64, liquidity: f64) -> f64 {
+        let sqrt_price = sqrt_price.clamp(lower, upper);
+        let amount0 = liquidity * (1f64 / sqrt_price - 1f64 / upper);
+        let amount1 = liquidity * (sqrt_price - lower);
+        amount0 * sqrt_price * sqrt_price + amount1
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!(
+            (a - b).abs() <= 1e-6 * a.abs().max(b.abs()),
+            "{} != {}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn test_invalid_range() {
+        assert_eq!(
+            position_greeks(ONE, ONE, ONE, 1),
+            Err(PositionGreeksError::InvalidSqrtRatioRange)
+        );
+        assert_eq!(
+            position_greeks(ONE, U256::zero(), ONE, 1),
+            Err(PositionGreeksError::InvalidSqrtRatioRange)
+        );
+        assert_eq!(
+            position_greeks_for_ticks(ONE, -100, 100_000_000, 1),
+            Err(PositionGreeksError::InvalidTick(100_000_000))
+        );
+    }
+
+    #[test]
+    fn test_delta_is_amount0_held() {
+        let sqrt_ratio = to_sqrt_ratio(500).unwrap();
+        let upper = to_sqrt_ratio(1000).unwrap();
+        let liquidity = 1_000_000_000_000u128;
+
+        let greeks = position_greeks_for_ticks(sqrt_ratio, -1000, 1000, liquidity).unwrap();
+        let amount0 = amount0_delta(sqrt_ratio, upper, liquidity, false).unwrap();
+        assert_close(greeks.delta, amount0 as f64);
+        assert!(greeks.gamma < 0f64);
+    }
+
+    #[test]
+    fn test_matches_finite_differences() {
+        let liquidity = 1_000_000_000_000u128;
+        let sqrt_ratio_lower = to_sqrt_ratio(-200_000).unwrap();
+        let sqrt_ratio_upper = to_sqrt_ratio(300_000).unwrap();
+
+        let sqrt_ratio = to_sqrt_ratio(0).unwrap();
+        let greeks =
+            position_greeks(sqrt_ratio, sqrt_ratio_lower, sqrt_ratio_upper, liquidity).unwrap();
+
+        let (lower, upper) = (
+            u256_to_float_base_x128(sqrt_ratio_lower),
+            u256_to_float_base_x128(sqrt_ratio_upper),
+        );
+        let value = |price: f64| {
+            // bisect the square root since core has no sqrt for f64
+            let (mut lo, mut hi) = (0f64, 2f64);
+            for _ in 0..100 {
+                let mid = (lo + hi) / 2f64;
+                if mid * mid < price {
+                    lo = mid;
+                } else {
+                    hi = mid;
+                }
+            }
+            position_value(lo, lower, upper, liquidity as f64)
+        };
+
+        let h = 1e-4;
+        let delta = (value(1f64 + h) - value(1f64 - h)) / (2f64 * h);
+        let gamma = (value(1f64 + h) - 2f64 * value(1f64) + value(1f64 - h)) / (h * h);
+        assert_close(greeks.delta, delta);
+        assert!((greeks.gamma - gamma).abs() <= 1e-3 * greeks.gamma.abs());
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        let liquidity = 1_000_000_000_000u128;
+
+        let below =
+            position_greeks_for_ticks(to_sqrt_ratio(-2000).unwrap(), -1000, 1000, liquidity)
+                .unwrap();
+        let amount0 = amount0_delta(
+            to_sqrt_ratio(-1000).unwrap(),
+            to_sqrt_ratio(1000).unwrap(),
+            liquidity,
+            false,
+        )
+        .unwrap();
+        assert_close(below.delta, amount0 as f64);
+        assert_eq!(below.gamma, 0f64);
+
+        let above = position_greeks_for_ticks(to_sqrt_ratio(2000).unwrap(), -1000, 1000, liquidity)
+            .unwrap();
+        assert_eq!(
+            above,
+            PositionGreeks {
+                delta: 0f64,
+                gamma: 0f64
+            }
+        );
+    }
+}
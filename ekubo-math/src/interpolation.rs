@@ -0,0 +1,232 @@
+use crate::muldiv::{muldiv, U512};
+use crate::tick::{sqrt_ratio_to_tick, to_sqrt_ratio_clamped};
+use crate::uint::U256;
+use alloc::vec::Vec;
+
+// Fractions are 0.128 fixed point numbers, i.e. FRACTION_ONE represents 1
+pub const FRACTION_ONE: U256 = U256([0, 0, 1, 0]);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum InterpolationError {
+    /// The fraction is greater than one.
+    InvalidFraction,
+    /// The lower bound is greater than the upper bound.
+    InvalidRange,
+    /// A scale must have at least one interval.
+    ZeroIntervals,
+}
+
+// How intermediate points between two sqrt ratios are spaced
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Interpolation {
+    /// Evenly spaced in sqrt ratio.
+    SqrtRatioLinear,
+    /// Evenly spaced in ticks, i.e. evenly spaced on a logarithmic price scale.
+    TickLinear,
+    /// Evenly spaced in price.
+    PriceLinear,
+}
+
+fn check_bounds<T: PartialOrd>(lower: T, upper: T, t: U256) -> Result<(), InterpolationError> {
+    if t > FRACTION_ONE {
+        Err(InterpolationError::InvalidFraction)
+    } else if lower > upper {
+        Err(InterpolationError::InvalidRange)
+    } else {
+        Ok(())
+    }
+}
+
+// Returns the sqrt ratio at the fraction t of the way from lower to upper, rounded down
+pub fn sqrt_ratio_at_fraction(
+    lower: U256,
+    upper: U256,
+    t: U256,
+) -> Result<U256, InterpolationError> {
+    check_bounds(lower, upper, t)?;
+    // cannot overflow since t <= 1
+    Ok(lower + muldiv(upper - lower, t, FRACTION_ONE, false).unwrap())
+}
+
+// Returns the tick at the fraction t of the way from the lower to the upper tick, rounded down
+pub fn tick_at_fraction(lower: i32, upper: i32, t: U256) -> Result<i32, InterpolationError> {
+    check_bounds(lower, upper, t)?;
+    let offset = muldiv(
+        U256::from((upper as i64 - lower as i64) as u64),
+        t,
+        FRACTION_ONE,
+        false,
+    )
+    .unwrap();
+    Ok((lower as i64 + offset.low_u64() as i64) as i32)
+}
+
+// Returns the sqrt ratio whose price is at the fraction t of the way from the price of lower to the price of upper,
+// rounded down
+pub fn sqrt_ratio_at_fraction_price_linear(
+    lower: U256,
+    upper: U256,
+    t: U256,
+) -> Result<U256, InterpolationError> {
+    check_bounds(lower, upper, t)?;
+
+    let lower_squared = U512::from(lower) * U512::from(lower);
+    let difference = U512::from(upper) * U512::from(upper) - lower_squared;
+    // split the difference so that multiplying by t cannot overflow
+    let t = U512::from(t);
+    let offset =
+        (difference >> 128) * t + (((difference & U512::from(U256::MAX >> 128)) * t) >> 128);
+
+    // the square root is between lower and upper, so it fits in a U256
+    Ok(U256::try_from((lower_squared + offset).integer_sqrt()).unwrap())
+}
+
+// Returns the sqrt ratio at the fraction t of the way from lower to upper using the given interpolation. Tick linear
+// interpolation is between the ticks of the bounds, clamped to the bounds.
+pub fn interpolate_sqrt_ratio(
+    lower: U256,
+    upper: U256,
+    t: U256,
+    interpolation: Interpolation,
+) -> Result<U256, InterpolationError> {
+    match interpolation {
+        Interpolation::SqrtRatioLinear => sqrt_ratio_at_fraction(lower, upper, t),
+        Interpolation::PriceLinear => sqrt_ratio_at_fraction_price_linear(lower, upper, t),
+        Interpolation::TickLinear => {
+            check_bounds(lower, upper, t)?;
+            if t == FRACTION_ONE {
+                return Ok(upper);
+            }
+            let tick = tick_at_fraction(sqrt_ratio_to_tick(lower), sqrt_ratio_to_tick(upper), t)?;
+            Ok(to_sqrt_ratio_clamped(tick).clamp(lower, upper))
+        }
+    }
+}
+
+// Returns intervals + 1 sqrt ratios from lower to upper (inclusive) spaced according to the interpolation, e.g. for
+// the gridlines of a price axis
+pub fn sqrt_ratio_scale(
+    lower: U256,
+    upper: U256,
+    intervals: u32,
+    interpolation: Interpolation,
+) -> Result<Vec<U256>, InterpolationError> {
+    if intervals == 0 {
+        return Err(InterpolationError::ZeroIntervals);
+    }
+
+    (0..=intervals)
+        .map(|i| {
+            // rounding up makes points that fall exactly on an integer, e.g. a tick, land on it
+            let t = muldiv(FRACTION_ONE, U256::from(i), U256::from(intervals), true).unwrap();
+            interpolate_sqrt_ratio(lower, upper, t, interpolation)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::{to_sqrt_ratio, MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO, MIN_TICK};
+
+    const HALF: U256 = U256([0, 0x8000000000000000, 0, 0]);
+
+    #[test]
+    fn test_sqrt_ratio_at_fraction() {
+        let (lower, upper) = (U256::from(100), U256::from(201));
+        assert_eq!(
+            sqrt_ratio_at_fraction(lower, upper, U256::zero()),
+            Ok(lower)
+        );
+        assert_eq!(
+            sqrt_ratio_at_fraction(lower, upper, HALF),
+            Ok(U256::from(150))
+        );
+        assert_eq!(
+            sqrt_ratio_at_fraction(lower, upper, FRACTION_ONE),
+            Ok(upper)
+        );
+        assert_eq!(
+            sqrt_ratio_at_fraction(MIN_SQRT_RATIO, MAX_SQRT_RATIO, FRACTION_ONE),
+            Ok(MAX_SQRT_RATIO)
+        );
+    }
+
+    #[test]
+    fn test_tick_at_fraction() {
+        assert_eq!(tick_at_fraction(-100, 100, HALF), Ok(0));
+        assert_eq!(tick_at_fraction(-100, 101, HALF), Ok(0));
+        assert_eq!(tick_at_fraction(-101, 100, HALF), Ok(-1));
+        assert_eq!(
+            tick_at_fraction(MIN_TICK, MAX_TICK, FRACTION_ONE),
+            Ok(MAX_TICK)
+        );
+        assert_eq!(
+            tick_at_fraction(MIN_TICK, MAX_TICK, U256::zero()),
+            Ok(MIN_TICK)
+        );
+    }
+
+    #[test]
+    fn test_price_linear() {
+        let (lower, upper) = (to_sqrt_ratio(0).unwrap(), FRACTION_ONE * 2);
+        // halfway between prices 1 and 4 is 2.5
+        let sqrt_ratio = sqrt_ratio_at_fraction_price_linear(lower, upper, HALF).unwrap();
+        let price_x256 = U512::from(sqrt_ratio) * U512::from(sqrt_ratio);
+        let expected_x256 = U512::from(5u64) << 255;
+        assert!(price_x256 <= expected_x256);
+        assert!((U512::from(sqrt_ratio + 1) * U512::from(sqrt_ratio + 1)) > expected_x256);
+
+        assert_eq!(
+            sqrt_ratio_at_fraction_price_linear(MIN_SQRT_RATIO, MAX_SQRT_RATIO, FRACTION_ONE),
+            Ok(MAX_SQRT_RATIO)
+        );
+        assert_eq!(
+            sqrt_ratio_at_fraction_price_linear(MIN_SQRT_RATIO, MAX_SQRT_RATIO, U256::zero()),
+            Ok(MIN_SQRT_RATIO)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            sqrt_ratio_at_fraction(U256::zero(), U256::one(), FRACTION_ONE + 1),
+            Err(InterpolationError::InvalidFraction)
+        );
+        assert_eq!(
+            sqrt_ratio_at_fraction_price_linear(U256::one(), U256::zero(), HALF),
+            Err(InterpolationError::InvalidRange)
+        );
+        assert_eq!(
+            sqrt_ratio_scale(U256::zero(), U256::one(), 0, Interpolation::SqrtRatioLinear),
+            Err(InterpolationError::ZeroIntervals)
+        );
+    }
+
+    #[test]
+    fn test_scales_are_monotonic_and_include_bounds() {
+        let lower = to_sqrt_ratio(-50_000).unwrap();
+        let upper = to_sqrt_ratio(70_000).unwrap();
+
+        for interpolation in [
+            Interpolation::SqrtRatioLinear,
+            Interpolation::TickLinear,
+            Interpolation::PriceLinear,
+        ] {
+            let scale = sqrt_ratio_scale(lower, upper, 12, interpolation).unwrap();
+            assert_eq!(scale.len(), 13);
+            assert_eq!(scale[0], lower);
+            assert_eq!(scale[12], upper);
+            assert!(scale.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+
+        // tick linear points are exactly at the interpolated ticks
+        let scale = sqrt_ratio_scale(lower, upper, 12, Interpolation::TickLinear).unwrap();
+        for (i, sqrt_ratio) in scale.into_iter().enumerate() {
+            assert_eq!(
+                sqrt_ratio,
+                to_sqrt_ratio(-50_000 + i as i32 * 10_000).unwrap()
+            );
+        }
+    }
+}
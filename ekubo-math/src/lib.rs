@@ -1,7 +1,14 @@
+#![no_std]
+extern crate alloc;
+
 pub mod delta;
+pub mod greeks;
+pub mod interpolation;
+pub mod liquidity;
 pub mod muldiv;
 pub mod sqrt_ratio;
 pub mod swap;
+mod tables;
 pub mod tick;
 pub mod twamm;
 pub mod uint;
@@ -0,0 +1,164 @@
+use crate::delta::{amount0_delta, amount1_delta, AmountDeltaError};
+use crate::muldiv::muldiv;
+use crate::uint::U256;
+
+const TWO_POW_128: U256 = U256([0, 0, 1, 0]);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LiquidityError {
+    /// The lower sqrt ratio must be non-zero and less than the upper sqrt ratio.
+    InvalidSqrtRatioRange,
+    /// The liquidity does not fit in a u128.
+    Overflow,
+}
+
+fn to_u128(value: U256) -> Result<u128, LiquidityError> {
+    if value > U256::from(u128::MAX) {
+        Err(LiquidityError::Overflow)
+    } else {
+        Ok(value.low_u128())
+    }
+}
+
+// The liquidity between the two sqrt ratios that is backed by the amount of token0
+fn liquidity_for_amount0(
+    sqrt_ratio_lower: U256,
+    sqrt_ratio_upper: U256,
+    amount0: u128,
+) -> Result<u128, LiquidityError> {
+    let product_x128 = muldiv(sqrt_ratio_lower, sqrt_ratio_upper, TWO_POW_128, false)
+        .map_err(|_| LiquidityError::Overflow)?;
+    to_u128(
+        muldiv(
+            U256::from(amount0),
+            product_x128,
+            sqrt_ratio_upper - sqrt_ratio_lower,
+            false,
+        )
+        .map_err(|_| LiquidityError::Overflow)?,
+    )
+}
+
+// The liquidity between the two sqrt ratios that is backed by the amount of token1
+fn liquidity_for_amount1(
+    sqrt_ratio_lower: U256,
+    sqrt_ratio_upper: U256,
+    amount1: u128,
+) -> Result<u128, LiquidityError> {
+    to_u128(
+        muldiv(
+            U256::from(amount1),
+            TWO_POW_128,
+            sqrt_ratio_upper - sqrt_ratio_lower,
+            false,
+        )
+        .map_err(|_| LiquidityError::Overflow)?,
+    )
+}
+
+// Returns the largest liquidity of a position between the lower and upper sqrt ratio that can be deposited with the
+// given amounts at the current sqrt ratio, rounded down
+pub fn max_liquidity(
+    sqrt_ratio: U256,
+    sqrt_ratio_lower: U256,
+    sqrt_ratio_upper: U256,
+    amount0: u128,
+    amount1: u128,
+) -> Result<u128, LiquidityError> {
+    if sqrt_ratio_lower.is_zero() || sqrt_ratio_lower >= sqrt_ratio_upper {
+        return Err(LiquidityError::InvalidSqrtRatioRange);
+    }
+
+    if sqrt_ratio <= sqrt_ratio_lower {
+        liquidity_for_amount0(sqrt_ratio_lower, sqrt_ratio_upper, amount0)
+    } else if sqrt_ratio >= sqrt_ratio_upper {
+        liquidity_for_amount1(sqrt_ratio_lower, sqrt_ratio_upper, amount1)
+    } else {
+        Ok(
+            liquidity_for_amount0(sqrt_ratio, sqrt_ratio_upper, amount0)?.min(
+                liquidity_for_amount1(sqrt_ratio_lower, sqrt_ratio, amount1)?,
+            ),
+        )
+    }
+}
+
+// Returns the amounts of token0 and token1 of a position with the given liquidity at the current sqrt ratio. Amounts
+// should be rounded up when depositing and down when withdrawing.
+pub fn position_amounts(
+    sqrt_ratio: U256,
+    sqrt_ratio_lower: U256,
+    sqrt_ratio_upper: U256,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<(u128, u128), AmountDeltaError> {
+    let clamped = sqrt_ratio.clamp(sqrt_ratio_lower, sqrt_ratio_upper);
+    Ok((
+        amount0_delta(clamped, sqrt_ratio_upper, liquidity, round_up)?,
+        amount1_delta(sqrt_ratio_lower, clamped, liquidity, round_up)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::to_sqrt_ratio;
+
+    #[test]
+    fn test_max_liquidity_round_trip() {
+        let lower = to_sqrt_ratio(-1000).unwrap();
+        let upper = to_sqrt_ratio(1000).unwrap();
+
+        for sqrt_ratio in [
+            to_sqrt_ratio(-2000).unwrap(),
+            U256::one() << 128,
+            to_sqrt_ratio(2000).unwrap(),
+        ] {
+            let (amount0, amount1) =
+                position_amounts(sqrt_ratio, lower, upper, 1_000_000_000, false).unwrap();
+            let liquidity = max_liquidity(sqrt_ratio, lower, upper, amount0, amount1).unwrap();
+
+            // rounding only ever loses liquidity, and the required amounts never exceed the available amounts
+            assert!(liquidity <= 1_000_000_000 && liquidity > 999_990_000);
+            let (required0, required1) =
+                position_amounts(sqrt_ratio, lower, upper, liquidity, true).unwrap();
+            assert!(required0 <= amount0 && required1 <= amount1);
+        }
+    }
+
+    #[test]
+    fn test_max_liquidity_limited_by_one_token() {
+        let lower = to_sqrt_ratio(-1000).unwrap();
+        let upper = to_sqrt_ratio(1000).unwrap();
+        let sqrt_ratio = U256::one() << 128;
+
+        let only_token0 = max_liquidity(sqrt_ratio, lower, upper, 1_000_000, 0).unwrap();
+        assert_eq!(only_token0, 0);
+
+        let balanced = max_liquidity(sqrt_ratio, lower, upper, 1_000_000, 1_000_000).unwrap();
+        let more_token0 = max_liquidity(sqrt_ratio, lower, upper, 2_000_000, 1_000_000).unwrap();
+        assert_eq!(balanced, more_token0);
+    }
+
+    #[test]
+    fn test_max_liquidity_errors() {
+        let sqrt_ratio = U256::one() << 128;
+        assert_eq!(
+            max_liquidity(sqrt_ratio, sqrt_ratio, sqrt_ratio, 1, 1),
+            Err(LiquidityError::InvalidSqrtRatioRange)
+        );
+        assert_eq!(
+            max_liquidity(sqrt_ratio, U256::zero(), sqrt_ratio, 1, 1),
+            Err(LiquidityError::InvalidSqrtRatioRange)
+        );
+        assert_eq!(
+            max_liquidity(
+                sqrt_ratio,
+                to_sqrt_ratio(-1).unwrap(),
+                to_sqrt_ratio(1).unwrap(),
+                u128::MAX,
+                u128::MAX
+            ),
+            Err(LiquidityError::Overflow)
+        );
+    }
+}
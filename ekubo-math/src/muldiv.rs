@@ -1,4 +1,4 @@
-use crate::math::uint::U256;
+use crate::uint::U256;
 use num_traits::Zero;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -8,7 +8,7 @@ pub enum MuldivError {
 }
 
 uint::construct_uint! {
-    struct U512(8);
+    pub(crate) struct U512(8);
 }
 
 impl From<U256> for U512 {
@@ -39,6 +39,47 @@ impl TryFrom<U512> for U256 {
 }
 
 pub fn muldiv(x: U256, y: U256, d: U256, round_up: bool) -> Result<U256, MuldivError> {
+    if let Some(result) = muldiv_fast(x, y, d, round_up) {
+        return result;
+    }
+
+    muldiv_portable(x, y, d, round_up)
+}
+
+// Handles the common cases where the product fits in 256 bits without going through U512, computing operands that fit
+// in a u128 directly. It runs on every target so that all of them take the same path. Returns None if the product may
+// not fit in 256 bits.
+fn muldiv_fast(x: U256, y: U256, d: U256, round_up: bool) -> Option<Result<U256, MuldivError>> {
+    if d.is_zero() {
+        return Some(Err(MuldivError::DenominatorZero));
+    }
+
+    if x.bits() <= 128 && y.bits() <= 128 && d.bits() <= 128 {
+        if let Some(product) = x.low_u128().checked_mul(y.low_u128()) {
+            let d = d.low_u128();
+            let (quotient, remainder) = (product / d, product % d);
+            // the quotient is at most u128::MAX / 2 whenever there is a remainder, so this cannot overflow
+            return Some(Ok(U256::from(
+                quotient + (round_up && !remainder.is_zero()) as u128,
+            )));
+        }
+    }
+
+    if x.bits() + y.bits() <= 256 {
+        let (quotient, remainder) = (x * y).div_mod(d);
+        // the quotient is less than U256::MAX whenever there is a remainder, so this cannot overflow
+        return Some(Ok(if round_up && !remainder.is_zero() {
+            quotient + 1
+        } else {
+            quotient
+        }));
+    }
+
+    None
+}
+
+// The reference implementation of muldiv that works on all architectures
+pub fn muldiv_portable(x: U256, y: U256, d: U256, round_up: bool) -> Result<U256, MuldivError> {
     if d.is_zero() {
         return Err(MuldivError::DenominatorZero);
     }
@@ -67,7 +108,9 @@ pub fn muldiv(x: U256, y: U256, d: U256, round_up: bool) -> Result<U256, MuldivE
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::math::uint::U256;
+    use crate::uint::U256;
+    use alloc::vec;
+    use alloc::vec::Vec;
 
     #[test]
     fn test_muldiv_no_rounding() {
@@ -312,4 +355,79 @@ mod tests {
         let expected = U256::from_dec_str("226854911280625642308916404954512140970").unwrap();
         assert_eq!(result, expected);
     }
+
+    // Values around the boundaries of the fast paths
+    fn boundary_values() -> Vec<U256> {
+        let mut values = vec![U256::zero(), U256::MAX, U256::MAX - 1];
+        for bits in [1, 63, 64, 65, 127, 128, 129, 191, 192, 255] {
+            let power = U256::one() << bits;
+            values.extend([power - 1, power, power + 1]);
+        }
+        values
+    }
+
+    // Deterministic pseudo random values of every bit length (splitmix64)
+    fn random_values(seed: u64, count: usize) -> Vec<U256> {
+        let mut state = seed;
+        let mut next_u64 = move || {
+            state = state.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+
+        (0..count)
+            .map(|_| {
+                let value = U256([next_u64(), next_u64(), next_u64(), next_u64()]);
+                value >> (next_u64() % 256) as usize
+            })
+            .collect()
+    }
+
+    fn assert_matches_portable(x: U256, y: U256, d: U256) {
+        for round_up in [false, true] {
+            assert_eq!(
+                muldiv(x, y, d, round_up),
+                muldiv_portable(x, y, d, round_up),
+                "muldiv({x}, {y}, {d}, {round_up})"
+            );
+        }
+    }
+
+    #[test]
+    fn test_differential_boundary_values() {
+        let values = boundary_values();
+        for &x in &values {
+            for &y in &values {
+                for &d in &values {
+                    assert_matches_portable(x, y, d);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_differential_random_values() {
+        let values = random_values(0x5eed, 30_000);
+        for operands in values.chunks_exact(3) {
+            assert_matches_portable(operands[0], operands[1], operands[2]);
+        }
+
+        // denominators close to the product exercise rounding and results near zero
+        for pair in random_values(0xd1ff, 10_000).chunks_exact(2) {
+            let (x, y) = (pair[0], pair[1]);
+            if x.bits() + y.bits() <= 256 && !(x * y).is_zero() {
+                let product = x * y;
+                let denominators = [
+                    Some(product - 1),
+                    Some(product),
+                    product.checked_add(1.into()),
+                ];
+                for d in denominators.into_iter().flatten().chain([x, y]) {
+                    assert_matches_portable(x, y, d);
+                }
+            }
+        }
+    }
 }
@@ -1,5 +1,6 @@
-use crate::math::muldiv::{muldiv, MuldivError};
-use crate::math::uint::U256;
+use crate::muldiv::{muldiv, MuldivError};
+use crate::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+use crate::uint::U256;
 use num_traits::Zero;
 
 #[derive(Debug, PartialEq)]
@@ -86,10 +87,128 @@ pub fn next_sqrt_ratio_from_amount1(
     }
 }
 
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+const TWO_POW_96: U256 = U256([0, 4294967296, 0, 0]);
+
+// Returns the sqrt ratio limit for a swap that may move the price at most `max_slippage_bps` basis points away from
+// the price at `sqrt_ratio`, in the direction of the swap.
+//
+// The direction can be computed with `crate::swap::is_price_increasing`. The limit is rounded towards the
+// current price, so the price at the limit is never worse than the requested slippage, and it is clamped to the
+// valid sqrt ratio range.
+pub fn sqrt_ratio_limit_from_slippage(
+    sqrt_ratio: U256,
+    is_price_increasing: bool,
+    max_slippage_bps: u32,
+) -> U256 {
+    let (numerator, round_up) = if is_price_increasing {
+        (BPS_DENOMINATOR + max_slippage_bps as u64, false)
+    } else {
+        match BPS_DENOMINATOR.checked_sub(max_slippage_bps as u64) {
+            Some(numerator) => (numerator, true),
+            None => return MIN_SQRT_RATIO,
+        }
+    };
+
+    // sqrt(numerator / BPS_DENOMINATOR) as a 96 bit fixed point number, the radicand is numerator / BPS_DENOMINATOR
+    // as a 192 bit fixed point number
+    let (radicand, remainder) = (U256::from(numerator) << 192).div_mod(BPS_DENOMINATOR.into());
+    let radicand = if round_up && !remainder.is_zero() {
+        radicand + 1
+    } else {
+        radicand
+    };
+    let factor_x96 = radicand.integer_sqrt();
+    let factor_x96 = if round_up && factor_x96 * factor_x96 < radicand {
+        factor_x96 + 1
+    } else {
+        factor_x96
+    };
+
+    muldiv(sqrt_ratio, factor_x96, TWO_POW_96, round_up).map_or(MAX_SQRT_RATIO, |limit| {
+        limit.clamp(MIN_SQRT_RATIO, MAX_SQRT_RATIO)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::muldiv::U512;
+
+    fn square(x: U256) -> U512 {
+        U512::from(x) * U512::from(x)
+    }
+
+    #[test]
+    fn test_sqrt_ratio_limit_from_slippage() {
+        let sqrt_ratio = U256::one() << 128;
+
+        // slightly below sqrt(1.005) and slightly above sqrt(0.995) as 128.128 fixed point numbers
+        let up = sqrt_ratio_limit_from_slippage(sqrt_ratio, true, 50);
+        let down = sqrt_ratio_limit_from_slippage(sqrt_ratio, false, 50);
+        assert_eq!(
+            up,
+            U256::from_dec_str("341132012106021466536315988668221227008").unwrap()
+        );
+        assert_eq!(
+            down,
+            U256::from_dec_str("339430594954446636497776550107626340352").unwrap()
+        );
+
+        assert_eq!(
+            sqrt_ratio_limit_from_slippage(sqrt_ratio, true, 0),
+            sqrt_ratio
+        );
+        assert_eq!(
+            sqrt_ratio_limit_from_slippage(sqrt_ratio, false, 0),
+            sqrt_ratio
+        );
+    }
+
+    #[test]
+    fn test_sqrt_ratio_limit_from_slippage_never_exceeds_slippage() {
+        for sqrt_ratio in [
+            MIN_SQRT_RATIO * 3,
+            U256::from(123456789u64) << 100,
+            U256::one() << 128,
+            MAX_SQRT_RATIO / 3,
+        ] {
+            for bps in [1, 50, 100, 9_999] {
+                let up = sqrt_ratio_limit_from_slippage(sqrt_ratio, true, bps);
+                // (up / sqrt_ratio)^2 <= 1 + bps / 10000
+                assert!(
+                    square(up) * U512::from(BPS_DENOMINATOR)
+                        <= square(sqrt_ratio) * U512::from(BPS_DENOMINATOR + bps as u64)
+                );
+
+                let down = sqrt_ratio_limit_from_slippage(sqrt_ratio, false, bps);
+                assert!(
+                    square(down) * U512::from(BPS_DENOMINATOR)
+                        >= square(sqrt_ratio) * U512::from(BPS_DENOMINATOR - bps as u64)
+                );
+                assert!(down < sqrt_ratio && sqrt_ratio < up);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sqrt_ratio_limit_from_slippage_clamped() {
+        assert_eq!(
+            sqrt_ratio_limit_from_slippage(U256::one() << 128, false, 10_000),
+            MIN_SQRT_RATIO
+        );
+        assert_eq!(
+            sqrt_ratio_limit_from_slippage(MAX_SQRT_RATIO, true, 50),
+            MAX_SQRT_RATIO
+        );
+        assert_eq!(
+            sqrt_ratio_limit_from_slippage(MIN_SQRT_RATIO, false, 50),
+            MIN_SQRT_RATIO
+        );
+    }
+
     #[test]
     fn test_next_sqrt_ratio_from_amount0_add_price_goes_down() {
         // Corresponds to:
@@ -1,6 +1,6 @@
-use crate::math::delta::{amount0_delta, amount1_delta, AmountDeltaError};
-use crate::math::sqrt_ratio::{next_sqrt_ratio_from_amount0, next_sqrt_ratio_from_amount1};
-use crate::math::uint::U256;
+use crate::delta::{amount0_delta, amount1_delta, AmountDeltaError};
+use crate::sqrt_ratio::{next_sqrt_ratio_from_amount0, next_sqrt_ratio_from_amount1};
+use crate::uint::U256;
 use core::num::TryFromIntError;
 use num_traits::Zero;
 
@@ -185,7 +185,7 @@ pub fn compute_step(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::math::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+    use crate::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
 
     #[test]
     fn zero_amount_token0() {
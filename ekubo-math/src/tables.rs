@@ -0,0 +1,166 @@
+use crate::uint::U256;
+
+// Tables of magic numbers used by the math. Each table documents how it is generated, and the tests at the bottom of
+// this file regenerate every table with exact integer arithmetic and compare it to the values below. New tables should
+// be added the same way.
+
+// MASKS[i] is the sqrt ratio of tick -2^i as a 128.128 fixed point number, i.e. 1.000001^(-2^i / 2) * 2^128, rounded
+// to the nearest integer. to_sqrt_ratio multiplies the masks of the set bits of the absolute value of the tick.
+pub(crate) const MASKS: [U256; 27] = [
+    U256([8987818235631183931, 18446734850344432284, 0, 0]),
+    U256([1390292817054524432, 18446725626983924632, 0, 0]),
+    U256([6106104599673403081, 18446707180276744355, 0, 0]),
+    U256([11001558419889720088, 18446670286917723849, 0, 0]),
+    U256([11758220747761187196, 18446596500421042512, 0, 0]),
+    U256([13410380190397192564, 18446448928313114404, 0, 0]),
+    U256([16901990496071521224, 18446153787638963396, 0, 0]),
+    U256([2628633744169581664, 18445563520457217769, 0, 0]),
+    U256([16741406942698519205, 18444383042757836574, 0, 0]),
+    U256([8444515413536692068, 18442022313998591526, 0, 0]),
+    U256([9306074004969915320, 18437301762902803792, 0, 0]),
+    U256([1215727185815661655, 18427864285319361663, 0, 0]),
+    U256([4836152305972799785, 18409003819927758022, 0, 0]),
+    U256([465769373252535706, 18371340779054097314, 0, 0]),
+    U256([11626538800767970419, 18296245704473805246, 0, 0]),
+    U256([13511344043162985703, 18146975181141493926, 0, 0]),
+    U256([17542275577360126846, 17852077684229624197, 0, 0]),
+    U256([9306072323298629247, 17276581513264360642, 0, 0]),
+    U256([8354374849381600509, 16180647793008867682, 0, 0]),
+    U256([1840363272369915117, 14192930847592841948, 0, 0]),
+    U256([16571633202497044730, 10920045577671636999, 0, 0]),
+    U256([7981864148337927882, 6464414258794766152, 0, 0]),
+    U256([4190795360855086819, 2265367348423649960, 0, 0]),
+    U256([14440677137918516476, 278200272243057167, 0, 0]),
+    U256([11954280435123913168, 4195612578938288, 0, 0]),
+    U256([1943989925737446246, 954269482040, 0, 0]),
+    U256([6723154418996326713, 49365, 0, 0]),
+];
+
+// EXP2_FACTORS[i] is 2^(2^(-1 - i)) as a 128.128 fixed point number, i.e. the factor that exp2 multiplies by when bit
+// 63 - i of the 64.64 fixed point exponent is set. The values are taken from the original Solidity implementation,
+// where they are truncated and may be one less than the rounded down exact value.
+pub(crate) const EXP2_FACTORS: [U256; 64] = [
+    U256([12896923290648804670, 7640891576956012808, 1, 0]),
+    U256([10185530680776453612, 3490255227380126430, 1, 0]),
+    U256([17854737389604993311, 1669572981167730125, 1, 0]),
+    U256([10057302232697242467, 816707133613602345, 1, 0]),
+    U256([8958942473441515517, 403931097166463918, 1, 0]),
+    U256([17855306115364535784, 200871872941133542, 1, 0]),
+    U256([5361163942339934783, 100163996173424344, 1, 0]),
+    U256([3002515481205445955, 50014196964519265, 1, 0]),
+    U256([18381954890662373275, 24990171141283489, 1, 0]),
+    U256([7863777292665987087, 12490856599448656, 1, 0]),
+    U256([2935407722259146207, 6244371414720417, 1, 0]),
+    U256([4099144763823068965, 3121921530820282, 1, 0]),
+    U256([7830867710921767997, 1560894726863213, 1, 0]),
+    U256([15030865137112770483, 780430854493329, 1, 0]),
+    U256([6072397496287043622, 390211300099399, 1, 0]),
+    U256([439311289473205244, 195104618273796, 1, 0]),
+    U256([3381501736036650923, 97552051194286, 1, 0]),
+    U256([11878183186495682505, 48775961111660, 1, 0]),
+    U256([436289341605138797, 24387964434481, 1, 0]),
+    U256([15706463035261523811, 12193978186905, 1, 0]),
+    U256([10951006159863813672, 6096988085869, 1, 0]),
+    U256([100320500613761579, 3048493791039, 1, 0]),
+    U256([10331567486136386213, 1524246832545, 1, 0]),
+    U256([5442291021268196350, 762123400529, 1, 0]),
+    U256([14320987186005390063, 381061696328, 1, 0]),
+    U256([7754798478847538042, 190530847180, 1, 0]),
+    U256([4025998895411732230, 95265423344, 1, 0]),
+    U256([11273524327873463545, 47632711610, 1, 0]),
+    U256([17175265786997884963, 23816355789, 1, 0]),
+    U256([2248886808180014672, 11908177891, 1, 0]),
+    U256([11068971934550065103, 5954088944, 1, 0]),
+    U256([1103089072964130132, 2977044472, 1, 0]),
+    U256([17890439386703286701, 1488522235, 1, 0]),
+    U256([17891629424345511116, 744261117, 1, 0]),
+    U256([18099946172563701162, 372130558, 1, 0]),
+    U256([9032662942166067630, 186065279, 1, 0]),
+    U256([13735375971908885710, 93032639, 1, 0]),
+    U256([16089978138801990413, 46516319, 1, 0]),
+    U256([17268090635253964293, 23258159, 1, 0]),
+    U256([17857349736731306316, 11629079, 1, 0]),
+    U256([18152030000782816061, 5814539, 1, 0]),
+    U256([18299382811136780613, 2907269, 1, 0]),
+    U256([18373062385895815308, 1453634, 1, 0]),
+    U256([9186530928816069952, 726817, 1, 0]),
+    U256([13816637435229851358, 363408, 1, 0]),
+    U256([6908318701106685822, 181704, 1, 0]),
+    U256([3454159346426282946, 90852, 1, 0]),
+    U256([1727079672181376481, 45426, 1, 0]),
+    U256([863539835832746992, 22713, 1, 0]),
+    U256([9655141954706663992, 11356, 1, 0]),
+    U256([4827570977337210668, 5678, 1, 0]),
+    U256([2413785488664575002, 2839, 1, 0]),
+    U256([10430264781186055726, 1419, 1, 0]),
+    U256([14438504427447551775, 709, 1, 0]),
+    U256([16442624250578488721, 354, 1, 0]),
+    U256([8221312125289228617, 177, 1, 0]),
+    U256([13334028099499386180, 88, 1, 0]),
+    U256([6667014049749692106, 44, 1, 0]),
+    U256([3333507024874845807, 22, 1, 0]),
+    U256([1666753512437422842, 11, 1, 0]),
+    U256([10056748793073487213, 5, 1, 0]),
+    U256([14251746433391519410, 2, 1, 0]),
+    U256([7125873216695759704, 1, 1, 0]),
+    U256([12786308645202655659, 0, 1, 0]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::muldiv::U512;
+
+    // The tables are generated with 192 fractional bits, so the accumulated rounding error is far below the precision of
+    // the 128.128 fixed point results
+    const FRACTION_BITS: usize = 192;
+
+    fn to_x128(value: U512, round: bool) -> U256 {
+        let half = if round {
+            U512::one() << (FRACTION_BITS - 129)
+        } else {
+            U512::zero()
+        };
+        U256::try_from((value + half) >> (FRACTION_BITS - 128)).unwrap()
+    }
+
+    // Starts with 1.000001^(-1/2) = sqrt(1000000 / 1000001) and squares it for each following mask
+    fn generate_masks() -> [U256; 27] {
+        let mut value = ((U512::from(1_000_000u64) << (2 * FRACTION_BITS))
+            / U512::from(1_000_001u64))
+        .integer_sqrt();
+
+        core::array::from_fn(|_| {
+            let mask = to_x128(value, true);
+            value = (value * value) >> FRACTION_BITS;
+            mask
+        })
+    }
+
+    // Starts with 2^(1/2) and takes the square root for each following factor
+    fn generate_exp2_factors() -> [U256; 64] {
+        let mut value = (U512::from(2u64) << (2 * FRACTION_BITS)).integer_sqrt();
+
+        core::array::from_fn(|_| {
+            let factor = to_x128(value, false);
+            value = (value << FRACTION_BITS).integer_sqrt();
+            factor
+        })
+    }
+
+    #[test]
+    fn test_masks_match_generated() {
+        assert_eq!(MASKS, generate_masks());
+    }
+
+    #[test]
+    fn test_exp2_factors_match_generated() {
+        for (i, (factor, generated)) in EXP2_FACTORS.iter().zip(generate_exp2_factors()).enumerate()
+        {
+            assert!(
+                *factor == generated || *factor + 1 == generated,
+                "factor {i}: {factor} vs {generated}"
+            );
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use crate::tables::MASKS;
+use crate::{uint::u256_to_float_base_x128, uint::U256};
+
+const ONE_X128: U256 = U256([0, 0, 1, 0]);
+
+pub const MIN_TICK: i32 = -88722835;
+pub const MAX_TICK: i32 = 88722835;
+pub const MAX_TICK_SPACING: u32 = 698605;
+pub const FULL_RANGE_TICK_SPACING: u32 = 0;
+
+pub const MIN_SQRT_RATIO: U256 = U256([447090492618908, 1, 0, 0]);
+pub const MAX_SQRT_RATIO: U256 = U256([0, 7567914946021818368, 18446296994052723738, 0]);
+
+const TWO_POW_160: U256 = U256([0, 0, 0x100000000, 0]);
+const TWO_POW_128: U256 = U256([0, 0, 1, 0]);
+const TWO_POW_96: U256 = U256([0, 0x0100000000, 0, 0]);
+
+pub fn to_sqrt_ratio(tick: i32) -> Option<U256> {
+    if tick < MIN_TICK || tick > MAX_TICK {
+        return None;
+    }
+
+    let mut ratio = ONE_X128.clone();
+
+    let tick_abs = tick.abs();
+
+    for (i, mask) in MASKS.iter().enumerate() {
+        if (tick_abs & (1 << i)) != 0 {
+            ratio = (ratio * mask) >> 128;
+        }
+    }
+
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+
+    ratio = if ratio >= TWO_POW_160 {
+        (ratio >> 98) << 98
+    } else if ratio >= TWO_POW_128 {
+        (ratio >> 66) << 66
+    } else if ratio >= TWO_POW_96 {
+        (ratio >> 34) << 34
+    } else {
+        (ratio >> 2) << 2
+    };
+
+    Some(ratio)
+}
+
+// The bound that a tick was clamped to
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TickBound {
+    Min,
+    Max,
+}
+
+// Clamps the tick to [MIN_TICK, MAX_TICK], returning the bound that was hit if the tick was out of range
+pub fn clamp_tick(tick: i32) -> (i32, Option<TickBound>) {
+    if tick < MIN_TICK {
+        (MIN_TICK, Some(TickBound::Min))
+    } else if tick > MAX_TICK {
+        (MAX_TICK, Some(TickBound::Max))
+    } else {
+        (tick, None)
+    }
+}
+
+// Same as to_sqrt_ratio, but out of range ticks return MIN_SQRT_RATIO or MAX_SQRT_RATIO instead of None
+pub fn to_sqrt_ratio_clamped(tick: i32) -> U256 {
+    to_sqrt_ratio_clamped_with_bound(tick).0
+}
+
+// Same as to_sqrt_ratio_clamped, but also returns the bound that was hit if the tick was out of range
+pub fn to_sqrt_ratio_clamped_with_bound(tick: i32) -> (U256, Option<TickBound>) {
+    match clamp_tick(tick) {
+        (_, Some(TickBound::Min)) => (MIN_SQRT_RATIO, Some(TickBound::Min)),
+        (_, Some(TickBound::Max)) => (MAX_SQRT_RATIO, Some(TickBound::Max)),
+        // the tick is within range so the conversion cannot fail
+        (tick, None) => (to_sqrt_ratio(tick).unwrap(), None),
+    }
+}
+
+const SQRT_TICK_SIZE: f64 =
+    1.00000049999987500006249996093752734372949220361326815796989439990616646_f64;
+
+pub fn approximate_sqrt_ratio_to_tick(sqrt_ratio: U256) -> i32 {
+    u256_to_float_base_x128(sqrt_ratio)
+        .log(SQRT_TICK_SIZE)
+        .round() as i32
+}
+
+// Returns the greatest tick whose sqrt ratio is less than or equal to the given sqrt ratio, clamped to [MIN_TICK, MAX_TICK]
+pub fn sqrt_ratio_to_tick(sqrt_ratio: U256) -> i32 {
+    if sqrt_ratio <= MIN_SQRT_RATIO {
+        return MIN_TICK;
+    }
+    if sqrt_ratio >= MAX_SQRT_RATIO {
+        return MAX_TICK;
+    }
+
+    let mut tick = approximate_sqrt_ratio_to_tick(sqrt_ratio).clamp(MIN_TICK, MAX_TICK);
+
+    while tick > MIN_TICK && to_sqrt_ratio(tick).unwrap() > sqrt_ratio {
+        tick -= 1;
+    }
+    while tick < MAX_TICK && to_sqrt_ratio(tick + 1).unwrap() <= sqrt_ratio {
+        tick += 1;
+    }
+
+    tick
+}
+
+#[cfg(test)]
+mod tests {
+    mod to_sqrt_ratio {
+        use super::super::{to_sqrt_ratio, MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO, MIN_TICK};
+        use crate::uint::U256;
+
+        #[test]
+        fn test_tick_examples() {
+            assert_eq!(
+                to_sqrt_ratio(1000000).unwrap(),
+                U256::from_str_radix("561030636129153856579134353873645338624", 10).unwrap(),
+            );
+            assert_eq!(
+                to_sqrt_ratio(10000000).unwrap(),
+                U256::from_str_radix("50502254805927926084423855178401471004672", 10).unwrap(),
+            );
+            assert_eq!(
+                to_sqrt_ratio(-1000000).unwrap(),
+                U256::from_str_radix("206391740095027370700312310528859963392", 10).unwrap(),
+            );
+            assert_eq!(
+                to_sqrt_ratio(-10000000).unwrap(),
+                U256::from_str_radix("2292810285051363400276741630355046400", 10).unwrap(),
+            );
+        }
+
+        #[test]
+        fn test_tick_too_small() {
+            assert!(to_sqrt_ratio(MIN_TICK - 1).is_none());
+            assert!(to_sqrt_ratio(i32::MIN).is_none());
+        }
+
+        #[test]
+        fn test_min_tick() {
+            assert_eq!(to_sqrt_ratio(MIN_TICK).unwrap(), MIN_SQRT_RATIO,);
+        }
+
+        #[test]
+        fn test_max_tick() {
+            assert_eq!(to_sqrt_ratio(MAX_TICK).unwrap(), MAX_SQRT_RATIO,);
+        }
+
+        #[test]
+        fn test_tick_too_large() {
+            assert!(to_sqrt_ratio(MAX_TICK + 1).is_none());
+            assert!(to_sqrt_ratio(i32::MAX).is_none());
+        }
+    }
+
+    mod to_sqrt_ratio_clamped {
+        use super::super::{
+            clamp_tick, to_sqrt_ratio, to_sqrt_ratio_clamped, to_sqrt_ratio_clamped_with_bound,
+            TickBound, MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO, MIN_TICK,
+        };
+
+        #[test]
+        fn test_in_range() {
+            assert_eq!(to_sqrt_ratio_clamped(0), to_sqrt_ratio(0).unwrap());
+            assert_eq!(
+                to_sqrt_ratio_clamped(-1000000),
+                to_sqrt_ratio(-1000000).unwrap()
+            );
+            assert_eq!(
+                to_sqrt_ratio_clamped_with_bound(MIN_TICK),
+                (MIN_SQRT_RATIO, None)
+            );
+            assert_eq!(
+                to_sqrt_ratio_clamped_with_bound(MAX_TICK),
+                (MAX_SQRT_RATIO, None)
+            );
+        }
+
+        #[test]
+        fn test_below_min_tick() {
+            assert_eq!(to_sqrt_ratio_clamped(MIN_TICK - 1), MIN_SQRT_RATIO);
+            assert_eq!(
+                to_sqrt_ratio_clamped_with_bound(i32::MIN),
+                (MIN_SQRT_RATIO, Some(TickBound::Min))
+            );
+        }
+
+        #[test]
+        fn test_above_max_tick() {
+            assert_eq!(to_sqrt_ratio_clamped(MAX_TICK + 1), MAX_SQRT_RATIO);
+            assert_eq!(
+                to_sqrt_ratio_clamped_with_bound(i32::MAX),
+                (MAX_SQRT_RATIO, Some(TickBound::Max))
+            );
+        }
+
+        #[test]
+        fn test_clamp_tick() {
+            assert_eq!(clamp_tick(5), (5, None));
+            assert_eq!(clamp_tick(MIN_TICK - 5), (MIN_TICK, Some(TickBound::Min)));
+            assert_eq!(clamp_tick(MAX_TICK + 5), (MAX_TICK, Some(TickBound::Max)));
+        }
+    }
+
+    mod approximate_sqrt_ratio_to_tick {
+        use crate::tick::{MAX_TICK, MIN_TICK};
+
+        use super::super::{approximate_sqrt_ratio_to_tick, to_sqrt_ratio};
+
+        #[test]
+        fn test_tick_examples() {
+            assert_eq!(approximate_sqrt_ratio_to_tick(to_sqrt_ratio(0).unwrap()), 0,);
+            assert_eq!(
+                approximate_sqrt_ratio_to_tick(to_sqrt_ratio(1000000).unwrap()),
+                1000000,
+            );
+            assert_eq!(
+                approximate_sqrt_ratio_to_tick(to_sqrt_ratio(10000000).unwrap()),
+                10000000
+            );
+            assert_eq!(
+                approximate_sqrt_ratio_to_tick(to_sqrt_ratio(-1000000).unwrap()),
+                -1000000,
+            );
+            assert_eq!(
+                approximate_sqrt_ratio_to_tick(to_sqrt_ratio(-10000000).unwrap()),
+                -10000000,
+            );
+        }
+
+        #[test]
+        fn test_min_tick() {
+            assert_eq!(
+                approximate_sqrt_ratio_to_tick(to_sqrt_ratio(MIN_TICK).unwrap()),
+                MIN_TICK,
+            );
+        }
+
+        #[test]
+        fn test_max_tick() {
+            assert_eq!(
+                approximate_sqrt_ratio_to_tick(to_sqrt_ratio(MAX_TICK).unwrap()),
+                MAX_TICK,
+            );
+        }
+    }
+
+    mod sqrt_ratio_to_tick {
+        use super::super::{
+            sqrt_ratio_to_tick, to_sqrt_ratio, MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO, MIN_TICK,
+        };
+        use crate::uint::U256;
+
+        #[test]
+        fn test_exact_ticks() {
+            for tick in [
+                MIN_TICK, -10000000, -1000000, -1, 0, 1, 1000000, 10000000, MAX_TICK,
+            ] {
+                assert_eq!(sqrt_ratio_to_tick(to_sqrt_ratio(tick).unwrap()), tick);
+            }
+        }
+
+        #[test]
+        fn test_between_ticks() {
+            for tick in [-10000000, -1, 0, 1000000] {
+                assert_eq!(sqrt_ratio_to_tick(to_sqrt_ratio(tick).unwrap() + 1), tick);
+                assert_eq!(
+                    sqrt_ratio_to_tick(to_sqrt_ratio(tick).unwrap() - 1),
+                    tick - 1
+                );
+            }
+        }
+
+        #[test]
+        fn test_out_of_range() {
+            assert_eq!(sqrt_ratio_to_tick(U256::zero()), MIN_TICK);
+            assert_eq!(sqrt_ratio_to_tick(MIN_SQRT_RATIO), MIN_TICK);
+            assert_eq!(sqrt_ratio_to_tick(MAX_SQRT_RATIO), MAX_TICK);
+            assert_eq!(sqrt_ratio_to_tick(U256::MAX), MAX_TICK);
+        }
+    }
+}
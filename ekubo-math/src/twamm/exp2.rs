@@ -0,0 +1,117 @@
+use crate::tables::EXP2_FACTORS;
+use crate::uint::U256;
+
+// Upper bound on the relative error of exp2, i.e. |exp2(x) - 2^(x / 2^64) * 2^64| / (2^(x / 2^64) * 2^64).
+// The result is always rounded down, so exp2 never overestimates the exact value. The bound is 2^-63.
+pub const EXP2_MAX_RELATIVE_ERROR: f64 = 1.0842021724855044e-19;
+
+pub fn exp2(x: u128) -> u128 {
+    // x must be less than 0x400000000000000000 == 64 << 64 (overflow check)
+    assert!(x < 0x400000000000000000, "Overflow");
+
+    // Start with 0x80000000000000000000000000000000 (i.e. 2^127)
+    let mut result = U256::one() << 127;
+
+    // Multiply by the factor of each set bit of the fractional part
+    for (i, factor) in EXP2_FACTORS.iter().enumerate() {
+        if (x & (0x8000000000000000 >> i)) != 0 {
+            result = (result * factor) >> 128;
+        }
+    }
+
+    // Final adjustment: shift right by 63 - (x >> 64). (x >> 64) is the integer part.
+    result >>= 63 - (x >> 64);
+
+    result.as_u128()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uint::u256_to_float_base_x128;
+
+    // ln(2) as a 128.128 fixed point number
+    const LN_2_X128: U256 = U256([14547668686819489455, 12786308645202655659, 0, 0]);
+
+    // Computes 2^(x / 2^64) as a 128.128 fixed point number using the taylor series of e^(frac(x) * ln(2))
+    fn reference_exp2_x128(x: u128) -> U256 {
+        let fraction_x128 = U256::from(x & 0xffffffffffffffff) << 64;
+        let y = (fraction_x128 * LN_2_X128) >> 128;
+
+        let mut sum = U256::one() << 128;
+        let mut term = sum;
+        let mut n = 1u64;
+        while !term.is_zero() {
+            term = ((term * y) >> 128) / n;
+            sum += term;
+            n += 1;
+        }
+
+        sum << (x >> 64)
+    }
+
+    fn assert_within_error_bound(x: u128) {
+        let result_x128 = U256::from(exp2(x)) << 64;
+        let reference_x128 = reference_exp2_x128(x);
+
+        assert!(
+            result_x128 <= reference_x128,
+            "exp2({}) overestimates: {} > {}",
+            x,
+            result_x128,
+            reference_x128
+        );
+
+        let relative_error = u256_to_float_base_x128(reference_x128 - result_x128)
+            / u256_to_float_base_x128(reference_x128);
+        assert!(
+            relative_error <= EXP2_MAX_RELATIVE_ERROR,
+            "exp2({}) relative error {} exceeds bound",
+            x,
+            relative_error
+        );
+    }
+
+    #[test]
+    fn test_max_relative_error_constant() {
+        assert_eq!(EXP2_MAX_RELATIVE_ERROR, 2f64.powi(-63));
+    }
+
+    #[test]
+    fn test_exp2_error_bound_single_bits() {
+        for i in 0..70 {
+            assert_within_error_bound(1 << i);
+            assert_within_error_bound((1 << i) - 1);
+        }
+    }
+
+    #[test]
+    fn test_exp2_error_bound_across_domain() {
+        // deterministic linear congruential generator so the samples are reproducible
+        let mut seed: u128 = 0x853c49e6748fea9b;
+        for integer_part in 0..64u128 {
+            for _ in 0..64 {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let fractional_part = (seed >> 64) & 0xffffffffffffffff;
+                assert_within_error_bound((integer_part << 64) + fractional_part);
+            }
+        }
+        assert_within_error_bound(0x400000000000000000 - 1);
+    }
+
+    #[test]
+    fn test_exp2_cases() {
+        assert_eq!(exp2(0), 1 << 64);
+        assert_eq!(exp2(1 << 64), 2 << 64);
+        assert_eq!(exp2((3 << 64) / 2), 52175271301331128849);
+        assert_eq!(
+            exp2((62 << 64) + (3 << 64) / 2),
+            240615969168004511545033772477625056927
+        );
+        assert_eq!(exp2(2 << 64), 4 << 64);
+        assert_eq!(exp2(3 << 64), 8 << 64);
+        assert_eq!(exp2(63 << 64), 9223372036854775808 << 64);
+    }
+}
@@ -1,6 +1,6 @@
-use crate::math::muldiv::muldiv;
-use crate::math::twamm::exp2::exp2;
-use crate::math::uint::U256;
+use crate::muldiv::muldiv;
+use crate::twamm::exp2::exp2;
+use crate::uint::U256;
 use num_traits::Zero;
 
 const TWO_POW_64: U256 = U256([0, 1, 0, 0]);
@@ -32,6 +32,30 @@ fn compute_c(sqrt_ratio: U256, sqrt_sale_ratio: U256) -> (U256, bool) {
     )
 }
 
+// Converts a fee in the 0.64 fixed point representation used by pool configs to a fraction of 1
+pub fn fee_to_fraction(fee: u64) -> f64 {
+    fee as f64 / 18446744073709551616f64
+}
+
+// Converts a fraction of 1 to the 0.64 fixed point fee representation, returning None if it is not in [0, 1)
+pub fn fee_from_fraction(fraction: f64) -> Option<u64> {
+    if !(0f64..1f64).contains(&fraction) {
+        return None;
+    }
+
+    let fee = fraction * 18446744073709551616f64;
+    if fee >= 18446744073709551616f64 {
+        None
+    } else {
+        Some(fee as u64)
+    }
+}
+
+// Applies the 0.64 fixed point fee to the combined sale rate, i.e. sqrt(sale_rate_token0 * sale_rate_token1)
+pub fn apply_twamm_fee(sale_rate: u128, fee: u64) -> u128 {
+    ((U256::from(sale_rate) * (TWO_POW_64 - fee)) / TWO_POW_64).low_u128()
+}
+
 pub fn calculate_next_sqrt_ratio(
     sqrt_ratio: U256,
     liquidity: u128,
@@ -51,10 +75,12 @@ pub fn calculate_next_sqrt_ratio(
     if c.is_zero() || liquidity == 0 {
         sqrt_sale_ratio
     } else {
-        let sale_rate = ((U256::from(sale_rate_token1) * U256::from(sale_rate_token0))
-            .integer_sqrt()
-            * (TWO_POW_64 - fee))
-            / TWO_POW_64;
+        let sale_rate = U256::from(apply_twamm_fee(
+            (U256::from(sale_rate_token1) * U256::from(sale_rate_token0))
+                .integer_sqrt()
+                .low_u128(),
+            fee,
+        ));
 
         let round_up = sqrt_ratio > sqrt_sale_ratio;
 
@@ -98,9 +124,11 @@ pub fn calculate_next_sqrt_ratio(
 
 #[cfg(test)]
 mod tests {
-    use crate::math::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
-    use crate::math::twamm::sqrt_ratio::{calculate_next_sqrt_ratio, compute_c};
-    use crate::math::uint::U256;
+    use crate::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+    use crate::twamm::sqrt_ratio::{
+        apply_twamm_fee, calculate_next_sqrt_ratio, compute_c, fee_from_fraction, fee_to_fraction,
+    };
+    use crate::uint::U256;
     use alloc::vec;
     use insta::assert_debug_snapshot;
 
@@ -299,4 +327,25 @@ mod tests {
             U256::from_dec_str("75660834358443397537995256863811143").unwrap()
         );
     }
+
+    #[test]
+    fn test_apply_twamm_fee() {
+        assert_eq!(apply_twamm_fee(1_000_000, 0), 1_000_000);
+        assert_eq!(apply_twamm_fee(1_000_000, 1 << 63), 500_000);
+        assert_eq!(apply_twamm_fee(u128::MAX, 0), u128::MAX);
+        assert_eq!(apply_twamm_fee(u128::MAX, u64::MAX), 18446744073709551615);
+        // rounds down
+        assert_eq!(apply_twamm_fee(3, 1 << 63), 1);
+    }
+
+    #[test]
+    fn test_fee_conversions() {
+        assert_eq!(fee_to_fraction(0), 0f64);
+        assert_eq!(fee_to_fraction(1 << 63), 0.5);
+        assert_eq!(fee_from_fraction(0.5), Some(1 << 63));
+        assert_eq!(fee_from_fraction(0.003), Some(55340232221128656));
+        assert_eq!(fee_from_fraction(1f64), None);
+        assert_eq!(fee_from_fraction(-0.1), None);
+        assert_eq!(fee_from_fraction(f64::NAN), None);
+    }
 }
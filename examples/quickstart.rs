@@ -0,0 +1,30 @@
+// Quotes selling 1 token0 with 18 decimals to a full range pool at a price of 1
+//
+//     cargo run --example quickstart --features quickstart
+
+use evm_ekubo_sdk::math::uint::U256;
+use evm_ekubo_sdk::quickstart::quote_simple;
+use evm_ekubo_sdk::quoting::types::Address;
+
+const POOL_JSON: &str = r#"{"full_range": {
+    "key": {
+        "token0": "1",
+        "token1": "2",
+        "config": {"fee": 1844674407370955, "tick_spacing": 0, "extension": "0"}
+    },
+    "state": {"sqrt_ratio": "100000000000000000000000000000000", "liquidity": 1000000000000000000000}
+}}"#;
+
+fn main() {
+    let quote = quote_simple(
+        POOL_JSON,
+        Address::from_raw(U256::one()),
+        1_000_000_000_000_000_000,
+    )
+    .unwrap();
+
+    println!(
+        "sold {} for {}, paying {} in fees",
+        quote.consumed_amount, quote.calculated_amount, quote.fees_paid
+    );
+}
@@ -0,0 +1,533 @@
+// Quotes a swap against pools loaded from a JSON file and prints the results, e.g.
+//
+//     ekubo_quoter --pools pools.json --token 0x1 --amount 1000 [--sqrt-ratio-limit <ratio>] [--json]
+//
+// or quotes swapping the amount through a route loaded from a JSON file, in the serde encoding of `Route`:
+//
+//     ekubo_quoter route --pools pools.json --route route.json --amount 1000 [--json]
+//
+// The pools file contains an array of pools in any of the forms accepted by `evm_ekubo_sdk::quickstart`, and tokens
+// are parsed like the addresses in the JSON, i.e. as hex with or without the 0x prefix
+
+use evm_ekubo_sdk::math::uint::U256;
+use evm_ekubo_sdk::quickstart::{
+    parse_pools, QuickstartError, QuickstartPool, QuickstartPoolError, QuickstartPoolState,
+    QuickstartQuoteError, SimpleQuote,
+};
+use evm_ekubo_sdk::quoting::pool_map::PoolMap;
+use evm_ekubo_sdk::quoting::route::{quote_route, Route, RouteQuote};
+use evm_ekubo_sdk::quoting::types::{Address, NodeKey, TokenAmount};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: ekubo_quoter --pools <file> --token <token> --amount <amount> [--sqrt-ratio-limit <ratio>] [--json]
+       ekubo_quoter route --pools <file> --route <file> --amount <amount> [--json]";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Args {
+    pools_path: String,
+    token_amount: TokenAmount,
+    sqrt_ratio_limit: Option<U256>,
+    json: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct RouteArgs {
+    pools_path: String,
+    route_path: String,
+    amount_in: u128,
+    json: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Command {
+    Quote(Args),
+    Route(RouteArgs),
+}
+
+struct PoolQuote {
+    pool_key: NodeKey,
+    result: Result<SimpleQuote, QuickstartQuoteError>,
+}
+
+// Parses a hex number prefixed with 0x or a decimal number
+fn parse_u256(value: &str) -> Result<U256, String> {
+    match value.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).map_err(|err| format!("{value}: {err:?}")),
+        None => U256::from_dec_str(value).map_err(|err| format!("{value}: {err:?}")),
+    }
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Args, String> {
+    let mut pools_path = None;
+    let mut token = None;
+    let mut amount = None;
+    let mut sqrt_ratio_limit = None;
+    let mut json = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--json" {
+            json = true;
+            continue;
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {arg}"))?;
+        match arg.as_str() {
+            "--pools" => pools_path = Some(value),
+            "--token" => {
+                token = Some(Address::parse(&value).map_err(|err| format!("{value}: {err:?}"))?)
+            }
+            "--amount" => {
+                amount = Some(
+                    value
+                        .parse::<i128>()
+                        .map_err(|err| format!("{value}: {err}"))?,
+                )
+            }
+            "--sqrt-ratio-limit" => sqrt_ratio_limit = Some(parse_u256(&value)?),
+            _ => return Err(format!("unknown argument {arg}")),
+        }
+    }
+
+    Ok(Args {
+        pools_path: pools_path.ok_or("missing --pools")?,
+        token_amount: TokenAmount {
+            token: token.ok_or("missing --token")?,
+            amount: amount.ok_or("missing --amount")?,
+        },
+        sqrt_ratio_limit,
+        json,
+    })
+}
+
+fn parse_route_args(args: impl IntoIterator<Item = String>) -> Result<RouteArgs, String> {
+    let mut pools_path = None;
+    let mut route_path = None;
+    let mut amount_in = None;
+    let mut json = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--json" {
+            json = true;
+            continue;
+        }
+
+        let value = args
+            .next()
+            .ok_or_else(|| format!("missing value for {arg}"))?;
+        match arg.as_str() {
+            "--pools" => pools_path = Some(value),
+            "--route" => route_path = Some(value),
+            "--amount" => {
+                amount_in = Some(
+                    value
+                        .parse::<u128>()
+                        .map_err(|err| format!("{value}: {err}"))?,
+                )
+            }
+            _ => return Err(format!("unknown argument {arg}")),
+        }
+    }
+
+    Ok(RouteArgs {
+        pools_path: pools_path.ok_or("missing --pools")?,
+        route_path: route_path.ok_or("missing --route")?,
+        amount_in: amount_in.ok_or("missing --amount")?,
+        json,
+    })
+}
+
+fn parse_command(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+    if args.peek().map(String::as_str) == Some("route") {
+        args.next();
+        parse_route_args(args).map(Command::Route)
+    } else {
+        parse_args(args).map(Command::Quote)
+    }
+}
+
+fn format_error(error: QuickstartError) -> String {
+    match error {
+        QuickstartError::InvalidJson(err) => err.to_string(),
+        QuickstartError::InvalidPool {
+            index,
+            error: QuickstartPoolError::FullRange(err),
+        } => format!("pool {index}: {err:?}"),
+        QuickstartError::InvalidPool {
+            index,
+            error: QuickstartPoolError::Base(err),
+        } => format!("pool {index}: {err:?}"),
+        err => format!("{err:?}"),
+    }
+}
+
+fn format_quote_error(error: &QuickstartQuoteError) -> String {
+    match error {
+        QuickstartQuoteError::FullRange(err) => format!("{err:?}"),
+        QuickstartQuoteError::Base(err) => format!("{err:?}"),
+        QuickstartQuoteError::StateMismatch => "StateMismatch".to_string(),
+    }
+}
+
+fn load_pools(json: &str) -> Result<Vec<QuickstartPool>, String> {
+    parse_pools(json).map_err(format_error)
+}
+
+// Quotes every pool and orders the successful quotes from best to worst, followed by the failed quotes
+fn quote_pools(pools: &[QuickstartPool], args: &Args) -> Vec<PoolQuote> {
+    let mut quotes: Vec<PoolQuote> = pools
+        .iter()
+        .map(|pool| PoolQuote {
+            pool_key: *pool.get_key(),
+            result: pool.quote(args.token_amount, args.sqrt_ratio_limit),
+        })
+        .collect();
+
+    let is_exact_out = args.token_amount.amount < 0;
+    quotes.sort_by(|a, b| match (&a.result, &b.result) {
+        (Ok(a), Ok(b)) => {
+            let by_consumed = b
+                .consumed_amount
+                .unsigned_abs()
+                .cmp(&a.consumed_amount.unsigned_abs());
+            if is_exact_out {
+                by_consumed.then(a.calculated_amount.cmp(&b.calculated_amount))
+            } else {
+                by_consumed.then(b.calculated_amount.cmp(&a.calculated_amount))
+            }
+        }
+        (Ok(_), Err(_)) => core::cmp::Ordering::Less,
+        (Err(_), Ok(_)) => core::cmp::Ordering::Greater,
+        (Err(_), Err(_)) => core::cmp::Ordering::Equal,
+    });
+
+    quotes
+}
+
+fn format_pool_key(key: &NodeKey) -> String {
+    format!(
+        "{:#x}/{:#x} fee={} tick_spacing={} extension={:#x}",
+        key.token0,
+        key.token1,
+        key.config.fee.raw(),
+        key.config.tick_spacing.raw(),
+        key.config.extension
+    )
+}
+
+fn format_human(quotes: &[PoolQuote]) -> String {
+    quotes
+        .iter()
+        .map(|quote| match &quote.result {
+            Ok(outcome) => format!(
+                "{}\n  consumed: {}\n  calculated: {}\n  fees paid: {}\n  sqrt ratio after: {}\n",
+                format_pool_key(&quote.pool_key),
+                outcome.consumed_amount,
+                outcome.calculated_amount,
+                outcome.fees_paid,
+                outcome.sqrt_ratio_after
+            ),
+            Err(err) => format!(
+                "{}\n  error: {}\n",
+                format_pool_key(&quote.pool_key),
+                format_quote_error(err)
+            ),
+        })
+        .collect()
+}
+
+// Amounts are encoded as decimal strings because they do not necessarily fit in a JSON number
+fn format_json(quotes: &[PoolQuote]) -> String {
+    let quotes: Vec<serde_json::Value> = quotes
+        .iter()
+        .map(|quote| {
+            let pool_key = serde_json::to_value(quote.pool_key).unwrap();
+            match &quote.result {
+                Ok(outcome) => serde_json::json!({
+                    "pool_key": pool_key,
+                    "consumed_amount": outcome.consumed_amount.to_string(),
+                    "calculated_amount": outcome.calculated_amount.to_string(),
+                    "fees_paid": outcome.fees_paid.to_string(),
+                    "sqrt_ratio_after": outcome.sqrt_ratio_after.to_string(),
+                }),
+                Err(err) => serde_json::json!({
+                    "pool_key": pool_key,
+                    "error": format_quote_error(err),
+                }),
+            }
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&quotes).unwrap()
+}
+
+fn quote_pool_route(
+    pools: Vec<QuickstartPool>,
+    route_json: &str,
+    amount_in: u128,
+) -> Result<RouteQuote<QuickstartPoolState>, String> {
+    let route: Route = serde_json::from_str(route_json).map_err(|err| err.to_string())?;
+    let pools: PoolMap<QuickstartPool> = pools.into_iter().collect();
+    quote_route(&pools, &route, amount_in, ()).map_err(|err| format!("{err:?}"))
+}
+
+fn format_route_human(quote: &RouteQuote<QuickstartPoolState>) -> String {
+    let splits: String = quote
+        .splits
+        .iter()
+        .enumerate()
+        .map(|(index, split)| {
+            let amounts: Vec<String> = split.amounts.iter().map(u128::to_string).collect();
+            format!("  split {index}: {}\n", amounts.join(" -> "))
+        })
+        .collect();
+    format!("amount out: {}\n{splits}", quote.amount_out)
+}
+
+// Amounts are encoded as decimal strings because they do not necessarily fit in a JSON number
+fn format_route_json(quote: &RouteQuote<QuickstartPoolState>) -> String {
+    let splits: Vec<serde_json::Value> = quote
+        .splits
+        .iter()
+        .map(|split| {
+            serde_json::json!({
+                "amounts": split.amounts.iter().map(u128::to_string).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&serde_json::json!({
+        "amount_out": quote.amount_out.to_string(),
+        "splits": splits,
+    }))
+    .unwrap()
+}
+
+fn read_file(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))
+}
+
+fn run(command: Command) -> Result<String, String> {
+    match command {
+        Command::Quote(args) => {
+            let quotes = quote_pools(&load_pools(&read_file(&args.pools_path)?)?, &args);
+
+            Ok(if args.json {
+                format_json(&quotes)
+            } else {
+                format_human(&quotes)
+            })
+        }
+        Command::Route(args) => {
+            let quote = quote_pool_route(
+                load_pools(&read_file(&args.pools_path)?)?,
+                &read_file(&args.route_path)?,
+                args.amount_in,
+            )?;
+
+            Ok(if args.json {
+                format_route_json(&quote)
+            } else {
+                format_route_human(&quote)
+            })
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match parse_command(std::env::args().skip(1)).and_then(run) {
+        Ok(output) => {
+            println!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {err}\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use evm_ekubo_sdk::quoting::route::{
+        HopDirection, RouteHop, RouteSplit, ROUTE_SHARE_DENOMINATOR,
+    };
+
+    const POOLS_JSON: &str = r#"[
+        {"full_range": {
+            "key": {"token0": "1", "token1": "2", "config": {"fee": 0, "tick_spacing": 0, "extension": "0"}},
+            "state": {"sqrt_ratio": "100000000000000000000000000000000", "liquidity": 1000000}
+        }},
+        {"lens": {
+            "key": {"token0": "1", "token1": "2", "config": {"fee": 0, "tick_spacing": 100, "extension": "0"}},
+            "sqrt_ratio": "100000000000000000000000000000000",
+            "liquidity": 100000000,
+            "tick": 0,
+            "ticks": [{"index": -100000, "liquidity_delta": 100000000}, {"index": 100000, "liquidity_delta": -100000000}],
+            "min_tick_searched": -100000,
+            "max_tick_searched": 100000
+        }},
+        {"full_range": {
+            "key": {"token0": "3", "token1": "4", "config": {"fee": 0, "tick_spacing": 0, "extension": "0"}},
+            "state": {"sqrt_ratio": "100000000000000000000000000000000", "liquidity": 1000000}
+        }}
+    ]"#;
+
+    fn args(amount: i128) -> Args {
+        Args {
+            pools_path: "pools.json".to_string(),
+            token_amount: TokenAmount {
+                token: Address::from_raw(U256::one()),
+                amount,
+            },
+            sqrt_ratio_limit: None,
+            json: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = parse_args(
+            [
+                "--pools",
+                "pools.json",
+                "--token",
+                "0x1",
+                "--amount",
+                "-1000",
+                "--json",
+            ]
+            .map(String::from),
+        )
+        .unwrap();
+        assert_eq!(
+            parsed,
+            Args {
+                json: true,
+                ..args(-1000)
+            }
+        );
+
+        assert_eq!(
+            parse_args(["--pools", "pools.json", "--token", "1"].map(String::from)),
+            Err("missing --amount".to_string())
+        );
+        assert_eq!(
+            parse_args(["--pools"].map(String::from)),
+            Err("missing value for --pools".to_string())
+        );
+    }
+
+    #[test]
+    fn test_tokens_are_parsed_like_json_addresses() {
+        let parse_token = |token: &str| {
+            parse_args(["--pools", "p", "--token", token, "--amount", "1"].map(String::from))
+                .map(|args| args.token_amount.token)
+        };
+        assert_eq!(parse_token("10"), Ok(Address::from_raw(U256::from(16))));
+        assert_eq!(parse_token("0x10"), parse_token("10"));
+        assert_eq!(
+            parse_token("0x1"),
+            Ok(serde_json::from_str::<Address>(r#""1""#).unwrap())
+        );
+        assert!(parse_token("0x1zz").is_err());
+        assert!(parse_token(&format!("0x{}", "f".repeat(41))).is_err());
+    }
+
+    #[test]
+    fn test_route_command() {
+        assert_eq!(
+            parse_command(
+                ["route", "--pools", "p.json", "--route", "r.json", "--amount", "10"]
+                    .map(String::from)
+            ),
+            Ok(Command::Route(RouteArgs {
+                pools_path: "p.json".to_string(),
+                route_path: "r.json".to_string(),
+                amount_in: 10,
+                json: false,
+            }))
+        );
+        assert_eq!(
+            parse_command(["route", "--pools", "p.json", "--amount", "10"].map(String::from)),
+            Err("missing --route".to_string())
+        );
+
+        let pools = load_pools(POOLS_JSON).unwrap();
+        let lens_pool = pools[1].clone();
+        let route = Route {
+            token_in: Address::from_raw(U256::one()),
+            token_out: Address::from_raw(U256::from(2)),
+            splits: vec![RouteSplit {
+                share: ROUTE_SHARE_DENOMINATOR,
+                hops: vec![RouteHop {
+                    pool_key: *lens_pool.get_key(),
+                    direction: HopDirection::ZeroForOne,
+                }],
+            }],
+        };
+        let quote =
+            quote_pool_route(pools, &serde_json::to_string(&route).unwrap(), 100_000).unwrap();
+
+        assert_eq!(
+            quote.amount_out,
+            lens_pool
+                .quote(args(100_000).token_amount, None)
+                .unwrap()
+                .calculated_amount
+        );
+        let output: serde_json::Value = serde_json::from_str(&format_route_json(&quote)).unwrap();
+        assert_eq!(output["amount_out"], quote.amount_out.to_string());
+        assert_eq!(output["splits"][0]["amounts"][0], "100000");
+    }
+
+    #[test]
+    fn test_parse_u256() {
+        assert_eq!(parse_u256("0xff"), Ok(U256::from(255)));
+        assert_eq!(parse_u256("255"), Ok(U256::from(255)));
+        assert!(parse_u256("0xzz").is_err());
+    }
+
+    #[test]
+    fn test_quotes_are_ordered_best_first() {
+        let pools = load_pools(POOLS_JSON).unwrap();
+        let quotes = quote_pools(&pools, &args(100_000));
+
+        assert_eq!(quotes.len(), 3);
+        // the concentrated pool has more liquidity around the current price
+        assert_eq!(quotes[0].pool_key.config.tick_spacing.raw(), 100);
+        assert_eq!(quotes[1].pool_key.config.tick_spacing.raw(), 0);
+        assert!(
+            quotes[0].result.as_ref().unwrap().calculated_amount
+                > quotes[1].result.as_ref().unwrap().calculated_amount
+        );
+        assert_eq!(
+            format_quote_error(quotes[2].result.as_ref().err().unwrap()),
+            "InvalidToken"
+        );
+    }
+
+    #[test]
+    fn test_json_output() {
+        let pools = load_pools(POOLS_JSON).unwrap();
+        let output: serde_json::Value =
+            serde_json::from_str(&format_json(&quote_pools(&pools, &args(1000)))).unwrap();
+
+        assert_eq!(output[0]["consumed_amount"], "1000");
+        assert_eq!(output[2]["error"], "InvalidToken");
+    }
+
+    #[test]
+    fn test_invalid_pool() {
+        let json = r#"[{"full_range": {
+            "key": {"token0": "2", "token1": "1", "config": {"fee": 0, "tick_spacing": 0, "extension": "0"}},
+            "state": {"sqrt_ratio": "100000000000000000000000000000000", "liquidity": 0}
+        }}]"#;
+        assert_eq!(load_pools(json).err().unwrap(), "pool 0: TokenOrderInvalid");
+    }
+}
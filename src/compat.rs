@@ -0,0 +1,3 @@
+// Types shaped like those of other Ekubo SDKs, with conversions to the types of this crate, for porting code written
+// against them
+pub mod starknet;
@@ -0,0 +1,310 @@
+// The types of the Starknet SDK under their names there, so routing code written against it can be ported by changing
+// its imports and converting at the boundary. Keys and tokens are felts, which fit in a U256, and signed values are
+// i129, a magnitude with a sign.
+
+use crate::math::tick::{MAX_TICK, MIN_TICK};
+use crate::math::uint::U256;
+use crate::quoting::signed_amount::SignedAmount;
+use crate::quoting::types::{self, Address, Config, ConfigError, Fee, TickSpacing};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StarknetCompatError {
+    /// The i129 value does not fit in the signed integer type of this crate.
+    Overflow,
+    /// The tick index is outside of the range [MIN_TICK, MAX_TICK] of this deployment.
+    TickOutOfRange,
+    /// The tick spacing cannot be represented in a config of this deployment.
+    InvalidConfig(ConfigError),
+    /// The token or extension felt does not fit in a 160 bit address.
+    AddressOutOfRange,
+}
+
+fn to_address(value: U256) -> Result<Address, StarknetCompatError> {
+    Address::try_from(value).map_err(|_| StarknetCompatError::AddressOutOfRange)
+}
+
+// A signed 129 bit integer, where zero is the same regardless of the sign
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct i129 {
+    pub mag: u128,
+    pub sign: bool,
+}
+
+impl PartialEq for i129 {
+    fn eq(&self, other: &Self) -> bool {
+        self.mag == other.mag && (self.sign == other.sign || self.mag == 0)
+    }
+}
+
+impl Eq for i129 {}
+
+impl From<i128> for i129 {
+    fn from(value: i128) -> Self {
+        Self {
+            mag: value.unsigned_abs(),
+            sign: value < 0,
+        }
+    }
+}
+
+impl TryFrom<i129> for i128 {
+    type Error = StarknetCompatError;
+
+    fn try_from(value: i129) -> Result<Self, Self::Error> {
+        if value.sign {
+            0i128
+                .checked_sub_unsigned(value.mag)
+                .ok_or(StarknetCompatError::Overflow)
+        } else {
+            i128::try_from(value.mag).map_err(|_| StarknetCompatError::Overflow)
+        }
+    }
+}
+
+impl From<i129> for SignedAmount {
+    fn from(value: i129) -> Self {
+        SignedAmount::new(value.mag, value.sign)
+    }
+}
+
+impl From<SignedAmount> for i129 {
+    fn from(value: SignedAmount) -> Self {
+        i129 {
+            mag: value.mag(),
+            sign: value.is_negative(),
+        }
+    }
+}
+
+// The pool key, where the fee is a 0.128 fixed point fraction instead of 0.64 and the config is not packed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeKey {
+    pub token0: U256,
+    pub token1: U256,
+    pub fee: u128,
+    pub tick_spacing: u128,
+    pub extension: U256,
+}
+
+// The fee is rounded down to the nearest 0.64 fee, like Fee::from_bps. Tokens and the extension must fit in 160 bits
+impl TryFrom<NodeKey> for types::NodeKey {
+    type Error = StarknetCompatError;
+
+    fn try_from(value: NodeKey) -> Result<Self, Self::Error> {
+        let tick_spacing = u32::try_from(value.tick_spacing)
+            .map_err(|_| ConfigError::TickSpacingTooLarge)
+            .and_then(TickSpacing::new)
+            .map_err(StarknetCompatError::InvalidConfig)?;
+        if value.extension.bits() > 160 {
+            return Err(StarknetCompatError::AddressOutOfRange);
+        }
+
+        Ok(types::NodeKey {
+            token0: to_address(value.token0)?,
+            token1: to_address(value.token1)?,
+            config: Config::new(
+                Fee::from_raw((value.fee >> 64) as u64),
+                tick_spacing,
+                value.extension,
+            ),
+        })
+    }
+}
+
+impl From<types::NodeKey> for NodeKey {
+    fn from(value: types::NodeKey) -> Self {
+        NodeKey {
+            token0: value.token0.into(),
+            token1: value.token1.into(),
+            fee: u128::from(value.config.fee.raw()) << 64,
+            tick_spacing: value.config.tick_spacing.raw().into(),
+            extension: value.config.extension,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenAmount {
+    pub token: U256,
+    pub amount: i129,
+}
+
+impl TryFrom<TokenAmount> for types::TokenAmount {
+    type Error = StarknetCompatError;
+
+    fn try_from(value: TokenAmount) -> Result<Self, Self::Error> {
+        Ok(types::TokenAmount {
+            token: to_address(value.token)?,
+            amount: value.amount.try_into()?,
+        })
+    }
+}
+
+impl From<types::TokenAmount> for TokenAmount {
+    fn from(value: types::TokenAmount) -> Self {
+        TokenAmount {
+            token: value.token.into(),
+            amount: value.amount.into(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tick {
+    pub index: i129,
+    pub liquidity_delta: i129,
+}
+
+impl TryFrom<Tick> for types::Tick {
+    type Error = StarknetCompatError;
+
+    fn try_from(value: Tick) -> Result<Self, Self::Error> {
+        let index = i128::try_from(value.index)
+            .ok()
+            .and_then(|index| i32::try_from(index).ok())
+            .filter(|index| (MIN_TICK..=MAX_TICK).contains(index))
+            .ok_or(StarknetCompatError::TickOutOfRange)?;
+
+        Ok(types::Tick {
+            index,
+            liquidity_delta: value.liquidity_delta.try_into()?,
+        })
+    }
+}
+
+impl From<types::Tick> for Tick {
+    fn from(value: types::Tick) -> Self {
+        Tick {
+            index: i128::from(value.index).into(),
+            liquidity_delta: value.liquidity_delta.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_i129_conversions() {
+        assert_eq!(
+            i128::try_from(i129 {
+                mag: 1 << 127,
+                sign: true
+            }),
+            Ok(i128::MIN)
+        );
+        assert_eq!(
+            i128::try_from(i129 {
+                mag: 1 << 127,
+                sign: false
+            }),
+            Err(StarknetCompatError::Overflow)
+        );
+        assert_eq!(i129::from(-5), i129 { mag: 5, sign: true });
+        assert_eq!(i129 { mag: 0, sign: true }, i129::default());
+        assert_eq!(
+            SignedAmount::from(i129 { mag: 0, sign: true }),
+            SignedAmount::ZERO
+        );
+        assert_eq!(i129::from(SignedAmount::from(-5)), i129::from(-5));
+    }
+
+    #[test]
+    fn test_node_key_round_trip() {
+        let key = types::NodeKey {
+            token0: Address::from_raw(U256::from(1)),
+            token1: Address::from_raw(U256::from(2)),
+            config: Config::new(
+                Fee::from_bps(30).unwrap(),
+                TickSpacing::new(100).unwrap(),
+                U256::from(3),
+            ),
+        };
+
+        let starknet_key = NodeKey::from(key);
+        assert_eq!(starknet_key.tick_spacing, 100);
+        assert_eq!(types::NodeKey::try_from(starknet_key), Ok(key));
+
+        // fees are rounded down to 0.64
+        assert_eq!(
+            types::NodeKey::try_from(NodeKey {
+                fee: starknet_key.fee + (1 << 63),
+                ..starknet_key
+            }),
+            Ok(key)
+        );
+        assert_eq!(
+            types::NodeKey::try_from(NodeKey {
+                tick_spacing: 1 << 40,
+                ..starknet_key
+            }),
+            Err(StarknetCompatError::InvalidConfig(
+                ConfigError::TickSpacingTooLarge
+            ))
+        );
+
+        // felts are wider than addresses
+        let felt = U256::one() << 160;
+        for starknet_key in [
+            NodeKey {
+                token0: felt,
+                ..starknet_key
+            },
+            NodeKey {
+                token1: felt,
+                ..starknet_key
+            },
+            NodeKey {
+                extension: felt,
+                ..starknet_key
+            },
+        ] {
+            assert_eq!(
+                types::NodeKey::try_from(starknet_key),
+                Err(StarknetCompatError::AddressOutOfRange)
+            );
+        }
+        assert_eq!(
+            types::NodeKey::try_from(NodeKey {
+                extension: felt - 1,
+                ..starknet_key
+            })
+            .map(|key| key.config.extension),
+            Ok(felt - 1)
+        );
+    }
+
+    #[test]
+    fn test_tick_and_amount_conversions() {
+        let tick = types::Tick {
+            index: -100,
+            liquidity_delta: 1000,
+        };
+        assert_eq!(types::Tick::try_from(Tick::from(tick)), Ok(tick));
+        assert_eq!(
+            types::Tick::try_from(Tick {
+                index: i129::from(i128::from(MAX_TICK) + 1),
+                liquidity_delta: i129::default(),
+            }),
+            Err(StarknetCompatError::TickOutOfRange)
+        );
+
+        let amount = types::TokenAmount {
+            token: Address::from_raw(U256::from(1)),
+            amount: -1000,
+        };
+        assert_eq!(
+            types::TokenAmount::try_from(TokenAmount::from(amount)),
+            Ok(amount)
+        );
+        assert_eq!(
+            types::TokenAmount::try_from(TokenAmount {
+                token: U256::one() << 200,
+                amount: i129::default(),
+            }),
+            Err(StarknetCompatError::AddressOutOfRange)
+        );
+    }
+}
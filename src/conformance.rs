@@ -0,0 +1,163 @@
+#[cfg(feature = "differential")]
+pub mod differential;
+
+use crate::math::twamm::sqrt_ratio::calculate_next_sqrt_ratio;
+use crate::math::uint::U256;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+// Test vectors for calculate_next_sqrt_ratio. All integers are encoded as decimal strings, except for
+// time_elapsed which always fits in a JSON number.
+pub const TWAMM_NEXT_SQRT_RATIO_JSON: &str =
+    include_str!("../conformance/twamm_next_sqrt_ratio.json");
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TwammNextSqrtRatioCase {
+    pub description: String,
+    #[serde(with = "serde_u256_dec")]
+    pub sqrt_ratio: U256,
+    #[serde(with = "serde_dec")]
+    pub liquidity: u128,
+    #[serde(with = "serde_dec")]
+    pub token0_sale_rate: u128,
+    #[serde(with = "serde_dec")]
+    pub token1_sale_rate: u128,
+    pub time_elapsed: u32,
+    #[serde(with = "serde_dec")]
+    pub fee: u64,
+    #[serde(with = "serde_u256_dec")]
+    pub expected_sqrt_ratio: U256,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConformanceFailure {
+    pub description: String,
+    pub expected: U256,
+    pub actual: U256,
+}
+
+// Parses the bundled TWAMM next sqrt ratio test vectors
+pub fn twamm_next_sqrt_ratio_cases() -> Result<Vec<TwammNextSqrtRatioCase>, serde_json::Error> {
+    serde_json::from_str(TWAMM_NEXT_SQRT_RATIO_JSON)
+}
+
+// Runs each case through the given implementation and returns the cases whose result does not match
+pub fn run_twamm_next_sqrt_ratio<F>(
+    cases: &[TwammNextSqrtRatioCase],
+    mut next_sqrt_ratio: F,
+) -> Vec<ConformanceFailure>
+where
+    F: FnMut(&TwammNextSqrtRatioCase) -> U256,
+{
+    cases
+        .iter()
+        .filter_map(|case| {
+            let actual = next_sqrt_ratio(case);
+            (actual != case.expected_sqrt_ratio).then(|| ConformanceFailure {
+                description: case.description.clone(),
+                expected: case.expected_sqrt_ratio,
+                actual,
+            })
+        })
+        .collect()
+}
+
+// Runs the bundled cases against this crate's implementation
+pub fn verify_twamm_next_sqrt_ratio() -> Result<Vec<ConformanceFailure>, serde_json::Error> {
+    Ok(run_twamm_next_sqrt_ratio(
+        &twamm_next_sqrt_ratio_cases()?,
+        |case| {
+            calculate_next_sqrt_ratio(
+                case.sqrt_ratio,
+                case.liquidity,
+                case.token0_sale_rate,
+                case.token1_sale_rate,
+                case.time_elapsed,
+                case.fee,
+            )
+        },
+    ))
+}
+
+mod serde_dec {
+    use alloc::borrow::Cow;
+    use core::fmt::Display;
+    use core::str::FromStr;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        let dec_str: Cow<'de, str> = serde::Deserialize::deserialize(deserializer)?;
+        dec_str.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+mod serde_u256_dec {
+    use crate::math::uint::U256;
+    use alloc::borrow::Cow;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &U256, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<U256, D::Error> {
+        let dec_str: Cow<'de, str> = serde::Deserialize::deserialize(deserializer)?;
+        U256::from_dec_str(&dec_str).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_cases_pass() {
+        let cases = twamm_next_sqrt_ratio_cases().unwrap();
+        assert_eq!(cases.len(), 11);
+        assert_eq!(verify_twamm_next_sqrt_ratio().unwrap(), []);
+    }
+
+    #[test]
+    fn test_runner_reports_mismatches() {
+        let cases = twamm_next_sqrt_ratio_cases().unwrap();
+        let failures = run_twamm_next_sqrt_ratio(&cases[..2], |case| {
+            if case.description == cases[0].description {
+                case.expected_sqrt_ratio
+            } else {
+                U256::zero()
+            }
+        });
+
+        assert_eq!(
+            failures,
+            [ConformanceFailure {
+                description: cases[1].description.clone(),
+                expected: cases[1].expected_sqrt_ratio,
+                actual: U256::zero(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_cases_round_trip() {
+        let cases = twamm_next_sqrt_ratio_cases().unwrap();
+        let serialized = serde_json::to_string(&cases).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<TwammNextSqrtRatioCase>>(&serialized).unwrap(),
+            cases
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use super::{serde_dec, serde_u256_dec, TwammNextSqrtRatioCase};
+use crate::math::tick::{sqrt_ratio_to_tick, to_sqrt_ratio};
+use crate::math::twamm::sqrt_ratio::calculate_next_sqrt_ratio;
+use crate::math::uint::U256;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Reference vectors vendored from the Starknet implementation, in the format of [`parse_reference_vectors`].
+///
+/// So far these are only the vectors whose Starknet results are fixed by the definitions both implementations
+/// share, e.g. tick 0 at a sqrt ratio of 1 and a TWAMM price that stays at the sale ratio. Vectors exported by running
+/// the Cairo implementation with `conformance/starknet/export.sh` replace them; `conformance/starknet/README.md`
+/// records where the current file came from.
+pub const STARKNET_REFERENCE_VECTORS_JSON: &str =
+    include_str!("../../conformance/starknet_reference_vectors.json");
+
+// A result computed by the Starknet (Cairo) implementation for one of the algorithms shared with this crate
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "function", rename_all = "snake_case")]
+pub enum ReferenceVector {
+    TickToSqrtRatio {
+        tick: i32,
+        #[serde(with = "serde_u256_dec")]
+        sqrt_ratio: U256,
+    },
+    SqrtRatioToTick {
+        #[serde(with = "serde_u256_dec")]
+        sqrt_ratio: U256,
+        tick: i32,
+    },
+    TwammNextSqrtRatio(StarknetTwammNextSqrtRatioCase),
+}
+
+// A calculate_next_sqrt_ratio vector in the representation of the Starknet implementation
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StarknetTwammNextSqrtRatioCase {
+    pub description: String,
+    #[serde(with = "serde_u256_dec")]
+    pub sqrt_ratio: U256,
+    #[serde(with = "serde_dec")]
+    pub liquidity: u128,
+    #[serde(with = "serde_dec")]
+    pub token0_sale_rate: u128,
+    #[serde(with = "serde_dec")]
+    pub token1_sale_rate: u128,
+    pub time_elapsed: u32,
+    /// The fee as a 0.128 fixed point number, see [`starknet_fee_to_evm`].
+    #[serde(with = "serde_dec")]
+    pub fee: u128,
+    #[serde(with = "serde_u256_dec")]
+    pub expected_sqrt_ratio: U256,
+}
+
+impl StarknetTwammNextSqrtRatioCase {
+    // The fee of the case as an EVM fee
+    pub fn evm_fee(&self) -> u64 {
+        starknet_fee_to_evm(self.fee)
+    }
+}
+
+// Converts an EVM vector, whose fee converts to Starknet without loss
+impl From<TwammNextSqrtRatioCase> for StarknetTwammNextSqrtRatioCase {
+    fn from(case: TwammNextSqrtRatioCase) -> Self {
+        Self {
+            description: case.description,
+            sqrt_ratio: case.sqrt_ratio,
+            liquidity: case.liquidity,
+            token0_sale_rate: case.token0_sale_rate,
+            token1_sale_rate: case.token1_sale_rate,
+            time_elapsed: case.time_elapsed,
+            fee: u128::from(case.fee) << 64,
+            expected_sqrt_ratio: case.expected_sqrt_ratio,
+        }
+    }
+}
+
+/// Converts a Starknet fee, a 0.128 fixed point number, to an EVM fee, a 0.64 fixed point number.
+///
+/// The lower 64 bits of the Starknet fee are dropped, so the EVM fee is rounded down and a vector whose fee is not a
+/// multiple of 2^64 may diverge only because of the conversion.
+pub const fn starknet_fee_to_evm(fee: u128) -> u64 {
+    (fee >> 64) as u64
+}
+
+// A reference vector for which this crate computes a different result than the Starknet implementation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Divergence {
+    /// The EVM result is `None` if the tick is outside of the EVM tick range.
+    TickToSqrtRatio {
+        tick: i32,
+        starknet: U256,
+        evm: Option<U256>,
+    },
+    SqrtRatioToTick {
+        sqrt_ratio: U256,
+        starknet: i32,
+        evm: i32,
+    },
+    TwammNextSqrtRatio {
+        case: StarknetTwammNextSqrtRatioCase,
+        evm: U256,
+    },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DifferentialReport {
+    pub vectors_checked: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+impl DifferentialReport {
+    pub fn is_conformant(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+// Parses reference vectors exported from the Starknet implementation
+pub fn parse_reference_vectors(json: &str) -> Result<Vec<ReferenceVector>, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+// Parses the vendored Starknet reference vectors
+pub fn starknet_reference_vectors() -> Result<Vec<ReferenceVector>, serde_json::Error> {
+    parse_reference_vectors(STARKNET_REFERENCE_VECTORS_JSON)
+}
+
+// Computes the result of each vector with this crate and reports every vector where it differs
+pub fn run_differential(vectors: &[ReferenceVector]) -> DifferentialReport {
+    let divergences = vectors
+        .iter()
+        .filter_map(|vector| match vector {
+            ReferenceVector::TickToSqrtRatio { tick, sqrt_ratio } => {
+                let evm = to_sqrt_ratio(*tick);
+                (evm != Some(*sqrt_ratio)).then_some(Divergence::TickToSqrtRatio {
+                    tick: *tick,
+                    starknet: *sqrt_ratio,
+                    evm,
+                })
+            }
+            ReferenceVector::SqrtRatioToTick { sqrt_ratio, tick } => {
+                let evm = sqrt_ratio_to_tick(*sqrt_ratio);
+                (evm != *tick).then_some(Divergence::SqrtRatioToTick {
+                    sqrt_ratio: *sqrt_ratio,
+                    starknet: *tick,
+                    evm,
+                })
+            }
+            ReferenceVector::TwammNextSqrtRatio(case) => {
+                let evm = calculate_next_sqrt_ratio(
+                    case.sqrt_ratio,
+                    case.liquidity,
+                    case.token0_sale_rate,
+                    case.token1_sale_rate,
+                    case.time_elapsed,
+                    case.evm_fee(),
+                );
+                (evm != case.expected_sqrt_ratio).then(|| Divergence::TwammNextSqrtRatio {
+                    case: case.clone(),
+                    evm,
+                })
+            }
+        })
+        .collect();
+
+    DifferentialReport {
+        vectors_checked: vectors.len(),
+        divergences,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::{MAX_TICK, MIN_SQRT_RATIO};
+
+    #[test]
+    fn test_matching_vectors() {
+        let vectors = parse_reference_vectors(
+            r#"[
+                {"function": "tick_to_sqrt_ratio", "tick": 0, "sqrt_ratio": "340282366920938463463374607431768211456"},
+                {"function": "sqrt_ratio_to_tick", "sqrt_ratio": "340282366920938463463374607431768211456", "tick": 0}
+            ]"#,
+        )
+        .unwrap();
+
+        let report = run_differential(&vectors);
+        assert_eq!(report.vectors_checked, 2);
+        assert!(report.is_conformant());
+    }
+
+    #[test]
+    fn test_vendored_starknet_vectors() {
+        let vectors = starknet_reference_vectors().unwrap();
+        assert!(vectors
+            .iter()
+            .any(|vector| matches!(vector, ReferenceVector::TickToSqrtRatio { .. })));
+        assert!(vectors
+            .iter()
+            .any(|vector| matches!(vector, ReferenceVector::TwammNextSqrtRatio(_))));
+
+        assert_eq!(
+            run_differential(&vectors),
+            DifferentialReport {
+                vectors_checked: 4,
+                divergences: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_divergences_are_reported() {
+        let mut case = StarknetTwammNextSqrtRatioCase::from(
+            crate::conformance::twamm_next_sqrt_ratio_cases().unwrap()[0].clone(),
+        );
+        let evm = case.expected_sqrt_ratio;
+        case.expected_sqrt_ratio += U256::one();
+
+        let vectors = [
+            ReferenceVector::TickToSqrtRatio {
+                tick: MAX_TICK + 1,
+                sqrt_ratio: U256::one() << 160,
+            },
+            ReferenceVector::SqrtRatioToTick {
+                sqrt_ratio: MIN_SQRT_RATIO,
+                tick: 0,
+            },
+            ReferenceVector::TwammNextSqrtRatio(case.clone()),
+        ];
+
+        let report = run_differential(&vectors);
+        assert_eq!(
+            report.divergences,
+            [
+                Divergence::TickToSqrtRatio {
+                    tick: MAX_TICK + 1,
+                    starknet: U256::one() << 160,
+                    evm: None,
+                },
+                Divergence::SqrtRatioToTick {
+                    sqrt_ratio: MIN_SQRT_RATIO,
+                    starknet: 0,
+                    evm: sqrt_ratio_to_tick(MIN_SQRT_RATIO),
+                },
+                Divergence::TwammNextSqrtRatio { case, evm },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_twamm_vector_format() {
+        let case = StarknetTwammNextSqrtRatioCase::from(
+            crate::conformance::twamm_next_sqrt_ratio_cases().unwrap()[1].clone(),
+        );
+        let serialized =
+            serde_json::to_string(&[ReferenceVector::TwammNextSqrtRatio(case.clone())]).unwrap();
+
+        assert!(serialized.contains(r#""function":"twamm_next_sqrt_ratio""#));
+        assert_eq!(
+            parse_reference_vectors(&serialized).unwrap(),
+            [ReferenceVector::TwammNextSqrtRatio(case)]
+        );
+    }
+
+    #[test]
+    fn test_starknet_fee_conversion() {
+        // 0.3% as a 0.128 fixed point number rounds down to the 0.64 fixed point number
+        let starknet_fee = 0x00c49ba5e353f7ced916872b020c49bau128;
+        assert_eq!(starknet_fee_to_evm(starknet_fee), 0x00c49ba5e353f7ce);
+        assert_eq!(starknet_fee_to_evm(u128::from(u64::MAX) << 64), u64::MAX);
+
+        let case = crate::conformance::twamm_next_sqrt_ratio_cases()
+            .unwrap()
+            .into_iter()
+            .find(|case| case.fee != 0)
+            .unwrap();
+        let fee = case.fee;
+        assert_eq!(StarknetTwammNextSqrtRatioCase::from(case).evm_fee(), fee);
+    }
+}
@@ -1,5 +1,13 @@
 #![no_std]
 extern crate alloc;
 
-pub mod math;
+pub mod compat;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+pub use ekubo_math as math;
+pub mod prelude;
+#[cfg(feature = "quickstart")]
+pub mod quickstart;
 pub mod quoting;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
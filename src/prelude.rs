@@ -0,0 +1,44 @@
+//! Re-exports the types that most integrations need, so they can be imported at once.
+//!
+//! ```
+//! use evm_ekubo_sdk::prelude::*;
+//!
+//! let pool = FullRangePool::new(
+//!     NodeKey {
+//!         token0: U256::from(1).try_into().unwrap(),
+//!         token1: U256::from(2).try_into().unwrap(),
+//!         config: Config::from_raw(0, 0, U256::zero()).unwrap(),
+//!     },
+//!     FullRangePoolState {
+//!         sqrt_ratio: MIN_SQRT_RATIO,
+//!         liquidity: 0,
+//!     },
+//! )
+//! .unwrap();
+//! assert!(!pool.has_liquidity());
+//! ```
+
+pub use crate::math::tick::{MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO, MIN_TICK};
+pub use crate::math::uint::U256;
+pub use crate::quoting::base_pool::{
+    BasePool, BasePoolError, BasePoolQuoteError, BasePoolResources, BasePoolState,
+};
+pub use crate::quoting::full_range_pool::{
+    FullRangePool, FullRangePoolError, FullRangePoolQuoteError, FullRangePoolResources,
+    FullRangePoolState,
+};
+pub use crate::quoting::mev_resist_pool::{
+    MEVResistPool, MEVResistPoolError, MEVResistPoolResources, MEVResistPoolState,
+};
+pub use crate::quoting::oracle_pool::{
+    OraclePool, OraclePoolError, OraclePoolResources, OraclePoolState,
+};
+pub use crate::quoting::pool_reads::{Inventory, PoolReads};
+pub use crate::quoting::twamm_pool::{
+    TwammPool, TwammPoolError, TwammPoolQuoteError, TwammPoolResources, TwammPoolState,
+    TwammSaleRateDelta,
+};
+pub use crate::quoting::types::{
+    Address, BlockTimestamp, Config, NodeKey, Pool, Quote, QuoteParams, SqrtRatioState,
+    SwapDirection, Tick, TokenAmount,
+};
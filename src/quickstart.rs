@@ -0,0 +1,441 @@
+// One-call helpers for quoting pools described as JSON, for integrations that do not need to manage pool state
+// themselves. See `examples/quickstart.rs`.
+//
+// A pool is described in one of the following forms:
+// - {"full_range": {"key": ..., "state": ...}}
+// - {"base": {"key": ..., "state": ..., "sorted_ticks": [...]}}
+// - {"lens": {"key": ..., "sqrt_ratio": ..., "liquidity": ..., "tick": ..., "ticks": [...],
+//    "min_tick_searched": ..., "max_tick_searched": ...}}, i.e. the partial data returned by the quote data fetcher
+
+use crate::math::uint::U256;
+use crate::quoting::base_pool::{
+    BasePool, BasePoolError, BasePoolQuoteError, BasePoolResources, BasePoolState,
+};
+use crate::quoting::batch::QuoteRequest;
+use crate::quoting::full_range_pool::{
+    FullRangePool, FullRangePoolError, FullRangePoolQuoteError, FullRangePoolState,
+};
+use crate::quoting::types::{Address, NodeKey, Pool, Quote, QuoteParams, Tick, TokenAmount};
+use alloc::vec::Vec;
+use serde::Deserialize;
+
+// Externally tagged, because internally tagged enums do not support u128 fields with serde_json
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PoolJson {
+    FullRange {
+        key: NodeKey,
+        state: FullRangePoolState,
+    },
+    Base {
+        key: NodeKey,
+        state: BasePoolState,
+        sorted_ticks: Vec<Tick>,
+    },
+    Lens {
+        key: NodeKey,
+        #[serde(with = "crate::quoting::types::serde_u256")]
+        sqrt_ratio: U256,
+        liquidity: u128,
+        tick: i32,
+        ticks: Vec<Tick>,
+        min_tick_searched: i32,
+        max_tick_searched: i32,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QuickstartPool {
+    FullRange(FullRangePool),
+    Base(BasePool),
+}
+
+// The state of a quickstart pool, which must match the type of the pool when it is used as an override state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuickstartPoolState {
+    FullRange(FullRangePoolState),
+    Base(BasePoolState),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum QuickstartPoolError {
+    FullRange(FullRangePoolError),
+    Base(BasePoolError),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum QuickstartQuoteError {
+    FullRange(FullRangePoolQuoteError),
+    Base(BasePoolQuoteError),
+    /// The override state is the state of a different type of pool.
+    StateMismatch,
+}
+
+#[derive(Debug)]
+pub enum QuickstartError {
+    /// The JSON does not describe a pool, or a list of pools.
+    InvalidJson(serde_json::Error),
+    /// The pool at the index of the list, or at index 0 for a single pool, is invalid.
+    InvalidPool {
+        index: usize,
+        error: QuickstartPoolError,
+    },
+    /// The amount must fit in a signed 128 bit integer.
+    AmountOverflow,
+    /// The pool failed to quote the swap.
+    QuoteFailed(QuickstartQuoteError),
+}
+
+// The result of a quote, with the amounts from the perspective of the swapper
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimpleQuote {
+    pub consumed_amount: i128,
+    pub calculated_amount: u128,
+    pub fees_paid: u128,
+    pub sqrt_ratio_after: U256,
+}
+
+impl PoolJson {
+    fn into_pool(self) -> Result<QuickstartPool, QuickstartPoolError> {
+        match self {
+            PoolJson::FullRange { key, state } => FullRangePool::new(key, state)
+                .map(QuickstartPool::FullRange)
+                .map_err(QuickstartPoolError::FullRange),
+            PoolJson::Base {
+                key,
+                state,
+                sorted_ticks,
+            } => BasePool::new(key, state, sorted_ticks)
+                .map(QuickstartPool::Base)
+                .map_err(QuickstartPoolError::Base),
+            PoolJson::Lens {
+                key,
+                sqrt_ratio,
+                liquidity,
+                tick,
+                ticks,
+                min_tick_searched,
+                max_tick_searched,
+            } => BasePool::from_partial_data(
+                key,
+                sqrt_ratio,
+                ticks,
+                min_tick_searched,
+                max_tick_searched,
+                liquidity,
+                tick,
+            )
+            .map(QuickstartPool::Base)
+            .map_err(QuickstartPoolError::Base),
+        }
+    }
+}
+
+impl QuickstartPool {
+    pub fn get_key(&self) -> &NodeKey {
+        match self {
+            QuickstartPool::FullRange(pool) => pool.get_key(),
+            QuickstartPool::Base(pool) => pool.get_key(),
+        }
+    }
+
+    // Quotes the token amount against the current state of the pool
+    pub fn quote(
+        &self,
+        token_amount: TokenAmount,
+        sqrt_ratio_limit: Option<U256>,
+    ) -> Result<SimpleQuote, QuickstartQuoteError> {
+        let request = QuoteRequest {
+            token_amount,
+            sqrt_ratio_limit,
+            meta: (),
+        };
+
+        match self {
+            QuickstartPool::FullRange(pool) => pool
+                .quote(request.to_params())
+                .map(|quote| SimpleQuote {
+                    consumed_amount: quote.consumed_amount,
+                    calculated_amount: quote.calculated_amount,
+                    fees_paid: quote.fees_paid,
+                    sqrt_ratio_after: quote.state_after.sqrt_ratio,
+                })
+                .map_err(QuickstartQuoteError::FullRange),
+            QuickstartPool::Base(pool) => pool
+                .quote(request.to_params())
+                .map(|quote| SimpleQuote {
+                    consumed_amount: quote.consumed_amount,
+                    calculated_amount: quote.calculated_amount,
+                    fees_paid: quote.fees_paid,
+                    sqrt_ratio_after: quote.state_after.sqrt_ratio,
+                })
+                .map_err(QuickstartQuoteError::Base),
+        }
+    }
+}
+
+// Quickstart pools implement Pool so that routes through any of them can be quoted. The resources of full range pools
+// are reported as base pool resources without any ticks crossed.
+impl Pool for QuickstartPool {
+    type Resources = BasePoolResources;
+    type State = QuickstartPoolState;
+    type QuoteError = QuickstartQuoteError;
+    type Meta = ();
+
+    fn get_key(&self) -> &NodeKey {
+        QuickstartPool::get_key(self)
+    }
+
+    fn get_state(&self) -> Self::State {
+        match self {
+            QuickstartPool::FullRange(pool) => QuickstartPoolState::FullRange(pool.get_state()),
+            QuickstartPool::Base(pool) => QuickstartPoolState::Base(pool.get_state()),
+        }
+    }
+
+    fn quote(
+        &self,
+        params: QuoteParams<Self::State, Self::Meta>,
+    ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
+        match self {
+            QuickstartPool::FullRange(pool) => {
+                let override_state = match params.override_state {
+                    None => None,
+                    Some(QuickstartPoolState::FullRange(state)) => Some(state),
+                    Some(_) => return Err(QuickstartQuoteError::StateMismatch),
+                };
+                let quote = pool
+                    .quote(QuoteParams {
+                        token_amount: params.token_amount,
+                        sqrt_ratio_limit: params.sqrt_ratio_limit,
+                        max_ticks_crossed: params.max_ticks_crossed,
+                        override_state,
+                        meta: (),
+                    })
+                    .map_err(QuickstartQuoteError::FullRange)?;
+
+                Ok(Quote {
+                    is_price_increasing: quote.is_price_increasing,
+                    consumed_amount: quote.consumed_amount,
+                    calculated_amount: quote.calculated_amount,
+                    execution_resources: BasePoolResources {
+                        no_override_price_change: quote
+                            .execution_resources
+                            .no_override_price_change,
+                        ..Default::default()
+                    },
+                    state_after: QuickstartPoolState::FullRange(quote.state_after),
+                    fees_paid: quote.fees_paid,
+                })
+            }
+            QuickstartPool::Base(pool) => {
+                let override_state = match params.override_state {
+                    None => None,
+                    Some(QuickstartPoolState::Base(state)) => Some(state),
+                    Some(_) => return Err(QuickstartQuoteError::StateMismatch),
+                };
+                let quote = pool
+                    .quote(QuoteParams {
+                        token_amount: params.token_amount,
+                        sqrt_ratio_limit: params.sqrt_ratio_limit,
+                        max_ticks_crossed: params.max_ticks_crossed,
+                        override_state,
+                        meta: (),
+                    })
+                    .map_err(QuickstartQuoteError::Base)?;
+
+                Ok(Quote {
+                    is_price_increasing: quote.is_price_increasing,
+                    consumed_amount: quote.consumed_amount,
+                    calculated_amount: quote.calculated_amount,
+                    execution_resources: quote.execution_resources,
+                    state_after: QuickstartPoolState::Base(quote.state_after),
+                    fees_paid: quote.fees_paid,
+                })
+            }
+        }
+    }
+
+    fn has_liquidity(&self) -> bool {
+        match self {
+            QuickstartPool::FullRange(pool) => pool.has_liquidity(),
+            QuickstartPool::Base(pool) => pool.has_liquidity(),
+        }
+    }
+
+    fn max_tick_with_liquidity(&self) -> Option<i32> {
+        match self {
+            QuickstartPool::FullRange(pool) => pool.max_tick_with_liquidity(),
+            QuickstartPool::Base(pool) => pool.max_tick_with_liquidity(),
+        }
+    }
+
+    fn min_tick_with_liquidity(&self) -> Option<i32> {
+        match self {
+            QuickstartPool::FullRange(pool) => pool.min_tick_with_liquidity(),
+            QuickstartPool::Base(pool) => pool.min_tick_with_liquidity(),
+        }
+    }
+
+    fn is_path_dependent(&self) -> bool {
+        false
+    }
+}
+
+// Parses and validates a single pool
+pub fn parse_pool(json: &str) -> Result<QuickstartPool, QuickstartError> {
+    serde_json::from_str::<PoolJson>(json)
+        .map_err(QuickstartError::InvalidJson)?
+        .into_pool()
+        .map_err(|error| QuickstartError::InvalidPool { index: 0, error })
+}
+
+// Parses and validates a list of pools
+pub fn parse_pools(json: &str) -> Result<Vec<QuickstartPool>, QuickstartError> {
+    serde_json::from_str::<Vec<PoolJson>>(json)
+        .map_err(QuickstartError::InvalidJson)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, pool)| {
+            pool.into_pool()
+                .map_err(|error| QuickstartError::InvalidPool { index, error })
+        })
+        .collect()
+}
+
+// Quotes selling `amount` of `token_in` to the pool described by the JSON
+pub fn quote_simple(
+    pool_state_json: &str,
+    token_in: Address,
+    amount: u128,
+) -> Result<SimpleQuote, QuickstartError> {
+    let amount = i128::try_from(amount).map_err(|_| QuickstartError::AmountOverflow)?;
+
+    parse_pool(pool_state_json)?
+        .quote(
+            TokenAmount {
+                token: token_in,
+                amount,
+            },
+            None,
+        )
+        .map_err(QuickstartError::QuoteFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL_RANGE_POOL_JSON: &str = r#"{"full_range": {
+        "key": {"token0": "1", "token1": "2", "config": {"fee": 0, "tick_spacing": 0, "extension": "0"}},
+        "state": {"sqrt_ratio": "100000000000000000000000000000000", "liquidity": 1000000}
+    }}"#;
+
+    const LENS_POOL_JSON: &str = r#"{"lens": {
+        "key": {"token0": "1", "token1": "2", "config": {"fee": 0, "tick_spacing": 100, "extension": "0"}},
+        "sqrt_ratio": "100000000000000000000000000000000",
+        "liquidity": 100000000,
+        "tick": 0,
+        "ticks": [{"index": -100000, "liquidity_delta": 100000000}, {"index": 100000, "liquidity_delta": -100000000}],
+        "min_tick_searched": -100000,
+        "max_tick_searched": 100000
+    }}"#;
+
+    #[test]
+    fn test_quote_simple() {
+        assert_eq!(
+            quote_simple(
+                FULL_RANGE_POOL_JSON,
+                Address::from_raw(U256::one()),
+                100_000
+            )
+            .unwrap(),
+            SimpleQuote {
+                consumed_amount: 100_000,
+                calculated_amount: 90909,
+                fees_paid: 0,
+                sqrt_ratio_after: U256::from_dec_str("309347606291762239512158734028880192233")
+                    .unwrap(),
+            }
+        );
+        assert_eq!(
+            quote_simple(LENS_POOL_JSON, Address::from_raw(U256::one()), 100_000)
+                .unwrap()
+                .calculated_amount,
+            99900
+        );
+    }
+
+    #[test]
+    fn test_quote_simple_errors() {
+        assert!(matches!(
+            quote_simple("{}", Address::from_raw(U256::one()), 1),
+            Err(QuickstartError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            quote_simple(
+                FULL_RANGE_POOL_JSON,
+                Address::from_raw(U256::one()),
+                u128::MAX
+            ),
+            Err(QuickstartError::AmountOverflow)
+        ));
+        assert!(matches!(
+            quote_simple(FULL_RANGE_POOL_JSON, Address::from_raw(U256::from(3)), 1),
+            Err(QuickstartError::QuoteFailed(
+                QuickstartQuoteError::FullRange(FullRangePoolQuoteError::InvalidToken)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_parse_pools() {
+        let json = alloc::format!("[{FULL_RANGE_POOL_JSON}, {LENS_POOL_JSON}]");
+        let pools = parse_pools(&json).unwrap();
+        assert_eq!(pools.len(), 2);
+        assert!(matches!(pools[1], QuickstartPool::Base(_)));
+
+        let invalid = r#"[{"full_range": {
+            "key": {"token0": "2", "token1": "1", "config": {"fee": 0, "tick_spacing": 0, "extension": "0"}},
+            "state": {"sqrt_ratio": "100000000000000000000000000000000", "liquidity": 0}
+        }}]"#;
+        let json = alloc::format!("[{FULL_RANGE_POOL_JSON}, {}", &invalid[1..]);
+        assert!(matches!(
+            parse_pools(&json),
+            Err(QuickstartError::InvalidPool {
+                index: 1,
+                error: QuickstartPoolError::FullRange(FullRangePoolError::TokenOrderInvalid),
+            })
+        ));
+    }
+
+    #[test]
+    fn test_pool_override_state() {
+        let full_range = parse_pool(FULL_RANGE_POOL_JSON).unwrap();
+        let lens = parse_pool(LENS_POOL_JSON).unwrap();
+        let params = |override_state| QuoteParams {
+            token_amount: TokenAmount {
+                token: Address::from_raw(U256::one()),
+                amount: 100_000,
+            },
+            sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
+            override_state,
+            meta: (),
+        };
+
+        let quote = Pool::quote(&full_range, params(None)).unwrap();
+        assert_eq!(quote.calculated_amount, 90909);
+        // quoting from the state after the first quote continues the swap
+        assert!(
+            Pool::quote(&full_range, params(Some(quote.state_after)))
+                .unwrap()
+                .calculated_amount
+                < quote.calculated_amount
+        );
+        assert_eq!(
+            Pool::quote(&lens, params(Some(quote.state_after))),
+            Err(QuickstartQuoteError::StateMismatch)
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use crate::math::uint::U256;
+use crate::quoting::constants::ChainConfig;
+use crate::quoting::route::{Route, RouteDecodingError};
+use crate::quoting::types::Address;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+// How the router is allowed to pull the input tokens of the user
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApprovalMethod {
+    // An ERC20 approval of the router
+    Direct { router: U256 },
+    // An ERC20 approval of the Permit2 contract, plus a Permit2 allowance or signature for the router
+    Permit2 { permit2: U256, router: U256 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ApprovalKind {
+    // `approve(spender, amount)` on the token
+    Erc20,
+    // `approve(token, spender, amount, expiration)` on Permit2, or a signed permit with the same values
+    Permit2,
+}
+
+// An allowance the user must have for the swap to succeed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TokenApproval {
+    pub kind: ApprovalKind,
+    pub token: Address,
+    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
+    pub spender: U256,
+    pub amount: u128,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ApprovalError {
+    /// The route at the index could not be decoded.
+    InvalidRoute {
+        index: usize,
+        error: RouteDecodingError,
+    },
+    /// The total amount of the token spent by the routes does not fit in a u128.
+    AmountOverflow(Address),
+}
+
+/// Returns the approvals the user must have to swap through the encoded routes, each paired with the maximum amount
+/// of its input token it may spend, e.g. the quoted input of an exact output swap plus slippage.
+///
+/// The amounts of routes with the same input token are added up, since they are settled together. The native token
+/// is sent with the transaction and does not need an approval. With Permit2, the Permit2 contract needs an ERC20
+/// approval for each token, which is listed before the Permit2 approval of the router.
+pub fn required_approvals(
+    encoded_routes: &[(&[u8], u128)],
+    method: ApprovalMethod,
+) -> Result<Vec<TokenApproval>, ApprovalError> {
+    required_approvals_with_chain_config(encoded_routes, method, &ChainConfig::default())
+}
+
+// Returns the required approvals on a chain whose native token is the one of the chain config
+pub fn required_approvals_with_chain_config(
+    encoded_routes: &[(&[u8], u128)],
+    method: ApprovalMethod,
+    chain: &ChainConfig,
+) -> Result<Vec<TokenApproval>, ApprovalError> {
+    let mut amounts: BTreeMap<Address, u128> = BTreeMap::new();
+
+    for (index, &(encoded_route, max_amount_in)) in encoded_routes.iter().enumerate() {
+        let token = Route::decode(encoded_route)
+            .map_err(|error| ApprovalError::InvalidRoute { index, error })?
+            .token_in;
+        if token == chain.native_token {
+            continue;
+        }
+
+        let amount = amounts.entry(token).or_default();
+        *amount = amount
+            .checked_add(max_amount_in)
+            .ok_or(ApprovalError::AmountOverflow(token))?;
+    }
+
+    Ok(amounts
+        .into_iter()
+        .filter(|&(_, amount)| amount != 0)
+        .flat_map(|(token, amount)| {
+            let approval = |kind, spender| TokenApproval {
+                kind,
+                token,
+                spender,
+                amount,
+            };
+            match method {
+                ApprovalMethod::Direct { router } => {
+                    [Some(approval(ApprovalKind::Erc20, router)), None]
+                }
+                ApprovalMethod::Permit2 { permit2, router } => [
+                    Some(approval(ApprovalKind::Erc20, permit2)),
+                    Some(approval(ApprovalKind::Permit2, router)),
+                ],
+            }
+        })
+        .flatten()
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::route::{HopDirection, RouteHop, RouteSplit, ROUTE_SHARE_DENOMINATOR};
+    use crate::quoting::types::{Config, NodeKey};
+    use alloc::vec;
+
+    const ROUTER: U256 = U256([100, 0, 0, 0]);
+    const PERMIT2: U256 = U256([200, 0, 0, 0]);
+
+    fn encoded_route(token_in: u64, token_out: u64) -> Vec<u8> {
+        let (token0, token1, direction) = if token_in < token_out {
+            (token_in, token_out, HopDirection::ZeroForOne)
+        } else {
+            (token_out, token_in, HopDirection::OneForZero)
+        };
+        Route {
+            token_in: Address::from_raw(U256::from(token_in)),
+            token_out: Address::from_raw(U256::from(token_out)),
+            splits: vec![RouteSplit {
+                share: ROUTE_SHARE_DENOMINATOR,
+                hops: vec![RouteHop {
+                    pool_key: NodeKey {
+                        token0: Address::from_raw(U256::from(token0)),
+                        token1: Address::from_raw(U256::from(token1)),
+                        config: Config::from_raw_unchecked(0, 100, U256::zero()),
+                    },
+                    direction,
+                }],
+            }],
+        }
+        .encode()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_direct_approvals() {
+        let (a, b, native) = (
+            encoded_route(2, 1),
+            encoded_route(2, 3),
+            encoded_route(0, 1),
+        );
+        let approvals = required_approvals(
+            &[(&a, 1_000), (&native, 5_000), (&b, 500)],
+            ApprovalMethod::Direct { router: ROUTER },
+        )
+        .unwrap();
+
+        assert_eq!(
+            approvals,
+            [TokenApproval {
+                kind: ApprovalKind::Erc20,
+                token: Address::from_raw(U256::from(2)),
+                spender: ROUTER,
+                amount: 1_500,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_configured_native_token_skips_approval() {
+        let (wrapped, native) = (encoded_route(5, 1), encoded_route(0, 1));
+        let approvals = required_approvals_with_chain_config(
+            &[(&wrapped, 1_000), (&native, 2_000)],
+            ApprovalMethod::Direct { router: ROUTER },
+            &ChainConfig {
+                native_token: Address::from_raw(U256::from(5)),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            approvals,
+            [TokenApproval {
+                kind: ApprovalKind::Erc20,
+                token: Address::from_raw(U256::zero()),
+                spender: ROUTER,
+                amount: 2_000,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_permit2_approvals() {
+        let (a, b) = (encoded_route(3, 1), encoded_route(1, 3));
+        let approvals = required_approvals(
+            &[(&a, 1_000), (&b, 2_000)],
+            ApprovalMethod::Permit2 {
+                permit2: PERMIT2,
+                router: ROUTER,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            approvals
+                .iter()
+                .map(|approval| (
+                    approval.kind,
+                    approval.token.raw().as_u64(),
+                    approval.spender,
+                    approval.amount
+                ))
+                .collect::<Vec<_>>(),
+            [
+                (ApprovalKind::Erc20, 1, PERMIT2, 2_000),
+                (ApprovalKind::Permit2, 1, ROUTER, 2_000),
+                (ApprovalKind::Erc20, 3, PERMIT2, 1_000),
+                (ApprovalKind::Permit2, 3, ROUTER, 1_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_approval_errors() {
+        let route = encoded_route(1, 2);
+        let method = ApprovalMethod::Direct { router: ROUTER };
+        assert_eq!(
+            required_approvals(&[(&route, 1), (&route[..10], 1)], method),
+            Err(ApprovalError::InvalidRoute {
+                index: 1,
+                error: RouteDecodingError::UnexpectedEnd
+            })
+        );
+        assert_eq!(
+            required_approvals(&[(&route, u128::MAX), (&route, 1)], method),
+            Err(ApprovalError::AmountOverflow(Address::from_raw(
+                U256::from(1)
+            )))
+        );
+    }
+}
@@ -0,0 +1,383 @@
+use crate::math::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+use crate::math::uint::{u256_to_float_base_x128, U256};
+use crate::quoting::base_pool::{BasePool, BasePoolView};
+use crate::quoting::full_range_pool::FullRangePool;
+use crate::quoting::types::{Address, Pool, Tick};
+
+// The natural logarithm of the sqrt ratio of tick 1
+const LN_SQRT_TICK_SIZE: f64 = 4.9999975000016666654166676666658333340476184226196031741031750577196410537756684185262518589393595459766211405607685305832e-7;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ApproxQuote {
+    // Negative for exact output, like the consumed amount of an exact quote
+    pub consumed_amount: f64,
+    pub calculated_amount: f64,
+    pub sqrt_ratio_after: f64,
+}
+
+/// Approximate quoting with floating point math, which is typically an order of magnitude faster than exact quoting.
+///
+/// These quotes are NOT exact: they do not round like the contracts and accumulate floating point error, so they must
+/// never be used to compute the amounts or limits of a swap. They are meant for heuristics such as pruning candidate
+/// routes before quoting the remaining ones exactly.
+pub trait QuoteApprox {
+    /// Returns an approximate quote for swapping the amount of the token, or None if the pool does not trade the
+    /// token. Negative amounts are exact output, like the amount of a TokenAmount.
+    fn quote_approx(&self, token: Address, amount: f64) -> Option<ApproxQuote>;
+}
+
+// The state of a swap that moves the price across ranges of constant liquidity
+struct ApproxSwap {
+    is_token1: bool,
+    is_increasing: bool,
+    fee: f64,
+    sqrt_ratio: f64,
+    liquidity: f64,
+    amount_remaining: f64,
+    calculated_amount: f64,
+}
+
+impl ApproxSwap {
+    fn new(sqrt_ratio: U256, liquidity: u128, fee: u64, is_token1: bool, amount: f64) -> Self {
+        Self {
+            is_token1,
+            is_increasing: (amount < 0.0) != is_token1,
+            fee: fee as f64 / 18446744073709551616f64,
+            sqrt_ratio: u256_to_float_base_x128(sqrt_ratio),
+            liquidity: liquidity as f64,
+            amount_remaining: amount.abs(),
+            calculated_amount: 0.0,
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.amount_remaining <= 0.0
+    }
+
+    // Amount of token0 and token1 between the current sqrt ratio and the target sqrt ratio
+    fn deltas(&self, target: f64) -> (f64, f64) {
+        (
+            self.liquidity * (1.0 / self.sqrt_ratio - 1.0 / target).abs(),
+            self.liquidity * (target - self.sqrt_ratio).abs(),
+        )
+    }
+
+    // Swaps until the amount is used up or the sqrt ratio reaches the boundary
+    fn step(&mut self, boundary: f64) {
+        if self.liquidity <= 0.0 {
+            self.sqrt_ratio = boundary;
+            return;
+        }
+
+        let (specified_to_boundary, calculated_to_boundary) = {
+            let (amount0, amount1) = self.deltas(boundary);
+            if self.is_token1 {
+                (amount1, amount0)
+            } else {
+                (amount0, amount1)
+            }
+        };
+
+        let is_exact_output = self.is_increasing != self.is_token1;
+        // fees are taken from the input amount
+        let fee_multiplier = 1.0 - self.fee;
+
+        if is_exact_output {
+            if self.amount_remaining < specified_to_boundary {
+                let target = if self.is_token1 {
+                    self.sqrt_ratio - self.amount_remaining / self.liquidity
+                } else {
+                    1.0 / (1.0 / self.sqrt_ratio - self.amount_remaining / self.liquidity)
+                };
+                let (amount0, amount1) = self.deltas(target);
+                self.calculated_amount +=
+                    if self.is_token1 { amount0 } else { amount1 } / fee_multiplier;
+                self.amount_remaining = 0.0;
+                self.sqrt_ratio = target;
+            } else {
+                self.calculated_amount += calculated_to_boundary / fee_multiplier;
+                self.amount_remaining -= specified_to_boundary;
+                self.sqrt_ratio = boundary;
+            }
+        } else {
+            let amount_after_fee = self.amount_remaining * fee_multiplier;
+            if amount_after_fee < specified_to_boundary {
+                let target = if self.is_token1 {
+                    self.sqrt_ratio + amount_after_fee / self.liquidity
+                } else {
+                    1.0 / (1.0 / self.sqrt_ratio + amount_after_fee / self.liquidity)
+                };
+                let (amount0, amount1) = self.deltas(target);
+                self.calculated_amount += if self.is_token1 { amount0 } else { amount1 };
+                self.amount_remaining = 0.0;
+                self.sqrt_ratio = target;
+            } else {
+                self.calculated_amount += calculated_to_boundary;
+                self.amount_remaining -= specified_to_boundary / fee_multiplier;
+                self.sqrt_ratio = boundary;
+            }
+        }
+    }
+
+    fn finish(self, amount: f64) -> ApproxQuote {
+        ApproxQuote {
+            consumed_amount: amount - amount.signum() * self.amount_remaining.max(0.0),
+            calculated_amount: self.calculated_amount,
+            sqrt_ratio_after: self.sqrt_ratio,
+        }
+    }
+}
+
+fn sqrt_ratio_bound(is_increasing: bool) -> f64 {
+    u256_to_float_base_x128(if is_increasing {
+        MAX_SQRT_RATIO
+    } else {
+        MIN_SQRT_RATIO
+    })
+}
+
+fn tick_to_sqrt_ratio_approx(tick: i32) -> f64 {
+    (tick as f64 * LN_SQRT_TICK_SIZE).exp()
+}
+
+fn token_side<P: Pool>(pool: &P, token: Address) -> Option<bool> {
+    let key = pool.get_key();
+    if token == key.token1 {
+        Some(true)
+    } else if token == key.token0 {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+impl QuoteApprox for FullRangePool {
+    fn quote_approx(&self, token: Address, amount: f64) -> Option<ApproxQuote> {
+        let is_token1 = token_side(self, token)?;
+        let state = self.get_state();
+        let mut swap = ApproxSwap::new(
+            state.sqrt_ratio,
+            state.liquidity,
+            self.get_key().config.fee.raw(),
+            is_token1,
+            amount,
+        );
+
+        if amount != 0.0 {
+            swap.step(sqrt_ratio_bound(swap.is_increasing));
+        }
+        Some(swap.finish(amount))
+    }
+}
+
+impl QuoteApprox for BasePoolView<'_> {
+    fn quote_approx(&self, token: Address, amount: f64) -> Option<ApproxQuote> {
+        let is_token1 = token_side(self, token)?;
+        let state = self.get_state();
+        let mut swap = ApproxSwap::new(
+            state.sqrt_ratio,
+            state.liquidity,
+            self.get_key().config.fee.raw(),
+            is_token1,
+            amount,
+        );
+        if amount == 0.0 {
+            return Some(swap.finish(amount));
+        }
+
+        let sorted_ticks = self.get_sorted_ticks();
+        let cross = |swap: &mut ApproxSwap, tick: &Tick| {
+            swap.step(tick_to_sqrt_ratio_approx(tick.index));
+            if !swap.is_done() {
+                let delta = tick.liquidity_delta as f64;
+                swap.liquidity += if swap.is_increasing { delta } else { -delta };
+            }
+        };
+
+        if swap.is_increasing {
+            let next = state.active_tick_index.map_or(0, |index| index + 1);
+            for tick in &sorted_ticks[next.min(sorted_ticks.len())..] {
+                cross(&mut swap, tick);
+                if swap.is_done() {
+                    break;
+                }
+            }
+        } else if let Some(active) = state.active_tick_index {
+            for tick in sorted_ticks[..=active].iter().rev() {
+                cross(&mut swap, tick);
+                if swap.is_done() {
+                    break;
+                }
+            }
+        }
+
+        if !swap.is_done() {
+            swap.step(sqrt_ratio_bound(swap.is_increasing));
+        }
+        Some(swap.finish(amount))
+    }
+}
+
+impl QuoteApprox for BasePool {
+    fn quote_approx(&self, token: Address, amount: f64) -> Option<ApproxQuote> {
+        self.as_view().quote_approx(token, amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::to_sqrt_ratio;
+    use crate::quoting::base_pool::BasePoolState;
+    use crate::quoting::full_range_pool::FullRangePoolState;
+    use crate::quoting::types::{Config, NodeKey, QuoteParams, SqrtRatioState, TokenAmount};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn key(fee: u64, tick_spacing: u32) -> NodeKey {
+        NodeKey {
+            token0: TOKEN0,
+            token1: TOKEN1,
+            config: Config::from_raw_unchecked(fee, tick_spacing, U256::zero()),
+        }
+    }
+
+    // positions [-20000, 20000) with 1e12, [-1000, 3000) with 5e12 and [2000, 50000) with 3e12 liquidity
+    fn base_pool(tick: i32) -> BasePool {
+        let sorted_ticks = vec![
+            Tick {
+                index: -20000,
+                liquidity_delta: 1_000_000_000_000,
+            },
+            Tick {
+                index: -1000,
+                liquidity_delta: 5_000_000_000_000,
+            },
+            Tick {
+                index: 2000,
+                liquidity_delta: 3_000_000_000_000,
+            },
+            Tick {
+                index: 3000,
+                liquidity_delta: -5_000_000_000_000,
+            },
+            Tick {
+                index: 20000,
+                liquidity_delta: -1_000_000_000_000,
+            },
+            Tick {
+                index: 50000,
+                liquidity_delta: -3_000_000_000_000,
+            },
+        ];
+        let active_tick_index = sorted_ticks.iter().rposition(|t| t.index <= tick);
+        let liquidity = sorted_ticks[..active_tick_index.map_or(0, |i| i + 1)]
+            .iter()
+            .map(|t| t.liquidity_delta)
+            .sum::<i128>() as u128;
+
+        BasePool::new(
+            key(1 << 61, 100),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(tick).unwrap(),
+                liquidity,
+                active_tick_index,
+            },
+            sorted_ticks,
+        )
+        .unwrap()
+    }
+
+    fn full_range_pool() -> FullRangePool {
+        FullRangePool::new(
+            key(1 << 59, 0),
+            FullRangePoolState {
+                sqrt_ratio: to_sqrt_ratio(693147).unwrap(),
+                liquidity: 10_000_000_000_000,
+            },
+        )
+        .unwrap()
+    }
+
+    fn assert_close<P: Pool + QuoteApprox>(pool: &P, token: Address, amount: i128)
+    where
+        P::Meta: Default,
+        P::State: SqrtRatioState,
+    {
+        let exact = pool
+            .quote(QuoteParams {
+                token_amount: TokenAmount { token, amount },
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: None,
+                meta: Default::default(),
+            })
+            .unwrap();
+        let approx = pool.quote_approx(token, amount as f64).unwrap();
+
+        let tolerance = |exact: f64| exact.abs() * 1e-6 + 10.0;
+        let calculated = exact.calculated_amount as f64;
+        assert!(
+            (approx.calculated_amount - calculated).abs() <= tolerance(calculated),
+            "{amount}: {} vs {calculated}",
+            approx.calculated_amount
+        );
+        let consumed = exact.consumed_amount as f64;
+        assert!(
+            (approx.consumed_amount - consumed).abs() <= tolerance(consumed),
+            "{amount}: {} vs {consumed}",
+            approx.consumed_amount
+        );
+        let sqrt_ratio = u256_to_float_base_x128(exact.state_after.sqrt_ratio());
+        assert!((approx.sqrt_ratio_after / sqrt_ratio - 1.0).abs() < 1e-6);
+    }
+
+    fn amounts() -> Vec<i128> {
+        vec![
+            1_000_000,
+            -1_000_000,
+            100_000_000_000,
+            -100_000_000_000,
+            1_000_000_000_000,
+            -1_000_000_000_000,
+        ]
+    }
+
+    #[test]
+    fn test_base_pool_deviation_is_bounded() {
+        for tick in [-25000, -1000, 0, 2500, 60000] {
+            let pool = base_pool(tick);
+            for amount in amounts() {
+                for token in [TOKEN0, TOKEN1] {
+                    assert_close(&pool, token, amount);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_full_range_pool_deviation_is_bounded() {
+        let pool = full_range_pool();
+        for amount in amounts() {
+            for token in [TOKEN0, TOKEN1] {
+                assert_close(&pool, token, amount);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_token_and_zero_amount() {
+        let pool = base_pool(0);
+        assert_eq!(
+            pool.quote_approx(Address::from_raw(U256::from(3)), 1.0),
+            None
+        );
+
+        let quote = pool.quote_approx(TOKEN0, 0.0).unwrap();
+        assert_eq!(quote.consumed_amount, 0.0);
+        assert_eq!(quote.calculated_amount, 0.0);
+    }
+}
@@ -0,0 +1,383 @@
+use crate::math::uint::{u256_to_float_base_x128, U256};
+use crate::quoting::cancellation::{Cancelled, NeverStop, StopSignal};
+use crate::quoting::pool_map::PoolMap;
+use crate::quoting::route::{
+    quote_route, HopDirection, Route, RouteHop, RouteSplit, ROUTE_SHARE_DENOMINATOR,
+};
+use crate::quoting::types::{Address, NodeKey, Pool, SqrtRatioState};
+use alloc::vec::Vec;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArbitrageConfig {
+    // The maximum number of hops in a cycle, at least 2
+    pub max_hops: usize,
+    // The minimum profit of a cycle at the marginal prices of its pools, in basis points, for it to be quoted
+    pub min_marginal_profit_bps: u32,
+    // The minimum profit in the start token of a cycle for it to be returned
+    pub min_profit: u128,
+}
+
+impl Default for ArbitrageConfig {
+    fn default() -> Self {
+        Self {
+            max_hops: 3,
+            min_marginal_profit_bps: 0,
+            min_profit: 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArbitrageOpportunity {
+    // A single split route from the start token back to the start token
+    pub route: Route,
+    pub amount_in: u128,
+    pub amount_out: u128,
+}
+
+impl ArbitrageOpportunity {
+    pub fn profit(&self) -> u128 {
+        self.amount_out - self.amount_in
+    }
+}
+
+// The amount of the output token received per unit of the input token at the current price, after fees
+fn marginal_rate(key: &NodeKey, sqrt_ratio: U256, direction: HopDirection) -> f64 {
+    let sqrt_price = u256_to_float_base_x128(sqrt_ratio);
+    let price = sqrt_price * sqrt_price;
+    let fee_multiplier = 1f64 - key.config.fee.raw() as f64 / 18446744073709551616f64;
+
+    match direction {
+        HopDirection::ZeroForOne => price * fee_multiplier,
+        HopDirection::OneForZero => fee_multiplier / price,
+    }
+}
+
+struct Edge {
+    hop: RouteHop,
+    token_out: Address,
+    rate: f64,
+}
+
+struct CycleSearch<'a> {
+    edges: &'a [Edge],
+    start_token: Address,
+    max_hops: usize,
+    min_rate: f64,
+    path: Vec<usize>,
+    cycles: Vec<Vec<RouteHop>>,
+}
+
+impl CycleSearch<'_> {
+    fn visit(&mut self, token: Address, rate: f64) {
+        if self.path.len() == self.max_hops {
+            return;
+        }
+
+        for (index, edge) in self.edges.iter().enumerate() {
+            if edge.hop.direction.token_in(&edge.hop.pool_key) != token
+                || self
+                    .path
+                    .iter()
+                    .any(|&used| self.edges[used].hop.pool_key == edge.hop.pool_key)
+            {
+                continue;
+            }
+
+            let rate = rate * edge.rate;
+            if edge.token_out == self.start_token {
+                if !self.path.is_empty() && rate >= self.min_rate {
+                    self.cycles.push(
+                        self.path
+                            .iter()
+                            .chain(core::iter::once(&index))
+                            .map(|&used| self.edges[used].hop)
+                            .collect(),
+                    );
+                }
+                continue;
+            }
+
+            // only simple cycles, so intermediate tokens are visited once
+            if self
+                .path
+                .iter()
+                .any(|&used| self.edges[used].token_out == edge.token_out)
+            {
+                continue;
+            }
+
+            self.path.push(index);
+            self.visit(edge.token_out, rate);
+            self.path.pop();
+        }
+    }
+}
+
+/// Searches for cycles of pools from the start token back to itself that are profitable.
+///
+/// Cycles are first screened using the marginal prices of their pools, so only cycles whose marginal profit is at
+/// least `min_marginal_profit_bps` are quoted. Each remaining cycle is then quoted with each of the trial amounts,
+/// and the most profitable amount is kept if its profit is at least `min_profit`. The opportunities are returned in
+/// descending order of profit.
+///
+/// The opportunities are independent of each other, executing one of them changes the profit of the others.
+pub fn find_arbitrage_cycles<P: Pool>(
+    pools: &PoolMap<P>,
+    start_token: Address,
+    trial_amounts: &[u128],
+    config: ArbitrageConfig,
+    meta: P::Meta,
+) -> Vec<ArbitrageOpportunity>
+where
+    P::State: SqrtRatioState,
+{
+    find_arbitrage_cycles_until(pools, start_token, trial_amounts, config, meta, &NeverStop)
+        .unwrap_or_else(|cancelled| cancelled.partial)
+}
+
+// Same as find_arbitrage_cycles, but checks the stop signal before quoting each cycle. A cancelled search returns the
+// opportunities among the cycles that were quoted before, sorted the same way.
+pub fn find_arbitrage_cycles_until<P: Pool>(
+    pools: &PoolMap<P>,
+    start_token: Address,
+    trial_amounts: &[u128],
+    config: ArbitrageConfig,
+    meta: P::Meta,
+    stop: &impl StopSignal,
+) -> Result<Vec<ArbitrageOpportunity>, Cancelled<Vec<ArbitrageOpportunity>>>
+where
+    P::State: SqrtRatioState,
+{
+    let edges: Vec<Edge> = pools
+        .iter()
+        .filter(|pool| pool.has_liquidity())
+        .flat_map(|pool| {
+            let key = *pool.get_key();
+            let sqrt_ratio = pool.get_state().sqrt_ratio();
+            [HopDirection::ZeroForOne, HopDirection::OneForZero].map(|direction| Edge {
+                hop: RouteHop {
+                    pool_key: key,
+                    direction,
+                },
+                token_out: direction.token_out(&key),
+                rate: marginal_rate(&key, sqrt_ratio, direction),
+            })
+        })
+        .collect();
+
+    let mut search = CycleSearch {
+        edges: &edges,
+        start_token,
+        max_hops: config.max_hops,
+        min_rate: 1f64 + config.min_marginal_profit_bps as f64 / ROUTE_SHARE_DENOMINATOR as f64,
+        path: Vec::new(),
+        cycles: Vec::new(),
+    };
+    search.visit(start_token, 1f64);
+
+    let mut opportunities: Vec<ArbitrageOpportunity> = Vec::new();
+    let mut cancelled = false;
+
+    for hops in search.cycles {
+        if stop.should_stop() {
+            cancelled = true;
+            break;
+        }
+
+        let route = Route {
+            token_in: start_token,
+            token_out: start_token,
+            splits: alloc::vec![RouteSplit {
+                share: ROUTE_SHARE_DENOMINATOR,
+                hops,
+            }],
+        };
+
+        let best = trial_amounts
+            .iter()
+            .filter_map(|&amount_in| {
+                quote_route(pools, &route, amount_in, meta)
+                    .ok()
+                    .map(|quote| (amount_in, quote.amount_out))
+            })
+            .filter(|&(amount_in, amount_out)| {
+                amount_out
+                    .checked_sub(amount_in)
+                    .is_some_and(|profit| profit >= config.min_profit)
+            })
+            .max_by_key(|&(amount_in, amount_out)| amount_out - amount_in);
+
+        if let Some((amount_in, amount_out)) = best {
+            opportunities.push(ArbitrageOpportunity {
+                route,
+                amount_in,
+                amount_out,
+            });
+        }
+    }
+
+    opportunities.sort_by_key(|opportunity| core::cmp::Reverse(opportunity.profit()));
+    if cancelled {
+        Err(Cancelled {
+            partial: opportunities,
+        })
+    } else {
+        Ok(opportunities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::types::Config;
+
+    fn pool(token0: u64, token1: u64, fee: u64, sqrt_ratio: U256) -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0: Address::from_raw(U256::from(token0)),
+                token1: Address::from_raw(U256::from(token1)),
+                config: Config::from_raw_unchecked(fee, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio,
+                liquidity: 1_000_000_000,
+            },
+        )
+        .unwrap()
+    }
+
+    const FEE: u64 = 1 << 54; // ~0.1%
+
+    #[test]
+    fn test_triangular_arbitrage() {
+        let one = U256::one() << 128;
+        // token1 is worth ~2% more in token0 terms through token2 than directly
+        let pools: PoolMap<FullRangePool> = [
+            pool(1, 2, FEE, one),
+            pool(2, 3, FEE, one),
+            pool(1, 3, FEE, one + one / 100),
+        ]
+        .into_iter()
+        .collect();
+
+        let opportunities = find_arbitrage_cycles(
+            &pools,
+            Address::from_raw(U256::from(1)),
+            &[1_000, 100_000, 10_000_000],
+            ArbitrageConfig::default(),
+            (),
+        );
+
+        assert_eq!(opportunities.len(), 1);
+        let opportunity = &opportunities[0];
+        assert_eq!(opportunity.amount_in, 100_000);
+        assert!(opportunity.profit() > 1_000);
+
+        // sell token1 for token3 where it is expensive, then go back through token2
+        let hops = &opportunity.route.splits[0].hops;
+        assert_eq!(hops.len(), 3);
+        assert_eq!(hops[0].pool_key.token1, U256::from(3));
+        assert_eq!(hops[0].direction, HopDirection::ZeroForOne);
+        assert_eq!(
+            quote_route(&pools, &opportunity.route, opportunity.amount_in, ())
+                .unwrap()
+                .amount_out,
+            opportunity.amount_out
+        );
+    }
+
+    #[test]
+    fn test_cancelled_search() {
+        let one = U256::one() << 128;
+        let pools: PoolMap<FullRangePool> =
+            [pool(1, 2, FEE, one), pool(1, 2, FEE * 2, one + one / 50)]
+                .into_iter()
+                .collect();
+        let search = |stop: &dyn Fn() -> bool| {
+            find_arbitrage_cycles_until(
+                &pools,
+                Address::from_raw(U256::from(1)),
+                &[100_000],
+                ArbitrageConfig::default(),
+                (),
+                &stop,
+            )
+        };
+
+        assert_eq!(search(&|| false).unwrap().len(), 1);
+        assert_eq!(
+            search(&|| true),
+            Err(Cancelled {
+                partial: Vec::new()
+            })
+        );
+    }
+
+    #[test]
+    fn test_two_pools_of_the_same_pair() {
+        let one = U256::one() << 128;
+        let pools: PoolMap<FullRangePool> =
+            [pool(1, 2, FEE, one), pool(1, 2, FEE * 2, one + one / 50)]
+                .into_iter()
+                .collect();
+
+        let opportunities = find_arbitrage_cycles(
+            &pools,
+            Address::from_raw(U256::from(1)),
+            &[100_000],
+            ArbitrageConfig::default(),
+            (),
+        );
+        assert_eq!(opportunities.len(), 1);
+        assert_eq!(opportunities[0].route.splits[0].hops.len(), 2);
+    }
+
+    #[test]
+    fn test_no_arbitrage_at_equal_prices() {
+        let one = U256::one() << 128;
+        let pools: PoolMap<FullRangePool> = [
+            pool(1, 2, FEE, one),
+            pool(2, 3, FEE, one),
+            pool(1, 3, FEE, one),
+        ]
+        .into_iter()
+        .collect();
+
+        assert!(find_arbitrage_cycles(
+            &pools,
+            Address::from_raw(U256::from(1)),
+            &[1_000, 100_000],
+            ArbitrageConfig::default(),
+            (),
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_marginal_threshold() {
+        let one = U256::one() << 128;
+        let pools: PoolMap<FullRangePool> = [
+            pool(1, 2, FEE, one),
+            pool(2, 3, FEE, one),
+            pool(1, 3, FEE, one + one / 100),
+        ]
+        .into_iter()
+        .collect();
+
+        // the marginal profit is ~1.7%
+        let config = ArbitrageConfig {
+            min_marginal_profit_bps: 500,
+            ..ArbitrageConfig::default()
+        };
+        assert!(find_arbitrage_cycles(
+            &pools,
+            Address::from_raw(U256::from(1)),
+            &[100_000],
+            config,
+            ()
+        )
+        .is_empty());
+    }
+}
@@ -1,11 +1,26 @@
-use crate::math::swap::{compute_step, is_price_increasing, ComputeStepError};
-use crate::math::tick::{to_sqrt_ratio, MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+use crate::math::swap::ComputeStepError;
+use crate::math::tick::{
+    sqrt_ratio_to_tick, to_sqrt_ratio, MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO, MIN_TICK,
+};
 use crate::math::uint::U256;
-use crate::quoting::types::{NodeKey, Pool, Quote, QuoteParams, Tick};
+use crate::quoting::events::{ApplyEventError, ApplyPoolEvent, PoolEvent};
+use crate::quoting::pool_diff::{diff_sorted, FieldDiff, PoolDiff};
+use crate::quoting::pool_reads::{ticks_inventory, Inventory, PoolReadError, PoolReads};
+use crate::quoting::profile::{
+    NoProfiler, QuotePhase, QuoteProfile, QuoteProfiler, Timer, TimingProfiler,
+};
+use crate::quoting::resources::MaxResourcesEstimate;
+use crate::quoting::tick_provider::{swap_through_ticks, TickCursor, TickProviderQuoteError};
+use crate::quoting::types::{NodeKey, Pool, Quote, QuoteParams, SqrtRatioState, Tick};
 use crate::quoting::util::{
-    approximate_number_of_tick_spacings_crossed, construct_sorted_ticks, ConstructSortedTicksError,
+    approximate_number_of_tick_spacings_crossed, construct_sorted_ticks_with_report,
+    find_nearest_initialized_tick_index, ConstructSortedTicksError, ReconstructedTicks,
+    ReconstructionReport,
 };
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::convert::Infallible;
 use core::ops::{Add, AddAssign, Sub, SubAssign};
 use num_traits::Zero;
 
@@ -17,6 +32,12 @@ pub struct BasePoolResources {
     pub tick_spacings_crossed: u32,
 }
 
+crate::impl_flatten_resources!(BasePoolResources {
+    no_override_price_change,
+    initialized_ticks_crossed,
+    tick_spacings_crossed
+});
+
 impl AddAssign for BasePoolResources {
     fn add_assign(&mut self, rhs: Self) {
         self.no_override_price_change += rhs.no_override_price_change;
@@ -54,12 +75,27 @@ impl Sub for BasePoolResources {
 pub const FULL_RANGE_TICK_SPACING: u32 = 0;
 pub const MAX_TICK_SPACING: u32 = 698605;
 
+// The part of a swap that was computed before the quote was aborted
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct PartialBasePoolQuote {
+    pub consumed_amount: i128,
+    pub calculated_amount: u128,
+    pub fees_paid: u128,
+    // The state right before crossing the tick that would exceed the limit
+    pub state_after: BasePoolState,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum BasePoolQuoteError {
     InvalidToken,
     InvalidSqrtRatioLimit,
     InvalidTick(i32),
     FailedComputeSwapStep(ComputeStepError),
+    /// Crossing the tick with the index would overflow or underflow the liquidity, which means the ticks are
+    /// inconsistent.
+    InvalidLiquidityDelta(i32),
+    /// The swap would cross more initialized ticks than the maximum of the quote params.
+    MaxTicksCrossedExceeded(PartialBasePoolQuote),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -71,12 +107,19 @@ pub struct BasePoolState {
     pub active_tick_index: Option<usize>,
 }
 
+impl SqrtRatioState for BasePoolState {
+    fn sqrt_ratio(&self) -> U256 {
+        self.sqrt_ratio
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BasePool {
     key: NodeKey,
     state: BasePoolState,
-    sorted_ticks: Vec<Tick>,
+    // shared so that pools with identical ticks, e.g. clones or snapshots of the same pool, do not copy them
+    sorted_ticks: Arc<[Tick]>,
 }
 
 /// Errors that can occur when constructing a BasePool.
@@ -110,7 +153,396 @@ pub enum BasePoolError {
     ActiveLiquidityOverflow,
 }
 
+// Checks that the state and sorted ticks are consistent with each other and with the pool key
+fn validate(
+    key: &NodeKey,
+    state: &BasePoolState,
+    sorted_ticks: &[Tick],
+) -> Result<(), BasePoolError> {
+    // Validate token ordering
+    if !(key.token0 < key.token1) {
+        return Err(BasePoolError::TokenOrderInvalid);
+    }
+
+    // Validate tick spacing
+    if key.config.tick_spacing.raw() > MAX_TICK_SPACING {
+        return Err(BasePoolError::TickSpacingTooLarge);
+    }
+
+    if key.config.tick_spacing.raw().is_zero() {
+        return Err(BasePoolError::TickSpacingCannotBeZero);
+    }
+
+    // Check ticks are sorted in linear time
+    let mut last_tick: Option<i32> = None;
+    let mut total_liquidity: u128 = 0;
+    let mut active_liquidity: u128 = 0;
+    let spacing_i32 = key.config.tick_spacing.raw() as i32;
+
+    for (i, tick) in sorted_ticks.iter().enumerate() {
+        // Verify ticks are sorted
+        if let Some(last) = last_tick {
+            if !(tick.index > last) {
+                return Err(BasePoolError::TicksNotSorted);
+            }
+        };
+
+        // Verify ticks are multiples of tick_spacing
+        if !(tick.index % spacing_i32).is_zero() {
+            return Err(BasePoolError::TickNotMultipleOfSpacing);
+        }
+
+        last_tick = Some(tick.index);
+
+        // Calculate total liquidity
+        total_liquidity = if tick.liquidity_delta < 0 {
+            total_liquidity.checked_sub(tick.liquidity_delta.unsigned_abs())
+        } else {
+            total_liquidity.checked_add(tick.liquidity_delta.unsigned_abs())
+        }
+        .ok_or(BasePoolError::ActiveLiquidityOverflow)?;
+
+        // Calculate active liquidity
+        if let Some(active_index) = state.active_tick_index {
+            if i <= active_index {
+                active_liquidity = if tick.liquidity_delta > 0 {
+                    active_liquidity.checked_add(tick.liquidity_delta.unsigned_abs())
+                } else {
+                    active_liquidity.checked_sub(tick.liquidity_delta.unsigned_abs())
+                }
+                .ok_or(BasePoolError::ActiveLiquidityOverflow)?;
+            }
+        }
+    }
+
+    // Verify total liquidity is zero
+    if !total_liquidity.is_zero() {
+        return Err(BasePoolError::TotalLiquidityNotZero);
+    }
+
+    // Verify active liquidity matches state liquidity
+    if active_liquidity != state.liquidity {
+        return Err(BasePoolError::ActiveLiquidityMismatch);
+    }
+
+    // Validate sqrt ratio against active or first tick
+    if let Some(active) = state.active_tick_index {
+        let tick = sorted_ticks
+            .get(active)
+            .ok_or(BasePoolError::ActiveTickIndexOutOfBounds)?;
+
+        let active_tick_sqrt_ratio =
+            to_sqrt_ratio(tick.index).ok_or(BasePoolError::InvalidTickIndex(tick.index))?;
+
+        if !(active_tick_sqrt_ratio <= state.sqrt_ratio) {
+            return Err(BasePoolError::ActiveTickSqrtRatioInvalid);
+        }
+    } else {
+        if let Some(first) = sorted_ticks.first() {
+            let first_tick_sqrt_ratio =
+                to_sqrt_ratio(first.index).ok_or(BasePoolError::InvalidTickIndex(first.index))?;
+
+            if !(state.sqrt_ratio <= first_tick_sqrt_ratio) {
+                return Err(BasePoolError::SqrtRatioTooHighWithNoActiveTick);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// How strictly the constructor treats inconsistent data
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConstructionMode {
+    /// Rejects any inconsistency between the state and the ticks.
+    #[default]
+    Strict,
+    /// Repairs common artifacts of indexed data before validating, and reports every repair.
+    Lenient,
+}
+
+// A change made to the state or the ticks of a pool constructed in lenient mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolRepair {
+    /// The ticks were not sorted by index.
+    SortedTicks,
+    /// A tick that was not a multiple of the tick spacing was moved to a multiple in the direction of the alignment.
+    MovedOffSpacingTick { from: i32, to: i32 },
+    /// Multiple ticks with the same index were merged into one, which is removed if the deltas cancel out.
+    MergedDuplicateTicks { index: i32, count: usize },
+    /// The active tick index did not match the sqrt ratio.
+    AdjustedActiveTickIndex {
+        from: Option<usize>,
+        to: Option<usize>,
+    },
+    /// A delta was added at the lowest or highest usable tick so that the liquidity deltas up to the active tick sum
+    /// to the liquidity and all liquidity deltas sum to zero, e.g. because ticks outside the fetched range are missing.
+    AddedBoundaryLiquidity { index: i32, liquidity_delta: i128 },
+}
+
+// The multiple of the tick spacing that a tick that is not a multiple is moved to
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TickAlignment {
+    /// Moves ticks away from the positions they bound, i.e. down for positive liquidity deltas and up for negative
+    /// ones, so that positions only get wider.
+    #[default]
+    Outward,
+    /// Moves ticks into the positions they bound, so that positions only get narrower.
+    Inward,
+    Down,
+    Up,
+    /// Moves ticks to the nearest multiple, and outwards if both are equally near.
+    Nearest,
+}
+
+impl TickAlignment {
+    fn align(self, tick: &Tick, spacing: i32) -> i32 {
+        let down = tick.index.div_euclid(spacing) * spacing;
+        let up = down + spacing;
+        let (outward, inward) = if tick.liquidity_delta > 0 {
+            (down, up)
+        } else {
+            (up, down)
+        };
+
+        match self {
+            TickAlignment::Outward => outward,
+            TickAlignment::Inward => inward,
+            TickAlignment::Down => down,
+            TickAlignment::Up => up,
+            TickAlignment::Nearest => match (tick.index - down).cmp(&(up - tick.index)) {
+                Ordering::Less => down,
+                Ordering::Greater => up,
+                Ordering::Equal => outward,
+            },
+        }
+    }
+}
+
+// Sorts the ticks, moves the ticks that are not multiples of the spacing and merges the ticks with the same index
+fn fold_ticks(
+    mut ticks: Vec<Tick>,
+    spacing: i32,
+    alignment: TickAlignment,
+    repairs: &mut Vec<PoolRepair>,
+) -> Vec<Tick> {
+    let (min_tick, max_tick) = (
+        (MIN_TICK / spacing) * spacing,
+        (MAX_TICK / spacing) * spacing,
+    );
+
+    if ticks.windows(2).any(|pair| pair[0].index > pair[1].index) {
+        ticks.sort_by_key(|tick| tick.index);
+        repairs.push(PoolRepair::SortedTicks);
+    }
+
+    let mut moved = false;
+    for tick in ticks.iter_mut() {
+        if !(tick.index % spacing).is_zero() {
+            let to = alignment.align(tick, spacing).clamp(min_tick, max_tick);
+            repairs.push(PoolRepair::MovedOffSpacingTick {
+                from: tick.index,
+                to,
+            });
+            tick.index = to;
+            moved = true;
+        }
+    }
+    if moved {
+        ticks.sort_by_key(|tick| tick.index);
+    }
+
+    let mut merged: Vec<(Tick, usize)> = Vec::with_capacity(ticks.len());
+    for tick in ticks {
+        match merged.last_mut() {
+            Some((last, count)) if last.index == tick.index => {
+                // duplicates whose deltas overflow are left for the validation to reject
+                match last.liquidity_delta.checked_add(tick.liquidity_delta) {
+                    Some(liquidity_delta) => {
+                        last.liquidity_delta = liquidity_delta;
+                        *count += 1;
+                    }
+                    None => merged.push((tick, 1)),
+                }
+            }
+            _ => merged.push((tick, 1)),
+        }
+    }
+    merged
+        .into_iter()
+        .filter_map(|(tick, count)| {
+            if count > 1 {
+                repairs.push(PoolRepair::MergedDuplicateTicks {
+                    index: tick.index,
+                    count,
+                });
+                if tick.liquidity_delta.is_zero() {
+                    return None;
+                }
+            }
+            Some(tick)
+        })
+        .collect()
+}
+
+/// Folds the liquidity deltas of ticks that are not multiples of the tick spacing into multiples of it, e.g. for ticks
+/// that an indexer emitted under an older config of the pool.
+///
+/// Each misaligned tick is moved in the direction of the alignment and merged with any tick already at its new index.
+/// Returns the sorted ticks together with the repairs that were made, which are empty if the ticks were already valid, or
+/// [`BasePoolError::ActiveLiquidityOverflow`] if the merged liquidity delta of an index overflows.
+pub fn fold_off_spacing_ticks(
+    ticks: Vec<Tick>,
+    tick_spacing: u32,
+    alignment: TickAlignment,
+) -> Result<(Vec<Tick>, Vec<PoolRepair>), BasePoolError> {
+    if tick_spacing.is_zero() {
+        return Err(BasePoolError::TickSpacingCannotBeZero);
+    }
+    if tick_spacing > MAX_TICK_SPACING {
+        return Err(BasePoolError::TickSpacingTooLarge);
+    }
+
+    let mut repairs = Vec::new();
+    let ticks = fold_ticks(ticks, tick_spacing as i32, alignment, &mut repairs);
+    if ticks.windows(2).any(|pair| pair[0].index == pair[1].index) {
+        return Err(BasePoolError::ActiveLiquidityOverflow);
+    }
+    Ok((ticks, repairs))
+}
+
+// Adds the liquidity delta to the tick with the given index, inserting the tick if it does not exist. Returns false
+// without changing the ticks if the delta of the tick would overflow.
+fn add_boundary_liquidity(
+    ticks: &mut Vec<Tick>,
+    index: i32,
+    liquidity_delta: i128,
+    repairs: &mut Vec<PoolRepair>,
+) -> bool {
+    if liquidity_delta.is_zero() {
+        return true;
+    }
+    match ticks.binary_search_by_key(&index, |tick| tick.index) {
+        Ok(position) => match ticks[position].liquidity_delta.checked_add(liquidity_delta) {
+            Some(sum) => ticks[position].liquidity_delta = sum,
+            None => return false,
+        },
+        Err(position) => ticks.insert(
+            position,
+            Tick {
+                index,
+                liquidity_delta,
+            },
+        ),
+    }
+    repairs.push(PoolRepair::AddedBoundaryLiquidity {
+        index,
+        liquidity_delta,
+    });
+    true
+}
+
+// Returns the index of the last tick at or below the sqrt ratio
+fn active_tick_index(sorted_ticks: &[Tick], sqrt_ratio: U256) -> Option<usize> {
+    sorted_ticks
+        .iter()
+        .rposition(|tick| to_sqrt_ratio(tick.index).is_some_and(|ratio| ratio <= sqrt_ratio))
+}
+
+// Repairs the ticks and the active tick index so that they are consistent with the sqrt ratio and the liquidity of the
+// state. Anything that cannot be repaired is left for the validation to reject.
+fn repair(
+    key: &NodeKey,
+    mut state: BasePoolState,
+    ticks: Vec<Tick>,
+    alignment: TickAlignment,
+) -> (BasePoolState, Vec<Tick>, Vec<PoolRepair>) {
+    let mut repairs = Vec::new();
+    let spacing = key.config.tick_spacing.raw();
+    if spacing.is_zero() || spacing > MAX_TICK_SPACING {
+        return (state, ticks, repairs);
+    }
+    let spacing = spacing as i32;
+    let (min_tick, max_tick) = (
+        (MIN_TICK / spacing) * spacing,
+        (MAX_TICK / spacing) * spacing,
+    );
+
+    let mut ticks = fold_ticks(ticks, spacing, alignment, &mut repairs);
+
+    // the deltas up to the price must sum to the liquidity, and the rest must cancel them out
+    let active_index = active_tick_index(&ticks, state.sqrt_ratio);
+    let active_sum = active_index.map_or(Some(0i128), |index| {
+        ticks[..=index]
+            .iter()
+            .try_fold(0i128, |sum, tick| sum.checked_add(tick.liquidity_delta))
+    });
+    let total_sum = ticks
+        .iter()
+        .try_fold(0i128, |sum, tick| sum.checked_add(tick.liquidity_delta));
+    if let (Some(active_sum), Some(total_sum), Ok(liquidity)) =
+        (active_sum, total_sum, i128::try_from(state.liquidity))
+    {
+        if let Some(missing) = liquidity.checked_sub(active_sum) {
+            // the lower boundary is only added if it does not overflow, otherwise the validation rejects the pool
+            if to_sqrt_ratio(min_tick).is_some_and(|ratio| ratio <= state.sqrt_ratio)
+                && add_boundary_liquidity(&mut ticks, min_tick, missing, &mut repairs)
+            {
+                if let Some(excess) = total_sum
+                    .checked_add(missing)
+                    .and_then(|excess| excess.checked_neg())
+                {
+                    add_boundary_liquidity(&mut ticks, max_tick, excess, &mut repairs);
+                }
+            }
+        }
+    }
+
+    let active_index = active_tick_index(&ticks, state.sqrt_ratio);
+    if active_index != state.active_tick_index {
+        repairs.push(PoolRepair::AdjustedActiveTickIndex {
+            from: state.active_tick_index,
+            to: active_index,
+        });
+        state.active_tick_index = active_index;
+    }
+
+    (state, ticks, repairs)
+}
+
 impl BasePool {
+    /// Creates a BasePool in the given construction mode, returning the pool and the repairs made to the data.
+    ///
+    /// Strict mode is the same as [`BasePool::new`] and never repairs anything. Lenient mode sorts the ticks, moves
+    /// ticks that are not multiples of the tick spacing outwards, merges duplicate ticks, adds the liquidity of
+    /// missing ticks at the boundaries and recomputes the active tick index from the sqrt ratio before validating. The
+    /// sqrt ratio and the liquidity of the state are assumed to be correct.
+    pub fn new_with_mode(
+        key: NodeKey,
+        state: BasePoolState,
+        sorted_ticks: Vec<Tick>,
+        mode: ConstructionMode,
+    ) -> Result<(Self, Vec<PoolRepair>), BasePoolError> {
+        match mode {
+            ConstructionMode::Strict => Ok((Self::new(key, state, sorted_ticks)?, Vec::new())),
+            ConstructionMode::Lenient => {
+                Self::new_lenient(key, state, sorted_ticks, TickAlignment::default())
+            }
+        }
+    }
+
+    /// Same as lenient [`BasePool::new_with_mode`], but moves the ticks that are not multiples of the tick spacing in
+    /// the direction of the alignment instead of outwards.
+    pub fn new_lenient(
+        key: NodeKey,
+        state: BasePoolState,
+        ticks: Vec<Tick>,
+        alignment: TickAlignment,
+    ) -> Result<(Self, Vec<PoolRepair>), BasePoolError> {
+        let (state, sorted_ticks, repairs) = repair(&key, state, ticks, alignment);
+        Ok((Self::new(key, state, sorted_ticks)?, repairs))
+    }
+
     /// Creates a BasePool from partial tick data retrieved from a quote data fetcher lens contract.
     ///
     /// This helper constructor takes partial tick data along with min/max tick boundaries and constructs
@@ -138,12 +570,38 @@ impl BasePool {
         liquidity: u128,
         current_tick: i32,
     ) -> Result<Self, BasePoolError> {
+        Self::from_partial_data_with_report(
+            key,
+            sqrt_ratio,
+            partial_ticks,
+            min_tick_searched,
+            max_tick_searched,
+            liquidity,
+            current_tick,
+        )
+        .map(|(pool, _)| pool)
+    }
+
+    /// Same as [`BasePool::from_partial_data`], but also returns the report of the ticks that were synthesized or
+    /// adjusted to make the partial data consistent.
+    pub fn from_partial_data_with_report(
+        key: NodeKey,
+        sqrt_ratio: U256,
+        partial_ticks: Vec<Tick>,
+        min_tick_searched: i32,
+        max_tick_searched: i32,
+        liquidity: u128,
+        current_tick: i32,
+    ) -> Result<(Self, ReconstructionReport), BasePoolError> {
         // Use the construct_sorted_ticks function to get valid sorted ticks
-        let tick_spacing = key.config.tick_spacing;
+        let tick_spacing = key.config.tick_spacing.raw();
         let spacing_i32 = tick_spacing as i32;
 
         // Get sorted ticks using the utility function
-        let sorted_ticks = construct_sorted_ticks(
+        let ReconstructedTicks {
+            sorted_ticks,
+            report,
+        } = construct_sorted_ticks_with_report(
             partial_ticks,
             min_tick_searched,
             max_tick_searched,
@@ -178,7 +636,7 @@ impl BasePool {
         };
 
         // Call the existing constructor with the prepared parameters
-        Self::new(key, state, sorted_ticks)
+        Ok((Self::new(key, state, sorted_ticks)?, report))
     }
 
     pub fn new(
@@ -186,104 +644,180 @@ impl BasePool {
         state: BasePoolState,
         sorted_ticks: Vec<Tick>,
     ) -> Result<Self, BasePoolError> {
-        // Validate token ordering
-        if !(key.token0 < key.token1) {
-            return Err(BasePoolError::TokenOrderInvalid);
-        }
+        Self::new_with_shared_ticks(key, state, sorted_ticks.into())
+    }
 
-        // Validate tick spacing
-        if key.config.tick_spacing > MAX_TICK_SPACING {
-            return Err(BasePoolError::TickSpacingTooLarge);
-        }
+    /// Creates a BasePool that shares the given sorted ticks with any other pools constructed from them.
+    pub fn new_with_shared_ticks(
+        key: NodeKey,
+        state: BasePoolState,
+        sorted_ticks: Arc<[Tick]>,
+    ) -> Result<Self, BasePoolError> {
+        validate(&key, &state, &sorted_ticks)?;
 
-        if key.config.tick_spacing.is_zero() {
-            return Err(BasePoolError::TickSpacingCannotBeZero);
-        }
+        Ok(Self {
+            key,
+            state,
+            sorted_ticks,
+        })
+    }
 
-        // Check ticks are sorted in linear time
-        let mut last_tick: Option<i32> = None;
-        let mut total_liquidity: u128 = 0;
-        let mut active_liquidity: u128 = 0;
-        let spacing_i32 = key.config.tick_spacing as i32;
+    pub fn get_sorted_ticks(&self) -> &[Tick] {
+        &self.sorted_ticks
+    }
 
-        for (i, tick) in sorted_ticks.iter().enumerate() {
-            // Verify ticks are sorted
-            if let Some(last) = last_tick {
-                if !(tick.index > last) {
-                    return Err(BasePoolError::TicksNotSorted);
-                }
-            };
+    // Returns the shared sorted ticks, which can be used to construct other pools without copying the ticks
+    pub fn get_shared_sorted_ticks(&self) -> &Arc<[Tick]> {
+        &self.sorted_ticks
+    }
 
-            // Verify ticks are multiples of tick_spacing
-            if !(tick.index % spacing_i32).is_zero() {
-                return Err(BasePoolError::TickNotMultipleOfSpacing);
-            }
+    // Reports the fields that differ between this pool and the other pool, including added, removed and changed ticks
+    pub fn diff(&self, other: &Self) -> PoolDiff {
+        self.as_view().diff(&other.as_view())
+    }
 
-            last_tick = Some(tick.index);
+    // Returns the initialized tick at or below the current price, if any
+    pub fn active_tick(&self) -> Option<&Tick> {
+        self.as_view().active_tick()
+    }
 
-            // Calculate total liquidity
-            total_liquidity = if tick.liquidity_delta < 0 {
-                total_liquidity.checked_sub(tick.liquidity_delta.unsigned_abs())
-            } else {
-                total_liquidity.checked_add(tick.liquidity_delta.unsigned_abs())
-            }
-            .ok_or(BasePoolError::ActiveLiquidityOverflow)?;
-
-            // Calculate active liquidity
-            if let Some(active_index) = state.active_tick_index {
-                if i <= active_index {
-                    active_liquidity = if tick.liquidity_delta > 0 {
-                        active_liquidity.checked_add(tick.liquidity_delta.unsigned_abs())
-                    } else {
-                        active_liquidity.checked_sub(tick.liquidity_delta.unsigned_abs())
-                    }
-                    .ok_or(BasePoolError::ActiveLiquidityOverflow)?;
-                }
-            }
-        }
+    // Returns the next initialized tick that a swap in the given direction would cross
+    pub fn next_initialized_tick(&self, is_increasing: bool) -> Option<&Tick> {
+        self.as_view().next_initialized_tick(is_increasing)
+    }
 
-        // Verify total liquidity is zero
-        if !total_liquidity.is_zero() {
-            return Err(BasePoolError::TotalLiquidityNotZero);
-        }
+    // Returns the number of ticks between the current price and the next initialized tick in the given direction
+    pub fn distance_to_next_initialized_tick(&self, is_increasing: bool) -> Option<u32> {
+        self.as_view()
+            .distance_to_next_initialized_tick(is_increasing)
+    }
 
-        // Verify active liquidity matches state liquidity
-        if active_liquidity != state.liquidity {
-            return Err(BasePoolError::ActiveLiquidityMismatch);
-        }
+    // Iterates the initialized ticks from the tick in the given direction with the liquidity after crossing each tick
+    pub fn iter_ticks_from(&self, tick: i32, is_increasing: bool) -> TickLiquidityIter<'_> {
+        self.as_view().iter_ticks_from(tick, is_increasing)
+    }
+
+    // Returns the least liquidity at any price between the lower and upper tick, e.g. around the current price
+    pub fn liquidity_between(&self, lower_tick: i32, upper_tick: i32) -> u128 {
+        self.as_view().liquidity_between(lower_tick, upper_tick)
+    }
+
+    // Returns whether there is liquidity at any price between the lower and upper tick
+    pub fn has_liquidity_in_range(&self, lower_tick: i32, upper_tick: i32) -> bool {
+        self.as_view()
+            .has_liquidity_in_range(lower_tick, upper_tick)
+    }
 
-        // Validate sqrt ratio against active or first tick
-        if let Some(active) = state.active_tick_index {
-            let tick = sorted_ticks
-                .get(active)
-                .ok_or(BasePoolError::ActiveTickIndexOutOfBounds)?;
+    // Returns a view of the pool that borrows its ticks
+    pub fn as_view(&self) -> BasePoolView<'_> {
+        BasePoolView {
+            key: self.key,
+            state: self.state,
+            sorted_ticks: &self.sorted_ticks,
+        }
+    }
+}
 
-            let active_tick_sqrt_ratio =
-                to_sqrt_ratio(tick.index).ok_or(BasePoolError::InvalidTickIndex(tick.index))?;
+impl ApplyPoolEvent for BasePool {
+    fn apply_event(&mut self, event: &PoolEvent) -> Result<(), ApplyEventError> {
+        match *event {
+            PoolEvent::Swapped {
+                sqrt_ratio_after,
+                tick_after,
+                liquidity_after,
+            } => {
+                let state = BasePoolState {
+                    sqrt_ratio: sqrt_ratio_after,
+                    liquidity: liquidity_after,
+                    active_tick_index: find_nearest_initialized_tick_index(
+                        &self.sorted_ticks,
+                        tick_after,
+                    ),
+                };
 
-            if !(active_tick_sqrt_ratio <= state.sqrt_ratio) {
-                return Err(BasePoolError::ActiveTickSqrtRatioInvalid);
+                *self = Self::new_with_shared_ticks(self.key, state, self.sorted_ticks.clone())
+                    .map_err(|_| ApplyEventError::InvalidStateAfter)?;
             }
-        } else {
-            if let Some(first) = sorted_ticks.first() {
-                let first_tick_sqrt_ratio = to_sqrt_ratio(first.index)
-                    .ok_or(BasePoolError::InvalidTickIndex(first.index))?;
+            PoolEvent::PositionUpdated {
+                tick_lower,
+                tick_upper,
+                liquidity_delta,
+            } => {
+                if liquidity_delta.is_zero() {
+                    return Ok(());
+                }
+
+                let spacing = self.key.config.tick_spacing.raw() as i32;
+                if tick_lower >= tick_upper
+                    || !(tick_lower % spacing).is_zero()
+                    || !(tick_upper % spacing).is_zero()
+                {
+                    return Err(ApplyEventError::InvalidPositionBounds);
+                }
+
+                let (sqrt_ratio_lower, sqrt_ratio_upper) =
+                    match (to_sqrt_ratio(tick_lower), to_sqrt_ratio(tick_upper)) {
+                        (Some(lower), Some(upper)) => (lower, upper),
+                        _ => return Err(ApplyEventError::InvalidPositionBounds),
+                    };
+
+                let mut sorted_ticks = self.sorted_ticks.to_vec();
+                for (index, delta) in [
+                    (tick_lower, liquidity_delta),
+                    (tick_upper, -liquidity_delta),
+                ] {
+                    match sorted_ticks.binary_search_by_key(&index, |tick| tick.index) {
+                        Ok(i) => {
+                            let tick = &mut sorted_ticks[i];
+                            tick.liquidity_delta = tick
+                                .liquidity_delta
+                                .checked_add(delta)
+                                .ok_or(ApplyEventError::LiquidityOverflow)?;
+                            if tick.liquidity_delta.is_zero() {
+                                sorted_ticks.remove(i);
+                            }
+                        }
+                        Err(i) => sorted_ticks.insert(
+                            i,
+                            Tick {
+                                index,
+                                liquidity_delta: delta,
+                            },
+                        ),
+                    }
+                }
 
-                if !(state.sqrt_ratio <= first_tick_sqrt_ratio) {
-                    return Err(BasePoolError::SqrtRatioTooHighWithNoActiveTick);
+                let mut liquidity = self.state.liquidity;
+                if sqrt_ratio_lower <= self.state.sqrt_ratio
+                    && self.state.sqrt_ratio < sqrt_ratio_upper
+                {
+                    liquidity = liquidity
+                        .checked_add_signed(liquidity_delta)
+                        .ok_or(ApplyEventError::LiquidityOverflow)?;
                 }
+
+                // the active tick is the last tick with a sqrt ratio at or below the current sqrt ratio
+                let active_tick_index = sorted_ticks
+                    .partition_point(|tick| {
+                        to_sqrt_ratio(tick.index)
+                            .is_some_and(|sqrt_ratio| sqrt_ratio <= self.state.sqrt_ratio)
+                    })
+                    .checked_sub(1);
+
+                *self = Self::new(
+                    self.key,
+                    BasePoolState {
+                        sqrt_ratio: self.state.sqrt_ratio,
+                        liquidity,
+                        active_tick_index,
+                    },
+                    sorted_ticks,
+                )
+                .map_err(|_| ApplyEventError::InvalidStateAfter)?;
             }
         }
 
-        Ok(Self {
-            key,
-            state,
-            sorted_ticks,
-        })
-    }
-
-    pub fn get_sorted_ticks(&self) -> &Vec<Tick> {
-        &self.sorted_ticks
+        Ok(())
     }
 }
 
@@ -292,20 +826,16 @@ impl BasePool {
 mod from_partial_data_tests {
     use super::*;
     use crate::math::tick::{MAX_TICK, MIN_TICK};
-    use crate::quoting::types::Config;
+    use crate::quoting::types::{Address, Config};
     use alloc::vec;
 
     // Constants for testing
-    const TOKEN0: U256 = U256([1, 0, 0, 0]);
-    const TOKEN1: U256 = U256([2, 0, 0, 0]);
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
 
     // Helper function to create a test config
     fn create_test_config(tick_spacing: u32) -> Config {
-        Config {
-            tick_spacing,
-            fee: 0,
-            extension: U256::zero(),
-        }
+        Config::from_raw_unchecked(0, tick_spacing, U256::zero())
     }
 
     #[test]
@@ -346,6 +876,46 @@ mod from_partial_data_tests {
         assert_eq!(ticks[1].liquidity_delta, -(liquidity as i128));
     }
 
+    #[test]
+    fn test_from_partial_data_with_report() {
+        let key = NodeKey {
+            token0: TOKEN0,
+            token1: TOKEN1,
+            config: create_test_config(10),
+        };
+
+        let (pool, report) = BasePool::from_partial_data_with_report(
+            key,
+            to_sqrt_ratio(0).unwrap(),
+            vec![Tick {
+                index: 100,
+                liquidity_delta: -400,
+            }],
+            -1005,
+            1005,
+            1000,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(report.partial_tick_count, 1);
+        assert_eq!(
+            report.synthesized_ticks,
+            vec![
+                Tick {
+                    index: -1010,
+                    liquidity_delta: 1000,
+                },
+                Tick {
+                    index: 1010,
+                    liquidity_delta: -600,
+                },
+            ]
+        );
+        assert!(report.adjusted_ticks.is_empty());
+        assert_eq!(pool.get_sorted_ticks().len(), 3);
+    }
+
     #[test]
     fn test_from_partial_data_with_partial_ticks() {
         // Test creating a pool with partial ticks
@@ -470,192 +1040,377 @@ impl Pool for BasePool {
         &self,
         params: QuoteParams<Self::State, Self::Meta>,
     ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
-        let amount = params.token_amount.amount;
-        let token = params.token_amount.token;
-        let is_token1 = token == self.key.token1;
+        self.as_view().quote(params)
+    }
 
-        if !is_token1 && token != self.key.token0 {
-            return Err(BasePoolQuoteError::InvalidToken);
-        }
+    // Checks if the pool has any liquidity.
+    fn has_liquidity(&self) -> bool {
+        self.as_view().has_liquidity()
+    }
 
-        let state = if let Some(override_state) = params.override_state {
-            override_state
-        } else {
-            self.state.clone()
-        };
+    fn max_tick_with_liquidity(&self) -> Option<i32> {
+        self.as_view().max_tick_with_liquidity()
+    }
 
-        if amount == 0 {
-            return Ok(Quote {
-                is_price_increasing: is_token1,
-                consumed_amount: 0,
-                calculated_amount: 0,
-                execution_resources: Default::default(),
-                state_after: state,
-                fees_paid: 0,
-            });
-        }
+    fn min_tick_with_liquidity(&self) -> Option<i32> {
+        self.as_view().min_tick_with_liquidity()
+    }
 
-        let is_increasing = is_price_increasing(amount, is_token1);
+    fn is_path_dependent(&self) -> bool {
+        false
+    }
+}
 
-        let mut sqrt_ratio = state.sqrt_ratio;
-        let mut liquidity = state.liquidity;
-        let mut active_tick_index = state.active_tick_index;
+/// A read-only view of a base pool that borrows its sorted ticks instead of owning them.
+///
+/// Services that keep the ticks of pools in their own storage can quote them without copying the ticks into a
+/// BasePool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BasePoolView<'a> {
+    key: NodeKey,
+    state: BasePoolState,
+    sorted_ticks: &'a [Tick],
+}
 
-        let sqrt_ratio_limit = if let Some(limit) = params.sqrt_ratio_limit {
-            if is_increasing && limit < sqrt_ratio {
-                return Err(BasePoolQuoteError::InvalidSqrtRatioLimit);
-            }
-            if !is_increasing && limit > sqrt_ratio {
-                return Err(BasePoolQuoteError::InvalidSqrtRatioLimit);
-            }
-            if limit < MIN_SQRT_RATIO {
-                return Err(BasePoolQuoteError::InvalidSqrtRatioLimit);
-            }
-            if limit > MAX_SQRT_RATIO {
-                return Err(BasePoolQuoteError::InvalidSqrtRatioLimit);
-            }
-            limit
-        } else {
-            if is_increasing {
-                MAX_SQRT_RATIO
-            } else {
-                MIN_SQRT_RATIO
-            }
-        };
+impl<'a> BasePoolView<'a> {
+    /// Creates a view with the same validation as BasePool::new.
+    pub fn new(
+        key: NodeKey,
+        state: BasePoolState,
+        sorted_ticks: &'a [Tick],
+    ) -> Result<Self, BasePoolError> {
+        validate(&key, &state, sorted_ticks)?;
 
-        let mut calculated_amount: u128 = 0;
-        let mut fees_paid: u128 = 0;
-        let mut initialized_ticks_crossed: u32 = 0;
-        let mut amount_remaining = amount;
-
-        let starting_sqrt_ratio = sqrt_ratio;
-
-        while amount_remaining != 0 && sqrt_ratio != sqrt_ratio_limit {
-            let next_initialized_tick: Option<(usize, &Tick, U256)> = if is_increasing {
-                if let Some(index) = active_tick_index {
-                    if let Some(next) = self.sorted_ticks.get(index + 1) {
-                        Some((
-                            index + 1,
-                            next,
-                            to_sqrt_ratio(next.index)
-                                .ok_or(BasePoolQuoteError::InvalidTick(next.index))?,
-                        ))
-                    } else {
-                        None
-                    }
-                } else {
-                    if let Some(next) = self.sorted_ticks.first() {
-                        Some((
-                            0,
-                            next,
-                            to_sqrt_ratio(next.index)
-                                .ok_or(BasePoolQuoteError::InvalidTick(next.index))?,
-                        ))
-                    } else {
-                        None
-                    }
-                }
-            } else {
-                if let Some(index) = active_tick_index {
-                    if let Some(tick) = self.sorted_ticks.get(index) {
-                        Some((
-                            index,
-                            tick,
-                            to_sqrt_ratio(tick.index)
-                                .ok_or(BasePoolQuoteError::InvalidTick(tick.index))?,
-                        ))
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            };
+        Ok(Self {
+            key,
+            state,
+            sorted_ticks,
+        })
+    }
 
-            let step_sqrt_ratio_limit =
-                next_initialized_tick.map_or(sqrt_ratio_limit, |(_, _, next_ratio)| {
-                    if (next_ratio < sqrt_ratio_limit) == is_increasing {
-                        next_ratio
-                    } else {
-                        sqrt_ratio_limit
-                    }
-                });
+    // Creates a view of a pool that was already validated, e.g. on construction of a pool that owns the ticks
+    pub(crate) fn new_unchecked(
+        key: NodeKey,
+        state: BasePoolState,
+        sorted_ticks: &'a [Tick],
+    ) -> Self {
+        Self {
+            key,
+            state,
+            sorted_ticks,
+        }
+    }
 
-            let step = compute_step(
-                sqrt_ratio,
-                liquidity,
-                step_sqrt_ratio_limit,
-                amount_remaining,
-                is_token1,
-                self.key.config.fee,
-            )
-            .map_err(BasePoolQuoteError::FailedComputeSwapStep)?;
-
-            amount_remaining -= step.consumed_amount;
-            calculated_amount += step.calculated_amount;
-            fees_paid += step.fee_amount;
-            sqrt_ratio = step.sqrt_ratio_next;
-
-            if let Some((index, next_tick, tick_sqrt_ratio)) = next_initialized_tick {
-                if sqrt_ratio == tick_sqrt_ratio {
-                    active_tick_index = if is_increasing {
-                        Some(index)
-                    } else if !index.is_zero() {
-                        Some(index - 1)
-                    } else {
-                        None
-                    };
+    pub fn get_sorted_ticks(&self) -> &'a [Tick] {
+        self.sorted_ticks
+    }
 
-                    initialized_ticks_crossed += 1;
+    // Returns the initialized tick at or below the current price, if any
+    pub fn active_tick(&self) -> Option<&'a Tick> {
+        self.state
+            .active_tick_index
+            .and_then(|index| self.sorted_ticks.get(index))
+    }
 
-                    if (next_tick.liquidity_delta.signum() == 1) == is_increasing {
-                        liquidity = liquidity + next_tick.liquidity_delta.unsigned_abs();
-                    } else {
-                        liquidity = liquidity - next_tick.liquidity_delta.unsigned_abs();
-                    };
-                }
-            } else {
-                active_tick_index = if is_increasing {
-                    self.sorted_ticks.len().checked_sub(1)
-                } else {
-                    None
-                };
-            }
+    // Returns the next initialized tick that a swap in the given direction would cross, which is the active tick when
+    // the price is decreasing
+    pub fn next_initialized_tick(&self, is_increasing: bool) -> Option<&'a Tick> {
+        if is_increasing {
+            self.sorted_ticks
+                .get(self.state.active_tick_index.map_or(0, |index| index + 1))
+        } else {
+            self.active_tick()
+        }
+    }
+
+    // Returns the number of ticks between the current price and the next initialized tick in the given direction
+    pub fn distance_to_next_initialized_tick(&self, is_increasing: bool) -> Option<u32> {
+        self.next_initialized_tick(is_increasing).map(|tick| {
+            tick.index
+                .abs_diff(sqrt_ratio_to_tick(self.state.sqrt_ratio))
+        })
+    }
+
+    // Iterates the initialized ticks at or above the tick when increasing, or at or below it when decreasing, with the
+    // liquidity that is active after crossing each tick, summed from the sorted ticks independent of the current price
+    pub fn iter_ticks_from(&self, tick: i32, is_increasing: bool) -> TickLiquidityIter<'a> {
+        let position = if is_increasing {
+            self.sorted_ticks.partition_point(|t| t.index < tick)
+        } else {
+            self.sorted_ticks.partition_point(|t| t.index <= tick)
+        };
+
+        TickLiquidityIter {
+            sorted_ticks: self.sorted_ticks,
+            position,
+            liquidity: self.sorted_ticks[..position]
+                .iter()
+                .map(|t| t.liquidity_delta)
+                .sum(),
+            is_increasing,
+        }
+    }
+
+    // Returns the least liquidity at any price between the lower and upper tick, or 0 if the range is empty
+    pub fn liquidity_between(&self, lower_tick: i32, upper_tick: i32) -> u128 {
+        self.liquidity_extremes_between(lower_tick, upper_tick)
+            .map_or(0, |(least, _)| least)
+    }
+
+    // Returns whether there is liquidity at any price between the lower and upper tick
+    pub fn has_liquidity_in_range(&self, lower_tick: i32, upper_tick: i32) -> bool {
+        self.liquidity_extremes_between(lower_tick, upper_tick)
+            .is_some_and(|(_, most)| most > 0)
+    }
+
+    // Computes the least and most liquidity at the prices in [lower_tick, upper_tick) by summing the liquidity deltas of
+    // the sorted ticks, independent of the current price
+    fn liquidity_extremes_between(&self, lower_tick: i32, upper_tick: i32) -> Option<(u128, u128)> {
+        if lower_tick >= upper_tick {
+            return None;
+        }
+
+        let below = self
+            .sorted_ticks
+            .partition_point(|tick| tick.index <= lower_tick);
+        let mut liquidity: i128 = self.sorted_ticks[..below]
+            .iter()
+            .map(|tick| tick.liquidity_delta)
+            .sum();
+        let (mut least, mut most) = (liquidity, liquidity);
+
+        for tick in self.sorted_ticks[below..]
+            .iter()
+            .take_while(|tick| tick.index < upper_tick)
+        {
+            liquidity += tick.liquidity_delta;
+            least = least.min(liquidity);
+            most = most.max(liquidity);
+        }
+
+        Some((least.unsigned_abs(), most.unsigned_abs()))
+    }
+
+    // Reports the fields that differ between this view and the other view, including added, removed and changed ticks
+    pub fn diff(&self, other: &BasePoolView<'_>) -> PoolDiff {
+        PoolDiff {
+            key: FieldDiff::of(self.key, other.key),
+            sqrt_ratio: FieldDiff::of(self.state.sqrt_ratio, other.state.sqrt_ratio),
+            liquidity: FieldDiff::of(self.state.liquidity, other.state.liquidity),
+            active_tick_index: FieldDiff::of(
+                self.state.active_tick_index,
+                other.state.active_tick_index,
+            ),
+            ticks: diff_sorted(self.sorted_ticks, other.sorted_ticks, |tick| tick.index),
+            ..PoolDiff::default()
+        }
+    }
+}
+
+// Iterates the initialized ticks of a pool in one direction from a tick, yielding the index and liquidity delta of each
+// tick with the liquidity that is active after crossing it in that direction
+#[derive(Clone, Debug)]
+pub struct TickLiquidityIter<'a> {
+    sorted_ticks: &'a [Tick],
+    // The number of ticks below the next tick when increasing, or the number of ticks up to and including the next tick
+    // when decreasing
+    position: usize,
+    liquidity: i128,
+    is_increasing: bool,
+}
+
+impl Iterator for TickLiquidityIter<'_> {
+    type Item = (i32, i128, u128);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tick = if self.is_increasing {
+            let tick = self.sorted_ticks.get(self.position)?;
+            self.position += 1;
+            self.liquidity += tick.liquidity_delta;
+            tick
+        } else {
+            self.position = self.position.checked_sub(1)?;
+            let tick = &self.sorted_ticks[self.position];
+            self.liquidity -= tick.liquidity_delta;
+            tick
+        };
+
+        Some((
+            tick.index,
+            tick.liquidity_delta,
+            self.liquidity.unsigned_abs(),
+        ))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = if self.is_increasing {
+            self.sorted_ticks.len() - self.position
+        } else {
+            self.position
+        };
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for TickLiquidityIter<'_> {}
+
+impl BasePoolView<'_> {
+    /// Quotes like [`Pool::quote`] while timing each phase of the quote with the timer, returning the profile even if
+    /// the quote fails.
+    pub fn quote_profiled<T: Timer + ?Sized>(
+        &self,
+        params: QuoteParams<BasePoolState, ()>,
+        timer: &T,
+    ) -> (
+        Result<Quote<BasePoolResources, BasePoolState>, BasePoolQuoteError>,
+        QuoteProfile,
+    ) {
+        let mut profiler = TimingProfiler {
+            timer,
+            profile: QuoteProfile::default(),
+        };
+        let result = self.quote_with_profiler(params, &mut profiler);
+        (result, profiler.profile)
+    }
+
+    fn quote_with_profiler(
+        &self,
+        params: QuoteParams<BasePoolState, ()>,
+        profiler: &mut impl QuoteProfiler,
+    ) -> Result<Quote<BasePoolResources, BasePoolState>, BasePoolQuoteError> {
+        let state = if let Some(override_state) = params.override_state {
+            override_state
+        } else {
+            self.state
+        };
+
+        let mut cursor = SortedTicksCursor {
+            sorted_ticks: self.sorted_ticks,
+            active_tick_index: state.active_tick_index,
+        };
+        let outcome = swap_through_ticks(
+            &self.key,
+            &mut cursor,
+            state.sqrt_ratio,
+            state.liquidity,
+            params.token_amount,
+            params.sqrt_ratio_limit,
+            params.max_ticks_crossed,
+            profiler,
+        )
+        .map_err(|err| match err {
+            TickProviderQuoteError::TickProviderError(never) => match never {},
+            TickProviderQuoteError::BasePoolQuoteError(err) => err,
+        })?;
+
+        let state_after = BasePoolState {
+            sqrt_ratio: outcome.sqrt_ratio,
+            liquidity: outcome.liquidity,
+            active_tick_index: cursor.active_tick_index,
+        };
+
+        if outcome.max_ticks_crossed_exceeded {
+            return Err(BasePoolQuoteError::MaxTicksCrossedExceeded(
+                PartialBasePoolQuote {
+                    consumed_amount: outcome.consumed_amount,
+                    calculated_amount: outcome.calculated_amount,
+                    fees_paid: outcome.fees_paid,
+                    state_after,
+                },
+            ));
         }
 
+        let accounting = profiler.start();
+        let starting_sqrt_ratio = state.sqrt_ratio;
         let resources = BasePoolResources {
             // we ignore changes from the override price because we assume the price has already changed
             no_override_price_change: if starting_sqrt_ratio == self.state.sqrt_ratio
-                && starting_sqrt_ratio != sqrt_ratio
+                && starting_sqrt_ratio != outcome.sqrt_ratio
             {
                 1
             } else {
                 0
             },
-            initialized_ticks_crossed,
+            initialized_ticks_crossed: outcome.initialized_ticks_crossed,
             tick_spacings_crossed: approximate_number_of_tick_spacings_crossed(
                 starting_sqrt_ratio,
-                sqrt_ratio,
-                self.key.config.tick_spacing,
+                outcome.sqrt_ratio,
+                self.key.config.tick_spacing.raw(),
             ),
         };
-
-        let state_after = BasePoolState {
-            sqrt_ratio,
-            liquidity,
-            active_tick_index,
-        };
+        profiler.record(QuotePhase::ResourceAccounting, accounting);
 
         Ok(Quote {
-            is_price_increasing: is_increasing,
-            consumed_amount: amount - amount_remaining,
-            calculated_amount,
+            is_price_increasing: outcome.is_increasing,
+            consumed_amount: outcome.consumed_amount,
+            calculated_amount: outcome.calculated_amount,
             execution_resources: resources,
             state_after,
-            fees_paid,
+            fees_paid: outcome.fees_paid,
+        })
+    }
+}
+
+// The cursor of sorted ticks, which tracks the position by the index of the active tick
+struct SortedTicksCursor<'a> {
+    sorted_ticks: &'a [Tick],
+    active_tick_index: Option<usize>,
+}
+
+impl TickCursor for SortedTicksCursor<'_> {
+    type Error = Infallible;
+
+    fn next_initialized_tick(&self, is_increasing: bool) -> Result<Option<Tick>, Self::Error> {
+        Ok(match (is_increasing, self.active_tick_index) {
+            (true, Some(index)) => self.sorted_ticks.get(index + 1).copied(),
+            (true, None) => self.sorted_ticks.first().copied(),
+            (false, Some(index)) => self.sorted_ticks.get(index).copied(),
+            (false, None) => None,
         })
     }
 
+    fn cross(&mut self, _tick: &Tick, is_increasing: bool) {
+        self.active_tick_index = match (is_increasing, self.active_tick_index) {
+            (true, Some(index)) => Some(index + 1),
+            (true, None) => Some(0),
+            (false, Some(index)) => index.checked_sub(1),
+            (false, None) => None,
+        };
+    }
+
+    fn move_to(&mut self, _sqrt_ratio: U256, is_increasing: bool, next_tick: Option<&Tick>) {
+        // without a next initialized tick the swap moved beyond all the ticks in its direction
+        if next_tick.is_none() {
+            self.active_tick_index = if is_increasing {
+                self.sorted_ticks.len().checked_sub(1)
+            } else {
+                None
+            };
+        }
+    }
+}
+
+impl Pool for BasePoolView<'_> {
+    type Resources = BasePoolResources;
+    type State = BasePoolState;
+    type QuoteError = BasePoolQuoteError;
+    type Meta = ();
+
+    fn get_key(&self) -> &NodeKey {
+        &self.key
+    }
+
+    fn get_state(&self) -> Self::State {
+        self.state
+    }
+
+    fn quote(
+        &self,
+        params: QuoteParams<Self::State, Self::Meta>,
+    ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
+        self.quote_with_profiler(params, &mut NoProfiler)
+    }
+
     // Checks if the pool has any liquidity.
     fn has_liquidity(&self) -> bool {
         self.state.liquidity > 0 || !self.sorted_ticks.is_empty()
@@ -674,24 +1429,71 @@ impl Pool for BasePool {
     }
 }
 
+impl MaxResourcesEstimate for BasePool {
+    fn max_resources_estimate(&self, amount_bound: u128) -> Self::Resources {
+        self.as_view().max_resources_estimate(amount_bound)
+    }
+}
+
+// A swap can cross every initialized tick and move the price to the end of the range in its direction
+impl MaxResourcesEstimate for BasePoolView<'_> {
+    fn max_resources_estimate(&self, amount_bound: u128) -> Self::Resources {
+        if amount_bound.is_zero() {
+            return BasePoolResources::default();
+        }
+
+        let (ticks_below, ticks_above) = match self.state.active_tick_index {
+            Some(index) => (index + 1, self.sorted_ticks.len() - (index + 1)),
+            None => (0, self.sorted_ticks.len()),
+        };
+        let tick_spacing = self.key.config.tick_spacing.raw();
+
+        BasePoolResources {
+            no_override_price_change: 1,
+            initialized_ticks_crossed: ticks_below.max(ticks_above).try_into().unwrap_or(u32::MAX),
+            // the approximation is rounded down, so it is padded by one
+            tick_spacings_crossed: approximate_number_of_tick_spacings_crossed(
+                self.state.sqrt_ratio,
+                MIN_SQRT_RATIO,
+                tick_spacing,
+            )
+            .max(approximate_number_of_tick_spacings_crossed(
+                self.state.sqrt_ratio,
+                MAX_SQRT_RATIO,
+                tick_spacing,
+            ))
+            .saturating_add(1),
+        }
+    }
+}
+
+impl PoolReads for BasePool {
+    fn inventory_at(&self, sqrt_ratio: U256) -> Result<Inventory, PoolReadError> {
+        self.as_view().inventory_at(sqrt_ratio)
+    }
+}
+
+impl PoolReads for BasePoolView<'_> {
+    fn inventory_at(&self, sqrt_ratio: U256) -> Result<Inventory, PoolReadError> {
+        ticks_inventory(self.sorted_ticks, sqrt_ratio)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::quoting::types::{Config, TokenAmount};
+    use crate::quoting::pool_diff::EntryDiff;
+    use crate::quoting::types::{Address, Config, TokenAmount};
     use alloc::vec;
 
-    const TOKEN0: U256 = U256([1, 0, 0, 0]);
-    const TOKEN1: U256 = U256([2, 0, 0, 0]);
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
 
     fn node_key(tick_spacing: u32, fee: u64) -> NodeKey {
         NodeKey {
             token0: TOKEN0,
             token1: TOKEN1,
-            config: Config {
-                tick_spacing,
-                fee,
-                extension: U256::zero(),
-            },
+            config: Config::from_raw_unchecked(fee, tick_spacing, U256::zero()),
         }
     }
 
@@ -700,19 +1502,15 @@ mod tests {
         use super::{to_sqrt_ratio, vec, BasePool, BasePoolState, NodeKey, MAX_TICK_SPACING, U256};
         use crate::math::tick::MAX_TICK;
         use crate::quoting::base_pool::BasePoolError::TickSpacingCannotBeZero;
-        use crate::quoting::types::{Config, Tick};
+        use crate::quoting::types::{Address, Config, Tick};
 
         #[test]
         fn test_token0_lt_token1() {
             let result = BasePool::new(
                 NodeKey {
-                    token0: U256::zero(),
-                    token1: U256::zero(),
-                    config: Config {
-                        extension: U256::zero(),
-                        fee: 0,
-                        tick_spacing: 0,
-                    },
+                    token0: Address::from_raw(U256::zero()),
+                    token1: Address::from_raw(U256::zero()),
+                    config: Config::from_raw_unchecked(0, 0, U256::zero()),
                 },
                 BasePoolState {
                     sqrt_ratio: to_sqrt_ratio(0).unwrap(),
@@ -728,13 +1526,9 @@ mod tests {
         fn test_token0_zero() {
             let result = BasePool::new(
                 NodeKey {
-                    token0: U256::zero(),
-                    token1: U256::one(),
-                    config: Config {
-                        extension: U256::zero(),
-                        fee: 0,
-                        tick_spacing: 1,
-                    },
+                    token0: Address::from_raw(U256::zero()),
+                    token1: Address::from_raw(U256::one()),
+                    config: Config::from_raw_unchecked(0, 1, U256::zero()),
                 },
                 BasePoolState {
                     sqrt_ratio: to_sqrt_ratio(0).unwrap(),
@@ -750,13 +1544,9 @@ mod tests {
         fn test_tick_spacing_zero_reverts() {
             let result = BasePool::new(
                 NodeKey {
-                    token0: U256::one(),
-                    token1: U256::one() + 1,
-                    config: Config {
-                        extension: U256::zero(),
-                        fee: 0,
-                        tick_spacing: 0,
-                    },
+                    token0: Address::from_raw(U256::one()),
+                    token1: Address::from_raw(U256::one() + 1),
+                    config: Config::from_raw_unchecked(0, 0, U256::zero()),
                 },
                 BasePoolState {
                     sqrt_ratio: to_sqrt_ratio(0).unwrap(),
@@ -772,13 +1562,9 @@ mod tests {
         fn test_tick_spacing_lte_max() {
             let result = BasePool::new(
                 NodeKey {
-                    token0: U256::one(),
-                    token1: U256::one() + 1,
-                    config: Config {
-                        extension: U256::zero(),
-                        fee: 0,
-                        tick_spacing: MAX_TICK_SPACING + 1,
-                    },
+                    token0: Address::from_raw(U256::one()),
+                    token1: Address::from_raw(U256::one() + 1),
+                    config: Config::from_raw_unchecked(0, MAX_TICK_SPACING + 1, U256::zero()),
                 },
                 BasePoolState {
                     sqrt_ratio: to_sqrt_ratio(0).unwrap(),
@@ -794,13 +1580,9 @@ mod tests {
         fn test_active_tick_index_within_range() {
             let result = BasePool::new(
                 NodeKey {
-                    token0: U256::one(),
-                    token1: U256::one() + 1,
-                    config: Config {
-                        extension: U256::zero(),
-                        fee: 0,
-                        tick_spacing: MAX_TICK_SPACING,
-                    },
+                    token0: Address::from_raw(U256::one()),
+                    token1: Address::from_raw(U256::one() + 1),
+                    config: Config::from_raw_unchecked(0, MAX_TICK_SPACING, U256::zero()),
                 },
                 BasePoolState {
                     sqrt_ratio: to_sqrt_ratio(0).unwrap(),
@@ -819,13 +1601,9 @@ mod tests {
         fn test_ticks_must_be_sorted() {
             let result = BasePool::new(
                 NodeKey {
-                    token0: U256::one(),
-                    token1: U256::one() + 1,
-                    config: Config {
-                        extension: U256::zero(),
-                        fee: 0,
-                        tick_spacing: MAX_TICK_SPACING,
-                    },
+                    token0: Address::from_raw(U256::one()),
+                    token1: Address::from_raw(U256::one() + 1),
+                    config: Config::from_raw_unchecked(0, MAX_TICK_SPACING, U256::zero()),
                 },
                 BasePoolState {
                     sqrt_ratio: to_sqrt_ratio(0).unwrap(),
@@ -850,13 +1628,9 @@ mod tests {
         fn test_ticks_must_be_multiple_of_tick_spacing() {
             let result = BasePool::new(
                 NodeKey {
-                    token0: U256::one(),
-                    token1: U256::one() + 1,
-                    config: Config {
-                        extension: U256::zero(),
-                        fee: 0,
-                        tick_spacing: MAX_TICK_SPACING,
-                    },
+                    token0: Address::from_raw(U256::one()),
+                    token1: Address::from_raw(U256::one() + 1),
+                    config: Config::from_raw_unchecked(0, MAX_TICK_SPACING, U256::zero()),
                 },
                 BasePoolState {
                     sqrt_ratio: to_sqrt_ratio(0).unwrap(),
@@ -881,13 +1655,9 @@ mod tests {
         fn test_ticks_must_total_to_zero_liquidity() {
             let result = BasePool::new(
                 NodeKey {
-                    token0: U256::one(),
-                    token1: U256::one() + 1,
-                    config: Config {
-                        extension: U256::zero(),
-                        fee: 0,
-                        tick_spacing: MAX_TICK_SPACING,
-                    },
+                    token0: Address::from_raw(U256::one()),
+                    token1: Address::from_raw(U256::one() + 1),
+                    config: Config::from_raw_unchecked(0, MAX_TICK_SPACING, U256::zero()),
                 },
                 BasePoolState {
                     sqrt_ratio: to_sqrt_ratio(0).unwrap(),
@@ -912,13 +1682,9 @@ mod tests {
         fn test_active_tick_index_must_be_within_bounds() {
             let result = BasePool::new(
                 NodeKey {
-                    token0: U256::one(),
-                    token1: U256::one() + 1,
-                    config: Config {
-                        extension: U256::zero(),
-                        fee: 0,
-                        tick_spacing: MAX_TICK_SPACING,
-                    },
+                    token0: Address::from_raw(U256::one()),
+                    token1: Address::from_raw(U256::one() + 1),
+                    config: Config::from_raw_unchecked(0, MAX_TICK_SPACING, U256::zero()),
                 },
                 BasePoolState {
                     sqrt_ratio: to_sqrt_ratio(0).unwrap(),
@@ -946,13 +1712,9 @@ mod tests {
         fn test_liquidity_equal_sum_of_deltas_active_ticks() {
             let result = BasePool::new(
                 NodeKey {
-                    token0: U256::one(),
-                    token1: U256::one() + 1,
-                    config: Config {
-                        extension: U256::zero(),
-                        fee: 0,
-                        tick_spacing: MAX_TICK_SPACING,
-                    },
+                    token0: Address::from_raw(U256::one()),
+                    token1: Address::from_raw(U256::one() + 1),
+                    config: Config::from_raw_unchecked(0, MAX_TICK_SPACING, U256::zero()),
                 },
                 BasePoolState {
                     sqrt_ratio: to_sqrt_ratio(0).unwrap(),
@@ -977,13 +1739,9 @@ mod tests {
         fn test_active_tick_sqrt_ratio_is_lte_current_sqrt_ratio() {
             let result = BasePool::new(
                 NodeKey {
-                    token0: U256::one(),
-                    token1: U256::one() + 1,
-                    config: Config {
-                        extension: U256::zero(),
-                        fee: 0,
-                        tick_spacing: MAX_TICK_SPACING,
-                    },
+                    token0: Address::from_raw(U256::one()),
+                    token1: Address::from_raw(U256::one() + 1),
+                    config: Config::from_raw_unchecked(0, MAX_TICK_SPACING, U256::zero()),
                 },
                 BasePoolState {
                     sqrt_ratio: to_sqrt_ratio(0).unwrap() - 1,
@@ -1011,13 +1769,9 @@ mod tests {
         fn test_if_no_active_tick_sqrt_ratio_lte_first() {
             let result = BasePool::new(
                 NodeKey {
-                    token0: U256::one(),
-                    token1: U256::one() + 1,
-                    config: Config {
-                        extension: U256::zero(),
-                        fee: 0,
-                        tick_spacing: MAX_TICK_SPACING,
-                    },
+                    token0: Address::from_raw(U256::one()),
+                    token1: Address::from_raw(U256::one() + 1),
+                    config: Config::from_raw_unchecked(0, MAX_TICK_SPACING, U256::zero()),
                 },
                 BasePoolState {
                     sqrt_ratio: to_sqrt_ratio(0).unwrap() + 1,
@@ -1061,6 +1815,7 @@ mod tests {
                 token: TOKEN1,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
             meta: (),
         };
@@ -1090,6 +1845,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
             meta: (),
         };
@@ -1129,6 +1885,7 @@ mod tests {
                 token: TOKEN1,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
             meta: (),
         };
@@ -1171,6 +1928,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
             meta: (),
         };
@@ -1185,54 +1943,357 @@ mod tests {
     }
 
     #[test]
-    fn test_example_failing_quote() {
+    fn test_quote_max_ticks_crossed() {
         let pool = BasePool::new(
-            node_key(100, 922337203685477),
+            node_key(1, 0),
             BasePoolState {
-                sqrt_ratio: U256([16035209758820767612, 757181812420893, 0, 0]),
-                liquidity: 99999,
-                active_tick_index: Some(16),
+                sqrt_ratio: to_sqrt_ratio(1).expect("Invalid tick"),
+                liquidity: 0,
+                active_tick_index: Some(1),
             },
             vec![
                 Tick {
-                    index: -88722000,
-                    liquidity_delta: 99999,
-                },
-                Tick {
-                    index: -24124600,
-                    liquidity_delta: 103926982998885,
-                },
-                Tick {
-                    index: -24124500,
-                    liquidity_delta: -103926982998885,
+                    index: 0,
+                    liquidity_delta: 1_000_000_000,
                 },
                 Tick {
-                    index: -20236100,
-                    liquidity_delta: 20192651866847,
+                    index: 1,
+                    liquidity_delta: -1_000_000_000,
                 },
+            ],
+        )
+        .expect("Pool creation should succeed");
+
+        let params = |max_ticks_crossed| QuoteParams {
+            token_amount: TokenAmount {
+                amount: 1000,
+                token: TOKEN0,
+            },
+            sqrt_ratio_limit: None,
+            max_ticks_crossed,
+            override_state: None,
+            meta: (),
+        };
+
+        assert_eq!(
+            pool.quote(params(Some(2)))
+                .unwrap()
+                .execution_resources
+                .initialized_ticks_crossed,
+            2
+        );
+
+        // the pool starts at the upper tick, which is crossed before anything is swapped
+        assert_eq!(
+            pool.quote(params(Some(0))).unwrap_err(),
+            BasePoolQuoteError::MaxTicksCrossedExceeded(PartialBasePoolQuote {
+                consumed_amount: 0,
+                calculated_amount: 0,
+                fees_paid: 0,
+                state_after: pool.get_state(),
+            })
+        );
+
+        let BasePoolQuoteError::MaxTicksCrossedExceeded(partial) =
+            pool.quote(params(Some(1))).unwrap_err()
+        else {
+            panic!("expected the limit to be exceeded");
+        };
+        assert_eq!(partial.calculated_amount, 499);
+        assert!(partial.consumed_amount > 0 && partial.consumed_amount < 1000);
+        assert_eq!(
+            partial.state_after,
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                liquidity: 1_000_000_000,
+                active_tick_index: Some(0),
+            }
+        );
+    }
+
+    #[test]
+    fn test_max_resources_estimate() {
+        let pool = BasePool::new(
+            node_key(1, 0),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(1).expect("Invalid tick"),
+                liquidity: 0,
+                active_tick_index: Some(1),
+            },
+            vec![
                 Tick {
-                    index: -20235900,
-                    liquidity_delta: 676843433645,
+                    index: 0,
+                    liquidity_delta: 1_000_000_000,
                 },
                 Tick {
-                    index: -20235400,
-                    liquidity_delta: 620315686813,
+                    index: 1,
+                    liquidity_delta: -1_000_000_000,
                 },
+            ],
+        )
+        .expect("Pool creation should succeed");
+
+        let estimate = pool.max_resources_estimate(u128::MAX);
+        assert_eq!(estimate.no_override_price_change, 1);
+        assert_eq!(estimate.initialized_ticks_crossed, 2);
+        assert_eq!(pool.max_resources_estimate(0), BasePoolResources::default());
+
+        for token in [TOKEN0, TOKEN1] {
+            let resources = pool
+                .quote(QuoteParams {
+                    token_amount: TokenAmount {
+                        amount: i128::MAX,
+                        token,
+                    },
+                    sqrt_ratio_limit: None,
+                    max_ticks_crossed: None,
+                    override_state: None,
+                    meta: (),
+                })
+                .unwrap()
+                .execution_resources;
+            assert!(resources.no_override_price_change <= estimate.no_override_price_change);
+            assert!(resources.initialized_ticks_crossed <= estimate.initialized_ticks_crossed);
+            assert!(resources.tick_spacings_crossed <= estimate.tick_spacings_crossed);
+        }
+    }
+
+    #[test]
+    fn test_liquidity_between() {
+        let pool = BasePool::new(
+            node_key(1, 0),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                liquidity: 300,
+                active_tick_index: Some(1),
+            },
+            vec![
                 Tick {
-                    index: -20235000,
-                    liquidity_delta: 3899271022058,
+                    index: -100,
+                    liquidity_delta: 100,
                 },
                 Tick {
-                    index: -20234900,
-                    liquidity_delta: 1985516133391,
+                    index: 0,
+                    liquidity_delta: 200,
                 },
                 Tick {
-                    index: -20233000,
-                    liquidity_delta: 2459469409600,
+                    index: 10,
+                    liquidity_delta: -200,
                 },
                 Tick {
-                    index: -20232100,
-                    liquidity_delta: -20192651866847,
+                    index: 100,
+                    liquidity_delta: -100,
+                },
+            ],
+        )
+        .expect("Pool creation should succeed");
+
+        assert_eq!(pool.liquidity_between(0, 10), 300);
+        assert_eq!(pool.liquidity_between(-5, 5), 100);
+        assert_eq!(pool.liquidity_between(-100, 100), 100);
+        assert_eq!(pool.liquidity_between(-200, 5), 0);
+        assert_eq!(pool.liquidity_between(5, 5), 0);
+
+        assert!(pool.has_liquidity_in_range(-200, -99));
+        assert!(pool.has_liquidity_in_range(99, 200));
+        assert!(!pool.has_liquidity_in_range(-200, -100));
+        assert!(!pool.has_liquidity_in_range(100, 200));
+        assert!(!pool.has_liquidity_in_range(10, 10));
+    }
+
+    #[test]
+    fn test_iter_ticks_from() {
+        let pool = BasePool::new(
+            node_key(1, 0),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                liquidity: 300,
+                active_tick_index: Some(1),
+            },
+            vec![
+                Tick {
+                    index: -100,
+                    liquidity_delta: 100,
+                },
+                Tick {
+                    index: 0,
+                    liquidity_delta: 200,
+                },
+                Tick {
+                    index: 10,
+                    liquidity_delta: -200,
+                },
+                Tick {
+                    index: 100,
+                    liquidity_delta: -100,
+                },
+            ],
+        )
+        .expect("Pool creation should succeed");
+
+        assert_eq!(
+            pool.iter_ticks_from(0, true).collect::<Vec<_>>(),
+            [(0, 200, 300), (10, -200, 100), (100, -100, 0)]
+        );
+        assert_eq!(
+            pool.iter_ticks_from(5, false).collect::<Vec<_>>(),
+            [(0, 200, 100), (-100, 100, 0)]
+        );
+        assert_eq!(pool.iter_ticks_from(-100, false).len(), 1);
+        assert_eq!(pool.iter_ticks_from(101, true).next(), None);
+        assert_eq!(pool.iter_ticks_from(-101, false).next(), None);
+    }
+
+    #[test]
+    fn test_next_initialized_tick() {
+        let ticks = vec![
+            Tick {
+                index: -100,
+                liquidity_delta: 100,
+            },
+            Tick {
+                index: 10,
+                liquidity_delta: -100,
+            },
+        ];
+        let pool = BasePool::new(
+            node_key(1, 0),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(-5).unwrap(),
+                liquidity: 100,
+                active_tick_index: Some(0),
+            },
+            ticks.clone(),
+        )
+        .expect("Pool creation should succeed");
+
+        assert_eq!(pool.active_tick(), Some(&ticks[0]));
+        assert_eq!(pool.next_initialized_tick(true), Some(&ticks[1]));
+        assert_eq!(pool.next_initialized_tick(false), Some(&ticks[0]));
+        assert_eq!(pool.distance_to_next_initialized_tick(true), Some(15));
+        assert_eq!(pool.distance_to_next_initialized_tick(false), Some(95));
+
+        let below = BasePool::new(
+            node_key(1, 0),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(-200).unwrap(),
+                liquidity: 0,
+                active_tick_index: None,
+            },
+            ticks.clone(),
+        )
+        .expect("Pool creation should succeed");
+
+        assert_eq!(below.active_tick(), None);
+        assert_eq!(below.next_initialized_tick(true), Some(&ticks[0]));
+        assert_eq!(below.next_initialized_tick(false), None);
+        assert_eq!(below.distance_to_next_initialized_tick(false), None);
+    }
+
+    #[test]
+    fn test_quote_profiled() {
+        let pool = BasePool::new(
+            node_key(1, 0),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(1).expect("Invalid tick"),
+                liquidity: 0,
+                active_tick_index: Some(1),
+            },
+            vec![
+                Tick {
+                    index: 0,
+                    liquidity_delta: 1_000_000_000,
+                },
+                Tick {
+                    index: 1,
+                    liquidity_delta: -1_000_000_000,
+                },
+            ],
+        )
+        .expect("Pool creation should succeed");
+        let params = QuoteParams {
+            token_amount: TokenAmount {
+                amount: 1000,
+                token: TOKEN0,
+            },
+            sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
+            override_state: None,
+            meta: (),
+        };
+
+        // every reading of the timer advances it by one, so each timed phase takes one unit
+        let time = core::cell::Cell::new(0);
+        let timer = || {
+            time.set(time.get() + 1);
+            time.get()
+        };
+        let (result, profile) = pool.as_view().quote_profiled(params, &timer);
+
+        assert_eq!(result.unwrap(), pool.quote(params).unwrap());
+        // the swap steps to the upper tick, through the liquidity, and past the lower tick
+        assert_eq!(profile.steps, 3);
+        assert_eq!(
+            profile,
+            QuoteProfile {
+                tick_search: 3,
+                step_math: 3,
+                resource_accounting: 4,
+                steps: 3,
+            }
+        );
+        assert_eq!(profile.total(), 10);
+    }
+
+    #[test]
+    fn test_example_failing_quote() {
+        let pool = BasePool::new(
+            node_key(100, 922337203685477),
+            BasePoolState {
+                sqrt_ratio: U256([16035209758820767612, 757181812420893, 0, 0]),
+                liquidity: 99999,
+                active_tick_index: Some(16),
+            },
+            vec![
+                Tick {
+                    index: -88722000,
+                    liquidity_delta: 99999,
+                },
+                Tick {
+                    index: -24124600,
+                    liquidity_delta: 103926982998885,
+                },
+                Tick {
+                    index: -24124500,
+                    liquidity_delta: -103926982998885,
+                },
+                Tick {
+                    index: -20236100,
+                    liquidity_delta: 20192651866847,
+                },
+                Tick {
+                    index: -20235900,
+                    liquidity_delta: 676843433645,
+                },
+                Tick {
+                    index: -20235400,
+                    liquidity_delta: 620315686813,
+                },
+                Tick {
+                    index: -20235000,
+                    liquidity_delta: 3899271022058,
+                },
+                Tick {
+                    index: -20234900,
+                    liquidity_delta: 1985516133391,
+                },
+                Tick {
+                    index: -20233000,
+                    liquidity_delta: 2459469409600,
+                },
+                Tick {
+                    index: -20232100,
+                    liquidity_delta: -20192651866847,
                 },
                 Tick {
                     index: -20231900,
@@ -1287,6 +2348,7 @@ mod tests {
                     token: TOKEN0,
                 },
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 override_state: None,
                 meta: (),
             })
@@ -1302,6 +2364,7 @@ mod tests {
                     token: TOKEN1,
                 },
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 override_state: None,
                 meta: (),
             })
@@ -1311,4 +2374,494 @@ mod tests {
         assert_eq!(quote.calculated_amount, 2436479431);
         assert_eq!(quote.execution_resources.initialized_ticks_crossed, 2);
     }
+
+    #[test]
+    fn test_shared_ticks() {
+        let pool = BasePool::new(
+            node_key(1, 0),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                liquidity: 1000,
+                active_tick_index: Some(0),
+            },
+            vec![
+                Tick {
+                    index: -1,
+                    liquidity_delta: 1000,
+                },
+                Tick {
+                    index: 1,
+                    liquidity_delta: -1000,
+                },
+            ],
+        )
+        .expect("Pool creation should succeed");
+
+        let cloned = pool.clone();
+        assert!(Arc::ptr_eq(
+            pool.get_shared_sorted_ticks(),
+            cloned.get_shared_sorted_ticks()
+        ));
+
+        let snapshot = BasePool::new_with_shared_ticks(
+            node_key(1, 0),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(1).unwrap(),
+                liquidity: 0,
+                active_tick_index: Some(1),
+            },
+            pool.get_shared_sorted_ticks().clone(),
+        )
+        .expect("Pool creation should succeed");
+        assert!(Arc::ptr_eq(
+            pool.get_shared_sorted_ticks(),
+            snapshot.get_shared_sorted_ticks()
+        ));
+        assert_eq!(snapshot.get_sorted_ticks(), pool.get_sorted_ticks());
+    }
+
+    #[test]
+    fn test_diff() {
+        let pool = BasePool::new(
+            node_key(1, 0),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                liquidity: 1000,
+                active_tick_index: Some(0),
+            },
+            vec![
+                Tick {
+                    index: -1,
+                    liquidity_delta: 1000,
+                },
+                Tick {
+                    index: 1,
+                    liquidity_delta: -1000,
+                },
+            ],
+        )
+        .unwrap();
+        assert!(pool.diff(&pool.clone()).is_empty());
+
+        // e.g. the same pool as derived from events, which missed a position update
+        let other = BasePool::new(
+            node_key(1, 0),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                liquidity: 1500,
+                active_tick_index: Some(0),
+            },
+            vec![
+                Tick {
+                    index: -1,
+                    liquidity_delta: 1500,
+                },
+                Tick {
+                    index: 1,
+                    liquidity_delta: -1000,
+                },
+                Tick {
+                    index: 2,
+                    liquidity_delta: -500,
+                },
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            pool.diff(&other),
+            PoolDiff {
+                liquidity: FieldDiff::of(1000, 1500),
+                ticks: vec![
+                    EntryDiff::Changed {
+                        left: Tick {
+                            index: -1,
+                            liquidity_delta: 1000,
+                        },
+                        right: Tick {
+                            index: -1,
+                            liquidity_delta: 1500,
+                        },
+                    },
+                    EntryDiff::Added(Tick {
+                        index: 2,
+                        liquidity_delta: -500,
+                    }),
+                ],
+                ..PoolDiff::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_view_quotes_borrowed_ticks() {
+        // e.g. ticks kept in a service's own storage
+        let storage = vec![
+            Tick {
+                index: -100,
+                liquidity_delta: 1_000_000,
+            },
+            Tick {
+                index: 100,
+                liquidity_delta: -1_000_000,
+            },
+        ];
+        let state = BasePoolState {
+            sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+            liquidity: 1_000_000,
+            active_tick_index: Some(0),
+        };
+
+        let view = BasePoolView::new(node_key(1, 1 << 60), state, &storage)
+            .expect("View creation should succeed");
+        let pool = BasePool::new(node_key(1, 1 << 60), state, storage.clone()).unwrap();
+        assert_eq!(pool.as_view(), view);
+        assert!(core::ptr::eq(view.get_sorted_ticks(), storage.as_slice()));
+
+        for amount in [1_000, -1_000, 100_000] {
+            let params = QuoteParams {
+                token_amount: TokenAmount {
+                    amount,
+                    token: TOKEN0,
+                },
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: None,
+                meta: (),
+            };
+            let (from_view, from_pool) = (view.quote(params).unwrap(), pool.quote(params).unwrap());
+            assert_eq!(from_view.calculated_amount, from_pool.calculated_amount);
+            assert_eq!(from_view.consumed_amount, from_pool.consumed_amount);
+            assert_eq!(from_view.state_after, from_pool.state_after);
+            assert_eq!(from_view.execution_resources, from_pool.execution_resources);
+        }
+    }
+
+    #[test]
+    fn test_view_is_validated() {
+        let storage = vec![Tick {
+            index: -100,
+            liquidity_delta: 1_000_000,
+        }];
+        assert_eq!(
+            BasePoolView::new(
+                node_key(1, 0),
+                BasePoolState {
+                    sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                    liquidity: 1_000_000,
+                    active_tick_index: Some(0),
+                },
+                &storage
+            ),
+            Err(BasePoolError::TotalLiquidityNotZero)
+        );
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_inconsistencies() {
+        let state = BasePoolState {
+            sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+            liquidity: 100,
+            active_tick_index: Some(0),
+        };
+        let ticks = vec![
+            Tick {
+                index: -10,
+                liquidity_delta: 100,
+            },
+            Tick {
+                index: 10,
+                liquidity_delta: -100,
+            },
+        ];
+
+        let (pool, repairs) = BasePool::new_with_mode(
+            node_key(10, 0),
+            state,
+            ticks.clone(),
+            ConstructionMode::Strict,
+        )
+        .unwrap();
+        assert!(repairs.is_empty());
+        assert_eq!(
+            pool,
+            BasePool::new(node_key(10, 0), state, ticks.clone()).unwrap()
+        );
+
+        assert_eq!(
+            BasePool::new_with_mode(node_key(20, 0), state, ticks, ConstructionMode::Strict),
+            Err(BasePoolError::TickNotMultipleOfSpacing)
+        );
+    }
+
+    #[test]
+    fn test_lenient_mode_repairs_indexer_artifacts() {
+        let state = BasePoolState {
+            sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+            liquidity: 300,
+            active_tick_index: None,
+        };
+        // unsorted, with a duplicate, a tick off the spacing and the upper tick of the duplicate missing
+        let ticks = vec![
+            Tick {
+                index: 100,
+                liquidity_delta: -100,
+            },
+            Tick {
+                index: -100,
+                liquidity_delta: 100,
+            },
+            Tick {
+                index: -100,
+                liquidity_delta: 100,
+            },
+            Tick {
+                index: -25,
+                liquidity_delta: 100,
+            },
+        ];
+
+        let (pool, repairs) =
+            BasePool::new_with_mode(node_key(10, 0), state, ticks, ConstructionMode::Lenient)
+                .unwrap();
+
+        let max_tick = (MAX_TICK / 10) * 10;
+        assert_eq!(
+            repairs,
+            vec![
+                PoolRepair::SortedTicks,
+                PoolRepair::MovedOffSpacingTick { from: -25, to: -30 },
+                PoolRepair::MergedDuplicateTicks {
+                    index: -100,
+                    count: 2
+                },
+                PoolRepair::AddedBoundaryLiquidity {
+                    index: max_tick,
+                    liquidity_delta: -200
+                },
+                PoolRepair::AdjustedActiveTickIndex {
+                    from: None,
+                    to: Some(1)
+                },
+            ]
+        );
+        assert_eq!(
+            pool.get_sorted_ticks(),
+            [
+                Tick {
+                    index: -100,
+                    liquidity_delta: 200,
+                },
+                Tick {
+                    index: -30,
+                    liquidity_delta: 100,
+                },
+                Tick {
+                    index: 100,
+                    liquidity_delta: -100,
+                },
+                Tick {
+                    index: max_tick,
+                    liquidity_delta: -200,
+                },
+            ]
+        );
+        assert_eq!(pool.get_state().active_tick_index, Some(1));
+    }
+
+    #[test]
+    fn test_lenient_mode_rejects_overflowing_repairs() {
+        let state = BasePoolState {
+            sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+            liquidity: 10,
+            active_tick_index: None,
+        };
+        let ticks = vec![
+            Tick {
+                index: MIN_TICK,
+                liquidity_delta: i128::MAX,
+            },
+            Tick {
+                index: -5,
+                liquidity_delta: -i128::MAX,
+            },
+        ];
+        assert!(
+            BasePool::new_lenient(node_key(1, 0), state, ticks, TickAlignment::default()).is_err()
+        );
+
+        let duplicates = vec![
+            Tick {
+                index: 0,
+                liquidity_delta: i128::MAX,
+            },
+            Tick {
+                index: 0,
+                liquidity_delta: 1,
+            },
+        ];
+        assert_eq!(
+            fold_off_spacing_ticks(duplicates, 1, TickAlignment::default()),
+            Err(BasePoolError::ActiveLiquidityOverflow)
+        );
+    }
+
+    #[test]
+    fn test_lenient_mode_adds_missing_lower_liquidity() {
+        // only the upper tick of a position was fetched
+        let (pool, repairs) = BasePool::new_with_mode(
+            node_key(10, 0),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                liquidity: 100,
+                active_tick_index: None,
+            },
+            vec![Tick {
+                index: 100,
+                liquidity_delta: -100,
+            }],
+            ConstructionMode::Lenient,
+        )
+        .unwrap();
+
+        let min_tick = (MIN_TICK / 10) * 10;
+        assert_eq!(
+            repairs,
+            vec![
+                PoolRepair::AddedBoundaryLiquidity {
+                    index: min_tick,
+                    liquidity_delta: 100
+                },
+                PoolRepair::AdjustedActiveTickIndex {
+                    from: None,
+                    to: Some(0)
+                },
+            ]
+        );
+        assert_eq!(pool.get_sorted_ticks()[0].index, min_tick);
+    }
+
+    #[test]
+    fn test_lenient_mode_still_validates() {
+        let mut key = node_key(10, 0);
+        key.token1 = Address::from_raw(U256::zero());
+        assert_eq!(
+            BasePool::new_with_mode(
+                key,
+                BasePoolState {
+                    sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                    liquidity: 0,
+                    active_tick_index: None,
+                },
+                vec![],
+                ConstructionMode::Lenient
+            ),
+            Err(BasePoolError::TokenOrderInvalid)
+        );
+    }
+
+    #[test]
+    fn test_fold_off_spacing_ticks() {
+        // a position from -25 to 14, and a position from -30 to 30 that one of its ticks folds into
+        let ticks = vec![
+            Tick {
+                index: -30,
+                liquidity_delta: 50,
+            },
+            Tick {
+                index: -25,
+                liquidity_delta: 100,
+            },
+            Tick {
+                index: 14,
+                liquidity_delta: -100,
+            },
+            Tick {
+                index: 30,
+                liquidity_delta: -50,
+            },
+        ];
+        let folded = |alignment| {
+            fold_off_spacing_ticks(ticks.clone(), 10, alignment)
+                .unwrap()
+                .0
+                .iter()
+                .map(|tick| (tick.index, tick.liquidity_delta))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            folded(TickAlignment::Outward),
+            vec![(-30, 150), (20, -100), (30, -50)]
+        );
+        assert_eq!(
+            folded(TickAlignment::Inward),
+            vec![(-30, 50), (-20, 100), (10, -100), (30, -50)]
+        );
+        assert_eq!(
+            folded(TickAlignment::Down),
+            vec![(-30, 150), (10, -100), (30, -50)]
+        );
+        assert_eq!(
+            folded(TickAlignment::Up),
+            vec![(-30, 50), (-20, 100), (20, -100), (30, -50)]
+        );
+        assert_eq!(
+            folded(TickAlignment::Nearest),
+            vec![(-30, 150), (10, -100), (30, -50)]
+        );
+
+        let (_, repairs) =
+            fold_off_spacing_ticks(ticks.clone(), 10, TickAlignment::Outward).unwrap();
+        assert_eq!(
+            repairs,
+            vec![
+                PoolRepair::MovedOffSpacingTick { from: -25, to: -30 },
+                PoolRepair::MovedOffSpacingTick { from: 14, to: 20 },
+                PoolRepair::MergedDuplicateTicks {
+                    index: -30,
+                    count: 2
+                },
+            ]
+        );
+        assert_eq!(
+            fold_off_spacing_ticks(ticks.clone(), 1, TickAlignment::Outward),
+            Ok((ticks.clone(), vec![]))
+        );
+        assert_eq!(
+            fold_off_spacing_ticks(ticks, 0, TickAlignment::Outward),
+            Err(BasePoolError::TickSpacingCannotBeZero)
+        );
+    }
+
+    #[test]
+    fn test_lenient_mode_with_alignment() {
+        let state = BasePoolState {
+            sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+            liquidity: 100,
+            active_tick_index: Some(0),
+        };
+        let ticks = vec![
+            Tick {
+                index: -25,
+                liquidity_delta: 100,
+            },
+            Tick {
+                index: 25,
+                liquidity_delta: -100,
+            },
+        ];
+
+        let (pool, repairs) =
+            BasePool::new_lenient(node_key(10, 0), state, ticks, TickAlignment::Inward).unwrap();
+        assert_eq!(
+            repairs,
+            vec![
+                PoolRepair::MovedOffSpacingTick { from: -25, to: -20 },
+                PoolRepair::MovedOffSpacingTick { from: 25, to: 20 },
+            ]
+        );
+        assert_eq!(pool.get_sorted_ticks()[0].index, -20);
+        assert_eq!(pool.get_state(), state);
+    }
 }
@@ -0,0 +1,358 @@
+use crate::math::uint::U256;
+use crate::quoting::cancellation::{Cancelled, StopSignal};
+use crate::quoting::types::{NodeKey, Pool, Quote, QuoteParams, TokenAmount};
+use alloc::vec::Vec;
+
+// A request for a quote that is not specific to any pool, i.e. it does not contain an override state
+#[derive(Clone, Copy, Debug)]
+pub struct QuoteRequest<M> {
+    pub token_amount: TokenAmount,
+    pub sqrt_ratio_limit: Option<U256>,
+    pub meta: M,
+}
+
+impl<M> QuoteRequest<M> {
+    // Returns the quote params for quoting the request against the current state of a pool
+    pub fn to_params<S>(self) -> QuoteParams<S, M> {
+        QuoteParams {
+            token_amount: self.token_amount,
+            sqrt_ratio_limit: self.sqrt_ratio_limit,
+            max_ticks_crossed: None,
+            override_state: None,
+            meta: self.meta,
+        }
+    }
+}
+
+pub type PoolQuoteResult<P> =
+    Result<Quote<<P as Pool>::Resources, <P as Pool>::State>, <P as Pool>::QuoteError>;
+
+// Quotes the request against each of the pools, returning the results in the same order as the pools
+pub fn quote_all<P: Pool>(pools: &[P], request: QuoteRequest<P::Meta>) -> Vec<PoolQuoteResult<P>> {
+    pools
+        .iter()
+        .map(|pool| pool.quote(request.to_params()))
+        .collect()
+}
+
+// Same as quote_all, but checks the stop signal before each pool. A cancelled batch returns the results of the pools
+// that were quoted before, which are the first pools of the slice.
+pub fn quote_all_until<P: Pool>(
+    pools: &[P],
+    request: QuoteRequest<P::Meta>,
+    stop: &impl StopSignal,
+) -> Result<Vec<PoolQuoteResult<P>>, Cancelled<Vec<PoolQuoteResult<P>>>> {
+    let mut results = Vec::with_capacity(pools.len());
+    for pool in pools {
+        if stop.should_stop() {
+            return Err(Cancelled { partial: results });
+        }
+        results.push(pool.quote(request.to_params()));
+    }
+    Ok(results)
+}
+
+// How pools that fail to quote are handled when quoting many pools at once
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum InvalidPoolPolicy {
+    /// Stop at the first pool that fails to quote and return its error.
+    #[default]
+    FailFast,
+    /// Skip the pools that fail to quote and report them alongside the successful quotes.
+    SkipAndReport,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SkippedPool<E> {
+    // The index of the pool in the quoted slice
+    pub index: usize,
+    pub pool_key: NodeKey,
+    pub error: E,
+}
+
+#[derive(Clone, Debug)]
+pub struct BatchQuoteReport<R, S, E> {
+    // The successful quotes paired with the index of the pool in the quoted slice, in the same order as the pools
+    pub quotes: Vec<(usize, Quote<R, S>)>,
+    pub skipped: Vec<SkippedPool<E>>,
+}
+
+pub type PoolBatchQuoteReport<P> =
+    BatchQuoteReport<<P as Pool>::Resources, <P as Pool>::State, <P as Pool>::QuoteError>;
+
+fn collect_report<P: Pool>(
+    pools: &[P],
+    results: Vec<PoolQuoteResult<P>>,
+    policy: InvalidPoolPolicy,
+) -> Result<PoolBatchQuoteReport<P>, SkippedPool<P::QuoteError>> {
+    let mut report = BatchQuoteReport {
+        quotes: Vec::with_capacity(results.len()),
+        skipped: Vec::new(),
+    };
+
+    for (index, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(quote) => report.quotes.push((index, quote)),
+            Err(error) => {
+                let skipped = SkippedPool {
+                    index,
+                    pool_key: *pools[index].get_key(),
+                    error,
+                };
+                match policy {
+                    InvalidPoolPolicy::FailFast => return Err(skipped),
+                    InvalidPoolPolicy::SkipAndReport => report.skipped.push(skipped),
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+// Quotes the request against each of the pools, handling the pools that fail to quote according to the policy
+pub fn quote_all_with_policy<P: Pool>(
+    pools: &[P],
+    request: QuoteRequest<P::Meta>,
+    policy: InvalidPoolPolicy,
+) -> Result<PoolBatchQuoteReport<P>, SkippedPool<P::QuoteError>> {
+    let results = match policy {
+        InvalidPoolPolicy::FailFast => {
+            let mut results = Vec::with_capacity(pools.len());
+            for pool in pools {
+                let result = pool.quote(request.to_params());
+                let failed = result.is_err();
+                results.push(result);
+                if failed {
+                    break;
+                }
+            }
+            results
+        }
+        InvalidPoolPolicy::SkipAndReport => quote_all(pools, request),
+    };
+
+    collect_report(pools, results, policy)
+}
+
+// Same as quote_all, but the pools are quoted concurrently on the rayon thread pool
+#[cfg(feature = "rayon")]
+pub fn quote_all_parallel<P>(pools: &[P], request: QuoteRequest<P::Meta>) -> Vec<PoolQuoteResult<P>>
+where
+    P: Pool,
+    P::Resources: Send,
+    P::State: Send,
+    P::QuoteError: Send,
+    P::Meta: Send + Sync,
+{
+    use rayon::prelude::*;
+
+    pools
+        .par_iter()
+        .map(|pool| pool.quote(request.to_params()))
+        .collect()
+}
+
+// Same as quote_all_with_policy, but the pools are quoted concurrently on the rayon thread pool.
+// With the fail fast policy the error of the first failing pool in slice order is returned.
+#[cfg(feature = "rayon")]
+pub fn quote_all_parallel_with_policy<P>(
+    pools: &[P],
+    request: QuoteRequest<P::Meta>,
+    policy: InvalidPoolPolicy,
+) -> Result<PoolBatchQuoteReport<P>, SkippedPool<P::QuoteError>>
+where
+    P: Pool,
+    P::Resources: Send,
+    P::State: Send,
+    P::QuoteError: Send,
+    P::Meta: Send + Sync,
+{
+    collect_report(pools, quote_all_parallel(pools, request), policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::full_range_pool::{
+        FullRangePool, FullRangePoolQuoteError, FullRangePoolState,
+    };
+    use crate::quoting::types::{Address, Config, NodeKey};
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn pools() -> Vec<FullRangePool> {
+        [0, 1 << 32, 1 << 60]
+            .into_iter()
+            .map(|fee| {
+                FullRangePool::new(
+                    NodeKey {
+                        token0: TOKEN0,
+                        token1: TOKEN1,
+                        config: Config::from_raw_unchecked(fee, 0, U256::zero()),
+                    },
+                    FullRangePoolState {
+                        sqrt_ratio: U256::one() << 128,
+                        liquidity: 1_000_000,
+                    },
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn request(token: Address) -> QuoteRequest<()> {
+        QuoteRequest {
+            token_amount: TokenAmount {
+                token,
+                amount: 1_000,
+            },
+            sqrt_ratio_limit: None,
+            meta: (),
+        }
+    }
+
+    #[test]
+    fn test_quote_all() {
+        let results = quote_all(&pools(), request(TOKEN0));
+
+        let calculated_amounts: Vec<u128> = results
+            .into_iter()
+            .map(|result| result.unwrap().calculated_amount)
+            .collect();
+        assert_eq!(calculated_amounts, [999, 998, 936]);
+    }
+
+    #[test]
+    fn test_quote_all_errors_per_pool() {
+        let results = quote_all(&pools(), request(Address::from_raw(U256::from(3))));
+
+        assert_eq!(results.len(), 3);
+        for result in results {
+            assert_eq!(result.unwrap_err(), FullRangePoolQuoteError::InvalidToken);
+        }
+    }
+
+    #[test]
+    fn test_quote_all_until() {
+        let pools = pools();
+        let results = quote_all_until(&pools, request(TOKEN0), &|| false).unwrap();
+        assert_eq!(results.len(), 3);
+
+        // stops before the third pool, e.g. when a new block arrives
+        let checks = core::cell::Cell::new(0);
+        let cancelled = quote_all_until(&pools, request(TOKEN0), &|| {
+            checks.set(checks.get() + 1);
+            checks.get() == 3
+        })
+        .unwrap_err();
+        let calculated_amounts: Vec<u128> = cancelled
+            .partial
+            .into_iter()
+            .map(|result| result.unwrap().calculated_amount)
+            .collect();
+        assert_eq!(calculated_amounts, [999, 998]);
+    }
+
+    fn pools_with_invalid() -> Vec<FullRangePool> {
+        let mut pools = pools();
+        // a pool for another pair cannot be quoted with the request
+        pools.insert(
+            1,
+            FullRangePool::new(
+                NodeKey {
+                    token0: Address::from_raw(U256::from(3)),
+                    token1: Address::from_raw(U256::from(4)),
+                    config: Config::from_raw_unchecked(0, 0, U256::zero()),
+                },
+                FullRangePoolState {
+                    sqrt_ratio: U256::one() << 128,
+                    liquidity: 1_000_000,
+                },
+            )
+            .unwrap(),
+        );
+        pools
+    }
+
+    #[test]
+    fn test_quote_all_with_policy_fail_fast() {
+        let pools = pools_with_invalid();
+        let skipped = quote_all_with_policy(&pools, request(TOKEN0), InvalidPoolPolicy::FailFast)
+            .unwrap_err();
+
+        assert_eq!(skipped.index, 1);
+        assert_eq!(skipped.pool_key, *pools[1].get_key());
+        assert_eq!(skipped.error, FullRangePoolQuoteError::InvalidToken);
+    }
+
+    #[test]
+    fn test_quote_all_with_policy_skip_and_report() {
+        let pools = pools_with_invalid();
+        let report =
+            quote_all_with_policy(&pools, request(TOKEN0), InvalidPoolPolicy::SkipAndReport)
+                .unwrap();
+
+        let quoted: Vec<(usize, u128)> = report
+            .quotes
+            .iter()
+            .map(|(index, quote)| (*index, quote.calculated_amount))
+            .collect();
+        assert_eq!(quoted, [(0, 999), (2, 998), (3, 936)]);
+        assert_eq!(
+            report.skipped,
+            [SkippedPool {
+                index: 1,
+                pool_key: *pools[1].get_key(),
+                error: FullRangePoolQuoteError::InvalidToken,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_quote_all_with_policy_no_errors() {
+        for policy in [
+            InvalidPoolPolicy::FailFast,
+            InvalidPoolPolicy::SkipAndReport,
+        ] {
+            let report = quote_all_with_policy(&pools(), request(TOKEN1), policy).unwrap();
+            assert_eq!(report.quotes.len(), 3);
+            assert!(report.skipped.is_empty());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_quote_all_parallel_with_policy() {
+        let pools = pools_with_invalid();
+        let report = quote_all_parallel_with_policy(
+            &pools,
+            request(TOKEN0),
+            InvalidPoolPolicy::SkipAndReport,
+        )
+        .unwrap();
+        assert_eq!(report.quotes.len(), 3);
+        assert_eq!(report.skipped.len(), 1);
+
+        let skipped =
+            quote_all_parallel_with_policy(&pools, request(TOKEN0), InvalidPoolPolicy::FailFast)
+                .unwrap_err();
+        assert_eq!(skipped.index, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_quote_all_parallel_matches_sequential() {
+        let pools = pools();
+        let sequential = quote_all(&pools, request(TOKEN1));
+        let parallel = quote_all_parallel(&pools, request(TOKEN1));
+
+        assert_eq!(sequential.len(), parallel.len());
+        for (a, b) in sequential.into_iter().zip(parallel) {
+            let (a, b) = (a.unwrap(), b.unwrap());
+            assert_eq!(a.calculated_amount, b.calculated_amount);
+            assert_eq!(a.state_after, b.state_after);
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use crate::quoting::batch::{PoolQuoteResult, QuoteRequest};
+use crate::quoting::types::{Address, NodeKey, Pool, Quote};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+// Identifies a class of requests that are expected to have the same best pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BestQuoteKey {
+    pub specified_token: Address,
+    pub calculated_token: Address,
+    pub size_bucket: i16,
+}
+
+// Returns the number of bits in the absolute value of the amount, negated for exact output amounts
+pub fn size_bucket(amount: i128) -> i16 {
+    let bits = (128 - amount.unsigned_abs().leading_zeros()) as i16;
+    if amount < 0 {
+        -bits
+    } else {
+        bits
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct BestQuote<R, S> {
+    pub pool_key: NodeKey,
+    pub quote: Quote<R, S>,
+}
+
+// The best pool of a size bucket with the quote it was picked with
+#[derive(Clone, Copy, Debug)]
+struct CachedBest {
+    pool_key: NodeKey,
+    amount: i128,
+    consumed_amount: i128,
+    calculated_amount: u128,
+}
+
+/// Caches the best pool for each pair and size bucket, so that on subsequent blocks only the
+/// previous best pool and the pools whose state changed need to be quoted.
+///
+/// This is an approximation: the best pool cached for one amount is reused for any other amount in the same size
+/// bucket, which assumes the relative ranking of pools whose state did not change is stable within a size bucket. If
+/// the previous best pool changed or quotes worse than when it was picked, all the pools of the pair are quoted again.
+#[derive(Clone, Debug, Default)]
+pub struct BestQuoteTracker {
+    best_pools: BTreeMap<BestQuoteKey, CachedBest>,
+}
+
+fn is_better<R, S>(quote: &Quote<R, S>, other: &Quote<R, S>) -> bool {
+    if quote.consumed_amount != other.consumed_amount {
+        return quote.consumed_amount.unsigned_abs() > other.consumed_amount.unsigned_abs();
+    }
+
+    if quote.consumed_amount < 0 {
+        quote.calculated_amount < other.calculated_amount
+    } else {
+        quote.calculated_amount > other.calculated_amount
+    }
+}
+
+fn is_worse_than_cached<R, S>(quote: &Quote<R, S>, cached: &CachedBest) -> bool {
+    if quote.consumed_amount != cached.consumed_amount {
+        return quote.consumed_amount.unsigned_abs() < cached.consumed_amount.unsigned_abs();
+    }
+
+    if quote.consumed_amount < 0 {
+        quote.calculated_amount > cached.calculated_amount
+    } else {
+        quote.calculated_amount < cached.calculated_amount
+    }
+}
+
+fn pick_best<'a, P: Pool + 'a>(
+    results: impl Iterator<Item = (&'a P, PoolQuoteResult<P>)>,
+) -> Option<BestQuote<P::Resources, P::State>> {
+    let mut best: Option<BestQuote<P::Resources, P::State>> = None;
+
+    for (pool, result) in results {
+        if let Ok(quote) = result {
+            if best.as_ref().is_none_or(|b| is_better(&quote, &b.quote)) {
+                best = Some(BestQuote {
+                    pool_key: *pool.get_key(),
+                    quote,
+                });
+            }
+        }
+    }
+
+    best
+}
+
+impl BestQuoteTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the best quote for the request among the pools that trade the specified token for the calculated token.
+    ///
+    /// `changed_pools` contains the keys of the pools whose state changed since the last call, e.g. as reported by
+    /// the events of the latest block. If the previous best pool for the pair and size bucket is known and did not
+    /// change, only it and the changed pools are quoted; otherwise all the pools of the pair are quoted.
+    pub fn best_quote<P: Pool>(
+        &mut self,
+        pools: &[P],
+        request: QuoteRequest<P::Meta>,
+        calculated_token: Address,
+        changed_pools: &[NodeKey],
+    ) -> Option<BestQuote<P::Resources, P::State>> {
+        let specified_token = request.token_amount.token;
+        let key = BestQuoteKey {
+            specified_token,
+            calculated_token,
+            size_bucket: size_bucket(request.token_amount.amount),
+        };
+
+        let candidates: Vec<&P> = pools
+            .iter()
+            .filter(|pool| {
+                let NodeKey { token0, token1, .. } = *pool.get_key();
+                (token0 == specified_token && token1 == calculated_token)
+                    || (token1 == specified_token && token0 == calculated_token)
+            })
+            .collect();
+
+        // a previous best pool whose state changed may have been overtaken by any of the unchanged pools
+        let cached = self
+            .best_pools
+            .get(&key)
+            .copied()
+            .filter(|cached| !changed_pools.contains(&cached.pool_key));
+        let previous_best = cached.and_then(|cached| {
+            candidates
+                .iter()
+                .find(|pool| *pool.get_key() == cached.pool_key)
+                .map(|pool| (*pool, cached))
+        });
+
+        let best = match previous_best {
+            Some((previous_best, cached)) => match previous_best.quote(request.to_params()) {
+                // if the previous best pool got worse for the same amount, the unchanged pools may now be better
+                Ok(quote)
+                    if cached.amount == request.token_amount.amount
+                        && is_worse_than_cached(&quote, &cached) =>
+                {
+                    None
+                }
+                Ok(quote) => pick_best(
+                    core::iter::once((previous_best, Ok(quote))).chain(
+                        candidates
+                            .iter()
+                            .filter(|pool| {
+                                pool.get_key() != previous_best.get_key()
+                                    && changed_pools.contains(pool.get_key())
+                            })
+                            .map(|pool| (*pool, pool.quote(request.to_params()))),
+                    ),
+                ),
+                // the previous best pool can no longer be quoted, so we have to quote all of them again
+                Err(_) => None,
+            },
+            None => None,
+        }
+        .or_else(|| {
+            pick_best(
+                candidates
+                    .iter()
+                    .map(|pool| (*pool, pool.quote(request.to_params()))),
+            )
+        });
+
+        match &best {
+            Some(best) => {
+                self.best_pools.insert(
+                    key,
+                    CachedBest {
+                        pool_key: best.pool_key,
+                        amount: request.token_amount.amount,
+                        consumed_amount: best.quote.consumed_amount,
+                        calculated_amount: best.quote.calculated_amount,
+                    },
+                );
+            }
+            None => {
+                self.best_pools.remove(&key);
+            }
+        }
+
+        best
+    }
+
+    // Forgets all cached best pools, so the next requests quote every pool
+    pub fn clear(&mut self) {
+        self.best_pools.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::uint::U256;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::types::{Config, TokenAmount};
+    use alloc::vec;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn pool(fee: u64, liquidity: u128) -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0: TOKEN0,
+                token1: TOKEN1,
+                config: Config::from_raw_unchecked(fee, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity,
+            },
+        )
+        .unwrap()
+    }
+
+    fn request(amount: i128) -> QuoteRequest<()> {
+        QuoteRequest {
+            token_amount: TokenAmount {
+                token: TOKEN0,
+                amount,
+            },
+            sqrt_ratio_limit: None,
+            meta: (),
+        }
+    }
+
+    #[test]
+    fn test_size_bucket() {
+        assert_eq!(size_bucket(0), 0);
+        assert_eq!(size_bucket(1), 1);
+        assert_eq!(size_bucket(1000), 10);
+        assert_eq!(size_bucket(1023), 10);
+        assert_eq!(size_bucket(1024), 11);
+        assert_eq!(size_bucket(-1000), -10);
+        assert_eq!(size_bucket(i128::MIN), -128);
+    }
+
+    #[test]
+    fn test_only_changed_pools_are_requoted() {
+        let mut tracker = BestQuoteTracker::new();
+
+        let pools = vec![pool(0, 1_000_000), pool(1, 1_000_000), pool(2, 1_000_000)];
+        let best = tracker
+            .best_quote(&pools, request(100_000), TOKEN1, &[])
+            .unwrap();
+        assert_eq!(best.pool_key, *pools[0].get_key());
+
+        // the third pool has much more liquidity, but it is not reported as changed, and the best pool cached for
+        // another amount of the same size bucket is reused
+        let pools = vec![pool(0, 1_000_000), pool(1, 1_000_000), pool(2, 100_000_000)];
+        let best = tracker
+            .best_quote(&pools, request(100_001), TOKEN1, &[])
+            .unwrap();
+        assert_eq!(best.pool_key, *pools[0].get_key());
+
+        let best = tracker
+            .best_quote(&pools, request(100_001), TOKEN1, &[*pools[2].get_key()])
+            .unwrap();
+        assert_eq!(best.pool_key, *pools[2].get_key());
+        assert_eq!(
+            best.quote.calculated_amount,
+            pools[2]
+                .quote(request(100_001).to_params())
+                .unwrap()
+                .calculated_amount
+        );
+    }
+
+    #[test]
+    fn test_previous_best_worsens() {
+        let mut tracker = BestQuoteTracker::new();
+
+        let pools = vec![
+            pool(0, 100_000_000),
+            pool(1, 10_000_000),
+            pool(2, 1_000_000),
+        ];
+        let best = tracker
+            .best_quote(&pools, request(100_000), TOKEN1, &[])
+            .unwrap();
+        assert_eq!(best.pool_key, *pools[0].get_key());
+
+        // the best pool lost most of its liquidity, so the unchanged second pool takes over
+        let pools = vec![pool(0, 100_000), pool(1, 10_000_000), pool(2, 1_000_000)];
+        let best = tracker
+            .best_quote(&pools, request(100_000), TOKEN1, &[*pools[0].get_key()])
+            .unwrap();
+        assert_eq!(best.pool_key, *pools[1].get_key());
+
+        // the same applies if the change was not reported but the quote of the same amount got worse
+        let pools = vec![pool(0, 100_000_000), pool(1, 1_000_000), pool(2, 1_000_000)];
+        tracker.clear();
+        tracker.best_quote(&pools, request(100_000), TOKEN1, &[]);
+        let pools = vec![pool(0, 10_000), pool(1, 1_000_000), pool(2, 1_000_000)];
+        let best = tracker
+            .best_quote(&pools, request(100_000), TOKEN1, &[])
+            .unwrap();
+        assert_eq!(best.pool_key, *pools[1].get_key());
+    }
+
+    #[test]
+    fn test_different_size_buckets_are_tracked_separately() {
+        let mut tracker = BestQuoteTracker::new();
+
+        let pools = vec![pool(0, 1_000_000), pool(1, 100_000_000)];
+        let best = tracker
+            .best_quote(&pools, request(100_000), TOKEN1, &[])
+            .unwrap();
+        assert_eq!(best.pool_key, *pools[1].get_key());
+
+        // exact output is a different bucket
+        let best = tracker
+            .best_quote(&pools, request(-100_000), TOKEN1, &[])
+            .unwrap();
+        assert_eq!(best.pool_key, *pools[1].get_key());
+        assert_eq!(best.quote.consumed_amount, -100_000);
+    }
+
+    #[test]
+    fn test_no_pools_for_pair() {
+        let mut tracker = BestQuoteTracker::new();
+        let pools = vec![pool(0, 1_000_000)];
+
+        assert!(tracker
+            .best_quote(
+                &pools,
+                request(100_000),
+                Address::from_raw(U256::from(3)),
+                &[]
+            )
+            .is_none());
+    }
+}
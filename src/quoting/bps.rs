@@ -0,0 +1,132 @@
+use crate::math::muldiv::muldiv;
+use crate::math::uint::U256;
+
+// A fraction in basis points, i.e. ten thousandths, for slippage tolerances, fees and split shares. Fractions greater
+// than one are allowed, e.g. for margins above the amount.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Bps(u32);
+
+impl Bps {
+    pub const DENOMINATOR: u32 = 10_000;
+    pub const ZERO: Bps = Bps(0);
+    pub const ONE: Bps = Bps(Self::DENOMINATOR);
+
+    pub const fn new(bps: u32) -> Self {
+        Self(bps)
+    }
+
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+
+    // Returns one minus the fraction, or zero if the fraction is greater than one, e.g. the share of an amount that is
+    // left after slippage
+    pub const fn complement(self) -> Self {
+        Self(Self::DENOMINATOR.saturating_sub(self.0))
+    }
+
+    // Returns the fraction of the whole that the part is, or None if the whole is zero or the fraction does not fit
+    pub fn ratio(part: U256, whole: U256, round_up: bool) -> Option<Self> {
+        muldiv(part, U256::from(Self::DENOMINATOR), whole, round_up)
+            .ok()
+            .filter(|bps| *bps <= U256::from(u32::MAX))
+            .map(|bps| Self(bps.as_u32()))
+    }
+
+    // Applies the fraction to the amount rounding down, or returns None on overflow
+    pub fn apply_floor(self, amount: U256) -> Option<U256> {
+        self.apply(amount, false)
+    }
+
+    pub fn apply_ceil(self, amount: U256) -> Option<U256> {
+        self.apply(amount, true)
+    }
+
+    // Applies the fraction to the signed amount rounding toward negative infinity, or returns None on overflow
+    pub fn apply_i128_floor(self, amount: i128) -> Option<i128> {
+        self.apply_i128(amount, false)
+    }
+
+    // Applies the fraction to the signed amount rounding toward positive infinity
+    pub fn apply_i128_ceil(self, amount: i128) -> Option<i128> {
+        self.apply_i128(amount, true)
+    }
+
+    fn apply(self, amount: U256, round_up: bool) -> Option<U256> {
+        muldiv(
+            amount,
+            U256::from(self.0),
+            U256::from(Self::DENOMINATOR),
+            round_up,
+        )
+        .ok()
+    }
+
+    fn apply_i128(self, amount: i128, round_up: bool) -> Option<i128> {
+        // the magnitude of a negative amount is rounded in the opposite direction
+        let negative = amount < 0;
+        let mag = self.apply(U256::from(amount.unsigned_abs()), round_up != negative)?;
+        if mag > U256::from(u128::MAX) {
+            return None;
+        }
+
+        if negative {
+            0i128.checked_sub_unsigned(mag.as_u128())
+        } else {
+            i128::try_from(mag.as_u128()).ok()
+        }
+    }
+}
+
+impl From<u32> for Bps {
+    fn from(value: u32) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Bps> for u32 {
+    fn from(value: Bps) -> Self {
+        value.raw()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let bps = Bps::new(3333);
+        assert_eq!(bps.apply_floor(U256::from(100)), Some(U256::from(33)));
+        assert_eq!(bps.apply_ceil(U256::from(100)), Some(U256::from(34)));
+        assert_eq!(Bps::ONE.apply_floor(U256::MAX), Some(U256::MAX));
+        assert_eq!(Bps::new(10_001).apply_floor(U256::MAX), None);
+
+        assert_eq!(bps.apply_i128_floor(100), Some(33));
+        assert_eq!(bps.apply_i128_ceil(100), Some(34));
+        assert_eq!(bps.apply_i128_floor(-100), Some(-34));
+        assert_eq!(bps.apply_i128_ceil(-100), Some(-33));
+        assert_eq!(Bps::ONE.apply_i128_floor(i128::MIN), Some(i128::MIN));
+        assert_eq!(Bps::new(20_000).apply_i128_floor(i128::MAX), None);
+    }
+
+    #[test]
+    fn test_ratio_and_complement() {
+        assert_eq!(
+            Bps::ratio(U256::from(1), U256::from(3), false),
+            Some(Bps::new(3333))
+        );
+        assert_eq!(
+            Bps::ratio(U256::from(1), U256::from(3), true),
+            Some(Bps::new(3334))
+        );
+        assert_eq!(Bps::ratio(U256::from(1), U256::zero(), false), None);
+        assert_eq!(Bps::new(50).complement(), Bps::new(9950));
+        assert_eq!(Bps::new(20_000).complement(), Bps::ZERO);
+    }
+}
@@ -0,0 +1,293 @@
+use crate::quoting::cancellation::{NeverStop, StopSignal};
+use crate::quoting::pool_map::PoolMap;
+use crate::quoting::route::{quote_route_with_states_until, Route, RouteQuote, RouteQuoteError};
+use crate::quoting::types::{NodeKey, Pool};
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::vec::Vec;
+
+// The input amount of a trade in a bundle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeAmount {
+    Fixed(u128),
+    // The whole output of another trade of the bundle, which must then be quoted first
+    OutputOf(usize),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleTrade {
+    pub route: Route,
+    pub amount_in: TradeAmount,
+}
+
+// A sequence of trades that are settled together, where trades may use the output of other trades as their input and
+// may trade through the same pools
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bundle {
+    pub trades: Vec<BundleTrade>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BundleError<E> {
+    /// The input of the trade refers to a trade that is not in the bundle.
+    UnknownTrade { trade: usize, dependency: usize },
+    /// The trade depends on its own output, directly or through other trades.
+    DependencyCycle { trade: usize },
+    /// The route of the trade could not be quoted.
+    TradeFailed {
+        trade: usize,
+        error: RouteQuoteError<E>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleQuote<S> {
+    // The indices of the trades in the order they were quoted
+    pub execution_order: Vec<usize>,
+    // The quote of each trade, in the same order as the trades of the bundle
+    pub trades: Vec<RouteQuote<S>>,
+    // The state of each pool traded by the bundle after all the trades
+    pub states_after: BTreeMap<NodeKey, S>,
+}
+
+impl Bundle {
+    pub fn new(trades: Vec<BundleTrade>) -> Self {
+        Self { trades }
+    }
+
+    /// Returns the order in which the trades have to be quoted so that every trade is quoted after the trade whose
+    /// output it uses. Trades without such a dependency between them keep their order in the bundle, so trades that
+    /// share pools see each other's effects in bundle order.
+    pub fn execution_order<E>(&self) -> Result<Vec<usize>, BundleError<E>> {
+        let mut dependents: Vec<Vec<usize>> = alloc::vec![Vec::new(); self.trades.len()];
+        let mut unresolved_dependencies: Vec<usize> = alloc::vec![0; self.trades.len()];
+
+        for (trade, bundle_trade) in self.trades.iter().enumerate() {
+            if let TradeAmount::OutputOf(dependency) = bundle_trade.amount_in {
+                if dependency >= self.trades.len() {
+                    return Err(BundleError::UnknownTrade { trade, dependency });
+                }
+                dependents[dependency].push(trade);
+                unresolved_dependencies[trade] += 1;
+            }
+        }
+
+        // Kahn's algorithm, always picking the lowest index that is ready
+        let mut ready: BTreeSet<usize> = (0..self.trades.len())
+            .filter(|&trade| unresolved_dependencies[trade] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(self.trades.len());
+
+        while let Some(trade) = ready.pop_first() {
+            order.push(trade);
+            for &dependent in &dependents[trade] {
+                unresolved_dependencies[dependent] -= 1;
+                if unresolved_dependencies[dependent] == 0 {
+                    ready.insert(dependent);
+                }
+            }
+        }
+
+        match unresolved_dependencies.iter().position(|&count| count > 0) {
+            Some(trade) => Err(BundleError::DependencyCycle { trade }),
+            None => Ok(order),
+        }
+    }
+}
+
+// Quotes the trades of the bundle in execution order, threading the state of each pool from one trade to the next
+pub fn quote_bundle<P: Pool>(
+    pools: &PoolMap<P>,
+    bundle: &Bundle,
+    meta: P::Meta,
+) -> Result<BundleQuote<P::State>, BundleError<P::QuoteError>> {
+    quote_bundle_until(pools, bundle, meta, &NeverStop)
+}
+
+// Same as quote_bundle, but checks the stop signal before each hop of each trade. A cancelled bundle fails with the
+// trade that was being quoted and a cancelled route error.
+pub fn quote_bundle_until<P: Pool>(
+    pools: &PoolMap<P>,
+    bundle: &Bundle,
+    meta: P::Meta,
+    stop: &impl StopSignal,
+) -> Result<BundleQuote<P::State>, BundleError<P::QuoteError>> {
+    let execution_order = bundle.execution_order()?;
+    let mut states = BTreeMap::new();
+    let mut quotes: Vec<Option<RouteQuote<P::State>>> = alloc::vec![None; bundle.trades.len()];
+
+    for &trade in &execution_order {
+        let bundle_trade = &bundle.trades[trade];
+        let amount_in = match bundle_trade.amount_in {
+            TradeAmount::Fixed(amount) => amount,
+            // the execution order guarantees the dependency was quoted before
+            TradeAmount::OutputOf(dependency) => quotes[dependency]
+                .as_ref()
+                .map(|quote| quote.amount_out)
+                .unwrap_or_default(),
+        };
+
+        let quote = quote_route_with_states_until(
+            pools,
+            &bundle_trade.route,
+            amount_in,
+            meta,
+            &mut states,
+            stop,
+        )
+        .map_err(|error| BundleError::TradeFailed { trade, error })?;
+        quotes[trade] = Some(quote);
+    }
+
+    Ok(BundleQuote {
+        execution_order,
+        trades: quotes.into_iter().flatten().collect(),
+        states_after: states,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::uint::U256;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::route::{
+        quote_route, HopDirection, RouteHop, RouteSplit, ROUTE_SHARE_DENOMINATOR,
+    };
+    use crate::quoting::types::{Address, Config};
+    use alloc::vec;
+
+    fn key(token0: u64, token1: u64) -> NodeKey {
+        NodeKey {
+            token0: Address::from_raw(U256::from(token0)),
+            token1: Address::from_raw(U256::from(token1)),
+            config: Config::from_raw_unchecked(0, 0, U256::zero()),
+        }
+    }
+
+    fn pools() -> PoolMap<FullRangePool> {
+        [key(1, 2), key(2, 3)]
+            .into_iter()
+            .map(|key| {
+                FullRangePool::new(
+                    key,
+                    FullRangePoolState {
+                        sqrt_ratio: U256::one() << 128,
+                        liquidity: 1_000_000,
+                    },
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn trade(token0: u64, token1: u64, amount_in: TradeAmount) -> BundleTrade {
+        BundleTrade {
+            route: Route {
+                token_in: Address::from_raw(U256::from(token0)),
+                token_out: Address::from_raw(U256::from(token1)),
+                splits: vec![RouteSplit {
+                    share: ROUTE_SHARE_DENOMINATOR,
+                    hops: vec![RouteHop {
+                        pool_key: key(token0, token1),
+                        direction: HopDirection::ZeroForOne,
+                    }],
+                }],
+            },
+            amount_in,
+        }
+    }
+
+    #[test]
+    fn test_quote_bundle() {
+        let pools = pools();
+        let bundle = Bundle::new(vec![
+            // uses the output of the last trade, so it is quoted last
+            trade(2, 3, TradeAmount::OutputOf(2)),
+            trade(1, 2, TradeAmount::Fixed(10_000)),
+            // trades through the same pool as the previous trade
+            trade(1, 2, TradeAmount::Fixed(10_000)),
+        ]);
+
+        let quote = quote_bundle(&pools, &bundle, ()).unwrap();
+        assert_eq!(quote.execution_order, [1, 2, 0]);
+
+        let first = quote_route(&pools, &bundle.trades[1].route, 10_000, ()).unwrap();
+        assert_eq!(quote.trades[1], first);
+        // the second trade through the pool gets a worse price
+        assert!(quote.trades[2].amount_out < first.amount_out);
+        assert_eq!(
+            quote.trades[0].splits[0].amount_in(),
+            quote.trades[2].amount_out
+        );
+        assert_eq!(quote.states_after.len(), 2);
+    }
+
+    #[test]
+    fn test_dependency_errors() {
+        let bundle = Bundle::new(vec![
+            trade(1, 2, TradeAmount::Fixed(1)),
+            trade(2, 3, TradeAmount::OutputOf(3)),
+        ]);
+        assert_eq!(
+            bundle.execution_order::<()>(),
+            Err(BundleError::UnknownTrade {
+                trade: 1,
+                dependency: 3
+            })
+        );
+
+        let bundle = Bundle::new(vec![
+            trade(1, 2, TradeAmount::Fixed(1)),
+            trade(1, 2, TradeAmount::OutputOf(2)),
+            trade(1, 2, TradeAmount::OutputOf(1)),
+        ]);
+        assert_eq!(
+            bundle.execution_order::<()>(),
+            Err(BundleError::DependencyCycle { trade: 1 })
+        );
+
+        let bundle = Bundle::new(vec![trade(1, 2, TradeAmount::OutputOf(0))]);
+        assert_eq!(
+            bundle.execution_order::<()>(),
+            Err(BundleError::DependencyCycle { trade: 0 })
+        );
+    }
+
+    #[test]
+    fn test_cancelled_bundle() {
+        let pools = pools();
+        let bundle = Bundle::new(vec![
+            trade(1, 2, TradeAmount::Fixed(1_000)),
+            trade(2, 3, TradeAmount::OutputOf(0)),
+        ]);
+        let stop = core::sync::atomic::AtomicBool::new(false);
+        assert_eq!(
+            quote_bundle_until(&pools, &bundle, (), &stop),
+            quote_bundle(&pools, &bundle, ())
+        );
+
+        stop.store(true, core::sync::atomic::Ordering::Relaxed);
+        assert_eq!(
+            quote_bundle_until(&pools, &bundle, (), &stop),
+            Err(BundleError::TradeFailed {
+                trade: 0,
+                error: RouteQuoteError::Cancelled { split: 0, hop: 0 }
+            })
+        );
+    }
+
+    #[test]
+    fn test_failed_trade() {
+        let bundle = Bundle::new(vec![
+            trade(1, 2, TradeAmount::Fixed(1_000)),
+            trade(1, 3, TradeAmount::Fixed(1_000)),
+        ]);
+        assert_eq!(
+            quote_bundle(&pools(), &bundle, ()),
+            Err(BundleError::TradeFailed {
+                trade: 1,
+                error: RouteQuoteError::PoolNotFound { split: 0, hop: 0 }
+            })
+        );
+    }
+}
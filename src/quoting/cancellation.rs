@@ -0,0 +1,62 @@
+use core::sync::atomic::{AtomicBool, Ordering};
+
+// Decides whether a long running quoting job should stop before its next step, e.g. because a new block arrived and
+// the results would be stale. Jobs check the signal between steps, so a step that already started is completed.
+pub trait StopSignal {
+    fn should_stop(&self) -> bool;
+}
+
+// Set from another thread to stop the jobs that check it
+impl StopSignal for AtomicBool {
+    fn should_stop(&self) -> bool {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+impl<F: Fn() -> bool> StopSignal for F {
+    fn should_stop(&self) -> bool {
+        self()
+    }
+}
+
+// A signal that never stops, for running the cancellable jobs to completion
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NeverStop;
+
+impl StopSignal for NeverStop {
+    fn should_stop(&self) -> bool {
+        false
+    }
+}
+
+// A job that was stopped before it finished, with the results it completed until then
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cancelled<T> {
+    pub partial: T,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    #[test]
+    fn test_stop_signals() {
+        let flag = AtomicBool::new(false);
+        assert!(!flag.should_stop());
+        flag.store(true, Ordering::Relaxed);
+        assert!(flag.should_stop());
+
+        let checks = Cell::new(0);
+        let after_two_checks = || {
+            checks.set(checks.get() + 1);
+            checks.get() > 2
+        };
+        assert_eq!(
+            [(); 3].map(|_| after_two_checks.should_stop()),
+            [false, false, true]
+        );
+
+        assert!(!NeverStop.should_stop());
+    }
+}
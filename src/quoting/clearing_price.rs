@@ -0,0 +1,375 @@
+use crate::math::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+use crate::math::uint::U256;
+use crate::quoting::solver::{convert, pro_rata, Order};
+use crate::quoting::types::{NodeKey, Pool, Quote, QuoteParams, SqrtRatioState, TokenAmount};
+use alloc::vec::Vec;
+
+// The amounts an order sells and buys at the clearing price
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClearingFill {
+    pub sell_amount: u128,
+    pub buy_amount: u128,
+}
+
+#[derive(Clone, Debug)]
+pub struct ClearingPrice<R, S> {
+    // The uniform price of token1 in token0 that every filled order trades at
+    pub sqrt_ratio: U256,
+    // The fill of each order, in the same order as the orders
+    pub fills: Vec<ClearingFill>,
+    // The swap of the excess of one token through the pool, if the orders do not balance each other
+    pub pool_quote: Option<Quote<R, S>>,
+    // The amounts of each token left over after paying the orders, because the bought amounts are rounded down
+    pub surplus0: u128,
+    pub surplus1: u128,
+}
+
+pub type PoolClearingPrice<P> = ClearingPrice<<P as Pool>::Resources, <P as Pool>::State>;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ClearingError<E> {
+    /// The order at the index does not trade the tokens of the pool.
+    OrderNotOnPair(usize),
+    /// The pool cannot absorb the excess of the orders at any price.
+    NoClearingPrice,
+    /// The pool failed to quote the swap of the excess.
+    QuoteFailed(E),
+}
+
+// The indices of the orders selling the token whose limit price is met at the sqrt ratio
+fn eligible(orders: &[Order], key: &NodeKey, sqrt_ratio: U256, sells_token0: bool) -> Vec<usize> {
+    orders
+        .iter()
+        .enumerate()
+        .filter(|(_, order)| {
+            (order.sell_token == key.token0) == sells_token0
+                && order.sell_amount != 0
+                && order.meets_limit(
+                    order.sell_amount,
+                    convert(order.sell_amount, sqrt_ratio, sells_token0),
+                )
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn volume(orders: &[Order], sellers: &[usize]) -> u128 {
+    sellers.iter().fold(0, |total, &index| {
+        total.saturating_add(orders[index].sell_amount)
+    })
+}
+
+// Clears the orders at the sqrt ratio, or returns None if the pool cannot absorb the excess of the orders at it
+fn clear_at<P: Pool>(
+    pool: &P,
+    orders: &[Order],
+    sqrt_ratio: U256,
+    excess_token0: bool,
+    meta: P::Meta,
+) -> Result<Option<PoolClearingPrice<P>>, P::QuoteError> {
+    let key = pool.get_key();
+    let (excess_sellers, other_sellers) = (
+        eligible(orders, key, sqrt_ratio, excess_token0),
+        eligible(orders, key, sqrt_ratio, !excess_token0),
+    );
+    let (excess_volume, other_volume) = (
+        volume(orders, &excess_sellers),
+        volume(orders, &other_sellers),
+    );
+
+    // the volume of the excess token the other side buys at the price
+    let demand = convert(other_volume, sqrt_ratio, !excess_token0);
+    let (other_sold, pool_quote) = if excess_volume > demand {
+        let excess = excess_volume - demand;
+        let quote = pool.quote(QuoteParams {
+            token_amount: TokenAmount {
+                token: if excess_token0 {
+                    key.token0
+                } else {
+                    key.token1
+                },
+                amount: i128::try_from(excess).unwrap_or(i128::MAX),
+            },
+            sqrt_ratio_limit: Some(sqrt_ratio),
+            max_ticks_crossed: None,
+            override_state: None,
+            meta,
+        })?;
+        if quote.consumed_amount as u128 != excess {
+            return Ok(None);
+        }
+        (other_volume, Some(quote))
+    } else {
+        // the other side is rationed to the volume the excess side buys
+        (
+            convert(excess_volume, sqrt_ratio, excess_token0).min(other_volume),
+            None,
+        )
+    };
+
+    let mut fills = alloc::vec![ClearingFill::default(); orders.len()];
+    let mut paid = [0u128; 2];
+    for (sellers, total, from_excess) in [
+        (&excess_sellers, excess_volume, true),
+        (&other_sellers, other_sold, false),
+    ] {
+        let weights: Vec<u128> = sellers
+            .iter()
+            .map(|&index| orders[index].sell_amount)
+            .collect();
+        for (&index, sell_amount) in sellers.iter().zip(pro_rata(total, &weights)) {
+            let buy_amount = convert(sell_amount, sqrt_ratio, excess_token0 == from_excess);
+            fills[index] = ClearingFill {
+                sell_amount,
+                buy_amount,
+            };
+            paid[from_excess as usize] += buy_amount;
+        }
+    }
+
+    // paid[0] is paid in the excess token, and paid[1] in the other token
+    let (pool_in, pool_out) = pool_quote.as_ref().map_or((0, 0), |quote| {
+        (quote.consumed_amount as u128, quote.calculated_amount)
+    });
+    let Some(surplus_excess) = excess_volume.checked_sub(pool_in + paid[0]) else {
+        return Ok(None);
+    };
+    let Some(surplus_other) = other_sold
+        .checked_add(pool_out)
+        .and_then(|received| received.checked_sub(paid[1]))
+    else {
+        return Ok(None);
+    };
+    let (surplus0, surplus1) = if excess_token0 {
+        (surplus_excess, surplus_other)
+    } else {
+        (surplus_other, surplus_excess)
+    };
+
+    Ok(Some(ClearingPrice {
+        sqrt_ratio,
+        fills,
+        pool_quote,
+        surplus0,
+        surplus1,
+    }))
+}
+
+/// Computes the uniform price that clears a batch of orders on the pair of the pool, along with the fill of each order.
+///
+/// At any price, the orders whose limit price is met supply one token and demand the other. The token in excess at
+/// the price of the pool is sold into the pool, which moves the price against it, until the price where the pool
+/// absorbs the excess exactly is found by bisecting the sqrt ratio. If the excess side falls short of the demand of
+/// the other side instead, the other side is filled proportionally to the sell amounts of its orders.
+///
+/// Every bought amount is rounded down from the sold amount at the clearing price, independently for each order, so
+/// the batch never pays out more than it receives. The leftover amounts are returned as the surplus.
+pub fn compute_clearing_price<P: Pool>(
+    pool: &P,
+    orders: &[Order],
+    meta: P::Meta,
+) -> Result<PoolClearingPrice<P>, ClearingError<P::QuoteError>>
+where
+    P::State: SqrtRatioState,
+{
+    let key = pool.get_key();
+    if let Some(index) = orders.iter().position(|order| {
+        !((order.sell_token == key.token0 && order.buy_token == key.token1)
+            || (order.sell_token == key.token1 && order.buy_token == key.token0))
+    }) {
+        return Err(ClearingError::OrderNotOnPair(index));
+    }
+
+    let current = pool.get_state().sqrt_ratio();
+    let excess_token0 = volume(orders, &eligible(orders, key, current, true))
+        >= convert(
+            volume(orders, &eligible(orders, key, current, false)),
+            current,
+            false,
+        );
+    let clear = |sqrt_ratio| {
+        clear_at(pool, orders, sqrt_ratio, excess_token0, meta).map_err(ClearingError::QuoteFailed)
+    };
+
+    if let Some(cleared) = clear(current)? {
+        return Ok(cleared);
+    }
+
+    // selling the excess token moves the price away from the pool price, and the closest price that clears is used
+    let (mut cleared_ratio, mut failed_ratio) = if excess_token0 {
+        (MIN_SQRT_RATIO, current)
+    } else {
+        (MAX_SQRT_RATIO, current)
+    };
+    let mut cleared = clear(cleared_ratio)?.ok_or(ClearingError::NoClearingPrice)?;
+    while cleared_ratio.abs_diff(failed_ratio) > U256::one() {
+        let middle = (cleared_ratio + failed_ratio) >> 1;
+        match clear(middle)? {
+            Some(at_middle) => {
+                cleared_ratio = middle;
+                cleared = at_middle;
+            }
+            None => failed_ratio = middle,
+        }
+    }
+
+    Ok(cleared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::types::{Address, Config};
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn pool(liquidity: u128) -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0: TOKEN0,
+                token1: TOKEN1,
+                config: Config::from_raw_unchecked(0, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity,
+            },
+        )
+        .unwrap()
+    }
+
+    fn sell(token0: bool, sell_amount: u128, min_buy_amount: u128) -> Order {
+        let (sell_token, buy_token) = if token0 {
+            (TOKEN0, TOKEN1)
+        } else {
+            (TOKEN1, TOKEN0)
+        };
+        Order {
+            sell_token,
+            buy_token,
+            sell_amount,
+            min_buy_amount,
+        }
+    }
+
+    // The tokens paid to and received from the orders and the pool add up to the surplus
+    fn assert_balanced(orders: &[Order], cleared: &ClearingPrice<impl Sized, impl Sized>) {
+        let mut balances = [0i128; 2];
+        for (order, fill) in orders.iter().zip(&cleared.fills) {
+            let sold = (order.sell_token == TOKEN1) as usize;
+            balances[sold] += fill.sell_amount as i128;
+            balances[1 - sold] -= fill.buy_amount as i128;
+            assert!(order.meets_limit(fill.sell_amount, fill.buy_amount) || fill.sell_amount == 0);
+        }
+        if let Some(quote) = &cleared.pool_quote {
+            let sold = quote.is_price_increasing as usize;
+            balances[sold] -= quote.consumed_amount;
+            balances[1 - sold] += quote.calculated_amount as i128;
+        }
+        assert_eq!(
+            balances,
+            [cleared.surplus0 as i128, cleared.surplus1 as i128]
+        );
+    }
+
+    #[test]
+    fn test_orders_clear_each_other_at_the_pool_price() {
+        let pool = pool(1_000_000_000);
+        let orders = [
+            sell(true, 1_000, 990),
+            sell(false, 400, 390),
+            sell(false, 600, 590),
+        ];
+
+        let cleared = compute_clearing_price(&pool, &orders, ()).unwrap();
+        assert_eq!(cleared.sqrt_ratio, U256::one() << 128);
+        assert!(cleared.pool_quote.is_none());
+        assert_eq!(
+            cleared
+                .fills
+                .iter()
+                .map(|fill| (fill.sell_amount, fill.buy_amount))
+                .collect::<Vec<_>>(),
+            [(1_000, 1_000), (400, 400), (600, 600)]
+        );
+        assert_eq!((cleared.surplus0, cleared.surplus1), (0, 0));
+    }
+
+    #[test]
+    fn test_orders_are_rationed_without_pool_liquidity() {
+        let pool = pool(0);
+        // the price of token1 rises until the first seller of token1 drops out, after which token0 is in excess
+        let orders = [
+            sell(true, 1_000, 900),
+            sell(false, 400, 390),
+            sell(false, 800, 700),
+        ];
+
+        let cleared = compute_clearing_price(&pool, &orders, ()).unwrap();
+        assert!(cleared.pool_quote.is_none());
+        assert_eq!(cleared.fills[1], ClearingFill::default());
+        assert_eq!(cleared.fills[2].sell_amount, 800);
+        // the seller of token0 only sells what the remaining seller of token1 buys
+        assert_eq!(
+            cleared.fills[0].sell_amount,
+            convert(800, cleared.sqrt_ratio, false)
+        );
+        assert!(cleared.fills[0].sell_amount < 1_000);
+        assert_balanced(&orders, &cleared);
+    }
+
+    #[test]
+    fn test_excess_is_swapped_through_the_pool() {
+        let pool = pool(1_000_000);
+        let orders = [sell(true, 100_000, 80_000), sell(false, 20_000, 19_000)];
+
+        let cleared = compute_clearing_price(&pool, &orders, ()).unwrap();
+        assert!(cleared.sqrt_ratio < U256::one() << 128);
+        // every order is filled completely, and the excess of token0 is sold into the pool
+        assert_eq!(cleared.fills[0].sell_amount, 100_000);
+        assert_eq!(cleared.fills[1].sell_amount, 20_000);
+        let quote = cleared.pool_quote.unwrap();
+        assert!(!quote.is_price_increasing);
+        assert_eq!(
+            quote.consumed_amount as u128,
+            100_000 - convert(20_000, cleared.sqrt_ratio, false)
+        );
+        assert!(quote.state_after.sqrt_ratio >= cleared.sqrt_ratio);
+        assert_balanced(&orders, &cleared);
+
+        // one step closer to the pool price, the pool cannot absorb the excess
+        assert!(clear_at(&pool, &orders, cleared.sqrt_ratio + 1, true, ())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_limit_prices_bound_the_clearing_price() {
+        let pool = pool(1_000_000);
+        // the price of token1 rises past the limit of the second order, so it drops out
+        let orders = [sell(false, 100_000, 80_000), sell(false, 1_000, 999)];
+
+        let cleared = compute_clearing_price(&pool, &orders, ()).unwrap();
+        assert!(cleared.sqrt_ratio > U256::one() << 128);
+        assert_eq!(cleared.fills[1], ClearingFill::default());
+        assert_balanced(&orders, &cleared);
+
+        // the pool cannot absorb an order without a limit
+        assert!(matches!(
+            compute_clearing_price(&pool, &[sell(false, i128::MAX as u128, 0)], ()),
+            Err(ClearingError::NoClearingPrice)
+        ));
+    }
+
+    #[test]
+    fn test_order_not_on_pair() {
+        let mut order = sell(true, 1, 1);
+        order.buy_token = Address::from_raw(U256::from(3));
+        assert!(matches!(
+            compute_clearing_price(&pool(1), &[sell(true, 1, 1), order], ()),
+            Err(ClearingError::OrderNotOnPair(1))
+        ));
+    }
+}
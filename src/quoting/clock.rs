@@ -0,0 +1,128 @@
+use crate::quoting::batch::QuoteRequest;
+use crate::quoting::types::{BlockTimestamp, Pool, Quote, QuoteParams};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+// A source of the current time for quoting pools whose quotes depend on the block timestamp
+pub trait Clock {
+    fn now(&self) -> BlockTimestamp;
+}
+
+impl<F: Fn() -> BlockTimestamp> Clock for F {
+    fn now(&self) -> BlockTimestamp {
+        self()
+    }
+}
+
+// A clock that always returns the same time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedClock(pub BlockTimestamp);
+
+impl Clock for FixedClock {
+    fn now(&self) -> BlockTimestamp {
+        self.0
+    }
+}
+
+// A clock that only moves when it is explicitly set or advanced, e.g. for backtests
+#[derive(Debug, Default)]
+pub struct SimulatedClock {
+    time: AtomicU64,
+}
+
+impl SimulatedClock {
+    pub fn new(time: BlockTimestamp) -> Self {
+        Self {
+            time: AtomicU64::new(time),
+        }
+    }
+
+    pub fn set(&self, time: BlockTimestamp) {
+        self.time.store(time, Ordering::Relaxed);
+    }
+
+    // Moves the clock forward by the given number of seconds and returns the new time
+    pub fn advance(&self, seconds: u64) -> BlockTimestamp {
+        self.time.fetch_add(seconds, Ordering::Relaxed) + seconds
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> BlockTimestamp {
+        self.time.load(Ordering::Relaxed)
+    }
+}
+
+// Quotes the request against the current state of the pool at the current time of the clock
+pub fn quote_now<P, C>(
+    pool: &P,
+    request: QuoteRequest<()>,
+    clock: &C,
+) -> Result<Quote<P::Resources, P::State>, P::QuoteError>
+where
+    P: Pool<Meta = BlockTimestamp>,
+    C: Clock + ?Sized,
+{
+    pool.quote(QuoteParams {
+        token_amount: request.token_amount,
+        sqrt_ratio_limit: request.sqrt_ratio_limit,
+        max_ticks_crossed: None,
+        override_state: None,
+        meta: clock.now(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::to_sqrt_ratio;
+    use crate::math::uint::U256;
+    use crate::quoting::oracle_pool::OraclePool;
+    use crate::quoting::types::{Address, TokenAmount};
+
+    const TOKEN: Address = Address::from_raw(U256([1, 0, 0, 0]));
+
+    fn request() -> QuoteRequest<()> {
+        QuoteRequest {
+            token_amount: TokenAmount {
+                amount: 1000,
+                token: TOKEN,
+            },
+            sqrt_ratio_limit: None,
+            meta: (),
+        }
+    }
+
+    #[test]
+    fn test_simulated_clock() {
+        let clock = SimulatedClock::new(10);
+        assert_eq!(clock.now(), 10);
+        assert_eq!(clock.advance(5), 15);
+        assert_eq!(clock.now(), 15);
+        clock.set(3);
+        assert_eq!(clock.now(), 3);
+    }
+
+    #[test]
+    fn test_quote_now() {
+        let pool = OraclePool::new(
+            TOKEN,
+            U256::from(3),
+            to_sqrt_ratio(0).unwrap(),
+            1_000_000_000,
+            1,
+        )
+        .unwrap();
+
+        let quote = quote_now(&pool, request(), &FixedClock(1)).unwrap();
+        assert_eq!(quote.execution_resources.snapshots_written, 0);
+
+        let clock = SimulatedClock::new(1);
+        clock.advance(1);
+        let quote = quote_now(&pool, request(), &clock).unwrap();
+        assert_eq!(quote.execution_resources.snapshots_written, 1);
+        assert_eq!(quote.state_after.last_snapshot_time, 2);
+
+        let quote = quote_now(&pool, request(), &|| 7).unwrap();
+        assert_eq!(quote.state_after.last_snapshot_time, 7);
+    }
+}
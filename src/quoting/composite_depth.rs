@@ -0,0 +1,247 @@
+use crate::quoting::base_pool::BasePoolView;
+use crate::quoting::order_book::{order_book, OrderBookError, OrderBookLevel};
+use crate::quoting::types::{NodeKey, Pool};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+// The part of a level that is provided by a single pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DepthSource {
+    pub pool_key: NodeKey,
+    pub size: u128,
+}
+
+// A price level of the combined liquidity of all pools, with the size contributed by each pool
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DepthLevel {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub size: u128,
+    pub sources: Vec<DepthSource>,
+}
+
+// The combined order book of the pools of a pair. Bids are sorted by descending price and asks by ascending price.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompositeDepth {
+    pub bids: Vec<DepthLevel>,
+    pub asks: Vec<DepthLevel>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CompositeDepthError {
+    /// At least one pool is required.
+    NoPools,
+    /// The pool trades a different pair than the first pool.
+    PairMismatch(NodeKey),
+    /// Computing the order book of a pool failed.
+    OrderBook(NodeKey, OrderBookError),
+}
+
+fn merge_levels<'a>(
+    books: impl Iterator<Item = (NodeKey, &'a [OrderBookLevel])>,
+) -> BTreeMap<i32, DepthLevel> {
+    let mut merged: BTreeMap<i32, DepthLevel> = BTreeMap::new();
+
+    for (pool_key, levels) in books {
+        for level in levels {
+            let merged_level = merged
+                .entry(level.tick_lower)
+                .or_insert_with(|| DepthLevel {
+                    tick_lower: level.tick_lower,
+                    tick_upper: level.tick_upper,
+                    size: 0,
+                    sources: Vec::new(),
+                });
+            merged_level.size = merged_level.size.saturating_add(level.size);
+            merged_level.sources.push(DepthSource {
+                pool_key,
+                size: level.size,
+            });
+        }
+    }
+
+    merged
+}
+
+/// Merges the liquidity of several pools of the same pair, e.g. with different fees or extensions, into a single
+/// depth curve with levels of `ticks_per_level` ticks and at most `max_levels` levels per side.
+///
+/// The levels are aligned the same way for every pool, so each level is the sum of the same price range across the
+/// pools, attributed to the pools that provide it. The pools can be at different prices, in which case the bids of
+/// one pool can overlap the asks of another.
+pub fn composite_depth(
+    pools: &[BasePoolView<'_>],
+    ticks_per_level: u32,
+    max_levels: usize,
+) -> Result<CompositeDepth, CompositeDepthError> {
+    let first = pools.first().ok_or(CompositeDepthError::NoPools)?.get_key();
+
+    let books = pools
+        .iter()
+        .map(|pool| {
+            let key = *pool.get_key();
+            if (key.token0, key.token1) != (first.token0, first.token1) {
+                return Err(CompositeDepthError::PairMismatch(key));
+            }
+            // no pool contributes more than max_levels levels to the closest max_levels levels of the merged curve
+            order_book(pool, ticks_per_level, max_levels)
+                .map(|book| (key, book))
+                .map_err(|error| CompositeDepthError::OrderBook(key, error))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let bids = merge_levels(books.iter().map(|(key, book)| (*key, book.bids.as_slice())));
+    let asks = merge_levels(books.iter().map(|(key, book)| (*key, book.asks.as_slice())));
+
+    Ok(CompositeDepth {
+        bids: bids.into_values().rev().take(max_levels).collect(),
+        asks: asks.into_values().take(max_levels).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::to_sqrt_ratio;
+    use crate::math::uint::U256;
+    use crate::quoting::base_pool::BasePoolState;
+    use crate::quoting::types::{Address, Config, Tick};
+    use alloc::vec;
+
+    fn key(fee: u64, token1: u64) -> NodeKey {
+        NodeKey {
+            token0: Address::from_raw(U256::one()),
+            token1: Address::from_raw(U256::from(token1)),
+            config: Config::from_raw_unchecked(fee, 100, U256::zero()),
+        }
+    }
+
+    fn ticks(lower: i32, upper: i32, liquidity: i128) -> Vec<Tick> {
+        vec![
+            Tick {
+                index: lower,
+                liquidity_delta: liquidity,
+            },
+            Tick {
+                index: upper,
+                liquidity_delta: -liquidity,
+            },
+        ]
+    }
+
+    fn view(key: NodeKey, tick: i32, liquidity: u128, ticks: &[Tick]) -> BasePoolView<'_> {
+        BasePoolView::new(
+            key,
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(tick).unwrap(),
+                liquidity,
+                active_tick_index: Some(0),
+            },
+            ticks,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_composite_depth_attributes_sources() {
+        let (ticks_a, ticks_b) = (ticks(-1000, 1000, 1_000_000), ticks(-200, 200, 5_000_000));
+        let pools = [
+            view(key(0, 2), 0, 1_000_000, &ticks_a),
+            view(key(1 << 60, 2), 0, 5_000_000, &ticks_b),
+        ];
+        let books: Vec<_> = pools
+            .iter()
+            .map(|pool| order_book(pool, 500, 10).unwrap())
+            .collect();
+
+        let depth = composite_depth(&pools, 500, 10).unwrap();
+
+        assert_eq!(depth.asks.len(), 2);
+        assert_eq!(
+            depth.asks[0],
+            DepthLevel {
+                tick_lower: 0,
+                tick_upper: 500,
+                size: books[0].asks[0].size + books[1].asks[0].size,
+                sources: vec![
+                    DepthSource {
+                        pool_key: key(0, 2),
+                        size: books[0].asks[0].size,
+                    },
+                    DepthSource {
+                        pool_key: key(1 << 60, 2),
+                        size: books[1].asks[0].size,
+                    },
+                ],
+            }
+        );
+        // only the wider pool has liquidity beyond tick 200
+        assert_eq!(depth.asks[1].sources.len(), 1);
+        assert_eq!(depth.asks[1].size, books[0].asks[1].size);
+
+        assert_eq!(
+            depth
+                .bids
+                .iter()
+                .map(|level| level.tick_lower)
+                .collect::<Vec<_>>(),
+            [-500, -1000]
+        );
+        assert_eq!(depth.bids[0].sources.len(), 2);
+    }
+
+    #[test]
+    fn test_composite_depth_truncates_to_max_levels() {
+        let (ticks_a, ticks_b) = (ticks(-1000, 1000, 1_000_000), ticks(-1000, 1000, 1_000_000));
+        // the second pool trades at a higher price
+        let pools = [
+            view(key(0, 2), 0, 1_000_000, &ticks_a),
+            view(key(1, 2), 600, 1_000_000, &ticks_b),
+        ];
+
+        let depth = composite_depth(&pools, 100, 3).unwrap();
+        assert_eq!(
+            depth
+                .asks
+                .iter()
+                .map(|level| (level.tick_lower, level.sources.len()))
+                .collect::<Vec<_>>(),
+            [(0, 1), (100, 1), (200, 1)]
+        );
+        assert_eq!(
+            depth
+                .bids
+                .iter()
+                .map(|level| (level.tick_lower, level.sources.len()))
+                .collect::<Vec<_>>(),
+            [(500, 1), (400, 1), (300, 1)]
+        );
+    }
+
+    #[test]
+    fn test_composite_depth_errors() {
+        let ticks = ticks(-1000, 1000, 1_000_000);
+        assert_eq!(
+            composite_depth(&[], 100, 3),
+            Err(CompositeDepthError::NoPools)
+        );
+        assert_eq!(
+            composite_depth(
+                &[
+                    view(key(0, 2), 0, 1_000_000, &ticks),
+                    view(key(0, 3), 0, 1_000_000, &ticks)
+                ],
+                100,
+                3
+            ),
+            Err(CompositeDepthError::PairMismatch(key(0, 3)))
+        );
+        assert_eq!(
+            composite_depth(&[view(key(0, 2), 0, 1_000_000, &ticks)], 0, 3),
+            Err(CompositeDepthError::OrderBook(
+                key(0, 2),
+                OrderBookError::InvalidLevelWidth
+            ))
+        );
+    }
+}
@@ -1,4 +1,22 @@
 use crate::math::uint::U256;
+use crate::quoting::types::Address;
 
 // This token is used to represent the native token in Ekubo on any EVM chain
-pub const NATIVE_TOKEN_ADDRESS: U256 = U256([0, 0, 0, 0]);
+pub const NATIVE_TOKEN_ADDRESS: Address = Address::from_raw(U256([0, 0, 0, 0]));
+
+// The conventions of the chain that the pools are deployed on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChainConfig {
+    // The token that oracle pools are paired with and that is sent with transactions instead of approved, e.g. a
+    // wrapped native token on chains where the pools use it
+    pub native_token: Address,
+}
+
+impl Default for ChainConfig {
+    fn default() -> Self {
+        Self {
+            native_token: NATIVE_TOKEN_ADDRESS,
+        }
+    }
+}
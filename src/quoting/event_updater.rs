@@ -0,0 +1,421 @@
+use crate::quoting::events::{ApplyEventError, ApplyPoolEvent, EventPosition, PoolLog};
+use crate::quoting::pool_map::{BlockDelta, PoolMap};
+use crate::quoting::types::Pool;
+use alloc::collections::VecDeque;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum EventUpdaterError {
+    /// Logs must be processed in strictly increasing position order.
+    LogOutOfOrder {
+        position: EventPosition,
+        previous: EventPosition,
+    },
+    /// The event of the log at the position could not be applied to its pool. All logs before it were applied.
+    ApplyEventFailed {
+        position: EventPosition,
+        error: ApplyEventError,
+    },
+    /// The rollback reverts a block whose delta was already discarded.
+    ReorgTooDeep {
+        block_number: u64,
+        last_discarded_block: u64,
+    },
+}
+
+#[derive(Clone, Debug)]
+struct RetainedBlock<P> {
+    checkpoint_before: Option<EventPosition>,
+    delta: BlockDelta<P>,
+}
+
+// Keeps a set of pools up to date by applying the logs of the core contract in order.
+//
+// The checkpoint is the position of the last applied log. Logs at or before the checkpoint are skipped, so after a
+// crash the processing can be resumed from a persisted copy of the pools and checkpoint by replaying the logs from
+// the start of the checkpoint block, and the resulting states are the same as if the processing never stopped.
+//
+// To handle reorgs, the deltas of the most recent `reorg_depth` blocks with logs are retained so that the pools can
+// be rolled back to the state at the end of any of those blocks.
+#[derive(Clone, Debug)]
+pub struct EventUpdater<P> {
+    pools: PoolMap<P>,
+    checkpoint: Option<EventPosition>,
+    reorg_depth: usize,
+    retained_blocks: VecDeque<RetainedBlock<P>>,
+    // The block number of the most recent delta that was discarded, blocks after it can still be reverted
+    last_discarded_block: Option<u64>,
+}
+
+impl<P: Pool + ApplyPoolEvent> EventUpdater<P> {
+    // Creates an updater for the pools, whose states reflect all logs up to and including the checkpoint
+    pub fn new(pools: PoolMap<P>, checkpoint: Option<EventPosition>) -> Self {
+        Self::with_reorg_depth(pools, checkpoint, 0)
+    }
+
+    // Same as new, but retains the deltas of the last `reorg_depth` blocks with logs for rolling back
+    pub fn with_reorg_depth(
+        pools: PoolMap<P>,
+        checkpoint: Option<EventPosition>,
+        reorg_depth: usize,
+    ) -> Self {
+        Self {
+            pools,
+            checkpoint,
+            reorg_depth,
+            retained_blocks: VecDeque::with_capacity(reorg_depth),
+            // blocks before the initial checkpoint cannot be reverted
+            last_discarded_block: checkpoint.map(|checkpoint| checkpoint.block_number),
+        }
+    }
+
+    pub fn pools(&self) -> &PoolMap<P> {
+        &self.pools
+    }
+
+    pub fn checkpoint(&self) -> Option<EventPosition> {
+        self.checkpoint
+    }
+
+    pub fn into_parts(self) -> (PoolMap<P>, Option<EventPosition>) {
+        (self.pools, self.checkpoint)
+    }
+
+    // Applies the logs that come after the checkpoint. Logs of pools that are not tracked only advance the checkpoint.
+    pub fn process_batch(&mut self, logs: &[PoolLog]) -> Result<(), EventUpdaterError> {
+        check_order(logs)?;
+
+        for log in logs {
+            if let Some(checkpoint) = self.checkpoint {
+                if log.position <= checkpoint {
+                    continue;
+                }
+            }
+
+            if self.pools.contains_key(&log.pool_key) {
+                if self.reorg_depth == 0 {
+                    self.last_discarded_block = Some(log.position.block_number);
+                } else {
+                    if self.retained_blocks.back().is_none_or(|retained| {
+                        retained.delta.block_number != log.position.block_number
+                    }) {
+                        if self.retained_blocks.len() == self.reorg_depth {
+                            self.last_discarded_block = self
+                                .retained_blocks
+                                .pop_front()
+                                .map(|retained| retained.delta.block_number);
+                        }
+                        self.retained_blocks.push_back(RetainedBlock {
+                            checkpoint_before: self.checkpoint,
+                            delta: BlockDelta::new(log.position.block_number),
+                        });
+                    }
+
+                    if let Some(retained) = self.retained_blocks.back_mut() {
+                        self.pools
+                            .record_previous(&mut retained.delta, &log.pool_key);
+                    }
+                }
+
+                if let Some(pool) = self.pools.get_mut(&log.pool_key) {
+                    pool.apply_event(&log.event).map_err(|error| {
+                        EventUpdaterError::ApplyEventFailed {
+                            position: log.position,
+                            error,
+                        }
+                    })?;
+                }
+            }
+
+            self.checkpoint = Some(log.position);
+        }
+
+        Ok(())
+    }
+
+    // Reverts the logs of all blocks after the given block, e.g. because they were removed from the chain by a reorg.
+    // The logs of the new blocks can then be processed as usual.
+    pub fn rollback_to(&mut self, block_number: u64) -> Result<(), EventUpdaterError> {
+        if let Some(last_discarded_block) = self.last_discarded_block {
+            if last_discarded_block > block_number {
+                return Err(EventUpdaterError::ReorgTooDeep {
+                    block_number,
+                    last_discarded_block,
+                });
+            }
+        }
+
+        while let Some(retained) = self.retained_blocks.pop_back() {
+            if retained.delta.block_number <= block_number {
+                self.retained_blocks.push_back(retained);
+                break;
+            }
+
+            self.pools.revert(retained.delta);
+            self.checkpoint = retained.checkpoint_before;
+        }
+
+        // logs of untracked pools after the block may have advanced the checkpoint without a delta
+        if self
+            .checkpoint
+            .is_some_and(|checkpoint| checkpoint.block_number > block_number)
+        {
+            self.checkpoint = Some(EventPosition {
+                block_number,
+                log_index: u32::MAX,
+            });
+        }
+
+        Ok(())
+    }
+
+    // Processes the batches in order, calling `on_checkpoint` after each batch so the caller can persist the progress
+    pub fn backfill<'a, I, F>(
+        &mut self,
+        batches: I,
+        mut on_checkpoint: F,
+    ) -> Result<(), EventUpdaterError>
+    where
+        I: IntoIterator<Item = &'a [PoolLog]>,
+        F: FnMut(&PoolMap<P>, Option<EventPosition>),
+    {
+        for batch in batches {
+            self.process_batch(batch)?;
+            on_checkpoint(&self.pools, self.checkpoint);
+        }
+
+        Ok(())
+    }
+}
+
+fn check_order(logs: &[PoolLog]) -> Result<(), EventUpdaterError> {
+    logs.windows(2).try_for_each(|pair| {
+        if pair[0].position < pair[1].position {
+            Ok(())
+        } else {
+            Err(EventUpdaterError::LogOutOfOrder {
+                position: pair[1].position,
+                previous: pair[0].position,
+            })
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::to_sqrt_ratio;
+    use crate::math::uint::U256;
+    use crate::quoting::base_pool::{BasePool, BasePoolState};
+    use crate::quoting::events::PoolEvent;
+    use crate::quoting::types::{Address, Config, NodeKey, Tick};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn key() -> NodeKey {
+        NodeKey {
+            token0: Address::from_raw(U256::from(1)),
+            token1: Address::from_raw(U256::from(2)),
+            config: Config::from_raw_unchecked(0, 10, U256::zero()),
+        }
+    }
+
+    fn pools() -> PoolMap<BasePool> {
+        [BasePool::new(
+            key(),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                liquidity: 1_000,
+                active_tick_index: Some(0),
+            },
+            vec![
+                Tick {
+                    index: -100,
+                    liquidity_delta: 1_000,
+                },
+                Tick {
+                    index: 100,
+                    liquidity_delta: -1_000,
+                },
+            ],
+        )
+        .unwrap()]
+        .into_iter()
+        .collect()
+    }
+
+    fn log(block_number: u64, log_index: u32, event: PoolEvent) -> PoolLog {
+        PoolLog {
+            position: EventPosition {
+                block_number,
+                log_index,
+            },
+            pool_key: key(),
+            event,
+        }
+    }
+
+    fn logs() -> Vec<PoolLog> {
+        vec![
+            log(
+                1,
+                0,
+                PoolEvent::PositionUpdated {
+                    tick_lower: -50,
+                    tick_upper: 50,
+                    liquidity_delta: 500,
+                },
+            ),
+            log(
+                1,
+                3,
+                PoolEvent::Swapped {
+                    sqrt_ratio_after: to_sqrt_ratio(60).unwrap(),
+                    tick_after: 60,
+                    liquidity_after: 1_000,
+                },
+            ),
+            log(
+                2,
+                1,
+                PoolEvent::PositionUpdated {
+                    tick_lower: 50,
+                    tick_upper: 200,
+                    liquidity_delta: 200,
+                },
+            ),
+            log(
+                3,
+                0,
+                PoolEvent::Swapped {
+                    sqrt_ratio_after: to_sqrt_ratio(-10).unwrap(),
+                    tick_after: -10,
+                    liquidity_after: 1_500,
+                },
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_process_batch() {
+        let mut updater = EventUpdater::new(pools(), None);
+        updater.process_batch(&logs()).unwrap();
+
+        assert_eq!(
+            updater.checkpoint(),
+            Some(EventPosition {
+                block_number: 3,
+                log_index: 0
+            })
+        );
+        let pool = updater.pools().get(&key()).unwrap();
+        assert_eq!(pool.get_state().liquidity, 1_500);
+        assert_eq!(pool.get_state().active_tick_index, Some(1));
+        assert_eq!(pool.get_sorted_ticks().len(), 5);
+    }
+
+    #[test]
+    fn test_resume_after_crash() {
+        let logs = logs();
+
+        let mut uninterrupted = EventUpdater::new(pools(), None);
+        uninterrupted.backfill([&logs[..]], |_, _| {}).unwrap();
+
+        // the last persisted checkpoint is after the first two logs, and the logs are replayed from block 1
+        let mut persisted = None;
+        let mut updater = EventUpdater::new(pools(), None);
+        updater
+            .backfill([&logs[..1], &logs[1..2]], |pools, checkpoint| {
+                persisted = Some((pools.clone(), checkpoint));
+            })
+            .unwrap();
+
+        let (pools, checkpoint) = persisted.unwrap();
+        let mut resumed = EventUpdater::new(pools, checkpoint);
+        resumed.backfill([&logs[..]], |_, _| {}).unwrap();
+
+        assert_eq!(resumed.pools(), uninterrupted.pools());
+        assert_eq!(resumed.checkpoint(), uninterrupted.checkpoint());
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut logs = logs();
+        logs.swap(0, 1);
+        let mut updater = EventUpdater::new(pools(), None);
+        assert_eq!(
+            updater.process_batch(&logs),
+            Err(EventUpdaterError::LogOutOfOrder {
+                position: logs[1].position,
+                previous: logs[0].position,
+            })
+        );
+        assert_eq!(updater.checkpoint(), None);
+
+        let invalid = log(
+            1,
+            0,
+            PoolEvent::PositionUpdated {
+                tick_lower: -55,
+                tick_upper: 50,
+                liquidity_delta: 1,
+            },
+        );
+        assert_eq!(
+            updater.process_batch(&[invalid]),
+            Err(EventUpdaterError::ApplyEventFailed {
+                position: invalid.position,
+                error: ApplyEventError::InvalidPositionBounds,
+            })
+        );
+    }
+
+    #[test]
+    fn test_rollback() {
+        let logs = logs();
+
+        let mut expected = EventUpdater::new(pools(), None);
+        expected.process_batch(&logs[..2]).unwrap();
+
+        let mut updater = EventUpdater::with_reorg_depth(pools(), None, 3);
+        updater.process_batch(&logs).unwrap();
+        updater.rollback_to(1).unwrap();
+
+        assert_eq!(updater.pools(), expected.pools());
+        assert_eq!(updater.checkpoint(), expected.checkpoint());
+
+        // the replacement blocks are processed as usual
+        updater.process_batch(&logs[2..]).unwrap();
+        expected.process_batch(&logs[2..]).unwrap();
+        assert_eq!(updater.pools(), expected.pools());
+
+        updater.rollback_to(0).unwrap();
+        assert_eq!(updater.pools(), &pools());
+        assert_eq!(updater.checkpoint(), None);
+    }
+
+    #[test]
+    fn test_rollback_too_deep() {
+        let mut updater = EventUpdater::with_reorg_depth(pools(), None, 2);
+        updater.process_batch(&logs()).unwrap();
+
+        // only blocks 2 and 3 are retained
+        assert_eq!(
+            updater.rollback_to(0),
+            Err(EventUpdaterError::ReorgTooDeep {
+                block_number: 0,
+                last_discarded_block: 1,
+            })
+        );
+        updater.rollback_to(1).unwrap();
+
+        let mut updater = EventUpdater::new(pools(), None);
+        updater.process_batch(&logs()).unwrap();
+        assert_eq!(
+            updater.rollback_to(2),
+            Err(EventUpdaterError::ReorgTooDeep {
+                block_number: 2,
+                last_discarded_block: 3,
+            })
+        );
+    }
+}
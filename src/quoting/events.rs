@@ -0,0 +1,49 @@
+use crate::math::uint::U256;
+use crate::quoting::types::NodeKey;
+
+// The position of a log in the chain, used to order events and to checkpoint their processing
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventPosition {
+    pub block_number: u64,
+    pub log_index: u32,
+}
+
+// A change to the state of a pool emitted by the core contract
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolEvent {
+    Swapped {
+        sqrt_ratio_after: U256,
+        tick_after: i32,
+        liquidity_after: u128,
+    },
+    PositionUpdated {
+        tick_lower: i32,
+        tick_upper: i32,
+        liquidity_delta: i128,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolLog {
+    pub position: EventPosition,
+    pub pool_key: NodeKey,
+    pub event: PoolEvent,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ApplyEventError {
+    /// The event is not a valid change for the type of pool.
+    UnsupportedEvent,
+    /// The bounds of the updated position are not valid for the pool.
+    InvalidPositionBounds,
+    /// The update would make the liquidity of the pool negative or overflow.
+    LiquidityOverflow,
+    /// The state after the event is not a valid state of the pool.
+    InvalidStateAfter,
+}
+
+// Pools that can be kept up to date by applying the events of the core contract
+pub trait ApplyPoolEvent {
+    fn apply_event(&mut self, event: &PoolEvent) -> Result<(), ApplyEventError>;
+}
@@ -0,0 +1,288 @@
+use crate::math::muldiv::muldiv;
+use crate::math::uint::U256;
+use crate::quoting::bps::Bps;
+use crate::quoting::types::Address;
+
+// The amounts and gas a swap was quoted with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionExpectation {
+    pub amount_in: u128,
+    pub amount_out: u128,
+    pub gas: u64,
+}
+
+// The amounts and gas of the executed swap, as read from its receipt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionResult {
+    pub amount_in: u128,
+    pub amount_out: u128,
+    pub gas_used: u64,
+    // The effective gas price in wei per gas
+    pub gas_price: u128,
+}
+
+// The quote-time expectations of a swap next to the results of its execution
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionRecord {
+    pub token_in: Address,
+    pub token_out: Address,
+    pub expected: ExecutionExpectation,
+    pub actual: ExecutionResult,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExecutionRecordError {
+    /// The expectations of the swap were not set.
+    MissingExpectation,
+    /// The results of the execution were not set.
+    MissingResult,
+    /// The input and output tokens are the same.
+    SameToken,
+}
+
+// Builds a record from the expectations at quote time and the results once the swap is executed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExecutionRecordBuilder {
+    token_in: Address,
+    token_out: Address,
+    expected: Option<ExecutionExpectation>,
+    actual: Option<ExecutionResult>,
+}
+
+impl ExecutionRecordBuilder {
+    pub fn new(token_in: Address, token_out: Address) -> Self {
+        Self {
+            token_in,
+            token_out,
+            expected: None,
+            actual: None,
+        }
+    }
+
+    pub fn with_expected(mut self, amount_in: u128, amount_out: u128, gas: u64) -> Self {
+        self.expected = Some(ExecutionExpectation {
+            amount_in,
+            amount_out,
+            gas,
+        });
+        self
+    }
+
+    pub fn with_actual(
+        mut self,
+        amount_in: u128,
+        amount_out: u128,
+        gas_used: u64,
+        gas_price: u128,
+    ) -> Self {
+        self.actual = Some(ExecutionResult {
+            amount_in,
+            amount_out,
+            gas_used,
+            gas_price,
+        });
+        self
+    }
+
+    pub fn build(self) -> Result<ExecutionRecord, ExecutionRecordError> {
+        if self.token_in == self.token_out {
+            return Err(ExecutionRecordError::SameToken);
+        }
+
+        Ok(ExecutionRecord {
+            token_in: self.token_in,
+            token_out: self.token_out,
+            expected: self
+                .expected
+                .ok_or(ExecutionRecordError::MissingExpectation)?,
+            actual: self.actual.ok_or(ExecutionRecordError::MissingResult)?,
+        })
+    }
+}
+
+// Returns the amount of output per unit of input as a 128.128 fixed point number, or None if no input was swapped
+fn price_x128(amount_in: u128, amount_out: u128) -> Option<U256> {
+    muldiv(
+        U256::from(amount_out),
+        U256::one() << 128,
+        U256::from(amount_in),
+        false,
+    )
+    .ok()
+}
+
+impl ExecutionRecord {
+    pub fn expected_price_x128(&self) -> Option<U256> {
+        price_x128(self.expected.amount_in, self.expected.amount_out)
+    }
+
+    pub fn realized_price_x128(&self) -> Option<U256> {
+        price_x128(self.actual.amount_in, self.actual.amount_out)
+    }
+
+    /// Returns the realized slippage of the execution in basis points of the expected price, positive if the swap
+    /// received less output per unit of input than quoted and negative if it received more.
+    ///
+    /// The loss is rounded up and the improvement rounded down, so that execution quality is never overstated.
+    /// Returns None if the expected swap has no output or the actual swap has no input.
+    pub fn realized_slippage_bps(&self) -> Option<i64> {
+        if self.expected.amount_out == 0 || self.actual.amount_in == 0 {
+            return None;
+        }
+
+        // the prices are compared by cross multiplication, which cannot overflow for 128 bit amounts
+        let realized = U256::from(self.actual.amount_out) * U256::from(self.expected.amount_in);
+        let expected = U256::from(self.expected.amount_out) * U256::from(self.actual.amount_in);
+
+        if realized <= expected {
+            Bps::ratio(expected - realized, expected, true).map(|bps| i64::from(bps.raw()))
+        } else {
+            Bps::ratio(realized - expected, expected, false).map(|bps| -i64::from(bps.raw()))
+        }
+    }
+
+    // Returns the cost of the gas used by the execution in wei
+    pub fn gas_cost(&self) -> u128 {
+        (self.actual.gas_used as u128).saturating_mul(self.actual.gas_price)
+    }
+
+    // Returns the gas used beyond the expected gas, negative if the execution used less
+    pub fn gas_overrun(&self) -> i128 {
+        self.actual.gas_used as i128 - self.expected.gas as i128
+    }
+}
+
+// Aggregated execution quality over many records
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExecutionSummary {
+    pub executions: u64,
+    // The number of executions with a defined slippage, which the slippage statistics are computed over
+    pub priced_executions: u64,
+    pub total_slippage_bps: i64,
+    // The largest slippage of any execution, or None if no execution had a defined slippage
+    pub worst_slippage_bps: Option<i64>,
+    pub total_gas_used: u64,
+    pub total_gas_cost: u128,
+}
+
+impl ExecutionSummary {
+    pub fn add(&mut self, record: &ExecutionRecord) {
+        self.executions += 1;
+        if let Some(slippage) = record.realized_slippage_bps() {
+            self.priced_executions += 1;
+            self.total_slippage_bps = self.total_slippage_bps.saturating_add(slippage);
+            self.worst_slippage_bps = Some(
+                self.worst_slippage_bps
+                    .map_or(slippage, |worst| worst.max(slippage)),
+            );
+        }
+        self.total_gas_used = self.total_gas_used.saturating_add(record.actual.gas_used);
+        self.total_gas_cost = self.total_gas_cost.saturating_add(record.gas_cost());
+    }
+
+    // Combines the summaries of disjoint sets of records, e.g. of different tokens or time windows
+    pub fn merge(&mut self, other: &ExecutionSummary) {
+        self.executions += other.executions;
+        self.priced_executions += other.priced_executions;
+        self.total_slippage_bps = self
+            .total_slippage_bps
+            .saturating_add(other.total_slippage_bps);
+        self.worst_slippage_bps = match (self.worst_slippage_bps, other.worst_slippage_bps) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        self.total_gas_used = self.total_gas_used.saturating_add(other.total_gas_used);
+        self.total_gas_cost = self.total_gas_cost.saturating_add(other.total_gas_cost);
+    }
+
+    // Returns the mean slippage of the priced executions in basis points, rounded toward zero
+    pub fn mean_slippage_bps(&self) -> Option<i64> {
+        if self.priced_executions == 0 {
+            return None;
+        }
+        Some(self.total_slippage_bps / self.priced_executions as i64)
+    }
+}
+
+impl<'a> FromIterator<&'a ExecutionRecord> for ExecutionSummary {
+    fn from_iter<T: IntoIterator<Item = &'a ExecutionRecord>>(iter: T) -> Self {
+        let mut summary = Self::default();
+        for record in iter {
+            summary.add(record);
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn record(actual_out: u128, gas_used: u64) -> ExecutionRecord {
+        ExecutionRecordBuilder::new(TOKEN0, TOKEN1)
+            .with_expected(1_000, 2_000, 100_000)
+            .with_actual(1_000, actual_out, gas_used, 10)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_realized_slippage() {
+        assert_eq!(record(2_000, 0).realized_slippage_bps(), Some(0));
+        assert_eq!(record(1_990, 0).realized_slippage_bps(), Some(50));
+        assert_eq!(record(2_001, 0).realized_slippage_bps(), Some(-5));
+        // a partial fill at the expected price has no slippage
+        let partial = ExecutionRecordBuilder::new(TOKEN0, TOKEN1)
+            .with_expected(1_000, 2_000, 100_000)
+            .with_actual(500, 1_000, 100_000, 10)
+            .build()
+            .unwrap();
+        assert_eq!(partial.realized_slippage_bps(), Some(0));
+        assert_eq!(partial.realized_price_x128(), partial.expected_price_x128());
+        assert_eq!(record(0, 0).realized_slippage_bps(), Some(10_000));
+
+        let unfilled = ExecutionRecordBuilder::new(TOKEN0, TOKEN1)
+            .with_expected(1_000, 2_000, 100_000)
+            .with_actual(0, 0, 21_000, 10)
+            .build()
+            .unwrap();
+        assert_eq!(unfilled.realized_slippage_bps(), None);
+
+        assert_eq!(
+            ExecutionRecordBuilder::new(TOKEN0, TOKEN1)
+                .with_expected(1_000, 2_000, 100_000)
+                .build(),
+            Err(ExecutionRecordError::MissingResult)
+        );
+        assert_eq!(
+            ExecutionRecordBuilder::new(TOKEN0, TOKEN0).build(),
+            Err(ExecutionRecordError::SameToken)
+        );
+    }
+
+    #[test]
+    fn test_summary() {
+        let records = [record(2_000, 90_000), record(1_990, 120_000)];
+        let mut summary: ExecutionSummary = records.iter().collect();
+
+        assert_eq!(summary.executions, 2);
+        assert_eq!(summary.worst_slippage_bps, Some(50));
+        assert_eq!(summary.mean_slippage_bps(), Some(25));
+        assert_eq!(summary.total_gas_used, 210_000);
+        assert_eq!(summary.total_gas_cost, 2_100_000);
+        assert_eq!(records[1].gas_overrun(), 20_000);
+
+        summary.merge(&[record(2_002, 100_000)].iter().collect());
+        assert_eq!(summary.executions, 3);
+        assert_eq!(summary.worst_slippage_bps, Some(50));
+        assert_eq!(summary.mean_slippage_bps(), Some(13));
+        assert_eq!(ExecutionSummary::default().mean_slippage_bps(), None);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::math::uint::{u256_to_float_base_x128, U256};
+use crate::quoting::types::{Address, NodeKey, Pool, Quote, QuoteParams, SqrtRatioState};
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+
+// A human readable breakdown of a quote, for logging and support
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuoteExplanation {
+    pub pool_key: NodeKey,
+    pub is_exact_output: bool,
+    pub token_in: Address,
+    pub amount_in: u128,
+    pub token_out: Address,
+    pub amount_out: u128,
+    // Fees are always paid in the input token
+    pub fees_paid: u128,
+    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
+    pub sqrt_ratio_before: U256,
+    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
+    pub sqrt_ratio_after: U256,
+    // Prices in token1 per token0, ignoring the decimals of the tokens
+    pub price_before: f64,
+    pub price_after: f64,
+    // Whether the price stopped at the sqrt ratio limit of the quote
+    pub limit_reached: bool,
+    // Whether less than the specified amount was swapped, because of the limit or because the pool ran out of liquidity
+    pub partial_fill: bool,
+    // The debug representation of the execution resources of the pool
+    pub resources: String,
+}
+
+fn price(sqrt_ratio: U256) -> f64 {
+    let sqrt_price = u256_to_float_base_x128(sqrt_ratio);
+    sqrt_price * sqrt_price
+}
+
+/// Explains the quote that the pool returned for the params, with the price before the swap taken from the override
+/// state of the params or the current state of the pool.
+pub fn explain<P: Pool>(
+    pool: &P,
+    params: &QuoteParams<P::State, P::Meta>,
+    quote: &Quote<P::Resources, P::State>,
+) -> QuoteExplanation
+where
+    P::State: SqrtRatioState,
+{
+    let key = *pool.get_key();
+    let specified = params.token_amount;
+    let other = if specified.token == key.token0 {
+        key.token1
+    } else {
+        key.token0
+    };
+    let is_exact_output = specified.amount < 0;
+    let consumed_amount = quote.consumed_amount.unsigned_abs();
+
+    let (token_in, amount_in, token_out, amount_out) = if is_exact_output {
+        (
+            other,
+            quote.calculated_amount,
+            specified.token,
+            consumed_amount,
+        )
+    } else {
+        (
+            specified.token,
+            consumed_amount,
+            other,
+            quote.calculated_amount,
+        )
+    };
+
+    let sqrt_ratio_before = params
+        .override_state
+        .unwrap_or_else(|| pool.get_state())
+        .sqrt_ratio();
+    let sqrt_ratio_after = quote.state_after.sqrt_ratio();
+
+    QuoteExplanation {
+        pool_key: key,
+        is_exact_output,
+        token_in,
+        amount_in,
+        token_out,
+        amount_out,
+        fees_paid: quote.fees_paid,
+        sqrt_ratio_before,
+        sqrt_ratio_after,
+        price_before: price(sqrt_ratio_before),
+        price_after: price(sqrt_ratio_after),
+        limit_reached: params.sqrt_ratio_limit == Some(sqrt_ratio_after),
+        partial_fill: quote.consumed_amount != specified.amount,
+        resources: format!("{:?}", quote.execution_resources),
+    }
+}
+
+impl QuoteExplanation {
+    // Serializes the explanation as a single line of JSON
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        // the explanation only contains strings, numbers and booleans, so serialization cannot fail
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+impl fmt::Display for QuoteExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = &self.pool_key;
+        writeln!(
+            f,
+            "pool: {:#x}/{:#x} fee {} tick spacing {} extension {:#x}",
+            key.token0,
+            key.token1,
+            key.config.fee.raw(),
+            key.config.tick_spacing.raw(),
+            key.config.extension
+        )?;
+        writeln!(
+            f,
+            "swap: exact {}",
+            if self.is_exact_output {
+                "output"
+            } else {
+                "input"
+            }
+        )?;
+        writeln!(f, "  in: {} of {:#x}", self.amount_in, self.token_in)?;
+        writeln!(f, "  out: {} of {:#x}", self.amount_out, self.token_out)?;
+        writeln!(f, "  fees: {} of {:#x}", self.fees_paid, self.token_in)?;
+        writeln!(
+            f,
+            "price: {} -> {} (sqrt ratio {:#x} -> {:#x})",
+            self.price_before, self.price_after, self.sqrt_ratio_before, self.sqrt_ratio_after
+        )?;
+        writeln!(
+            f,
+            "limit reached: {}, partial fill: {}",
+            self.limit_reached, self.partial_fill
+        )?;
+        write!(f, "resources: {}", self.resources)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::types::{Config, TokenAmount};
+    use alloc::string::ToString;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn pool() -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0: TOKEN0,
+                token1: TOKEN1,
+                config: Config::from_raw_unchecked(1 << 56, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 1_000_000_000,
+            },
+        )
+        .unwrap()
+    }
+
+    fn params(
+        token: Address,
+        amount: i128,
+        sqrt_ratio_limit: Option<U256>,
+    ) -> QuoteParams<FullRangePoolState, ()> {
+        QuoteParams {
+            token_amount: TokenAmount { token, amount },
+            sqrt_ratio_limit,
+            max_ticks_crossed: None,
+            override_state: None,
+            meta: (),
+        }
+    }
+
+    #[test]
+    fn test_explain_exact_input() {
+        let pool = pool();
+        let params = params(TOKEN0, 1_000_000, None);
+        let quote = pool.quote(params).unwrap();
+        let explanation = explain(&pool, &params, &quote);
+
+        assert!(!explanation.is_exact_output);
+        assert_eq!(
+            (explanation.token_in, explanation.amount_in),
+            (TOKEN0, 1_000_000)
+        );
+        assert_eq!(
+            (explanation.token_out, explanation.amount_out),
+            (TOKEN1, quote.calculated_amount)
+        );
+        assert_eq!(explanation.fees_paid, quote.fees_paid);
+        assert_eq!(explanation.price_before, 1f64);
+        assert!(explanation.price_after < 1f64);
+        assert!(!explanation.limit_reached && !explanation.partial_fill);
+
+        assert_eq!(
+            explanation.to_string(),
+            alloc::format!(
+                "pool: 0x1/0x2 fee 72057594037927936 tick spacing 0 extension 0x0\n\
+                 swap: exact input\n  in: 1000000 of 0x1\n  out: {} of 0x2\n  fees: {} of 0x1\n\
+                 price: 1 -> {} (sqrt ratio 0x100000000000000000000000000000000 -> {:#x})\n\
+                 limit reached: false, partial fill: false\n\
+                 resources: {:?}",
+                quote.calculated_amount,
+                quote.fees_paid,
+                explanation.price_after,
+                quote.state_after.sqrt_ratio,
+                quote.execution_resources
+            )
+        );
+    }
+
+    #[test]
+    fn test_explain_exact_output_with_limit() {
+        let pool = pool();
+        let limit = (U256::one() << 128) + (U256::one() << 120);
+        let params = params(TOKEN0, -1_000_000_000, Some(limit));
+        let quote = pool.quote(params).unwrap();
+        let explanation = explain(&pool, &params, &quote);
+
+        assert!(explanation.is_exact_output);
+        assert_eq!(explanation.token_in, TOKEN1);
+        assert_eq!(explanation.token_out, TOKEN0);
+        assert_eq!(explanation.amount_in, quote.calculated_amount);
+        assert_eq!(explanation.amount_out, quote.consumed_amount.unsigned_abs());
+        assert!(explanation.limit_reached && explanation.partial_fill);
+        assert!(explanation.price_after > explanation.price_before);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_explanation_json() {
+        let pool = pool();
+        let params = params(TOKEN1, 1_000, None);
+        let explanation = explain(&pool, &params, &pool.quote(params).unwrap());
+
+        let json: serde_json::Value = serde_json::from_str(&explanation.to_json()).unwrap();
+        assert_eq!(
+            json["token_in"],
+            "0x0000000000000000000000000000000000000002"
+        );
+        assert_eq!(json["amount_in"], 1_000);
+        assert_eq!(json["price_before"], 1f64);
+        assert_eq!(json["partial_fill"], false);
+        assert_eq!(
+            serde_json::from_value::<QuoteExplanation>(json).unwrap(),
+            explanation
+        );
+    }
+}
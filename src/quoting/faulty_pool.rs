@@ -0,0 +1,444 @@
+use crate::quoting::tick_provider::TickProvider;
+use crate::quoting::types::{Address, NodeKey, Pool, Quote, QuoteParams, Tick, TokenAmount};
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+// The quotes a fault is injected into, decided by the quoted token amount so that failures are reproducible
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaultTrigger {
+    Always,
+    Token(Address),
+    // Quotes of at least the absolute amount, e.g. to make only large swaps fail
+    AmountAtLeast(u128),
+}
+
+impl FaultTrigger {
+    fn matches(&self, token_amount: &TokenAmount) -> bool {
+        match *self {
+            FaultTrigger::Always => true,
+            FaultTrigger::Token(token) => token_amount.token == token,
+            FaultTrigger::AmountAtLeast(amount) => token_amount.amount.unsigned_abs() >= amount,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FaultyPoolQuoteError<E> {
+    /// The wrapped pool failed to quote.
+    Inner(E),
+    /// A fault was injected, with the code of the fault.
+    Injected(u32),
+}
+
+// Wraps a pool to inject quote errors and corrupted states, for testing the error handling of code that uses pools
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FaultyPool<P: Pool> {
+    inner: P,
+    // The first matching fault fails the quote with its code
+    quote_faults: Vec<(FaultTrigger, u32)>,
+    // Returned by get_state instead of the state of the wrapped pool
+    reported_state: Option<P::State>,
+    // Replaces the state after of every successful quote
+    state_after: Option<P::State>,
+}
+
+impl<P: Pool> FaultyPool<P> {
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            quote_faults: Vec::new(),
+            reported_state: None,
+            state_after: None,
+        }
+    }
+
+    pub fn with_quote_fault(mut self, trigger: FaultTrigger, code: u32) -> Self {
+        self.quote_faults.push((trigger, code));
+        self
+    }
+
+    pub fn with_reported_state(mut self, state: P::State) -> Self {
+        self.reported_state = Some(state);
+        self
+    }
+
+    pub fn with_state_after(mut self, state: P::State) -> Self {
+        self.state_after = Some(state);
+        self
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+}
+
+// The states are stored in the wrapper, which pools must be able to share between threads
+impl<P: Pool> Pool for FaultyPool<P>
+where
+    P::State: Send + Sync,
+{
+    type Resources = P::Resources;
+    type State = P::State;
+    type QuoteError = FaultyPoolQuoteError<P::QuoteError>;
+    type Meta = P::Meta;
+
+    fn get_key(&self) -> &NodeKey {
+        self.inner.get_key()
+    }
+
+    fn get_state(&self) -> Self::State {
+        self.reported_state
+            .unwrap_or_else(|| self.inner.get_state())
+    }
+
+    fn quote(
+        &self,
+        params: QuoteParams<Self::State, Self::Meta>,
+    ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
+        if let Some(&(_, code)) = self
+            .quote_faults
+            .iter()
+            .find(|(trigger, _)| trigger.matches(&params.token_amount))
+        {
+            return Err(FaultyPoolQuoteError::Injected(code));
+        }
+
+        let quote = self
+            .inner
+            .quote(params)
+            .map_err(FaultyPoolQuoteError::Inner)?;
+        Ok(Quote {
+            state_after: self.state_after.unwrap_or(quote.state_after),
+            ..quote
+        })
+    }
+
+    fn has_liquidity(&self) -> bool {
+        self.inner.has_liquidity()
+    }
+
+    fn max_tick_with_liquidity(&self) -> Option<i32> {
+        self.inner.max_tick_with_liquidity()
+    }
+
+    fn min_tick_with_liquidity(&self) -> Option<i32> {
+        self.inner.min_tick_with_liquidity()
+    }
+
+    fn is_path_dependent(&self) -> bool {
+        self.inner.is_path_dependent()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FaultyTickProviderError<E> {
+    /// The wrapped provider failed.
+    Inner(E),
+    /// The request was failed by the wrapper.
+    Injected,
+}
+
+/// Wraps a tick provider to fail requests after a number of them succeeded, and with the `async` feature, to delay
+/// the responses of an asynchronous provider by a number of polls.
+#[derive(Debug, Default)]
+pub struct FaultyTickProvider<T> {
+    pub inner: T,
+    // The number of requests that succeed before every following request fails
+    pub fail_after: Option<u32>,
+    // The number of times each asynchronous request is pending before it completes
+    pub pending_polls: u32,
+    requests: Cell<u32>,
+}
+
+impl<T> FaultyTickProvider<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            fail_after: None,
+            pending_polls: 0,
+            requests: Cell::new(0),
+        }
+    }
+
+    // The number of requests made so far, including the failed ones
+    pub fn requests(&self) -> u32 {
+        self.requests.get()
+    }
+
+    fn next_request<E>(&self) -> Result<(), FaultyTickProviderError<E>> {
+        let request = self.requests.get();
+        self.requests.set(request + 1);
+        if self
+            .fail_after
+            .is_some_and(|fail_after| request >= fail_after)
+        {
+            Err(FaultyTickProviderError::Injected)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<T: TickProvider + ?Sized> TickProvider for FaultyTickProvider<&T> {
+    type Error = FaultyTickProviderError<T::Error>;
+
+    fn prev_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+        self.next_request()?;
+        self.inner
+            .prev_initialized_tick(tick)
+            .map_err(FaultyTickProviderError::Inner)
+    }
+
+    fn next_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+        self.next_request()?;
+        self.inner
+            .next_initialized_tick(tick)
+            .map_err(FaultyTickProviderError::Inner)
+    }
+}
+
+#[cfg(feature = "async")]
+pub mod asynchronous {
+    use super::{FaultyTickProvider, FaultyTickProviderError};
+    use crate::quoting::tick_provider::asynchronous::AsyncTickProvider;
+    use crate::quoting::types::Tick;
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::task::{Context, Poll};
+
+    // A future that is pending the given number of times before it completes
+    struct Pending(u32);
+
+    impl Future for Pending {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 == 0 {
+                Poll::Ready(())
+            } else {
+                self.0 -= 1;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    impl<T: AsyncTickProvider> AsyncTickProvider for FaultyTickProvider<T> {
+        type Error = FaultyTickProviderError<T::Error>;
+
+        async fn prev_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+            Pending(self.pending_polls).await;
+            self.next_request()?;
+            self.inner
+                .prev_initialized_tick(tick)
+                .await
+                .map_err(FaultyTickProviderError::Inner)
+        }
+
+        async fn next_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+            Pending(self.pending_polls).await;
+            self.next_request()?;
+            self.inner
+                .next_initialized_tick(tick)
+                .await
+                .map_err(FaultyTickProviderError::Inner)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::to_sqrt_ratio;
+    use crate::math::uint::U256;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::pool_map::PoolMap;
+    use crate::quoting::route::{
+        quote_route, HopDirection, Route, RouteHop, RouteQuoteError, RouteSplit,
+        ROUTE_SHARE_DENOMINATOR,
+    };
+    use crate::quoting::tick_provider::{quote_with_tick_provider, TickProviderPoolState};
+    use crate::quoting::types::{Address, Config};
+    use alloc::vec;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn key() -> NodeKey {
+        NodeKey {
+            token0: TOKEN0,
+            token1: TOKEN1,
+            config: Config::from_raw_unchecked(0, 1, U256::zero()),
+        }
+    }
+
+    fn pool() -> FullRangePool {
+        FullRangePool::new(
+            key(),
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 1_000_000,
+            },
+        )
+        .unwrap()
+    }
+
+    fn params(token: Address, amount: i128) -> QuoteParams<FullRangePoolState, ()> {
+        QuoteParams {
+            token_amount: TokenAmount { token, amount },
+            sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
+            override_state: None,
+            meta: (),
+        }
+    }
+
+    #[test]
+    fn test_quote_faults() {
+        let faulty = FaultyPool::new(pool())
+            .with_quote_fault(FaultTrigger::Token(TOKEN1), 1)
+            .with_quote_fault(FaultTrigger::AmountAtLeast(1_000), 2);
+
+        assert_eq!(
+            faulty.quote(params(TOKEN1, 1)).unwrap_err(),
+            FaultyPoolQuoteError::Injected(1)
+        );
+        assert_eq!(
+            faulty.quote(params(TOKEN0, -1_000)).unwrap_err(),
+            FaultyPoolQuoteError::Injected(2)
+        );
+        assert_eq!(
+            faulty.quote(params(TOKEN0, 999)).unwrap(),
+            pool().quote(params(TOKEN0, 999)).unwrap()
+        );
+        assert_eq!(
+            faulty
+                .quote(params(Address::from_raw(U256::from(3)), 1))
+                .unwrap_err(),
+            FaultyPoolQuoteError::Inner(
+                pool()
+                    .quote(params(Address::from_raw(U256::from(3)), 1))
+                    .unwrap_err()
+            )
+        );
+
+        // the errors surface through the route quoting
+        let pools: PoolMap<_> = [faulty].into_iter().collect();
+        let route = Route {
+            token_in: TOKEN1,
+            token_out: TOKEN0,
+            splits: vec![RouteSplit {
+                share: ROUTE_SHARE_DENOMINATOR,
+                hops: vec![RouteHop {
+                    pool_key: key(),
+                    direction: HopDirection::OneForZero,
+                }],
+            }],
+        };
+        assert!(matches!(
+            quote_route(&pools, &route, 100, ()),
+            Err(RouteQuoteError::QuoteFailed {
+                error: FaultyPoolQuoteError::Injected(1),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_corrupted_states() {
+        let corrupted = FullRangePoolState {
+            sqrt_ratio: U256::zero(),
+            liquidity: u128::MAX,
+        };
+        let faulty = FaultyPool::new(pool())
+            .with_reported_state(corrupted)
+            .with_state_after(corrupted);
+
+        assert_eq!(faulty.get_state(), corrupted);
+        let quote = faulty.quote(params(TOKEN0, 100)).unwrap();
+        assert_eq!(quote.state_after, corrupted);
+        assert_eq!(
+            quote.calculated_amount,
+            pool().quote(params(TOKEN0, 100)).unwrap().calculated_amount
+        );
+    }
+
+    #[test]
+    fn test_tick_provider_faults() {
+        let ticks = [
+            Tick {
+                index: -100,
+                liquidity_delta: 1_000_000,
+            },
+            Tick {
+                index: 100,
+                liquidity_delta: -1_000_000,
+            },
+        ];
+        let provider = FaultyTickProvider {
+            fail_after: Some(1),
+            ..FaultyTickProvider::new(&ticks[..])
+        };
+        let state = TickProviderPoolState {
+            sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+            liquidity: 1_000_000,
+            tick: 0,
+        };
+        let token_amount = TokenAmount {
+            token: TOKEN1,
+            amount: 1_000_000,
+        };
+
+        // the swap crosses a tick, so it needs a second request
+        assert!(matches!(
+            quote_with_tick_provider(&key(), state, token_amount, None, &provider),
+            Err(
+                crate::quoting::tick_provider::TickProviderQuoteError::TickProviderError(
+                    FaultyTickProviderError::Injected
+                )
+            )
+        ));
+        assert_eq!(provider.requests(), 2);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_latency() {
+        use crate::quoting::tick_provider::asynchronous::AsyncTickProvider;
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::{Context, Waker};
+
+        struct AsyncTicks(Vec<Tick>);
+
+        impl AsyncTickProvider for AsyncTicks {
+            type Error = core::convert::Infallible;
+
+            async fn prev_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+                self.0.prev_initialized_tick(tick)
+            }
+
+            async fn next_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+                self.0.next_initialized_tick(tick)
+            }
+        }
+
+        let tick = Tick {
+            index: 10,
+            liquidity_delta: 1,
+        };
+        let provider = FaultyTickProvider {
+            pending_polls: 3,
+            ..FaultyTickProvider::new(AsyncTicks(vec![tick]))
+        };
+
+        let mut future = pin!(provider.next_initialized_tick(0));
+        let mut context = Context::from_waker(Waker::noop());
+        let mut polls = 1;
+        while future.as_mut().poll(&mut context).is_pending() {
+            polls += 1;
+        }
+        assert_eq!(polls, 4);
+        assert_eq!(provider.requests(), 1);
+    }
+}
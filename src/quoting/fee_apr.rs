@@ -0,0 +1,257 @@
+use crate::math::tick::{to_sqrt_ratio, MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+use crate::math::uint::{u256_to_float_base_x128, U256};
+use num_traits::Zero;
+
+pub const SECONDS_PER_YEAR: u64 = 31_536_000;
+
+// The fees collected by a pool over a period, e.g. aggregated from its swap events
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeVolume {
+    pub fees0: u128,
+    pub fees1: u128,
+    pub period_seconds: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeeAprEstimate {
+    // The share of the fees of swaps at the current price that the position would earn
+    pub in_range_liquidity_share: f64,
+    // The value of the fees the position would earn per year, in token1
+    pub fees_per_year: f64,
+    // The value of the position, in token1
+    pub position_value: f64,
+    // The fees earned per year divided by the value of the position, e.g. 0.1 for 10%
+    pub apr: f64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FeeAprError {
+    /// The lower bound of the range must be less than the upper bound.
+    InvalidRange,
+    /// One of the bounds is not a valid tick.
+    InvalidTick(i32),
+    /// The period of the fee volume must be greater than zero.
+    ZeroPeriod,
+    /// The position liquidity must be greater than zero.
+    ZeroLiquidity,
+}
+
+// Returns the share of the liquidity at the current price that a new position would own, which is zero if the
+// current price is outside of the range of the position
+pub fn in_range_liquidity_share(
+    sqrt_ratio: U256,
+    active_liquidity: u128,
+    sqrt_ratio_lower: U256,
+    sqrt_ratio_upper: U256,
+    position_liquidity: u128,
+) -> f64 {
+    // the range includes the lower bound but not the upper bound, like the active liquidity of a pool
+    if sqrt_ratio < sqrt_ratio_lower
+        || sqrt_ratio >= sqrt_ratio_upper
+        || position_liquidity.is_zero()
+    {
+        return 0f64;
+    }
+
+    let position_liquidity = position_liquidity as f64;
+    position_liquidity / (active_liquidity as f64 + position_liquidity)
+}
+
+// Estimates the fee APR of adding a position between the given sqrt ratios to a pool. Assumes the fee volume
+// is representative of the future and that all of it is traded at the current price, so it is most accurate
+// for ranges that are wide relative to the price movement over the period.
+pub fn estimate_fee_apr(
+    sqrt_ratio: U256,
+    active_liquidity: u128,
+    volume: FeeVolume,
+    sqrt_ratio_lower: U256,
+    sqrt_ratio_upper: U256,
+    position_liquidity: u128,
+) -> Result<FeeAprEstimate, FeeAprError> {
+    if sqrt_ratio_lower >= sqrt_ratio_upper || sqrt_ratio_lower.is_zero() {
+        return Err(FeeAprError::InvalidRange);
+    }
+    if volume.period_seconds.is_zero() {
+        return Err(FeeAprError::ZeroPeriod);
+    }
+    if position_liquidity.is_zero() {
+        return Err(FeeAprError::ZeroLiquidity);
+    }
+
+    let share = in_range_liquidity_share(
+        sqrt_ratio,
+        active_liquidity,
+        sqrt_ratio_lower,
+        sqrt_ratio_upper,
+        position_liquidity,
+    );
+
+    let sqrt_price = u256_to_float_base_x128(sqrt_ratio);
+    let price = sqrt_price * sqrt_price;
+    let sqrt_price_lower = u256_to_float_base_x128(sqrt_ratio_lower);
+    let sqrt_price_upper = u256_to_float_base_x128(sqrt_ratio_upper);
+    let clamped_sqrt_price = sqrt_price.clamp(sqrt_price_lower, sqrt_price_upper);
+
+    let liquidity = position_liquidity as f64;
+    let amount0 = liquidity * (1f64 / clamped_sqrt_price - 1f64 / sqrt_price_upper);
+    let amount1 = liquidity * (clamped_sqrt_price - sqrt_price_lower);
+    let position_value = amount0 * price + amount1;
+
+    let fees_value = volume.fees0 as f64 * price + volume.fees1 as f64;
+    let fees_per_year =
+        fees_value * share * (SECONDS_PER_YEAR as f64 / volume.period_seconds as f64);
+
+    Ok(FeeAprEstimate {
+        in_range_liquidity_share: share,
+        fees_per_year,
+        position_value,
+        apr: fees_per_year / position_value,
+    })
+}
+
+// Same as estimate_fee_apr, with the bounds of the position given as ticks
+pub fn estimate_fee_apr_for_ticks(
+    sqrt_ratio: U256,
+    active_liquidity: u128,
+    volume: FeeVolume,
+    tick_lower: i32,
+    tick_upper: i32,
+    position_liquidity: u128,
+) -> Result<FeeAprEstimate, FeeAprError> {
+    estimate_fee_apr(
+        sqrt_ratio,
+        active_liquidity,
+        volume,
+        to_sqrt_ratio(tick_lower).ok_or(FeeAprError::InvalidTick(tick_lower))?,
+        to_sqrt_ratio(tick_upper).ok_or(FeeAprError::InvalidTick(tick_upper))?,
+        position_liquidity,
+    )
+}
+
+// Estimates the fee APR of the pool as a whole, i.e. of a full range position
+pub fn estimate_full_range_fee_apr(
+    sqrt_ratio: U256,
+    active_liquidity: u128,
+    volume: FeeVolume,
+    position_liquidity: u128,
+) -> Result<FeeAprEstimate, FeeAprError> {
+    estimate_fee_apr(
+        sqrt_ratio,
+        active_liquidity,
+        volume,
+        MIN_SQRT_RATIO,
+        MAX_SQRT_RATIO,
+        position_liquidity,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: U256 = U256([0, 0, 1, 0]);
+
+    fn volume() -> FeeVolume {
+        FeeVolume {
+            fees0: 1_000,
+            fees1: 1_000,
+            period_seconds: SECONDS_PER_YEAR / 365,
+        }
+    }
+
+    #[test]
+    fn test_in_range_liquidity_share() {
+        let (lower, upper) = (to_sqrt_ratio(-100).unwrap(), to_sqrt_ratio(100).unwrap());
+
+        assert_eq!(in_range_liquidity_share(ONE, 300, lower, upper, 100), 0.25);
+        assert_eq!(
+            in_range_liquidity_share(lower, 300, lower, upper, 100),
+            0.25
+        );
+        assert_eq!(
+            in_range_liquidity_share(upper, 300, lower, upper, 100),
+            0f64
+        );
+        assert_eq!(
+            in_range_liquidity_share(to_sqrt_ratio(-101).unwrap(), 300, lower, upper, 100),
+            0f64
+        );
+        assert_eq!(in_range_liquidity_share(ONE, 0, lower, upper, 100), 1f64);
+    }
+
+    #[test]
+    fn test_estimate_fee_apr() {
+        let estimate = estimate_fee_apr_for_ticks(
+            ONE,
+            3_000_000_000,
+            volume(),
+            -1_000_000,
+            1_000_000,
+            1_000_000_000,
+        )
+        .unwrap();
+
+        assert_eq!(estimate.in_range_liquidity_share, 0.25);
+        // 2000 of fees per day, of which a quarter is earned
+        assert!((estimate.fees_per_year - 182_500f64).abs() < 1e-6);
+        assert!((estimate.apr - estimate.fees_per_year / estimate.position_value).abs() < 1e-12);
+
+        // a narrower range requires less capital for the same liquidity
+        let narrow = estimate_fee_apr_for_ticks(
+            ONE,
+            3_000_000_000,
+            volume(),
+            -100_000,
+            100_000,
+            1_000_000_000,
+        )
+        .unwrap();
+        assert_eq!(narrow.fees_per_year, estimate.fees_per_year);
+        assert!(narrow.apr > estimate.apr);
+
+        // out of range positions earn nothing
+        let out_of_range = estimate_fee_apr_for_ticks(
+            ONE,
+            3_000_000_000,
+            volume(),
+            100_000,
+            200_000,
+            1_000_000_000,
+        )
+        .unwrap();
+        assert_eq!(out_of_range.apr, 0f64);
+        assert!(out_of_range.position_value > 0f64);
+
+        let full_range =
+            estimate_full_range_fee_apr(ONE, 3_000_000_000, volume(), 1_000_000_000).unwrap();
+        assert!(full_range.apr < estimate.apr);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            estimate_fee_apr(ONE, 0, volume(), ONE, ONE, 1),
+            Err(FeeAprError::InvalidRange)
+        );
+        assert_eq!(
+            estimate_fee_apr_for_ticks(ONE, 0, volume(), 0, 100_000_000, 1),
+            Err(FeeAprError::InvalidTick(100_000_000))
+        );
+        assert_eq!(
+            estimate_full_range_fee_apr(
+                ONE,
+                0,
+                FeeVolume {
+                    period_seconds: 0,
+                    ..volume()
+                },
+                1
+            ),
+            Err(FeeAprError::ZeroPeriod)
+        );
+        assert_eq!(
+            estimate_full_range_fee_apr(ONE, 0, volume(), 0),
+            Err(FeeAprError::ZeroLiquidity)
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use crate::math::muldiv::muldiv;
+use crate::math::uint::U256;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+const TWO_POW_128: U256 = U256([0, 0, 1, 0]);
+
+// Fees of both tokens per unit of liquidity as 128.128 fixed point numbers, which wrap on overflow like in the core
+// contract so that only differences between them are meaningful
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FeesPerLiquidity {
+    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
+    pub value0: U256,
+    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
+    pub value1: U256,
+}
+
+impl FeesPerLiquidity {
+    pub fn wrapping_sub(self, other: Self) -> Self {
+        Self {
+            value0: self.value0.overflowing_sub(other.value0).0,
+            value1: self.value1.overflowing_sub(other.value1).0,
+        }
+    }
+}
+
+// The fee growth of a pool at one block, as read from the core contract
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FeeGrowthSnapshot {
+    pub tick: i32,
+    pub global: FeesPerLiquidity,
+    // The fees per liquidity outside of each initialized tick, where missing ticks are uninitialized and have none
+    pub outside: BTreeMap<i32, FeesPerLiquidity>,
+}
+
+// A position of the pool with the fees per liquidity inside its bounds at its last update
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PositionFees {
+    pub lower: i32,
+    pub upper: i32,
+    pub liquidity: u128,
+    pub fees_per_liquidity_inside_last: FeesPerLiquidity,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeesOwed {
+    pub amount0: u128,
+    pub amount1: u128,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FeeSettlementError {
+    /// The lower bound of the position is not less than its upper bound.
+    InvalidBounds { lower: i32, upper: i32 },
+}
+
+impl FeeGrowthSnapshot {
+    fn outside(&self, tick: i32) -> FeesPerLiquidity {
+        self.outside.get(&tick).copied().unwrap_or_default()
+    }
+
+    // Returns the fees per liquidity earned inside the bounds, computed like the core contract does
+    pub fn fees_per_liquidity_inside(
+        &self,
+        lower: i32,
+        upper: i32,
+    ) -> Result<FeesPerLiquidity, FeeSettlementError> {
+        if lower >= upper {
+            return Err(FeeSettlementError::InvalidBounds { lower, upper });
+        }
+
+        let (lower_outside, upper_outside) = (self.outside(lower), self.outside(upper));
+        Ok(if self.tick < lower {
+            lower_outside.wrapping_sub(upper_outside)
+        } else if self.tick < upper {
+            self.global
+                .wrapping_sub(lower_outside)
+                .wrapping_sub(upper_outside)
+        } else {
+            upper_outside.wrapping_sub(lower_outside)
+        })
+    }
+}
+
+// Returns the fees owed to a position with the fees per liquidity currently inside of its bounds, truncated to 128 bits
+// like the amounts collected from the core contract
+pub fn fees_owed(position: &PositionFees, fees_per_liquidity_inside: FeesPerLiquidity) -> FeesOwed {
+    let growth = fees_per_liquidity_inside.wrapping_sub(position.fees_per_liquidity_inside_last);
+    let amount = |growth: U256| {
+        // the quotient of a 256 bit growth and a 128 bit liquidity over 2**128 always fits in 256 bits
+        muldiv(growth, U256::from(position.liquidity), TWO_POW_128, false)
+            .unwrap()
+            .low_u128()
+    };
+
+    FeesOwed {
+        amount0: amount(growth.value0),
+        amount1: amount(growth.value1),
+    }
+}
+
+/// Computes the fees owed to each of the positions of the pool at the snapshot, in the order of the positions.
+///
+/// The fees per liquidity inside of each distinct pair of bounds are computed once and shared by all positions with
+/// those bounds, which is what most of the positions of a pool have in common. Positions with invalid bounds fail
+/// individually without affecting the others.
+pub fn batch_fees_owed(
+    snapshot: &FeeGrowthSnapshot,
+    positions: &[PositionFees],
+) -> Vec<Result<FeesOwed, FeeSettlementError>> {
+    let mut inside: BTreeMap<(i32, i32), FeesPerLiquidity> = BTreeMap::new();
+
+    positions
+        .iter()
+        .map(|position| {
+            let bounds = (position.lower, position.upper);
+            let fees_per_liquidity_inside = match inside.get(&bounds) {
+                Some(fees_per_liquidity_inside) => *fees_per_liquidity_inside,
+                None => {
+                    let fees_per_liquidity_inside =
+                        snapshot.fees_per_liquidity_inside(position.lower, position.upper)?;
+                    inside.insert(bounds, fees_per_liquidity_inside);
+                    fees_per_liquidity_inside
+                }
+            };
+            Ok(fees_owed(position, fees_per_liquidity_inside))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn fees(value0: u64, value1: u64) -> FeesPerLiquidity {
+        FeesPerLiquidity {
+            value0: U256::from(value0) << 128,
+            value1: U256::from(value1) << 128,
+        }
+    }
+
+    fn position(lower: i32, upper: i32, liquidity: u128, last: FeesPerLiquidity) -> PositionFees {
+        PositionFees {
+            lower,
+            upper,
+            liquidity,
+            fees_per_liquidity_inside_last: last,
+        }
+    }
+
+    fn snapshot(tick: i32) -> FeeGrowthSnapshot {
+        FeeGrowthSnapshot {
+            tick,
+            global: fees(100, 50),
+            outside: [(-10, fees(10, 5)), (10, fees(30, 20))]
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_fees_per_liquidity_inside() {
+        assert_eq!(
+            snapshot(0).fees_per_liquidity_inside(-10, 10),
+            Ok(fees(60, 25))
+        );
+        assert_eq!(
+            snapshot(-20).fees_per_liquidity_inside(-10, 10),
+            Ok(fees(10, 5).wrapping_sub(fees(30, 20)))
+        );
+        assert_eq!(
+            snapshot(10).fees_per_liquidity_inside(-10, 10),
+            Ok(fees(20, 15))
+        );
+        assert_eq!(
+            snapshot(0).fees_per_liquidity_inside(10, 10),
+            Err(FeeSettlementError::InvalidBounds {
+                lower: 10,
+                upper: 10
+            })
+        );
+    }
+
+    #[test]
+    fn test_batch_fees_owed() {
+        let snapshot = snapshot(0);
+        let positions = [
+            position(-10, 10, 2, fees(0, 0)),
+            position(-10, 10, 3, fees(50, 20)),
+            // the fees per liquidity inside wrapped around since the last update of the position
+            position(
+                -10,
+                10,
+                1,
+                FeesPerLiquidity {
+                    value0: U256::MAX << 128,
+                    value1: U256::MAX << 128,
+                },
+            ),
+            position(10, -10, 1, fees(0, 0)),
+        ];
+
+        assert_eq!(
+            batch_fees_owed(&snapshot, &positions),
+            vec![
+                Ok(FeesOwed {
+                    amount0: 120,
+                    amount1: 50
+                }),
+                Ok(FeesOwed {
+                    amount0: 30,
+                    amount1: 15
+                }),
+                Ok(FeesOwed {
+                    amount0: 61,
+                    amount1: 26
+                }),
+                Err(FeeSettlementError::InvalidBounds {
+                    lower: 10,
+                    upper: -10
+                }),
+            ]
+        );
+        assert_eq!(
+            batch_fees_owed(&snapshot, &positions[..1]),
+            vec![Ok(FeesOwed {
+                amount0: 120,
+                amount1: 50
+            })]
+        );
+    }
+}
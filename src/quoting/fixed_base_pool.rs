@@ -0,0 +1,201 @@
+use crate::quoting::base_pool::{
+    BasePoolError, BasePoolQuoteError, BasePoolResources, BasePoolState, BasePoolView,
+};
+use crate::quoting::resources::MaxResourcesEstimate;
+use crate::quoting::types::{NodeKey, Pool, Quote, QuoteParams, Tick};
+
+/// Errors that can occur when constructing a FixedBasePool.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FixedBasePoolError {
+    /// The pool has more initialized ticks than the capacity of its storage.
+    CapacityExceeded { capacity: usize, len: usize },
+    /// The pool is invalid for the same reasons as a BasePool.
+    InvalidPool(BasePoolError),
+}
+
+/// A base pool that stores up to N sorted ticks inline instead of on the heap, so that constructing, cloning and
+/// quoting it does not allocate, e.g. for embedded or wasm targets. The crate itself still requires an allocator.
+///
+/// Quotes are identical to a BasePool with the same ticks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixedBasePool<const N: usize> {
+    key: NodeKey,
+    state: BasePoolState,
+    ticks: [Tick; N],
+    // The number of ticks at the start of the array that are initialized
+    len: usize,
+}
+
+impl<const N: usize> FixedBasePool<N> {
+    /// Creates a pool with the same validation as BasePool::new, copying the sorted ticks into its storage.
+    pub fn new(
+        key: NodeKey,
+        state: BasePoolState,
+        sorted_ticks: &[Tick],
+    ) -> Result<Self, FixedBasePoolError> {
+        if sorted_ticks.len() > N {
+            return Err(FixedBasePoolError::CapacityExceeded {
+                capacity: N,
+                len: sorted_ticks.len(),
+            });
+        }
+        BasePoolView::new(key, state, sorted_ticks).map_err(FixedBasePoolError::InvalidPool)?;
+
+        let mut ticks = [Tick {
+            index: 0,
+            liquidity_delta: 0,
+        }; N];
+        ticks[..sorted_ticks.len()].copy_from_slice(sorted_ticks);
+
+        Ok(Self {
+            key,
+            state,
+            ticks,
+            len: sorted_ticks.len(),
+        })
+    }
+
+    pub fn get_sorted_ticks(&self) -> &[Tick] {
+        &self.ticks[..self.len]
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    // Returns a view of the pool that borrows its ticks
+    pub fn as_view(&self) -> BasePoolView<'_> {
+        BasePoolView::new_unchecked(self.key, self.state, self.get_sorted_ticks())
+    }
+}
+
+impl<const N: usize> Pool for FixedBasePool<N> {
+    type Resources = BasePoolResources;
+    type State = BasePoolState;
+    type QuoteError = BasePoolQuoteError;
+    type Meta = ();
+
+    fn get_key(&self) -> &NodeKey {
+        &self.key
+    }
+
+    fn get_state(&self) -> Self::State {
+        self.state
+    }
+
+    fn quote(
+        &self,
+        params: QuoteParams<Self::State, Self::Meta>,
+    ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
+        self.as_view().quote(params)
+    }
+
+    fn has_liquidity(&self) -> bool {
+        self.state.liquidity > 0 || self.len != 0
+    }
+
+    fn max_tick_with_liquidity(&self) -> Option<i32> {
+        self.get_sorted_ticks().last().map(|tick| tick.index)
+    }
+
+    fn min_tick_with_liquidity(&self) -> Option<i32> {
+        self.get_sorted_ticks().first().map(|tick| tick.index)
+    }
+
+    fn is_path_dependent(&self) -> bool {
+        false
+    }
+}
+
+impl<const N: usize> MaxResourcesEstimate for FixedBasePool<N> {
+    fn max_resources_estimate(&self, amount_bound: u128) -> Self::Resources {
+        self.as_view().max_resources_estimate(amount_bound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::to_sqrt_ratio;
+    use crate::math::uint::U256;
+    use crate::quoting::base_pool::BasePool;
+    use crate::quoting::types::{Address, Config, TokenAmount};
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn key() -> NodeKey {
+        NodeKey {
+            token0: TOKEN0,
+            token1: TOKEN1,
+            config: Config::from_raw_unchecked(1 << 60, 100, U256::zero()),
+        }
+    }
+
+    fn state() -> BasePoolState {
+        BasePoolState {
+            sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+            liquidity: 1_000_000,
+            active_tick_index: Some(1),
+        }
+    }
+
+    const TICKS: [Tick; 4] = [
+        Tick {
+            index: -1000,
+            liquidity_delta: 500_000,
+        },
+        Tick {
+            index: 0,
+            liquidity_delta: 500_000,
+        },
+        Tick {
+            index: 100,
+            liquidity_delta: -500_000,
+        },
+        Tick {
+            index: 1000,
+            liquidity_delta: -500_000,
+        },
+    ];
+
+    #[test]
+    fn test_quotes_match_base_pool() {
+        let fixed = FixedBasePool::<8>::new(key(), state(), &TICKS).unwrap();
+        let base = BasePool::new(key(), state(), TICKS.to_vec()).unwrap();
+        assert_eq!(fixed.get_sorted_ticks(), TICKS);
+        assert_eq!(fixed.capacity(), 8);
+
+        for (token, amount) in [(TOKEN0, 100_000), (TOKEN1, 100_000), (TOKEN0, -5_000)] {
+            let params = QuoteParams {
+                token_amount: TokenAmount { token, amount },
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: None,
+                meta: (),
+            };
+            assert_eq!(fixed.quote(params), base.quote(params));
+        }
+        assert_eq!(
+            fixed.max_tick_with_liquidity(),
+            base.max_tick_with_liquidity()
+        );
+    }
+
+    #[test]
+    fn test_construction_errors() {
+        assert_eq!(
+            FixedBasePool::<3>::new(key(), state(), &TICKS),
+            Err(FixedBasePoolError::CapacityExceeded {
+                capacity: 3,
+                len: 4
+            })
+        );
+        assert_eq!(
+            FixedBasePool::<4>::new(key(), state(), &TICKS[..3]),
+            Err(FixedBasePoolError::InvalidPool(
+                BasePoolError::TotalLiquidityNotZero
+            ))
+        );
+    }
+}
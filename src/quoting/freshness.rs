@@ -0,0 +1,166 @@
+use crate::quoting::types::{BlockContext, Quote};
+
+// The block of the pool state that a quote was computed from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuoteContext {
+    pub state_block: BlockContext,
+}
+
+// How far the state of a quote lags behind the head of the chain
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuoteAge {
+    pub blocks: u64,
+    pub seconds: u64,
+}
+
+// The maximum age of the state a quote may be computed from. Limits that are None are not enforced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FreshnessPolicy {
+    pub max_blocks: Option<u64>,
+    pub max_seconds: Option<u64>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StaleQuoteError {
+    /// The quote has no context, so its freshness cannot be determined.
+    MissingContext,
+    /// The state of the quote is older than the policy allows.
+    TooOld(QuoteAge),
+}
+
+impl QuoteContext {
+    pub fn new(state_block: BlockContext) -> Self {
+        Self { state_block }
+    }
+
+    // Returns the age of the state relative to the head, which is zero if the head is not after the state block
+    pub fn age(&self, head: BlockContext) -> QuoteAge {
+        QuoteAge {
+            blocks: head.number.saturating_sub(self.state_block.number),
+            seconds: head.timestamp.saturating_sub(self.state_block.timestamp),
+        }
+    }
+
+    pub fn check_freshness(
+        &self,
+        head: BlockContext,
+        policy: FreshnessPolicy,
+    ) -> Result<(), StaleQuoteError> {
+        let age = self.age(head);
+        let exceeds = |value: u64, limit: Option<u64>| limit.is_some_and(|limit| value > limit);
+
+        if exceeds(age.blocks, policy.max_blocks) || exceeds(age.seconds, policy.max_seconds) {
+            Err(StaleQuoteError::TooOld(age))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn is_stale(&self, head: BlockContext, policy: FreshnessPolicy) -> bool {
+        self.check_freshness(head, policy).is_err()
+    }
+}
+
+// A quote annotated with the context of the state it was computed from, if known
+#[derive(Clone, Copy, Debug)]
+pub struct AnnotatedQuote<R, S> {
+    pub quote: Quote<R, S>,
+    pub context: Option<QuoteContext>,
+}
+
+impl<R, S> AnnotatedQuote<R, S> {
+    pub fn new(quote: Quote<R, S>, context: Option<QuoteContext>) -> Self {
+        Self { quote, context }
+    }
+
+    // Quotes without a context are always considered stale
+    pub fn check_freshness(
+        &self,
+        head: BlockContext,
+        policy: FreshnessPolicy,
+    ) -> Result<(), StaleQuoteError> {
+        self.context
+            .ok_or(StaleQuoteError::MissingContext)?
+            .check_freshness(head, policy)
+    }
+
+    pub fn is_stale(&self, head: BlockContext, policy: FreshnessPolicy) -> bool {
+        self.check_freshness(head, policy).is_err()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(number: u64, timestamp: u64) -> BlockContext {
+        BlockContext { number, timestamp }
+    }
+
+    fn quote() -> Quote<(), ()> {
+        Quote {
+            is_price_increasing: false,
+            consumed_amount: 100,
+            calculated_amount: 99,
+            execution_resources: (),
+            state_after: (),
+            fees_paid: 1,
+        }
+    }
+
+    #[test]
+    fn test_age() {
+        let context = QuoteContext::new(block(100, 1_000));
+        assert_eq!(
+            context.age(block(103, 1_036)),
+            QuoteAge {
+                blocks: 3,
+                seconds: 36
+            }
+        );
+        // a head behind the state, e.g. from a lagging node, has no age
+        assert_eq!(context.age(block(99, 988)), QuoteAge::default());
+    }
+
+    #[test]
+    fn test_check_freshness() {
+        let context = QuoteContext::new(block(100, 1_000));
+        let policy = FreshnessPolicy {
+            max_blocks: Some(2),
+            max_seconds: Some(30),
+        };
+
+        assert_eq!(context.check_freshness(block(102, 1_024), policy), Ok(()));
+        assert_eq!(
+            context.check_freshness(block(103, 1_024), policy),
+            Err(StaleQuoteError::TooOld(QuoteAge {
+                blocks: 3,
+                seconds: 24
+            }))
+        );
+        assert!(context.is_stale(block(101, 1_031), policy));
+
+        // no limits are enforced by default
+        assert!(!context.is_stale(block(1_000_000, 1_000_000), FreshnessPolicy::default()));
+    }
+
+    #[test]
+    fn test_annotated_quote() {
+        let head = block(101, 1_012);
+        let policy = FreshnessPolicy {
+            max_blocks: Some(1),
+            max_seconds: None,
+        };
+
+        let annotated = AnnotatedQuote::new(quote(), Some(QuoteContext::new(block(100, 1_000))));
+        assert_eq!(annotated.check_freshness(head, policy), Ok(()));
+
+        let unknown = AnnotatedQuote::new(quote(), None);
+        assert_eq!(
+            unknown.check_freshness(head, policy),
+            Err(StaleQuoteError::MissingContext)
+        );
+        assert!(unknown.is_stale(head, FreshnessPolicy::default()));
+    }
+}
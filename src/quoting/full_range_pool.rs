@@ -1,7 +1,13 @@
+use crate::math::delta::AmountDeltaError;
+use crate::math::liquidity::{max_liquidity, position_amounts, LiquidityError};
 use crate::math::swap::{compute_step, is_price_increasing, ComputeStepError};
-use crate::math::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+use crate::math::tick::{MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO, MIN_TICK};
 use crate::math::uint::U256;
-use crate::quoting::types::{NodeKey, Pool, Quote, QuoteParams};
+use crate::quoting::events::{ApplyEventError, ApplyPoolEvent, PoolEvent};
+use crate::quoting::pool_diff::{FieldDiff, PoolDiff};
+use crate::quoting::pool_reads::{range_inventory, Inventory, PoolReadError, PoolReads};
+use crate::quoting::resources::MaxResourcesEstimate;
+use crate::quoting::types::{NodeKey, Pool, Quote, QuoteParams, SqrtRatioState};
 use core::ops::{Add, AddAssign, Sub, SubAssign};
 use num_traits::Zero;
 
@@ -11,6 +17,10 @@ pub struct FullRangePoolResources {
     pub no_override_price_change: u32,
 }
 
+crate::impl_flatten_resources!(FullRangePoolResources {
+    no_override_price_change
+});
+
 impl AddAssign for FullRangePoolResources {
     fn add_assign(&mut self, rhs: Self) {
         self.no_override_price_change += rhs.no_override_price_change;
@@ -56,6 +66,12 @@ pub struct FullRangePoolState {
     pub liquidity: u128,
 }
 
+impl SqrtRatioState for FullRangePoolState {
+    fn sqrt_ratio(&self) -> U256 {
+        self.sqrt_ratio
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FullRangePool {
@@ -71,6 +87,30 @@ pub enum FullRangePoolError {
     SqrtRatioInvalid,
 }
 
+/// Errors that can occur when computing the liquidity of a deposit into a full range pool or the amounts of a
+/// withdrawal.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FullRangeLiquidityError {
+    /// Computing the liquidity of the amounts failed.
+    Liquidity(LiquidityError),
+    /// Computing the amounts of the liquidity failed.
+    AmountDelta(AmountDeltaError),
+    /// The liquidity of the pool after the deposit does not fit in a u128.
+    TotalLiquidityOverflow,
+    /// The liquidity withdrawn is more than the liquidity of the pool.
+    InsufficientLiquidity,
+}
+
+// A deposit into a full range pool, with the amounts it uses and the liquidity of the pool after it, so the share of
+// the depositor is liquidity / total_liquidity
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FullRangeDeposit {
+    pub liquidity: u128,
+    pub amount0: u128,
+    pub amount1: u128,
+    pub total_liquidity: u128,
+}
+
 impl FullRangePool {
     pub fn new(key: NodeKey, state: FullRangePoolState) -> Result<Self, FullRangePoolError> {
         if !(key.token0 < key.token1) {
@@ -89,6 +129,111 @@ impl FullRangePool {
             },
         })
     }
+
+    /// Returns the most liquidity that the amounts can deposit at the current price, and the amounts that the deposit
+    /// uses.
+    ///
+    /// Like the contract, the liquidity is rounded down and the amounts are rounded up, so the amounts never exceed the
+    /// amounts given.
+    pub fn deposit(
+        &self,
+        amount0: u128,
+        amount1: u128,
+    ) -> Result<FullRangeDeposit, FullRangeLiquidityError> {
+        let liquidity = max_liquidity(
+            self.state.sqrt_ratio,
+            MIN_SQRT_RATIO,
+            MAX_SQRT_RATIO,
+            amount0,
+            amount1,
+        )
+        .map_err(FullRangeLiquidityError::Liquidity)?;
+        let (amount0, amount1) = self.position_amounts(liquidity, true)?;
+
+        Ok(FullRangeDeposit {
+            liquidity,
+            amount0,
+            amount1,
+            total_liquidity: self
+                .state
+                .liquidity
+                .checked_add(liquidity)
+                .ok_or(FullRangeLiquidityError::TotalLiquidityOverflow)?,
+        })
+    }
+
+    // Returns the amounts that withdrawing the liquidity from the pool pays out, rounded down like the contract
+    pub fn withdrawal(&self, liquidity: u128) -> Result<(u128, u128), FullRangeLiquidityError> {
+        if liquidity > self.state.liquidity {
+            return Err(FullRangeLiquidityError::InsufficientLiquidity);
+        }
+        self.position_amounts(liquidity, false)
+    }
+
+    // Returns the amounts of the liquidity at the current price, which are rounded up for deposits and down for
+    // withdrawals
+    pub fn position_amounts(
+        &self,
+        liquidity: u128,
+        round_up: bool,
+    ) -> Result<(u128, u128), FullRangeLiquidityError> {
+        position_amounts(
+            self.state.sqrt_ratio,
+            MIN_SQRT_RATIO,
+            MAX_SQRT_RATIO,
+            liquidity,
+            round_up,
+        )
+        .map_err(FullRangeLiquidityError::AmountDelta)
+    }
+
+    // Reports the fields that differ between this pool and the other pool
+    pub fn diff(&self, other: &Self) -> PoolDiff {
+        PoolDiff {
+            key: FieldDiff::of(self.key, other.key),
+            sqrt_ratio: FieldDiff::of(self.state.sqrt_ratio, other.state.sqrt_ratio),
+            liquidity: FieldDiff::of(self.state.liquidity, other.state.liquidity),
+            ..PoolDiff::default()
+        }
+    }
+}
+
+impl ApplyPoolEvent for FullRangePool {
+    fn apply_event(&mut self, event: &PoolEvent) -> Result<(), ApplyEventError> {
+        match *event {
+            PoolEvent::Swapped {
+                sqrt_ratio_after,
+                liquidity_after,
+                ..
+            } => {
+                *self = Self::new(
+                    self.key,
+                    FullRangePoolState {
+                        sqrt_ratio: sqrt_ratio_after,
+                        liquidity: liquidity_after,
+                    },
+                )
+                .map_err(|_| ApplyEventError::InvalidStateAfter)?;
+            }
+            PoolEvent::PositionUpdated {
+                tick_lower,
+                tick_upper,
+                liquidity_delta,
+            } => {
+                if (tick_lower, tick_upper) != (MIN_TICK, MAX_TICK) {
+                    return Err(ApplyEventError::InvalidPositionBounds);
+                }
+
+                self.state.liquidity = self
+                    .state
+                    .liquidity
+                    .checked_add_signed(liquidity_delta)
+                    .ok_or(ApplyEventError::LiquidityOverflow)?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Pool for FullRangePool {
@@ -182,7 +327,7 @@ impl Pool for FullRangePool {
             sqrt_ratio_limit,
             amount,
             is_token1,
-            self.key.config.fee,
+            self.key.config.fee.raw(),
         )
         .map_err(FullRangePoolQuoteError::FailedComputeSwapStep)?;
 
@@ -243,39 +388,88 @@ impl Pool for FullRangePool {
     }
 }
 
+// A swap moves the price of a full range pool in a single step
+impl MaxResourcesEstimate for FullRangePool {
+    fn max_resources_estimate(&self, amount_bound: u128) -> Self::Resources {
+        FullRangePoolResources {
+            no_override_price_change: (amount_bound != 0).into(),
+        }
+    }
+}
+
+impl PoolReads for FullRangePool {
+    fn inventory_at(&self, sqrt_ratio: U256) -> Result<Inventory, PoolReadError> {
+        range_inventory(
+            sqrt_ratio,
+            MIN_SQRT_RATIO,
+            MAX_SQRT_RATIO,
+            self.state.liquidity,
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::quoting::types::{Config, TokenAmount};
+    use crate::quoting::types::{Address, Config, TokenAmount};
 
-    const TOKEN0: U256 = U256([1, 0, 0, 0]);
-    const TOKEN1: U256 = U256([2, 0, 0, 0]);
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
 
     fn node_key(fee: u64) -> NodeKey {
         NodeKey {
             token0: TOKEN0,
             token1: TOKEN1,
-            config: Config {
-                tick_spacing: 0,
-                fee,
-                extension: U256::zero(),
-            },
+            config: Config::from_raw_unchecked(fee, 0, U256::zero()),
         }
     }
 
     use super::FullRangePoolError;
 
+    #[test]
+    fn test_deposit_and_withdrawal_round_against_depositor() {
+        let pool = FullRangePool::new(
+            node_key(0),
+            FullRangePoolState {
+                // a price of 4
+                sqrt_ratio: U256::from(2) << 128,
+                liquidity: 1_000_000,
+            },
+        )
+        .unwrap();
+
+        let deposit = pool.deposit(1_000_000, 10_000_000).unwrap();
+        // token0 limits the deposit at a price of 4
+        assert_eq!(deposit.amount0, 1_000_000);
+        crate::assert_amount_within!(deposit.amount1, 4_000_000, 1);
+        assert_eq!(deposit.total_liquidity, 1_000_000 + deposit.liquidity);
+
+        let after = FullRangePool::new(
+            node_key(0),
+            FullRangePoolState {
+                sqrt_ratio: pool.state.sqrt_ratio,
+                liquidity: deposit.total_liquidity,
+            },
+        )
+        .unwrap();
+        let (amount0, amount1) = after.withdrawal(deposit.liquidity).unwrap();
+        assert!(amount0 <= deposit.amount0 && amount1 <= deposit.amount1);
+        crate::assert_amount_within!(amount0, deposit.amount0, 1);
+        crate::assert_amount_within!(amount1, deposit.amount1, 1);
+
+        assert_eq!(
+            pool.withdrawal(1_000_001),
+            Err(FullRangeLiquidityError::InsufficientLiquidity)
+        );
+    }
+
     #[test]
     fn test_token0_lt_token1() {
         let result = FullRangePool::new(
             NodeKey {
-                token0: U256::zero(),
-                token1: U256::zero(),
-                config: Config {
-                    extension: U256::zero(),
-                    fee: 0,
-                    tick_spacing: 0,
-                },
+                token0: Address::from_raw(U256::zero()),
+                token1: Address::from_raw(U256::zero()),
+                config: Config::from_raw_unchecked(0, 0, U256::zero()),
             },
             FullRangePoolState {
                 sqrt_ratio: U256::one() << 128,
@@ -285,6 +479,63 @@ mod tests {
         assert_eq!(result.unwrap_err(), FullRangePoolError::TokenOrderInvalid);
     }
 
+    #[test]
+    fn test_apply_events() {
+        let mut pool = FullRangePool::new(
+            node_key(0),
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 0,
+            },
+        )
+        .expect("Pool creation should succeed");
+
+        pool.apply_event(&PoolEvent::PositionUpdated {
+            tick_lower: MIN_TICK,
+            tick_upper: MAX_TICK,
+            liquidity_delta: 1_000,
+        })
+        .unwrap();
+        pool.apply_event(&PoolEvent::Swapped {
+            sqrt_ratio_after: U256::one() << 127,
+            tick_after: -1386295,
+            liquidity_after: 1_000,
+        })
+        .unwrap();
+        assert_eq!(
+            pool.get_state(),
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 127,
+                liquidity: 1_000,
+            }
+        );
+
+        assert_eq!(
+            pool.apply_event(&PoolEvent::PositionUpdated {
+                tick_lower: -100,
+                tick_upper: 100,
+                liquidity_delta: 1_000,
+            }),
+            Err(ApplyEventError::InvalidPositionBounds)
+        );
+        assert_eq!(
+            pool.apply_event(&PoolEvent::PositionUpdated {
+                tick_lower: MIN_TICK,
+                tick_upper: MAX_TICK,
+                liquidity_delta: -1_001,
+            }),
+            Err(ApplyEventError::LiquidityOverflow)
+        );
+        assert_eq!(
+            pool.apply_event(&PoolEvent::Swapped {
+                sqrt_ratio_after: U256::zero(),
+                tick_after: MIN_TICK,
+                liquidity_after: 1_000,
+            }),
+            Err(ApplyEventError::InvalidStateAfter)
+        );
+    }
+
     #[test]
     fn test_quote_zero_liquidity() {
         let pool = FullRangePool::new(
@@ -302,6 +553,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
             meta: (),
         };
@@ -330,6 +582,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
             meta: (),
         };
@@ -358,6 +611,7 @@ mod tests {
                 token: TOKEN1,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
             meta: (),
         };
@@ -386,6 +640,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
             meta: (),
         };
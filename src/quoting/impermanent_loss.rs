@@ -0,0 +1,299 @@
+use crate::math::delta::AmountDeltaError;
+use crate::math::liquidity::position_amounts;
+use crate::math::muldiv::muldiv;
+use crate::math::tick::{to_sqrt_ratio, MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+use crate::math::uint::{u256_to_float_base_x128, U256};
+use crate::quoting::fee_apr::{estimate_fee_apr, FeeAprError, FeeVolume, SECONDS_PER_YEAR};
+use alloc::vec::Vec;
+
+const TWO_POW_128: U256 = U256([0, 0, 1, 0]);
+
+// A concentrated liquidity position between two ticks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionRange {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+}
+
+// The outcome of holding the position for a duration, as returns relative to holding the initial tokens
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImpermanentLossCell {
+    pub duration: u64,
+    // The fees earned over the duration divided by the initial value of the position
+    pub fee_return: f64,
+    // The impermanent loss plus the fee return, which is non-negative if the fees make up for the loss
+    pub net_return: f64,
+}
+
+// The outcomes of a single price move over all durations of the grid
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImpermanentLossRow {
+    // The price move in ticks, e.g. 693147 for the price of token0 doubling
+    pub price_move_ticks: i32,
+    // The value of the position after the move divided by the value of the initial tokens at the new price, minus one
+    pub impermanent_loss: f64,
+    // The time it takes for the fees to make up for the impermanent loss, if the position earns fees
+    pub breakeven_seconds: Option<f64>,
+    pub cells: Vec<ImpermanentLossCell>,
+}
+
+// Impermanent loss and fee breakeven over a grid of price moves (rows) and durations (cells of each row)
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImpermanentLossSurface {
+    pub position: PositionRange,
+    // The fee APR that the fee returns are based on
+    pub apr: f64,
+    pub rows: Vec<ImpermanentLossRow>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ImpermanentLossError {
+    /// Estimating the fees of the position failed.
+    FeeApr(FeeAprError),
+    /// The price move results in a price outside of the valid range.
+    InvalidPriceMove(i32),
+    /// Computing the amounts of the position failed.
+    AmountDelta(AmountDeltaError),
+}
+
+// Returns the sqrt ratio after moving the price by the given number of ticks
+fn move_sqrt_ratio(sqrt_ratio: U256, price_move_ticks: i32) -> Option<U256> {
+    let factor = to_sqrt_ratio(price_move_ticks)?;
+    muldiv(sqrt_ratio, factor, TWO_POW_128, false)
+        .ok()
+        .filter(|moved| (MIN_SQRT_RATIO..=MAX_SQRT_RATIO).contains(moved))
+}
+
+// The value of the amounts in token1 at the given sqrt ratio
+fn value(amounts: (u128, u128), sqrt_ratio: U256) -> f64 {
+    let sqrt_price = u256_to_float_base_x128(sqrt_ratio);
+    amounts.0 as f64 * sqrt_price * sqrt_price + amounts.1 as f64
+}
+
+/// Computes the impermanent loss and the fee return of a position over a grid of price moves and durations, for
+/// plotting e.g. as a heatmap of the net return.
+///
+/// The impermanent loss of a price move compares the position after the move with holding the tokens it was created
+/// with. The fees are estimated with [`estimate_fee_apr`] at the current price and accrue linearly over the duration,
+/// which assumes the volume is representative of the future and the price stays in range for most of the duration.
+pub fn impermanent_loss_surface(
+    sqrt_ratio: U256,
+    active_liquidity: u128,
+    volume: FeeVolume,
+    position: PositionRange,
+    price_moves_ticks: &[i32],
+    durations: &[u64],
+) -> Result<ImpermanentLossSurface, ImpermanentLossError> {
+    let sqrt_ratio_lower = to_sqrt_ratio(position.tick_lower).ok_or(
+        ImpermanentLossError::FeeApr(FeeAprError::InvalidTick(position.tick_lower)),
+    )?;
+    let sqrt_ratio_upper = to_sqrt_ratio(position.tick_upper).ok_or(
+        ImpermanentLossError::FeeApr(FeeAprError::InvalidTick(position.tick_upper)),
+    )?;
+
+    let apr = estimate_fee_apr(
+        sqrt_ratio,
+        active_liquidity,
+        volume,
+        sqrt_ratio_lower,
+        sqrt_ratio_upper,
+        position.liquidity,
+    )
+    .map_err(ImpermanentLossError::FeeApr)?
+    .apr;
+
+    let initial_amounts = position_amounts(
+        sqrt_ratio,
+        sqrt_ratio_lower,
+        sqrt_ratio_upper,
+        position.liquidity,
+        false,
+    )
+    .map_err(ImpermanentLossError::AmountDelta)?;
+
+    let rows = price_moves_ticks
+        .iter()
+        .map(|&price_move_ticks| {
+            let moved = move_sqrt_ratio(sqrt_ratio, price_move_ticks)
+                .ok_or(ImpermanentLossError::InvalidPriceMove(price_move_ticks))?;
+            let amounts = position_amounts(
+                moved,
+                sqrt_ratio_lower,
+                sqrt_ratio_upper,
+                position.liquidity,
+                false,
+            )
+            .map_err(ImpermanentLossError::AmountDelta)?;
+
+            let hodl_value = value(initial_amounts, moved);
+            let impermanent_loss = if hodl_value > 0f64 {
+                // rounding can make it slightly positive, but a position never outperforms holding
+                (value(amounts, moved) / hodl_value - 1f64).min(0f64)
+            } else {
+                0f64
+            };
+
+            let breakeven_seconds =
+                (apr > 0f64).then(|| -impermanent_loss / apr * SECONDS_PER_YEAR as f64);
+
+            let cells = durations
+                .iter()
+                .map(|&duration| {
+                    let fee_return = apr * duration as f64 / SECONDS_PER_YEAR as f64;
+                    ImpermanentLossCell {
+                        duration,
+                        fee_return,
+                        net_return: impermanent_loss + fee_return,
+                    }
+                })
+                .collect();
+
+            Ok(ImpermanentLossRow {
+                price_move_ticks,
+                impermanent_loss,
+                breakeven_seconds,
+                cells,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ImpermanentLossSurface {
+        position,
+        apr,
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::MAX_TICK;
+
+    const ONE: U256 = TWO_POW_128;
+    // the price of token0 doubles
+    const DOUBLE: i32 = 693_147;
+
+    fn volume() -> FeeVolume {
+        FeeVolume {
+            fees0: 1_000,
+            fees1: 1_000,
+            period_seconds: SECONDS_PER_YEAR / 365,
+        }
+    }
+
+    fn wide_position() -> PositionRange {
+        PositionRange {
+            tick_lower: -80_000_000,
+            tick_upper: 80_000_000,
+            liquidity: 1_000_000_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_matches_full_range_impermanent_loss() {
+        let surface = impermanent_loss_surface(
+            ONE,
+            1_000_000_000_000_000,
+            volume(),
+            wide_position(),
+            &[-DOUBLE, 0, DOUBLE],
+            &[],
+        )
+        .unwrap();
+
+        // 2 * sqrt(r) / (1 + r) - 1 for r = 2 and r = 1/2
+        let expected = 2f64 * 2f64.sqrt() / 3f64 - 1f64;
+        assert!((surface.rows[0].impermanent_loss - expected).abs() < 1e-6);
+        assert_eq!(surface.rows[1].impermanent_loss, 0f64);
+        assert!((surface.rows[2].impermanent_loss - expected).abs() < 1e-6);
+        assert_eq!(surface.rows[1].breakeven_seconds, Some(0f64));
+    }
+
+    #[test]
+    fn test_fee_breakeven() {
+        let durations = [0, SECONDS_PER_YEAR / 12, SECONDS_PER_YEAR];
+        let surface = impermanent_loss_surface(
+            ONE,
+            1_000_000_000,
+            volume(),
+            PositionRange {
+                tick_lower: -1_000_000,
+                tick_upper: 1_000_000,
+                liquidity: 1_000_000_000,
+            },
+            &[DOUBLE],
+            &durations,
+        )
+        .unwrap();
+        assert!(surface.apr > 0f64);
+
+        let row = &surface.rows[0];
+        assert!(row.impermanent_loss < 0f64);
+        assert_eq!(row.cells.len(), durations.len());
+        assert_eq!(row.cells[0].fee_return, 0f64);
+        assert_eq!(row.cells[0].net_return, row.impermanent_loss);
+        assert!((row.cells[2].fee_return - surface.apr).abs() < 1e-12);
+
+        // the net return is zero at the breakeven time
+        let breakeven = row.breakeven_seconds.unwrap();
+        let fee_return = surface.apr * breakeven / SECONDS_PER_YEAR as f64;
+        assert!((row.impermanent_loss + fee_return).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_no_fees_never_break_even() {
+        let surface = impermanent_loss_surface(
+            ONE,
+            0,
+            FeeVolume {
+                fees0: 0,
+                fees1: 0,
+                period_seconds: 1,
+            },
+            wide_position(),
+            &[DOUBLE],
+            &[SECONDS_PER_YEAR],
+        )
+        .unwrap();
+        assert_eq!(surface.rows[0].breakeven_seconds, None);
+        assert_eq!(
+            surface.rows[0].cells[0].net_return,
+            surface.rows[0].impermanent_loss
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            impermanent_loss_surface(
+                to_sqrt_ratio(1).unwrap(),
+                0,
+                volume(),
+                wide_position(),
+                &[MAX_TICK],
+                &[]
+            ),
+            Err(ImpermanentLossError::InvalidPriceMove(MAX_TICK))
+        );
+        assert_eq!(
+            impermanent_loss_surface(
+                ONE,
+                0,
+                volume(),
+                PositionRange {
+                    tick_lower: 100,
+                    tick_upper: -100,
+                    liquidity: 1,
+                },
+                &[0],
+                &[]
+            ),
+            Err(ImpermanentLossError::FeeApr(FeeAprError::InvalidRange))
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use crate::quoting::twamm_pool::{
+    TwammPool, TwammPoolQuoteError, TwammPoolResources, TwammPoolState, VirtualOrderInterval,
+};
+use crate::quoting::types::{BlockTimestamp, Pool, QuoteParams, TokenAmount};
+use alloc::vec::Vec;
+
+// Estimates the gas units used by executing the virtual orders of a TWAMM pool from the resources of the execution
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VirtualOrderGasModel {
+    // The gas of the transaction and the call to the extension, independent of the orders
+    pub base: u64,
+    pub per_interval: u64,
+    pub per_swap: u64,
+    // The gas of loading and applying the sale rate deltas at a time
+    pub per_delta_time_crossed: u64,
+}
+
+impl Default for VirtualOrderGasModel {
+    // Rough costs of executing virtual orders, to be calibrated for the deployment
+    fn default() -> Self {
+        Self {
+            base: 40_000,
+            per_interval: 5_000,
+            per_swap: 25_000,
+            per_delta_time_crossed: 10_000,
+        }
+    }
+}
+
+impl VirtualOrderGasModel {
+    pub fn execution_gas(&self, resources: &TwammPoolResources) -> u64 {
+        self.base
+            + self.per_interval * resources.virtual_order_intervals_executed as u64
+            + self.per_swap * resources.virtual_order_swaps_executed as u64
+            + self.per_delta_time_crossed * resources.virtual_order_delta_times_crossed as u64
+    }
+}
+
+// The times at which execution is considered and the price of the gas to execute
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeeperSchedule {
+    pub gas_model: VirtualOrderGasModel,
+    // The gas price in wei per gas
+    pub gas_price: u128,
+    // The times from the last execution time up to and including the deadline are considered every step seconds
+    pub step: u64,
+    pub deadline: BlockTimestamp,
+}
+
+// The cost and benefit of executing the virtual orders at a time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeeperEstimate {
+    pub time: BlockTimestamp,
+    pub gas: u64,
+    // The cost of the gas in wei
+    pub cost: u128,
+    // The benefit of executing in wei, as valued by the keeper
+    pub benefit: u128,
+}
+
+impl KeeperEstimate {
+    pub fn is_profitable(&self) -> bool {
+        self.benefit > self.cost
+    }
+}
+
+// Estimates the cost of executing the pending virtual orders of the pool at the time, and values the intervals that
+// would be executed with the benefit function
+pub fn estimate_execution(
+    pool: &TwammPool,
+    override_state: Option<TwammPoolState>,
+    time: BlockTimestamp,
+    schedule: &KeeperSchedule,
+    benefit: &mut impl FnMut(BlockTimestamp, &[VirtualOrderInterval]) -> u128,
+) -> Result<KeeperEstimate, TwammPoolQuoteError> {
+    let mut intervals = Vec::new();
+    pool.execute_virtual_orders(override_state, time, |interval| intervals.push(*interval))?;
+
+    // executing the virtual orders is the same as a swap of nothing
+    let quote = pool.quote(QuoteParams {
+        token_amount: TokenAmount {
+            token: pool.get_key().token0,
+            amount: 0,
+        },
+        sqrt_ratio_limit: None,
+        max_ticks_crossed: None,
+        override_state,
+        meta: time,
+    })?;
+    let gas = schedule.gas_model.execution_gas(&quote.execution_resources);
+
+    Ok(KeeperEstimate {
+        time,
+        gas,
+        cost: (gas as u128).saturating_mul(schedule.gas_price),
+        benefit: benefit(time, &intervals),
+    })
+}
+
+/// Returns the earliest time in the schedule at which executing the pending virtual orders of the pool is profitable.
+///
+/// The benefit function values the virtual order intervals that an execution at a time would execute, e.g. the
+/// protocol incentive or the arbitrage that the execution opens up, in wei. Returns None if the execution is not
+/// profitable at any time up to the deadline.
+pub fn earliest_profitable_execution(
+    pool: &TwammPool,
+    override_state: Option<TwammPoolState>,
+    schedule: &KeeperSchedule,
+    mut benefit: impl FnMut(BlockTimestamp, &[VirtualOrderInterval]) -> u128,
+) -> Result<Option<KeeperEstimate>, TwammPoolQuoteError> {
+    let last_execution_time = override_state
+        .unwrap_or_else(|| pool.get_state())
+        .last_execution_time;
+    let mut time = last_execution_time;
+
+    while time <= schedule.deadline {
+        let estimate = estimate_execution(pool, override_state, time, schedule, &mut benefit)?;
+        if estimate.is_profitable() {
+            return Ok(Some(estimate));
+        }
+
+        time = match time.checked_add(schedule.step.max(1)) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::uint::U256;
+    use crate::quoting::twamm_pool::TwammSaleRateDelta;
+    use crate::quoting::types::Address;
+    use alloc::vec;
+
+    fn pool() -> TwammPool {
+        TwammPool::new(
+            Address::from_raw(U256::from(1)),
+            Address::from_raw(U256::from(2)),
+            0,
+            U256::from(3),
+            U256::one() << 128,
+            1_000_000_000,
+            0,
+            1000 << 32,
+            0,
+            vec![
+                TwammSaleRateDelta {
+                    time: 50,
+                    sale_rate_delta0: 1000 << 32,
+                    sale_rate_delta1: 0,
+                },
+                TwammSaleRateDelta {
+                    time: 100,
+                    sale_rate_delta0: -(2000 << 32),
+                    sale_rate_delta1: 0,
+                },
+            ],
+        )
+        .unwrap()
+    }
+
+    // values the token1 bought by the orders at one wei each
+    fn proceeds(_time: BlockTimestamp, intervals: &[VirtualOrderInterval]) -> u128 {
+        intervals.iter().map(|interval| interval.proceeds0).sum()
+    }
+
+    fn schedule(gas_price: u128) -> KeeperSchedule {
+        KeeperSchedule {
+            gas_model: VirtualOrderGasModel::default(),
+            gas_price,
+            step: 10,
+            deadline: 200,
+        }
+    }
+
+    #[test]
+    fn test_execution_gas_grows_with_intervals() {
+        let pool = pool();
+        let before = estimate_execution(&pool, None, 40, &schedule(1), &mut proceeds).unwrap();
+        let after = estimate_execution(&pool, None, 60, &schedule(1), &mut proceeds).unwrap();
+
+        assert_eq!(before.gas, 40_000 + 5_000 + 25_000);
+        assert_eq!(after.gas, 40_000 + 2 * 5_000 + 2 * 25_000 + 10_000);
+        assert!(after.benefit > before.benefit);
+    }
+
+    #[test]
+    fn test_earliest_profitable_execution() {
+        let pool = pool();
+        let estimate = earliest_profitable_execution(&pool, None, &schedule(1), proceeds)
+            .unwrap()
+            .unwrap();
+
+        // the first time at which the proceeds exceed the gas
+        assert!(estimate.is_profitable());
+        let earlier =
+            estimate_execution(&pool, None, estimate.time - 10, &schedule(1), &mut proceeds)
+                .unwrap();
+        assert!(!earlier.is_profitable());
+
+        // never profitable when gas is expensive
+        assert_eq!(
+            earliest_profitable_execution(&pool, None, &schedule(1 << 40), proceeds),
+            Ok(None)
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use crate::quoting::route::CalldataSize;
+
+// The fee that rollups charge for posting the calldata of a transaction to L1, on top of the L2 gas
+pub trait L1DataFee {
+    // Returns the fee in wei of the L2 native token
+    fn l1_data_fee(&self, calldata: CalldataSize) -> u128;
+}
+
+// For L1 deployments, where calldata is only paid with gas
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NoL1DataFee;
+
+impl L1DataFee for NoL1DataFee {
+    fn l1_data_fee(&self, _calldata: CalldataSize) -> u128 {
+        0
+    }
+}
+
+// The L1 data fee of OP stack chains since the Ecotone upgrade, with the parameters of the GasPriceOracle predeploy
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpStackL1DataFee {
+    pub l1_base_fee: u128,
+    pub blob_base_fee: u128,
+    pub base_fee_scalar: u32,
+    pub blob_base_fee_scalar: u32,
+}
+
+impl L1DataFee for OpStackL1DataFee {
+    fn l1_data_fee(&self, calldata: CalldataSize) -> u128 {
+        // the compressed size is estimated as the calldata gas divided by 16, scaled by 16 again in the fee
+        let weighted_gas_price = 16u128
+            .saturating_mul(self.base_fee_scalar as u128)
+            .saturating_mul(self.l1_base_fee)
+            .saturating_add((self.blob_base_fee_scalar as u128).saturating_mul(self.blob_base_fee));
+        (calldata.gas() as u128).saturating_mul(weighted_gas_price) / (16 * 1_000_000)
+    }
+}
+
+// The L1 data fee of Arbitrum chains, with the price per unit reported by the ArbGasInfo precompile
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArbitrumL1DataFee {
+    // The estimated L1 base fee, in wei per unit of L1 calldata gas
+    pub l1_price_per_unit: u128,
+}
+
+impl L1DataFee for ArbitrumL1DataFee {
+    fn l1_data_fee(&self, calldata: CalldataSize) -> u128 {
+        // the poster is charged 16 units per byte of the compressed calldata, which is approximated by the
+        // uncompressed calldata gas
+        (calldata.gas() as u128).saturating_mul(self.l1_price_per_unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALLDATA: CalldataSize = CalldataSize {
+        zero_bytes: 100,
+        nonzero_bytes: 50,
+    };
+
+    #[test]
+    fn test_op_stack_l1_data_fee() {
+        let fee = OpStackL1DataFee {
+            l1_base_fee: 10_000_000_000,
+            blob_base_fee: 1,
+            base_fee_scalar: 1368,
+            blob_base_fee_scalar: 810_949,
+        };
+        // (400 + 800) / 16 compressed bytes
+        let expected = 75 * (16 * 1368 * 10_000_000_000 + 810_949) / 1_000_000;
+        assert_eq!(fee.l1_data_fee(CALLDATA), expected);
+        assert_eq!(fee.l1_data_fee(CalldataSize::default()), 0);
+    }
+
+    #[test]
+    fn test_arbitrum_l1_data_fee() {
+        let fee = ArbitrumL1DataFee {
+            l1_price_per_unit: 1_000,
+        };
+        assert_eq!(fee.l1_data_fee(CALLDATA), 1_200_000);
+        assert_eq!(NoL1DataFee.l1_data_fee(CALLDATA), 0);
+    }
+}
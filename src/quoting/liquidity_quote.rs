@@ -0,0 +1,252 @@
+use crate::math::delta::AmountDeltaError;
+use crate::math::liquidity::{max_liquidity, position_amounts, LiquidityError};
+use crate::math::tick::{to_sqrt_ratio, FULL_RANGE_TICK_SPACING, MAX_TICK, MIN_TICK};
+use crate::math::uint::U256;
+use crate::quoting::types::{Pool, SqrtRatioState};
+
+// The bounds of a position, which must be multiples of the tick spacing of the pool, or the min and max tick for pools
+// that only have full range liquidity
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionBounds {
+    pub lower: i32,
+    pub upper: i32,
+}
+
+impl PositionBounds {
+    pub const FULL_RANGE: PositionBounds = PositionBounds {
+        lower: MIN_TICK,
+        upper: MAX_TICK,
+    };
+}
+
+// A deposit of liquidity into a position, with the amounts it uses and what is left of the desired amounts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AddLiquidityQuote {
+    pub liquidity: u128,
+    pub amount0: u128,
+    pub amount1: u128,
+    pub leftover0: u128,
+    pub leftover1: u128,
+}
+
+// The amounts that withdrawing liquidity from a position pays out
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemoveLiquidityQuote {
+    pub amount0: u128,
+    pub amount1: u128,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LiquidityQuoteError {
+    /// The bounds are not ordered, are outside of the valid ticks or are not valid for the tick spacing of the pool.
+    InvalidBounds(PositionBounds),
+    /// Computing the liquidity of the desired amounts failed.
+    Liquidity(LiquidityError),
+    /// Computing the amounts of the liquidity failed.
+    AmountDelta(AmountDeltaError),
+}
+
+// Returns the sqrt ratios of the bounds if they are valid for a pool with the tick spacing
+fn bounds_sqrt_ratios(
+    bounds: PositionBounds,
+    tick_spacing: u32,
+) -> Result<(U256, U256), LiquidityQuoteError> {
+    let valid = if tick_spacing == FULL_RANGE_TICK_SPACING {
+        bounds == PositionBounds::FULL_RANGE
+    } else {
+        let spacing = tick_spacing as i32;
+        bounds.lower < bounds.upper && bounds.lower % spacing == 0 && bounds.upper % spacing == 0
+    };
+
+    match (
+        valid,
+        to_sqrt_ratio(bounds.lower),
+        to_sqrt_ratio(bounds.upper),
+    ) {
+        (true, Some(lower), Some(upper)) => Ok((lower, upper)),
+        _ => Err(LiquidityQuoteError::InvalidBounds(bounds)),
+    }
+}
+
+/// Quotes depositing the most liquidity that the desired amounts allow into a position of the pool at its current
+/// price.
+///
+/// Like the contract, the liquidity is rounded down and the amounts are rounded up, so the amounts are exactly the
+/// amounts the contract takes and never exceed the desired amounts. The leftover amounts are the rest of the desired
+/// amounts, which is at least the dust of the token that does not limit the liquidity.
+pub fn quote_add_liquidity<P: Pool>(
+    pool: &P,
+    bounds: PositionBounds,
+    desired0: u128,
+    desired1: u128,
+) -> Result<AddLiquidityQuote, LiquidityQuoteError>
+where
+    P::State: SqrtRatioState,
+{
+    let (sqrt_ratio_lower, sqrt_ratio_upper) =
+        bounds_sqrt_ratios(bounds, pool.get_key().config.tick_spacing.raw())?;
+    let sqrt_ratio = pool.get_state().sqrt_ratio();
+
+    let liquidity = max_liquidity(
+        sqrt_ratio,
+        sqrt_ratio_lower,
+        sqrt_ratio_upper,
+        desired0,
+        desired1,
+    )
+    .map_err(LiquidityQuoteError::Liquidity)?;
+    let (amount0, amount1) = position_amounts(
+        sqrt_ratio,
+        sqrt_ratio_lower,
+        sqrt_ratio_upper,
+        liquidity,
+        true,
+    )
+    .map_err(LiquidityQuoteError::AmountDelta)?;
+
+    Ok(AddLiquidityQuote {
+        liquidity,
+        amount0,
+        amount1,
+        // the liquidity is rounded down, so the amounts it takes are at most the desired amounts
+        leftover0: desired0.saturating_sub(amount0),
+        leftover1: desired1.saturating_sub(amount1),
+    })
+}
+
+// Quotes withdrawing the liquidity from a position of the pool at its current price, with the amounts rounded down
+// like the contract
+pub fn quote_remove_liquidity<P: Pool>(
+    pool: &P,
+    bounds: PositionBounds,
+    liquidity: u128,
+) -> Result<RemoveLiquidityQuote, LiquidityQuoteError>
+where
+    P::State: SqrtRatioState,
+{
+    let (sqrt_ratio_lower, sqrt_ratio_upper) =
+        bounds_sqrt_ratios(bounds, pool.get_key().config.tick_spacing.raw())?;
+
+    let (amount0, amount1) = position_amounts(
+        pool.get_state().sqrt_ratio(),
+        sqrt_ratio_lower,
+        sqrt_ratio_upper,
+        liquidity,
+        false,
+    )
+    .map_err(LiquidityQuoteError::AmountDelta)?;
+
+    Ok(RemoveLiquidityQuote { amount0, amount1 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::base_pool::{BasePool, BasePoolState};
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::types::{Address, Config, NodeKey};
+    use alloc::vec;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn key(tick_spacing: u32) -> NodeKey {
+        NodeKey {
+            token0: TOKEN0,
+            token1: TOKEN1,
+            config: Config::from_raw_unchecked(0, tick_spacing, U256::zero()),
+        }
+    }
+
+    fn base_pool(tick: i32) -> BasePool {
+        BasePool::new(
+            key(100),
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(tick).unwrap(),
+                liquidity: 0,
+                active_tick_index: None,
+            },
+            vec![],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_quote_add_and_remove_liquidity() {
+        let bounds = PositionBounds {
+            lower: -1000,
+            upper: 1000,
+        };
+        let pool = base_pool(0);
+
+        let added = quote_add_liquidity(&pool, bounds, 1_000_000, 2_000_000).unwrap();
+        // token0 limits the liquidity at the middle of the range
+        assert!(added.leftover0 <= 1);
+        assert_eq!(added.amount0 + added.leftover0, 1_000_000);
+        assert_eq!(added.amount1 + added.leftover1, 2_000_000);
+        assert!(added.leftover1 > 900_000);
+
+        // withdrawing right away pays out at most the deposited amounts, which differ only by the rounding
+        let removed = quote_remove_liquidity(&pool, bounds, added.liquidity).unwrap();
+        assert!(removed.amount0 <= added.amount0 && added.amount0 - removed.amount0 <= 1);
+        assert!(removed.amount1 <= added.amount1 && added.amount1 - removed.amount1 <= 1);
+
+        // a position above the price only takes token0
+        let added = quote_add_liquidity(&base_pool(-2000), bounds, 1_000_000, 1_000_000).unwrap();
+        assert_eq!((added.amount1, added.leftover1), (0, 1_000_000));
+    }
+
+    #[test]
+    fn test_invalid_bounds() {
+        let pool = base_pool(0);
+        for bounds in [
+            PositionBounds {
+                lower: 100,
+                upper: 100,
+            },
+            PositionBounds {
+                lower: -150,
+                upper: 100,
+            },
+            PositionBounds::FULL_RANGE,
+        ] {
+            assert_eq!(
+                quote_add_liquidity(&pool, bounds, 1, 1),
+                Err(LiquidityQuoteError::InvalidBounds(bounds))
+            );
+        }
+
+        let full_range_pool = FullRangePool::new(
+            key(0),
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 0,
+            },
+        )
+        .unwrap();
+        let added = quote_add_liquidity(&full_range_pool, PositionBounds::FULL_RANGE, 1_000, 1_000)
+            .unwrap();
+        let deposit = full_range_pool.deposit(1_000, 1_000).unwrap();
+        assert_eq!(
+            (added.liquidity, added.amount0, added.amount1),
+            (deposit.liquidity, deposit.amount0, deposit.amount1)
+        );
+        assert_eq!(
+            quote_remove_liquidity(
+                &full_range_pool,
+                PositionBounds {
+                    lower: -100,
+                    upper: 100
+                },
+                1
+            ),
+            Err(LiquidityQuoteError::InvalidBounds(PositionBounds {
+                lower: -100,
+                upper: 100
+            }))
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use crate::math::uint::U256;
+use crate::quoting::bps::Bps;
+use crate::quoting::pool_map::PoolMap;
+use crate::quoting::route::{quote_route, quote_route_with_states, Route, RouteQuoteError};
+use crate::quoting::types::{Pool, QuoteParams, TokenAmount};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+// The outcome of the route when the pool of a single hop is front-run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrontRunScenario {
+    pub split: usize,
+    pub hop: usize,
+    // The amount of the input token of the hop that is swapped through its pool before the route
+    pub front_run_amount: u128,
+    pub amount_out: u128,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MevImpactEstimate {
+    // The output of the route against the current states of the pools
+    pub expected_amount_out: u128,
+    // The lowest output across all the scenarios, or the expected output if the route has no hops
+    pub worst_case_amount_out: u128,
+    pub scenarios: Vec<FrontRunScenario>,
+}
+
+impl MevImpactEstimate {
+    // Returns the loss of the worst case relative to the expected output in basis points, rounded up
+    pub fn worst_case_loss_bps(&self) -> u32 {
+        if self.expected_amount_out == 0 {
+            return 0;
+        }
+
+        Bps::ratio(
+            U256::from(self.expected_amount_out - self.worst_case_amount_out),
+            U256::from(self.expected_amount_out),
+            true,
+        )
+        .map_or(0, Bps::raw)
+    }
+}
+
+/// Estimates the output of the route when an adversary front-runs the swap.
+///
+/// For each hop of the route, the pool of the hop is front-run by swapping `front_run_share_bps` basis points of the
+/// input amount of the hop in the same direction, and the route is quoted against the resulting state. The
+/// back-running leg of a sandwich does not affect the output of the route, so it is not simulated.
+///
+/// Fails if the route cannot be quoted, either against the current states or in any of the scenarios.
+pub fn estimate_mev_impact<P: Pool>(
+    pools: &PoolMap<P>,
+    route: &Route,
+    amount_in: u128,
+    front_run_share_bps: u32,
+    meta: P::Meta,
+) -> Result<MevImpactEstimate, RouteQuoteError<P::QuoteError>> {
+    let expected = quote_route(pools, route, amount_in, meta)?;
+    let mut scenarios = Vec::new();
+
+    for (split_index, (split, split_quote)) in route.splits.iter().zip(&expected.splits).enumerate()
+    {
+        for (hop_index, hop) in split.hops.iter().enumerate() {
+            let front_run_amount = Bps::new(front_run_share_bps)
+                .apply_floor(U256::from(split_quote.amounts[hop_index]))
+                .and_then(|amount| amount.try_into().ok())
+                .ok_or(RouteQuoteError::AmountOverflow)?;
+
+            let pool = pools
+                .get(&hop.pool_key)
+                .ok_or(RouteQuoteError::PoolNotFound {
+                    split: split_index,
+                    hop: hop_index,
+                })?;
+            // the front-run may be only partially filled, which still moves the price as far as possible
+            let front_run = pool
+                .quote(QuoteParams {
+                    token_amount: TokenAmount {
+                        token: hop.direction.token_in(&hop.pool_key),
+                        amount: i128::try_from(front_run_amount)
+                            .map_err(|_| RouteQuoteError::AmountOverflow)?,
+                    },
+                    sqrt_ratio_limit: None,
+                    max_ticks_crossed: None,
+                    override_state: None,
+                    meta,
+                })
+                .map_err(|error| RouteQuoteError::QuoteFailed {
+                    split: split_index,
+                    hop: hop_index,
+                    error,
+                })?;
+
+            let mut states = BTreeMap::from([(hop.pool_key, front_run.state_after)]);
+            let quote = quote_route_with_states(pools, route, amount_in, meta, &mut states)?;
+
+            scenarios.push(FrontRunScenario {
+                split: split_index,
+                hop: hop_index,
+                front_run_amount,
+                amount_out: quote.amount_out,
+            });
+        }
+    }
+
+    Ok(MevImpactEstimate {
+        expected_amount_out: expected.amount_out,
+        worst_case_amount_out: scenarios
+            .iter()
+            .map(|scenario| scenario.amount_out)
+            .min()
+            .unwrap_or(expected.amount_out),
+        scenarios,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::route::{HopDirection, RouteHop, RouteSplit, ROUTE_SHARE_DENOMINATOR};
+    use crate::quoting::types::{Address, Config, NodeKey};
+    use alloc::vec;
+
+    fn pool(token0: u64, token1: u64, liquidity: u128) -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0: Address::from_raw(U256::from(token0)),
+                token1: Address::from_raw(U256::from(token1)),
+                config: Config::from_raw_unchecked(0, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity,
+            },
+        )
+        .unwrap()
+    }
+
+    fn two_hop_route(pools: &PoolMap<FullRangePool>) -> Route {
+        let keys: Vec<NodeKey> = pools.iter().map(|pool| *pool.get_key()).collect();
+        Route {
+            token_in: Address::from_raw(U256::from(1)),
+            token_out: Address::from_raw(U256::from(3)),
+            splits: vec![RouteSplit {
+                share: ROUTE_SHARE_DENOMINATOR,
+                hops: vec![
+                    RouteHop {
+                        pool_key: keys[0],
+                        direction: HopDirection::ZeroForOne,
+                    },
+                    RouteHop {
+                        pool_key: keys[1],
+                        direction: HopDirection::ZeroForOne,
+                    },
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_estimate_mev_impact() {
+        // the second hop has much less liquidity, so front-running it hurts the most
+        let pools: PoolMap<FullRangePool> = [pool(1, 2, 100_000_000), pool(2, 3, 1_000_000)]
+            .into_iter()
+            .collect();
+        let route = two_hop_route(&pools);
+
+        let estimate = estimate_mev_impact(&pools, &route, 10_000, 10_000, ()).unwrap();
+
+        assert_eq!(estimate.expected_amount_out, 9900);
+        assert_eq!(estimate.scenarios.len(), 2);
+        assert_eq!(estimate.scenarios[0].front_run_amount, 10_000);
+        assert_eq!(estimate.scenarios[1].front_run_amount, 9999);
+        assert!(estimate.scenarios[0].amount_out > estimate.scenarios[1].amount_out);
+        assert_eq!(
+            estimate.worst_case_amount_out,
+            estimate.scenarios[1].amount_out
+        );
+        assert_eq!(estimate.worst_case_amount_out, 9704);
+        assert_eq!(estimate.worst_case_loss_bps(), 198);
+    }
+
+    #[test]
+    fn test_no_front_run() {
+        let pools: PoolMap<FullRangePool> = [pool(1, 2, 1_000_000), pool(2, 3, 1_000_000)]
+            .into_iter()
+            .collect();
+        let route = two_hop_route(&pools);
+
+        let estimate = estimate_mev_impact(&pools, &route, 10_000, 0, ()).unwrap();
+        assert_eq!(estimate.worst_case_amount_out, estimate.expected_amount_out);
+        assert_eq!(estimate.worst_case_loss_bps(), 0);
+    }
+}
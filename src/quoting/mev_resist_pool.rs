@@ -1,7 +1,11 @@
 use crate::math::swap::{amount_before_fee, compute_fee};
 use crate::math::tick::{approximate_sqrt_ratio_to_tick, FULL_RANGE_TICK_SPACING};
+use crate::math::uint::U256;
 use crate::quoting::base_pool::{BasePool, BasePoolQuoteError, BasePoolResources, BasePoolState};
-use crate::quoting::types::{BlockTimestamp, NodeKey, Pool, Quote, QuoteParams};
+use crate::quoting::pool_diff::{FieldDiff, PoolDiff};
+use crate::quoting::pool_reads::{Inventory, PoolReadError, PoolReads};
+use crate::quoting::resources::MaxResourcesEstimate;
+use crate::quoting::types::{BlockTimestamp, NodeKey, Pool, Quote, QuoteParams, SqrtRatioState};
 use core::ops::{Add, AddAssign, Sub, SubAssign};
 
 // Resources consumed during any swap execution in a full range pool.
@@ -11,6 +15,8 @@ pub struct MEVResistPoolResources {
     pub base_pool_resources: BasePoolResources,
 }
 
+crate::impl_flatten_resources!(MEVResistPoolResources { state_update_count } nested { base_pool_resources });
+
 impl AddAssign for MEVResistPoolResources {
     fn add_assign(&mut self, rhs: Self) {
         self.state_update_count += rhs.state_update_count;
@@ -57,6 +63,12 @@ pub struct MEVResistPoolState {
     pub base_pool_state: BasePoolState,
 }
 
+impl SqrtRatioState for MEVResistPoolState {
+    fn sqrt_ratio(&self) -> U256 {
+        self.base_pool_state.sqrt_ratio
+    }
+}
+
 /// Errors that can occur when constructing a MEVResistPool.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum MEVResistPoolError {
@@ -74,10 +86,10 @@ impl MEVResistPool {
         tick: i32,
     ) -> Result<Self, MEVResistPoolError> {
         let key = base_pool.get_key();
-        if key.config.fee == 0 {
+        if key.config.fee.raw() == 0 {
             return Err(MEVResistPoolError::FeeMustBeGreaterThanZero);
         }
-        if key.config.tick_spacing == FULL_RANGE_TICK_SPACING {
+        if key.config.tick_spacing.raw() == FULL_RANGE_TICK_SPACING {
             return Err(MEVResistPoolError::CannotBeFullRange);
         }
         if key.config.extension.is_zero() {
@@ -111,6 +123,18 @@ impl MEVResistPool {
             tick,
         })
     }
+
+    // Reports the fields that differ between this pool and the other pool, including added, removed and changed ticks
+    pub fn diff(&self, other: &Self) -> PoolDiff {
+        PoolDiff {
+            last_update_time: FieldDiff::of(
+                self.last_update_time.into(),
+                other.last_update_time.into(),
+            ),
+            reference_tick: FieldDiff::of(self.tick, other.tick),
+            ..self.base_pool.diff(&other.base_pool)
+        }
+    }
 }
 
 impl Pool for MEVResistPool {
@@ -134,10 +158,24 @@ impl Pool for MEVResistPool {
         &self,
         params: QuoteParams<Self::State, Self::Meta>,
     ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
-        match self.base_pool.quote(QuoteParams {
+        self.quote_borrowed(QuoteParams {
             token_amount: params.token_amount,
             sqrt_ratio_limit: params.sqrt_ratio_limit,
-            override_state: params.override_state.map(|o| o.base_pool_state),
+            max_ticks_crossed: params.max_ticks_crossed,
+            override_state: params.override_state.as_ref(),
+            meta: params.meta,
+        })
+    }
+
+    fn quote_borrowed(
+        &self,
+        params: QuoteParams<&Self::State, Self::Meta>,
+    ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
+        match self.base_pool.quote_borrowed(QuoteParams {
+            token_amount: params.token_amount,
+            sqrt_ratio_limit: params.sqrt_ratio_limit,
+            max_ticks_crossed: params.max_ticks_crossed,
+            override_state: params.override_state.map(|o| &o.base_pool_state),
             meta: (),
         }) {
             Ok(quote) => {
@@ -147,10 +185,10 @@ impl Pool for MEVResistPool {
 
                 let pool_config = self.base_pool.get_key().config;
                 let approximate_fee_multiplier = (((tick_after_swap - self.tick).abs() + 1) as f64)
-                    / (pool_config.tick_spacing as f64);
+                    / (pool_config.tick_spacing.raw() as f64);
 
                 let fixed_point_additional_fee: u64 =
-                    ((approximate_fee_multiplier * pool_config.fee as f64).round() as u128)
+                    ((approximate_fee_multiplier * pool_config.fee.raw() as f64).round() as u128)
                         .min(u64::MAX as u128) as u64;
 
                 let pool_time = params
@@ -167,7 +205,8 @@ impl Pool for MEVResistPool {
                     // exact input, remove the additional fee from the output
                     calculated_amount -= compute_fee(calculated_amount, fixed_point_additional_fee);
                 } else {
-                    let input_amount_fee: u128 = compute_fee(calculated_amount, pool_config.fee);
+                    let input_amount_fee: u128 =
+                        compute_fee(calculated_amount, pool_config.fee.raw());
                     let input_amount = calculated_amount - input_amount_fee;
 
                     if let Some(bf) = amount_before_fee(input_amount, fixed_point_additional_fee) {
@@ -217,8 +256,24 @@ impl Pool for MEVResistPool {
     }
 }
 
+impl MaxResourcesEstimate for MEVResistPool {
+    fn max_resources_estimate(&self, amount_bound: u128) -> Self::Resources {
+        MEVResistPoolResources {
+            state_update_count: 1,
+            base_pool_resources: self.base_pool.max_resources_estimate(amount_bound),
+        }
+    }
+}
+
+impl PoolReads for MEVResistPool {
+    fn inventory_at(&self, sqrt_ratio: U256) -> Result<Inventory, PoolReadError> {
+        self.base_pool.inventory_at(sqrt_ratio)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::quoting::types::Address;
     use alloc::vec;
 
     use crate::{
@@ -236,13 +291,13 @@ mod tests {
         let pool = MEVResistPool::new(
             BasePool::new(
                 NodeKey {
-                    token0: U256::one(),
-                    token1: U256::one() + U256::one(),
-                    config: Config {
-                        fee: ((1_u128 << 64) / 100) as u64,
-                        tick_spacing: 20_000,
-                        extension: U256::one(),
-                    },
+                    token0: Address::from_raw(U256::one()),
+                    token1: Address::from_raw(U256::one() + U256::one()),
+                    config: Config::from_raw_unchecked(
+                        ((1_u128 << 64) / 100) as u64,
+                        20_000,
+                        U256::one(),
+                    ),
                 },
                 BasePoolState {
                     active_tick_index: Some(0),
@@ -271,9 +326,10 @@ mod tests {
                 meta: 1,
                 override_state: None,
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 token_amount: TokenAmount {
                     amount: 100_000,
-                    token: U256::one(),
+                    token: Address::from_raw(U256::one()),
                 },
             })
             .unwrap();
@@ -290,9 +346,10 @@ mod tests {
                 meta: 1,
                 override_state: None,
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 token_amount: TokenAmount {
                     amount: 300_000,
-                    token: U256::one(),
+                    token: Address::from_raw(U256::one()),
                 },
             })
             .unwrap();
@@ -301,9 +358,10 @@ mod tests {
                 meta: 1,
                 override_state: Some(result.state_after),
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 token_amount: TokenAmount {
                     amount: 300_000,
-                    token: U256::one(),
+                    token: Address::from_raw(U256::one()),
                 },
             })
             .unwrap();
@@ -319,13 +377,13 @@ mod tests {
         let pool = MEVResistPool::new(
             BasePool::new(
                 NodeKey {
-                    token0: U256::one(),
-                    token1: U256::one() + U256::one(),
-                    config: Config {
-                        fee: ((1_u128 << 64) / 100) as u64,
-                        tick_spacing: 20_000,
-                        extension: U256::one(),
-                    },
+                    token0: Address::from_raw(U256::one()),
+                    token1: Address::from_raw(U256::one() + U256::one()),
+                    config: Config::from_raw_unchecked(
+                        ((1_u128 << 64) / 100) as u64,
+                        20_000,
+                        U256::one(),
+                    ),
                 },
                 BasePoolState {
                     active_tick_index: Some(0),
@@ -354,9 +412,10 @@ mod tests {
                 meta: 1,
                 override_state: None,
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 token_amount: TokenAmount {
                     amount: -100_000,
-                    token: U256::one(),
+                    token: Address::from_raw(U256::one()),
                 },
             })
             .unwrap();
@@ -378,13 +437,9 @@ mod tests {
         let pool = MEVResistPool::new(
             BasePool::new(
                 NodeKey {
-                    token0: U256::zero(),
-                    token1: U256::one(),
-                    config: Config {
-                        fee: fee,
-                        tick_spacing: tick_spacing,
-                        extension: U256::one(),
-                    },
+                    token0: Address::from_raw(U256::zero()),
+                    token1: Address::from_raw(U256::one()),
+                    config: Config::from_raw_unchecked(fee, tick_spacing, U256::one()),
                 },
                 BasePoolState {
                     active_tick_index: Some(0),
@@ -415,9 +470,10 @@ mod tests {
                 meta: 2,
                 override_state: None,
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 token_amount: TokenAmount {
                     amount: specified_amount,
-                    token: U256::zero(),
+                    token: Address::from_raw(U256::zero()),
                 },
             })
             .unwrap();
@@ -433,9 +489,10 @@ mod tests {
                 meta: 2,
                 override_state: None,
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 token_amount: TokenAmount {
                     amount: specified_amount,
-                    token: U256::zero(),
+                    token: Address::from_raw(U256::zero()),
                 },
             })
             .unwrap();
@@ -456,13 +513,9 @@ mod tests {
         let pool = MEVResistPool::new(
             BasePool::new(
                 NodeKey {
-                    token0: U256::zero(),
-                    token1: U256::one(),
-                    config: Config {
-                        fee: fee,
-                        tick_spacing: tick_spacing,
-                        extension: U256::one(),
-                    },
+                    token0: Address::from_raw(U256::zero()),
+                    token1: Address::from_raw(U256::one()),
+                    config: Config::from_raw_unchecked(fee, tick_spacing, U256::one()),
                 },
                 BasePoolState {
                     active_tick_index: Some(0),
@@ -494,9 +547,10 @@ mod tests {
                 meta: 2,
                 override_state: None,
                 sqrt_ratio_limit,
+                max_ticks_crossed: None,
                 token_amount: TokenAmount {
                     amount: 125000000000000000,
-                    token: U256::zero(),
+                    token: Address::from_raw(U256::zero()),
                 },
             })
             .unwrap();
@@ -511,9 +565,10 @@ mod tests {
                 meta: 2,
                 override_state: Some(result0.state_after),
                 sqrt_ratio_limit,
+                max_ticks_crossed: None,
                 token_amount: TokenAmount {
                     amount: 50000000000000000,
-                    token: U256::zero(),
+                    token: Address::from_raw(U256::zero()),
                 },
             })
             .unwrap();
@@ -528,9 +583,10 @@ mod tests {
                 meta: 2,
                 override_state: Some(result1.state_after),
                 sqrt_ratio_limit,
+                max_ticks_crossed: None,
                 token_amount: TokenAmount {
                     amount: 12500000000000000,
-                    token: U256::zero(),
+                    token: Address::from_raw(U256::zero()),
                 },
             })
             .unwrap();
@@ -545,9 +601,10 @@ mod tests {
                 meta: 2,
                 override_state: Some(result2.state_after),
                 sqrt_ratio_limit,
+                max_ticks_crossed: None,
                 token_amount: TokenAmount {
                     amount: 12500000000000000,
-                    token: U256::zero(),
+                    token: Address::from_raw(U256::zero()),
                 },
             })
             .unwrap();
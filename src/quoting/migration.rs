@@ -0,0 +1,275 @@
+use crate::math::delta::AmountDeltaError;
+use crate::math::liquidity::{max_liquidity, position_amounts, LiquidityError};
+use crate::math::tick::{
+    to_sqrt_ratio, FULL_RANGE_TICK_SPACING, MAX_TICK, MAX_TICK_SPACING, MIN_TICK,
+};
+use crate::math::uint::U256;
+use alloc::vec::Vec;
+
+// A position that is withdrawn from the source pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MigratedPosition {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+}
+
+// How a single position is redeployed in the target pool. The dust is the part of the withdrawn amounts that cannot be
+// redeployed, either because of rounding or because the target pool price uses the tokens in a different ratio.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MigrationPlan {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    pub withdrawn0: u128,
+    pub withdrawn1: u128,
+    pub deposited0: u128,
+    pub deposited1: u128,
+    pub dust0: u128,
+    pub dust1: u128,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MigrationSummary {
+    pub plans: Vec<MigrationPlan>,
+    pub total_dust0: u128,
+    pub total_dust1: u128,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MigrationError {
+    /// The target tick spacing is greater than the max tick spacing.
+    InvalidTickSpacing,
+    /// The position bounds are not ordered or are outside of the range [MIN_TICK, MAX_TICK].
+    InvalidBounds { tick_lower: i32, tick_upper: i32 },
+    /// Computing the withdrawn or deposited amounts failed.
+    AmountDelta(AmountDeltaError),
+    /// Computing the liquidity to redeploy failed.
+    Liquidity(LiquidityError),
+    /// The total dust does not fit in a u128.
+    DustOverflow,
+}
+
+// Re-aligns the bounds to the target tick spacing, widening the range so the migrated position covers at least the
+// same prices. A tick spacing of zero means the target is a full range pool.
+pub fn align_bounds(
+    tick_lower: i32,
+    tick_upper: i32,
+    tick_spacing: u32,
+) -> Result<(i32, i32), MigrationError> {
+    if tick_spacing > MAX_TICK_SPACING {
+        return Err(MigrationError::InvalidTickSpacing);
+    }
+    if tick_lower >= tick_upper || tick_lower < MIN_TICK || tick_upper > MAX_TICK {
+        return Err(MigrationError::InvalidBounds {
+            tick_lower,
+            tick_upper,
+        });
+    }
+
+    if tick_spacing == FULL_RANGE_TICK_SPACING {
+        return Ok((MIN_TICK, MAX_TICK));
+    }
+
+    let spacing = tick_spacing as i32;
+    let min_usable_tick = (MIN_TICK / spacing) * spacing;
+    let max_usable_tick = (MAX_TICK / spacing) * spacing;
+
+    let lower = tick_lower.div_euclid(spacing) * spacing;
+    let upper = -(-tick_upper).div_euclid(spacing) * spacing;
+
+    Ok((lower.max(min_usable_tick), upper.min(max_usable_tick)))
+}
+
+// Plans the migration of a single position from the source pool at source_sqrt_ratio to a pool with the target tick
+// spacing at target_sqrt_ratio
+pub fn plan_migration(
+    position: MigratedPosition,
+    source_sqrt_ratio: U256,
+    target_tick_spacing: u32,
+    target_sqrt_ratio: U256,
+) -> Result<MigrationPlan, MigrationError> {
+    let (tick_lower, tick_upper) = align_bounds(
+        position.tick_lower,
+        position.tick_upper,
+        target_tick_spacing,
+    )?;
+
+    let bounds_to_sqrt_ratios = |lower: i32, upper: i32| {
+        // the bounds have already been checked to be in range
+        (to_sqrt_ratio(lower).unwrap(), to_sqrt_ratio(upper).unwrap())
+    };
+
+    let (source_lower, source_upper) =
+        bounds_to_sqrt_ratios(position.tick_lower, position.tick_upper);
+    let (withdrawn0, withdrawn1) = position_amounts(
+        source_sqrt_ratio,
+        source_lower,
+        source_upper,
+        position.liquidity,
+        false,
+    )
+    .map_err(MigrationError::AmountDelta)?;
+
+    let (target_lower, target_upper) = bounds_to_sqrt_ratios(tick_lower, tick_upper);
+    let liquidity = max_liquidity(
+        target_sqrt_ratio,
+        target_lower,
+        target_upper,
+        withdrawn0,
+        withdrawn1,
+    )
+    .map_err(MigrationError::Liquidity)?;
+    let (deposited0, deposited1) = position_amounts(
+        target_sqrt_ratio,
+        target_lower,
+        target_upper,
+        liquidity,
+        true,
+    )
+    .map_err(MigrationError::AmountDelta)?;
+
+    Ok(MigrationPlan {
+        tick_lower,
+        tick_upper,
+        liquidity,
+        withdrawn0,
+        withdrawn1,
+        deposited0,
+        deposited1,
+        dust0: withdrawn0 - deposited0,
+        dust1: withdrawn1 - deposited1,
+    })
+}
+
+// Plans the migration of each of the positions, returning the plans in the same order as the positions along with the
+// total dust left over
+pub fn plan_migrations(
+    positions: &[MigratedPosition],
+    source_sqrt_ratio: U256,
+    target_tick_spacing: u32,
+    target_sqrt_ratio: U256,
+) -> Result<MigrationSummary, MigrationError> {
+    let mut summary = MigrationSummary {
+        plans: Vec::with_capacity(positions.len()),
+        total_dust0: 0,
+        total_dust1: 0,
+    };
+
+    for position in positions {
+        let plan = plan_migration(
+            *position,
+            source_sqrt_ratio,
+            target_tick_spacing,
+            target_sqrt_ratio,
+        )?;
+        summary.total_dust0 = summary
+            .total_dust0
+            .checked_add(plan.dust0)
+            .ok_or(MigrationError::DustOverflow)?;
+        summary.total_dust1 = summary
+            .total_dust1
+            .checked_add(plan.dust1)
+            .ok_or(MigrationError::DustOverflow)?;
+        summary.plans.push(plan);
+    }
+
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: U256 = U256([0, 0, 1, 0]);
+
+    fn position(tick_lower: i32, tick_upper: i32) -> MigratedPosition {
+        MigratedPosition {
+            tick_lower,
+            tick_upper,
+            liquidity: 1_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_align_bounds() {
+        assert_eq!(align_bounds(-15, 25, 10), Ok((-20, 30)));
+        assert_eq!(align_bounds(-20, 30, 10), Ok((-20, 30)));
+        assert_eq!(align_bounds(1, 2, 100), Ok((0, 100)));
+        assert_eq!(align_bounds(-1000, 1000, 0), Ok((MIN_TICK, MAX_TICK)));
+        assert_eq!(
+            align_bounds(MIN_TICK, MAX_TICK, 1000),
+            Ok((-88722000, 88722000))
+        );
+    }
+
+    #[test]
+    fn test_align_bounds_errors() {
+        assert_eq!(
+            align_bounds(0, 10, MAX_TICK_SPACING + 1),
+            Err(MigrationError::InvalidTickSpacing)
+        );
+        assert_eq!(
+            align_bounds(10, 10, 1),
+            Err(MigrationError::InvalidBounds {
+                tick_lower: 10,
+                tick_upper: 10
+            })
+        );
+        assert_eq!(
+            align_bounds(MIN_TICK - 1, 0, 1),
+            Err(MigrationError::InvalidBounds {
+                tick_lower: MIN_TICK - 1,
+                tick_upper: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_same_bounds_and_price_has_only_rounding_dust() {
+        let plan = plan_migration(position(-1000, 1000), ONE, 100, ONE).unwrap();
+
+        assert_eq!((plan.tick_lower, plan.tick_upper), (-1000, 1000));
+        // the withdrawn amounts are rounded down, so a little liquidity is lost
+        assert!(plan.liquidity <= 1_000_000_000 && plan.liquidity > 999_990_000);
+        assert!(plan.dust0 <= 2 && plan.dust1 <= 2);
+        assert_eq!(plan.withdrawn0, plan.deposited0 + plan.dust0);
+        assert_eq!(plan.withdrawn1, plan.deposited1 + plan.dust1);
+    }
+
+    #[test]
+    fn test_widened_bounds_reduce_liquidity() {
+        let plan = plan_migration(position(-1005, 995), ONE, 100, ONE).unwrap();
+
+        assert_eq!((plan.tick_lower, plan.tick_upper), (-1100, 1000));
+        assert!(plan.liquidity < 1_000_000_000);
+        assert!(plan.deposited0 <= plan.withdrawn0 && plan.deposited1 <= plan.withdrawn1);
+    }
+
+    #[test]
+    fn test_price_difference_leaves_one_token_as_dust() {
+        // the position is entirely token0 above its range in the source pool, but in range in the target pool
+        let plan =
+            plan_migration(position(1000, 2000), ONE, 10, to_sqrt_ratio(1500).unwrap()).unwrap();
+
+        assert_eq!(plan.withdrawn1, 0);
+        assert_eq!(plan.liquidity, 0);
+        assert_eq!(plan.dust0, plan.withdrawn0);
+    }
+
+    #[test]
+    fn test_plan_migrations_totals_dust() {
+        let positions = [position(-1005, 995), position(1000, 2000)];
+        let summary = plan_migrations(&positions, ONE, 100, to_sqrt_ratio(1500).unwrap()).unwrap();
+
+        assert_eq!(summary.plans.len(), 2);
+        assert_eq!(
+            summary.total_dust0,
+            summary.plans.iter().map(|plan| plan.dust0).sum::<u128>()
+        );
+        assert_eq!(
+            summary.total_dust1,
+            summary.plans.iter().map(|plan| plan.dust1).sum::<u128>()
+        );
+    }
+}
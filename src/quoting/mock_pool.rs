@@ -0,0 +1,330 @@
+use crate::math::swap::is_price_increasing;
+use crate::math::tick::{MAX_TICK, MIN_TICK};
+use crate::math::uint::U256;
+use crate::quoting::types::{
+    Address, NodeKey, Pool, Quote, QuoteParams, SqrtRatioState, TokenAmount,
+};
+use alloc::vec::Vec;
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+
+// Resources reported by the scripted quotes of a mock pool, e.g. to test gas accounting
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct MockPoolResources {
+    pub units: u32,
+}
+
+crate::impl_flatten_resources!(MockPoolResources { units });
+
+impl AddAssign for MockPoolResources {
+    fn add_assign(&mut self, rhs: Self) {
+        self.units += rhs.units;
+    }
+}
+
+impl Add for MockPoolResources {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self::Output {
+        self += rhs;
+        self
+    }
+}
+
+impl SubAssign for MockPoolResources {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.units -= rhs.units;
+    }
+}
+
+impl Sub for MockPoolResources {
+    type Output = Self;
+
+    fn sub(mut self, rhs: Self) -> Self::Output {
+        self -= rhs;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MockPoolState {
+    pub sqrt_ratio: U256,
+    pub liquidity: u128,
+}
+
+impl SqrtRatioState for MockPoolState {
+    fn sqrt_ratio(&self) -> U256 {
+        self.sqrt_ratio
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MockPoolQuoteError {
+    InvalidToken,
+    /// No response of the pool matches the quoted token amount.
+    NoResponse(TokenAmount),
+    /// A failure scripted by a response, with a code chosen by the test.
+    Scripted(u32),
+}
+
+// What the pool returns for the quotes a response matches
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MockOutcome {
+    // Consumes the whole amount and calculates it multiplied by the rate, rounded down, without changing the state
+    Rate {
+        numerator: u128,
+        denominator: u128,
+        resources: MockPoolResources,
+    },
+    // Returns the quote as is
+    Quote(Quote<MockPoolResources, MockPoolState>),
+    Error(u32),
+}
+
+// A scripted response to the quotes of a token, and optionally of a specific amount
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MockResponse {
+    pub token: Address,
+    pub amount: Option<i128>,
+    pub outcome: MockOutcome,
+}
+
+// A pool that answers quotes with scripted responses, for testing code that is generic over pools
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MockPool {
+    key: NodeKey,
+    state: MockPoolState,
+    // Matched against each quote in order, so responses for specific amounts must come before the catch-all ones
+    responses: Vec<MockResponse>,
+    path_dependent: bool,
+}
+
+impl MockPool {
+    pub fn new(key: NodeKey, state: MockPoolState) -> Self {
+        Self {
+            key,
+            state,
+            responses: Vec::new(),
+            path_dependent: false,
+        }
+    }
+
+    pub fn with_response(
+        mut self,
+        token: Address,
+        amount: Option<i128>,
+        outcome: MockOutcome,
+    ) -> Self {
+        self.responses.push(MockResponse {
+            token,
+            amount,
+            outcome,
+        });
+        self
+    }
+
+    // Responds to the quotes of the token with the rate, consuming no resources
+    pub fn with_rate(self, token: Address, numerator: u128, denominator: u128) -> Self {
+        self.with_response(
+            token,
+            None,
+            MockOutcome::Rate {
+                numerator,
+                denominator,
+                resources: MockPoolResources::default(),
+            },
+        )
+    }
+
+    pub fn with_path_dependence(mut self, path_dependent: bool) -> Self {
+        self.path_dependent = path_dependent;
+        self
+    }
+
+    pub fn responses(&self) -> &[MockResponse] {
+        &self.responses
+    }
+}
+
+impl Pool for MockPool {
+    type Resources = MockPoolResources;
+    type State = MockPoolState;
+    type QuoteError = MockPoolQuoteError;
+    type Meta = ();
+
+    fn get_key(&self) -> &NodeKey {
+        &self.key
+    }
+
+    fn get_state(&self) -> Self::State {
+        self.state
+    }
+
+    fn quote(
+        &self,
+        params: QuoteParams<Self::State, Self::Meta>,
+    ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
+        let TokenAmount { token, amount } = params.token_amount;
+        let is_token1 = token == self.key.token1;
+        if !is_token1 && token != self.key.token0 {
+            return Err(MockPoolQuoteError::InvalidToken);
+        }
+
+        let response = self
+            .responses
+            .iter()
+            .find(|response| {
+                response.token == token && response.amount.is_none_or(|expected| expected == amount)
+            })
+            .ok_or(MockPoolQuoteError::NoResponse(params.token_amount))?;
+
+        match response.outcome {
+            MockOutcome::Rate {
+                numerator,
+                denominator,
+                resources,
+            } => {
+                let calculated_amount = U256::from(amount.unsigned_abs()) * U256::from(numerator)
+                    / U256::from(denominator);
+                Ok(Quote {
+                    is_price_increasing: is_price_increasing(amount, is_token1),
+                    consumed_amount: amount,
+                    calculated_amount: if calculated_amount > U256::from(u128::MAX) {
+                        u128::MAX
+                    } else {
+                        calculated_amount.as_u128()
+                    },
+                    execution_resources: resources,
+                    state_after: params.override_state.unwrap_or(self.state),
+                    fees_paid: 0,
+                })
+            }
+            MockOutcome::Quote(quote) => Ok(quote),
+            MockOutcome::Error(code) => Err(MockPoolQuoteError::Scripted(code)),
+        }
+    }
+
+    fn has_liquidity(&self) -> bool {
+        self.state.liquidity > 0
+    }
+
+    fn max_tick_with_liquidity(&self) -> Option<i32> {
+        self.has_liquidity().then_some(MAX_TICK)
+    }
+
+    fn min_tick_with_liquidity(&self) -> Option<i32> {
+        self.has_liquidity().then_some(MIN_TICK)
+    }
+
+    fn is_path_dependent(&self) -> bool {
+        self.path_dependent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::pool_map::PoolMap;
+    use crate::quoting::route::{quote_route, HopDirection, Route, RouteHop, RouteSplit};
+    use crate::quoting::types::Config;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn key() -> NodeKey {
+        NodeKey {
+            token0: TOKEN0,
+            token1: TOKEN1,
+            config: Config::from_raw_unchecked(0, 0, U256::zero()),
+        }
+    }
+
+    fn pool() -> MockPool {
+        MockPool::new(
+            key(),
+            MockPoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 1,
+            },
+        )
+    }
+
+    fn params(token: Address, amount: i128) -> QuoteParams<MockPoolState, ()> {
+        QuoteParams {
+            token_amount: TokenAmount { token, amount },
+            sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
+            override_state: None,
+            meta: (),
+        }
+    }
+
+    #[test]
+    fn test_scripted_responses() {
+        let scripted = Quote {
+            is_price_increasing: true,
+            consumed_amount: 50,
+            calculated_amount: 7,
+            execution_resources: MockPoolResources { units: 3 },
+            state_after: pool().get_state(),
+            fees_paid: 1,
+        };
+        let pool = pool()
+            .with_response(TOKEN1, Some(100), MockOutcome::Quote(scripted))
+            .with_response(TOKEN1, Some(200), MockOutcome::Error(42))
+            .with_rate(TOKEN1, 1, 2);
+
+        assert_eq!(pool.quote(params(TOKEN1, 100)).unwrap(), scripted);
+        assert_eq!(
+            pool.quote(params(TOKEN1, 200)),
+            Err(MockPoolQuoteError::Scripted(42))
+        );
+
+        let quote = pool.quote(params(TOKEN1, 301)).unwrap();
+        assert_eq!(quote.consumed_amount, 301);
+        assert_eq!(quote.calculated_amount, 150);
+        assert!(quote.is_price_increasing);
+
+        assert_eq!(
+            pool.quote(params(TOKEN0, 1)),
+            Err(MockPoolQuoteError::NoResponse(TokenAmount {
+                token: TOKEN0,
+                amount: 1
+            }))
+        );
+        assert_eq!(
+            pool.quote(params(Address::from_raw(U256::from(3)), 1)),
+            Err(MockPoolQuoteError::InvalidToken)
+        );
+    }
+
+    #[test]
+    fn test_routes_through_mock_pools() {
+        let pools: PoolMap<MockPool> = [pool().with_response(
+            TOKEN0,
+            None,
+            MockOutcome::Rate {
+                numerator: 3,
+                denominator: 1,
+                resources: MockPoolResources { units: 5 },
+            },
+        )]
+        .into_iter()
+        .collect();
+        let route = Route {
+            token_in: TOKEN0,
+            token_out: TOKEN1,
+            splits: alloc::vec![RouteSplit {
+                share: crate::quoting::route::ROUTE_SHARE_DENOMINATOR,
+                hops: alloc::vec![RouteHop {
+                    pool_key: key(),
+                    direction: HopDirection::ZeroForOne,
+                }],
+            }],
+        };
+
+        assert_eq!(
+            quote_route(&pools, &route, 1_000, ()).unwrap().amount_out,
+            3_000
+        );
+    }
+}
@@ -1,8 +1,69 @@
+pub mod approvals;
+#[cfg(feature = "approx")]
+pub mod approx;
+pub mod arbitrage;
 pub mod base_pool;
+pub mod batch;
+pub mod best_quote;
+pub mod bps;
+pub mod bundle;
+pub mod cancellation;
+pub mod clearing_price;
+pub mod clock;
+pub mod composite_depth;
 pub mod constants;
+pub mod event_updater;
+pub mod events;
+pub mod execution_record;
+pub mod explain;
+#[cfg(any(test, feature = "mock"))]
+pub mod faulty_pool;
+pub mod fee_apr;
+pub mod fee_settlement;
+pub mod fixed_base_pool;
+pub mod freshness;
 pub mod full_range_pool;
+pub mod impermanent_loss;
+pub mod keeper;
+pub mod l1_data_fee;
+pub mod liquidity_quote;
+pub mod mev_impact;
 pub mod mev_resist_pool;
+pub mod migration;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock_pool;
+pub mod multichain;
+pub mod oracle_capacity;
 pub mod oracle_pool;
+pub mod order_book;
+pub mod order_flow;
+pub mod pool_diff;
+pub mod pool_map;
+pub mod pool_reads;
+pub mod portfolio;
+pub mod pricing;
+pub mod profile;
+pub mod quoter_abi;
+pub mod range_order;
+pub mod recovery;
+pub mod resources;
+pub mod round_trip;
+pub mod route;
+pub mod route_ranking;
+pub mod settlement;
+pub mod signed_amount;
+pub mod solver;
+pub mod state_store;
+pub mod tick_bitmap;
+pub mod tick_codec;
+pub mod tick_fetch;
+pub mod tick_provider;
+pub mod tick_reconciliation;
+pub mod tick_target;
+pub mod tick_view;
+pub mod twamm_planner;
 pub mod twamm_pool;
+pub mod twamm_rewards;
+pub mod twamm_simulator;
 pub mod types;
 pub mod util;
@@ -0,0 +1,257 @@
+use crate::math::uint::{u256_to_float_base_x128, U256};
+use crate::quoting::route::{Route, RouteQuote};
+use crate::quoting::types::Address;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+// Amounts of tokens with different decimals on different chains are normalized to this many decimals
+pub const NORMALIZED_DECIMALS: u8 = 18;
+
+// The identity of a token across chains, e.g. USDC on every chain it is deployed to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CanonicalToken {
+    // Chosen by the registry, e.g. the address of the token on its home chain
+    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
+    pub id: U256,
+}
+
+// The canonical identity and the decimals of a token deployment on one chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TokenDeployment {
+    pub canonical: CanonicalToken,
+    pub decimals: u8,
+}
+
+// Maps the tokens of each chain to their canonical identity, e.g. backed by a token list
+pub trait TokenRegistry {
+    fn deployment(&self, chain_id: u64, token: Address) -> Option<TokenDeployment>;
+}
+
+// A registry of the deployments that were inserted into it
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StaticTokenRegistry {
+    deployments: BTreeMap<(u64, Address), TokenDeployment>,
+}
+
+impl StaticTokenRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, chain_id: u64, token: Address, deployment: TokenDeployment) {
+        self.deployments.insert((chain_id, token), deployment);
+    }
+}
+
+impl TokenRegistry for StaticTokenRegistry {
+    fn deployment(&self, chain_id: u64, token: Address) -> Option<TokenDeployment> {
+        self.deployments.get(&(chain_id, token)).copied()
+    }
+}
+
+// A quote of a swap on a specific chain, in the tokens of that chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChainQuote {
+    pub chain_id: u64,
+    pub token_in: Address,
+    pub amount_in: u128,
+    pub token_out: Address,
+    pub amount_out: u128,
+}
+
+impl ChainQuote {
+    // Tags the quote of a route with the chain of the deployment it was quoted against
+    pub fn from_route_quote<S>(chain_id: u64, route: &Route, quote: &RouteQuote<S>) -> Self {
+        Self {
+            chain_id,
+            token_in: route.token_in,
+            amount_in: quote.splits.iter().map(|split| split.amount_in()).sum(),
+            token_out: route.token_out,
+            amount_out: quote.amount_out,
+        }
+    }
+}
+
+// A quote in canonical tokens with amounts in NORMALIZED_DECIMALS, comparable with the quotes of other chains
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NormalizedQuote {
+    pub quote: ChainQuote,
+    pub token_in: CanonicalToken,
+    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
+    pub amount_in: U256,
+    pub token_out: CanonicalToken,
+    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
+    pub amount_out: U256,
+}
+
+impl NormalizedQuote {
+    // The output per unit of input, independent of the decimals of the tokens
+    pub fn price(&self) -> f64 {
+        // the X128 scaling cancels out
+        u256_to_float_base_x128(self.amount_out) / u256_to_float_base_x128(self.amount_in)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum NormalizationError {
+    /// The registry does not know the token on the chain.
+    UnknownToken { chain_id: u64, token: Address },
+    /// The quotes trade different canonical tokens, so they cannot be compared.
+    PairMismatch { chain_id: u64 },
+}
+
+fn normalize_amount(amount: u128, decimals: u8) -> U256 {
+    let amount = U256::from(amount);
+    if decimals <= NORMALIZED_DECIMALS {
+        amount * U256::exp10((NORMALIZED_DECIMALS - decimals) as usize)
+    } else {
+        amount / U256::exp10((decimals - NORMALIZED_DECIMALS) as usize)
+    }
+}
+
+// Converts the quote to canonical tokens and normalized amounts
+pub fn normalize_quote(
+    quote: ChainQuote,
+    registry: &impl TokenRegistry,
+) -> Result<NormalizedQuote, NormalizationError> {
+    let deployment = |token| {
+        registry
+            .deployment(quote.chain_id, token)
+            .ok_or(NormalizationError::UnknownToken {
+                chain_id: quote.chain_id,
+                token,
+            })
+    };
+    let (token_in, token_out) = (deployment(quote.token_in)?, deployment(quote.token_out)?);
+
+    Ok(NormalizedQuote {
+        quote,
+        token_in: token_in.canonical,
+        amount_in: normalize_amount(quote.amount_in, token_in.decimals),
+        token_out: token_out.canonical,
+        amount_out: normalize_amount(quote.amount_out, token_out.decimals),
+    })
+}
+
+/// Normalizes the quotes of several chains and sorts them from the best to the worst price.
+///
+/// All quotes must trade the same canonical tokens, e.g. USDC for WETH on every chain, even if the tokens have
+/// different addresses or decimals on each chain.
+pub fn compare_chain_quotes(
+    quotes: &[ChainQuote],
+    registry: &impl TokenRegistry,
+) -> Result<Vec<NormalizedQuote>, NormalizationError> {
+    let mut normalized = quotes
+        .iter()
+        .map(|&quote| normalize_quote(quote, registry))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some(first) = normalized.first() {
+        let pair = (first.token_in, first.token_out);
+        if let Some(mismatch) = normalized
+            .iter()
+            .find(|quote| (quote.token_in, quote.token_out) != pair)
+        {
+            return Err(NormalizationError::PairMismatch {
+                chain_id: mismatch.quote.chain_id,
+            });
+        }
+    }
+
+    normalized.sort_by(|a, b| b.price().total_cmp(&a.price()));
+    Ok(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC: CanonicalToken = CanonicalToken {
+        id: U256([1, 0, 0, 0]),
+    };
+    const WETH: CanonicalToken = CanonicalToken {
+        id: U256([2, 0, 0, 0]),
+    };
+
+    fn registry() -> StaticTokenRegistry {
+        let mut registry = StaticTokenRegistry::new();
+        // USDC has 6 decimals on mainnet but 18 on the other chain
+        for (chain_id, token, canonical, decimals) in [
+            (1, 10, USDC, 6),
+            (1, 11, WETH, 18),
+            (56, 20, USDC, 18),
+            (56, 21, WETH, 18),
+        ] {
+            registry.insert(
+                chain_id,
+                Address::from_raw(U256::from(token)),
+                TokenDeployment {
+                    canonical,
+                    decimals,
+                },
+            );
+        }
+        registry
+    }
+
+    fn quote(
+        chain_id: u64,
+        token_in: u64,
+        amount_in: u128,
+        token_out: u64,
+        amount_out: u128,
+    ) -> ChainQuote {
+        ChainQuote {
+            chain_id,
+            token_in: Address::from_raw(U256::from(token_in)),
+            amount_in,
+            token_out: Address::from_raw(U256::from(token_out)),
+            amount_out,
+        }
+    }
+
+    #[test]
+    fn test_compare_chain_quotes() {
+        let registry = registry();
+        // 2000 USDC for 1 WETH on mainnet, and slightly more WETH for the same amount on the other chain
+        let mainnet = quote(1, 10, 2_000_000_000, 11, 1_000_000_000_000_000_000);
+        let other = quote(
+            56,
+            20,
+            2_000_000_000_000_000_000_000,
+            21,
+            1_001_000_000_000_000_000,
+        );
+
+        let compared = compare_chain_quotes(&[mainnet, other], &registry).unwrap();
+        assert_eq!(compared[0].quote, other);
+        assert_eq!(compared[1].quote, mainnet);
+        assert_eq!(compared[0].amount_in, compared[1].amount_in);
+        assert_eq!(compared[1].price(), 0.0005);
+        assert_eq!((compared[0].token_in, compared[0].token_out), (USDC, WETH));
+    }
+
+    #[test]
+    fn test_normalization_errors() {
+        let registry = registry();
+        assert_eq!(
+            normalize_quote(quote(10, 10, 1, 11, 1), &registry),
+            Err(NormalizationError::UnknownToken {
+                chain_id: 10,
+                token: Address::from_raw(U256::from(10))
+            })
+        );
+        assert_eq!(
+            compare_chain_quotes(
+                &[quote(1, 10, 1, 11, 1), quote(56, 21, 1, 20, 1)],
+                &registry
+            ),
+            Err(NormalizationError::PairMismatch { chain_id: 56 })
+        );
+        assert_eq!(compare_chain_quotes(&[], &registry), Ok(Vec::new()));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::quoting::oracle_pool::OraclePoolResources;
+
+// Estimates the gas of the snapshots that the oracle extension writes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotGasModel {
+    // The gas of writing a snapshot to a slot that is already initialized
+    pub per_snapshot_written: u64,
+    // The gas of initializing a slot when the capacity of the snapshots is expanded
+    pub per_slot_expanded: u64,
+}
+
+impl Default for SnapshotGasModel {
+    // The costs of overwriting and initializing a cold storage slot
+    fn default() -> Self {
+        Self {
+            per_snapshot_written: 5_000,
+            per_slot_expanded: 22_100,
+        }
+    }
+}
+
+impl SnapshotGasModel {
+    // Returns the gas that the snapshots written by a quote add to the swap
+    pub fn snapshot_gas(&self, resources: &OraclePoolResources) -> u64 {
+        self.per_snapshot_written * resources.snapshots_written as u64
+    }
+}
+
+// The snapshot capacity that an oracle pool needs and what it costs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OracleCapacityPlan {
+    pub required_capacity: u64,
+    // The gas of expanding the current capacity to the required capacity, or zero if it is already enough
+    pub expansion_gas: u64,
+    // The gas of the snapshots written over one TWAP window at the expected frequency
+    pub snapshot_gas_per_window: u64,
+}
+
+// Returns the number of snapshots needed so the oldest snapshot is at least the window old when the pool is interacted
+// with at most once every interval. The extension writes at most one snapshot per second, so the interval is at least
+// one second.
+pub fn required_snapshot_capacity(min_seconds_between_interactions: u64, twap_window: u64) -> u64 {
+    // one more snapshot than the intervals in the window, so that there is a snapshot at or before its start
+    twap_window.div_ceil(min_seconds_between_interactions.max(1)) + 1
+}
+
+/// Plans the snapshot capacity of an oracle pool that is interacted with at most once every
+/// `min_seconds_between_interactions` seconds, so that a TWAP over `twap_window` seconds can always be computed.
+///
+/// The interval should be the shortest expected interval between quotes, since interactions that are more frequent
+/// than planned overwrite the snapshots of the window sooner.
+pub fn plan_oracle_capacity(
+    current_capacity: u64,
+    min_seconds_between_interactions: u64,
+    twap_window: u64,
+    gas_model: &SnapshotGasModel,
+) -> OracleCapacityPlan {
+    let required_capacity =
+        required_snapshot_capacity(min_seconds_between_interactions, twap_window);
+    let snapshots_per_window = required_capacity - 1;
+
+    OracleCapacityPlan {
+        required_capacity,
+        expansion_gas: gas_model
+            .per_slot_expanded
+            .saturating_mul(required_capacity.saturating_sub(current_capacity)),
+        snapshot_gas_per_window: gas_model
+            .per_snapshot_written
+            .saturating_mul(snapshots_per_window),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_snapshot_capacity() {
+        assert_eq!(required_snapshot_capacity(12, 3600), 301);
+        assert_eq!(required_snapshot_capacity(7, 20), 4);
+        // at most one snapshot is written per second
+        assert_eq!(required_snapshot_capacity(0, 60), 61);
+        assert_eq!(required_snapshot_capacity(60, 0), 1);
+    }
+
+    #[test]
+    fn test_plan_oracle_capacity() {
+        let model = SnapshotGasModel::default();
+        assert_eq!(
+            plan_oracle_capacity(100, 12, 3600, &model),
+            OracleCapacityPlan {
+                required_capacity: 301,
+                expansion_gas: 201 * 22_100,
+                snapshot_gas_per_window: 300 * 5_000,
+            }
+        );
+        assert_eq!(
+            plan_oracle_capacity(1000, 12, 3600, &model).expansion_gas,
+            0
+        );
+        assert_eq!(
+            model.snapshot_gas(&OraclePoolResources {
+                snapshots_written: 1,
+                ..Default::default()
+            }),
+            5_000
+        );
+    }
+}
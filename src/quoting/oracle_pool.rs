@@ -1,10 +1,16 @@
-use super::types::Config;
+use super::types::{Config, TickSpacing};
 use crate::math::uint::U256;
 use crate::quoting::constants::NATIVE_TOKEN_ADDRESS;
 use crate::quoting::full_range_pool::{
     FullRangePool, FullRangePoolQuoteError, FullRangePoolResources, FullRangePoolState,
 };
-use crate::quoting::types::{BlockTimestamp, NodeKey, Pool, Quote, QuoteParams};
+use crate::quoting::pool_diff::{FieldDiff, PoolDiff};
+use crate::quoting::pool_reads::{Inventory, PoolReadError, PoolReads};
+use crate::quoting::resources::MaxResourcesEstimate;
+use crate::quoting::types::{
+    Address, BlockTimestamp, Fee, NodeKey, Pool, Quote, QuoteParams, SqrtRatioState,
+};
+use alloc::vec::Vec;
 use core::ops::{Add, AddAssign, Sub, SubAssign};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -14,12 +20,39 @@ pub struct OraclePoolState {
     pub last_snapshot_time: u64,
 }
 
+impl OraclePoolState {
+    // Returns the snapshots written by each of a sequence of interactions with the pool at the block timestamps, where
+    // only the first interaction in a block writes a snapshot. The sum is the number of snapshots of the whole batch.
+    pub fn snapshots_written(&self, timestamps: &[BlockTimestamp]) -> Vec<u32> {
+        let mut last_snapshot_time = self.last_snapshot_time;
+        timestamps
+            .iter()
+            .map(|&time| {
+                if time == last_snapshot_time {
+                    0
+                } else {
+                    last_snapshot_time = time;
+                    1
+                }
+            })
+            .collect()
+    }
+}
+
+impl SqrtRatioState for OraclePoolState {
+    fn sqrt_ratio(&self) -> U256 {
+        self.full_range_pool_state.sqrt_ratio
+    }
+}
+
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
 pub struct OraclePoolResources {
     pub full_range_pool_resources: FullRangePoolResources,
     pub snapshots_written: u32,
 }
 
+crate::impl_flatten_resources!(OraclePoolResources { snapshots_written } nested { full_range_pool_resources });
+
 impl AddAssign for OraclePoolResources {
     fn add_assign(&mut self, rhs: Self) {
         self.full_range_pool_resources += rhs.full_range_pool_resources;
@@ -64,28 +97,92 @@ pub struct OraclePool {
 pub enum OraclePoolError {
     /// Errors from the underlying FullRangePool constructor.
     FullRangePoolError(FullRangePoolError),
+    /// The oracle extension only supports full range pools.
+    NotFullRange,
+    /// The config has no extension, so it cannot be the config of an oracle pool.
+    MissingExtension,
 }
 
 use crate::quoting::full_range_pool::FullRangePoolError;
 
 impl OraclePool {
+    // Creates an oracle pool of the token and the native token
     pub fn new(
-        token1: U256,
+        token1: Address,
         extension: U256,
         sqrt_ratio: U256,
         active_liquidity: u128,
         last_snapshot_time: u64,
     ) -> Result<Self, OraclePoolError> {
-        let full_range_pool = FullRangePool::new(
+        Self::new_with_token0(
+            NATIVE_TOKEN_ADDRESS,
+            token1,
+            extension,
+            sqrt_ratio,
+            active_liquidity,
+            last_snapshot_time,
+        )
+    }
+
+    // Creates an oracle pool paired with the given token0 instead of the native token, e.g. the wrapped native token
+    // on chains where the oracle pools use it. Token0 must be less than token1 like in any other pool.
+    pub fn new_with_token0(
+        token0: Address,
+        token1: Address,
+        extension: U256,
+        sqrt_ratio: U256,
+        active_liquidity: u128,
+        last_snapshot_time: u64,
+    ) -> Result<Self, OraclePoolError> {
+        Self::from_key(
             NodeKey {
-                token0: NATIVE_TOKEN_ADDRESS,
+                token0,
                 token1,
-                config: Config {
-                    fee: 0,
-                    tick_spacing: 0,
-                    extension,
-                },
+                config: Config::new(Fee::ZERO, TickSpacing::FULL_RANGE, extension),
+            },
+            sqrt_ratio,
+            active_liquidity,
+            last_snapshot_time,
+        )
+    }
+
+    // Creates an oracle pool with the full config of the pool key, e.g. for deployments that pair the oracle extension
+    // with pools that charge a fee. The extension only constrains the pool to be full range, so any fee is accepted.
+    pub fn new_with_config(
+        token0: Address,
+        token1: Address,
+        config: Config,
+        sqrt_ratio: U256,
+        active_liquidity: u128,
+        last_snapshot_time: u64,
+    ) -> Result<Self, OraclePoolError> {
+        if config.tick_spacing != TickSpacing::FULL_RANGE {
+            return Err(OraclePoolError::NotFullRange);
+        }
+        if config.extension.is_zero() {
+            return Err(OraclePoolError::MissingExtension);
+        }
+
+        Self::from_key(
+            NodeKey {
+                token0,
+                token1,
+                config,
             },
+            sqrt_ratio,
+            active_liquidity,
+            last_snapshot_time,
+        )
+    }
+
+    fn from_key(
+        key: NodeKey,
+        sqrt_ratio: U256,
+        active_liquidity: u128,
+        last_snapshot_time: u64,
+    ) -> Result<Self, OraclePoolError> {
+        let full_range_pool = FullRangePool::new(
+            key,
             FullRangePoolState {
                 sqrt_ratio,
                 liquidity: active_liquidity,
@@ -98,6 +195,14 @@ impl OraclePool {
             last_snapshot_time,
         })
     }
+
+    // Reports the fields that differ between this pool and the other pool
+    pub fn diff(&self, other: &Self) -> PoolDiff {
+        PoolDiff {
+            last_update_time: FieldDiff::of(self.last_snapshot_time, other.last_snapshot_time),
+            ..self.full_range_pool.diff(&other.full_range_pool)
+        }
+    }
 }
 
 impl Pool for OraclePool {
@@ -120,15 +225,29 @@ impl Pool for OraclePool {
     fn quote(
         &self,
         params: QuoteParams<Self::State, Self::Meta>,
+    ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
+        self.quote_borrowed(QuoteParams {
+            token_amount: params.token_amount,
+            sqrt_ratio_limit: params.sqrt_ratio_limit,
+            max_ticks_crossed: params.max_ticks_crossed,
+            override_state: params.override_state.as_ref(),
+            meta: params.meta,
+        })
+    }
+
+    fn quote_borrowed(
+        &self,
+        params: QuoteParams<&Self::State, Self::Meta>,
     ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
         let block_time = params.meta;
         let pool_time = params
             .override_state
             .map_or(self.last_snapshot_time, |os| os.last_snapshot_time);
 
-        let result = self.full_range_pool.quote(QuoteParams {
+        let result = self.full_range_pool.quote_borrowed(QuoteParams {
             sqrt_ratio_limit: params.sqrt_ratio_limit,
-            override_state: params.override_state.map(|s| s.full_range_pool_state),
+            max_ticks_crossed: params.max_ticks_crossed,
+            override_state: params.override_state.map(|s| &s.full_range_pool_state),
             token_amount: params.token_amount,
             meta: (),
         })?;
@@ -166,28 +285,53 @@ impl Pool for OraclePool {
     }
 }
 
+// A quote writes at most one snapshot, even if it swaps nothing
+impl MaxResourcesEstimate for OraclePool {
+    fn max_resources_estimate(&self, amount_bound: u128) -> Self::Resources {
+        OraclePoolResources {
+            full_range_pool_resources: self.full_range_pool.max_resources_estimate(amount_bound),
+            snapshots_written: 1,
+        }
+    }
+}
+
+impl PoolReads for OraclePool {
+    fn inventory_at(&self, sqrt_ratio: U256) -> Result<Inventory, PoolReadError> {
+        self.full_range_pool.inventory_at(sqrt_ratio)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::math::tick::to_sqrt_ratio;
     use crate::math::uint::U256;
     use crate::quoting::constants::NATIVE_TOKEN_ADDRESS;
-    use crate::quoting::oracle_pool::OraclePool;
-    use crate::quoting::types::{Pool, QuoteParams, TokenAmount};
+    use crate::quoting::oracle_pool::{OraclePool, OraclePoolError};
+    use crate::quoting::types::{Address, BlockContext, Config, Pool, QuoteParams, TokenAmount};
+    use alloc::vec;
+    use alloc::vec::Vec;
 
     mod constructor_validation {
         use crate::math::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
         use crate::math::uint::U256;
-        use crate::quoting::oracle_pool::OraclePool;
-        use crate::quoting::types::Pool;
+        use crate::quoting::full_range_pool::FullRangePoolError;
+        use crate::quoting::oracle_pool::{OraclePool, OraclePoolError};
+        use crate::quoting::types::{Address, Pool};
 
         #[test]
         fn test_max_price_constructor() {
             assert_eq!(
-                OraclePool::new(U256::one(), U256::zero(), MAX_SQRT_RATIO, 1, 0)
-                    .expect("Pool creation should succeed")
-                    .get_state()
-                    .full_range_pool_state
-                    .liquidity,
+                OraclePool::new(
+                    Address::from_raw(U256::one()),
+                    U256::zero(),
+                    MAX_SQRT_RATIO,
+                    1,
+                    0
+                )
+                .expect("Pool creation should succeed")
+                .get_state()
+                .full_range_pool_state
+                .liquidity,
                 1
             );
         }
@@ -195,11 +339,17 @@ mod tests {
         #[test]
         fn test_min_price_constructor() {
             assert_eq!(
-                OraclePool::new(U256::one(), U256::zero(), MIN_SQRT_RATIO, 1, 0)
-                    .expect("Pool creation should succeed")
-                    .get_state()
-                    .full_range_pool_state
-                    .liquidity,
+                OraclePool::new(
+                    Address::from_raw(U256::one()),
+                    U256::zero(),
+                    MIN_SQRT_RATIO,
+                    1,
+                    0
+                )
+                .expect("Pool creation should succeed")
+                .get_state()
+                .full_range_pool_state
+                .liquidity,
                 1
             );
         }
@@ -207,11 +357,17 @@ mod tests {
         #[test]
         fn test_min_sqrt_ratio() {
             assert_eq!(
-                OraclePool::new(U256::one(), U256::zero(), MIN_SQRT_RATIO, 1, 0)
-                    .expect("Pool creation should succeed")
-                    .get_state()
-                    .full_range_pool_state
-                    .liquidity,
+                OraclePool::new(
+                    Address::from_raw(U256::one()),
+                    U256::zero(),
+                    MIN_SQRT_RATIO,
+                    1,
+                    0
+                )
+                .expect("Pool creation should succeed")
+                .get_state()
+                .full_range_pool_state
+                .liquidity,
                 1
             );
         }
@@ -219,19 +375,137 @@ mod tests {
         #[test]
         fn test_max_sqrt_ratio() {
             assert_eq!(
-                OraclePool::new(U256::one(), U256::zero(), MAX_SQRT_RATIO, 1, 0)
-                    .expect("Pool creation should succeed")
-                    .get_state()
-                    .full_range_pool_state
-                    .liquidity,
+                OraclePool::new(
+                    Address::from_raw(U256::one()),
+                    U256::zero(),
+                    MAX_SQRT_RATIO,
+                    1,
+                    0
+                )
+                .expect("Pool creation should succeed")
+                .get_state()
+                .full_range_pool_state
+                .liquidity,
                 1
             );
         }
+
+        #[test]
+        fn test_explicit_token0() {
+            let pool = OraclePool::new_with_token0(
+                Address::from_raw(U256::from(5)),
+                Address::from_raw(U256::from(7)),
+                U256::zero(),
+                MIN_SQRT_RATIO,
+                1,
+                0,
+            )
+            .expect("Pool creation should succeed");
+            assert_eq!(pool.get_key().token0, U256::from(5));
+
+            assert_eq!(
+                OraclePool::new_with_token0(
+                    Address::from_raw(U256::from(7)),
+                    Address::from_raw(U256::from(5)),
+                    U256::zero(),
+                    MIN_SQRT_RATIO,
+                    1,
+                    0
+                ),
+                Err(OraclePoolError::FullRangePoolError(
+                    FullRangePoolError::TokenOrderInvalid
+                ))
+            );
+        }
     }
 
-    const TOKEN: U256 = U256([1, 0, 0, 0]);
+    const TOKEN: Address = Address::from_raw(U256([1, 0, 0, 0]));
     const EXTENSION: U256 = U256([3, 0, 0, 0]);
 
+    #[test]
+    fn test_snapshots_written() {
+        let pool = OraclePool::new(
+            TOKEN,
+            EXTENSION,
+            to_sqrt_ratio(0).unwrap(),
+            1_000_000_000,
+            5,
+        )
+        .unwrap();
+        let state = pool.get_state();
+
+        assert_eq!(
+            state.snapshots_written(&[5, 5, 6, 6, 6, 8]),
+            vec![0, 0, 1, 0, 0, 1]
+        );
+        assert_eq!(state.snapshots_written(&[]), Vec::<u32>::new());
+
+        // matches the snapshots written by quoting the interactions one after another
+        let mut state = state;
+        for (time, expected) in [7, 7, 9]
+            .into_iter()
+            .zip(state.snapshots_written(&[7, 7, 9]))
+        {
+            let quote = pool
+                .quote(QuoteParams {
+                    token_amount: TokenAmount {
+                        amount: 1000,
+                        token: TOKEN,
+                    },
+                    sqrt_ratio_limit: None,
+                    max_ticks_crossed: None,
+                    override_state: Some(state),
+                    meta: time,
+                })
+                .unwrap();
+            assert_eq!(quote.execution_resources.snapshots_written, expected);
+            state = quote.state_after;
+        }
+    }
+
+    #[test]
+    fn test_new_with_config() {
+        let config =
+            |fee, tick_spacing, extension| Config::from_raw_unchecked(fee, tick_spacing, extension);
+        let new = |config| {
+            OraclePool::new_with_config(
+                NATIVE_TOKEN_ADDRESS,
+                TOKEN,
+                config,
+                to_sqrt_ratio(0).unwrap(),
+                1_000_000_000,
+                1,
+            )
+        };
+
+        let fee = 1 << 60;
+        let pool = new(config(fee, 0, EXTENSION)).unwrap();
+        assert_eq!(pool.get_key().config, config(fee, 0, EXTENSION));
+        let quote = pool
+            .quote(QuoteParams {
+                token_amount: TokenAmount {
+                    amount: 1000,
+                    token: TOKEN,
+                },
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: None,
+                meta: 2,
+            })
+            .unwrap();
+        // a sixteenth of the input is paid as fees
+        assert_eq!(quote.fees_paid, 63);
+
+        assert_eq!(
+            new(config(fee, 100, EXTENSION)),
+            Err(OraclePoolError::NotFullRange)
+        );
+        assert_eq!(
+            new(config(fee, 0, U256::zero())),
+            Err(OraclePoolError::MissingExtension)
+        );
+    }
+
     #[test]
     fn test_quote_token1_input_update() {
         let pool = OraclePool::new(
@@ -249,6 +523,7 @@ mod tests {
                 token: TOKEN,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
             meta: 2,
         };
@@ -261,6 +536,49 @@ mod tests {
         assert_eq!(quote.state_after.last_snapshot_time, 2);
     }
 
+    #[test]
+    fn test_quote_borrowed_override() {
+        let pool = OraclePool::new(
+            TOKEN,
+            EXTENSION,
+            to_sqrt_ratio(0).unwrap(),
+            1_000_000_000,
+            1,
+        )
+        .expect("Pool creation should succeed");
+        let params = QuoteParams {
+            token_amount: TokenAmount {
+                amount: 1000,
+                token: TOKEN,
+            },
+            sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
+            override_state: None,
+            meta: 2,
+        };
+        let state_after = pool.quote(params).unwrap().state_after;
+
+        let by_value = pool
+            .quote(QuoteParams {
+                override_state: Some(state_after),
+                ..params
+            })
+            .unwrap();
+        let borrowed = pool
+            .quote_borrowed(QuoteParams {
+                token_amount: params.token_amount,
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: Some(&state_after),
+                meta: 2,
+            })
+            .unwrap();
+
+        assert_eq!(borrowed, by_value);
+        // the snapshot was already written by the first swap
+        assert_eq!(borrowed.execution_resources.snapshots_written, 0);
+    }
+
     #[test]
     fn test_quote_token0_input() {
         let pool = OraclePool::new(
@@ -278,6 +596,7 @@ mod tests {
                 token: NATIVE_TOKEN_ADDRESS,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
             meta: 2,
         };
@@ -289,4 +608,36 @@ mod tests {
         assert_eq!(quote.execution_resources.snapshots_written, 1);
         assert_eq!(quote.state_after.last_snapshot_time, 2);
     }
+
+    #[test]
+    fn test_quote_with_block_context() {
+        let pool = OraclePool::new(
+            TOKEN,
+            EXTENSION,
+            to_sqrt_ratio(0).unwrap(),
+            1_000_000_000,
+            1,
+        )
+        .expect("Pool creation should succeed");
+
+        let quote = pool
+            .quote_with_block_context(QuoteParams {
+                token_amount: TokenAmount {
+                    amount: 1000,
+                    token: TOKEN,
+                },
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: None,
+                meta: BlockContext {
+                    number: 100,
+                    timestamp: 2,
+                },
+            })
+            .expect("Failed to get quote");
+
+        assert_eq!(quote.calculated_amount, 999);
+        assert_eq!(quote.execution_resources.snapshots_written, 1);
+        assert_eq!(quote.state_after.last_snapshot_time, 2);
+    }
 }
@@ -0,0 +1,328 @@
+use crate::math::delta::{amount0_delta, AmountDeltaError};
+use crate::math::tick::{
+    sqrt_ratio_to_tick, to_sqrt_ratio, MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO, MIN_TICK,
+};
+use crate::quoting::base_pool::BasePoolView;
+use crate::quoting::types::Pool;
+use alloc::vec::Vec;
+
+// The liquidity of a pool between two ticks, expressed as the amount of token0 that is bought (bids) or sold (asks)
+// when the price moves through the range, excluding fees
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrderBookLevel {
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub size: u128,
+}
+
+// The liquidity of a pool aggregated into price levels like an order book. Bids are sorted by descending price and
+// asks by ascending price, both starting at the current price. Levels without liquidity are omitted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OrderBook {
+    pub bids: Vec<OrderBookLevel>,
+    pub asks: Vec<OrderBookLevel>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OrderBookError {
+    /// The number of ticks per level must be greater than zero.
+    InvalidLevelWidth,
+    /// Computing the size of a level failed.
+    AmountDelta(AmountDeltaError),
+}
+
+fn asks(
+    pool: &BasePoolView<'_>,
+    ticks_per_level: i32,
+    max_levels: usize,
+) -> Result<Vec<OrderBookLevel>, AmountDeltaError> {
+    let ticks = pool.get_sorted_ticks();
+    let state = pool.get_state();
+    let mut next = state.active_tick_index.map_or(0, |index| index + 1);
+    let mut liquidity = state.liquidity;
+    let mut sqrt_ratio = state.sqrt_ratio;
+    let mut levels = Vec::new();
+
+    while levels.len() < max_levels && sqrt_ratio < MAX_SQRT_RATIO {
+        if liquidity == 0 {
+            // skip to the next initialized tick, since there is nothing to sell before it
+            match ticks.get(next) {
+                Some(tick) => sqrt_ratio = sqrt_ratio.max(to_sqrt_ratio(tick.index).unwrap()),
+                None => break,
+            }
+        }
+
+        let tick_lower =
+            sqrt_ratio_to_tick(sqrt_ratio).div_euclid(ticks_per_level) * ticks_per_level;
+        let tick_upper = tick_lower.saturating_add(ticks_per_level).min(MAX_TICK);
+        let sqrt_ratio_upper = to_sqrt_ratio(tick_upper).unwrap();
+
+        let mut size: u128 = 0;
+        loop {
+            let crossed = ticks.get(next).filter(|tick| tick.index <= tick_upper);
+            let segment_end =
+                crossed.map_or(sqrt_ratio_upper, |tick| to_sqrt_ratio(tick.index).unwrap());
+
+            size = size.saturating_add(amount0_delta(sqrt_ratio, segment_end, liquidity, false)?);
+            sqrt_ratio = segment_end;
+
+            match crossed {
+                Some(tick) => {
+                    liquidity = liquidity.saturating_add_signed(tick.liquidity_delta);
+                    next += 1;
+                }
+                None => break,
+            }
+        }
+
+        if size != 0 {
+            levels.push(OrderBookLevel {
+                tick_lower,
+                tick_upper,
+                size,
+            });
+        }
+    }
+
+    Ok(levels)
+}
+
+fn bids(
+    pool: &BasePoolView<'_>,
+    ticks_per_level: i32,
+    max_levels: usize,
+) -> Result<Vec<OrderBookLevel>, AmountDeltaError> {
+    let ticks = pool.get_sorted_ticks();
+    let state = pool.get_state();
+    // the next tick to cross while the price decreases
+    let mut next = state.active_tick_index;
+    let mut liquidity = state.liquidity;
+    let mut sqrt_ratio = state.sqrt_ratio;
+    let mut levels = Vec::new();
+
+    while levels.len() < max_levels && sqrt_ratio > MIN_SQRT_RATIO {
+        if liquidity == 0 {
+            // skip to the next initialized tick, since there is nothing to buy before it
+            match next.map(|index| ticks[index]) {
+                Some(tick) => sqrt_ratio = sqrt_ratio.min(to_sqrt_ratio(tick.index).unwrap()),
+                None => break,
+            }
+        }
+
+        let mut tick_lower =
+            sqrt_ratio_to_tick(sqrt_ratio).div_euclid(ticks_per_level) * ticks_per_level;
+        if to_sqrt_ratio(tick_lower) == Some(sqrt_ratio) {
+            tick_lower = tick_lower.saturating_sub(ticks_per_level);
+        }
+        let tick_lower = tick_lower.max(MIN_TICK);
+        let tick_upper = tick_lower.saturating_add(ticks_per_level).min(MAX_TICK);
+        let sqrt_ratio_lower = to_sqrt_ratio(tick_lower).unwrap();
+
+        let mut size: u128 = 0;
+        loop {
+            let crossed = next
+                .map(|index| ticks[index])
+                .filter(|tick| tick.index >= tick_lower);
+            let segment_end =
+                crossed.map_or(sqrt_ratio_lower, |tick| to_sqrt_ratio(tick.index).unwrap());
+
+            size = size.saturating_add(amount0_delta(segment_end, sqrt_ratio, liquidity, false)?);
+            sqrt_ratio = segment_end;
+
+            match crossed {
+                Some(tick) => {
+                    liquidity = liquidity.saturating_add_signed(-tick.liquidity_delta);
+                    next = next.and_then(|index| index.checked_sub(1));
+                }
+                None => break,
+            }
+        }
+
+        if size != 0 {
+            levels.push(OrderBookLevel {
+                tick_lower,
+                tick_upper,
+                size,
+            });
+        }
+    }
+
+    Ok(levels)
+}
+
+/// Aggregates the liquidity of a base pool into an order book with levels of `ticks_per_level` ticks, aligned to
+/// multiples of `ticks_per_level`, and at most `max_levels` non-empty levels per side.
+///
+/// Each level is sized by the amount of token0 that the pool sells (asks) or buys (bids) as the price moves through
+/// it, starting from the current price, so the first level on each side is partial.
+pub fn order_book(
+    pool: &BasePoolView<'_>,
+    ticks_per_level: u32,
+    max_levels: usize,
+) -> Result<OrderBook, OrderBookError> {
+    let ticks_per_level = i32::try_from(ticks_per_level)
+        .ok()
+        .filter(|width| *width > 0)
+        .ok_or(OrderBookError::InvalidLevelWidth)?;
+
+    Ok(OrderBook {
+        bids: bids(pool, ticks_per_level, max_levels).map_err(OrderBookError::AmountDelta)?,
+        asks: asks(pool, ticks_per_level, max_levels).map_err(OrderBookError::AmountDelta)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::uint::U256;
+    use crate::quoting::base_pool::BasePoolState;
+    use crate::quoting::types::{Address, Config, NodeKey, Tick};
+    use alloc::vec;
+
+    const LIQUIDITY: u128 = 1_000_000_000_000;
+
+    fn ticks() -> Vec<Tick> {
+        vec![
+            Tick {
+                index: -1000,
+                liquidity_delta: LIQUIDITY as i128,
+            },
+            Tick {
+                index: 250,
+                liquidity_delta: LIQUIDITY as i128,
+            },
+            Tick {
+                index: 1000,
+                liquidity_delta: -2 * LIQUIDITY as i128,
+            },
+        ]
+    }
+
+    fn view(
+        ticks: &[Tick],
+        tick: i32,
+        liquidity: u128,
+        active_tick_index: Option<usize>,
+    ) -> BasePoolView<'_> {
+        BasePoolView::new(
+            NodeKey {
+                token0: Address::from_raw(U256::one()),
+                token1: Address::from_raw(U256::from(2)),
+                config: Config::from_raw_unchecked(0, 50, U256::zero()),
+            },
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(tick).unwrap(),
+                liquidity,
+                active_tick_index,
+            },
+            ticks,
+        )
+        .unwrap()
+    }
+
+    fn size(lower: i32, upper: i32, liquidity: u128) -> u128 {
+        amount0_delta(
+            to_sqrt_ratio(lower).unwrap(),
+            to_sqrt_ratio(upper).unwrap(),
+            liquidity,
+            false,
+        )
+        .unwrap()
+    }
+
+    fn level(tick_lower: i32, tick_upper: i32, size: u128) -> OrderBookLevel {
+        OrderBookLevel {
+            tick_lower,
+            tick_upper,
+            size,
+        }
+    }
+
+    #[test]
+    fn test_order_book() {
+        let ticks = ticks();
+        let book = order_book(&view(&ticks, 100, LIQUIDITY, Some(0)), 500, 10).unwrap();
+
+        assert_eq!(
+            book.asks,
+            vec![
+                level(
+                    0,
+                    500,
+                    size(100, 250, LIQUIDITY) + size(250, 500, 2 * LIQUIDITY)
+                ),
+                level(500, 1000, size(500, 1000, 2 * LIQUIDITY)),
+            ]
+        );
+        assert_eq!(
+            book.bids,
+            vec![
+                level(0, 500, size(0, 100, LIQUIDITY)),
+                level(-500, 0, size(-500, 0, LIQUIDITY)),
+                level(-1000, -500, size(-1000, -500, LIQUIDITY)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_order_book_from_aligned_price() {
+        let ticks = ticks();
+        // the price is exactly at the initialized tick, which is already crossed
+        let book = order_book(&view(&ticks, 250, 2 * LIQUIDITY, Some(1)), 250, 10).unwrap();
+
+        assert_eq!(
+            book.asks,
+            vec![
+                level(250, 500, size(250, 500, 2 * LIQUIDITY)),
+                level(500, 750, size(500, 750, 2 * LIQUIDITY)),
+                level(750, 1000, size(750, 1000, 2 * LIQUIDITY)),
+            ]
+        );
+        assert_eq!(
+            book.bids,
+            vec![
+                level(0, 250, size(0, 250, LIQUIDITY)),
+                level(-250, 0, size(-250, 0, LIQUIDITY)),
+                level(-500, -250, size(-500, -250, LIQUIDITY)),
+                level(-750, -500, size(-750, -500, LIQUIDITY)),
+                level(-1000, -750, size(-1000, -750, LIQUIDITY)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_order_book_skips_empty_ranges() {
+        let ticks = ticks();
+        // the price is below all the ticks, so there are no bids and asks start at the first tick
+        let book = order_book(&view(&ticks, -5000, 0, None), 1000, 2).unwrap();
+
+        assert!(book.bids.is_empty());
+        assert_eq!(
+            book.asks,
+            vec![
+                level(-1000, 0, size(-1000, 0, LIQUIDITY)),
+                level(
+                    0,
+                    1000,
+                    size(0, 250, LIQUIDITY) + size(250, 1000, 2 * LIQUIDITY)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_levels_and_errors() {
+        let ticks = ticks();
+        let pool = view(&ticks, 100, LIQUIDITY, Some(0));
+
+        let book = order_book(&pool, 100, 3).unwrap();
+        assert_eq!((book.bids.len(), book.asks.len()), (3, 3));
+        assert_eq!(book.asks[0].tick_lower, 100);
+        assert_eq!(book.bids[0].tick_lower, 0);
+
+        assert_eq!(
+            order_book(&pool, 0, 3),
+            Err(OrderBookError::InvalidLevelWidth)
+        );
+    }
+}
@@ -0,0 +1,303 @@
+use crate::quoting::types::{Address, Pool, Quote, QuoteParams, TokenAmount};
+use alloc::vec::Vec;
+
+// Parameters of the synthetic order flow
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrderFlowConfig {
+    pub token0: Address,
+    pub token1: Address,
+    // Swap sizes are distributed log-uniformly between the min and max amount (inclusive)
+    pub min_amount: u128,
+    pub max_amount: u128,
+    // Probability in [0, 1] that a swap sells the same token as the previous swap
+    pub direction_persistence: f64,
+    // Probability in [0, 1] that a swap specifies the output amount instead of the input amount
+    pub exact_output_probability: f64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OrderFlowConfigError {
+    /// The min amount must be greater than zero and not greater than the max amount.
+    InvalidAmountRange,
+    /// The max amount must fit in an i128.
+    MaxAmountOverflow,
+    /// Probabilities must be between 0 and 1.
+    InvalidProbability,
+}
+
+// Deterministic pseudo random number generator (splitmix64), so that flows are reproducible from the seed
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SplitMix64(pub(crate) u64);
+
+impl SplitMix64 {
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    pub(crate) fn next_u128(&mut self) -> u128 {
+        ((self.next_u64() as u128) << 64) | self.next_u64() as u128
+    }
+
+    // Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Uniform in [low, high]
+    fn next_in_range(&mut self, low: u128, high: u128) -> u128 {
+        match (high - low).checked_add(1) {
+            Some(span) => low + self.next_u128() % span,
+            None => self.next_u128(),
+        }
+    }
+}
+
+// An infinite iterator of synthetic swaps
+#[derive(Clone, Debug)]
+pub struct OrderFlowGenerator {
+    config: OrderFlowConfig,
+    rng: SplitMix64,
+    sell_token1: bool,
+}
+
+impl OrderFlowGenerator {
+    pub fn new(config: OrderFlowConfig, seed: u64) -> Result<Self, OrderFlowConfigError> {
+        if config.min_amount == 0 || config.min_amount > config.max_amount {
+            return Err(OrderFlowConfigError::InvalidAmountRange);
+        }
+        if config.max_amount > i128::MAX as u128 {
+            return Err(OrderFlowConfigError::MaxAmountOverflow);
+        }
+        if !(0f64..=1f64).contains(&config.direction_persistence)
+            || !(0f64..=1f64).contains(&config.exact_output_probability)
+        {
+            return Err(OrderFlowConfigError::InvalidProbability);
+        }
+
+        let mut rng = SplitMix64(seed);
+        let sell_token1 = rng.next_u64() & 1 == 1;
+
+        Ok(Self {
+            config,
+            rng,
+            sell_token1,
+        })
+    }
+
+    // Picks a bit length uniformly and then an amount uniformly within that bit length
+    fn next_size(&mut self) -> u128 {
+        let (min, max) = (self.config.min_amount, self.config.max_amount);
+        let min_bits = 128 - min.leading_zeros();
+        let max_bits = 128 - max.leading_zeros();
+
+        let bits = self.rng.next_in_range(min_bits as u128, max_bits as u128) as u32;
+        let low = (1u128 << (bits - 1)).max(min);
+        let high = if bits == 128 {
+            max
+        } else {
+            ((1u128 << bits) - 1).min(max)
+        };
+
+        self.rng.next_in_range(low, high)
+    }
+}
+
+impl Iterator for OrderFlowGenerator {
+    type Item = TokenAmount;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rng.next_f64() >= self.config.direction_persistence {
+            self.sell_token1 = !self.sell_token1;
+        }
+
+        let size = self.next_size() as i128;
+        let (sold, bought) = if self.sell_token1 {
+            (self.config.token1, self.config.token0)
+        } else {
+            (self.config.token0, self.config.token1)
+        };
+
+        Some(
+            if self.rng.next_f64() < self.config.exact_output_probability {
+                TokenAmount {
+                    token: bought,
+                    amount: -size,
+                }
+            } else {
+                TokenAmount {
+                    token: sold,
+                    amount: size,
+                }
+            },
+        )
+    }
+}
+
+pub type SimulationResult<P> =
+    Result<Vec<Quote<<P as Pool>::Resources, <P as Pool>::State>>, <P as Pool>::QuoteError>;
+
+// Quotes the swaps in sequence against the pool, each starting from the state after the previous swap
+pub fn simulate<P, I>(pool: &P, swaps: I, meta: P::Meta) -> SimulationResult<P>
+where
+    P: Pool,
+    I: IntoIterator<Item = TokenAmount>,
+{
+    let mut state = pool.get_state();
+
+    swaps
+        .into_iter()
+        .map(|token_amount| {
+            let quote = pool.quote(QuoteParams {
+                token_amount,
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: Some(state),
+                meta,
+            })?;
+            state = quote.state_after;
+            Ok(quote)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::uint::U256;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::types::{Config, NodeKey};
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn config() -> OrderFlowConfig {
+        OrderFlowConfig {
+            token0: TOKEN0,
+            token1: TOKEN1,
+            min_amount: 100,
+            max_amount: 1_000_000,
+            direction_persistence: 0.7,
+            exact_output_probability: 0.2,
+        }
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let invalid = |f: fn(&mut OrderFlowConfig)| {
+            let mut config = config();
+            f(&mut config);
+            OrderFlowGenerator::new(config, 0).unwrap_err()
+        };
+
+        assert_eq!(
+            invalid(|c| c.min_amount = 0),
+            OrderFlowConfigError::InvalidAmountRange
+        );
+        assert_eq!(
+            invalid(|c| c.max_amount = 10),
+            OrderFlowConfigError::InvalidAmountRange
+        );
+        assert_eq!(
+            invalid(|c| c.max_amount = u128::MAX),
+            OrderFlowConfigError::MaxAmountOverflow
+        );
+        assert_eq!(
+            invalid(|c| c.direction_persistence = 1.5),
+            OrderFlowConfigError::InvalidProbability
+        );
+        assert_eq!(
+            invalid(|c| c.exact_output_probability = f64::NAN),
+            OrderFlowConfigError::InvalidProbability
+        );
+    }
+
+    #[test]
+    fn test_deterministic_and_bounded() {
+        let a: Vec<TokenAmount> = OrderFlowGenerator::new(config(), 42)
+            .unwrap()
+            .take(1000)
+            .collect();
+        let b: Vec<TokenAmount> = OrderFlowGenerator::new(config(), 42)
+            .unwrap()
+            .take(1000)
+            .collect();
+        assert_eq!(a, b);
+
+        for swap in &a {
+            assert!((100..=1_000_000).contains(&swap.amount.unsigned_abs()));
+            assert!(swap.token == TOKEN0 || swap.token == TOKEN1);
+        }
+
+        // both exact input and exact output swaps are generated, and small sizes are as common as large ones
+        assert!(a.iter().any(|swap| swap.amount < 0));
+        assert!(a.iter().any(|swap| swap.amount > 0));
+        assert!(a.iter().any(|swap| swap.amount.unsigned_abs() < 1_000));
+        assert!(a.iter().any(|swap| swap.amount.unsigned_abs() > 100_000));
+    }
+
+    #[test]
+    fn test_direction_persistence() {
+        let sells_token1 = |swap: &TokenAmount| (swap.token == TOKEN1) == (swap.amount > 0);
+
+        let mut config = config();
+        config.direction_persistence = 1f64;
+        let swaps: Vec<TokenAmount> = OrderFlowGenerator::new(config, 1)
+            .unwrap()
+            .take(100)
+            .collect();
+        assert!(swaps
+            .iter()
+            .all(|swap| sells_token1(swap) == sells_token1(&swaps[0])));
+
+        config.direction_persistence = 0f64;
+        let swaps: Vec<TokenAmount> = OrderFlowGenerator::new(config, 1)
+            .unwrap()
+            .take(100)
+            .collect();
+        assert!(swaps
+            .windows(2)
+            .all(|pair| sells_token1(&pair[0]) != sells_token1(&pair[1])));
+    }
+
+    #[test]
+    fn test_simulate() {
+        let pool = FullRangePool::new(
+            NodeKey {
+                token0: TOKEN0,
+                token1: TOKEN1,
+                config: Config::from_raw_unchecked(1 << 54, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 1_000_000_000,
+            },
+        )
+        .unwrap();
+
+        let swaps: Vec<TokenAmount> = OrderFlowGenerator::new(config(), 7)
+            .unwrap()
+            .take(50)
+            .collect();
+        let quotes = simulate(&pool, swaps.iter().copied(), ()).unwrap();
+
+        assert_eq!(quotes.len(), 50);
+        let mut state = pool.get_state();
+        for (swap, quote) in swaps.into_iter().zip(&quotes) {
+            let expected = pool
+                .quote(QuoteParams {
+                    token_amount: swap,
+                    sqrt_ratio_limit: None,
+                    max_ticks_crossed: None,
+                    override_state: Some(state),
+                    meta: (),
+                })
+                .unwrap();
+            assert_eq!(quote.calculated_amount, expected.calculated_amount);
+            state = expected.state_after;
+        }
+    }
+}
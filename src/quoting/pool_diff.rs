@@ -0,0 +1,141 @@
+use crate::math::uint::U256;
+use crate::quoting::twamm_pool::TwammSaleRateDelta;
+use crate::quoting::types::{NodeKey, Tick};
+use alloc::vec::Vec;
+
+// The value of a field in the pool that diff was called on (left) and in the other pool (right)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FieldDiff<T> {
+    pub left: T,
+    pub right: T,
+}
+
+impl<T: PartialEq> FieldDiff<T> {
+    // Returns None if the values are equal
+    pub fn of(left: T, right: T) -> Option<Self> {
+        (left != right).then_some(Self { left, right })
+    }
+}
+
+// A difference between two sorted lists of entries, e.g. ticks keyed by index
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryDiff<T> {
+    /// The entry only exists in the other pool.
+    Added(T),
+    /// The entry only exists in the pool that diff was called on.
+    Removed(T),
+    /// The entry exists in both pools with different values.
+    Changed { left: T, right: T },
+}
+
+// The fields that differ between two pools of the same type. Fields that do not apply to the pool type are
+// always None or empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolDiff {
+    pub key: Option<FieldDiff<NodeKey>>,
+    pub sqrt_ratio: Option<FieldDiff<U256>>,
+    pub liquidity: Option<FieldDiff<u128>>,
+    pub active_tick_index: Option<FieldDiff<Option<usize>>>,
+    pub ticks: Vec<EntryDiff<Tick>>,
+    // The last snapshot time of oracle pools, the last execution time of TWAMM pools and the last update time of
+    // MEV resist pools
+    pub last_update_time: Option<FieldDiff<u64>>,
+    // The tick that the additional fees of MEV resist pools are computed from
+    pub reference_tick: Option<FieldDiff<i32>>,
+    pub token0_sale_rate: Option<FieldDiff<u128>>,
+    pub token1_sale_rate: Option<FieldDiff<u128>>,
+    pub sale_rate_deltas: Vec<EntryDiff<TwammSaleRateDelta>>,
+}
+
+impl PoolDiff {
+    // Returns true if the two pools are identical
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// Diffs two lists that are sorted by the given key, which must be unique within each list
+pub(crate) fn diff_sorted<T, K, F>(left: &[T], right: &[T], key: F) -> Vec<EntryDiff<T>>
+where
+    T: Copy + PartialEq,
+    K: Ord,
+    F: Fn(&T) -> K,
+{
+    let mut diffs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < left.len() || j < right.len() {
+        match (left.get(i), right.get(j)) {
+            (Some(l), Some(r)) => match key(l).cmp(&key(r)) {
+                core::cmp::Ordering::Less => {
+                    diffs.push(EntryDiff::Removed(*l));
+                    i += 1;
+                }
+                core::cmp::Ordering::Greater => {
+                    diffs.push(EntryDiff::Added(*r));
+                    j += 1;
+                }
+                core::cmp::Ordering::Equal => {
+                    if l != r {
+                        diffs.push(EntryDiff::Changed {
+                            left: *l,
+                            right: *r,
+                        });
+                    }
+                    i += 1;
+                    j += 1;
+                }
+            },
+            (Some(l), None) => {
+                diffs.push(EntryDiff::Removed(*l));
+                i += 1;
+            }
+            (None, Some(r)) => {
+                diffs.push(EntryDiff::Added(*r));
+                j += 1;
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+
+    diffs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn tick(index: i32, liquidity_delta: i128) -> Tick {
+        Tick {
+            index,
+            liquidity_delta,
+        }
+    }
+
+    #[test]
+    fn test_diff_sorted() {
+        let left = [tick(-10, 5), tick(0, 3), tick(10, -8)];
+        let right = [tick(-20, 1), tick(0, 4), tick(10, -8), tick(20, -5)];
+
+        assert_eq!(
+            diff_sorted(&left, &right, |t| t.index),
+            vec![
+                EntryDiff::Added(tick(-20, 1)),
+                EntryDiff::Removed(tick(-10, 5)),
+                EntryDiff::Changed {
+                    left: tick(0, 3),
+                    right: tick(0, 4)
+                },
+                EntryDiff::Added(tick(20, -5)),
+            ]
+        );
+        assert!(diff_sorted(&left, &left, |t| t.index).is_empty());
+    }
+
+    #[test]
+    fn test_field_diff_of() {
+        assert_eq!(FieldDiff::of(1, 1), None);
+        assert_eq!(FieldDiff::of(1, 2), Some(FieldDiff { left: 1, right: 2 }));
+    }
+}
@@ -0,0 +1,475 @@
+use crate::math::uint::U256;
+use crate::quoting::events::{ApplyEventError, ApplyPoolEvent, EventPosition, PoolEvent, PoolLog};
+use crate::quoting::types::{Address, Config, Fee, NodeKey, Pool, TickSpacing};
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+// A collection of pools indexed by their key.
+//
+// Pools are stored behind an `Arc` and copied on write, so cloning the map is a cheap snapshot that concurrent
+// readers can keep using while the original map is updated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolMap<P> {
+    pools: BTreeMap<NodeKey, Arc<P>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PoolChangeKind {
+    // The price or active liquidity changed because of a swap
+    Swapped,
+    // The liquidity of a range of the pool changed because of a position update
+    LiquidityUpdated,
+}
+
+impl From<&PoolEvent> for PoolChangeKind {
+    fn from(event: &PoolEvent) -> Self {
+        match event {
+            PoolEvent::Swapped { .. } => PoolChangeKind::Swapped,
+            PoolEvent::PositionUpdated { .. } => PoolChangeKind::LiquidityUpdated,
+        }
+    }
+}
+
+// Notification that a block changed a pool in a certain way
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolChanged {
+    pub key: NodeKey,
+    pub kind: PoolChangeKind,
+}
+
+// The states of the pools before a block was applied, which can be used to revert the block
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockDelta<P> {
+    pub block_number: u64,
+    previous_pools: BTreeMap<NodeKey, Arc<P>>,
+    changes: Vec<PoolChanged>,
+}
+
+impl<P> BlockDelta<P> {
+    pub fn new(block_number: u64) -> Self {
+        Self {
+            block_number,
+            previous_pools: BTreeMap::new(),
+            changes: Vec::new(),
+        }
+    }
+
+    // Returns the changes of the block in the order they first occurred, with one notification per pool and kind
+    pub fn changes(&self) -> &[PoolChanged] {
+        &self.changes
+    }
+
+    pub(crate) fn record_change(&mut self, change: PoolChanged) {
+        if !self.changes.contains(&change) {
+            self.changes.push(change);
+        }
+    }
+
+    // Returns the keys of the pools that were changed by the block
+    pub fn changed_pools(&self) -> impl Iterator<Item = &NodeKey> + '_ {
+        self.previous_pools.keys()
+    }
+
+    // Returns the state of the pool before the block, if the block changed it
+    pub fn previous_pool(&self, key: &NodeKey) -> Option<&P> {
+        self.previous_pools.get(key).map(|pool| pool.as_ref())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.previous_pools.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ApplyBlockError {
+    /// All logs of a block must have the block number of the block.
+    LogNotInBlock(EventPosition),
+    /// The event of the log at the position could not be applied to its pool.
+    ApplyEventFailed {
+        position: EventPosition,
+        error: ApplyEventError,
+    },
+}
+
+impl<P> Default for PoolMap<P> {
+    fn default() -> Self {
+        Self {
+            pools: BTreeMap::new(),
+        }
+    }
+}
+
+impl<P: Pool> PoolMap<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Inserts the pool, returning the pool previously stored under the same key
+    pub fn insert(&mut self, pool: P) -> Option<P> {
+        self.pools
+            .insert(*pool.get_key(), Arc::new(pool))
+            .map(Arc::unwrap_or_clone)
+    }
+
+    pub fn remove(&mut self, key: &NodeKey) -> Option<P> {
+        self.pools.remove(key).map(Arc::unwrap_or_clone)
+    }
+
+    pub fn get(&self, key: &NodeKey) -> Option<&P> {
+        self.pools.get(key).map(|pool| pool.as_ref())
+    }
+
+    // Returns the pool for modification, copying it first if it is shared with a snapshot
+    pub fn get_mut(&mut self, key: &NodeKey) -> Option<&mut P> {
+        self.pools.get_mut(key).map(Arc::make_mut)
+    }
+
+    pub fn contains_key(&self, key: &NodeKey) -> bool {
+        self.pools.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+
+    // Iterates over the pools in the order of their keys
+    pub fn iter(&self) -> impl Iterator<Item = &P> + '_ {
+        self.pools.values().map(|pool| pool.as_ref())
+    }
+
+    // Iterates over the pools that trade the given token
+    pub fn pools_for_token(&self, token: Address) -> impl Iterator<Item = &P> + '_ {
+        self.iter().filter(move |pool| {
+            let key = pool.get_key();
+            key.token0 == token || key.token1 == token
+        })
+    }
+
+    // Iterates over the pools that trade the two tokens for each other, in any order
+    pub fn pools_for_pair(
+        &self,
+        token_a: Address,
+        token_b: Address,
+    ) -> impl Iterator<Item = &P> + '_ {
+        let (token0, token1) = if token_a < token_b {
+            (token_a, token_b)
+        } else {
+            (token_b, token_a)
+        };
+
+        // pool keys are ordered by token0 and then token1, so the pools of a pair are contiguous
+        let first_key = NodeKey {
+            token0,
+            token1,
+            config: Config::new(Fee::ZERO, TickSpacing::FULL_RANGE, U256::zero()),
+        };
+
+        self.pools
+            .range(first_key..)
+            .take_while(move |(key, _)| key.token0 == token0 && key.token1 == token1)
+            .map(|(_, pool)| pool.as_ref())
+    }
+}
+
+impl<P: Pool + ApplyPoolEvent> PoolMap<P> {
+    // Records the state of the pool in the delta if it is the first change to the pool in the block
+    pub(crate) fn record_previous(&self, delta: &mut BlockDelta<P>, key: &NodeKey) {
+        if let Some(pool) = self.pools.get(key) {
+            delta
+                .previous_pools
+                .entry(*key)
+                .or_insert_with(|| pool.clone());
+        }
+    }
+
+    // Applies all the logs of a block, which must be sorted by log index. Either all logs are applied, or none of them
+    // are and the error is returned. Logs of pools that are not in the map are ignored.
+    pub fn apply_block(
+        &mut self,
+        block_number: u64,
+        logs: &[PoolLog],
+    ) -> Result<BlockDelta<P>, ApplyBlockError> {
+        let mut delta = BlockDelta::new(block_number);
+
+        for log in logs {
+            let result = if log.position.block_number != block_number {
+                Err(ApplyBlockError::LogNotInBlock(log.position))
+            } else {
+                self.record_previous(&mut delta, &log.pool_key);
+                match self.get_mut(&log.pool_key) {
+                    Some(pool) => pool
+                        .apply_event(&log.event)
+                        .map(|()| {
+                            delta.record_change(PoolChanged {
+                                key: log.pool_key,
+                                kind: (&log.event).into(),
+                            })
+                        })
+                        .map_err(|error| ApplyBlockError::ApplyEventFailed {
+                            position: log.position,
+                            error,
+                        }),
+                    None => Ok(()),
+                }
+            };
+
+            if let Err(error) = result {
+                self.revert(delta);
+                return Err(error);
+            }
+        }
+
+        Ok(delta)
+    }
+
+    // Restores the states of the pools before the block of the delta
+    pub fn revert(&mut self, delta: BlockDelta<P>) {
+        self.pools.extend(delta.previous_pools);
+    }
+}
+
+impl<P: Pool> FromIterator<P> for PoolMap<P> {
+    fn from_iter<I: IntoIterator<Item = P>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<P: Pool> Extend<P> for PoolMap<P> {
+    fn extend<I: IntoIterator<Item = P>>(&mut self, iter: I) {
+        for pool in iter {
+            self.insert(pool);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::{MAX_TICK, MIN_TICK};
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use alloc::vec::Vec;
+
+    fn pool(token0: u64, token1: u64, fee: u64) -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0: Address::from_raw(U256::from(token0)),
+                token1: Address::from_raw(U256::from(token1)),
+                config: Config::from_raw_unchecked(fee, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 1_000,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut map = PoolMap::new();
+        assert!(map.is_empty());
+        assert_eq!(map.insert(pool(1, 2, 0)), None);
+        assert_eq!(map.insert(pool(1, 2, 0)), Some(pool(1, 2, 0)));
+        assert_eq!(map.len(), 1);
+
+        let key = *pool(1, 2, 0).get_key();
+        assert!(map.contains_key(&key));
+        assert_eq!(map.get(&key), Some(&pool(1, 2, 0)));
+        assert_eq!(map.remove(&key), Some(pool(1, 2, 0)));
+        assert!(map.get(&key).is_none());
+    }
+
+    #[test]
+    fn test_pools_for_pair_and_token() {
+        let map: PoolMap<FullRangePool> = [
+            pool(1, 2, 0),
+            pool(1, 3, 0),
+            pool(1, 2, 5),
+            pool(2, 3, 0),
+            pool(0, 1, 0),
+        ]
+        .into_iter()
+        .collect();
+
+        let fees = |pools: Vec<&FullRangePool>| -> Vec<(u64, u64, u64)> {
+            pools
+                .into_iter()
+                .map(|pool| {
+                    let key = pool.get_key();
+                    (
+                        key.token0.raw().as_u64(),
+                        key.token1.raw().as_u64(),
+                        key.config.fee.raw(),
+                    )
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            fees(
+                map.pools_for_pair(
+                    Address::from_raw(U256::from(2)),
+                    Address::from_raw(U256::from(1))
+                )
+                .collect()
+            ),
+            [(1, 2, 0), (1, 2, 5)]
+        );
+        assert_eq!(
+            fees(
+                map.pools_for_pair(
+                    Address::from_raw(U256::from(2)),
+                    Address::from_raw(U256::from(3))
+                )
+                .collect()
+            ),
+            [(2, 3, 0)]
+        );
+        assert!(map
+            .pools_for_pair(
+                Address::from_raw(U256::from(0)),
+                Address::from_raw(U256::from(3))
+            )
+            .next()
+            .is_none());
+        assert_eq!(
+            fees(
+                map.pools_for_token(Address::from_raw(U256::from(3)))
+                    .collect()
+            ),
+            [(1, 3, 0), (2, 3, 0)]
+        );
+    }
+
+    fn log(block_number: u64, log_index: u32, pool: &FullRangePool, delta: i128) -> PoolLog {
+        PoolLog {
+            position: EventPosition {
+                block_number,
+                log_index,
+            },
+            pool_key: *pool.get_key(),
+            event: PoolEvent::PositionUpdated {
+                tick_lower: MIN_TICK,
+                tick_upper: MAX_TICK,
+                liquidity_delta: delta,
+            },
+        }
+    }
+
+    #[test]
+    fn test_apply_block_and_revert() {
+        let (a, b) = (pool(1, 2, 0), pool(1, 3, 0));
+        let mut map: PoolMap<FullRangePool> = [a.clone(), b.clone()].into_iter().collect();
+        let original = map.clone();
+
+        let delta = map
+            .apply_block(5, &[log(5, 0, &a, 100), log(5, 1, &a, -50)])
+            .unwrap();
+        assert_eq!(delta.block_number, 5);
+        assert_eq!(delta.changed_pools().collect::<Vec<_>>(), [a.get_key()]);
+        assert_eq!(delta.previous_pool(a.get_key()), Some(&a));
+        assert_eq!(
+            delta.changes(),
+            [PoolChanged {
+                key: *a.get_key(),
+                kind: PoolChangeKind::LiquidityUpdated,
+            }]
+        );
+        assert_eq!(map.get(a.get_key()).unwrap().get_state().liquidity, 1_050);
+
+        // the clone taken before the block is a snapshot that is not affected by it
+        assert_eq!(
+            original.get(a.get_key()).unwrap().get_state().liquidity,
+            1_000
+        );
+
+        map.revert(delta);
+        assert_eq!(map, original);
+    }
+
+    #[test]
+    fn test_apply_block_is_atomic() {
+        let (a, b) = (pool(1, 2, 0), pool(1, 3, 0));
+        let mut map: PoolMap<FullRangePool> = [a.clone(), b.clone()].into_iter().collect();
+        let original = map.clone();
+
+        assert_eq!(
+            map.apply_block(5, &[log(5, 0, &a, 100), log(5, 1, &b, -2_000)]),
+            Err(ApplyBlockError::ApplyEventFailed {
+                position: EventPosition {
+                    block_number: 5,
+                    log_index: 1
+                },
+                error: ApplyEventError::LiquidityOverflow,
+            })
+        );
+        assert_eq!(map, original);
+
+        assert_eq!(
+            map.apply_block(5, &[log(5, 0, &a, 100), log(6, 0, &b, 100)]),
+            Err(ApplyBlockError::LogNotInBlock(EventPosition {
+                block_number: 6,
+                log_index: 0
+            }))
+        );
+        assert_eq!(map, original);
+    }
+
+    #[test]
+    fn test_block_changes() {
+        let (a, b) = (pool(1, 2, 0), pool(1, 3, 0));
+        let mut map: PoolMap<FullRangePool> = [a.clone(), b.clone()].into_iter().collect();
+
+        let swap = |log_index: u32, pool: &FullRangePool| PoolLog {
+            position: EventPosition {
+                block_number: 5,
+                log_index,
+            },
+            pool_key: *pool.get_key(),
+            event: PoolEvent::Swapped {
+                sqrt_ratio_after: U256::one() << 127,
+                tick_after: -1386295,
+                liquidity_after: 1_000,
+            },
+        };
+        let untracked = pool(2, 3, 0);
+
+        let delta = map
+            .apply_block(
+                5,
+                &[
+                    swap(0, &b),
+                    log(5, 1, &a, 100),
+                    swap(2, &b),
+                    swap(3, &a),
+                    swap(4, &untracked),
+                ],
+            )
+            .unwrap();
+
+        assert_eq!(
+            delta.changes(),
+            [
+                PoolChanged {
+                    key: *b.get_key(),
+                    kind: PoolChangeKind::Swapped,
+                },
+                PoolChanged {
+                    key: *a.get_key(),
+                    kind: PoolChangeKind::LiquidityUpdated,
+                },
+                PoolChanged {
+                    key: *a.get_key(),
+                    kind: PoolChangeKind::Swapped,
+                },
+            ]
+        );
+    }
+}
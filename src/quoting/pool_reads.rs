@@ -0,0 +1,348 @@
+use crate::math::delta::AmountDeltaError;
+use crate::math::liquidity::position_amounts;
+use crate::math::muldiv::muldiv;
+use crate::math::tick::{to_sqrt_ratio, MAX_TICK, MIN_TICK};
+use crate::math::uint::U256;
+use crate::quoting::types::{Pool, SqrtRatioState, Tick};
+use alloc::vec::Vec;
+
+const TWO_POW_128: U256 = U256([0, 0, 1, 0]);
+
+// The amounts of both tokens held by the liquidity of a pool at its current price
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inventory {
+    pub amount0: u128,
+    pub amount1: u128,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PoolReadError {
+    /// Computing the amounts of a range of liquidity failed.
+    AmountDelta(AmountDeltaError),
+    /// The amounts or the value do not fit.
+    Overflow,
+    /// The tick range is empty, outside of the valid ticks or has a step of zero.
+    InvalidTickRange,
+}
+
+/// The numbers that consumers of a pool compute first: its spot price in both directions, the tokens it holds and its
+/// total value in terms of either token.
+///
+/// The amounts are the amounts that withdrawing all the liquidity of the pool at the current price would return,
+/// rounded down, so they exclude e.g. the tokens of TWAMM orders and fees that have not been collected.
+pub trait PoolReads: Pool
+where
+    Self::State: SqrtRatioState,
+{
+    // Returns the amounts the liquidity of the pool would hold if the pool was at the sqrt ratio
+    fn inventory_at(&self, sqrt_ratio: U256) -> Result<Inventory, PoolReadError>;
+
+    fn inventory(&self) -> Result<Inventory, PoolReadError> {
+        self.inventory_at(self.get_state().sqrt_ratio())
+    }
+
+    // Returns the price of token0 in token1 as a 128.128 fixed point number, or None if it does not fit
+    fn spot_price_x128(&self) -> Option<U256> {
+        let sqrt_ratio = self.get_state().sqrt_ratio();
+        muldiv(sqrt_ratio, sqrt_ratio, TWO_POW_128, false).ok()
+    }
+
+    // Returns the price of token1 in token0 as a 128.128 fixed point number, or None if it does not fit
+    fn inverse_spot_price_x128(&self) -> Option<U256> {
+        let sqrt_ratio = self.get_state().sqrt_ratio();
+        let inverse_sqrt_ratio = muldiv(TWO_POW_128, TWO_POW_128, sqrt_ratio, false).ok()?;
+        muldiv(inverse_sqrt_ratio, inverse_sqrt_ratio, TWO_POW_128, false).ok()
+    }
+
+    // Returns the value of the inventory in token0 at the spot price, rounded down
+    fn tvl_in_token0(&self) -> Result<U256, PoolReadError> {
+        let inventory = self.inventory()?;
+        let sqrt_ratio = self.get_state().sqrt_ratio();
+        let amount1_in_token0 = muldiv(
+            U256::from(inventory.amount1),
+            TWO_POW_128,
+            sqrt_ratio,
+            false,
+        )
+        .and_then(|amount| muldiv(amount, TWO_POW_128, sqrt_ratio, false))
+        .map_err(|_| PoolReadError::Overflow)?;
+        amount1_in_token0
+            .checked_add(U256::from(inventory.amount0))
+            .ok_or(PoolReadError::Overflow)
+    }
+
+    // Returns the value of the inventory in token1 at the spot price, rounded down
+    fn tvl_in_token1(&self) -> Result<U256, PoolReadError> {
+        let inventory = self.inventory()?;
+        let sqrt_ratio = self.get_state().sqrt_ratio();
+        let amount0_in_token1 = muldiv(
+            U256::from(inventory.amount0),
+            sqrt_ratio,
+            TWO_POW_128,
+            false,
+        )
+        .and_then(|amount| muldiv(amount, sqrt_ratio, TWO_POW_128, false))
+        .map_err(|_| PoolReadError::Overflow)?;
+        amount0_in_token1
+            .checked_add(U256::from(inventory.amount1))
+            .ok_or(PoolReadError::Overflow)
+    }
+}
+
+// The inventory of a pool if its price was at the tick
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InventoryPoint {
+    pub tick: i32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
+    pub sqrt_ratio: U256,
+    pub inventory: Inventory,
+}
+
+/// Returns the inventory of the pool as a function of its price, at every `step` ticks from `tick_lower` up to and
+/// including `tick_upper`, e.g. for risk systems that model the liquidity of a pool like a book of options.
+///
+/// The liquidity of the pool is assumed to stay as it is while the price moves, so the curve shows the tokens that
+/// swaps moving the price to each tick would leave in the pool, not including the fees of those swaps.
+pub fn inventory_curve<P: PoolReads>(
+    pool: &P,
+    tick_lower: i32,
+    tick_upper: i32,
+    step: u32,
+) -> Result<Vec<InventoryPoint>, PoolReadError>
+where
+    P::State: SqrtRatioState,
+{
+    if step == 0 || tick_lower > tick_upper || tick_lower < MIN_TICK || tick_upper > MAX_TICK {
+        return Err(PoolReadError::InvalidTickRange);
+    }
+
+    let mut ticks: Vec<i32> = (tick_lower..tick_upper).step_by(step as usize).collect();
+    ticks.push(tick_upper);
+
+    ticks
+        .into_iter()
+        .map(|tick| {
+            // the tick is within the valid ticks, so it has a sqrt ratio
+            let sqrt_ratio = to_sqrt_ratio(tick).ok_or(PoolReadError::InvalidTickRange)?;
+            Ok(InventoryPoint {
+                tick,
+                sqrt_ratio,
+                inventory: pool.inventory_at(sqrt_ratio)?,
+            })
+        })
+        .collect()
+}
+
+impl Inventory {
+    fn checked_add(self, other: Inventory) -> Result<Inventory, PoolReadError> {
+        Ok(Inventory {
+            amount0: self
+                .amount0
+                .checked_add(other.amount0)
+                .ok_or(PoolReadError::Overflow)?,
+            amount1: self
+                .amount1
+                .checked_add(other.amount1)
+                .ok_or(PoolReadError::Overflow)?,
+        })
+    }
+}
+
+// Returns the amounts of the liquidity between the sqrt ratios at the current sqrt ratio
+pub(crate) fn range_inventory(
+    sqrt_ratio: U256,
+    sqrt_ratio_lower: U256,
+    sqrt_ratio_upper: U256,
+    liquidity: u128,
+) -> Result<Inventory, PoolReadError> {
+    let (amount0, amount1) = position_amounts(
+        sqrt_ratio,
+        sqrt_ratio_lower,
+        sqrt_ratio_upper,
+        liquidity,
+        false,
+    )
+    .map_err(PoolReadError::AmountDelta)?;
+    Ok(Inventory { amount0, amount1 })
+}
+
+// Returns the amounts of the liquidity between each pair of adjacent initialized ticks at the current sqrt ratio
+pub(crate) fn ticks_inventory(
+    sorted_ticks: &[Tick],
+    sqrt_ratio: U256,
+) -> Result<Inventory, PoolReadError> {
+    let mut inventory = Inventory::default();
+    let mut liquidity = 0u128;
+
+    for pair in sorted_ticks.windows(2) {
+        liquidity = liquidity
+            .checked_add_signed(pair[0].liquidity_delta)
+            .ok_or(PoolReadError::Overflow)?;
+        if liquidity == 0 {
+            continue;
+        }
+
+        // the ticks of a valid pool are always within the bounds
+        let (lower, upper) = (
+            to_sqrt_ratio(pair[0].index).ok_or(PoolReadError::Overflow)?,
+            to_sqrt_ratio(pair[1].index).ok_or(PoolReadError::Overflow)?,
+        );
+        inventory = inventory.checked_add(range_inventory(sqrt_ratio, lower, upper, liquidity)?)?;
+    }
+
+    Ok(inventory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+    use crate::quoting::base_pool::{BasePool, BasePoolState};
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::types::{Address, Config, NodeKey};
+    use alloc::vec;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn key(tick_spacing: u32) -> NodeKey {
+        NodeKey {
+            token0: TOKEN0,
+            token1: TOKEN1,
+            config: Config::from_raw_unchecked(0, tick_spacing, U256::zero()),
+        }
+    }
+
+    #[test]
+    fn test_spot_price_and_tvl() {
+        // a price of 4 token1 per token0
+        let pool = FullRangePool::new(
+            key(0),
+            FullRangePoolState {
+                sqrt_ratio: U256::from(2) << 128,
+                liquidity: 1_000_000,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(pool.spot_price_x128(), Some(U256::from(4) << 128));
+        assert_eq!(pool.inverse_spot_price_x128(), Some(U256::one() << 126));
+
+        let inventory = pool.inventory().unwrap();
+        // the liquidity is the geometric mean of the amounts, and the amounts are in the ratio of the price
+        assert_eq!(inventory.amount0, 499_999);
+        assert_eq!(inventory.amount1, 1_999_999);
+        assert_eq!(pool.tvl_in_token1(), Ok(U256::from(3_999_995)));
+        assert_eq!(pool.tvl_in_token0(), Ok(U256::from(999_998)));
+
+        let at_max_price = FullRangePool::new(
+            key(0),
+            FullRangePoolState {
+                sqrt_ratio: MAX_SQRT_RATIO,
+                liquidity: 1_000_000,
+            },
+        )
+        .unwrap();
+        // all the liquidity is in token1 at the highest price
+        assert_eq!(at_max_price.inventory().unwrap().amount0, 0);
+        assert!(at_max_price.spot_price_x128().is_some());
+    }
+
+    #[test]
+    fn test_base_pool_inventory() {
+        let ticks = vec![
+            Tick {
+                index: -1000,
+                liquidity_delta: 1_000_000,
+            },
+            Tick {
+                index: 1000,
+                liquidity_delta: 1_000_000,
+            },
+            Tick {
+                index: 2000,
+                liquidity_delta: -2_000_000,
+            },
+        ];
+        let pool = BasePool::new(
+            key(1000),
+            BasePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 1_000_000,
+                active_tick_index: Some(0),
+            },
+            ticks,
+        )
+        .unwrap();
+
+        let sqrt_ratio = |tick| to_sqrt_ratio(tick).unwrap();
+        let below = range_inventory(
+            U256::one() << 128,
+            sqrt_ratio(-1000),
+            sqrt_ratio(1000),
+            1_000_000,
+        )
+        .unwrap();
+        let above = range_inventory(
+            U256::one() << 128,
+            sqrt_ratio(1000),
+            sqrt_ratio(2000),
+            2_000_000,
+        )
+        .unwrap();
+
+        // the range above the price only holds token0
+        assert_eq!(above.amount1, 0);
+        assert_eq!(pool.inventory(), below.checked_add(above));
+        assert_eq!(
+            ticks_inventory(&[], MIN_SQRT_RATIO),
+            Ok(Inventory::default())
+        );
+    }
+
+    #[test]
+    fn test_inventory_curve() {
+        let pool = FullRangePool::new(
+            key(0),
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 1_000_000,
+            },
+        )
+        .unwrap();
+
+        let curve = inventory_curve(&pool, -1000, 1000, 300).unwrap();
+        assert_eq!(
+            curve.iter().map(|point| point.tick).collect::<Vec<_>>(),
+            vec![-1000, -700, -400, -100, 200, 500, 800, 1000]
+        );
+        // token0 is sold into the pool as the price falls and bought out of it as the price rises
+        assert!(curve
+            .windows(2)
+            .all(|pair| pair[0].inventory.amount0 > pair[1].inventory.amount0
+                && pair[0].inventory.amount1 < pair[1].inventory.amount1));
+        assert_eq!(
+            curve[0].inventory,
+            pool.inventory_at(to_sqrt_ratio(-1000).unwrap()).unwrap()
+        );
+
+        assert_eq!(
+            inventory_curve(&pool, 0, 0, 1).unwrap()[0].inventory,
+            pool.inventory().unwrap()
+        );
+        assert_eq!(
+            inventory_curve(&pool, 1, 0, 1),
+            Err(PoolReadError::InvalidTickRange)
+        );
+        assert_eq!(
+            inventory_curve(&pool, 0, 10, 0),
+            Err(PoolReadError::InvalidTickRange)
+        );
+        assert_eq!(
+            inventory_curve(&pool, MIN_TICK - 1, 0, 1),
+            Err(PoolReadError::InvalidTickRange)
+        );
+    }
+}
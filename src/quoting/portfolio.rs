@@ -0,0 +1,349 @@
+use crate::quoting::pool_map::PoolMap;
+use crate::quoting::types::{Address, NodeKey, Pool, QuoteParams, TokenAmount};
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+// A position is a set of token balances, where negative amounts are liabilities
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PortfolioPosition {
+    pub balances: Vec<TokenAmount>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PositionValuation {
+    // The value of each balance of the position in the numéraire, in the same order as the balances
+    pub balance_values: Vec<i128>,
+    pub value: i128,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PortfolioValuation {
+    pub positions: Vec<PositionValuation>,
+    pub total_value: i128,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PortfolioValuationError<E> {
+    /// No pool or pair of pools connects the token to the numéraire.
+    NoConversionRoute(Address),
+    /// A pool on the conversion route of the token failed to quote.
+    QuoteError(Address, E),
+    /// The pools on the conversion route do not have enough liquidity to convert the whole balance of the token.
+    InsufficientLiquidity(Address),
+    /// The value does not fit in an i128.
+    ValueOverflow,
+}
+
+// Values portfolios in a numéraire by converting every balance with quotes against the pools.
+//
+// Assets are valued at the amount of numéraire received for selling them and liabilities at the amount of numéraire
+// required to buy them back, through either a single pool or two pools via one of the intermediate tokens.
+// The best route for each token is chosen the first time the token is valued and cached until `clear_routes`.
+#[derive(Clone, Debug)]
+pub struct PortfolioValuer {
+    numeraire: Address,
+    intermediate_tokens: Vec<Address>,
+    routes: BTreeMap<Address, Vec<NodeKey>>,
+}
+
+impl PortfolioValuer {
+    pub fn new(numeraire: Address, intermediate_tokens: Vec<Address>) -> Self {
+        Self {
+            numeraire,
+            intermediate_tokens,
+            routes: BTreeMap::new(),
+        }
+    }
+
+    pub fn numeraire(&self) -> Address {
+        self.numeraire
+    }
+
+    // Returns the cached conversion route of the token, as the keys of the pools from the token to the numéraire
+    pub fn cached_route(&self, token: Address) -> Option<&[NodeKey]> {
+        self.routes.get(&token).map(Vec::as_slice)
+    }
+
+    // Forgets the cached routes, e.g. after pools were added or removed
+    pub fn clear_routes(&mut self) {
+        self.routes.clear();
+    }
+
+    pub fn value_portfolio<P: Pool>(
+        &mut self,
+        positions: &[PortfolioPosition],
+        pools: &PoolMap<P>,
+        meta: P::Meta,
+    ) -> Result<PortfolioValuation, PortfolioValuationError<P::QuoteError>> {
+        let positions = positions
+            .iter()
+            .map(|position| self.value_position(position, pools, meta))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let total_value = positions
+            .iter()
+            .try_fold(0i128, |total, position| total.checked_add(position.value))
+            .ok_or(PortfolioValuationError::ValueOverflow)?;
+
+        Ok(PortfolioValuation {
+            positions,
+            total_value,
+        })
+    }
+
+    pub fn value_position<P: Pool>(
+        &mut self,
+        position: &PortfolioPosition,
+        pools: &PoolMap<P>,
+        meta: P::Meta,
+    ) -> Result<PositionValuation, PortfolioValuationError<P::QuoteError>> {
+        let balance_values = position
+            .balances
+            .iter()
+            .map(|balance| self.value_balance(*balance, pools, meta))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let value = balance_values
+            .iter()
+            .try_fold(0i128, |total, value| total.checked_add(*value))
+            .ok_or(PortfolioValuationError::ValueOverflow)?;
+
+        Ok(PositionValuation {
+            balance_values,
+            value,
+        })
+    }
+
+    // Returns the value of a single balance in the numéraire
+    pub fn value_balance<P: Pool>(
+        &mut self,
+        balance: TokenAmount,
+        pools: &PoolMap<P>,
+        meta: P::Meta,
+    ) -> Result<i128, PortfolioValuationError<P::QuoteError>> {
+        if balance.token == self.numeraire || balance.amount == 0 {
+            return Ok(balance.amount);
+        }
+
+        let route = match self.routes.get(&balance.token) {
+            Some(route) => route.clone(),
+            None => {
+                let route = self.find_route(balance, pools, meta)?;
+                self.routes.insert(balance.token, route.clone());
+                route
+            }
+        };
+
+        let amount = convert(balance, &route, pools, meta)?;
+        let amount: i128 = amount
+            .try_into()
+            .map_err(|_| PortfolioValuationError::ValueOverflow)?;
+
+        Ok(if balance.amount < 0 { -amount } else { amount })
+    }
+
+    fn find_route<P: Pool>(
+        &self,
+        balance: TokenAmount,
+        pools: &PoolMap<P>,
+        meta: P::Meta,
+    ) -> Result<Vec<NodeKey>, PortfolioValuationError<P::QuoteError>> {
+        let token = balance.token;
+
+        let direct = pools
+            .pools_for_pair(token, self.numeraire)
+            .map(|pool| vec![*pool.get_key()]);
+        let two_hop = self
+            .intermediate_tokens
+            .iter()
+            .filter(|intermediate| **intermediate != token && **intermediate != self.numeraire)
+            .flat_map(|intermediate| {
+                pools
+                    .pools_for_pair(token, *intermediate)
+                    .flat_map(move |first| {
+                        pools
+                            .pools_for_pair(*intermediate, self.numeraire)
+                            .map(move |second| vec![*first.get_key(), *second.get_key()])
+                    })
+            });
+
+        // assets are best converted with the route giving the most numéraire, liabilities with the route costing the least
+        let mut best: Option<(Vec<NodeKey>, u128)> = None;
+        for route in direct.chain(two_hop) {
+            if let Ok(amount) = convert(balance, &route, pools, meta) {
+                let is_better = best.as_ref().is_none_or(|(_, best_amount)| {
+                    if balance.amount < 0 {
+                        amount < *best_amount
+                    } else {
+                        amount > *best_amount
+                    }
+                });
+                if is_better {
+                    best = Some((route, amount));
+                }
+            }
+        }
+
+        best.map(|(route, _)| route)
+            .ok_or(PortfolioValuationError::NoConversionRoute(token))
+    }
+}
+
+// Converts the balance along the route, returning the absolute amount of the last token received or required
+fn convert<P: Pool>(
+    balance: TokenAmount,
+    route: &[NodeKey],
+    pools: &PoolMap<P>,
+    meta: P::Meta,
+) -> Result<u128, PortfolioValuationError<P::QuoteError>> {
+    let is_liability = balance.amount < 0;
+    let mut token = balance.token;
+    let mut amount = balance.amount.unsigned_abs();
+
+    for key in route {
+        let pool = pools
+            .get(key)
+            .ok_or(PortfolioValuationError::NoConversionRoute(balance.token))?;
+
+        let specified: i128 = amount
+            .try_into()
+            .map_err(|_| PortfolioValuationError::ValueOverflow)?;
+        let specified = if is_liability { -specified } else { specified };
+
+        let quote = pool
+            .quote(QuoteParams {
+                token_amount: TokenAmount {
+                    token,
+                    amount: specified,
+                },
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: None,
+                meta,
+            })
+            .map_err(|err| PortfolioValuationError::QuoteError(balance.token, err))?;
+
+        if quote.consumed_amount != specified {
+            return Err(PortfolioValuationError::InsufficientLiquidity(
+                balance.token,
+            ));
+        }
+
+        token = if key.token0 == token {
+            key.token1
+        } else {
+            key.token0
+        };
+        amount = quote.calculated_amount;
+    }
+
+    Ok(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::uint::U256;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::types::Config;
+
+    const USD: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const ETH: Address = Address::from_raw(U256([2, 0, 0, 0]));
+    const XYZ: Address = Address::from_raw(U256([3, 0, 0, 0]));
+    const ABC: Address = Address::from_raw(U256([4, 0, 0, 0]));
+
+    fn pool(token0: Address, token1: Address, fee: u64, sqrt_ratio: U256) -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0,
+                token1,
+                config: Config::from_raw_unchecked(fee, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio,
+                liquidity: 1_000_000_000_000,
+            },
+        )
+        .unwrap()
+    }
+
+    fn pools() -> PoolMap<FullRangePool> {
+        [
+            // ETH is worth 4 USD
+            pool(USD, ETH, 0, U256::one() << 127),
+            pool(USD, ETH, 1 << 60, U256::one() << 127),
+            // XYZ is worth 1 ETH and only trades against ETH
+            pool(ETH, XYZ, 0, U256::one() << 128),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn balance(token: Address, amount: i128) -> TokenAmount {
+        TokenAmount { token, amount }
+    }
+
+    #[test]
+    fn test_value_portfolio() {
+        let pools = pools();
+        let mut valuer = PortfolioValuer::new(USD, vec![ETH]);
+
+        let valuation = valuer
+            .value_portfolio(
+                &[
+                    PortfolioPosition {
+                        balances: vec![balance(USD, 100), balance(ETH, 1_000)],
+                    },
+                    PortfolioPosition {
+                        balances: vec![balance(XYZ, 1_000), balance(ETH, -1_000)],
+                    },
+                ],
+                &pools,
+                (),
+            )
+            .unwrap();
+
+        assert_eq!(valuation.positions[0].balance_values, [100, 3999]);
+        assert_eq!(valuation.positions[0].value, 4099);
+        assert_eq!(valuation.positions[1].balance_values, [3995, -4001]);
+        assert_eq!(valuation.positions[1].value, -6);
+        assert_eq!(valuation.total_value, 4093);
+
+        // the zero fee pool is the cheapest route for ETH
+        assert_eq!(
+            valuer.cached_route(ETH),
+            Some([*pools.iter().next().unwrap().get_key()].as_slice())
+        );
+        assert_eq!(valuer.cached_route(XYZ).map(|route| route.len()), Some(2));
+    }
+
+    #[test]
+    fn test_errors() {
+        let pools = pools();
+        let mut valuer = PortfolioValuer::new(USD, vec![]);
+
+        // XYZ can only be converted via ETH
+        assert_eq!(
+            valuer.value_balance(balance(XYZ, 1_000), &pools, ()),
+            Err(PortfolioValuationError::NoConversionRoute(XYZ))
+        );
+        assert_eq!(
+            valuer.value_balance(balance(ABC, 1_000), &pools, ()),
+            Err(PortfolioValuationError::NoConversionRoute(ABC))
+        );
+
+        // buying back more ETH than the pools hold
+        assert_eq!(
+            valuer.value_balance(balance(ETH, -(1 << 100)), &pools, ()),
+            Err(PortfolioValuationError::NoConversionRoute(ETH))
+        );
+        valuer
+            .value_balance(balance(ETH, -1_000), &pools, ())
+            .unwrap();
+        assert_eq!(
+            valuer.value_balance(balance(ETH, -(1 << 100)), &pools, ()),
+            Err(PortfolioValuationError::InsufficientLiquidity(ETH))
+        );
+    }
+}
@@ -0,0 +1,588 @@
+use crate::math::muldiv::muldiv;
+use crate::math::tick::to_sqrt_ratio;
+use crate::math::uint::U256;
+use crate::quoting::constants::ChainConfig;
+use crate::quoting::oracle_pool::OraclePool;
+use crate::quoting::pool_map::PoolMap;
+use crate::quoting::portfolio::{PortfolioValuationError, PortfolioValuer};
+use crate::quoting::types::{Address, Pool, TokenAmount};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+const TWO_POW_128: U256 = U256([0, 0, 1, 0]);
+
+// A snapshot of the tick accumulator of an oracle pool, as written by the oracle extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OracleObservation {
+    pub timestamp: u64,
+    pub tick_cumulative: i64,
+}
+
+// Returns the time weighted average tick between two observations, rounded towards negative infinity, or None if the
+// later observation is not after the earlier one
+pub fn average_tick(earlier: OracleObservation, later: OracleObservation) -> Option<i32> {
+    let elapsed = later.timestamp.checked_sub(earlier.timestamp)?;
+    if elapsed == 0 {
+        return None;
+    }
+    let delta = later.tick_cumulative as i128 - earlier.tick_cumulative as i128;
+    delta.div_euclid(elapsed as i128).try_into().ok()
+}
+
+// Returns the tick of the price of the base token in the quote token from the ticks of their oracle pools, which price
+// each token in the native token. The tick of the native token itself is zero.
+pub fn cross_tick(base_tick: i32, quote_tick: i32) -> i32 {
+    quote_tick - base_tick
+}
+
+// Limits on the estimates of a price that are combined into a median
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MedianPriceConfig {
+    // Estimates further than this from the median of all estimates are rejected as outliers
+    pub max_deviation_ticks: u32,
+    // The least number of estimates that must remain after rejecting outliers
+    pub min_estimates: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MedianPrice {
+    pub tick: i32,
+    pub sqrt_ratio: U256,
+    // The indices of the estimates that were rejected as outliers, in ascending order
+    pub rejected: Vec<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum MedianPriceError {
+    /// Fewer estimates than the minimum remain after rejecting outliers.
+    NotEnoughEstimates { required: usize, remaining: usize },
+    /// The median tick is outside of the range [MIN_TICK, MAX_TICK].
+    InvalidTick(i32),
+}
+
+// Returns the median of the sorted ticks, rounding the average of the middle ticks towards negative infinity
+fn median_of_sorted(ticks: &[i32]) -> i32 {
+    let middle = ticks.len() / 2;
+    if ticks.len() % 2 == 1 {
+        ticks[middle]
+    } else {
+        (ticks[middle - 1] as i64 + ticks[middle] as i64).div_euclid(2) as i32
+    }
+}
+
+/// Combines several estimates of the same price as ticks, e.g. the TWAPs of a token through different oracle pools of
+/// tokens that are worth the same, into their median.
+///
+/// Estimates that deviate from the median of all estimates by more than the configured number of ticks are rejected,
+/// and the median of the remaining estimates is returned if there are enough of them.
+pub fn median_price(
+    estimates: &[i32],
+    config: &MedianPriceConfig,
+) -> Result<MedianPrice, MedianPriceError> {
+    let not_enough = |remaining| MedianPriceError::NotEnoughEstimates {
+        required: config.min_estimates.max(1),
+        remaining,
+    };
+
+    let mut sorted = estimates.to_vec();
+    sorted.sort_unstable();
+    if sorted.is_empty() {
+        return Err(not_enough(0));
+    }
+    let median = median_of_sorted(&sorted);
+
+    let (accepted, rejected): (Vec<usize>, Vec<usize>) = (0..estimates.len())
+        .partition(|index| estimates[*index].abs_diff(median) <= config.max_deviation_ticks);
+    if accepted.len() < config.min_estimates.max(1) {
+        return Err(not_enough(accepted.len()));
+    }
+
+    let mut remaining: Vec<i32> = accepted.iter().map(|index| estimates[*index]).collect();
+    remaining.sort_unstable();
+    let tick = median_of_sorted(&remaining);
+
+    Ok(MedianPrice {
+        tick,
+        sqrt_ratio: to_sqrt_ratio(tick).ok_or(MedianPriceError::InvalidTick(tick))?,
+        rejected,
+    })
+}
+
+// A way of pricing a token in the numéraire
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PriceSource {
+    /// The time weighted average prices of the oracle pools of the token and the numéraire.
+    OracleTwap,
+    /// The current prices of the oracle pools of the token and the numéraire.
+    OracleSpot,
+    /// Quotes for selling the token, or buying it back for liabilities, against the spot pools.
+    SpotQuote,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PricedAmount {
+    pub value: i128,
+    // The source that priced the amount, or None if no conversion was needed
+    pub source: Option<PriceSource>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PricingError<E> {
+    /// None of the configured sources has a price for the token.
+    NoPrice(Address),
+    /// Pricing the token with spot quotes failed and no later source has a price for it.
+    SpotQuote(PortfolioValuationError<E>),
+    /// The value does not fit in an i128.
+    ValueOverflow,
+}
+
+// The prices of a token in the native token, which every oracle pool is paired with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct OraclePrices {
+    sqrt_ratio: U256,
+    twap_sqrt_ratio: Option<U256>,
+}
+
+// Converts token amounts to a numéraire, trying each of the configured price sources in order.
+//
+// Oracle pools price their token in the native token, so oracle prices convert through the native token and need
+// a price for both the token and the numéraire, unless either is the native token. TWAPs are computed from the
+// observations of the oracle extension and registered with the spot price of each oracle pool.
+#[derive(Clone, Debug)]
+pub struct NumerairePricer {
+    sources: Vec<PriceSource>,
+    oracles: BTreeMap<Address, OraclePrices>,
+    valuer: PortfolioValuer,
+    chain: ChainConfig,
+}
+
+impl NumerairePricer {
+    // Creates a pricer that tries the sources in the given order, and quotes through the intermediate tokens when
+    // there is no spot pool between a token and the numéraire
+    pub fn new(
+        numeraire: Address,
+        sources: Vec<PriceSource>,
+        intermediate_tokens: Vec<Address>,
+    ) -> Self {
+        Self::new_with_chain_config(
+            numeraire,
+            sources,
+            intermediate_tokens,
+            ChainConfig::default(),
+        )
+    }
+
+    // Creates a pricer for a chain whose oracle pools are paired with the native token of the chain config
+    pub fn new_with_chain_config(
+        numeraire: Address,
+        sources: Vec<PriceSource>,
+        intermediate_tokens: Vec<Address>,
+        chain: ChainConfig,
+    ) -> Self {
+        Self {
+            sources,
+            oracles: BTreeMap::new(),
+            valuer: PortfolioValuer::new(numeraire, intermediate_tokens),
+            chain,
+        }
+    }
+
+    pub fn numeraire(&self) -> Address {
+        self.valuer.numeraire()
+    }
+
+    pub fn sources(&self) -> &[PriceSource] {
+        &self.sources
+    }
+
+    // Registers the current price of an oracle pool and optionally its average tick over the TWAP window, replacing
+    // the previous prices of its token
+    pub fn set_oracle(&mut self, pool: &OraclePool, twap_tick: Option<i32>) {
+        self.oracles.insert(
+            pool.get_key().token1,
+            OraclePrices {
+                sqrt_ratio: pool.get_state().full_range_pool_state.sqrt_ratio,
+                twap_sqrt_ratio: twap_tick.and_then(to_sqrt_ratio),
+            },
+        );
+    }
+
+    // Forgets the oracle prices and the cached spot quote routes, e.g. at a new block
+    pub fn clear(&mut self) {
+        self.oracles.clear();
+        self.valuer.clear_routes();
+    }
+
+    // Returns the sqrt ratio of the oracle pool of the token, i.e. of the price of the native token in the token
+    fn oracle_sqrt_ratio(&self, token: Address, source: PriceSource) -> Option<U256> {
+        if token == self.chain.native_token {
+            return Some(TWO_POW_128);
+        }
+        let prices = self.oracles.get(&token)?;
+        match source {
+            PriceSource::OracleTwap => prices.twap_sqrt_ratio,
+            PriceSource::OracleSpot => Some(prices.sqrt_ratio),
+            PriceSource::SpotQuote => None,
+        }
+    }
+
+    // Returns the value of the amount with the oracle source, or None if the source has no price for it
+    fn oracle_value<E>(
+        &self,
+        amount: TokenAmount,
+        source: PriceSource,
+    ) -> Option<Result<i128, PricingError<E>>> {
+        let token_sqrt_ratio = self.oracle_sqrt_ratio(amount.token, source)?;
+        let numeraire_sqrt_ratio = self.oracle_sqrt_ratio(self.numeraire(), source)?;
+
+        // amount * (numeraire per native) / (token per native), rounded down in absolute value
+        let value = muldiv(
+            U256::from(amount.amount.unsigned_abs()),
+            numeraire_sqrt_ratio,
+            token_sqrt_ratio,
+            false,
+        )
+        .and_then(|value| muldiv(value, numeraire_sqrt_ratio, token_sqrt_ratio, false))
+        .ok()
+        .filter(|value| *value <= U256::from(i128::MAX as u128))
+        .map(|value| value.low_u128() as i128);
+
+        Some(
+            value
+                .map(|value| if amount.amount < 0 { -value } else { value })
+                .ok_or(PricingError::ValueOverflow),
+        )
+    }
+
+    /// Converts the amount of a token to the numéraire with the first source that has a price for it.
+    ///
+    /// Negative amounts are liabilities and convert to negative values. Oracle sources are skipped if either the
+    /// token or the numéraire has no registered price for them, and spot quotes are skipped if they fail.
+    pub fn convert<P: Pool>(
+        &mut self,
+        amount: TokenAmount,
+        spot_pools: &PoolMap<P>,
+        meta: P::Meta,
+    ) -> Result<PricedAmount, PricingError<P::QuoteError>> {
+        if amount.token == self.numeraire() || amount.amount == 0 {
+            return Ok(PricedAmount {
+                value: amount.amount,
+                source: None,
+            });
+        }
+
+        let mut error = PricingError::NoPrice(amount.token);
+        for source in self.sources.clone() {
+            let value = match source {
+                PriceSource::OracleTwap | PriceSource::OracleSpot => {
+                    match self.oracle_value(amount, source) {
+                        Some(value) => value,
+                        None => continue,
+                    }
+                }
+                PriceSource::SpotQuote => self
+                    .valuer
+                    .value_balance(amount, spot_pools, meta)
+                    .map_err(PricingError::SpotQuote),
+            };
+
+            match value {
+                Ok(value) => {
+                    return Ok(PricedAmount {
+                        value,
+                        source: Some(source),
+                    })
+                }
+                Err(err) => error = err,
+            }
+        }
+
+        Err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::MAX_TICK;
+    use crate::quoting::constants::NATIVE_TOKEN_ADDRESS;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::types::{Config, NodeKey};
+    use alloc::vec;
+
+    const USD: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const XYZ: Address = Address::from_raw(U256([2, 0, 0, 0]));
+    // tick of a price of about 4
+    const TICK_4: i32 = 1_386_295;
+    // tick of a price of about 2000
+    const TICK_2000: i32 = 7_600_906;
+
+    fn oracle(token: Address, tick: i32) -> OraclePool {
+        OraclePool::new(
+            token,
+            U256::from(9),
+            to_sqrt_ratio(tick).unwrap(),
+            1_000_000_000_000_000_000,
+            0,
+        )
+        .unwrap()
+    }
+
+    fn spot_pools() -> PoolMap<FullRangePool> {
+        let mut pools = PoolMap::new();
+        pools.insert(
+            FullRangePool::new(
+                NodeKey {
+                    token0: USD,
+                    token1: XYZ,
+                    config: Config::from_raw_unchecked(0, 0, U256::zero()),
+                },
+                FullRangePoolState {
+                    // 3 XYZ per USD
+                    sqrt_ratio: U256::from_dec_str("589386348426860448620552798897367416832")
+                        .unwrap(),
+                    liquidity: 1_000_000_000_000_000_000,
+                },
+            )
+            .unwrap(),
+        );
+        pools
+    }
+
+    fn assert_close(value: i128, expected: f64) {
+        assert!(
+            ((value as f64) / expected - 1f64).abs() < 1e-3,
+            "{} != {}",
+            value,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_average_tick() {
+        let earlier = OracleObservation {
+            timestamp: 100,
+            tick_cumulative: 1_000,
+        };
+        let later = |timestamp, tick_cumulative| OracleObservation {
+            timestamp,
+            tick_cumulative,
+        };
+        assert_eq!(average_tick(earlier, later(110, 1_105)), Some(10));
+        assert_eq!(average_tick(earlier, later(110, 995)), Some(-1));
+        assert_eq!(average_tick(earlier, later(100, 1_105)), None);
+        assert_eq!(average_tick(earlier, later(90, 1_105)), None);
+    }
+
+    #[test]
+    fn test_median_price_rejects_outliers() {
+        let config = MedianPriceConfig {
+            max_deviation_ticks: 100,
+            min_estimates: 2,
+        };
+        // the price of XYZ in three stablecoins, one of which has depegged
+        let estimates = [
+            cross_tick(TICK_4, TICK_2000),
+            cross_tick(TICK_4, TICK_2000 + 20),
+            cross_tick(TICK_4, TICK_2000 - 5_000),
+            cross_tick(TICK_4, TICK_2000 + 50),
+        ];
+
+        let price = median_price(&estimates, &config).unwrap();
+        assert_eq!(price.rejected, [2]);
+        assert_eq!(price.tick, TICK_2000 - TICK_4 + 20);
+        assert_eq!(price.sqrt_ratio, to_sqrt_ratio(price.tick).unwrap());
+
+        // an even number of estimates averages the middle two
+        let price = median_price(&estimates[..2], &config).unwrap();
+        assert_eq!(price.tick, TICK_2000 - TICK_4 + 10);
+    }
+
+    #[test]
+    fn test_median_price_needs_enough_estimates() {
+        let config = MedianPriceConfig {
+            max_deviation_ticks: 10,
+            min_estimates: 2,
+        };
+        assert_eq!(
+            median_price(&[0, 1_000, 2_000], &config),
+            Err(MedianPriceError::NotEnoughEstimates {
+                required: 2,
+                remaining: 1
+            })
+        );
+        assert_eq!(
+            median_price(&[], &config),
+            Err(MedianPriceError::NotEnoughEstimates {
+                required: 2,
+                remaining: 0
+            })
+        );
+        assert_eq!(
+            median_price(
+                &[MAX_TICK + 5],
+                &MedianPriceConfig {
+                    max_deviation_ticks: 0,
+                    min_estimates: 1
+                }
+            ),
+            Err(MedianPriceError::InvalidTick(MAX_TICK + 5))
+        );
+    }
+
+    #[test]
+    fn test_converts_through_native_token() {
+        let mut pricer = NumerairePricer::new(USD, vec![PriceSource::OracleSpot], vec![]);
+        // 2000 USD and 4 XYZ per native token, so 1 XYZ is 500 USD
+        pricer.set_oracle(&oracle(USD, TICK_2000), None);
+        pricer.set_oracle(&oracle(XYZ, TICK_4), None);
+
+        let pools = spot_pools();
+        let priced = pricer
+            .convert(
+                TokenAmount {
+                    token: XYZ,
+                    amount: 1_000_000,
+                },
+                &pools,
+                (),
+            )
+            .unwrap();
+        assert_eq!(priced.source, Some(PriceSource::OracleSpot));
+        assert_close(priced.value, 500_000_000f64);
+
+        let native = pricer
+            .convert(
+                TokenAmount {
+                    token: NATIVE_TOKEN_ADDRESS,
+                    amount: -1_000,
+                },
+                &pools,
+                (),
+            )
+            .unwrap();
+        assert_close(-native.value, 2_000_000f64);
+        assert!(native.value < 0);
+
+        let numeraire = pricer
+            .convert(
+                TokenAmount {
+                    token: USD,
+                    amount: 5,
+                },
+                &pools,
+                (),
+            )
+            .unwrap();
+        assert_eq!(
+            numeraire,
+            PricedAmount {
+                value: 5,
+                source: None
+            }
+        );
+    }
+
+    #[test]
+    fn test_converts_through_configured_native_token() {
+        let wrapped_native = Address::from_raw(U256::from(10));
+        let token = Address::from_raw(U256::from(11));
+        let mut pricer = NumerairePricer::new_with_chain_config(
+            token,
+            vec![PriceSource::OracleSpot],
+            vec![],
+            ChainConfig {
+                native_token: wrapped_native,
+            },
+        );
+        pricer.set_oracle(
+            &OraclePool::new_with_token0(
+                wrapped_native,
+                token,
+                U256::from(9),
+                to_sqrt_ratio(TICK_4).unwrap(),
+                1_000_000_000_000_000_000,
+                0,
+            )
+            .unwrap(),
+            None,
+        );
+
+        let priced = pricer
+            .convert(
+                TokenAmount {
+                    token: wrapped_native,
+                    amount: 1_000_000,
+                },
+                &spot_pools(),
+                (),
+            )
+            .unwrap();
+        assert_eq!(priced.source, Some(PriceSource::OracleSpot));
+        assert_close(priced.value, 4_000_000f64);
+    }
+
+    #[test]
+    fn test_falls_back_in_order() {
+        let mut pricer = NumerairePricer::new(
+            USD,
+            vec![
+                PriceSource::OracleTwap,
+                PriceSource::OracleSpot,
+                PriceSource::SpotQuote,
+            ],
+            vec![],
+        );
+        let pools = spot_pools();
+        let amount = TokenAmount {
+            token: XYZ,
+            amount: 3_000_000,
+        };
+
+        // without oracles the spot pool prices the token at about 1/3 USD
+        let priced = pricer.convert(amount, &pools, ()).unwrap();
+        assert_eq!(priced.source, Some(PriceSource::SpotQuote));
+        assert_close(priced.value, 1_000_000f64);
+
+        // the TWAP of the numéraire is missing, so the oracle spot prices are used
+        pricer.set_oracle(&oracle(USD, TICK_2000), None);
+        pricer.set_oracle(&oracle(XYZ, TICK_4), Some(TICK_4));
+        let priced = pricer.convert(amount, &pools, ()).unwrap();
+        assert_eq!(priced.source, Some(PriceSource::OracleSpot));
+
+        // the TWAP is preferred over the current price
+        pricer.set_oracle(&oracle(USD, TICK_4), Some(TICK_2000));
+        let priced = pricer.convert(amount, &pools, ()).unwrap();
+        assert_eq!(priced.source, Some(PriceSource::OracleTwap));
+        assert_close(priced.value, 1_500_000_000f64);
+    }
+
+    #[test]
+    fn test_no_price() {
+        let mut pricer = NumerairePricer::new(USD, vec![PriceSource::OracleTwap], vec![]);
+        let pools = spot_pools();
+        let amount = TokenAmount {
+            token: XYZ,
+            amount: 1,
+        };
+        assert_eq!(
+            pricer.convert(amount, &pools, ()),
+            Err(PricingError::NoPrice(XYZ))
+        );
+
+        let mut pricer = NumerairePricer::new(USD, vec![PriceSource::SpotQuote], vec![]);
+        let unknown = TokenAmount {
+            token: Address::from_raw(U256::from(7)),
+            amount: 1,
+        };
+        assert_eq!(
+            pricer.convert(unknown, &pools, ()),
+            Err(PricingError::SpotQuote(
+                PortfolioValuationError::NoConversionRoute(Address::from_raw(U256::from(7)))
+            ))
+        );
+    }
+}
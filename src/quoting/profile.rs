@@ -0,0 +1,83 @@
+// A monotonic source of time for profiling quotes, in any unit, e.g. the nanoseconds elapsed since an Instant
+pub trait Timer {
+    fn now(&self) -> u64;
+}
+
+impl<F: Fn() -> u64> Timer for F {
+    fn now(&self) -> u64 {
+        self()
+    }
+}
+
+// The phases of a quote that are timed separately
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuotePhase {
+    // Finding the next initialized tick and its sqrt ratio
+    TickSearch,
+    // Computing each swap step
+    StepMath,
+    // Crossing ticks and computing the resources of the quote
+    ResourceAccounting,
+}
+
+// The time spent in each phase of a quote, in the unit of the timer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuoteProfile {
+    pub tick_search: u64,
+    pub step_math: u64,
+    pub resource_accounting: u64,
+    // The number of swap steps that were computed
+    pub steps: u32,
+}
+
+impl QuoteProfile {
+    pub fn total(&self) -> u64 {
+        self.tick_search + self.step_math + self.resource_accounting
+    }
+}
+
+// Receives the phases of a quote as they complete, compiling away entirely in the unprofiled quote
+pub(crate) trait QuoteProfiler {
+    type Mark: Copy;
+
+    fn start(&self) -> Self::Mark;
+
+    fn record(&mut self, phase: QuotePhase, start: Self::Mark);
+}
+
+pub(crate) struct NoProfiler;
+
+impl QuoteProfiler for NoProfiler {
+    type Mark = ();
+
+    #[inline(always)]
+    fn start(&self) {}
+
+    #[inline(always)]
+    fn record(&mut self, _phase: QuotePhase, _start: ()) {}
+}
+
+pub(crate) struct TimingProfiler<'a, T: ?Sized> {
+    pub timer: &'a T,
+    pub profile: QuoteProfile,
+}
+
+impl<T: Timer + ?Sized> QuoteProfiler for TimingProfiler<'_, T> {
+    type Mark = u64;
+
+    fn start(&self) -> u64 {
+        self.timer.now()
+    }
+
+    fn record(&mut self, phase: QuotePhase, start: u64) {
+        let elapsed = self.timer.now().saturating_sub(start);
+        match phase {
+            QuotePhase::TickSearch => self.profile.tick_search += elapsed,
+            QuotePhase::StepMath => {
+                self.profile.step_math += elapsed;
+                self.profile.steps += 1;
+            }
+            QuotePhase::ResourceAccounting => self.profile.resource_accounting += elapsed,
+        }
+    }
+}
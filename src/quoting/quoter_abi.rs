@@ -0,0 +1,237 @@
+use crate::math::uint::U256;
+use crate::quoting::signed_amount::SignedAmount;
+use crate::quoting::types::{NodeKey, Quote, TokenAmount};
+use alloc::vec::Vec;
+
+// The length of the ABI encoded `(int128 delta0, int128 delta1)` tuple
+pub const QUOTER_RETURN_LENGTH: usize = 64;
+
+// The balance changes of the pool that the quoter contract returns, positive for the amounts paid to the pool
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QuoterReturn {
+    pub delta0: i128,
+    pub delta1: i128,
+}
+
+// The amounts of a quote relative to its specified token, in the layout of `Quote`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuotedAmounts {
+    pub consumed_amount: i128,
+    pub calculated_amount: u128,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum QuoterAbiError {
+    /// The specified token is neither token0 nor token1 of the pool.
+    TokenNotInPool,
+    /// The calculated amount does not fit in an int128.
+    AmountOverflow,
+    /// The return data is not exactly two words long.
+    InvalidLength(usize),
+    /// A word is not the sign extension of an int128.
+    InvalidWord,
+    /// The delta of the calculated token has the same sign as the delta of the specified token.
+    InvalidSign,
+}
+
+impl QuoterReturn {
+    /// Returns the deltas the quoter contract returns for a swap of the token amount, so that quotes computed by the
+    /// SDK can be passed on wherever quotes from the contract are expected.
+    pub fn from_quote<R, S>(
+        key: &NodeKey,
+        token_amount: TokenAmount,
+        quote: &Quote<R, S>,
+    ) -> Result<Self, QuoterAbiError> {
+        let is_token1 = is_token1(key, token_amount)?;
+        let calculated =
+            i128::try_from(quote.calculated_amount).map_err(|_| QuoterAbiError::AmountOverflow)?;
+        // the pool pays out the calculated amount of an exact input swap and receives it for an exact output swap
+        let calculated = if token_amount.amount < 0 {
+            calculated
+        } else {
+            -calculated
+        };
+
+        Ok(if is_token1 {
+            Self {
+                delta0: calculated,
+                delta1: quote.consumed_amount,
+            }
+        } else {
+            Self {
+                delta0: quote.consumed_amount,
+                delta1: calculated,
+            }
+        })
+    }
+
+    // Returns the amounts of the quote relative to the specified token of the swap the deltas were returned for
+    pub fn quoted_amounts(
+        self,
+        key: &NodeKey,
+        token_amount: TokenAmount,
+    ) -> Result<QuotedAmounts, QuoterAbiError> {
+        let (specified, calculated) = if is_token1(key, token_amount)? {
+            (self.delta1, self.delta0)
+        } else {
+            (self.delta0, self.delta1)
+        };
+        // a non-zero calculated delta must flow in the opposite direction of the specified delta
+        if (token_amount.amount < 0 && calculated < 0)
+            || (token_amount.amount >= 0 && calculated > 0)
+        {
+            return Err(QuoterAbiError::InvalidSign);
+        }
+
+        Ok(QuotedAmounts {
+            consumed_amount: specified,
+            calculated_amount: calculated.unsigned_abs(),
+        })
+    }
+
+    // Encodes the deltas as the ABI encoded tuple the quoter contract returns
+    pub fn encode(self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(QUOTER_RETURN_LENGTH);
+        bytes.extend_from_slice(&SignedAmount::from(self.delta0).to_word().to_big_endian());
+        bytes.extend_from_slice(&SignedAmount::from(self.delta1).to_word().to_big_endian());
+        bytes
+    }
+
+    // Decodes the return data of the quoter contract
+    pub fn decode(bytes: &[u8]) -> Result<Self, QuoterAbiError> {
+        if bytes.len() != QUOTER_RETURN_LENGTH {
+            return Err(QuoterAbiError::InvalidLength(bytes.len()));
+        }
+
+        let word = |bytes: &[u8]| {
+            SignedAmount::from_word(U256::from_big_endian(bytes))
+                .ok()
+                .and_then(|amount| i128::try_from(amount).ok())
+                .ok_or(QuoterAbiError::InvalidWord)
+        };
+        Ok(Self {
+            delta0: word(&bytes[..32])?,
+            delta1: word(&bytes[32..])?,
+        })
+    }
+}
+
+fn is_token1(key: &NodeKey, token_amount: TokenAmount) -> Result<bool, QuoterAbiError> {
+    if token_amount.token == key.token0 {
+        Ok(false)
+    } else if token_amount.token == key.token1 {
+        Ok(true)
+    } else {
+        Err(QuoterAbiError::TokenNotInPool)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::types::{Address, Config, Pool, QuoteParams};
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn pool() -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0: TOKEN0,
+                token1: TOKEN1,
+                config: Config::from_raw_unchecked(1 << 60, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 1_000_000_000,
+            },
+        )
+        .unwrap()
+    }
+
+    fn round_trip(token: Address, amount: i128) {
+        let pool = pool();
+        let token_amount = TokenAmount { token, amount };
+        let quote = pool
+            .quote(QuoteParams {
+                token_amount,
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: None,
+                meta: (),
+            })
+            .unwrap();
+
+        let returned = QuoterReturn::from_quote(pool.get_key(), token_amount, &quote).unwrap();
+        assert_eq!(QuoterReturn::decode(&returned.encode()), Ok(returned));
+        assert_eq!(
+            returned.quoted_amounts(pool.get_key(), token_amount),
+            Ok(QuotedAmounts {
+                consumed_amount: quote.consumed_amount,
+                calculated_amount: quote.calculated_amount,
+            })
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(TOKEN0, 1_000);
+        round_trip(TOKEN1, 1_000);
+        round_trip(TOKEN0, -1_000);
+        round_trip(TOKEN1, -1_000);
+    }
+
+    #[test]
+    fn test_encoding() {
+        let returned = QuoterReturn {
+            delta0: 1_000,
+            delta1: -990,
+        };
+        let bytes = returned.encode();
+
+        assert_eq!(U256::from_big_endian(&bytes[..32]), U256::from(1_000));
+        assert_eq!(
+            U256::from_big_endian(&bytes[32..]),
+            U256::MAX - U256::from(989)
+        );
+        assert_eq!(
+            QuoterReturn::decode(&bytes[1..]),
+            Err(QuoterAbiError::InvalidLength(63))
+        );
+
+        let mut bytes = bytes;
+        bytes[16] = 0x80;
+        assert_eq!(
+            QuoterReturn::decode(&bytes),
+            Err(QuoterAbiError::InvalidWord)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let returned = QuoterReturn {
+            delta0: 1_000,
+            delta1: 990,
+        };
+        let token_amount = TokenAmount {
+            token: TOKEN0,
+            amount: 1_000,
+        };
+
+        assert_eq!(
+            returned.quoted_amounts(pool().get_key(), token_amount),
+            Err(QuoterAbiError::InvalidSign)
+        );
+        assert_eq!(
+            returned.quoted_amounts(
+                pool().get_key(),
+                TokenAmount {
+                    token: Address::from_raw(U256::from(3)),
+                    amount: 1_000,
+                }
+            ),
+            Err(QuoterAbiError::TokenNotInPool)
+        );
+    }
+}
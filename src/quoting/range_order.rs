@@ -0,0 +1,311 @@
+use crate::math::delta::AmountDeltaError;
+use crate::math::liquidity::{max_liquidity, position_amounts, LiquidityError};
+use crate::math::muldiv::muldiv;
+use crate::math::tick::{sqrt_ratio_to_tick, to_sqrt_ratio, MAX_TICK, MAX_TICK_SPACING, MIN_TICK};
+use crate::math::uint::U256;
+use crate::quoting::bps::Bps;
+
+// The side of an order, with prices expressed in token1 per token0
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OrderSide {
+    /// Buys token0 with token1, i.e. sells token1.
+    Bid,
+    /// Sells token0 for token1.
+    Ask,
+}
+
+// A position one tick spacing wide that is entirely in the sold token and is converted into the other token when the
+// price crosses it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RangeOrder {
+    pub side: OrderSide,
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub liquidity: u128,
+    // The amount of the sold token to deposit, which is at most the requested size
+    pub amount: u128,
+    // The amount of the other token that is withdrawn after the price crossed the whole range
+    pub proceeds: u128,
+}
+
+// How much of a range order is executed when the price is at some point
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RangeOrderFill {
+    // The amount of the sold token that was converted
+    pub sold: u128,
+    // The amount of the other token that the position holds
+    pub received: u128,
+    // The share of the deposited amount that was converted, rounded down
+    pub filled: Bps,
+    // The average price of the converted amount in token1 per token0 as a 128.128 fixed point number, or None if
+    // nothing was converted
+    pub average_price_x128: Option<U256>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RangeOrderError {
+    /// Tick spacing must be greater than zero and less than or equal to max tick spacing.
+    InvalidTickSpacing,
+    /// There is no range of one tick spacing on the executing side of the price.
+    PriceOutOfRange,
+    /// The size is too small to back any liquidity.
+    ZeroLiquidity,
+    /// Computing the liquidity of the order failed.
+    Liquidity(LiquidityError),
+    /// Computing the deposited amount or the proceeds failed.
+    AmountDelta(AmountDeltaError),
+}
+
+// Returns the bounds of the range of one tick spacing that executes at the given price or better for the side
+fn order_bounds(
+    side: OrderSide,
+    sqrt_ratio: U256,
+    tick_spacing: u32,
+) -> Result<(i32, i32), RangeOrderError> {
+    if tick_spacing == 0 || tick_spacing > MAX_TICK_SPACING {
+        return Err(RangeOrderError::InvalidTickSpacing);
+    }
+
+    let spacing = tick_spacing as i64;
+    let tick = sqrt_ratio_to_tick(sqrt_ratio) as i64;
+
+    let tick_lower = match side {
+        OrderSide::Ask => {
+            // the lowest aligned tick at or above the price
+            let tick = if to_sqrt_ratio(tick as i32).unwrap() < sqrt_ratio {
+                tick + 1
+            } else {
+                tick
+            };
+            -(-tick).div_euclid(spacing) * spacing
+        }
+        // the highest aligned tick at or below the price is the upper bound
+        OrderSide::Bid => tick.div_euclid(spacing) * spacing - spacing,
+    };
+    let tick_upper = tick_lower + spacing;
+
+    if tick_lower < MIN_TICK as i64 || tick_upper > MAX_TICK as i64 {
+        return Err(RangeOrderError::PriceOutOfRange);
+    }
+
+    Ok((tick_lower as i32, tick_upper as i32))
+}
+
+/// Converts an order to sell `amount` of a token at `sqrt_ratio` or better into the position to place.
+///
+/// Asks are placed in the first range above the price and bids in the first range below it, aligned to the tick
+/// spacing, so the whole order executes at the requested price or better. The position only consists of the sold
+/// token while the pool price is on the other side of the range, so the order has to be placed before the price
+/// reaches it.
+///
+/// This only covers the range math. Extensions that manage orders on top of pools, e.g. a limit order extension,
+/// can impose additional rules on the ticks that are not checked here.
+pub fn place_range_order(
+    side: OrderSide,
+    sqrt_ratio: U256,
+    amount: u128,
+    tick_spacing: u32,
+) -> Result<RangeOrder, RangeOrderError> {
+    let (tick_lower, tick_upper) = order_bounds(side, sqrt_ratio, tick_spacing)?;
+    // the bounds are within [MIN_TICK, MAX_TICK]
+    let (sqrt_ratio_lower, sqrt_ratio_upper) = (
+        to_sqrt_ratio(tick_lower).unwrap(),
+        to_sqrt_ratio(tick_upper).unwrap(),
+    );
+
+    // the pool price before and after the order executes
+    let (before, after, amount0, amount1) = match side {
+        OrderSide::Ask => (sqrt_ratio_lower, sqrt_ratio_upper, amount, 0),
+        OrderSide::Bid => (sqrt_ratio_upper, sqrt_ratio_lower, 0, amount),
+    };
+
+    let liquidity = max_liquidity(before, sqrt_ratio_lower, sqrt_ratio_upper, amount0, amount1)
+        .map_err(RangeOrderError::Liquidity)?;
+    if liquidity == 0 {
+        return Err(RangeOrderError::ZeroLiquidity);
+    }
+
+    let deposited = position_amounts(before, sqrt_ratio_lower, sqrt_ratio_upper, liquidity, true)
+        .map_err(RangeOrderError::AmountDelta)?;
+    let withdrawn = position_amounts(after, sqrt_ratio_lower, sqrt_ratio_upper, liquidity, false)
+        .map_err(RangeOrderError::AmountDelta)?;
+
+    let (amount, proceeds) = match side {
+        OrderSide::Ask => (deposited.0, withdrawn.1),
+        OrderSide::Bid => (deposited.1, withdrawn.0),
+    };
+
+    Ok(RangeOrder {
+        side,
+        tick_lower,
+        tick_upper,
+        liquidity,
+        amount,
+        proceeds,
+    })
+}
+
+impl RangeOrder {
+    /// Estimates how much of the order is executed if the pool price ends up at `sqrt_ratio`.
+    ///
+    /// The fill only depends on where the price ends up and not on the path it takes, because the position converts
+    /// back into the sold token when the price returns through the range. Fees earned by the position are not
+    /// included. The remaining amount is rounded up and the received amount down, so the fill is never overstated.
+    pub fn estimate_fill(&self, sqrt_ratio: U256) -> Result<RangeOrderFill, RangeOrderError> {
+        let (sqrt_ratio_lower, sqrt_ratio_upper) = (
+            to_sqrt_ratio(self.tick_lower).ok_or(RangeOrderError::PriceOutOfRange)?,
+            to_sqrt_ratio(self.tick_upper).ok_or(RangeOrderError::PriceOutOfRange)?,
+        );
+        let amounts = |round_up| {
+            position_amounts(
+                sqrt_ratio,
+                sqrt_ratio_lower,
+                sqrt_ratio_upper,
+                self.liquidity,
+                round_up,
+            )
+            .map_err(RangeOrderError::AmountDelta)
+        };
+        let (remaining, received) = match self.side {
+            OrderSide::Ask => (amounts(true)?.0, amounts(false)?.1),
+            OrderSide::Bid => (amounts(true)?.1, amounts(false)?.0),
+        };
+        let sold = self.amount.saturating_sub(remaining);
+
+        let average_price_x128 = if sold == 0 || received == 0 {
+            None
+        } else {
+            let (amount0, amount1) = match self.side {
+                OrderSide::Ask => (sold, received),
+                OrderSide::Bid => (received, sold),
+            };
+            muldiv(
+                U256::from(amount1),
+                U256::one() << 128,
+                U256::from(amount0),
+                false,
+            )
+            .ok()
+        };
+
+        Ok(RangeOrderFill {
+            sold,
+            received,
+            filled: Bps::ratio(U256::from(sold), U256::from(self.amount), false)
+                .unwrap_or(Bps::ZERO),
+            average_price_x128,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ask_is_placed_above_price() {
+        let order = place_range_order(
+            OrderSide::Ask,
+            to_sqrt_ratio(150).unwrap() + 1,
+            1_000_000_000,
+            100,
+        )
+        .unwrap();
+        assert_eq!((order.tick_lower, order.tick_upper), (200, 300));
+        assert!(order.amount <= 1_000_000_000 && order.amount > 999_999_000);
+        // executes between 1.000001^200 and 1.000001^300, i.e. above the requested 1.000001^150
+        assert!(order.proceeds > 1_000_200_000 && order.proceeds < 1_000_300_000);
+
+        // an aligned price is a valid lower bound
+        let order = place_range_order(OrderSide::Ask, to_sqrt_ratio(200).unwrap(), 1, 100);
+        assert_eq!(
+            order.map(|order| (order.tick_lower, order.tick_upper)),
+            Ok((200, 300))
+        );
+    }
+
+    #[test]
+    fn test_bid_is_placed_below_price() {
+        let order = place_range_order(
+            OrderSide::Bid,
+            to_sqrt_ratio(-150).unwrap(),
+            1_000_000_000,
+            100,
+        )
+        .unwrap();
+        assert_eq!((order.tick_lower, order.tick_upper), (-300, -200));
+        assert!(order.amount <= 1_000_000_000);
+        // buys token0 between 1.000001^-300 and 1.000001^-200
+        assert!(order.proceeds > 1_000_200_000 && order.proceeds < 1_000_300_000);
+
+        let order = place_range_order(OrderSide::Bid, to_sqrt_ratio(200).unwrap(), 1_000, 100);
+        assert_eq!(
+            order.map(|order| (order.tick_lower, order.tick_upper)),
+            Ok((100, 200))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let price = U256::one() << 128;
+        assert_eq!(
+            place_range_order(OrderSide::Ask, price, 1_000, 0),
+            Err(RangeOrderError::InvalidTickSpacing)
+        );
+        assert_eq!(
+            place_range_order(OrderSide::Ask, price, 0, 100),
+            Err(RangeOrderError::ZeroLiquidity)
+        );
+        assert_eq!(
+            place_range_order(OrderSide::Ask, to_sqrt_ratio(MAX_TICK).unwrap(), 1_000, 100),
+            Err(RangeOrderError::PriceOutOfRange)
+        );
+        assert_eq!(
+            place_range_order(OrderSide::Bid, to_sqrt_ratio(MIN_TICK).unwrap(), 1_000, 100),
+            Err(RangeOrderError::PriceOutOfRange)
+        );
+    }
+
+    #[test]
+    fn test_estimate_fill() {
+        let price_x128 = |tick| {
+            let sqrt_ratio = to_sqrt_ratio(tick).unwrap();
+            muldiv(sqrt_ratio, sqrt_ratio, U256::one() << 128, false).unwrap()
+        };
+
+        for side in [OrderSide::Ask, OrderSide::Bid] {
+            let order = place_range_order(side, U256::one() << 128, 1_000_000_000, 100).unwrap();
+            let (before, after) = match side {
+                OrderSide::Ask => (order.tick_lower, order.tick_upper),
+                OrderSide::Bid => (order.tick_upper, order.tick_lower),
+            };
+
+            let unfilled = order.estimate_fill(to_sqrt_ratio(before).unwrap()).unwrap();
+            assert_eq!((unfilled.sold, unfilled.received), (0, 0));
+            assert_eq!(unfilled.filled, Bps::ZERO);
+            assert_eq!(unfilled.average_price_x128, None);
+
+            let filled = order.estimate_fill(to_sqrt_ratio(after).unwrap()).unwrap();
+            assert_eq!(
+                (filled.sold, filled.received),
+                (order.amount, order.proceeds)
+            );
+            assert_eq!(filled.filled, Bps::ONE);
+            // the price path beyond the range does not change the fill
+            assert_eq!(
+                order.estimate_fill(to_sqrt_ratio(after * 10).unwrap()),
+                Ok(filled)
+            );
+
+            let half = order
+                .estimate_fill(to_sqrt_ratio((order.tick_lower + order.tick_upper) / 2).unwrap())
+                .unwrap();
+            assert!(half.filled > Bps::new(4_900) && half.filled < Bps::new(5_100));
+            let average_price = half.average_price_x128.unwrap();
+            assert!(
+                average_price > price_x128(order.tick_lower)
+                    && average_price < price_x128(order.tick_upper)
+            );
+        }
+    }
+}
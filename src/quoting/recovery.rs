@@ -0,0 +1,265 @@
+use crate::quoting::pool_map::PoolMap;
+use crate::quoting::route::{
+    quote_route_with_states, Route, RouteQuote, RouteQuoteError, RouteSplit,
+    ROUTE_SHARE_DENOMINATOR,
+};
+use crate::quoting::types::{Address, Pool, TokenAmount};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+// Swaps a stranded token to the output token of the original route
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompletionLeg<S> {
+    pub token: Address,
+    pub amount: u128,
+    pub route: Route,
+    pub quote: RouteQuote<S>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecoveryPlan<S> {
+    pub legs: Vec<CompletionLeg<S>>,
+    // The total output of the legs in the output token of the original route
+    pub amount_out: u128,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RecoveryError<E> {
+    /// No hop of the original route trades the stranded token.
+    NoCompletionRoute(Address),
+    /// None of the completion routes of the stranded token could be quoted, with the error of the first one.
+    QuoteFailed {
+        token: Address,
+        error: RouteQuoteError<E>,
+    },
+}
+
+// The remaining hops of each split of the route that trades the token, as single split routes to the output token
+fn completion_routes(route: &Route, token: Address) -> Vec<Route> {
+    let mut routes: Vec<Route> = Vec::new();
+    for split in &route.splits {
+        if let Some(start) = split
+            .hops
+            .iter()
+            .position(|hop| hop.direction.token_in(&hop.pool_key) == token)
+        {
+            let completion = Route {
+                token_in: token,
+                token_out: route.token_out,
+                splits: alloc::vec![RouteSplit {
+                    share: ROUTE_SHARE_DENOMINATOR,
+                    hops: split.hops[start..].to_vec(),
+                }],
+            };
+            if !routes.contains(&completion) {
+                routes.push(completion);
+            }
+        }
+    }
+    routes
+}
+
+/// Plans the swaps that complete a route that only partially executed, e.g. because a hop hit its price limit, from
+/// the deltas of the partial execution.
+///
+/// The deltas follow the convention of the settlement verifier, so the tokens with negative deltas were received. Every
+/// received token other than the output token of the route is stranded, and is swapped through the remaining hops of
+/// the split of the route that gives the most output. The legs are quoted one after another, so legs that trade through
+/// the same pools see each other's effects.
+pub fn plan_recovery<P: Pool>(
+    pools: &PoolMap<P>,
+    route: &Route,
+    deltas: &[TokenAmount],
+    meta: P::Meta,
+) -> Result<RecoveryPlan<P::State>, RecoveryError<P::QuoteError>> {
+    let mut states = BTreeMap::new();
+    let mut legs = Vec::new();
+
+    for delta in deltas {
+        if delta.amount >= 0 || delta.token == route.token_out {
+            continue;
+        }
+        let amount = delta.amount.unsigned_abs();
+
+        let mut best: Option<(Route, RouteQuote<P::State>, BTreeMap<_, _>)> = None;
+        let mut first_error = None;
+        for completion in completion_routes(route, delta.token) {
+            let mut leg_states = states.clone();
+            match quote_route_with_states(pools, &completion, amount, meta, &mut leg_states) {
+                Ok(quote) => {
+                    if best
+                        .as_ref()
+                        .is_none_or(|(_, best_quote, _)| quote.amount_out > best_quote.amount_out)
+                    {
+                        best = Some((completion, quote, leg_states));
+                    }
+                }
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+
+        let (completion, quote, leg_states) = match (best, first_error) {
+            (Some(best), _) => best,
+            (None, Some(error)) => {
+                return Err(RecoveryError::QuoteFailed {
+                    token: delta.token,
+                    error,
+                })
+            }
+            (None, None) => return Err(RecoveryError::NoCompletionRoute(delta.token)),
+        };
+
+        states = leg_states;
+        legs.push(CompletionLeg {
+            token: delta.token,
+            amount,
+            route: completion,
+            quote,
+        });
+    }
+
+    Ok(RecoveryPlan {
+        amount_out: legs.iter().map(|leg| leg.quote.amount_out).sum(),
+        legs,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::uint::U256;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::route::{quote_route, HopDirection, RouteHop};
+    use crate::quoting::types::{Config, NodeKey};
+    use alloc::vec;
+
+    fn key(token0: u64, token1: u64, fee: u64) -> NodeKey {
+        NodeKey {
+            token0: Address::from_raw(U256::from(token0)),
+            token1: Address::from_raw(U256::from(token1)),
+            config: Config::from_raw_unchecked(fee, 0, U256::zero()),
+        }
+    }
+
+    fn hop(pool_key: NodeKey) -> RouteHop {
+        RouteHop {
+            pool_key,
+            direction: HopDirection::ZeroForOne,
+        }
+    }
+
+    fn pools(keys: &[NodeKey]) -> PoolMap<FullRangePool> {
+        keys.iter()
+            .map(|&key| {
+                FullRangePool::new(
+                    key,
+                    FullRangePoolState {
+                        sqrt_ratio: U256::one() << 128,
+                        liquidity: 1_000_000_000,
+                    },
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_recovery() {
+        // 1 -> 2 -> 3 -> 4, and 1 -> 2 -> 4 through a pool with a ~6% fee
+        let keys = [key(1, 2, 0), key(2, 3, 0), key(3, 4, 0), key(2, 4, 1 << 60)];
+        let pools = pools(&keys);
+        let route = Route {
+            token_in: Address::from_raw(U256::from(1)),
+            token_out: Address::from_raw(U256::from(4)),
+            splits: vec![
+                RouteSplit {
+                    share: 5_000,
+                    hops: vec![hop(keys[0]), hop(keys[3])],
+                },
+                RouteSplit {
+                    share: 5_000,
+                    hops: vec![hop(keys[0]), hop(keys[1]), hop(keys[2])],
+                },
+            ],
+        };
+
+        // the first split stopped after its first hop, and the second split after its second hop
+        let deltas = [
+            TokenAmount {
+                token: Address::from_raw(U256::from(1)),
+                amount: 20_000,
+            },
+            TokenAmount {
+                token: Address::from_raw(U256::from(2)),
+                amount: -10_000,
+            },
+            TokenAmount {
+                token: Address::from_raw(U256::from(3)),
+                amount: -5_000,
+            },
+            TokenAmount {
+                token: Address::from_raw(U256::from(4)),
+                amount: -1,
+            },
+        ];
+
+        let plan = plan_recovery(&pools, &route, &deltas, ()).unwrap();
+        assert_eq!(plan.legs.len(), 2);
+
+        // token 2 can go through either remaining path, and the fee free one gives more output
+        assert_eq!(plan.legs[0].token, U256::from(2));
+        assert_eq!(plan.legs[0].amount, 10_000);
+        assert_eq!(
+            plan.legs[0].route.splits[0].hops,
+            [hop(keys[1]), hop(keys[2])]
+        );
+
+        // the second leg sees the state left by the first in the pool of the last hop
+        assert_eq!(plan.legs[1].route.splits[0].hops, [hop(keys[2])]);
+        assert!(
+            plan.legs[1].quote.amount_out
+                < quote_route(&pools, &plan.legs[1].route, 5_000, ())
+                    .unwrap()
+                    .amount_out
+        );
+        assert_eq!(
+            plan.amount_out,
+            plan.legs[0].quote.amount_out + plan.legs[1].quote.amount_out
+        );
+    }
+
+    #[test]
+    fn test_recovery_errors() {
+        let keys = [key(1, 2, 0), key(2, 3, 0)];
+        let route = Route {
+            token_in: Address::from_raw(U256::from(1)),
+            token_out: Address::from_raw(U256::from(3)),
+            splits: vec![RouteSplit {
+                share: ROUTE_SHARE_DENOMINATOR,
+                hops: vec![hop(keys[0]), hop(keys[1])],
+            }],
+        };
+        let stranded = |token: u64| {
+            [TokenAmount {
+                token: Address::from_raw(U256::from(token)),
+                amount: -1_000,
+            }]
+        };
+
+        assert_eq!(
+            plan_recovery(&pools(&keys), &route, &stranded(5), ()),
+            Err(RecoveryError::NoCompletionRoute(Address::from_raw(
+                U256::from(5)
+            )))
+        );
+        assert_eq!(
+            plan_recovery(&pools(&keys[..1]), &route, &stranded(2), ()),
+            Err(RecoveryError::QuoteFailed {
+                token: Address::from_raw(U256::from(2)),
+                error: RouteQuoteError::PoolNotFound { split: 0, hop: 0 }
+            })
+        );
+    }
+}
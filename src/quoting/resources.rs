@@ -0,0 +1,113 @@
+use crate::quoting::types::Pool;
+use alloc::collections::BTreeMap;
+
+// The resources of a quote by name, e.g. for a metrics pipeline
+pub type FlatResources = BTreeMap<&'static str, u64>;
+
+/// Resources that can be reported as a flat map from the name of each resource to its value.
+///
+/// Nested resources, e.g. the resources of the pool that a pool wraps, are merged into the map of the resources that
+/// contain them, so a resource has the same name however deeply its pool is wrapped. Implement it with
+/// [`crate::impl_flatten_resources`].
+pub trait FlattenResources {
+    // Adds the value of each resource to the entry of its name in the map
+    fn flatten_into(&self, flat: &mut FlatResources);
+
+    fn flatten(&self) -> FlatResources {
+        let mut flat = FlatResources::new();
+        self.flatten_into(&mut flat);
+        flat
+    }
+}
+
+/// Implements [`FlattenResources`] for a struct of resources, given its fields that are counted and its fields that
+/// are nested resources.
+///
+/// ```
+/// # use evm_ekubo_sdk::impl_flatten_resources;
+/// # use evm_ekubo_sdk::quoting::full_range_pool::FullRangePoolResources;
+/// # use evm_ekubo_sdk::quoting::resources::FlattenResources;
+/// struct HookResources {
+///     hook_calls: u32,
+///     inner: FullRangePoolResources,
+/// }
+///
+/// impl_flatten_resources!(HookResources { hook_calls } nested { inner });
+///
+/// let resources = HookResources { hook_calls: 1, inner: Default::default() };
+/// assert_eq!(resources.flatten().get("hook_calls"), Some(&1));
+/// assert_eq!(resources.flatten().get("no_override_price_change"), Some(&0));
+/// ```
+#[macro_export]
+macro_rules! impl_flatten_resources {
+    ($resources:ty { $($field:ident),* $(,)? } $(nested { $($nested:ident),* $(,)? })?) => {
+        impl $crate::quoting::resources::FlattenResources for $resources {
+            fn flatten_into(&self, flat: &mut $crate::quoting::resources::FlatResources) {
+                $(*flat.entry(stringify!($field)).or_default() += u64::from(self.$field);)*
+                $($($crate::quoting::resources::FlattenResources::flatten_into(&self.$nested, flat);)*)?
+            }
+        }
+    };
+}
+
+/// Pools that can bound the resources of their quotes without quoting, e.g. to prune pools that cannot fit in a
+/// budget before routing.
+pub trait MaxResourcesEstimate: Pool {
+    /// Returns resources that are at least the resources of any quote of at most `amount_bound` of either token
+    /// against the current state of the pool. The bound is conservative and only depends on the ticks and orders of
+    /// the pool, except that a zero amount bound does not move the price.
+    fn max_resources_estimate(&self, amount_bound: u128) -> Self::Resources;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::base_pool::BasePoolResources;
+    use crate::quoting::mev_resist_pool::MEVResistPoolResources;
+    use crate::quoting::oracle_pool::OraclePoolResources;
+    use crate::quoting::twamm_pool::TwammPoolResources;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_nested_resources_are_merged() {
+        let resources = MEVResistPoolResources {
+            state_update_count: 1,
+            base_pool_resources: BasePoolResources {
+                no_override_price_change: 1,
+                initialized_ticks_crossed: 3,
+                tick_spacings_crossed: 20,
+            },
+        };
+
+        assert_eq!(
+            resources.flatten().into_iter().collect::<Vec<_>>(),
+            [
+                ("initialized_ticks_crossed", 3),
+                ("no_override_price_change", 1),
+                ("state_update_count", 1),
+                ("tick_spacings_crossed", 20),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resources_of_different_pools_aggregate() {
+        let mut flat = FlatResources::new();
+        OraclePoolResources {
+            snapshots_written: 1,
+            ..Default::default()
+        }
+        .flatten_into(&mut flat);
+        TwammPoolResources {
+            virtual_orders_executed: 1,
+            ..Default::default()
+        }
+        .flatten_into(&mut flat);
+
+        assert_eq!(flat.get("snapshots_written"), Some(&1));
+        assert_eq!(flat.get("virtual_orders_executed"), Some(&1));
+        // both pools wrap a full range pool
+        assert_eq!(flat.get("no_override_price_change"), Some(&0));
+        assert_eq!(flat.len(), 7);
+    }
+}
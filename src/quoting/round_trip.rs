@@ -0,0 +1,213 @@
+use crate::quoting::types::{Address, Pool, QuoteParams, TokenAmount};
+
+// The amounts of a swap from one token of a pool to the other and back
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoundTrip {
+    // The amount of the input token consumed by the first swap
+    pub amount_in: u128,
+    // The amount of the other token received from the first swap and swapped back
+    pub intermediate_amount: u128,
+    // The amount of the other token that the second swap did not consume, e.g. because it hit a price limit
+    pub intermediate_unspent: u128,
+    // The amount of the input token received from the second swap
+    pub amount_out: u128,
+    // amount_in - amount_out
+    pub loss: u128,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RoundTripError<E> {
+    /// The amount must be greater than zero and fit in an i128.
+    InvalidAmount,
+    /// The swap to the other token failed to quote.
+    ForwardQuoteFailed(E),
+    /// The swap back to the input token failed to quote.
+    ReverseQuoteFailed(E),
+    /// The swap back returned more of the input token than was swapped.
+    ValueCreated { amount_in: u128, amount_out: u128 },
+}
+
+/// Quotes an exact input swap of `amount` of `token` and then swaps the output back against the state after the
+/// first swap, returning the round trip loss.
+///
+/// A correct pool never returns more than was swapped in, so this fails with `ValueCreated` if it does. This is a
+/// cheap sanity check for imported pool state and a primitive for property tests.
+pub fn verify_round_trip<P: Pool>(
+    pool: &P,
+    token: Address,
+    amount: u128,
+    meta: P::Meta,
+) -> Result<RoundTrip, RoundTripError<P::QuoteError>> {
+    let key = pool.get_key();
+    let other_token = if token == key.token0 {
+        key.token1
+    } else {
+        key.token0
+    };
+
+    let amount = i128::try_from(amount)
+        .ok()
+        .filter(|amount| *amount > 0)
+        .ok_or(RoundTripError::InvalidAmount)?;
+
+    let forward = pool
+        .quote(QuoteParams {
+            token_amount: TokenAmount { token, amount },
+            sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
+            override_state: None,
+            meta,
+        })
+        .map_err(RoundTripError::ForwardQuoteFailed)?;
+
+    let amount_in = forward.consumed_amount.unsigned_abs();
+    let intermediate_amount = forward.calculated_amount;
+
+    let (intermediate_unspent, amount_out) = if intermediate_amount == 0 {
+        (0, 0)
+    } else {
+        let reverse = pool
+            .quote(QuoteParams {
+                token_amount: TokenAmount {
+                    token: other_token,
+                    amount: i128::try_from(intermediate_amount)
+                        .map_err(|_| RoundTripError::InvalidAmount)?,
+                },
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: Some(forward.state_after),
+                meta,
+            })
+            .map_err(RoundTripError::ReverseQuoteFailed)?;
+
+        (
+            intermediate_amount - reverse.consumed_amount.unsigned_abs(),
+            reverse.calculated_amount,
+        )
+    };
+
+    if amount_out > amount_in {
+        return Err(RoundTripError::ValueCreated {
+            amount_in,
+            amount_out,
+        });
+    }
+
+    Ok(RoundTrip {
+        amount_in,
+        intermediate_amount,
+        intermediate_unspent,
+        amount_out,
+        loss: amount_in - amount_out,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::to_sqrt_ratio;
+    use crate::math::uint::U256;
+    use crate::quoting::base_pool::{BasePool, BasePoolState};
+    use crate::quoting::full_range_pool::{
+        FullRangePool, FullRangePoolQuoteError, FullRangePoolState,
+    };
+    use crate::quoting::order_flow::SplitMix64;
+    use crate::quoting::types::{Config, NodeKey, Tick};
+    use alloc::vec;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn full_range_pool(fee: u64) -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0: TOKEN0,
+                token1: TOKEN1,
+                config: Config::from_raw_unchecked(fee, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 1_000_000_000,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_loses_fees() {
+        let round_trip =
+            verify_round_trip(&full_range_pool(1 << 60), TOKEN0, 1_000_000, ()).unwrap();
+        assert_eq!(round_trip.amount_in, 1_000_000);
+        assert_eq!(round_trip.intermediate_unspent, 0);
+        assert!(round_trip.loss > 0);
+        assert_eq!(
+            round_trip.amount_in - round_trip.amount_out,
+            round_trip.loss
+        );
+
+        // without fees only rounding is lost
+        let round_trip = verify_round_trip(&full_range_pool(0), TOKEN1, 1_000_000, ()).unwrap();
+        assert!(round_trip.loss <= 2);
+    }
+
+    #[test]
+    fn test_round_trip_errors() {
+        let pool = full_range_pool(0);
+        assert_eq!(
+            verify_round_trip(&pool, TOKEN0, 0, ()),
+            Err(RoundTripError::InvalidAmount)
+        );
+        assert_eq!(
+            verify_round_trip(&pool, TOKEN0, u128::MAX, ()),
+            Err(RoundTripError::InvalidAmount)
+        );
+        assert_eq!(
+            verify_round_trip(&pool, Address::from_raw(U256::from(3)), 1, ()),
+            Err(RoundTripError::ForwardQuoteFailed(
+                FullRangePoolQuoteError::InvalidToken
+            ))
+        );
+    }
+
+    #[test]
+    fn test_round_trip_never_creates_value() {
+        let pool = BasePool::new(
+            NodeKey {
+                token0: TOKEN0,
+                token1: TOKEN1,
+                config: Config::from_raw_unchecked(1 << 55, 100, U256::zero()),
+            },
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(50).unwrap(),
+                liquidity: 1_000_000,
+                active_tick_index: Some(1),
+            },
+            vec![
+                Tick {
+                    index: -1000,
+                    liquidity_delta: 500_000,
+                },
+                Tick {
+                    index: 0,
+                    liquidity_delta: 500_000,
+                },
+                Tick {
+                    index: 1000,
+                    liquidity_delta: -1_000_000,
+                },
+            ],
+        )
+        .unwrap();
+
+        let mut rng = SplitMix64(7);
+        for _ in 0..200 {
+            let token = if rng.next_u64() & 1 == 0 {
+                TOKEN0
+            } else {
+                TOKEN1
+            };
+            let amount = rng.next_u128() % 1_000_000 + 1;
+            verify_round_trip(&pool, token, amount, ()).unwrap();
+        }
+    }
+}
@@ -0,0 +1,952 @@
+use crate::math::uint::U256;
+use crate::quoting::batch::{InvalidPoolPolicy, SkippedPool};
+use crate::quoting::bps::Bps;
+use crate::quoting::cancellation::{NeverStop, StopSignal};
+use crate::quoting::pool_map::PoolMap;
+use crate::quoting::types::{
+    Address, Config, ConfigError, NodeKey, Pool, QuoteParams, SwapDirection, TokenAmount,
+};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+// Version of the binary and serde encodings produced by this version of the crate
+pub const ROUTE_ENCODING_VERSION: u8 = 1;
+
+// The shares of the splits of a route add up to this amount
+pub const ROUTE_SHARE_DENOMINATOR: u16 = Bps::DENOMINATOR as u16;
+
+// token0, token1 and the packed config, as in the pool key of the core contract
+const ENCODED_NODE_KEY_LENGTH: usize = 96;
+
+// The direction of a hop, which is serialized in snake case
+pub type HopDirection = SwapDirection;
+
+// A swap through a single pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RouteHop {
+    pub pool_key: NodeKey,
+    pub direction: HopDirection,
+}
+
+// A sequence of hops that receives a share of the input amount of a route
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RouteSplit {
+    // Share of the input amount out of ROUTE_SHARE_DENOMINATOR
+    pub share: u16,
+    pub hops: Vec<RouteHop>,
+}
+
+// A route from one token to another, possibly split across multiple paths.
+//
+// With the serde feature, routes are serialized with a version field so that persisted routes can be decoded by
+// later versions of the crate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(from = "VersionedRoute", into = "VersionedRoute")
+)]
+pub struct Route {
+    pub token_in: Address,
+    pub token_out: Address,
+    pub splits: Vec<RouteSplit>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RouteEncodingError {
+    /// A route can have at most 255 splits.
+    TooManySplits,
+    /// A split can have at most 255 hops.
+    TooManyHops,
+    /// The extension of a pool key must fit in 160 bits to be packed into the config.
+    ExtensionTooLarge,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RouteDecodingError {
+    /// The encoding was produced by an unknown version of the crate.
+    UnsupportedVersion(u8),
+    /// The bytes ended before the route was fully decoded.
+    UnexpectedEnd,
+    /// There are bytes left after the route was decoded.
+    TrailingBytes,
+    /// The byte does not encode a hop direction.
+    InvalidDirection(u8),
+    /// The word of a token does not fit in an address.
+    InvalidAddress,
+    /// The word of a pool config is not a valid config.
+    InvalidConfig(ConfigError),
+}
+
+// The size of calldata, with zero bytes counted separately because they are priced lower
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CalldataSize {
+    pub zero_bytes: usize,
+    pub nonzero_bytes: usize,
+}
+
+impl CalldataSize {
+    fn of(bytes: &[u8]) -> Self {
+        let zero_bytes = bytes.iter().filter(|&&byte| byte == 0).count();
+        Self {
+            zero_bytes,
+            nonzero_bytes: bytes.len() - zero_bytes,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.zero_bytes + self.nonzero_bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // The gas of the calldata on L1 as priced by EIP-2028
+    pub fn gas(&self) -> u64 {
+        4 * self.zero_bytes as u64 + 16 * self.nonzero_bytes as u64
+    }
+}
+
+impl core::ops::Add for CalldataSize {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            zero_bytes: self.zero_bytes + rhs.zero_bytes,
+            nonzero_bytes: self.nonzero_bytes + rhs.nonzero_bytes,
+        }
+    }
+}
+
+// The size of the binary encoding of a route, broken down by hop
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RouteCalldataSize {
+    // The version, the tokens and the header of each split
+    pub overhead: CalldataSize,
+    // The size of each hop, grouped by split
+    pub hops: Vec<Vec<CalldataSize>>,
+    pub total: CalldataSize,
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], RouteDecodingError> {
+        if self.bytes.len() < length {
+            return Err(RouteDecodingError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, RouteDecodingError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, RouteDecodingError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u256(&mut self) -> Result<U256, RouteDecodingError> {
+        Ok(U256::from_big_endian(self.take(32)?))
+    }
+
+    fn address(&mut self) -> Result<Address, RouteDecodingError> {
+        Address::new(self.u256()?).map_err(|_| RouteDecodingError::InvalidAddress)
+    }
+}
+
+impl Route {
+    // Returns the length in bytes of the binary encoding of the route
+    pub fn encoded_len(&self) -> usize {
+        1 + 32
+            + 32
+            + 1
+            + self
+                .splits
+                .iter()
+                .map(|split| 2 + 1 + split.hops.len() * (ENCODED_NODE_KEY_LENGTH + 1))
+                .sum::<usize>()
+    }
+
+    // Returns the size of the binary encoding of the route without encoding it, so it does not validate the route
+    pub fn calldata_size(&self) -> RouteCalldataSize {
+        let mut overhead = CalldataSize::of(&[ROUTE_ENCODING_VERSION, self.splits.len() as u8])
+            + CalldataSize::of(&self.token_in.raw().to_big_endian())
+            + CalldataSize::of(&self.token_out.raw().to_big_endian());
+
+        let hops: Vec<Vec<CalldataSize>> = self
+            .splits
+            .iter()
+            .map(|split| {
+                let [share_high, share_low] = split.share.to_be_bytes();
+                overhead =
+                    overhead + CalldataSize::of(&[share_high, share_low, split.hops.len() as u8]);
+
+                split
+                    .hops
+                    .iter()
+                    .map(|hop| {
+                        CalldataSize::of(&hop.pool_key.token0.raw().to_big_endian())
+                            + CalldataSize::of(&hop.pool_key.token1.raw().to_big_endian())
+                            + CalldataSize::of(&U256::from(hop.pool_key.config).to_big_endian())
+                            + CalldataSize::of(&[match hop.direction {
+                                HopDirection::ZeroForOne => 0,
+                                HopDirection::OneForZero => 1,
+                            }])
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let total = hops
+            .iter()
+            .flatten()
+            .fold(overhead, |total, &hop| total + hop);
+        RouteCalldataSize {
+            overhead,
+            hops,
+            total,
+        }
+    }
+
+    // Encodes the route as: version, token_in, token_out, the number of splits, and for each split its share, the
+    // number of hops and for each hop the pool key and direction. Integers are big endian and pool keys are encoded
+    // as token0, token1 and the packed config.
+    pub fn encode(&self) -> Result<Vec<u8>, RouteEncodingError> {
+        let mut bytes = Vec::with_capacity(self.encoded_len());
+        bytes.push(ROUTE_ENCODING_VERSION);
+        bytes.extend_from_slice(&self.token_in.raw().to_big_endian());
+        bytes.extend_from_slice(&self.token_out.raw().to_big_endian());
+        bytes.push(u8::try_from(self.splits.len()).map_err(|_| RouteEncodingError::TooManySplits)?);
+
+        for split in &self.splits {
+            bytes.extend_from_slice(&split.share.to_be_bytes());
+            bytes
+                .push(u8::try_from(split.hops.len()).map_err(|_| RouteEncodingError::TooManyHops)?);
+
+            for hop in &split.hops {
+                if hop.pool_key.config.extension.bits() > 160 {
+                    return Err(RouteEncodingError::ExtensionTooLarge);
+                }
+                bytes.extend_from_slice(&hop.pool_key.token0.raw().to_big_endian());
+                bytes.extend_from_slice(&hop.pool_key.token1.raw().to_big_endian());
+                bytes.extend_from_slice(&U256::from(hop.pool_key.config).to_big_endian());
+                bytes.push(match hop.direction {
+                    HopDirection::ZeroForOne => 0,
+                    HopDirection::OneForZero => 1,
+                });
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    // Decodes a route encoded with `encode`
+    pub fn decode(bytes: &[u8]) -> Result<Self, RouteDecodingError> {
+        let mut decoder = Decoder { bytes };
+
+        let version = decoder.u8()?;
+        if version != ROUTE_ENCODING_VERSION {
+            return Err(RouteDecodingError::UnsupportedVersion(version));
+        }
+
+        let token_in = decoder.address()?;
+        let token_out = decoder.address()?;
+        let split_count = decoder.u8()?;
+
+        let mut splits = Vec::with_capacity(split_count as usize);
+        for _ in 0..split_count {
+            let share = decoder.u16()?;
+            let hop_count = decoder.u8()?;
+
+            let mut hops = Vec::with_capacity(hop_count as usize);
+            for _ in 0..hop_count {
+                let pool_key = NodeKey {
+                    token0: decoder.address()?,
+                    token1: decoder.address()?,
+                    config: Config::try_from(decoder.u256()?)
+                        .map_err(RouteDecodingError::InvalidConfig)?,
+                };
+                let direction = match decoder.u8()? {
+                    0 => HopDirection::ZeroForOne,
+                    1 => HopDirection::OneForZero,
+                    other => return Err(RouteDecodingError::InvalidDirection(other)),
+                };
+                hops.push(RouteHop {
+                    pool_key,
+                    direction,
+                });
+            }
+
+            splits.push(RouteSplit { share, hops });
+        }
+
+        if !decoder.bytes.is_empty() {
+            return Err(RouteDecodingError::TrailingBytes);
+        }
+
+        Ok(Route {
+            token_in,
+            token_out,
+            splits,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RouteQuoteError<E> {
+    /// The shares of the splits must add up to ROUTE_SHARE_DENOMINATOR.
+    InvalidShares,
+    /// The input token of the hop is not the output token of the previous hop, or the route tokens do not match the
+    /// first and last hops.
+    InvalidPath { split: usize, hop: usize },
+    /// The pool of the hop is not in the pool map.
+    PoolNotFound { split: usize, hop: usize },
+    /// The pool of the hop failed to quote.
+    QuoteFailed { split: usize, hop: usize, error: E },
+    /// The pool of the hop could not swap the whole input amount.
+    PartialFill { split: usize, hop: usize },
+    /// The input amount of a hop does not fit in an i128.
+    AmountOverflow,
+    /// The stop signal was raised before the hop was quoted.
+    Cancelled { split: usize, hop: usize },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SplitQuote {
+    // The input amount of each hop followed by the output amount of the last hop
+    pub amounts: Vec<u128>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteQuote<S> {
+    pub amount_out: u128,
+    pub splits: Vec<SplitQuote>,
+    // The state of each pool of the route after all of its hops
+    pub states_after: BTreeMap<NodeKey, S>,
+}
+
+// A split of a route that was not swapped because the pool of one of its hops failed to quote
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SkippedSplit<E> {
+    pub split: usize,
+    // The input amount of the split, which is not part of the quote
+    pub amount_in: u128,
+    // The pool that failed to quote, with the index of its hop in the split
+    pub pool: SkippedPool<E>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteQuoteReport<S, E> {
+    // The quote of the splits that were not skipped, in the same order as the splits of the route
+    pub quote: RouteQuote<S>,
+    pub skipped: Vec<SkippedSplit<E>>,
+}
+
+pub type PoolRouteQuoteReport<P> = RouteQuoteReport<<P as Pool>::State, <P as Pool>::QuoteError>;
+
+impl SplitQuote {
+    pub fn amount_in(&self) -> u128 {
+        self.amounts[0]
+    }
+
+    pub fn amount_out(&self) -> u128 {
+        self.amounts[self.amounts.len() - 1]
+    }
+}
+
+// Splits the amount according to the shares of the route, assigning the rounding remainder to the last split
+fn split_amounts(route: &Route, amount_in: u128) -> Option<Vec<u128>> {
+    let mut remaining = amount_in;
+    let mut amounts: Vec<u128> = route
+        .splits
+        .iter()
+        .map(|split| {
+            let amount = Bps::new(split.share.into())
+                .apply_floor(U256::from(amount_in))?
+                .as_u128();
+            remaining -= amount;
+            Some(amount)
+        })
+        .collect::<Option<_>>()?;
+    *amounts.last_mut()? += remaining;
+    Some(amounts)
+}
+
+// Quotes swapping `amount_in` of the input token through the route, starting from the given states of the pools
+// instead of their current states. Pools that are traded through multiple times see the state left by their previous
+// hops, and `states` is updated to the state of each pool after the route.
+pub fn quote_route_with_states<P: Pool>(
+    pools: &PoolMap<P>,
+    route: &Route,
+    amount_in: u128,
+    meta: P::Meta,
+    states: &mut BTreeMap<NodeKey, P::State>,
+) -> Result<RouteQuote<P::State>, RouteQuoteError<P::QuoteError>> {
+    quote_route_with_states_until(pools, route, amount_in, meta, states, &NeverStop)
+}
+
+// Same as quote_route_with_states, but checks the stop signal before each hop. A cancelled quote leaves the states of
+// the pools that were already quoted in `states`.
+pub fn quote_route_with_states_until<P: Pool>(
+    pools: &PoolMap<P>,
+    route: &Route,
+    amount_in: u128,
+    meta: P::Meta,
+    states: &mut BTreeMap<NodeKey, P::State>,
+    stop: &impl StopSignal,
+) -> Result<RouteQuote<P::State>, RouteQuoteError<P::QuoteError>> {
+    quote_route_inner(
+        pools,
+        route,
+        amount_in,
+        meta,
+        states,
+        stop,
+        InvalidPoolPolicy::FailFast,
+    )
+    .map(|report| report.quote)
+}
+
+/// Same as [`quote_route_with_states`], but handles the pools that fail to quote according to the policy.
+///
+/// With [`InvalidPoolPolicy::SkipAndReport`], a split with a hop whose pool fails to quote is skipped as a whole: its
+/// input amount is not swapped, the states of its pools are left as they were before the split and it is reported
+/// alongside the quote of the other splits. Errors of the route itself, e.g. invalid shares or paths, always fail.
+pub fn quote_route_with_states_and_policy<P: Pool>(
+    pools: &PoolMap<P>,
+    route: &Route,
+    amount_in: u128,
+    meta: P::Meta,
+    states: &mut BTreeMap<NodeKey, P::State>,
+    policy: InvalidPoolPolicy,
+) -> Result<PoolRouteQuoteReport<P>, RouteQuoteError<P::QuoteError>> {
+    quote_route_inner(pools, route, amount_in, meta, states, &NeverStop, policy)
+}
+
+fn quote_route_inner<P: Pool>(
+    pools: &PoolMap<P>,
+    route: &Route,
+    amount_in: u128,
+    meta: P::Meta,
+    states: &mut BTreeMap<NodeKey, P::State>,
+    stop: &impl StopSignal,
+    policy: InvalidPoolPolicy,
+) -> Result<PoolRouteQuoteReport<P>, RouteQuoteError<P::QuoteError>> {
+    if route
+        .splits
+        .iter()
+        .map(|split| split.share as u32)
+        .sum::<u32>()
+        != ROUTE_SHARE_DENOMINATOR as u32
+    {
+        return Err(RouteQuoteError::InvalidShares);
+    }
+
+    let split_amounts = split_amounts(route, amount_in).ok_or(RouteQuoteError::InvalidShares)?;
+    let mut splits = Vec::with_capacity(route.splits.len());
+    let mut skipped = Vec::new();
+
+    'splits: for (split_index, (split, split_amount)) in
+        route.splits.iter().zip(split_amounts).enumerate()
+    {
+        let mut amounts = Vec::with_capacity(split.hops.len() + 1);
+        amounts.push(split_amount);
+        let mut token = route.token_in;
+        // the states of the pools are restored if the split is skipped
+        let states_before = match policy {
+            InvalidPoolPolicy::FailFast => None,
+            InvalidPoolPolicy::SkipAndReport => Some(states.clone()),
+        };
+
+        for (hop_index, hop) in split.hops.iter().enumerate() {
+            if stop.should_stop() {
+                return Err(RouteQuoteError::Cancelled {
+                    split: split_index,
+                    hop: hop_index,
+                });
+            }
+            if hop.direction.token_in(&hop.pool_key) != token {
+                return Err(RouteQuoteError::InvalidPath {
+                    split: split_index,
+                    hop: hop_index,
+                });
+            }
+            let pool = pools
+                .get(&hop.pool_key)
+                .ok_or(RouteQuoteError::PoolNotFound {
+                    split: split_index,
+                    hop: hop_index,
+                })?;
+
+            let amount = amounts[hop_index];
+            let quote = match pool.quote_borrowed(QuoteParams {
+                token_amount: TokenAmount {
+                    token,
+                    amount: i128::try_from(amount).map_err(|_| RouteQuoteError::AmountOverflow)?,
+                },
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: states.get(&hop.pool_key),
+                meta,
+            }) {
+                Ok(quote) => quote,
+                Err(error) => match states_before {
+                    None => {
+                        return Err(RouteQuoteError::QuoteFailed {
+                            split: split_index,
+                            hop: hop_index,
+                            error,
+                        })
+                    }
+                    Some(states_before) => {
+                        *states = states_before;
+                        skipped.push(SkippedSplit {
+                            split: split_index,
+                            amount_in: split_amount,
+                            pool: SkippedPool {
+                                index: hop_index,
+                                pool_key: hop.pool_key,
+                                error,
+                            },
+                        });
+                        continue 'splits;
+                    }
+                },
+            };
+
+            if quote.consumed_amount.unsigned_abs() != amount {
+                return Err(RouteQuoteError::PartialFill {
+                    split: split_index,
+                    hop: hop_index,
+                });
+            }
+
+            states.insert(hop.pool_key, quote.state_after);
+            amounts.push(quote.calculated_amount);
+            token = hop.direction.token_out(&hop.pool_key);
+        }
+
+        if token != route.token_out {
+            return Err(RouteQuoteError::InvalidPath {
+                split: split_index,
+                hop: split.hops.len(),
+            });
+        }
+
+        splits.push(SplitQuote { amounts });
+    }
+
+    Ok(RouteQuoteReport {
+        quote: RouteQuote {
+            amount_out: splits.iter().map(SplitQuote::amount_out).sum(),
+            splits,
+            states_after: states.clone(),
+        },
+        skipped,
+    })
+}
+
+// Quotes swapping `amount_in` of the input token through the route against the current states of the pools
+pub fn quote_route<P: Pool>(
+    pools: &PoolMap<P>,
+    route: &Route,
+    amount_in: u128,
+    meta: P::Meta,
+) -> Result<RouteQuote<P::State>, RouteQuoteError<P::QuoteError>> {
+    quote_route_with_states(pools, route, amount_in, meta, &mut BTreeMap::new())
+}
+
+// Same as quote_route, but handles the pools that fail to quote according to the policy
+pub fn quote_route_with_policy<P: Pool>(
+    pools: &PoolMap<P>,
+    route: &Route,
+    amount_in: u128,
+    meta: P::Meta,
+    policy: InvalidPoolPolicy,
+) -> Result<PoolRouteQuoteReport<P>, RouteQuoteError<P::QuoteError>> {
+    quote_route_with_states_and_policy(pools, route, amount_in, meta, &mut BTreeMap::new(), policy)
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RouteV1 {
+    #[serde(with = "crate::quoting::types::serde_address_hex")]
+    token_in: Address,
+    #[serde(with = "crate::quoting::types::serde_address_hex")]
+    token_out: Address,
+    splits: Vec<RouteSplit>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "version")]
+enum VersionedRoute {
+    #[serde(rename = "1")]
+    V1(RouteV1),
+}
+
+#[cfg(feature = "serde")]
+impl From<VersionedRoute> for Route {
+    fn from(value: VersionedRoute) -> Self {
+        match value {
+            VersionedRoute::V1(RouteV1 {
+                token_in,
+                token_out,
+                splits,
+            }) => Route {
+                token_in,
+                token_out,
+                splits,
+            },
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Route> for VersionedRoute {
+    fn from(value: Route) -> Self {
+        VersionedRoute::V1(RouteV1 {
+            token_in: value.token_in,
+            token_out: value.token_out,
+            splits: value.splits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use alloc::vec;
+
+    fn pool_key(token0: u64, token1: u64, fee: u64) -> NodeKey {
+        NodeKey {
+            token0: Address::from_raw(U256::from(token0)),
+            token1: Address::from_raw(U256::from(token1)),
+            config: Config::from_raw_unchecked(fee, 100, (U256::one() << 160) - 1),
+        }
+    }
+
+    fn route() -> Route {
+        Route {
+            token_in: Address::from_raw(U256::from(1)),
+            token_out: Address::from_raw(U256::from(3)),
+            splits: vec![
+                RouteSplit {
+                    share: 7_000,
+                    hops: vec![RouteHop {
+                        pool_key: pool_key(1, 3, 1 << 60),
+                        direction: HopDirection::ZeroForOne,
+                    }],
+                },
+                RouteSplit {
+                    share: 3_000,
+                    hops: vec![
+                        RouteHop {
+                            pool_key: pool_key(1, 2, u64::MAX),
+                            direction: HopDirection::ZeroForOne,
+                        },
+                        RouteHop {
+                            pool_key: pool_key(2, 3, 0),
+                            direction: HopDirection::ZeroForOne,
+                        },
+                    ],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let route = route();
+        let encoded = route.encode().unwrap();
+
+        assert_eq!(encoded.len(), route.encoded_len());
+        assert_eq!(encoded[0], ROUTE_ENCODING_VERSION);
+        assert_eq!(Route::decode(&encoded), Ok(route));
+    }
+
+    #[test]
+    fn test_calldata_size() {
+        let route = route();
+        let encoded = route.encode().unwrap();
+        let size = route.calldata_size();
+
+        assert_eq!(size.total.len(), encoded.len());
+        assert_eq!(
+            size.total.zero_bytes,
+            encoded.iter().filter(|&&byte| byte == 0).count()
+        );
+        assert_eq!(size.hops.iter().map(Vec::len).collect::<Vec<_>>(), [1, 2]);
+        assert!(size
+            .hops
+            .iter()
+            .flatten()
+            .all(|hop| hop.len() == ENCODED_NODE_KEY_LENGTH + 1));
+        assert_eq!(size.overhead.len(), 1 + 32 + 32 + 1 + 2 * 3);
+        // the fee of the first hop of the second split takes more nonzero bytes
+        assert!(size.hops[1][0].nonzero_bytes > size.hops[1][1].nonzero_bytes);
+        assert_eq!(
+            size.total.gas(),
+            4 * size.total.len() as u64 + 12 * size.total.nonzero_bytes as u64
+        );
+    }
+
+    #[test]
+    fn test_decoding_errors() {
+        let mut encoded = route().encode().unwrap();
+
+        assert_eq!(
+            Route::decode(&encoded[..encoded.len() - 1]),
+            Err(RouteDecodingError::UnexpectedEnd)
+        );
+        assert_eq!(Route::decode(&[]), Err(RouteDecodingError::UnexpectedEnd));
+
+        encoded.push(0);
+        assert_eq!(
+            Route::decode(&encoded),
+            Err(RouteDecodingError::TrailingBytes)
+        );
+        encoded.pop();
+
+        let last = encoded.len() - 1;
+        encoded[last] = 2;
+        assert_eq!(
+            Route::decode(&encoded),
+            Err(RouteDecodingError::InvalidDirection(2))
+        );
+        encoded[last] = 0;
+
+        // the first byte of token_in and of the tick spacing of the first hop
+        let mut invalid = encoded.clone();
+        invalid[1] = 1;
+        assert_eq!(
+            Route::decode(&invalid),
+            Err(RouteDecodingError::InvalidAddress)
+        );
+        let mut invalid = encoded.clone();
+        invalid[1 + 32 + 32 + 1 + 2 + 1 + 32 + 32 + 28] = 0xff;
+        assert_eq!(
+            Route::decode(&invalid),
+            Err(RouteDecodingError::InvalidConfig(
+                ConfigError::TickSpacingTooLarge
+            ))
+        );
+
+        encoded[0] = 2;
+        assert_eq!(
+            Route::decode(&encoded),
+            Err(RouteDecodingError::UnsupportedVersion(2))
+        );
+    }
+
+    #[test]
+    fn test_encoding_errors() {
+        let mut route = route();
+        route.splits[0].hops[0].pool_key.config.extension = U256::one() << 160;
+        assert_eq!(route.encode(), Err(RouteEncodingError::ExtensionTooLarge));
+
+        route.splits = vec![
+            RouteSplit {
+                share: 0,
+                hops: vec![],
+            };
+            256
+        ];
+        assert_eq!(route.encode(), Err(RouteEncodingError::TooManySplits));
+    }
+
+    #[test]
+    fn test_hop_tokens() {
+        let hop = route().splits[1].hops[1];
+        assert_eq!(hop.direction.token_in(&hop.pool_key), U256::from(2));
+        assert_eq!(hop.direction.token_out(&hop.pool_key), U256::from(3));
+    }
+
+    fn pools() -> PoolMap<FullRangePool> {
+        [(1, 3), (1, 2), (2, 3)]
+            .into_iter()
+            .map(|(token0, token1)| {
+                FullRangePool::new(
+                    NodeKey {
+                        token0: Address::from_raw(U256::from(token0)),
+                        token1: Address::from_raw(U256::from(token1)),
+                        config: Config::from_raw_unchecked(0, 0, U256::zero()),
+                    },
+                    FullRangePoolState {
+                        sqrt_ratio: U256::one() << 128,
+                        liquidity: 1_000_000,
+                    },
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    fn quotable_route() -> Route {
+        let mut route = route();
+        for split in route.splits.iter_mut() {
+            for hop in split.hops.iter_mut() {
+                hop.pool_key.config = Config::from_raw_unchecked(0, 0, U256::zero());
+            }
+        }
+        route
+    }
+
+    #[test]
+    fn test_quote_route() {
+        let quote = quote_route(&pools(), &quotable_route(), 1_001, ()).unwrap();
+
+        assert_eq!(quote.splits[0].amounts, [700, 699]);
+        // the rounding remainder goes to the last split
+        assert_eq!(quote.splits[1].amounts, [301, 300, 299]);
+        assert_eq!(quote.amount_out, 998);
+        assert_eq!(quote.states_after.len(), 3);
+    }
+
+    #[test]
+    fn test_quote_route_with_policy() {
+        use crate::quoting::faulty_pool::{FaultTrigger, FaultyPool, FaultyPoolQuoteError};
+
+        // the last hop of the second split fails to quote
+        let failing_key = quotable_route().splits[1].hops[1].pool_key;
+        let pools: PoolMap<_> = pools()
+            .iter()
+            .map(|pool| {
+                let faulty = FaultyPool::new(pool.clone());
+                if *pool.get_key() == failing_key {
+                    faulty.with_quote_fault(FaultTrigger::Always, 7)
+                } else {
+                    faulty
+                }
+            })
+            .collect();
+
+        assert_eq!(
+            quote_route_with_policy(
+                &pools,
+                &quotable_route(),
+                1_001,
+                (),
+                InvalidPoolPolicy::FailFast
+            ),
+            Err(RouteQuoteError::QuoteFailed {
+                split: 1,
+                hop: 1,
+                error: FaultyPoolQuoteError::Injected(7)
+            })
+        );
+
+        let report = quote_route_with_policy(
+            &pools,
+            &quotable_route(),
+            1_001,
+            (),
+            InvalidPoolPolicy::SkipAndReport,
+        )
+        .unwrap();
+        assert_eq!(report.quote.splits.len(), 1);
+        assert_eq!(report.quote.amount_out, 699);
+        assert_eq!(
+            report.skipped,
+            [SkippedSplit {
+                split: 1,
+                amount_in: 301,
+                pool: SkippedPool {
+                    index: 1,
+                    pool_key: failing_key,
+                    error: FaultyPoolQuoteError::Injected(7)
+                }
+            }]
+        );
+        // the state of the first hop of the skipped split is rolled back
+        assert_eq!(
+            report.quote.states_after.keys().collect::<Vec<_>>(),
+            [&quotable_route().splits[0].hops[0].pool_key]
+        );
+    }
+
+    #[test]
+    fn test_quote_route_reuses_state_of_repeated_pools() {
+        let pools = pools();
+        let key = *pools.iter().next().unwrap().get_key();
+        let hop = RouteHop {
+            pool_key: key,
+            direction: HopDirection::ZeroForOne,
+        };
+        let single_hop_route = |shares: &[u16]| Route {
+            token_in: key.token0,
+            token_out: key.token1,
+            splits: shares
+                .iter()
+                .map(|&share| RouteSplit {
+                    share,
+                    hops: vec![hop],
+                })
+                .collect(),
+        };
+
+        let whole = quote_route(&pools, &single_hop_route(&[10_000]), 100_000, ()).unwrap();
+        let halves = quote_route(&pools, &single_hop_route(&[5_000, 5_000]), 100_000, ()).unwrap();
+
+        // the second half trades against the state left by the first half, so splitting only loses to rounding
+        assert_eq!(whole.amount_out, 90909);
+        assert_eq!(halves.amount_out, 90908);
+        assert!(halves.splits[1].amount_out() < halves.splits[0].amount_out());
+    }
+
+    #[test]
+    fn test_quote_route_errors() {
+        let pools = pools();
+
+        let mut invalid_shares = quotable_route();
+        invalid_shares.splits[0].share = 1;
+        assert_eq!(
+            quote_route(&pools, &invalid_shares, 1_000, ()),
+            Err(RouteQuoteError::InvalidShares)
+        );
+
+        let mut swapped = quotable_route();
+        swapped.splits[1].hops.swap(0, 1);
+        assert_eq!(
+            quote_route(&pools, &swapped, 1_000, ()),
+            Err(RouteQuoteError::InvalidPath { split: 1, hop: 0 })
+        );
+
+        let mut truncated = quotable_route();
+        truncated.splits[1].hops.pop();
+        assert_eq!(
+            quote_route(&pools, &truncated, 1_000, ()),
+            Err(RouteQuoteError::InvalidPath { split: 1, hop: 1 })
+        );
+
+        assert_eq!(
+            quote_route(&pools, &route(), 1_000, ()),
+            Err(RouteQuoteError::PoolNotFound { split: 0, hop: 0 })
+        );
+        assert_eq!(
+            quote_route(&pools, &quotable_route(), u128::MAX, ()),
+            Err(RouteQuoteError::AmountOverflow)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde_round_trip() {
+        let route = route();
+        let json = serde_json::to_value(&route).unwrap();
+
+        assert_eq!(json["version"], "1");
+        assert_eq!(json["splits"][1]["hops"][0]["direction"], "zero_for_one");
+        assert_eq!(serde_json::from_value::<Route>(json).unwrap(), route);
+    }
+}
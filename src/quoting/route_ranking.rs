@@ -0,0 +1,297 @@
+use crate::quoting::l1_data_fee::{L1DataFee, NoL1DataFee};
+use crate::quoting::pool_map::PoolMap;
+use crate::quoting::route::{quote_route, CalldataSize, Route, RouteQuote, RouteQuoteError};
+use crate::quoting::types::Pool;
+use alloc::vec::Vec;
+
+// Estimates the gas units used by a swap through a route from its shape and the size of its calldata
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GasModel {
+    // The gas of the transaction and the router, independent of the route
+    pub base: u64,
+    pub per_split: u64,
+    pub per_hop: u64,
+    // The gas per byte of calldata, which L2 deployments can raise to account for the cost of posting it to L1
+    pub per_zero_byte: u64,
+    pub per_nonzero_byte: u64,
+}
+
+impl Default for GasModel {
+    // Rough costs of swapping through the router, to be calibrated for the deployment
+    fn default() -> Self {
+        Self {
+            base: 50_000,
+            per_split: 10_000,
+            per_hop: 30_000,
+            // EIP-2028
+            per_zero_byte: 4,
+            per_nonzero_byte: 16,
+        }
+    }
+}
+
+impl GasModel {
+    pub fn calldata_gas(&self, calldata: CalldataSize) -> u64 {
+        self.per_zero_byte * calldata.zero_bytes as u64
+            + self.per_nonzero_byte * calldata.nonzero_bytes as u64
+    }
+
+    pub fn route_gas(&self, route: &Route) -> u64 {
+        let hops: u64 = route
+            .splits
+            .iter()
+            .map(|split| split.hops.len() as u64)
+            .sum();
+        self.base
+            + self.per_split * route.splits.len() as u64
+            + self.per_hop * hops
+            + self.calldata_gas(route.calldata_size().total)
+    }
+}
+
+// The fees of an EIP-1559 transaction and the chance that it is included in the next block at those fees
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Eip1559FeeModel {
+    // The base fee of the next block in wei per gas
+    pub base_fee: u128,
+    // The priority fee paid to the block builder in wei per gas
+    pub priority_fee: u128,
+    // The probability that the transaction is included in each block while it is pending, which grows with the
+    // priority fee
+    pub inclusion_probability: f64,
+}
+
+impl Eip1559FeeModel {
+    // The price paid per gas if the transaction is included
+    pub fn gas_price(&self) -> u128 {
+        self.base_fee.saturating_add(self.priority_fee)
+    }
+
+    // The expected number of blocks until the transaction is included
+    pub fn expected_inclusion_delay(&self) -> f64 {
+        1f64 / self.inclusion_probability
+    }
+
+    // The probability that the transaction is included within the given number of blocks
+    pub fn inclusion_probability_within(&self, blocks: u32) -> f64 {
+        let probability = self.inclusion_probability.clamp(0f64, 1f64);
+        1f64 - (1f64 - probability).powi(blocks as i32)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RankingParams<F = NoL1DataFee> {
+    pub gas: GasModel,
+    pub fees: Eip1559FeeModel,
+    // The fee for posting the calldata to L1 on rollups
+    pub l1_data_fee: F,
+    // The number of blocks after which the quote is stale and the swap reverts, e.g. because of its deadline or its
+    // minimum output
+    pub validity_blocks: u32,
+    // The amount of the output token that is worth one wei, for expressing the gas cost in the output token
+    pub output_per_wei: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RankedRoute<S> {
+    // The index of the route in the ranked slice
+    pub index: usize,
+    pub quote: RouteQuote<S>,
+    pub gas: u64,
+    pub calldata: CalldataSize,
+    // In wei, zero outside of rollups
+    pub l1_data_fee: u128,
+    // The cost of the gas and the L1 data fee in the output token
+    pub gas_cost: f64,
+    // The probability that the swap is included before the quote is stale
+    pub inclusion_probability: f64,
+    // The output that is received if the swap is included in time, minus the gas that is paid either way
+    pub expected_net_output: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteRanking<S, E> {
+    // Sorted by descending expected net output
+    pub ranked: Vec<RankedRoute<S>>,
+    // The routes that failed to quote, paired with their index
+    pub failed: Vec<(usize, RouteQuoteError<E>)>,
+}
+
+/// Quotes each of the routes and ranks them by their expected net output, from the best to the worst.
+///
+/// A route only pays out if the swap is included within `validity_blocks`, while its gas is paid whether it succeeds
+/// or reverts, so routes that use more gas are penalized by their full gas cost and the output of every route is
+/// discounted by the probability of inclusion in time. On rollups, the L1 data fee of the calldata of the route is paid
+/// on top of the gas.
+pub fn rank_routes<P: Pool, F: L1DataFee>(
+    pools: &PoolMap<P>,
+    routes: &[Route],
+    amount_in: u128,
+    meta: P::Meta,
+    params: &RankingParams<F>,
+) -> RouteRanking<P::State, P::QuoteError> {
+    let inclusion_probability = params
+        .fees
+        .inclusion_probability_within(params.validity_blocks);
+    let mut ranked = Vec::with_capacity(routes.len());
+    let mut failed = Vec::new();
+
+    for (index, route) in routes.iter().enumerate() {
+        match quote_route(pools, route, amount_in, meta) {
+            Ok(quote) => {
+                let gas = params.gas.route_gas(route);
+                let calldata = route.calldata_size().total;
+                let l1_data_fee = params.l1_data_fee.l1_data_fee(calldata);
+                let gas_cost = (gas as f64 * params.fees.gas_price() as f64 + l1_data_fee as f64)
+                    * params.output_per_wei;
+                ranked.push(RankedRoute {
+                    index,
+                    gas,
+                    calldata,
+                    l1_data_fee,
+                    gas_cost,
+                    inclusion_probability,
+                    expected_net_output: inclusion_probability * quote.amount_out as f64 - gas_cost,
+                    quote,
+                });
+            }
+            Err(error) => failed.push((index, error)),
+        }
+    }
+
+    ranked.sort_by(|a, b| b.expected_net_output.total_cmp(&a.expected_net_output));
+    RouteRanking { ranked, failed }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::uint::U256;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::l1_data_fee::ArbitrumL1DataFee;
+    use crate::quoting::route::{HopDirection, RouteHop, RouteSplit, ROUTE_SHARE_DENOMINATOR};
+    use crate::quoting::types::{Address, Config, NodeKey};
+    use alloc::vec;
+
+    fn key(token0: u64, token1: u64, fee: u64) -> NodeKey {
+        NodeKey {
+            token0: Address::from_raw(U256::from(token0)),
+            token1: Address::from_raw(U256::from(token1)),
+            config: Config::from_raw_unchecked(fee, 0, U256::zero()),
+        }
+    }
+
+    fn route(keys: &[NodeKey]) -> Route {
+        Route {
+            token_in: Address::from_raw(U256::from(1)),
+            token_out: Address::from_raw(U256::from(2)),
+            splits: vec![RouteSplit {
+                share: ROUTE_SHARE_DENOMINATOR,
+                hops: keys
+                    .iter()
+                    .map(|&pool_key| RouteHop {
+                        pool_key,
+                        direction: if pool_key.token0 == U256::from(1)
+                            || pool_key.token1 == U256::from(2)
+                        {
+                            HopDirection::ZeroForOne
+                        } else {
+                            HopDirection::OneForZero
+                        },
+                    })
+                    .collect(),
+            }],
+        }
+    }
+
+    fn params(base_fee: u128) -> RankingParams {
+        RankingParams {
+            gas: GasModel::default(),
+            fees: Eip1559FeeModel {
+                base_fee,
+                priority_fee: 1,
+                inclusion_probability: 0.5,
+            },
+            l1_data_fee: NoL1DataFee,
+            validity_blocks: 3,
+            output_per_wei: 1f64,
+        }
+    }
+
+    #[test]
+    fn test_gas_price_changes_best_route() {
+        // the direct pool charges a 1% fee, while the two hop route is free
+        let (direct, first, second) = (key(1, 2, 1 << 57), key(1, 3, 0), key(2, 3, 0));
+        let pools: PoolMap<FullRangePool> = [direct, first, second]
+            .into_iter()
+            .map(|key| {
+                FullRangePool::new(
+                    key,
+                    FullRangePoolState {
+                        sqrt_ratio: U256::one() << 128,
+                        liquidity: 1_000_000_000_000,
+                    },
+                )
+                .unwrap()
+            })
+            .collect();
+        let routes = [
+            route(&[direct]),
+            route(&[first, second]),
+            route(&[key(1, 2, 0)]),
+        ];
+
+        let ranking = rank_routes(&pools, &routes, 10_000_000, (), &params(0));
+        assert_eq!(
+            ranking.ranked.iter().map(|r| r.index).collect::<Vec<_>>(),
+            [1, 0]
+        );
+        assert_eq!(ranking.failed.len(), 1);
+        assert_eq!(ranking.failed[0].0, 2);
+
+        let best = &ranking.ranked[0];
+        assert_eq!(best.calldata, routes[1].calldata_size().total);
+        assert_eq!(best.gas, 50_000 + 10_000 + 2 * 30_000 + best.calldata.gas());
+        assert_eq!(best.inclusion_probability, 0.875);
+        assert_eq!(
+            best.expected_net_output,
+            0.875 * best.quote.amount_out as f64 - best.gas_cost
+        );
+
+        // the extra hop costs more than the fee it saves
+        let ranking = rank_routes(&pools, &routes, 10_000_000, (), &params(10));
+        assert_eq!(
+            ranking.ranked.iter().map(|r| r.index).collect::<Vec<_>>(),
+            [0, 1]
+        );
+
+        // so does the calldata of the extra hop on a rollup with expensive L1 data
+        let params = RankingParams {
+            gas: params(0).gas,
+            fees: params(0).fees,
+            l1_data_fee: ArbitrumL1DataFee {
+                l1_price_per_unit: 100,
+            },
+            validity_blocks: 3,
+            output_per_wei: 1f64,
+        };
+        let ranking = rank_routes(&pools, &routes, 10_000_000, (), &params);
+        assert_eq!(ranking.ranked[0].index, 0);
+        assert_eq!(
+            ranking.ranked[0].l1_data_fee,
+            100 * ranking.ranked[0].calldata.gas() as u128
+        );
+    }
+
+    #[test]
+    fn test_inclusion_probability() {
+        let fees = params(1).fees;
+        assert_eq!(fees.gas_price(), 2);
+        assert_eq!(fees.expected_inclusion_delay(), 2f64);
+        assert_eq!(fees.inclusion_probability_within(0), 0f64);
+        assert_eq!(fees.inclusion_probability_within(1), 0.5);
+        assert_eq!(fees.inclusion_probability_within(2), 0.75);
+    }
+}
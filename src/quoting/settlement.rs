@@ -0,0 +1,209 @@
+use crate::quoting::pool_map::PoolMap;
+use crate::quoting::route::{quote_route, Route, RouteDecodingError, RouteQuote, RouteQuoteError};
+use crate::quoting::types::{Pool, TokenAmount};
+use alloc::vec::Vec;
+
+// What the swapper expects from submitting the encoded route
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SettlementExpectation {
+    pub amount_in: u128,
+    // The minimum output the swap is submitted with
+    pub min_amount_out: u128,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedSettlement<S> {
+    pub route: Route,
+    pub quote: RouteQuote<S>,
+    // The net amount of each token paid by the swapper, negative for the tokens it receives
+    pub deltas: Vec<TokenAmount>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SettlementError<E> {
+    /// The calldata could not be decoded.
+    Decoding(RouteDecodingError),
+    /// Encoding the decoded route does not reproduce the calldata.
+    EncodingMismatch,
+    /// The decoded route is not the route that was intended to be encoded.
+    RouteMismatch,
+    /// The decoded route could not be quoted against the current states of the pools.
+    QuoteFailed(RouteQuoteError<E>),
+    /// The route no longer outputs the minimum amount, e.g. because the quote it was built from is stale.
+    InsufficientOutput { amount_out: u128 },
+    /// A delta does not fit in an i128.
+    AmountOverflow,
+}
+
+/// Decodes the calldata of a route, checks that it encodes the intended route, and re-quotes it against the current
+/// states of the pools to verify the swap still meets its minimum output before it is submitted.
+///
+/// Re-encoding the decoded route must reproduce the calldata byte for byte, which catches drift between the encoder
+/// and the decoder. If `intended` is given, the decoded route must also be equal to it.
+pub fn verify_settlement<P: Pool>(
+    pools: &PoolMap<P>,
+    calldata: &[u8],
+    intended: Option<&Route>,
+    expectation: SettlementExpectation,
+    meta: P::Meta,
+) -> Result<VerifiedSettlement<P::State>, SettlementError<P::QuoteError>> {
+    let route = Route::decode(calldata).map_err(SettlementError::Decoding)?;
+    if route.encode().ok().as_deref() != Some(calldata) {
+        return Err(SettlementError::EncodingMismatch);
+    }
+    if intended.is_some_and(|intended| *intended != route) {
+        return Err(SettlementError::RouteMismatch);
+    }
+
+    let quote = quote_route(pools, &route, expectation.amount_in, meta)
+        .map_err(SettlementError::QuoteFailed)?;
+    if quote.amount_out < expectation.min_amount_out {
+        return Err(SettlementError::InsufficientOutput {
+            amount_out: quote.amount_out,
+        });
+    }
+
+    let amount_in =
+        i128::try_from(expectation.amount_in).map_err(|_| SettlementError::AmountOverflow)?;
+    let amount_out =
+        i128::try_from(quote.amount_out).map_err(|_| SettlementError::AmountOverflow)?;
+    let deltas = if route.token_in == route.token_out {
+        alloc::vec![TokenAmount {
+            token: route.token_in,
+            amount: amount_in - amount_out,
+        }]
+    } else {
+        alloc::vec![
+            TokenAmount {
+                token: route.token_in,
+                amount: amount_in,
+            },
+            TokenAmount {
+                token: route.token_out,
+                amount: -amount_out,
+            },
+        ]
+    };
+
+    Ok(VerifiedSettlement {
+        route,
+        quote,
+        deltas,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::uint::U256;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::route::{HopDirection, RouteHop, RouteSplit, ROUTE_SHARE_DENOMINATOR};
+    use crate::quoting::types::{Address, Config, NodeKey};
+    use alloc::vec;
+
+    fn key() -> NodeKey {
+        NodeKey {
+            token0: Address::from_raw(U256::from(1)),
+            token1: Address::from_raw(U256::from(2)),
+            config: Config::from_raw_unchecked(0, 0, U256::zero()),
+        }
+    }
+
+    fn pools() -> PoolMap<FullRangePool> {
+        [FullRangePool::new(
+            key(),
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 1_000_000,
+            },
+        )
+        .unwrap()]
+        .into_iter()
+        .collect()
+    }
+
+    fn route() -> Route {
+        Route {
+            token_in: Address::from_raw(U256::from(1)),
+            token_out: Address::from_raw(U256::from(2)),
+            splits: vec![RouteSplit {
+                share: ROUTE_SHARE_DENOMINATOR,
+                hops: vec![RouteHop {
+                    pool_key: key(),
+                    direction: HopDirection::ZeroForOne,
+                }],
+            }],
+        }
+    }
+
+    const EXPECTATION: SettlementExpectation = SettlementExpectation {
+        amount_in: 1_000,
+        min_amount_out: 990,
+    };
+
+    #[test]
+    fn test_verify_settlement() {
+        let route = route();
+        let calldata = route.encode().unwrap();
+
+        let verified =
+            verify_settlement(&pools(), &calldata, Some(&route), EXPECTATION, ()).unwrap();
+        assert_eq!(verified.route, route);
+        assert_eq!(
+            verified.quote,
+            quote_route(&pools(), &route, 1_000, ()).unwrap()
+        );
+        assert_eq!(
+            verified.deltas,
+            [
+                TokenAmount {
+                    token: Address::from_raw(U256::from(1)),
+                    amount: 1_000
+                },
+                TokenAmount {
+                    token: Address::from_raw(U256::from(2)),
+                    amount: -(verified.quote.amount_out as i128)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_settlement_errors() {
+        let pools = pools();
+        let route = route();
+        let calldata = route.encode().unwrap();
+
+        assert_eq!(
+            verify_settlement(&pools, &calldata[1..], None, EXPECTATION, ()),
+            Err(SettlementError::Decoding(
+                RouteDecodingError::UnsupportedVersion(0)
+            ))
+        );
+
+        let mut other = route.clone();
+        other.splits[0].hops[0].direction = HopDirection::OneForZero;
+        assert_eq!(
+            verify_settlement(&pools, &calldata, Some(&other), EXPECTATION, ()),
+            Err(SettlementError::RouteMismatch)
+        );
+
+        // the price moved since the minimum output was computed
+        let quote = quote_route(&pools, &route, 1_000, ()).unwrap();
+        assert_eq!(
+            verify_settlement(
+                &pools,
+                &calldata,
+                None,
+                SettlementExpectation {
+                    amount_in: 1_000,
+                    min_amount_out: quote.amount_out + 1,
+                },
+                ()
+            ),
+            Err(SettlementError::InsufficientOutput {
+                amount_out: quote.amount_out
+            })
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::math::uint::U256;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SignedAmountError {
+    /// The amount does not fit in the target type.
+    Overflow,
+}
+
+// A signed amount as a magnitude and a sign, like the amounts of the core contracts, so amounts up to u128::MAX of either
+// sign are representable. Zero is never negative, so each amount has exactly one representation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SignedAmount {
+    mag: u128,
+    negative: bool,
+}
+
+impl SignedAmount {
+    pub const ZERO: SignedAmount = SignedAmount {
+        mag: 0,
+        negative: false,
+    };
+
+    pub const fn new(mag: u128, negative: bool) -> Self {
+        Self {
+            mag,
+            negative: negative && mag != 0,
+        }
+    }
+
+    pub const fn mag(self) -> u128 {
+        self.mag
+    }
+
+    pub const fn is_negative(self) -> bool {
+        self.negative
+    }
+
+    // Encodes the amount as a 32 byte two's complement word, as it is passed in calldata
+    pub fn to_word(self) -> U256 {
+        let mag = U256::from(self.mag);
+        if self.negative {
+            (!mag).overflowing_add(U256::one()).0
+        } else {
+            mag
+        }
+    }
+
+    // Decodes a 32 byte two's complement word, which must be the sign extension of an amount
+    pub fn from_word(word: U256) -> Result<Self, SignedAmountError> {
+        let negative = word.bit(255);
+        let mag = if negative {
+            (!word).overflowing_add(U256::one()).0
+        } else {
+            word
+        };
+
+        if mag > U256::from(u128::MAX) {
+            return Err(SignedAmountError::Overflow);
+        }
+        Ok(Self::new(mag.low_u128(), negative))
+    }
+}
+
+impl From<i128> for SignedAmount {
+    fn from(value: i128) -> Self {
+        Self::new(value.unsigned_abs(), value < 0)
+    }
+}
+
+impl TryFrom<SignedAmount> for i128 {
+    type Error = SignedAmountError;
+
+    fn try_from(value: SignedAmount) -> Result<Self, Self::Error> {
+        if value.negative {
+            0i128.checked_sub_unsigned(value.mag)
+        } else {
+            i128::try_from(value.mag).ok()
+        }
+        .ok_or(SignedAmountError::Overflow)
+    }
+}
+
+impl From<SignedAmount> for U256 {
+    fn from(value: SignedAmount) -> Self {
+        value.to_word()
+    }
+}
+
+impl TryFrom<U256> for SignedAmount {
+    type Error = SignedAmountError;
+
+    fn try_from(value: U256) -> Result<Self, Self::Error> {
+        Self::from_word(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_i128_conversions() {
+        for value in [0, 1, -1, i128::MAX, i128::MIN] {
+            assert_eq!(i128::try_from(SignedAmount::from(value)), Ok(value));
+        }
+        assert_eq!(
+            i128::try_from(SignedAmount::new(u128::MAX, false)),
+            Err(SignedAmountError::Overflow)
+        );
+        assert_eq!(SignedAmount::new(0, true), SignedAmount::ZERO);
+        assert!(!SignedAmount::new(0, true).is_negative());
+    }
+
+    #[test]
+    fn test_word_conversions() {
+        assert_eq!(SignedAmount::from(-1).to_word(), U256::MAX);
+        assert_eq!(SignedAmount::from(5).to_word(), U256::from(5));
+
+        for amount in [
+            SignedAmount::ZERO,
+            SignedAmount::from(i128::MIN),
+            SignedAmount::new(u128::MAX, true),
+            SignedAmount::new(u128::MAX, false),
+        ] {
+            assert_eq!(SignedAmount::try_from(U256::from(amount)), Ok(amount));
+        }
+
+        assert_eq!(
+            SignedAmount::from_word(U256::from(u128::MAX) + 1),
+            Err(SignedAmountError::Overflow)
+        );
+        assert_eq!(
+            SignedAmount::from_word(U256::MAX << 129),
+            Err(SignedAmountError::Overflow)
+        );
+    }
+}
@@ -0,0 +1,424 @@
+use crate::math::muldiv::muldiv;
+use crate::math::uint::U256;
+use crate::quoting::pool_map::PoolMap;
+use crate::quoting::route::{
+    quote_route_with_states, HopDirection, Route, RouteHop, RouteSplit, ROUTE_SHARE_DENOMINATOR,
+};
+use crate::quoting::types::{Address, NodeKey, Pool, SqrtRatioState};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+const TWO_POW_128: U256 = U256([0, 0, 1, 0]);
+
+// An order to sell an amount of a token for at least an amount of another token, which may be partially filled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Order {
+    pub sell_token: Address,
+    pub buy_token: Address,
+    pub sell_amount: u128,
+    // The limit price of the order is min_buy_amount / sell_amount, which every part of a fill must meet
+    pub min_buy_amount: u128,
+}
+
+impl Order {
+    pub fn limit_price(&self) -> f64 {
+        self.min_buy_amount as f64 / self.sell_amount as f64
+    }
+
+    // Whether selling the amount for the amount bought meets the limit price
+    pub(crate) fn meets_limit(&self, sell_amount: u128, buy_amount: u128) -> bool {
+        U256::from(buy_amount) * U256::from(self.sell_amount)
+            >= U256::from(sell_amount) * U256::from(self.min_buy_amount)
+    }
+}
+
+// The part of an order that is swapped through the pools
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoutedFill {
+    pub route: Route,
+    pub sell_amount: u128,
+    pub buy_amount: u128,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OrderFill {
+    // The part of the order that is matched with orders in the opposite direction, at the reference price of the pair
+    pub matched_sell_amount: u128,
+    pub matched_buy_amount: u128,
+    pub routed: Option<RoutedFill>,
+}
+
+impl OrderFill {
+    pub fn sell_amount(&self) -> u128 {
+        self.matched_sell_amount + self.routed.as_ref().map_or(0, |routed| routed.sell_amount)
+    }
+
+    pub fn buy_amount(&self) -> u128 {
+        self.matched_buy_amount + self.routed.as_ref().map_or(0, |routed| routed.buy_amount)
+    }
+}
+
+// Orders of a pair in opposite directions that are settled against each other instead of through the pools
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoincidenceOfWants {
+    // token0 and token1 of the pair, as in the pool keys
+    pub token0: Address,
+    pub token1: Address,
+    // The price of token1 in token0 that the orders are matched at, from the spot price of a pool of the pair
+    pub sqrt_ratio: U256,
+    pub amount0: u128,
+    pub amount1: u128,
+    pub orders: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Solution {
+    // The fill of each order, in the same order as the orders
+    pub fills: Vec<OrderFill>,
+    pub coincidences: Vec<CoincidenceOfWants>,
+}
+
+// Converts the amount of one token of the pair to the other at the sqrt ratio, rounding down
+pub(crate) fn convert(amount: u128, sqrt_ratio: U256, from_token0: bool) -> u128 {
+    let (numerator, denominator) = if from_token0 {
+        (sqrt_ratio, TWO_POW_128)
+    } else {
+        (TWO_POW_128, sqrt_ratio)
+    };
+    muldiv(U256::from(amount), numerator, denominator, false)
+        .and_then(|once| muldiv(once, numerator, denominator, false))
+        .map_or(u128::MAX, |converted| {
+            if converted > U256::from(u128::MAX) {
+                u128::MAX
+            } else {
+                converted.as_u128()
+            }
+        })
+}
+
+// Splits the total proportionally to the weights, assigning the rounding remainder to the last weight
+pub(crate) fn pro_rata(total: u128, weights: &[u128]) -> Vec<u128> {
+    let sum: U256 = weights
+        .iter()
+        .map(|&weight| U256::from(weight))
+        .fold(U256::zero(), |a, b| a + b);
+    let mut remaining = total;
+    let mut shares: Vec<u128> = weights
+        .iter()
+        .map(|&weight| {
+            let share = (U256::from(total) * U256::from(weight) / sum).as_u128();
+            remaining -= share;
+            share
+        })
+        .collect();
+    if let Some(last) = shares.last_mut() {
+        *last += remaining;
+    }
+    shares
+}
+
+/// Solves a batch of orders by matching orders of the same pair in opposite directions against each other, and
+/// routing the rest of each order through the pools.
+///
+/// Orders of a pair are matched at the spot price of the first pool of the pair with liquidity, and only the orders
+/// whose limit price is met at that price take part. The side of the pair with less volume is matched completely and
+/// the matched volume of the other side is split proportionally to the sell amounts. The rest of each order is routed
+/// through a direct pool or two pools via one of the intermediate tokens, whichever gives the most, and only if it
+/// meets the limit price of the order. Orders are routed one after another, so they see each other's price impact.
+pub fn solve<P: Pool>(
+    pools: &PoolMap<P>,
+    orders: &[Order],
+    intermediate_tokens: &[Address],
+    meta: P::Meta,
+) -> Solution
+where
+    P::State: SqrtRatioState,
+{
+    let mut fills: Vec<OrderFill> = alloc::vec![OrderFill::default(); orders.len()];
+    let mut coincidences = Vec::new();
+
+    let mut pairs: BTreeMap<(Address, Address), Vec<usize>> = BTreeMap::new();
+    for (index, order) in orders.iter().enumerate() {
+        if order.sell_token != order.buy_token && order.sell_amount != 0 {
+            let pair = if order.sell_token < order.buy_token {
+                (order.sell_token, order.buy_token)
+            } else {
+                (order.buy_token, order.sell_token)
+            };
+            pairs.entry(pair).or_default().push(index);
+        }
+    }
+
+    for ((token0, token1), indices) in pairs {
+        let Some(sqrt_ratio) = pools
+            .pools_for_pair(token0, token1)
+            .find(|pool| pool.has_liquidity())
+            .map(|pool| pool.get_state().sqrt_ratio())
+        else {
+            continue;
+        };
+
+        let eligible = |sells_token0: bool| -> Vec<usize> {
+            indices
+                .iter()
+                .copied()
+                .filter(|&index| {
+                    let order = &orders[index];
+                    (order.sell_token == token0) == sells_token0
+                        && order.meets_limit(
+                            order.sell_amount,
+                            convert(order.sell_amount, sqrt_ratio, sells_token0),
+                        )
+                })
+                .collect()
+        };
+        let (sellers0, sellers1) = (eligible(true), eligible(false));
+        let volume = |sellers: &[usize]| -> u128 {
+            sellers.iter().fold(0u128, |total, &index| {
+                total.saturating_add(orders[index].sell_amount)
+            })
+        };
+        let (volume0, volume1) = (volume(&sellers0), volume(&sellers1));
+        if volume0 == 0 || volume1 == 0 {
+            continue;
+        }
+
+        // the matched amounts of each token, limited by the side with less volume at the reference price
+        let (amount0, amount1) = if volume0 <= convert(volume1, sqrt_ratio, false) {
+            (volume0, convert(volume0, sqrt_ratio, true).min(volume1))
+        } else {
+            (convert(volume1, sqrt_ratio, false), volume1)
+        };
+
+        for (sellers, sold, bought) in
+            [(&sellers0, amount0, amount1), (&sellers1, amount1, amount0)]
+        {
+            let weights: Vec<u128> = sellers
+                .iter()
+                .map(|&index| orders[index].sell_amount)
+                .collect();
+            for ((&index, sell), buy) in sellers
+                .iter()
+                .zip(pro_rata(sold, &weights))
+                .zip(pro_rata(bought, &weights))
+            {
+                fills[index].matched_sell_amount = sell;
+                fills[index].matched_buy_amount = buy;
+            }
+        }
+
+        coincidences.push(CoincidenceOfWants {
+            token0,
+            token1,
+            sqrt_ratio,
+            amount0,
+            amount1,
+            orders: sellers0.iter().chain(&sellers1).copied().collect(),
+        });
+    }
+
+    let mut states = BTreeMap::new();
+    for (order, fill) in orders.iter().zip(fills.iter_mut()) {
+        let remaining = order.sell_amount - fill.matched_sell_amount;
+        if remaining == 0 || order.sell_token == order.buy_token {
+            continue;
+        }
+
+        let mut best: Option<(RoutedFill, BTreeMap<NodeKey, P::State>)> = None;
+        for route in candidate_routes(
+            pools,
+            order.sell_token,
+            order.buy_token,
+            intermediate_tokens,
+        ) {
+            let mut route_states = states.clone();
+            if let Ok(quote) =
+                quote_route_with_states(pools, &route, remaining, meta, &mut route_states)
+            {
+                if order.meets_limit(remaining, quote.amount_out)
+                    && best
+                        .as_ref()
+                        .is_none_or(|(routed, _)| quote.amount_out > routed.buy_amount)
+                {
+                    best = Some((
+                        RoutedFill {
+                            route,
+                            sell_amount: remaining,
+                            buy_amount: quote.amount_out,
+                        },
+                        route_states,
+                    ));
+                }
+            }
+        }
+
+        if let Some((routed, route_states)) = best {
+            states = route_states;
+            fill.routed = Some(routed);
+        }
+    }
+
+    Solution {
+        fills,
+        coincidences,
+    }
+}
+
+fn hop(key: NodeKey, token_in: Address) -> RouteHop {
+    RouteHop {
+        pool_key: key,
+        direction: if key.token0 == token_in {
+            HopDirection::ZeroForOne
+        } else {
+            HopDirection::OneForZero
+        },
+    }
+}
+
+// The routes through a single pool of the pair or two pools via one of the intermediate tokens
+fn candidate_routes<'a, P: Pool>(
+    pools: &'a PoolMap<P>,
+    token_in: Address,
+    token_out: Address,
+    intermediate_tokens: &'a [Address],
+) -> impl Iterator<Item = Route> + 'a {
+    let route = move |hops: Vec<RouteHop>| Route {
+        token_in,
+        token_out,
+        splits: alloc::vec![RouteSplit {
+            share: ROUTE_SHARE_DENOMINATOR,
+            hops,
+        }],
+    };
+
+    let direct = pools
+        .pools_for_pair(token_in, token_out)
+        .map(move |pool| route(alloc::vec![hop(*pool.get_key(), token_in)]));
+    let two_hop = intermediate_tokens
+        .iter()
+        .filter(move |&&intermediate| intermediate != token_in && intermediate != token_out)
+        .flat_map(move |&intermediate| {
+            pools
+                .pools_for_pair(token_in, intermediate)
+                .flat_map(move |first| {
+                    pools
+                        .pools_for_pair(intermediate, token_out)
+                        .map(move |second| {
+                            route(alloc::vec![
+                                hop(*first.get_key(), token_in),
+                                hop(*second.get_key(), intermediate),
+                            ])
+                        })
+                })
+        });
+
+    direct.chain(two_hop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::to_sqrt_ratio;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::route::quote_route;
+    use crate::quoting::types::Config;
+
+    const A: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const B: Address = Address::from_raw(U256([2, 0, 0, 0]));
+    const C: Address = Address::from_raw(U256([3, 0, 0, 0]));
+
+    fn pool(token0: Address, token1: Address, sqrt_ratio: U256) -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0,
+                token1,
+                config: Config::from_raw_unchecked(1 << 54, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio,
+                liquidity: 1_000_000_000,
+            },
+        )
+        .unwrap()
+    }
+
+    fn order(
+        sell_token: Address,
+        buy_token: Address,
+        sell_amount: u128,
+        min_buy_amount: u128,
+    ) -> Order {
+        Order {
+            sell_token,
+            buy_token,
+            sell_amount,
+            min_buy_amount,
+        }
+    }
+
+    #[test]
+    fn test_coincidence_of_wants() {
+        // B is worth twice as much as A
+        let two = to_sqrt_ratio(693_147).unwrap();
+        let pools: PoolMap<FullRangePool> = [pool(A, B, two)].into_iter().collect();
+        let orders = [
+            order(A, B, 10_000, 19_000),
+            order(B, A, 4_000, 1_900),
+            order(B, A, 2_000, 950),
+            // the limit is not met at the reference price
+            order(B, A, 1_000, 600),
+        ];
+
+        let solution = solve(&pools, &orders, &[], ());
+        assert_eq!(solution.coincidences.len(), 1);
+        let coincidence = &solution.coincidences[0];
+        assert_eq!(coincidence.orders, [0, 1, 2]);
+        assert_eq!(coincidence.amount1, 6_000);
+        assert_eq!(coincidence.amount0, convert(6_000, two, false));
+
+        // the sellers of B are matched completely, and the rest of the A seller is routed
+        assert_eq!(solution.fills[1].sell_amount(), 4_000);
+        assert_eq!(solution.fills[1].routed, None);
+        assert_eq!(
+            solution.fills[1].matched_buy_amount + solution.fills[2].matched_buy_amount,
+            coincidence.amount0
+        );
+        let fill = &solution.fills[0];
+        assert_eq!(fill.matched_sell_amount, coincidence.amount0);
+        assert_eq!(fill.matched_buy_amount, 6_000);
+        let routed = fill.routed.as_ref().unwrap();
+        assert_eq!(routed.sell_amount, 10_000 - coincidence.amount0);
+        assert!(orders[0].meets_limit(fill.sell_amount(), fill.buy_amount()));
+
+        // the limit of the last order is not met by the pool either
+        assert_eq!(solution.fills[3], OrderFill::default());
+    }
+
+    #[test]
+    fn test_routes_through_intermediate_tokens() {
+        let one = U256::one() << 128;
+        let pools: PoolMap<FullRangePool> =
+            [pool(A, C, one), pool(B, C, one)].into_iter().collect();
+        let orders = [order(A, B, 1_000, 990), order(A, B, 1_000, 1_000)];
+
+        let solution = solve(&pools, &orders, &[C], ());
+        assert!(solution.coincidences.is_empty());
+
+        let routed = solution.fills[0].routed.as_ref().unwrap();
+        assert_eq!(routed.route.splits[0].hops.len(), 2);
+        assert_eq!(
+            routed.buy_amount,
+            quote_route(&pools, &routed.route, 1_000, ())
+                .unwrap()
+                .amount_out
+        );
+        // the fees make the second order unfillable at its limit
+        assert_eq!(solution.fills[1], OrderFill::default());
+
+        assert_eq!(
+            solve(&pools, &orders, &[], ()).fills[0],
+            OrderFill::default()
+        );
+    }
+}
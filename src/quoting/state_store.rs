@@ -0,0 +1,159 @@
+#[cfg(feature = "sled")]
+mod sled_store;
+
+#[cfg(feature = "sled")]
+pub use sled_store::{SledStateStore, SledStateStoreError};
+
+use crate::quoting::pool_map::PoolMap;
+use crate::quoting::types::{NodeKey, Pool};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+// Persists snapshots of pools by key and block number, so a service can restart from the stored state instead of
+// fetching and indexing all the pools again
+pub trait StateStore<P> {
+    type Error;
+
+    // Returns the latest snapshot of the pool stored at or before the block, together with the block it was stored at
+    fn get(&self, key: &NodeKey, block_number: u64) -> Result<Option<(u64, P)>, Self::Error>;
+
+    // Stores the snapshot of the pool at the block, replacing any snapshot stored for the same pool and block
+    fn put(&mut self, block_number: u64, pool: &P) -> Result<(), Self::Error>;
+
+    // Returns the keys of all the pools that have at least one snapshot, in ascending order
+    fn keys(&self) -> Result<Vec<NodeKey>, Self::Error>;
+}
+
+// Stores a snapshot of every pool in the map at the block
+pub fn save_pool_map<P: Pool, S: StateStore<P>>(
+    store: &mut S,
+    pools: &PoolMap<P>,
+    block_number: u64,
+) -> Result<(), S::Error> {
+    pools
+        .iter()
+        .try_for_each(|pool| store.put(block_number, pool))
+}
+
+// Loads the latest snapshot at or before the block of every stored pool. Pools with no snapshot at or before the
+// block are not included.
+pub fn load_pool_map<P: Pool, S: StateStore<P>>(
+    store: &S,
+    block_number: u64,
+) -> Result<PoolMap<P>, S::Error> {
+    let mut pools = PoolMap::new();
+    for key in store.keys()? {
+        if let Some((_, pool)) = store.get(&key, block_number)? {
+            pools.insert(pool);
+        }
+    }
+    Ok(pools)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InMemoryStateStore<P> {
+    snapshots: BTreeMap<(NodeKey, u64), P>,
+}
+
+impl<P> Default for InMemoryStateStore<P> {
+    fn default() -> Self {
+        Self {
+            snapshots: BTreeMap::new(),
+        }
+    }
+}
+
+impl<P> InMemoryStateStore<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns the number of stored snapshots
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
+
+impl<P: Pool + Clone> StateStore<P> for InMemoryStateStore<P> {
+    type Error = Infallible;
+
+    fn get(&self, key: &NodeKey, block_number: u64) -> Result<Option<(u64, P)>, Self::Error> {
+        Ok(self
+            .snapshots
+            .range((*key, 0)..=(*key, block_number))
+            .next_back()
+            .map(|((_, block_number), pool)| (*block_number, pool.clone())))
+    }
+
+    fn put(&mut self, block_number: u64, pool: &P) -> Result<(), Self::Error> {
+        self.snapshots
+            .insert((*pool.get_key(), block_number), pool.clone());
+        Ok(())
+    }
+
+    fn keys(&self) -> Result<Vec<NodeKey>, Self::Error> {
+        let mut keys: Vec<NodeKey> = self.snapshots.keys().map(|(key, _)| *key).collect();
+        keys.dedup();
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::uint::U256;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::types::{Address, Config};
+
+    fn pool(token1: u64, liquidity: u128) -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0: Address::from_raw(U256::one()),
+                token1: Address::from_raw(U256::from(token1)),
+                config: Config::from_raw_unchecked(0, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_get_returns_latest_snapshot_at_or_before_block() {
+        let mut store = InMemoryStateStore::new();
+        store.put(10, &pool(2, 100)).unwrap();
+        store.put(20, &pool(2, 200)).unwrap();
+
+        let key = *pool(2, 0).get_key();
+        assert_eq!(store.get(&key, 9).unwrap(), None);
+        assert_eq!(store.get(&key, 10).unwrap(), Some((10, pool(2, 100))));
+        assert_eq!(store.get(&key, 19).unwrap(), Some((10, pool(2, 100))));
+        assert_eq!(store.get(&key, u64::MAX).unwrap(), Some((20, pool(2, 200))));
+        assert_eq!(store.get(pool(3, 0).get_key(), u64::MAX).unwrap(), None);
+    }
+
+    #[test]
+    fn test_save_and_load_pool_map() {
+        let mut store = InMemoryStateStore::new();
+        let pools: PoolMap<FullRangePool> = [pool(2, 100), pool(3, 100)].into_iter().collect();
+        save_pool_map(&mut store, &pools, 10).unwrap();
+
+        let mut updated = pools.clone();
+        updated.insert(pool(2, 500));
+        updated.insert(pool(4, 100));
+        save_pool_map(&mut store, &updated, 20).unwrap();
+
+        assert_eq!(store.len(), 5);
+        assert_eq!(store.keys().unwrap().len(), 3);
+        assert_eq!(load_pool_map(&store, 15).unwrap(), pools);
+        assert_eq!(load_pool_map(&store, 20).unwrap(), updated);
+        assert!(load_pool_map(&store, 5).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,234 @@
+use crate::math::uint::U256;
+use crate::quoting::state_store::StateStore;
+use crate::quoting::types::{Address, Config, NodeKey, Pool};
+use alloc::vec::Vec;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+// token0, token1, fee, tick spacing and extension
+const NODE_KEY_LENGTH: usize = 32 + 32 + 8 + 4 + 32;
+
+#[derive(Debug)]
+pub enum SledStateStoreError {
+    /// The underlying database returned an error.
+    Sled(sled::Error),
+    /// A snapshot could not be encoded or decoded.
+    Encoding(serde_json::Error),
+    /// A key in the tree is not the key of a snapshot, e.g. because the tree is shared with other data.
+    InvalidKey(Vec<u8>),
+}
+
+impl From<sled::Error> for SledStateStoreError {
+    fn from(value: sled::Error) -> Self {
+        SledStateStoreError::Sled(value)
+    }
+}
+
+impl From<serde_json::Error> for SledStateStoreError {
+    fn from(value: serde_json::Error) -> Self {
+        SledStateStoreError::Encoding(value)
+    }
+}
+
+// Stores pool snapshots as JSON in a sled tree, keyed by the big endian encoding of the pool key followed by the
+// block number so that the snapshots of a pool are sorted by block
+#[derive(Clone, Debug)]
+pub struct SledStateStore {
+    tree: sled::Tree,
+}
+
+fn encode_node_key(key: &NodeKey) -> [u8; NODE_KEY_LENGTH] {
+    let mut bytes = [0; NODE_KEY_LENGTH];
+    bytes[0..32].copy_from_slice(&key.token0.raw().to_big_endian());
+    bytes[32..64].copy_from_slice(&key.token1.raw().to_big_endian());
+    bytes[64..72].copy_from_slice(&key.config.fee.raw().to_be_bytes());
+    bytes[72..76].copy_from_slice(&key.config.tick_spacing.raw().to_be_bytes());
+    bytes[76..108].copy_from_slice(&key.config.extension.to_big_endian());
+    bytes
+}
+
+fn decode_node_key(bytes: &[u8]) -> Result<NodeKey, SledStateStoreError> {
+    if bytes.len() != NODE_KEY_LENGTH {
+        return Err(SledStateStoreError::InvalidKey(bytes.to_vec()));
+    }
+
+    let mut fee = [0; 8];
+    fee.copy_from_slice(&bytes[64..72]);
+    let mut tick_spacing = [0; 4];
+    tick_spacing.copy_from_slice(&bytes[72..76]);
+
+    let token = |word: &[u8]| {
+        Address::new(U256::from_big_endian(word))
+            .map_err(|_| SledStateStoreError::InvalidKey(bytes.to_vec()))
+    };
+    Ok(NodeKey {
+        token0: token(&bytes[0..32])?,
+        token1: token(&bytes[32..64])?,
+        config: Config::from_raw(
+            u64::from_be_bytes(fee),
+            u32::from_be_bytes(tick_spacing),
+            U256::from_big_endian(&bytes[76..108]),
+        )
+        .map_err(|_| SledStateStoreError::InvalidKey(bytes.to_vec()))?,
+    })
+}
+
+fn encode_snapshot_key(key: &NodeKey, block_number: u64) -> [u8; NODE_KEY_LENGTH + 8] {
+    let mut bytes = [0; NODE_KEY_LENGTH + 8];
+    bytes[..NODE_KEY_LENGTH].copy_from_slice(&encode_node_key(key));
+    bytes[NODE_KEY_LENGTH..].copy_from_slice(&block_number.to_be_bytes());
+    bytes
+}
+
+fn decode_snapshot_key(bytes: &[u8]) -> Result<(NodeKey, u64), SledStateStoreError> {
+    if bytes.len() != NODE_KEY_LENGTH + 8 {
+        return Err(SledStateStoreError::InvalidKey(bytes.to_vec()));
+    }
+
+    let mut block_number = [0; 8];
+    block_number.copy_from_slice(&bytes[NODE_KEY_LENGTH..]);
+    Ok((
+        decode_node_key(&bytes[..NODE_KEY_LENGTH])?,
+        u64::from_be_bytes(block_number),
+    ))
+}
+
+impl SledStateStore {
+    pub fn new(tree: sled::Tree) -> Self {
+        Self { tree }
+    }
+
+    // Flushes the written snapshots to disk
+    pub fn flush(&self) -> Result<(), SledStateStoreError> {
+        self.tree.flush()?;
+        Ok(())
+    }
+}
+
+impl<P: Pool + Serialize + DeserializeOwned> StateStore<P> for SledStateStore {
+    type Error = SledStateStoreError;
+
+    fn get(&self, key: &NodeKey, block_number: u64) -> Result<Option<(u64, P)>, Self::Error> {
+        match self
+            .tree
+            .range(encode_snapshot_key(key, 0)..=encode_snapshot_key(key, block_number))
+            .next_back()
+            .transpose()?
+        {
+            Some((snapshot_key, value)) => {
+                let (_, block_number) = decode_snapshot_key(&snapshot_key)?;
+                Ok(Some((block_number, serde_json::from_slice(&value)?)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn put(&mut self, block_number: u64, pool: &P) -> Result<(), Self::Error> {
+        self.tree.insert(
+            encode_snapshot_key(pool.get_key(), block_number),
+            serde_json::to_vec(pool)?,
+        )?;
+        Ok(())
+    }
+
+    fn keys(&self) -> Result<Vec<NodeKey>, Self::Error> {
+        let mut keys: Vec<NodeKey> = Vec::new();
+        for entry in self.tree.iter().keys() {
+            let (key, _) = decode_snapshot_key(&entry?)?;
+            if keys.last() != Some(&key) {
+                keys.push(key);
+            }
+        }
+        Ok(keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::pool_map::PoolMap;
+    use crate::quoting::state_store::{load_pool_map, save_pool_map};
+    use crate::quoting::types::Address;
+
+    fn pool(token1: Address, liquidity: u128) -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0: Address::from_raw(U256::one()),
+                token1,
+                config: Config::from_raw_unchecked(1 << 63, 0, U256::MAX),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_node_key_round_trip() {
+        let key = *pool(Address::from_raw(U256::MAX), 0).get_key();
+        assert_eq!(decode_node_key(&encode_node_key(&key)).unwrap(), key);
+        assert_eq!(
+            decode_snapshot_key(&encode_snapshot_key(&key, 7)).unwrap(),
+            (key, 7)
+        );
+
+        // tokens that do not fit in an address and invalid tick spacings are rejected
+        for index in [0, 72] {
+            let mut bytes = encode_node_key(&key);
+            bytes[index] = 0xff;
+            assert!(matches!(
+                decode_node_key(&bytes),
+                Err(SledStateStoreError::InvalidKey(key)) if key == bytes
+            ));
+        }
+    }
+
+    #[test]
+    fn test_foreign_keys() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let tree = db.open_tree("pools").unwrap();
+        tree.insert(b"version", b"1").unwrap();
+        let store = SledStateStore::new(tree);
+
+        assert!(matches!(
+            StateStore::<FullRangePool>::keys(&store),
+            Err(SledStateStoreError::InvalidKey(key)) if key == b"version"
+        ));
+    }
+
+    #[test]
+    fn test_save_and_load_pool_map() {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        let mut store = SledStateStore::new(db.open_tree("pools").unwrap());
+
+        // addresses are serialized as 20 bytes, so the largest token is the largest address
+        let max_token = Address::from_raw((U256::one() << 160) - 1);
+        let pools: PoolMap<FullRangePool> = [
+            pool(Address::from_raw(U256::from(2)), 100),
+            pool(max_token, 100),
+        ]
+        .into_iter()
+        .collect();
+        save_pool_map(&mut store, &pools, 10).unwrap();
+        let mut updated = pools.clone();
+        updated.insert(pool(Address::from_raw(U256::from(2)), 500));
+        save_pool_map(&mut store, &updated, 256).unwrap();
+        store.flush().unwrap();
+
+        assert_eq!(
+            StateStore::<FullRangePool>::keys(&store).unwrap(),
+            pools.iter().map(|pool| *pool.get_key()).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            store
+                .get(pool(Address::from_raw(U256::from(2)), 0).get_key(), 255)
+                .unwrap(),
+            Some((10, pool(Address::from_raw(U256::from(2)), 100)))
+        );
+        assert_eq!(load_pool_map(&store, 255).unwrap(), pools);
+        assert_eq!(load_pool_map(&store, 256).unwrap(), updated);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::math::tick::{MAX_TICK, MAX_TICK_SPACING, MIN_TICK};
+use crate::math::uint::U256;
+use crate::quoting::types::Tick;
+use alloc::collections::btree_map::{BTreeMap, Range};
+use alloc::vec::Vec;
+use num_traits::Zero;
+
+// Offset added to the compressed tick (tick / tick_spacing) so that all bitmap positions are non-negative
+pub const TICK_BITMAP_OFFSET: i64 = 89421695;
+
+// Mapping of word index to the 256 bit word, where each set bit represents an initialized tick
+pub type TickBitmap = BTreeMap<u32, U256>;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TickBitmapError {
+    /// Tick spacing must be greater than zero and less than or equal to max tick spacing.
+    InvalidTickSpacing,
+    /// The tick is not a multiple of the tick spacing.
+    TickNotMultipleOfSpacing(i32),
+    /// The tick is outside of the range [MIN_TICK, MAX_TICK].
+    TickOutOfRange(i32),
+}
+
+fn validate_tick_spacing(tick_spacing: u32) -> Result<(), TickBitmapError> {
+    if tick_spacing.is_zero() || tick_spacing > MAX_TICK_SPACING {
+        Err(TickBitmapError::InvalidTickSpacing)
+    } else {
+        Ok(())
+    }
+}
+
+// Returns the word index and the bit index within the word for the given tick
+pub fn tick_to_bitmap_word_and_index(
+    tick: i32,
+    tick_spacing: u32,
+) -> Result<(u32, u8), TickBitmapError> {
+    validate_tick_spacing(tick_spacing)?;
+
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(TickBitmapError::TickOutOfRange(tick));
+    }
+
+    if tick % (tick_spacing as i32) != 0 {
+        return Err(TickBitmapError::TickNotMultipleOfSpacing(tick));
+    }
+
+    let position = (tick / (tick_spacing as i32)) as i64 + TICK_BITMAP_OFFSET;
+
+    Ok(((position >> 8) as u32, (position & 0xff) as u8))
+}
+
+// Returns the tick represented by the given word index and bit index, or None if it does not fit in an i32
+pub fn bitmap_word_and_index_to_tick(word: u32, index: u8, tick_spacing: u32) -> Option<i32> {
+    let position = ((word as i64) << 8) + index as i64;
+    (position - TICK_BITMAP_OFFSET)
+        .checked_mul(tick_spacing as i64)
+        .and_then(|tick| i32::try_from(tick).ok())
+}
+
+// Computes the bitmap of initialized ticks from the sorted ticks of a pool
+pub fn sorted_ticks_to_bitmap(
+    sorted_ticks: &[Tick],
+    tick_spacing: u32,
+) -> Result<TickBitmap, TickBitmapError> {
+    let mut bitmap = TickBitmap::new();
+
+    for tick in sorted_ticks {
+        let (word, index) = tick_to_bitmap_word_and_index(tick.index, tick_spacing)?;
+        *bitmap.entry(word).or_default() |= U256::one() << index;
+    }
+
+    Ok(bitmap)
+}
+
+// Computes the sorted ticks from a bitmap, calling `liquidity_delta` for each initialized tick to get its liquidity delta
+pub fn bitmap_to_sorted_ticks<F>(
+    bitmap: &TickBitmap,
+    tick_spacing: u32,
+    mut liquidity_delta: F,
+) -> Result<Vec<Tick>, TickBitmapError>
+where
+    F: FnMut(i32) -> i128,
+{
+    Ok(
+        initialized_ticks_in_range(bitmap, tick_spacing, MIN_TICK, MAX_TICK)?
+            .map(|index| Tick {
+                index,
+                liquidity_delta: liquidity_delta(index),
+            })
+            .collect(),
+    )
+}
+
+// Returns an iterator over the initialized ticks in the bitmap between min_tick and max_tick (inclusive) in ascending order
+pub fn initialized_ticks_in_range(
+    bitmap: &TickBitmap,
+    tick_spacing: u32,
+    min_tick: i32,
+    max_tick: i32,
+) -> Result<InitializedTicks<'_>, TickBitmapError> {
+    validate_tick_spacing(tick_spacing)?;
+
+    let spacing = tick_spacing as i64;
+    // the min tick is rounded up and the max tick is rounded down to the nearest multiple of tick spacing
+    let min_position = -(-(min_tick.max(MIN_TICK) as i64)).div_euclid(spacing) + TICK_BITMAP_OFFSET;
+    let max_position = (max_tick.min(MAX_TICK) as i64).div_euclid(spacing) + TICK_BITMAP_OFFSET;
+
+    let words = if min_position > max_position {
+        // an empty range of words
+        bitmap.range(1..1)
+    } else {
+        bitmap.range(((min_position >> 8) as u32)..=((max_position >> 8) as u32))
+    };
+
+    Ok(InitializedTicks {
+        words,
+        current: None,
+        tick_spacing,
+        min_position,
+        max_position,
+    })
+}
+
+pub struct InitializedTicks<'a> {
+    words: Range<'a, u32, U256>,
+    current: Option<(u32, U256)>,
+    tick_spacing: u32,
+    min_position: i64,
+    max_position: i64,
+}
+
+impl Iterator for InitializedTicks<'_> {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((word, bits)) = self.current.as_mut() {
+                if !bits.is_zero() {
+                    let index = bits.trailing_zeros();
+                    *bits ^= U256::one() << index;
+
+                    let position = ((*word as i64) << 8) + index as i64;
+                    if position < self.min_position {
+                        continue;
+                    }
+                    if position > self.max_position {
+                        self.current = None;
+                        return None;
+                    }
+
+                    // the position is within the tick range, so the tick always fits
+                    return bitmap_word_and_index_to_tick(*word, index as u8, self.tick_spacing);
+                }
+            }
+
+            let (word, bits) = self.words.next()?;
+            self.current = Some((*word, *bits));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_word_and_index_round_trip() {
+        for tick_spacing in [1, 10, 100, MAX_TICK_SPACING] {
+            let spacing = tick_spacing as i32;
+            for tick in [
+                0,
+                spacing,
+                -spacing,
+                (MIN_TICK / spacing) * spacing,
+                (MAX_TICK / spacing) * spacing,
+            ] {
+                let (word, index) = tick_to_bitmap_word_and_index(tick, tick_spacing).unwrap();
+                assert_eq!(
+                    bitmap_word_and_index_to_tick(word, index, tick_spacing),
+                    Some(tick)
+                );
+            }
+        }
+
+        assert_eq!(bitmap_word_and_index_to_tick(u32::MAX, 255, 1), None);
+        assert_eq!(bitmap_word_and_index_to_tick(u32::MAX, 255, u32::MAX), None);
+    }
+
+    #[test]
+    fn test_word_and_index_errors() {
+        assert_eq!(
+            tick_to_bitmap_word_and_index(0, 0),
+            Err(TickBitmapError::InvalidTickSpacing)
+        );
+        assert_eq!(
+            tick_to_bitmap_word_and_index(5, 10),
+            Err(TickBitmapError::TickNotMultipleOfSpacing(5))
+        );
+        assert_eq!(
+            tick_to_bitmap_word_and_index(MAX_TICK + 1, 1),
+            Err(TickBitmapError::TickOutOfRange(MAX_TICK + 1))
+        );
+    }
+
+    #[test]
+    fn test_sorted_ticks_round_trip() {
+        let sorted_ticks = vec![
+            Tick {
+                index: -88722000,
+                liquidity_delta: 5,
+            },
+            Tick {
+                index: -10,
+                liquidity_delta: 10,
+            },
+            Tick {
+                index: 0,
+                liquidity_delta: -3,
+            },
+            Tick {
+                index: 2550,
+                liquidity_delta: -2,
+            },
+            Tick {
+                index: 88722000,
+                liquidity_delta: -10,
+            },
+        ];
+
+        let bitmap = sorted_ticks_to_bitmap(&sorted_ticks, 10).unwrap();
+        let result = bitmap_to_sorted_ticks(&bitmap, 10, |index| {
+            sorted_ticks
+                .iter()
+                .find(|t| t.index == index)
+                .unwrap()
+                .liquidity_delta
+        })
+        .unwrap();
+
+        assert_eq!(result, sorted_ticks);
+    }
+
+    #[test]
+    fn test_initialized_ticks_in_range() {
+        let sorted_ticks: Vec<Tick> = [-5000, -2560, -20, 0, 10, 2560, 5000]
+            .into_iter()
+            .map(|index| Tick {
+                index,
+                liquidity_delta: 0,
+            })
+            .collect();
+        let bitmap = sorted_ticks_to_bitmap(&sorted_ticks, 10).unwrap();
+
+        let ticks: Vec<i32> = initialized_ticks_in_range(&bitmap, 10, -2560, 2559)
+            .unwrap()
+            .collect();
+        assert_eq!(ticks, vec![-2560, -20, 0, 10]);
+
+        // bounds that are not multiples of the tick spacing
+        let ticks: Vec<i32> = initialized_ticks_in_range(&bitmap, 10, -25, 15)
+            .unwrap()
+            .collect();
+        assert_eq!(ticks, vec![-20, 0, 10]);
+
+        let ticks: Vec<i32> = initialized_ticks_in_range(&bitmap, 10, 1, 9)
+            .unwrap()
+            .collect();
+        assert!(ticks.is_empty());
+
+        let ticks: Vec<i32> = initialized_ticks_in_range(&bitmap, 10, 10, -10)
+            .unwrap()
+            .collect();
+        assert!(ticks.is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+use crate::quoting::types::Tick;
+use alloc::vec::Vec;
+
+// Compact binary encoding of tick arrays. The encoding is the number of ticks followed by, for each tick, the
+// difference between its index and the index of the previous tick (zero for the first tick) and its liquidity delta.
+// All integers are zigzag encoded LEB128 varints, so sorted ticks with small spacing and liquidity deltas take only a
+// few bytes each.
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TickDecompressionError {
+    /// The input ended in the middle of a value.
+    UnexpectedEnd,
+    /// A varint is longer than the integer it encodes.
+    VarintOverflow,
+    /// A decoded tick index does not fit in an i32.
+    TickIndexOverflow,
+    /// There are bytes left after the last tick.
+    TrailingBytes,
+}
+
+fn zigzag_encode(value: i128) -> u128 {
+    ((value << 1) ^ (value >> 127)) as u128
+}
+
+fn zigzag_decode(value: u128) -> i128 {
+    ((value >> 1) as i128) ^ -((value & 1) as i128)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u128) {
+    while value >= 0x80 {
+        bytes.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], offset: &mut usize) -> Result<u128, TickDecompressionError> {
+    let mut value = 0u128;
+    let mut shift = 0u32;
+
+    loop {
+        let byte = *bytes
+            .get(*offset)
+            .ok_or(TickDecompressionError::UnexpectedEnd)?;
+        *offset += 1;
+
+        let bits = (byte & 0x7f) as u128;
+        if shift >= 128 || (shift > 0 && bits >> (128 - shift) != 0) {
+            return Err(TickDecompressionError::VarintOverflow);
+        }
+        value |= bits << shift;
+
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+        shift += 7;
+    }
+}
+
+// Compresses the ticks, which are expected to be sorted by index for the encoding to be compact
+pub fn compress_ticks(ticks: &[Tick]) -> Vec<u8> {
+    // most ticks take 2-4 bytes per index and 4-10 bytes per liquidity delta
+    let mut bytes = Vec::with_capacity(ticks.len() * 8 + 3);
+    write_varint(&mut bytes, ticks.len() as u128);
+
+    let mut previous_index = 0i32;
+    for tick in ticks {
+        write_varint(
+            &mut bytes,
+            zigzag_encode(tick.index as i128 - previous_index as i128),
+        );
+        write_varint(&mut bytes, zigzag_encode(tick.liquidity_delta));
+        previous_index = tick.index;
+    }
+
+    bytes
+}
+
+// Decompresses ticks previously compressed with compress_ticks
+pub fn decompress_ticks(bytes: &[u8]) -> Result<Vec<Tick>, TickDecompressionError> {
+    let mut offset = 0;
+    let count = read_varint(bytes, &mut offset)?;
+
+    // every tick takes at least two bytes, which bounds the allocation for malicious inputs
+    let mut ticks = Vec::with_capacity(count.min((bytes.len() / 2) as u128) as usize);
+
+    let mut previous_index = 0i32;
+    for _ in 0..count {
+        let index_delta = zigzag_decode(read_varint(bytes, &mut offset)?);
+        let index = i32::try_from(previous_index as i128 + index_delta)
+            .map_err(|_| TickDecompressionError::TickIndexOverflow)?;
+        let liquidity_delta = zigzag_decode(read_varint(bytes, &mut offset)?);
+
+        ticks.push(Tick {
+            index,
+            liquidity_delta,
+        });
+        previous_index = index;
+    }
+
+    if offset != bytes.len() {
+        return Err(TickDecompressionError::TrailingBytes);
+    }
+
+    Ok(ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::{MAX_TICK, MIN_TICK};
+    use crate::quoting::order_flow::SplitMix64;
+    use alloc::vec;
+
+    fn tick(index: i32, liquidity_delta: i128) -> Tick {
+        Tick {
+            index,
+            liquidity_delta,
+        }
+    }
+
+    #[test]
+    fn test_zigzag_round_trip() {
+        for value in [0, 1, -1, 63, -64, i128::MAX, i128::MIN] {
+            assert_eq!(zigzag_decode(zigzag_encode(value)), value);
+        }
+        assert_eq!(zigzag_encode(-1), 1);
+        assert_eq!(zigzag_encode(1), 2);
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(compress_ticks(&[]), vec![0]);
+        assert_eq!(decompress_ticks(&[0]), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_sorted_ticks_are_compact() {
+        let ticks: Vec<Tick> = (0..1000)
+            .map(|i| {
+                tick(
+                    -50_000 + i * 100,
+                    if i % 2 == 0 { 1 << 40 } else { -(1 << 40) },
+                )
+            })
+            .collect();
+
+        let bytes = compress_ticks(&ticks);
+        // 2 bytes per index delta and 6 bytes per liquidity delta, compared to 20 bytes per tick uncompressed
+        assert_eq!(bytes.len(), 2 + 1000 * 8 + 1);
+        assert_eq!(decompress_ticks(&bytes).unwrap(), ticks);
+    }
+
+    #[test]
+    fn test_extremes() {
+        let ticks = vec![
+            tick(MIN_TICK, i128::MIN),
+            tick(MAX_TICK, i128::MAX),
+            tick(i32::MIN, 0),
+            tick(i32::MAX, -1),
+        ];
+        assert_eq!(decompress_ticks(&compress_ticks(&ticks)).unwrap(), ticks);
+    }
+
+    #[test]
+    fn test_decompress_errors() {
+        let bytes = compress_ticks(&[tick(10, 5), tick(20, -5)]);
+
+        assert_eq!(
+            decompress_ticks(&bytes[..bytes.len() - 1]),
+            Err(TickDecompressionError::UnexpectedEnd)
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            decompress_ticks(&trailing),
+            Err(TickDecompressionError::TrailingBytes)
+        );
+
+        // a count of one, followed by a 20 byte varint
+        let mut overflowing = vec![1];
+        overflowing.extend([0xff; 19]);
+        overflowing.push(0x01);
+        assert_eq!(
+            decompress_ticks(&overflowing),
+            Err(TickDecompressionError::VarintOverflow)
+        );
+
+        // an index delta that takes the index past i32::MAX
+        let mut out_of_range = vec![1];
+        write_varint(&mut out_of_range, zigzag_encode(i32::MAX as i128 + 1));
+        write_varint(&mut out_of_range, 0);
+        assert_eq!(
+            decompress_ticks(&out_of_range),
+            Err(TickDecompressionError::TickIndexOverflow)
+        );
+    }
+
+    #[test]
+    fn test_fuzz_round_trip() {
+        let mut rng = SplitMix64(0x5eed);
+
+        for _ in 0..500 {
+            let len = (rng.next_u64() % 64) as usize;
+            let mut index = MIN_TICK + (rng.next_u64() % 1_000_000) as i32;
+            let ticks: Vec<Tick> = (0..len)
+                .map(|_| {
+                    // a mix of small and large gaps and liquidity deltas
+                    index = index.saturating_add(match rng.next_u64() % 3 {
+                        0 => (rng.next_u64() % 100) as i32 + 1,
+                        1 => (rng.next_u64() % 100_000) as i32 + 1,
+                        _ => (rng.next_u64() % (1 << 24)) as i32 + 1,
+                    });
+                    let liquidity_delta = match rng.next_u64() % 3 {
+                        0 => (rng.next_u64() % 1000) as i128 - 500,
+                        1 => rng.next_u64() as i64 as i128,
+                        _ => rng.next_u128() as i128,
+                    };
+                    tick(index, liquidity_delta)
+                })
+                .collect();
+
+            let bytes = compress_ticks(&ticks);
+            assert_eq!(decompress_ticks(&bytes).unwrap(), ticks);
+        }
+    }
+
+    #[test]
+    fn test_fuzz_random_bytes_do_not_panic() {
+        let mut rng = SplitMix64(0xbad);
+
+        for _ in 0..2000 {
+            let len = (rng.next_u64() % 48) as usize;
+            let bytes: Vec<u8> = (0..len).map(|_| rng.next_u64() as u8).collect();
+
+            // any successfully decoded input must re-encode to a valid encoding of the same ticks
+            if let Ok(ticks) = decompress_ticks(&bytes) {
+                assert_eq!(decompress_ticks(&compress_ticks(&ticks)).unwrap(), ticks);
+            }
+        }
+    }
+}
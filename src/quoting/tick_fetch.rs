@@ -0,0 +1,343 @@
+use crate::math::tick::{MAX_TICK, MAX_TICK_SPACING, MIN_TICK};
+use crate::quoting::types::Tick;
+use alloc::vec::Vec;
+use num_traits::Zero;
+
+// A single query to the lens contract: the initialized ticks between min_tick and max_tick (inclusive), skipping the
+// first `skip` of them
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TickFetchWindow {
+    pub min_tick: i32,
+    pub max_tick: i32,
+    pub skip: u32,
+}
+
+impl TickFetchWindow {
+    // Returns the window for the next page of this window if the call returned a full page of ticks
+    pub fn next_page(&self, returned: u32, tick_limit: u32) -> Option<TickFetchWindow> {
+        (returned >= tick_limit && !returned.is_zero()).then(|| TickFetchWindow {
+            skip: self.skip.saturating_add(returned),
+            ..*self
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TickFetchPlanError {
+    /// Tick spacing must be greater than zero and less than or equal to max tick spacing.
+    InvalidTickSpacing,
+    /// The min tick must not be greater than the max tick, and both must be in the range [MIN_TICK, MAX_TICK].
+    InvalidRange,
+    /// The tick limit of the lens contract must be greater than zero.
+    ZeroTickLimit,
+}
+
+// Splits the range between min_tick and max_tick into the fewest windows that each contain at most tick_limit
+// initializable ticks, so that every window can be fetched in a single call regardless of how many ticks are
+// initialized. The windows are in ascending order and together cover exactly the requested range.
+pub fn plan_tick_fetch(
+    min_tick: i32,
+    max_tick: i32,
+    tick_spacing: u32,
+    tick_limit: u32,
+) -> Result<Vec<TickFetchWindow>, TickFetchPlanError> {
+    if tick_spacing.is_zero() || tick_spacing > MAX_TICK_SPACING {
+        return Err(TickFetchPlanError::InvalidTickSpacing);
+    }
+    if min_tick > max_tick || min_tick < MIN_TICK || max_tick > MAX_TICK {
+        return Err(TickFetchPlanError::InvalidRange);
+    }
+    if tick_limit.is_zero() {
+        return Err(TickFetchPlanError::ZeroTickLimit);
+    }
+
+    let spacing = tick_spacing as i64;
+    // the positions of the first and last initializable ticks in the range
+    let first = -(-(min_tick as i64)).div_euclid(spacing);
+    let last = (max_tick as i64).div_euclid(spacing);
+
+    let mut windows = Vec::new();
+    let mut start = first;
+    loop {
+        let end = (start + tick_limit as i64 - 1).min(last);
+        windows.push(TickFetchWindow {
+            min_tick: if windows.is_empty() {
+                min_tick
+            } else {
+                (start * spacing) as i32
+            },
+            max_tick: if end >= last {
+                max_tick
+            } else {
+                (end * spacing) as i32
+            },
+            skip: 0,
+        });
+
+        if end >= last {
+            return Ok(windows);
+        }
+        start = end + 1;
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TickMergeError {
+    /// Tick spacing must be greater than zero and less than or equal to max tick spacing.
+    InvalidTickSpacing,
+    /// There were no windows to merge.
+    NoWindows,
+    /// The windows leave out initializable ticks between them.
+    NonContiguousWindows { min_tick: i32 },
+    /// A tick was returned for a window that does not contain it.
+    TickOutsideWindow(i32),
+    /// The tick is not a multiple of the tick spacing.
+    TickNotMultipleOfSpacing(i32),
+    /// The same tick was returned more than once with different liquidity deltas.
+    ConflictingTick(i32),
+}
+
+// The merged result of all the windows, ready to be passed to construct_sorted_ticks
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergedTicks {
+    pub sorted_ticks: Vec<Tick>,
+    pub min_tick_searched: i32,
+    pub max_tick_searched: i32,
+}
+
+// Stitches the ticks returned for each window into one sorted tick array. Windows may overlap, e.g. when the pages of
+// a window shift between calls, and ticks returned more than once are deduplicated.
+pub fn merge_tick_windows(
+    results: &[(TickFetchWindow, Vec<Tick>)],
+    tick_spacing: u32,
+) -> Result<MergedTicks, TickMergeError> {
+    if tick_spacing.is_zero() || tick_spacing > MAX_TICK_SPACING {
+        return Err(TickMergeError::InvalidTickSpacing);
+    }
+    let spacing = tick_spacing as i64;
+
+    let mut windows: Vec<TickFetchWindow> = results.iter().map(|(window, _)| *window).collect();
+    windows.sort_by_key(|window| (window.min_tick, window.max_tick));
+
+    let first = windows.first().ok_or(TickMergeError::NoWindows)?;
+    let (min_tick_searched, mut max_tick_searched) = (first.min_tick, first.max_tick);
+    for window in &windows[1..] {
+        // the gap between the windows is fine as long as it does not contain an initializable tick
+        let next_initializable = -(-(max_tick_searched as i64 + 1)).div_euclid(spacing) * spacing;
+        if (window.min_tick as i64) > next_initializable {
+            return Err(TickMergeError::NonContiguousWindows {
+                min_tick: window.min_tick,
+            });
+        }
+        max_tick_searched = max_tick_searched.max(window.max_tick);
+    }
+
+    let mut sorted_ticks: Vec<Tick> =
+        Vec::with_capacity(results.iter().map(|(_, t)| t.len()).sum());
+    for (window, ticks) in results {
+        for tick in ticks {
+            if tick.index < window.min_tick || tick.index > window.max_tick {
+                return Err(TickMergeError::TickOutsideWindow(tick.index));
+            }
+            if tick.index % tick_spacing as i32 != 0 {
+                return Err(TickMergeError::TickNotMultipleOfSpacing(tick.index));
+            }
+            sorted_ticks.push(*tick);
+        }
+    }
+
+    sorted_ticks.sort_by_key(|tick| tick.index);
+    let mut conflict = None;
+    sorted_ticks.dedup_by(|tick, previous| {
+        let duplicate = tick.index == previous.index;
+        if duplicate && tick.liquidity_delta != previous.liquidity_delta {
+            conflict.get_or_insert(tick.index);
+        }
+        duplicate
+    });
+    if let Some(index) = conflict {
+        return Err(TickMergeError::ConflictingTick(index));
+    }
+
+    Ok(MergedTicks {
+        sorted_ticks,
+        min_tick_searched,
+        max_tick_searched,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn window(min_tick: i32, max_tick: i32) -> TickFetchWindow {
+        TickFetchWindow {
+            min_tick,
+            max_tick,
+            skip: 0,
+        }
+    }
+
+    fn tick(index: i32, liquidity_delta: i128) -> Tick {
+        Tick {
+            index,
+            liquidity_delta,
+        }
+    }
+
+    #[test]
+    fn test_plan_single_window() {
+        assert_eq!(
+            plan_tick_fetch(-105, 95, 10, 100),
+            Ok(vec![window(-105, 95)])
+        );
+        // no initializable ticks in the range
+        assert_eq!(plan_tick_fetch(1, 9, 10, 1), Ok(vec![window(1, 9)]));
+    }
+
+    #[test]
+    fn test_plan_splits_range() {
+        // initializable ticks -100, -90, ..., 90 are 20 ticks
+        assert_eq!(
+            plan_tick_fetch(-105, 95, 10, 8),
+            Ok(vec![window(-105, -30), window(-20, 50), window(60, 95)])
+        );
+        assert_eq!(
+            plan_tick_fetch(-100, 90, 10, 10),
+            Ok(vec![window(-100, -10), window(0, 90)])
+        );
+    }
+
+    #[test]
+    fn test_plan_full_range_is_optimal() {
+        let windows = plan_tick_fetch(MIN_TICK, MAX_TICK, 1000, 10_000).unwrap();
+        // 177445 initializable ticks
+        assert_eq!(windows.len(), 18);
+        assert_eq!(windows.first().unwrap().min_tick, MIN_TICK);
+        assert_eq!(windows.last().unwrap().max_tick, MAX_TICK);
+        for pair in windows.windows(2) {
+            assert_eq!(pair[0].max_tick + 1000, pair[1].min_tick);
+        }
+    }
+
+    #[test]
+    fn test_plan_errors() {
+        assert_eq!(
+            plan_tick_fetch(0, 10, 0, 10),
+            Err(TickFetchPlanError::InvalidTickSpacing)
+        );
+        assert_eq!(
+            plan_tick_fetch(10, 0, 1, 10),
+            Err(TickFetchPlanError::InvalidRange)
+        );
+        assert_eq!(
+            plan_tick_fetch(MIN_TICK - 1, 0, 1, 10),
+            Err(TickFetchPlanError::InvalidRange)
+        );
+        assert_eq!(
+            plan_tick_fetch(0, 10, 1, 0),
+            Err(TickFetchPlanError::ZeroTickLimit)
+        );
+    }
+
+    #[test]
+    fn test_next_page() {
+        let first = window(-100, 100);
+        assert_eq!(first.next_page(9, 10), None);
+
+        let second = first.next_page(10, 10).unwrap();
+        assert_eq!(
+            second,
+            TickFetchWindow {
+                min_tick: -100,
+                max_tick: 100,
+                skip: 10
+            }
+        );
+        assert_eq!(second.next_page(10, 10).unwrap().skip, 20);
+    }
+
+    #[test]
+    fn test_merge_deduplicates_overlap() {
+        let page = window(-100, 100);
+        let merged = merge_tick_windows(
+            &[
+                (window(0, 200), vec![tick(100, -5), tick(200, 3)]),
+                (page, vec![tick(-100, 5), tick(0, 2), tick(100, -5)]),
+                (TickFetchWindow { skip: 2, ..page }, vec![tick(100, -5)]),
+            ],
+            10,
+        )
+        .unwrap();
+
+        assert_eq!(
+            merged,
+            MergedTicks {
+                sorted_ticks: vec![tick(-100, 5), tick(0, 2), tick(100, -5), tick(200, 3)],
+                min_tick_searched: -100,
+                max_tick_searched: 200,
+            }
+        );
+    }
+
+    #[test]
+    fn test_merge_errors() {
+        assert_eq!(merge_tick_windows(&[], 10), Err(TickMergeError::NoWindows));
+        assert_eq!(
+            merge_tick_windows(&[(window(0, 10), vec![])], 0),
+            Err(TickMergeError::InvalidTickSpacing)
+        );
+        assert_eq!(
+            merge_tick_windows(&[(window(0, 10), vec![]), (window(21, 30), vec![])], 10),
+            Err(TickMergeError::NonContiguousWindows { min_tick: 21 })
+        );
+        // the gap contains no multiple of the tick spacing
+        assert!(
+            merge_tick_windows(&[(window(0, 11), vec![]), (window(20, 30), vec![])], 10).is_ok()
+        );
+        assert_eq!(
+            merge_tick_windows(&[(window(0, 10), vec![tick(20, 1)])], 10),
+            Err(TickMergeError::TickOutsideWindow(20))
+        );
+        assert_eq!(
+            merge_tick_windows(&[(window(0, 10), vec![tick(5, 1)])], 10),
+            Err(TickMergeError::TickNotMultipleOfSpacing(5))
+        );
+        assert_eq!(
+            merge_tick_windows(
+                &[
+                    (window(0, 10), vec![tick(10, 1)]),
+                    (window(10, 20), vec![tick(10, 2)])
+                ],
+                10
+            ),
+            Err(TickMergeError::ConflictingTick(10))
+        );
+    }
+
+    #[test]
+    fn test_planned_windows_merge_back() {
+        let all_ticks: Vec<Tick> = (-50..50).map(|i| tick(i * 20, 1)).collect();
+        let windows = plan_tick_fetch(-1000, 1000, 10, 7).unwrap();
+
+        let results: Vec<(TickFetchWindow, Vec<Tick>)> = windows
+            .iter()
+            .map(|w| {
+                let ticks = all_ticks
+                    .iter()
+                    .filter(|t| t.index >= w.min_tick && t.index <= w.max_tick)
+                    .copied()
+                    .collect();
+                (*w, ticks)
+            })
+            .collect();
+
+        let merged = merge_tick_windows(&results, 10).unwrap();
+        assert_eq!(merged.sorted_ticks, all_ticks);
+        assert_eq!(
+            (merged.min_tick_searched, merged.max_tick_searched),
+            (-1000, 1000)
+        );
+    }
+}
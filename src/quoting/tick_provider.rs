@@ -0,0 +1,661 @@
+use crate::math::swap::{compute_step, is_price_increasing};
+use crate::math::tick::{sqrt_ratio_to_tick, to_sqrt_ratio, MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+use crate::math::uint::U256;
+use crate::quoting::base_pool::{BasePool, BasePoolQuoteError, BasePoolResources};
+use crate::quoting::profile::{NoProfiler, QuotePhase, QuoteProfiler};
+use crate::quoting::types::{NodeKey, Quote, Tick, TokenAmount};
+use crate::quoting::util::{
+    approximate_number_of_tick_spacings_crossed, find_nearest_initialized_tick_index,
+};
+use core::convert::Infallible;
+use core::fmt::Debug;
+
+/// A source of initialized ticks that is queried on demand while quoting, so that the entire
+/// set of ticks of a pool does not need to be fetched up front.
+pub trait TickProvider {
+    type Error: Debug;
+
+    /// Returns the greatest initialized tick that is less than or equal to `tick`.
+    fn prev_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error>;
+
+    /// Returns the smallest initialized tick that is strictly greater than `tick`.
+    fn next_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error>;
+}
+
+// Sorted ticks are the simplest tick provider
+impl TickProvider for [Tick] {
+    type Error = Infallible;
+
+    fn prev_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+        Ok(find_nearest_initialized_tick_index(self, tick).map(|i| self[i]))
+    }
+
+    fn next_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+        Ok(match find_nearest_initialized_tick_index(self, tick) {
+            Some(i) => self.get(i + 1).copied(),
+            None => self.first().copied(),
+        })
+    }
+}
+
+impl TickProvider for BasePool {
+    type Error = Infallible;
+
+    fn prev_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+        self.get_sorted_ticks().prev_initialized_tick(tick)
+    }
+
+    fn next_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+        self.get_sorted_ticks().next_initialized_tick(tick)
+    }
+}
+
+/// The state of a pool that is quoted with a tick provider. Unlike [`crate::quoting::base_pool::BasePoolState`],
+/// the position in the ticks is tracked by the current tick rather than an index into the sorted ticks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TickProviderPoolState {
+    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
+    pub sqrt_ratio: U256,
+    pub liquidity: u128,
+    pub tick: i32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TickProviderQuoteError<E> {
+    TickProviderError(E),
+    BasePoolQuoteError(BasePoolQuoteError),
+}
+
+// A position in the initialized ticks of a pool that a swap moves through
+pub(crate) trait TickCursor {
+    type Error;
+
+    // Returns the next initialized tick from the position in the direction of the swap
+    fn next_initialized_tick(&self, is_increasing: bool) -> Result<Option<Tick>, Self::Error>;
+
+    // Moves the position past the tick that was returned by next_initialized_tick
+    fn cross(&mut self, tick: &Tick, is_increasing: bool);
+
+    // Moves the position to the sqrt ratio, which did not reach the next initialized tick
+    fn move_to(&mut self, sqrt_ratio: U256, is_increasing: bool, next_tick: Option<&Tick>);
+}
+
+// The cursor of a tick provider, which tracks the position by the current tick
+struct ProviderCursor<'a, P: ?Sized> {
+    tick_provider: &'a P,
+    tick: i32,
+}
+
+impl<P: TickProvider + ?Sized> TickCursor for ProviderCursor<'_, P> {
+    type Error = P::Error;
+
+    fn next_initialized_tick(&self, is_increasing: bool) -> Result<Option<Tick>, Self::Error> {
+        if is_increasing {
+            self.tick_provider.next_initialized_tick(self.tick)
+        } else {
+            self.tick_provider.prev_initialized_tick(self.tick)
+        }
+    }
+
+    fn cross(&mut self, tick: &Tick, is_increasing: bool) {
+        self.tick = if is_increasing {
+            tick.index
+        } else {
+            tick.index - 1
+        };
+    }
+
+    fn move_to(&mut self, sqrt_ratio: U256, _is_increasing: bool, _next_tick: Option<&Tick>) {
+        self.tick = sqrt_ratio_to_tick(sqrt_ratio);
+    }
+}
+
+// The result of moving a swap through the ticks of a pool
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SwapOutcome {
+    pub is_increasing: bool,
+    pub consumed_amount: i128,
+    pub calculated_amount: u128,
+    pub fees_paid: u128,
+    pub sqrt_ratio: U256,
+    pub liquidity: u128,
+    pub initialized_ticks_crossed: u32,
+    // Whether the swap stopped right before crossing more initialized ticks than the maximum
+    pub max_ticks_crossed_exceeded: bool,
+}
+
+/// Moves a swap of the amount through the initialized ticks of the cursor, starting at the sqrt ratio and liquidity.
+///
+/// This is the swap loop of every concentrated liquidity quote, so that quoting with sorted ticks and quoting with a
+/// tick provider cannot diverge.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn swap_through_ticks<C: TickCursor>(
+    key: &NodeKey,
+    cursor: &mut C,
+    sqrt_ratio: U256,
+    liquidity: u128,
+    token_amount: TokenAmount,
+    sqrt_ratio_limit: Option<U256>,
+    max_ticks_crossed: Option<u32>,
+    profiler: &mut impl QuoteProfiler,
+) -> Result<SwapOutcome, TickProviderQuoteError<C::Error>> {
+    let amount = token_amount.amount;
+    let token = token_amount.token;
+    let is_token1 = token == key.token1;
+
+    if !is_token1 && token != key.token0 {
+        return Err(TickProviderQuoteError::BasePoolQuoteError(
+            BasePoolQuoteError::InvalidToken,
+        ));
+    }
+
+    let mut outcome = SwapOutcome {
+        is_increasing: is_token1,
+        consumed_amount: 0,
+        calculated_amount: 0,
+        fees_paid: 0,
+        sqrt_ratio,
+        liquidity,
+        initialized_ticks_crossed: 0,
+        max_ticks_crossed_exceeded: false,
+    };
+
+    if amount == 0 {
+        return Ok(outcome);
+    }
+
+    let is_increasing = is_price_increasing(amount, is_token1);
+    outcome.is_increasing = is_increasing;
+
+    let sqrt_ratio_limit = match sqrt_ratio_limit {
+        Some(limit) => {
+            if (is_increasing && limit < sqrt_ratio)
+                || (!is_increasing && limit > sqrt_ratio)
+                || !(MIN_SQRT_RATIO..=MAX_SQRT_RATIO).contains(&limit)
+            {
+                return Err(TickProviderQuoteError::BasePoolQuoteError(
+                    BasePoolQuoteError::InvalidSqrtRatioLimit,
+                ));
+            }
+            limit
+        }
+        None if is_increasing => MAX_SQRT_RATIO,
+        None => MIN_SQRT_RATIO,
+    };
+
+    let mut amount_remaining = amount;
+
+    while amount_remaining != 0 && outcome.sqrt_ratio != sqrt_ratio_limit {
+        let tick_search = profiler.start();
+        let next_initialized_tick = cursor
+            .next_initialized_tick(is_increasing)
+            .map_err(TickProviderQuoteError::TickProviderError)?
+            .map(|next| {
+                to_sqrt_ratio(next.index)
+                    .map(|next_sqrt_ratio| (next, next_sqrt_ratio))
+                    .ok_or(TickProviderQuoteError::BasePoolQuoteError(
+                        BasePoolQuoteError::InvalidTick(next.index),
+                    ))
+            })
+            .transpose()?;
+
+        let step_sqrt_ratio_limit =
+            next_initialized_tick.map_or(sqrt_ratio_limit, |(_, next_ratio)| {
+                if (next_ratio < sqrt_ratio_limit) == is_increasing {
+                    next_ratio
+                } else {
+                    sqrt_ratio_limit
+                }
+            });
+        profiler.record(QuotePhase::TickSearch, tick_search);
+
+        let step_math = profiler.start();
+        let step = compute_step(
+            outcome.sqrt_ratio,
+            outcome.liquidity,
+            step_sqrt_ratio_limit,
+            amount_remaining,
+            is_token1,
+            key.config.fee.raw(),
+        )
+        .map_err(|err| {
+            TickProviderQuoteError::BasePoolQuoteError(BasePoolQuoteError::FailedComputeSwapStep(
+                err,
+            ))
+        })?;
+        profiler.record(QuotePhase::StepMath, step_math);
+
+        let accounting = profiler.start();
+        amount_remaining -= step.consumed_amount;
+        outcome.consumed_amount = amount - amount_remaining;
+        outcome.calculated_amount += step.calculated_amount;
+        outcome.fees_paid += step.fee_amount;
+        outcome.sqrt_ratio = step.sqrt_ratio_next;
+
+        match next_initialized_tick {
+            Some((next_tick, next_sqrt_ratio)) if outcome.sqrt_ratio == next_sqrt_ratio => {
+                if max_ticks_crossed == Some(outcome.initialized_ticks_crossed) {
+                    outcome.max_ticks_crossed_exceeded = true;
+                    return Ok(outcome);
+                }
+
+                // the ticks may come from an unvalidated source, so their liquidity deltas are not trusted
+                let liquidity_delta = next_tick.liquidity_delta.unsigned_abs();
+                outcome.liquidity = if (next_tick.liquidity_delta.signum() == 1) == is_increasing {
+                    outcome.liquidity.checked_add(liquidity_delta)
+                } else {
+                    outcome.liquidity.checked_sub(liquidity_delta)
+                }
+                .ok_or(TickProviderQuoteError::BasePoolQuoteError(
+                    BasePoolQuoteError::InvalidLiquidityDelta(next_tick.index),
+                ))?;
+
+                cursor.cross(&next_tick, is_increasing);
+                outcome.initialized_ticks_crossed += 1;
+            }
+            next_initialized_tick => {
+                cursor.move_to(
+                    outcome.sqrt_ratio,
+                    is_increasing,
+                    next_initialized_tick
+                        .as_ref()
+                        .map(|(next_tick, _)| next_tick),
+                );
+            }
+        }
+        profiler.record(QuotePhase::ResourceAccounting, accounting);
+    }
+
+    Ok(outcome)
+}
+
+/// Computes a quote for a concentrated liquidity pool, fetching the initialized ticks from the
+/// provider as they are needed. The result is identical to [`BasePool::quote`] for the same ticks.
+///
+/// The `no_override_price_change` resource assumes that the given state is the current state of the pool.
+pub fn quote_with_tick_provider<P: TickProvider + ?Sized>(
+    key: &NodeKey,
+    state: TickProviderPoolState,
+    token_amount: TokenAmount,
+    sqrt_ratio_limit: Option<U256>,
+    tick_provider: &P,
+) -> Result<Quote<BasePoolResources, TickProviderPoolState>, TickProviderQuoteError<P::Error>> {
+    let mut cursor = ProviderCursor {
+        tick_provider,
+        tick: state.tick,
+    };
+    let outcome = swap_through_ticks(
+        key,
+        &mut cursor,
+        state.sqrt_ratio,
+        state.liquidity,
+        token_amount,
+        sqrt_ratio_limit,
+        None,
+        &mut NoProfiler,
+    )?;
+
+    Ok(Quote {
+        is_price_increasing: outcome.is_increasing,
+        consumed_amount: outcome.consumed_amount,
+        calculated_amount: outcome.calculated_amount,
+        execution_resources: BasePoolResources {
+            no_override_price_change: if state.sqrt_ratio != outcome.sqrt_ratio {
+                1
+            } else {
+                0
+            },
+            initialized_ticks_crossed: outcome.initialized_ticks_crossed,
+            tick_spacings_crossed: approximate_number_of_tick_spacings_crossed(
+                state.sqrt_ratio,
+                outcome.sqrt_ratio,
+                key.config.tick_spacing.raw(),
+            ),
+        },
+        state_after: TickProviderPoolState {
+            sqrt_ratio: outcome.sqrt_ratio,
+            liquidity: outcome.liquidity,
+            tick: cursor.tick,
+        },
+        fees_paid: outcome.fees_paid,
+    })
+}
+
+#[cfg(feature = "async")]
+pub mod asynchronous {
+    use super::TickProvider;
+    use crate::quoting::types::Tick;
+    use core::fmt::Debug;
+    use core::future::Future;
+
+    /// An asynchronous source of initialized ticks, e.g. an RPC node.
+    pub trait AsyncTickProvider {
+        type Error: Debug;
+
+        /// Returns the greatest initialized tick that is less than or equal to `tick`.
+        fn prev_initialized_tick(
+            &self,
+            tick: i32,
+        ) -> impl Future<Output = Result<Option<Tick>, Self::Error>>;
+
+        /// Returns the smallest initialized tick that is strictly greater than `tick`.
+        fn next_initialized_tick(
+            &self,
+            tick: i32,
+        ) -> impl Future<Output = Result<Option<Tick>, Self::Error>>;
+    }
+
+    /// Drives a future to completion, e.g. using the block_on function of the async runtime.
+    pub trait BlockOn {
+        fn block_on<F: Future>(&self, future: F) -> F::Output;
+    }
+
+    /// Adapts an [`AsyncTickProvider`] to a [`TickProvider`] by blocking on each request.
+    pub struct BlockingTickProvider<P, B> {
+        pub provider: P,
+        pub executor: B,
+    }
+
+    impl<P: AsyncTickProvider, B: BlockOn> TickProvider for BlockingTickProvider<P, B> {
+        type Error = P::Error;
+
+        fn prev_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+            self.executor
+                .block_on(self.provider.prev_initialized_tick(tick))
+        }
+
+        fn next_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+            self.executor
+                .block_on(self.provider.next_initialized_tick(tick))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::{MAX_TICK, MIN_TICK};
+    use crate::quoting::base_pool::BasePoolState;
+    use crate::quoting::types::{Address, Config, Pool, QuoteParams};
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn sorted_ticks() -> Vec<Tick> {
+        vec![
+            Tick {
+                index: -1000,
+                liquidity_delta: 1_000_000,
+            },
+            Tick {
+                index: -100,
+                liquidity_delta: 5_000_000,
+            },
+            Tick {
+                index: 200,
+                liquidity_delta: -5_000_000,
+            },
+            Tick {
+                index: 1000,
+                liquidity_delta: -1_000_000,
+            },
+        ]
+    }
+
+    fn base_pool() -> BasePool {
+        BasePool::new(
+            NodeKey {
+                token0: TOKEN0,
+                token1: TOKEN1,
+                config: Config::from_raw_unchecked(1 << 60, 100, U256::zero()),
+            },
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                liquidity: 6_000_000,
+                active_tick_index: Some(1),
+            },
+            sorted_ticks(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_slice_provider() {
+        let ticks = sorted_ticks();
+        assert_eq!(ticks.prev_initialized_tick(-1001).unwrap(), None);
+        assert_eq!(ticks.prev_initialized_tick(-1000).unwrap(), Some(ticks[0]));
+        assert_eq!(ticks.prev_initialized_tick(0).unwrap(), Some(ticks[1]));
+        assert_eq!(
+            ticks.prev_initialized_tick(MAX_TICK).unwrap(),
+            Some(ticks[3])
+        );
+        assert_eq!(
+            ticks.next_initialized_tick(MIN_TICK).unwrap(),
+            Some(ticks[0])
+        );
+        assert_eq!(ticks.next_initialized_tick(-1000).unwrap(), Some(ticks[1]));
+        assert_eq!(ticks.next_initialized_tick(200).unwrap(), Some(ticks[3]));
+        assert_eq!(ticks.next_initialized_tick(1000).unwrap(), None);
+    }
+
+    #[test]
+    fn test_matches_base_pool_quote() {
+        let pool = base_pool();
+        let state = TickProviderPoolState {
+            sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+            liquidity: 6_000_000,
+            tick: 0,
+        };
+
+        for (amount, token) in [
+            (1_000, TOKEN0),
+            (1_000, TOKEN1),
+            (-1_000, TOKEN0),
+            (-1_000, TOKEN1),
+            (100_000, TOKEN0),
+            (100_000, TOKEN1),
+            (10_000_000, TOKEN0),
+            (10_000_000, TOKEN1),
+        ] {
+            let token_amount = TokenAmount { amount, token };
+
+            let expected = pool
+                .quote(QuoteParams {
+                    token_amount,
+                    sqrt_ratio_limit: None,
+                    max_ticks_crossed: None,
+                    override_state: None,
+                    meta: (),
+                })
+                .unwrap();
+            let result =
+                quote_with_tick_provider(pool.get_key(), state, token_amount, None, &pool).unwrap();
+
+            assert_eq!(result.consumed_amount, expected.consumed_amount);
+            assert_eq!(result.calculated_amount, expected.calculated_amount);
+            assert_eq!(result.fees_paid, expected.fees_paid);
+            assert_eq!(result.execution_resources, expected.execution_resources);
+            assert_eq!(
+                result.state_after.sqrt_ratio,
+                expected.state_after.sqrt_ratio
+            );
+            assert_eq!(result.state_after.liquidity, expected.state_after.liquidity);
+        }
+    }
+
+    #[test]
+    fn test_tick_after_crossing() {
+        let pool = base_pool();
+        let state = TickProviderPoolState {
+            sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+            liquidity: 6_000_000,
+            tick: 0,
+        };
+
+        let down = quote_with_tick_provider(
+            pool.get_key(),
+            state,
+            TokenAmount {
+                amount: 1_000_000_000,
+                token: TOKEN0,
+            },
+            Some(to_sqrt_ratio(-100).unwrap()),
+            &pool,
+        )
+        .unwrap();
+        assert_eq!(down.state_after.tick, -101);
+        assert_eq!(down.state_after.liquidity, 1_000_000);
+        assert_eq!(down.execution_resources.initialized_ticks_crossed, 1);
+
+        let up = quote_with_tick_provider(
+            pool.get_key(),
+            state,
+            TokenAmount {
+                amount: 100,
+                token: TOKEN1,
+            },
+            None,
+            &pool,
+        )
+        .unwrap();
+        assert_eq!(
+            up.state_after.tick,
+            sqrt_ratio_to_tick(up.state_after.sqrt_ratio)
+        );
+        assert_eq!(up.execution_resources.initialized_ticks_crossed, 0);
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct FetchFailed;
+
+    struct FailingProvider;
+
+    impl TickProvider for FailingProvider {
+        type Error = FetchFailed;
+
+        fn prev_initialized_tick(&self, _tick: i32) -> Result<Option<Tick>, Self::Error> {
+            Err(FetchFailed)
+        }
+
+        fn next_initialized_tick(&self, _tick: i32) -> Result<Option<Tick>, Self::Error> {
+            Err(FetchFailed)
+        }
+    }
+
+    #[test]
+    fn test_provider_error() {
+        let pool = base_pool();
+        assert_eq!(
+            quote_with_tick_provider(
+                pool.get_key(),
+                TickProviderPoolState {
+                    sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                    liquidity: 6_000_000,
+                    tick: 0,
+                },
+                TokenAmount {
+                    amount: 1_000,
+                    token: TOKEN0,
+                },
+                None,
+                &FailingProvider,
+            )
+            .unwrap_err(),
+            TickProviderQuoteError::TickProviderError(FetchFailed)
+        );
+    }
+
+    #[test]
+    fn test_inconsistent_ticks() {
+        let pool = base_pool();
+        // the provider does not know about the tick that added the active liquidity
+        let ticks = &sorted_ticks()[1..];
+
+        assert_eq!(
+            quote_with_tick_provider(
+                pool.get_key(),
+                TickProviderPoolState {
+                    sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                    liquidity: 0,
+                    tick: 0,
+                },
+                TokenAmount {
+                    amount: 1_000_000_000,
+                    token: TOKEN1,
+                },
+                None,
+                ticks,
+            )
+            .unwrap_err(),
+            TickProviderQuoteError::BasePoolQuoteError(BasePoolQuoteError::InvalidLiquidityDelta(
+                200
+            ))
+        );
+    }
+
+    #[cfg(feature = "async")]
+    mod asynchronous {
+        use super::*;
+        use crate::quoting::tick_provider::asynchronous::{
+            AsyncTickProvider, BlockOn, BlockingTickProvider,
+        };
+        use core::future::Future;
+        use core::pin::pin;
+        use core::task::{Context, Poll, Waker};
+
+        struct AsyncSortedTicks(Vec<Tick>);
+
+        impl AsyncTickProvider for AsyncSortedTicks {
+            type Error = Infallible;
+
+            async fn prev_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+                self.0.prev_initialized_tick(tick)
+            }
+
+            async fn next_initialized_tick(&self, tick: i32) -> Result<Option<Tick>, Self::Error> {
+                self.0.next_initialized_tick(tick)
+            }
+        }
+
+        struct PollOnce;
+
+        impl BlockOn for PollOnce {
+            fn block_on<F: Future>(&self, future: F) -> F::Output {
+                match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+                    Poll::Ready(output) => output,
+                    Poll::Pending => panic!("future is not ready"),
+                }
+            }
+        }
+
+        #[test]
+        fn test_blocking_provider() {
+            let pool = base_pool();
+            let provider = BlockingTickProvider {
+                provider: AsyncSortedTicks(sorted_ticks()),
+                executor: PollOnce,
+            };
+            let state = TickProviderPoolState {
+                sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                liquidity: 6_000_000,
+                tick: 0,
+            };
+            let token_amount = TokenAmount {
+                amount: 10_000_000,
+                token: TOKEN1,
+            };
+
+            assert_eq!(
+                quote_with_tick_provider(pool.get_key(), state, token_amount, None, &provider)
+                    .unwrap()
+                    .calculated_amount,
+                quote_with_tick_provider(pool.get_key(), state, token_amount, None, &pool)
+                    .unwrap()
+                    .calculated_amount
+            );
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use crate::math::uint::U256;
+use crate::quoting::base_pool::{BasePool, BasePoolResources, BasePoolState};
+use crate::quoting::events::PoolEvent;
+use crate::quoting::types::{Pool, Quote, Tick};
+use crate::quoting::util::find_nearest_initialized_tick_index;
+use alloc::vec::Vec;
+
+// An initialized tick crossed by a swap with the active liquidity after crossing it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TickCrossing {
+    pub tick: i32,
+    pub liquidity_after: u128,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TickDivergence {
+    /// The swap crossed different initialized ticks than quoted, starting at the crossing with the index.
+    CrossedTicks { index: usize },
+    /// The liquidity after the swap differs from the liquidity the local ticks imply at the tick the swap ended at,
+    /// which means that the local ticks are missing position updates.
+    Liquidity { local: u128, actual: u128 },
+    /// The swap ended at a different price than quoted.
+    SqrtRatio { quoted: U256, actual: U256 },
+}
+
+// The ticks a quote expected a swap to cross next to the ticks it actually crossed according to its event
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TickReconciliation {
+    pub expected: Vec<TickCrossing>,
+    pub actual: Vec<TickCrossing>,
+    pub divergences: Vec<TickDivergence>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TickReconciliationError {
+    /// The event is not a swap.
+    NotASwap,
+}
+
+impl TickReconciliation {
+    pub fn is_consistent(&self) -> bool {
+        self.divergences.is_empty()
+    }
+}
+
+// Returns the initialized ticks crossed moving the active tick index of the state to the target index
+fn crossings(
+    sorted_ticks: &[Tick],
+    state: &BasePoolState,
+    to_index: Option<usize>,
+) -> Vec<TickCrossing> {
+    let (from, to) = (
+        ticks_at_or_below(state.active_tick_index),
+        ticks_at_or_below(to_index),
+    );
+    let mut liquidity = state.liquidity;
+
+    if to >= from {
+        sorted_ticks[from..to]
+            .iter()
+            .map(|tick| {
+                liquidity = liquidity.saturating_add_signed(tick.liquidity_delta);
+                TickCrossing {
+                    tick: tick.index,
+                    liquidity_after: liquidity,
+                }
+            })
+            .collect()
+    } else {
+        sorted_ticks[to..from]
+            .iter()
+            .rev()
+            .map(|tick| {
+                liquidity = liquidity.saturating_add_signed(-tick.liquidity_delta);
+                TickCrossing {
+                    tick: tick.index,
+                    liquidity_after: liquidity,
+                }
+            })
+            .collect()
+    }
+}
+
+// Returns the number of initialized ticks at or below the active tick index
+fn ticks_at_or_below(index: Option<usize>) -> usize {
+    index.map_or(0, |index| index + 1)
+}
+
+/// Reconciles the quote of a swap against the pool with the `Swapped` event the swap emitted.
+///
+/// The ticks the swap actually crossed are derived from the local ticks of the pool between the state the quote
+/// started from and the tick in the event. Divergent crossings mean that the price moved differently than quoted,
+/// while a liquidity that differs from the one the local ticks imply means that the local state of the pool is stale.
+pub fn reconcile_swap(
+    pool: &BasePool,
+    state_before: Option<BasePoolState>,
+    quote: &Quote<BasePoolResources, BasePoolState>,
+    event: &PoolEvent,
+) -> Result<TickReconciliation, TickReconciliationError> {
+    let PoolEvent::Swapped {
+        sqrt_ratio_after,
+        tick_after,
+        liquidity_after,
+    } = *event
+    else {
+        return Err(TickReconciliationError::NotASwap);
+    };
+
+    let sorted_ticks = pool.get_sorted_ticks();
+    let state_before = state_before.unwrap_or_else(|| pool.get_state());
+
+    let expected = crossings(
+        sorted_ticks,
+        &state_before,
+        quote.state_after.active_tick_index,
+    );
+    let actual = crossings(
+        sorted_ticks,
+        &state_before,
+        find_nearest_initialized_tick_index(sorted_ticks, tick_after),
+    );
+
+    let mut divergences = Vec::new();
+    if let Some(index) = (0..expected.len().max(actual.len()))
+        .find(|index| expected.get(*index) != actual.get(*index))
+    {
+        divergences.push(TickDivergence::CrossedTicks { index });
+    }
+
+    let local = actual
+        .last()
+        .map_or(state_before.liquidity, |crossing| crossing.liquidity_after);
+    if local != liquidity_after {
+        divergences.push(TickDivergence::Liquidity {
+            local,
+            actual: liquidity_after,
+        });
+    }
+
+    if quote.state_after.sqrt_ratio != sqrt_ratio_after {
+        divergences.push(TickDivergence::SqrtRatio {
+            quoted: quote.state_after.sqrt_ratio,
+            actual: sqrt_ratio_after,
+        });
+    }
+
+    Ok(TickReconciliation {
+        expected,
+        actual,
+        divergences,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::tick::to_sqrt_ratio;
+    use crate::quoting::types::{Address, Config, NodeKey, QuoteParams, TokenAmount};
+    use alloc::vec;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn pool() -> BasePool {
+        BasePool::new(
+            NodeKey {
+                token0: TOKEN0,
+                token1: TOKEN1,
+                config: Config::from_raw_unchecked(0, 1, U256::zero()),
+            },
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(0).unwrap(),
+                liquidity: 1_000_000_000_000,
+                active_tick_index: Some(0),
+            },
+            vec![
+                Tick {
+                    index: -1,
+                    liquidity_delta: 1_000_000_000_000,
+                },
+                Tick {
+                    index: 1,
+                    liquidity_delta: 500_000_000_000,
+                },
+                Tick {
+                    index: 2,
+                    liquidity_delta: -1_500_000_000_000,
+                },
+            ],
+        )
+        .unwrap()
+    }
+
+    fn quote(pool: &BasePool, amount: i128) -> Quote<BasePoolResources, BasePoolState> {
+        pool.quote(QuoteParams {
+            token_amount: TokenAmount {
+                token: TOKEN1,
+                amount,
+            },
+            sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
+            override_state: None,
+            meta: (),
+        })
+        .unwrap()
+    }
+
+    fn swapped(state: BasePoolState, tick_after: i32) -> PoolEvent {
+        PoolEvent::Swapped {
+            sqrt_ratio_after: state.sqrt_ratio,
+            tick_after,
+            liquidity_after: state.liquidity,
+        }
+    }
+
+    #[test]
+    fn test_reconcile_consistent_swap() {
+        let pool = pool();
+        let quote = quote(&pool, 600_000);
+        let event = swapped(quote.state_after, 1);
+
+        let reconciliation = reconcile_swap(&pool, None, &quote, &event).unwrap();
+        assert!(reconciliation.is_consistent());
+        assert_eq!(
+            reconciliation.expected,
+            vec![TickCrossing {
+                tick: 1,
+                liquidity_after: 1_500_000_000_000
+            }]
+        );
+        assert_eq!(reconciliation.actual, reconciliation.expected);
+
+        assert_eq!(
+            reconcile_swap(
+                &pool,
+                None,
+                &quote,
+                &PoolEvent::PositionUpdated {
+                    tick_lower: -1,
+                    tick_upper: 1,
+                    liquidity_delta: 1,
+                }
+            ),
+            Err(TickReconciliationError::NotASwap)
+        );
+    }
+
+    #[test]
+    fn test_reconcile_divergent_swap() {
+        let pool = pool();
+        let quote = quote(&pool, 600_000);
+        // the swap went further than quoted with more liquidity than the local ticks know about
+        let event = PoolEvent::Swapped {
+            sqrt_ratio_after: to_sqrt_ratio(2).unwrap(),
+            tick_after: 2,
+            liquidity_after: 100,
+        };
+
+        let reconciliation = reconcile_swap(&pool, None, &quote, &event).unwrap();
+        assert_eq!(
+            reconciliation.actual,
+            vec![
+                TickCrossing {
+                    tick: 1,
+                    liquidity_after: 1_500_000_000_000
+                },
+                TickCrossing {
+                    tick: 2,
+                    liquidity_after: 0
+                }
+            ]
+        );
+        assert_eq!(
+            reconciliation.divergences,
+            vec![
+                TickDivergence::CrossedTicks { index: 1 },
+                TickDivergence::Liquidity {
+                    local: 0,
+                    actual: 100
+                },
+                TickDivergence::SqrtRatio {
+                    quoted: quote.state_after.sqrt_ratio,
+                    actual: to_sqrt_ratio(2).unwrap()
+                }
+            ]
+        );
+    }
+}
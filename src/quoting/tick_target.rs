@@ -0,0 +1,226 @@
+use crate::math::tick::{sqrt_ratio_to_tick, to_sqrt_ratio, MAX_TICK, MIN_TICK};
+use crate::math::uint::U256;
+use crate::quoting::types::{Address, Pool, QuoteParams, SqrtRatioState, TokenAmount};
+
+// The exact input swap that moves the price of a pool into a target tick
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TickTargetSwap {
+    // The token to swap in, token1 if the price has to increase and token0 otherwise
+    pub token: Address,
+    pub amount: u128,
+    // The sqrt ratio that the swap moves the price to, which is within the target tick
+    pub sqrt_ratio_target: U256,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TickTargetError<E> {
+    /// The target tick is outside of the range [MIN_TICK, MAX_TICK).
+    InvalidTargetTick,
+    /// The swap to the target price failed to quote.
+    QuoteFailed(E),
+    /// Swapping the amount does not leave the price in the target tick, e.g. because the pool has no liquidity there.
+    TargetNotReached,
+    /// The required input does not fit in an i128.
+    AmountOverflow,
+}
+
+// Returns the sqrt ratio closest to the current sqrt ratio that is within the target tick
+fn sqrt_ratio_in_tick(current_sqrt_ratio: U256, target_tick: i32) -> U256 {
+    let lower = to_sqrt_ratio(target_tick).unwrap();
+    if current_sqrt_ratio < lower {
+        lower
+    } else {
+        to_sqrt_ratio(target_tick + 1)
+            .unwrap()
+            .min(current_sqrt_ratio + 1)
+            - 1
+    }
+}
+
+/// Computes the exact input that moves the price of the pool into `target_tick`, i.e. so that the greatest tick whose
+/// sqrt ratio is less than or equal to the price after the swap is the target tick.
+///
+/// The amount is found by quoting with the nearest sqrt ratio within the target tick as the sqrt ratio limit, and
+/// is then checked by quoting it without a limit. The check fails if the pool has too little liquidity in the target
+/// tick to stop the price there. Returns a zero amount if the price is already within the target tick.
+pub fn amount_to_reach_tick<P: Pool>(
+    pool: &P,
+    target_tick: i32,
+    meta: P::Meta,
+) -> Result<TickTargetSwap, TickTargetError<P::QuoteError>>
+where
+    P::State: SqrtRatioState,
+{
+    if !(MIN_TICK..MAX_TICK).contains(&target_tick) {
+        return Err(TickTargetError::InvalidTargetTick);
+    }
+
+    let key = pool.get_key();
+    let sqrt_ratio = pool.get_state().sqrt_ratio();
+    let sqrt_ratio_target = sqrt_ratio_in_tick(sqrt_ratio, target_tick);
+    let token = if sqrt_ratio_target > sqrt_ratio {
+        key.token1
+    } else {
+        key.token0
+    };
+
+    if sqrt_ratio_target == sqrt_ratio {
+        return Ok(TickTargetSwap {
+            token,
+            amount: 0,
+            sqrt_ratio_target,
+        });
+    }
+
+    let quote = pool
+        .quote(QuoteParams {
+            token_amount: TokenAmount {
+                token,
+                amount: i128::MAX,
+            },
+            sqrt_ratio_limit: Some(sqrt_ratio_target),
+            max_ticks_crossed: None,
+            override_state: None,
+            meta,
+        })
+        .map_err(TickTargetError::QuoteFailed)?;
+
+    if quote.consumed_amount == i128::MAX {
+        return Err(TickTargetError::AmountOverflow);
+    }
+
+    // without a limit the fee and the price are rounded differently, so the price can stop just short of the target
+    for amount in [quote.consumed_amount, quote.consumed_amount + 1] {
+        let check = pool
+            .quote(QuoteParams {
+                token_amount: TokenAmount { token, amount },
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: None,
+                meta,
+            })
+            .map_err(TickTargetError::QuoteFailed)?;
+
+        if sqrt_ratio_to_tick(check.state_after.sqrt_ratio()) == target_tick {
+            return Ok(TickTargetSwap {
+                token,
+                amount: amount.unsigned_abs(),
+                sqrt_ratio_target,
+            });
+        }
+    }
+
+    Err(TickTargetError::TargetNotReached)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::base_pool::{BasePool, BasePoolQuoteError, BasePoolState};
+    use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+    use crate::quoting::types::{Config, NodeKey, Tick};
+    use alloc::vec;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn base_pool() -> BasePool {
+        BasePool::new(
+            NodeKey {
+                token0: TOKEN0,
+                token1: TOKEN1,
+                config: Config::from_raw_unchecked(1 << 60, 10, U256::zero()),
+            },
+            BasePoolState {
+                sqrt_ratio: to_sqrt_ratio(5).unwrap(),
+                liquidity: 1_000_000_000,
+                active_tick_index: Some(0),
+            },
+            vec![
+                Tick {
+                    index: -1000,
+                    liquidity_delta: 1_000_000_000,
+                },
+                Tick {
+                    index: 1000,
+                    liquidity_delta: -1_000_000_000,
+                },
+            ],
+        )
+        .unwrap()
+    }
+
+    fn landed_tick<P: Pool<Meta = ()>>(pool: &P, swap: TickTargetSwap) -> i32
+    where
+        P::State: SqrtRatioState,
+    {
+        let quote = pool
+            .quote(QuoteParams {
+                token_amount: TokenAmount {
+                    token: swap.token,
+                    amount: swap.amount as i128,
+                },
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: None,
+                meta: (),
+            })
+            .unwrap();
+        sqrt_ratio_to_tick(quote.state_after.sqrt_ratio())
+    }
+
+    #[test]
+    fn test_reaches_target_tick() {
+        let pool = base_pool();
+        for target in [-999, -500, -11, 4, 6, 10, 500, 999] {
+            let swap = amount_to_reach_tick(&pool, target, ()).unwrap();
+            assert_eq!(sqrt_ratio_to_tick(swap.sqrt_ratio_target), target);
+            assert_eq!(landed_tick(&pool, swap), target, "target {}", target);
+        }
+
+        let pool = FullRangePool::new(
+            NodeKey {
+                token0: TOKEN0,
+                token1: TOKEN1,
+                config: Config::from_raw_unchecked(0, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 1_000_000_000_000,
+            },
+        )
+        .unwrap();
+        for target in [-100_000, -1, 1, 100_000] {
+            let swap = amount_to_reach_tick(&pool, target, ()).unwrap();
+            assert_eq!(landed_tick(&pool, swap), target, "target {}", target);
+        }
+    }
+
+    #[test]
+    fn test_direction() {
+        let pool = base_pool();
+        assert_eq!(amount_to_reach_tick(&pool, 6, ()).unwrap().token, TOKEN1);
+        assert_eq!(amount_to_reach_tick(&pool, 4, ()).unwrap().token, TOKEN0);
+    }
+
+    #[test]
+    fn test_already_in_target_tick() {
+        let swap = amount_to_reach_tick(&base_pool(), 5, ()).unwrap();
+        assert_eq!(swap.amount, 0);
+        assert_eq!(swap.sqrt_ratio_target, to_sqrt_ratio(5).unwrap());
+    }
+
+    #[test]
+    fn test_errors() {
+        let pool = base_pool();
+        assert_eq!(
+            amount_to_reach_tick(&pool, MAX_TICK, ()),
+            Err(TickTargetError::<BasePoolQuoteError>::InvalidTargetTick)
+        );
+        // there is no liquidity beyond the last tick, so the price cannot stop at the target
+        assert_eq!(
+            amount_to_reach_tick(&pool, 2000, ()),
+            Err(TickTargetError::TargetNotReached)
+        );
+    }
+}
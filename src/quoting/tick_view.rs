@@ -0,0 +1,347 @@
+use crate::math::tick::{MAX_TICK, MAX_TICK_SPACING, MIN_TICK};
+use crate::quoting::types::Tick;
+use alloc::vec::Vec;
+use num_traits::Zero;
+
+// A consistent sorted tick array for a pool, e.g. as constructed by construct_sorted_ticks, together with the range
+// that was searched to build it. Ticks outside of the searched range are synthesized boundary ticks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialTickView {
+    pub sorted_ticks: Vec<Tick>,
+    pub min_tick_searched: i32,
+    pub max_tick_searched: i32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TickViewMergeError {
+    /// Tick spacing must be greater than zero and less than or equal to max tick spacing.
+    InvalidTickSpacing,
+    /// The min tick searched of a view is greater than its max tick searched.
+    InvalidSearchedRange,
+    /// The views disagree on the active liquidity at the tick within the range searched by both.
+    LiquidityMismatch { tick: i32 },
+}
+
+// The active liquidity of a view at each tick within its searched range
+struct LiquidityProfile<'a> {
+    view: &'a PartialTickView,
+    cumulative: Vec<i128>,
+    // the last tick after which the liquidity is known. The delta of an initialized tick at the boundary tick above
+    // the searched range is merged with the boundary delta, so the liquidity after crossing it is unknown.
+    max_tick: i32,
+}
+
+impl<'a> LiquidityProfile<'a> {
+    fn new(view: &'a PartialTickView, tick_spacing: u32) -> Self {
+        let cumulative = view
+            .sorted_ticks
+            .iter()
+            .scan(0i128, |liquidity, tick| {
+                *liquidity += tick.liquidity_delta;
+                Some(*liquidity)
+            })
+            .collect();
+
+        let spacing = tick_spacing as i32;
+        let valid_max_tick = (-(-(view.max_tick_searched as i64)).div_euclid(spacing as i64)
+            * spacing as i64)
+            .min(((MAX_TICK / spacing) * spacing) as i64) as i32;
+        Self {
+            view,
+            cumulative,
+            max_tick: view.max_tick_searched.min(valid_max_tick - 1),
+        }
+    }
+
+    // Ticks below the searched range are treated as the start of the range, since the boundary tick may have been
+    // merged into an initialized tick at the min tick searched
+    fn liquidity_at(&self, tick: i32) -> i128 {
+        let tick = tick.max(self.view.min_tick_searched);
+        match self.view.sorted_ticks.partition_point(|t| t.index <= tick) {
+            0 => 0,
+            count => self.cumulative[count - 1],
+        }
+    }
+
+    fn searched_tick_indices(&self) -> impl Iterator<Item = i32> + 'a {
+        let (min, max) = (self.view.min_tick_searched, self.max_tick);
+        self.view
+            .sorted_ticks
+            .iter()
+            .map(|tick| tick.index)
+            .filter(move |index| (min..=max).contains(index))
+    }
+}
+
+/// Merges two partial views of the ticks of the same pool into a single view covering both searched ranges.
+///
+/// Within the range searched by both views the active liquidity must agree, otherwise the first tick where they
+/// disagree is reported. If the searched ranges do not meet, the liquidity across the gap is assumed to be the
+/// liquidity at the end of the lower view, and a boundary tick is synthesized at the start of the upper view to
+/// move to its liquidity. Boundary ticks are also synthesized outside of the merged range so that the deltas sum to
+/// zero.
+pub fn merge_partial_tick_views(
+    a: &PartialTickView,
+    b: &PartialTickView,
+    tick_spacing: u32,
+) -> Result<PartialTickView, TickViewMergeError> {
+    if tick_spacing.is_zero() || tick_spacing > MAX_TICK_SPACING {
+        return Err(TickViewMergeError::InvalidTickSpacing);
+    }
+    if a.min_tick_searched > a.max_tick_searched || b.min_tick_searched > b.max_tick_searched {
+        return Err(TickViewMergeError::InvalidSearchedRange);
+    }
+
+    let (lower, upper) = if (a.min_tick_searched, a.max_tick_searched)
+        <= (b.min_tick_searched, b.max_tick_searched)
+    {
+        (
+            LiquidityProfile::new(a, tick_spacing),
+            LiquidityProfile::new(b, tick_spacing),
+        )
+    } else {
+        (
+            LiquidityProfile::new(b, tick_spacing),
+            LiquidityProfile::new(a, tick_spacing),
+        )
+    };
+
+    let spacing = tick_spacing as i64;
+    let align_down = |tick: i32| ((tick as i64).div_euclid(spacing) * spacing) as i32;
+    let align_up = |tick: i32| (-(-(tick as i64)).div_euclid(spacing) * spacing) as i32;
+
+    let lower_max = lower.max_tick;
+    let upper_min = upper.view.min_tick_searched;
+
+    // the liquidity in the range searched by both views must match
+    if upper_min <= lower_max {
+        let overlap_max = lower_max.min(upper.max_tick);
+        let overlapping = core::iter::once(upper_min)
+            .chain(lower.searched_tick_indices())
+            .chain(upper.searched_tick_indices())
+            .filter(|index| (upper_min..=overlap_max).contains(index));
+        for tick in overlapping {
+            if lower.liquidity_at(tick) != upper.liquidity_at(tick) {
+                return Err(TickViewMergeError::LiquidityMismatch { tick });
+            }
+        }
+    }
+
+    // the views meet if there is no initializable tick between them
+    let gap_start = (upper_min > lower_max && align_up(lower_max.saturating_add(1)) < upper_min)
+        .then(|| align_down(upper_min));
+
+    let liquidity_at = |tick: i32| {
+        if tick <= lower_max {
+            lower.liquidity_at(tick)
+        } else if tick >= upper_min || gap_start.is_none() {
+            upper.liquidity_at(tick)
+        } else {
+            lower.liquidity_at(lower_max)
+        }
+    };
+
+    let min_tick_searched = lower.view.min_tick_searched;
+    let max_tick_searched = lower
+        .view
+        .max_tick_searched
+        .max(upper.view.max_tick_searched);
+    let spacing_i32 = tick_spacing as i32;
+    let valid_min_tick = align_down(min_tick_searched).max((MIN_TICK / spacing_i32) * spacing_i32);
+    let valid_max_tick = align_up(max_tick_searched).min((MAX_TICK / spacing_i32) * spacing_i32);
+
+    let mut breakpoints: Vec<i32> = core::iter::once(valid_min_tick)
+        .chain(lower.searched_tick_indices())
+        .chain(upper.searched_tick_indices())
+        .chain(gap_start)
+        .filter(|index| *index < valid_max_tick)
+        .collect();
+    breakpoints.sort_unstable();
+    breakpoints.dedup();
+
+    let mut sorted_ticks = Vec::with_capacity(breakpoints.len() + 1);
+    let mut previous_liquidity = 0i128;
+    for index in breakpoints {
+        let liquidity = liquidity_at(index);
+        if liquidity != previous_liquidity {
+            sorted_ticks.push(Tick {
+                index,
+                liquidity_delta: liquidity - previous_liquidity,
+            });
+            previous_liquidity = liquidity;
+        }
+    }
+
+    // an initialized tick at the max tick is merged with the boundary tick, which brings the liquidity back to zero
+    let final_delta = -previous_liquidity;
+    if final_delta != 0 {
+        sorted_ticks.push(Tick {
+            index: valid_max_tick,
+            liquidity_delta: final_delta,
+        });
+    }
+
+    Ok(PartialTickView {
+        sorted_ticks,
+        min_tick_searched,
+        max_tick_searched,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::util::construct_sorted_ticks;
+    use alloc::vec;
+
+    // positions [-300, 300) with 100, [-50, 150) with 40 and [200, 400) with 7 liquidity
+    fn all_ticks() -> Vec<Tick> {
+        [
+            (-300, 100),
+            (-50, 40),
+            (150, -40),
+            (200, 7),
+            (300, -100),
+            (400, -7),
+        ]
+        .into_iter()
+        .map(|(index, liquidity_delta)| Tick {
+            index,
+            liquidity_delta,
+        })
+        .collect()
+    }
+
+    fn true_liquidity(tick: i32) -> i128 {
+        all_ticks()
+            .iter()
+            .filter(|t| t.index <= tick)
+            .map(|t| t.liquidity_delta)
+            .sum()
+    }
+
+    fn view(min_tick_searched: i32, max_tick_searched: i32, current_tick: i32) -> PartialTickView {
+        let partial_ticks = all_ticks()
+            .into_iter()
+            .filter(|t| t.index >= min_tick_searched && t.index <= max_tick_searched)
+            .collect();
+        PartialTickView {
+            sorted_ticks: construct_sorted_ticks(
+                partial_ticks,
+                min_tick_searched,
+                max_tick_searched,
+                10,
+                true_liquidity(current_tick) as u128,
+                current_tick,
+            )
+            .unwrap(),
+            min_tick_searched,
+            max_tick_searched,
+        }
+    }
+
+    fn assert_consistent(merged: &PartialTickView, ticks: impl Iterator<Item = i32>) {
+        assert_eq!(
+            merged
+                .sorted_ticks
+                .iter()
+                .map(|t| t.liquidity_delta)
+                .sum::<i128>(),
+            0
+        );
+        let profile = LiquidityProfile::new(merged, 10);
+        for tick in ticks {
+            assert_eq!(profile.liquidity_at(tick), true_liquidity(tick), "{tick}");
+        }
+    }
+
+    #[test]
+    fn test_merge_overlapping_views() {
+        let a = view(-125, 65, 0);
+        let b = view(35, 265, 100);
+
+        let merged = merge_partial_tick_views(&a, &b, 10).unwrap();
+        assert_eq!(
+            (merged.min_tick_searched, merged.max_tick_searched),
+            (-125, 265)
+        );
+        assert_consistent(&merged, (-125..=265).step_by(5));
+
+        // the order of the views does not matter
+        assert_eq!(merge_partial_tick_views(&b, &a, 10).unwrap(), merged);
+    }
+
+    #[test]
+    fn test_merge_contained_view() {
+        let a = view(-350, 450, 0);
+        let b = view(-20, 20, 0);
+
+        let merged = merge_partial_tick_views(&a, &b, 10).unwrap();
+        assert_eq!(merged.sorted_ticks, all_ticks());
+    }
+
+    #[test]
+    fn test_merge_views_with_gap() {
+        let a = view(-125, 0, 0);
+        let b = view(170, 265, 200);
+
+        let merged = merge_partial_tick_views(&a, &b, 10).unwrap();
+        assert_consistent(&merged, (-125..=0).chain(170..=265));
+
+        // the liquidity across the gap stays at the end of the lower view until the synthesized tick
+        let profile = LiquidityProfile::new(&merged, 10);
+        assert_eq!(profile.liquidity_at(100), true_liquidity(0));
+        assert_eq!(profile.liquidity_at(169), true_liquidity(0));
+        assert!(merged.sorted_ticks.contains(&Tick {
+            index: 170,
+            liquidity_delta: true_liquidity(170) - true_liquidity(0),
+        }));
+    }
+
+    #[test]
+    fn test_merge_adjacent_views() {
+        // there is no initializable tick between 61 and 69
+        let merged = merge_partial_tick_views(&view(-125, 61, 0), &view(69, 265, 100), 10).unwrap();
+        assert_consistent(&merged, -125..=265);
+    }
+
+    #[test]
+    fn test_merge_reports_liquidity_mismatch() {
+        let a = view(-125, 65, 0);
+        let mut b = view(35, 265, 100);
+        // the second view was built with a different liquidity
+        b.sorted_ticks[0].liquidity_delta += 1;
+
+        assert_eq!(
+            merge_partial_tick_views(&a, &b, 10),
+            Err(TickViewMergeError::LiquidityMismatch { tick: 35 })
+        );
+
+        let mut b = view(35, 265, 100);
+        let index = b.sorted_ticks.iter().position(|t| t.index == 150).unwrap();
+        b.sorted_ticks[index].index = 60;
+        b.sorted_ticks.sort_by_key(|t| t.index);
+        assert_eq!(
+            merge_partial_tick_views(&a, &b, 10),
+            Err(TickViewMergeError::LiquidityMismatch { tick: 60 })
+        );
+    }
+
+    #[test]
+    fn test_merge_errors() {
+        let a = view(-125, 65, 0);
+        assert_eq!(
+            merge_partial_tick_views(&a, &a, 0),
+            Err(TickViewMergeError::InvalidTickSpacing)
+        );
+        let invalid = PartialTickView {
+            sorted_ticks: vec![],
+            min_tick_searched: 10,
+            max_tick_searched: 0,
+        };
+        assert_eq!(
+            merge_partial_tick_views(&a, &invalid, 10),
+            Err(TickViewMergeError::InvalidSearchedRange)
+        );
+    }
+}
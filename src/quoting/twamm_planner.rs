@@ -0,0 +1,474 @@
+use crate::math::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
+use crate::math::twamm::sqrt_ratio::calculate_next_sqrt_ratio;
+use crate::math::uint::U256;
+use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolQuoteError, FullRangePoolState};
+use crate::quoting::twamm_pool::TwammPool;
+use crate::quoting::types::{Address, Pool, QuoteParams, SwapDirection, TokenAmount};
+use alloc::vec;
+use alloc::vec::Vec;
+
+// The amounts of an order executed with a single strategy
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExecutionEstimate {
+    pub amount_in: u128,
+    pub amount_out: u128,
+}
+
+impl ExecutionEstimate {
+    // The average price of the execution in output token per input token
+    pub fn average_price(&self) -> f64 {
+        if self.amount_in == 0 {
+            return 0f64;
+        }
+        self.amount_out as f64 / self.amount_in as f64
+    }
+}
+
+// The outcome of selling an amount across spot pools
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpotExecution {
+    pub estimate: ExecutionEstimate,
+    // The amount swapped through each pool, in the same order as the pools
+    pub allocation: Vec<u128>,
+}
+
+// The expected outcome of selling an amount through a TWAMM pool over time and through spot pools immediately
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecutionComparison {
+    pub twamm: ExecutionEstimate,
+    pub spot: SpotExecution,
+}
+
+impl ExecutionComparison {
+    // Returns true if the TWAMM order is expected to receive more than the spot swaps
+    pub fn prefers_twamm(&self) -> bool {
+        self.twamm.amount_out > self.spot.estimate.amount_out
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TwammExecutionError {
+    /// The token is not one of the tokens of the pool.
+    InvalidToken,
+    /// The amount and the duration must be greater than zero.
+    InvalidParameters,
+    /// The order cannot start before the last execution time of the pool.
+    StartBeforeLastExecution,
+    /// The sale rate or the sold amount of an interval does not fit in the pool math.
+    SaleRateOverflow,
+    /// An interval between two sale rate changes is longer than u32::MAX seconds.
+    IntervalTooLong,
+    /// Executing the virtual orders against the full range pool failed.
+    QuoteFailed(FullRangePoolQuoteError),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SpotExecutionError<E> {
+    /// The amount and the number of chunks must be greater than zero, and at least one pool is required.
+    InvalidParameters,
+    /// A spot pool failed to quote.
+    QuoteFailed(E),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExecutionPlanError<E> {
+    /// Estimating the TWAMM execution failed.
+    Twamm(TwammExecutionError),
+    /// Estimating the spot execution failed.
+    Spot(SpotExecutionError<E>),
+}
+
+fn amount_sold(sale_rate: u128, seconds: u32) -> u128 {
+    ((U256::from(sale_rate) * U256::from(seconds)) >> 32).low_u128()
+}
+
+/// Estimates the output of a TWAMM order that sells `amount` of `token` from `start_time` for `duration` seconds.
+///
+/// The virtual orders of the pool are executed the same way as when the pool is quoted, with the sale rate of the
+/// order added while it is active. In each interval the sellers of the token that is swapped into the pool receive
+/// the output of the swap and everything sold by the other side, and the other side receives the part of the swapped
+/// token that the pool did not take. The order gets a share in proportion to its sale rate. Swaps and orders that
+/// happen after the current state are not taken into account.
+pub fn estimate_twamm_execution(
+    pool: &TwammPool,
+    token: Address,
+    amount: u128,
+    start_time: u64,
+    duration: u32,
+) -> Result<ExecutionEstimate, TwammExecutionError> {
+    let key = *pool.get_key();
+    let is_token1 = SwapDirection::from_token_in(&key, token)
+        .map_err(|_| TwammExecutionError::InvalidToken)?
+        == SwapDirection::OneForZero;
+    if amount == 0 || duration == 0 {
+        return Err(TwammExecutionError::InvalidParameters);
+    }
+
+    let state = pool.get_state();
+    if start_time < state.last_execution_time {
+        return Err(TwammExecutionError::StartBeforeLastExecution);
+    }
+
+    let order_sale_rate = u128::try_from((U256::from(amount) << 32) / U256::from(duration))
+        .map_err(|_| TwammExecutionError::SaleRateOverflow)?;
+    let end_time = start_time + duration as u64;
+
+    // the TWAMM pool executes its virtual orders against a full range pool with the same key
+    let full_range_pool = FullRangePool::new(key, state.full_range_pool_state)
+        .map_err(|_| TwammExecutionError::InvalidParameters)?;
+    let mut full_range_state: FullRangePoolState = state.full_range_pool_state;
+    let liquidity = full_range_state.liquidity;
+    let mut next_sqrt_ratio = full_range_state.sqrt_ratio;
+
+    let mut sale_rates = [state.token0_sale_rate, state.token1_sale_rate];
+    let mut time = state.last_execution_time;
+    let mut deltas = pool
+        .get_sale_rate_deltas()
+        .iter()
+        .filter(|delta| delta.time > state.last_execution_time)
+        .peekable();
+    let mut amount_out: u128 = 0;
+
+    while time < end_time {
+        let mut next_time = end_time;
+        if time < start_time {
+            next_time = next_time.min(start_time);
+        }
+        if let Some(delta) = deltas.peek() {
+            next_time = next_time.min(delta.time);
+        }
+
+        let seconds =
+            u32::try_from(next_time - time).map_err(|_| TwammExecutionError::IntervalTooLong)?;
+        let is_active = time >= start_time;
+        let mut rates = sale_rates;
+        if is_active {
+            rates[is_token1 as usize] = rates[is_token1 as usize]
+                .checked_add(order_sale_rate)
+                .ok_or(TwammExecutionError::SaleRateOverflow)?;
+        }
+        let sold = [
+            amount_sold(rates[0], seconds),
+            amount_sold(rates[1], seconds),
+        ];
+
+        // the amount received by the sellers of each token
+        let mut received = [0u128; 2];
+        if sold[0] > 0 || sold[1] > 0 {
+            let (swap_token1, sqrt_ratio_limit) = if sold[0] > 0 && sold[1] > 0 {
+                let current_sqrt_ratio = next_sqrt_ratio.clamp(MIN_SQRT_RATIO, MAX_SQRT_RATIO);
+                next_sqrt_ratio = calculate_next_sqrt_ratio(
+                    current_sqrt_ratio,
+                    liquidity,
+                    rates[0],
+                    rates[1],
+                    seconds,
+                    key.config.fee.raw(),
+                );
+                (current_sqrt_ratio < next_sqrt_ratio, next_sqrt_ratio)
+            } else if sold[0] > 0 {
+                (false, MIN_SQRT_RATIO)
+            } else {
+                (true, MAX_SQRT_RATIO)
+            };
+
+            let (swapped, other) = (swap_token1 as usize, !swap_token1 as usize);
+            let quote = full_range_pool
+                .quote(QuoteParams {
+                    token_amount: TokenAmount {
+                        token: if swap_token1 { key.token1 } else { key.token0 },
+                        amount: i128::try_from(sold[swapped])
+                            .map_err(|_| TwammExecutionError::SaleRateOverflow)?,
+                    },
+                    sqrt_ratio_limit: Some(sqrt_ratio_limit),
+                    max_ticks_crossed: None,
+                    override_state: Some(full_range_state),
+                    meta: (),
+                })
+                .map_err(TwammExecutionError::QuoteFailed)?;
+            full_range_state = quote.state_after;
+            if sold[other] == 0 {
+                next_sqrt_ratio = quote.state_after.sqrt_ratio;
+            }
+
+            received[swapped] = quote.calculated_amount.saturating_add(sold[other]);
+            received[other] = sold[swapped] - quote.consumed_amount.unsigned_abs();
+        }
+
+        if is_active {
+            let index = is_token1 as usize;
+            amount_out = amount_out.saturating_add(
+                (U256::from(received[index]) * U256::from(order_sale_rate)
+                    / U256::from(rates[index]))
+                .low_u128(),
+            );
+        }
+
+        if let Some(delta) = deltas.next_if(|delta| delta.time == next_time) {
+            sale_rates[0] = sale_rates[0].saturating_add_signed(delta.sale_rate_delta0);
+            sale_rates[1] = sale_rates[1].saturating_add_signed(delta.sale_rate_delta1);
+        }
+        time = next_time;
+    }
+
+    Ok(ExecutionEstimate {
+        amount_in: amount_sold(order_sale_rate, duration),
+        amount_out,
+    })
+}
+
+/// Estimates the output of selling `amount` of `token` immediately across the spot pools, by swapping it in `chunks`
+/// equal parts and routing each part to the pool that returns the most for it, after the previous parts.
+pub fn estimate_spot_execution<P: Pool>(
+    pools: &[P],
+    token: Address,
+    amount: u128,
+    chunks: u32,
+    meta: P::Meta,
+) -> Result<SpotExecution, SpotExecutionError<P::QuoteError>> {
+    if amount == 0 || chunks == 0 || pools.is_empty() {
+        return Err(SpotExecutionError::InvalidParameters);
+    }
+
+    let mut states: Vec<P::State> = pools.iter().map(|pool| pool.get_state()).collect();
+    let mut allocation = vec![0u128; pools.len()];
+    let mut amount_out: u128 = 0;
+
+    let chunk = amount / chunks as u128;
+    for i in 0..chunks {
+        // the last chunk includes the remainder
+        let size = if i + 1 == chunks {
+            amount - chunk * (chunks as u128 - 1)
+        } else {
+            chunk
+        };
+        if size == 0 {
+            continue;
+        }
+        let size = i128::try_from(size).map_err(|_| SpotExecutionError::InvalidParameters)?;
+
+        let quotes = pools
+            .iter()
+            .zip(states.iter())
+            .map(|(pool, state)| {
+                pool.quote(QuoteParams {
+                    token_amount: TokenAmount {
+                        token,
+                        amount: size,
+                    },
+                    sqrt_ratio_limit: None,
+                    max_ticks_crossed: None,
+                    override_state: Some(*state),
+                    meta,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(SpotExecutionError::QuoteFailed)?;
+
+        // the first pool with the greatest output, there is at least one pool
+        let (index, quote) = quotes
+            .into_iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, quote)| quote.calculated_amount)
+            .unwrap();
+        states[index] = quote.state_after;
+        allocation[index] += quote.consumed_amount.unsigned_abs();
+        amount_out = amount_out.saturating_add(quote.calculated_amount);
+    }
+
+    Ok(SpotExecution {
+        estimate: ExecutionEstimate {
+            amount_in: allocation.iter().sum(),
+            amount_out,
+        },
+        allocation,
+    })
+}
+
+// The order to compare, executed either as a TWAMM order over `duration` seconds or as `chunks` immediate swaps
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExecutionPlan {
+    pub token: Address,
+    pub amount: u128,
+    pub start_time: u64,
+    pub duration: u32,
+    pub chunks: u32,
+}
+
+/// Compares selling an amount through a TWAMM order with selling it immediately across the spot pools, see
+/// [`estimate_twamm_execution`] and [`estimate_spot_execution`].
+pub fn compare_execution<P: Pool>(
+    twamm_pool: &TwammPool,
+    spot_pools: &[P],
+    plan: ExecutionPlan,
+    meta: P::Meta,
+) -> Result<ExecutionComparison, ExecutionPlanError<P::QuoteError>> {
+    let twamm = estimate_twamm_execution(
+        twamm_pool,
+        plan.token,
+        plan.amount,
+        plan.start_time,
+        plan.duration,
+    )
+    .map_err(ExecutionPlanError::Twamm)?;
+    let spot = estimate_spot_execution(spot_pools, plan.token, plan.amount, plan.chunks, meta)
+        .map_err(ExecutionPlanError::Spot)?;
+
+    Ok(ExecutionComparison { twamm, spot })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::twamm_pool::TwammSaleRateDelta;
+    use crate::quoting::types::{Config, NodeKey};
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+    const LIQUIDITY: u128 = 1_000_000_000_000;
+    const FEE: u64 = 1 << 55;
+
+    fn twamm_pool(token0_sale_rate: u128, token1_sale_rate: u128, end_time: u64) -> TwammPool {
+        let deltas = if token0_sale_rate == 0 && token1_sale_rate == 0 {
+            vec![]
+        } else {
+            vec![TwammSaleRateDelta {
+                time: end_time,
+                sale_rate_delta0: -(token0_sale_rate as i128),
+                sale_rate_delta1: -(token1_sale_rate as i128),
+            }]
+        };
+        TwammPool::new(
+            TOKEN0,
+            TOKEN1,
+            FEE,
+            U256::from(3),
+            U256::one() << 128,
+            LIQUIDITY,
+            0,
+            token0_sale_rate,
+            token1_sale_rate,
+            deltas,
+        )
+        .unwrap()
+    }
+
+    fn spot_pool(liquidity: u128) -> FullRangePool {
+        FullRangePool::new(
+            NodeKey {
+                token0: TOKEN0,
+                token1: TOKEN1,
+                config: Config::from_raw_unchecked(FEE, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_twamm_without_other_orders_matches_single_swap() {
+        let pool = twamm_pool(0, 0, 0);
+        let amount = 10_000_000_000;
+        let estimate = estimate_twamm_execution(&pool, TOKEN0, amount, 100, 1000).unwrap();
+        assert!(estimate.amount_in <= amount && estimate.amount_in > amount - 1000);
+
+        // the whole order is swapped in one interval, so it is the same as a single swap
+        let spot =
+            estimate_spot_execution(&[spot_pool(LIQUIDITY)], TOKEN0, estimate.amount_in, 1, ())
+                .unwrap();
+        assert_eq!(estimate.amount_out, spot.estimate.amount_out);
+    }
+
+    #[test]
+    fn test_opposing_orders_improve_twamm_price() {
+        let amount = 10_000_000_000;
+        let duration = 1000;
+        // an opposing order of the same size over the same period
+        let opposing_rate = (amount << 32) / duration as u128;
+        let pool = twamm_pool(0, opposing_rate, duration as u64);
+
+        let comparison = compare_execution(
+            &pool,
+            &[spot_pool(LIQUIDITY)],
+            ExecutionPlan {
+                token: TOKEN0,
+                amount,
+                start_time: 0,
+                duration,
+                chunks: 1,
+            },
+            (),
+        )
+        .unwrap();
+
+        assert!(comparison.prefers_twamm());
+        // the orders are matched against each other, so the price is close to 1
+        assert!(comparison.twamm.average_price() > 0.99);
+        assert!(comparison.spot.estimate.average_price() < 0.99);
+    }
+
+    #[test]
+    fn test_order_waits_for_start_time() {
+        let amount = 1_000_000_000;
+        // the existing order buys token0 until time 500, so an order that starts later sells at a higher price
+        let pool = twamm_pool(0, (10_000_000_000 << 32) / 500, 500);
+        let later = estimate_twamm_execution(&pool, TOKEN0, amount, 1000, 100).unwrap();
+        let overlapping = estimate_twamm_execution(&pool, TOKEN0, amount, 0, 100).unwrap();
+
+        assert!(later.average_price() > overlapping.average_price());
+        // an overlapping order is matched against the existing order, so it does not pay the fee on most of it
+        assert!(overlapping.average_price() > 1.0);
+    }
+
+    #[test]
+    fn test_spot_splits_across_pools() {
+        let pools = [spot_pool(LIQUIDITY), spot_pool(LIQUIDITY)];
+        let amount = 100_000_000_000;
+
+        let split = estimate_spot_execution(&pools, TOKEN1, amount, 10, ()).unwrap();
+        let single = estimate_spot_execution(&pools[..1], TOKEN1, amount, 10, ()).unwrap();
+
+        assert_eq!(split.allocation, vec![amount / 2, amount / 2]);
+        assert_eq!(split.estimate.amount_in, amount);
+        assert!(split.estimate.amount_out > single.estimate.amount_out);
+    }
+
+    #[test]
+    fn test_errors() {
+        let pool = twamm_pool(0, 0, 0);
+        assert_eq!(
+            estimate_twamm_execution(&pool, Address::from_raw(U256::from(5)), 1, 0, 1),
+            Err(TwammExecutionError::InvalidToken)
+        );
+        assert_eq!(
+            estimate_twamm_execution(&pool, TOKEN0, 1, 0, 0),
+            Err(TwammExecutionError::InvalidParameters)
+        );
+        assert_eq!(
+            estimate_spot_execution::<FullRangePool>(&[], TOKEN0, 1, 1, ()),
+            Err(SpotExecutionError::InvalidParameters)
+        );
+
+        let pool = TwammPool::new(
+            TOKEN0,
+            TOKEN1,
+            FEE,
+            U256::from(3),
+            U256::one() << 128,
+            LIQUIDITY,
+            100,
+            0,
+            0,
+            vec![],
+        )
+        .unwrap();
+        assert_eq!(
+            estimate_twamm_execution(&pool, TOKEN0, 1, 50, 1),
+            Err(TwammExecutionError::StartBeforeLastExecution)
+        );
+    }
+}
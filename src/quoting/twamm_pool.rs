@@ -4,8 +4,11 @@ use crate::math::uint::U256;
 use crate::quoting::full_range_pool::{
     FullRangePool, FullRangePoolQuoteError, FullRangePoolResources, FullRangePoolState,
 };
-use crate::quoting::types::{BlockTimestamp, Config};
-use crate::quoting::types::{NodeKey, Pool, Quote, QuoteParams, TokenAmount};
+use crate::quoting::pool_diff::{diff_sorted, FieldDiff, PoolDiff};
+use crate::quoting::pool_reads::{Inventory, PoolReadError, PoolReads};
+use crate::quoting::resources::MaxResourcesEstimate;
+use crate::quoting::types::{Address, BlockTimestamp, Config, Fee, TickSpacing};
+use crate::quoting::types::{NodeKey, Pool, Quote, QuoteParams, SqrtRatioState, TokenAmount};
 use alloc::vec::Vec;
 use core::ops::{Add, AddAssign, Sub, SubAssign};
 use num_traits::{ToPrimitive, Zero};
@@ -19,6 +22,13 @@ pub struct TwammPoolState {
     pub last_execution_time: u64,
 }
 
+// The price before the virtual orders since the last execution are executed
+impl SqrtRatioState for TwammPoolState {
+    fn sqrt_ratio(&self) -> U256 {
+        self.full_range_pool_state.sqrt_ratio
+    }
+}
+
 #[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
 pub struct TwammPoolResources {
     pub full_range_pool_resources: FullRangePoolResources,
@@ -28,14 +38,28 @@ pub struct TwammPoolResources {
     pub virtual_order_delta_times_crossed: u32,
     // Whether the virtual orders were executed or not (for a single swap, 1 or 0)
     pub virtual_orders_executed: u32,
+    // The number of time intervals (bounded by sale rate delta times) that virtual orders were executed over
+    pub virtual_order_intervals_executed: u32,
+    // The number of swaps against the full range pool that were performed to execute the virtual orders
+    pub virtual_order_swaps_executed: u32,
 }
 
+crate::impl_flatten_resources!(TwammPoolResources {
+    virtual_order_seconds_executed,
+    virtual_order_delta_times_crossed,
+    virtual_orders_executed,
+    virtual_order_intervals_executed,
+    virtual_order_swaps_executed
+} nested { full_range_pool_resources });
+
 impl AddAssign for TwammPoolResources {
     fn add_assign(&mut self, rhs: Self) {
         self.full_range_pool_resources += rhs.full_range_pool_resources;
         self.virtual_order_delta_times_crossed += rhs.virtual_order_delta_times_crossed;
         self.virtual_order_seconds_executed += rhs.virtual_order_seconds_executed;
         self.virtual_orders_executed += rhs.virtual_orders_executed;
+        self.virtual_order_intervals_executed += rhs.virtual_order_intervals_executed;
+        self.virtual_order_swaps_executed += rhs.virtual_order_swaps_executed;
     }
 }
 
@@ -54,6 +78,8 @@ impl SubAssign for TwammPoolResources {
         self.virtual_order_delta_times_crossed -= rhs.virtual_order_delta_times_crossed;
         self.virtual_order_seconds_executed -= rhs.virtual_order_seconds_executed;
         self.virtual_orders_executed -= rhs.virtual_orders_executed;
+        self.virtual_order_intervals_executed -= rhs.virtual_order_intervals_executed;
+        self.virtual_order_swaps_executed -= rhs.virtual_order_swaps_executed;
     }
 }
 
@@ -102,8 +128,8 @@ use crate::quoting::full_range_pool::FullRangePoolError;
 
 impl TwammPool {
     pub fn new(
-        token0: U256,
-        token1: U256,
+        token0: Address,
+        token1: Address,
         fee: u64,
         extension: U256,
         sqrt_ratio: U256,
@@ -153,11 +179,7 @@ impl TwammPool {
             NodeKey {
                 token0,
                 token1,
-                config: Config {
-                    fee,
-                    tick_spacing: 0,
-                    extension,
-                },
+                config: Config::new(Fee::from_raw(fee), TickSpacing::FULL_RANGE, extension),
             },
             FullRangePoolState {
                 // we just force the pool state to always be within the bounds of min/max to simplify the state
@@ -183,48 +205,31 @@ impl TwammPool {
     pub fn get_sale_rate_deltas(&self) -> &Vec<TwammSaleRateDelta> {
         &self.virtual_order_deltas
     }
-}
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub enum TwammPoolQuoteError {
-    ExecutionTimeExceedsBlockTime,
-    FailedCalculateNextSqrtRatio,
-    SaleAmountOverflow,
-    TooMuchTimePassedSinceLastExecution,
-    FullRangePoolQuoteError(FullRangePoolQuoteError),
-}
-
-impl Pool for TwammPool {
-    type Resources = TwammPoolResources;
-    type State = TwammPoolState;
-    type QuoteError = TwammPoolQuoteError;
-    type Meta = BlockTimestamp;
 
-    fn get_key(&self) -> &NodeKey {
-        self.full_range_pool.get_key()
-    }
-
-    fn get_state(&self) -> Self::State {
-        TwammPoolState {
-            full_range_pool_state: self.full_range_pool.get_state(),
-            last_execution_time: self.last_execution_time,
-            token0_sale_rate: self.token0_sale_rate,
-            token1_sale_rate: self.token1_sale_rate,
+    // Reports the fields that differ between this pool and the other pool, including added, removed and changed
+    // sale rate deltas
+    pub fn diff(&self, other: &Self) -> PoolDiff {
+        PoolDiff {
+            last_update_time: FieldDiff::of(self.last_execution_time, other.last_execution_time),
+            token0_sale_rate: FieldDiff::of(self.token0_sale_rate, other.token0_sale_rate),
+            token1_sale_rate: FieldDiff::of(self.token1_sale_rate, other.token1_sale_rate),
+            sale_rate_deltas: diff_sorted(
+                &self.virtual_order_deltas,
+                &other.virtual_order_deltas,
+                |delta| delta.time,
+            ),
+            ..self.full_range_pool.diff(&other.full_range_pool)
         }
     }
 
-    fn quote(
+    // Executes the virtual orders from the last execution time to the current time like the contract does before a
+    // swap, reporting each interval between sale rate changes as it is executed
+    pub(crate) fn execute_virtual_orders(
         &self,
-        params: QuoteParams<Self::State, Self::Meta>,
-    ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
-        let QuoteParams {
-            token_amount,
-            sqrt_ratio_limit,
-            override_state,
-            meta,
-        } = params;
-
-        let current_time = meta;
+        override_state: Option<TwammPoolState>,
+        current_time: BlockTimestamp,
+        mut on_interval: impl FnMut(&VirtualOrderInterval),
+    ) -> Result<VirtualOrderExecution, TwammPoolQuoteError> {
         let initial_state = override_state.unwrap_or_else(|| self.get_state());
 
         let mut next_sqrt_ratio = initial_state.full_range_pool_state.sqrt_ratio;
@@ -237,6 +242,8 @@ impl Pool for TwammPool {
         }
 
         let mut virtual_order_delta_times_crossed = 0;
+        let mut virtual_order_intervals_executed = 0;
+        let mut virtual_order_swaps_executed = 0;
         let mut next_sale_rate_delta_index = self
             .virtual_order_deltas
             .iter()
@@ -269,6 +276,7 @@ impl Pool for TwammPool {
                 ((U256::from(token0_sale_rate) * U256::from(time_elapsed)) >> 32).low_u128();
             let amount1: u128 =
                 ((U256::from(token1_sale_rate) * U256::from(time_elapsed)) >> 32).low_u128();
+            let mut proceeds: (u128, u128) = (0, 0);
 
             if amount0 > 0 && amount1 > 0 {
                 let current_sqrt_ratio = next_sqrt_ratio.min(MAX_SQRT_RATIO).max(MIN_SQRT_RATIO);
@@ -280,10 +288,11 @@ impl Pool for TwammPool {
                     token0_sale_rate,
                     token1_sale_rate,
                     time_elapsed as u32,
-                    config.fee,
+                    config.fee.raw(),
                 );
 
-                let (token, amount) = if current_sqrt_ratio < next_sqrt_ratio {
+                let is_token1 = current_sqrt_ratio < next_sqrt_ratio;
+                let (token, amount) = if is_token1 {
                     (token1, amount1)
                 } else {
                     (token0, amount0)
@@ -299,13 +308,29 @@ impl Pool for TwammPool {
                             token: *token,
                         },
                         sqrt_ratio_limit: Some(next_sqrt_ratio),
+                        max_ticks_crossed: None,
                         override_state: full_range_pool_state_override,
                         meta: (),
                     })
                     .map_err(TwammPoolQuoteError::FullRangePoolQuoteError)?;
 
+                // the sellers of the token that is swapped receive what is left of the other token, and the sellers of
+                // the other token also receive the output of the swap
+                proceeds = if is_token1 {
+                    (
+                        amount1.saturating_sub(quote.consumed_amount.unsigned_abs()),
+                        amount0.saturating_add(quote.calculated_amount),
+                    )
+                } else {
+                    (
+                        amount1.saturating_add(quote.calculated_amount),
+                        amount0.saturating_sub(quote.consumed_amount.unsigned_abs()),
+                    )
+                };
+
                 full_range_pool_state_override = Some(quote.state_after);
                 full_range_pool_execution_resources += quote.execution_resources;
+                virtual_order_swaps_executed += 1;
             } else if amount0 > 0 || amount1 > 0 {
                 let (amount, is_token1, sqrt_ratio_limit) = if amount0 != 0 {
                     (amount0, false, MIN_SQRT_RATIO)
@@ -329,18 +354,39 @@ impl Pool for TwammPool {
                             token,
                         },
                         sqrt_ratio_limit: Some(sqrt_ratio_limit),
+                        max_ticks_crossed: None,
                         override_state: full_range_pool_state_override,
                         meta: (),
                     })
                     .map_err(TwammPoolQuoteError::FullRangePoolQuoteError)?;
 
+                proceeds = if is_token1 {
+                    (0, quote.calculated_amount)
+                } else {
+                    (quote.calculated_amount, 0)
+                };
+
                 full_range_pool_state_override = Some(quote.state_after);
                 full_range_pool_execution_resources =
                     full_range_pool_execution_resources + quote.execution_resources;
+                virtual_order_swaps_executed += 1;
 
                 next_sqrt_ratio = quote.state_after.sqrt_ratio;
             }
 
+            on_interval(&VirtualOrderInterval {
+                start_time: last_execution_time,
+                end_time: next_execution_time,
+                token0_sale_rate,
+                token1_sale_rate,
+                sqrt_ratio_after: full_range_pool_state_override
+                    .map_or(initial_state.full_range_pool_state.sqrt_ratio, |state| {
+                        state.sqrt_ratio
+                    }),
+                proceeds0: proceeds.0,
+                proceeds1: proceeds.1,
+            });
+
             if let Some(next_delta) = sale_rate_delta {
                 if next_delta.time == next_execution_time {
                     token0_sale_rate = if next_delta.sale_rate_delta0 < 0 {
@@ -359,13 +405,106 @@ impl Pool for TwammPool {
             }
 
             last_execution_time = next_execution_time;
+            virtual_order_intervals_executed += 1;
         }
 
+        Ok(VirtualOrderExecution {
+            full_range_pool_state_override,
+            full_range_pool_execution_resources,
+            token0_sale_rate,
+            token1_sale_rate,
+            virtual_order_delta_times_crossed,
+            virtual_order_intervals_executed,
+            virtual_order_swaps_executed,
+        })
+    }
+}
+
+// The virtual orders executed over an interval in which the sale rates do not change
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VirtualOrderInterval {
+    pub start_time: BlockTimestamp,
+    pub end_time: BlockTimestamp,
+    pub token0_sale_rate: u128,
+    pub token1_sale_rate: u128,
+    // The price of the pool after the interval is executed
+    pub sqrt_ratio_after: U256,
+    // The amount of token1 that the sellers of token0 receive
+    pub proceeds0: u128,
+    // The amount of token0 that the sellers of token1 receive
+    pub proceeds1: u128,
+}
+
+// The state and resources after executing the virtual orders, before the swap of a quote
+pub(crate) struct VirtualOrderExecution {
+    pub full_range_pool_state_override: Option<FullRangePoolState>,
+    pub full_range_pool_execution_resources: FullRangePoolResources,
+    pub token0_sale_rate: u128,
+    pub token1_sale_rate: u128,
+    pub virtual_order_delta_times_crossed: u32,
+    pub virtual_order_intervals_executed: u32,
+    pub virtual_order_swaps_executed: u32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TwammPoolQuoteError {
+    ExecutionTimeExceedsBlockTime,
+    FailedCalculateNextSqrtRatio,
+    SaleAmountOverflow,
+    TooMuchTimePassedSinceLastExecution,
+    FullRangePoolQuoteError(FullRangePoolQuoteError),
+}
+
+impl Pool for TwammPool {
+    type Resources = TwammPoolResources;
+    type State = TwammPoolState;
+    type QuoteError = TwammPoolQuoteError;
+    type Meta = BlockTimestamp;
+
+    fn get_key(&self) -> &NodeKey {
+        self.full_range_pool.get_key()
+    }
+
+    fn get_state(&self) -> Self::State {
+        TwammPoolState {
+            full_range_pool_state: self.full_range_pool.get_state(),
+            last_execution_time: self.last_execution_time,
+            token0_sale_rate: self.token0_sale_rate,
+            token1_sale_rate: self.token1_sale_rate,
+        }
+    }
+
+    fn quote(
+        &self,
+        params: QuoteParams<Self::State, Self::Meta>,
+    ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
+        let QuoteParams {
+            token_amount,
+            sqrt_ratio_limit,
+            override_state,
+            meta,
+            ..
+        } = params;
+
+        let current_time = meta;
+        let initial_state = override_state.unwrap_or_else(|| self.get_state());
+
+        let VirtualOrderExecution {
+            full_range_pool_state_override,
+            full_range_pool_execution_resources,
+            token0_sale_rate,
+            token1_sale_rate,
+            virtual_order_delta_times_crossed,
+            virtual_order_intervals_executed,
+            virtual_order_swaps_executed,
+        } = self.execute_virtual_orders(override_state, current_time, |_| {})?;
+
         let final_quote = self
             .full_range_pool
             .quote(QuoteParams {
                 token_amount,
                 sqrt_ratio_limit,
+                max_ticks_crossed: None,
                 meta: (),
                 override_state: full_range_pool_state_override,
             })
@@ -387,6 +526,8 @@ impl Pool for TwammPool {
                 } else {
                     0
                 },
+                virtual_order_intervals_executed,
+                virtual_order_swaps_executed,
             },
             state_after: TwammPoolState {
                 full_range_pool_state: final_quote.state_after,
@@ -414,31 +555,71 @@ impl Pool for TwammPool {
     }
 }
 
+// The virtual orders are executed over an interval for each sale rate delta that has not been executed yet, with one
+// swap per interval, and the seconds executed depend on the time of the quote, so they are bounded only by the most
+// the pool executes at once
+impl MaxResourcesEstimate for TwammPool {
+    fn max_resources_estimate(&self, amount_bound: u128) -> Self::Resources {
+        let pending_deltas: u32 = self
+            .virtual_order_deltas
+            .iter()
+            .filter(|delta| delta.time > self.last_execution_time)
+            .count()
+            .try_into()
+            .unwrap_or(u32::MAX);
+        let intervals = pending_deltas.saturating_add(1);
+
+        TwammPoolResources {
+            full_range_pool_resources: FullRangePoolResources {
+                no_override_price_change: intervals.saturating_add(
+                    self.full_range_pool
+                        .max_resources_estimate(amount_bound)
+                        .no_override_price_change,
+                ),
+            },
+            virtual_order_seconds_executed: u32::MAX,
+            virtual_order_delta_times_crossed: pending_deltas,
+            virtual_orders_executed: 1,
+            virtual_order_intervals_executed: intervals,
+            virtual_order_swaps_executed: intervals,
+        }
+    }
+}
+
+// The tokens of the orders are not liquidity of the pool, so only the full range liquidity is counted
+impl PoolReads for TwammPool {
+    fn inventory_at(&self, sqrt_ratio: U256) -> Result<Inventory, PoolReadError> {
+        self.full_range_pool.inventory_at(sqrt_ratio)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::math::tick::{to_sqrt_ratio, MAX_SQRT_RATIO, MIN_SQRT_RATIO};
     use crate::math::uint::U256;
+    use crate::quoting::pool_diff::{EntryDiff, FieldDiff, PoolDiff};
+    use crate::quoting::resources::MaxResourcesEstimate;
     use crate::quoting::twamm_pool::{TwammPool, TwammSaleRateDelta};
-    use crate::quoting::types::{Pool, QuoteParams, TokenAmount};
+    use crate::quoting::types::{Address, Pool, QuoteParams, TokenAmount};
     use alloc::vec;
 
-    const TOKEN0: U256 = U256([1, 0, 0, 0]);
-    const TOKEN1: U256 = U256([2, 0, 0, 0]);
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
 
     mod constructor_validation {
         use crate::math::tick::{MAX_SQRT_RATIO, MIN_SQRT_RATIO};
         use crate::math::uint::U256;
         use crate::quoting::twamm_pool::TwammPoolError;
         use crate::quoting::twamm_pool::{TwammPool, TwammSaleRateDelta};
-        use crate::quoting::types::Pool;
+        use crate::quoting::types::{Address, Pool};
         use alloc::vec;
 
         #[test]
         fn test_max_price_constructor() {
             assert_eq!(
                 TwammPool::new(
-                    U256::one(),
-                    U256::one() + 1,
+                    Address::from_raw(U256::one()),
+                    Address::from_raw(U256::one() + 1),
                     0,
                     U256::zero(),
                     MAX_SQRT_RATIO,
@@ -460,8 +641,8 @@ mod tests {
         fn test_min_price_constructor() {
             assert_eq!(
                 TwammPool::new(
-                    U256::one(),
-                    U256::one() + 1,
+                    Address::from_raw(U256::one()),
+                    Address::from_raw(U256::one() + 1),
                     0,
                     U256::zero(),
                     MIN_SQRT_RATIO,
@@ -483,8 +664,8 @@ mod tests {
         fn test_min_sqrt_ratio() {
             assert_eq!(
                 TwammPool::new(
-                    U256::one(),
-                    U256::one() + 1,
+                    Address::from_raw(U256::one()),
+                    Address::from_raw(U256::one() + 1),
                     0,
                     U256::zero(),
                     MIN_SQRT_RATIO,
@@ -506,8 +687,8 @@ mod tests {
         fn test_max_sqrt_ratio() {
             assert_eq!(
                 TwammPool::new(
-                    U256::one(),
-                    U256::one() + 1,
+                    Address::from_raw(U256::one()),
+                    Address::from_raw(U256::one() + 1),
                     0,
                     U256::zero(),
                     MAX_SQRT_RATIO,
@@ -528,8 +709,8 @@ mod tests {
         #[test]
         fn test_sale_rate_deltas_must_be_gt_last_execution_time() {
             let result = TwammPool::new(
-                U256::one(),
-                U256::one() + 1,
+                Address::from_raw(U256::one()),
+                Address::from_raw(U256::one() + 1),
                 0,
                 U256::zero(),
                 MAX_SQRT_RATIO,
@@ -549,8 +730,8 @@ mod tests {
         #[test]
         fn test_sale_rate_deltas_must_be_ordered() {
             let result = TwammPool::new(
-                U256::one(),
-                U256::one() + 1,
+                Address::from_raw(U256::one()),
+                Address::from_raw(U256::one() + 1),
                 0,
                 U256::zero(),
                 MAX_SQRT_RATIO,
@@ -577,8 +758,8 @@ mod tests {
         #[test]
         fn test_sale_rate_deltas_must_sum_to_zero() {
             let result = TwammPool::new(
-                U256::one(),
-                U256::one() + 1,
+                Address::from_raw(U256::one()),
+                Address::from_raw(U256::one() + 1),
                 0,
                 U256::zero(),
                 MAX_SQRT_RATIO,
@@ -605,8 +786,8 @@ mod tests {
         #[test]
         fn test_sale_rate_deltas_sum_to_zero() {
             let result = TwammPool::new(
-                U256::one(),
-                U256::one() + 1,
+                Address::from_raw(U256::one()),
+                Address::from_raw(U256::one() + 1),
                 0,
                 U256::zero(),
                 MAX_SQRT_RATIO,
@@ -653,6 +834,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: Some(MIN_SQRT_RATIO),
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -665,6 +847,11 @@ mod tests {
             quote.execution_resources.virtual_order_delta_times_crossed,
             0
         );
+        assert_eq!(
+            quote.execution_resources.virtual_order_intervals_executed,
+            1
+        );
+        assert_eq!(quote.execution_resources.virtual_order_swaps_executed, 0);
     }
 
     #[test]
@@ -689,6 +876,7 @@ mod tests {
                 token: TOKEN1,
             },
             sqrt_ratio_limit: Some(MAX_SQRT_RATIO),
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -729,6 +917,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: Some(MIN_SQRT_RATIO),
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -769,6 +958,7 @@ mod tests {
                 token: TOKEN1,
             },
             sqrt_ratio_limit: Some(MAX_SQRT_RATIO),
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -812,6 +1002,7 @@ mod tests {
                 token: TOKEN1,
             },
             sqrt_ratio_limit: Some(MAX_SQRT_RATIO),
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -863,6 +1054,7 @@ mod tests {
                 },
                 meta: 32,
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 override_state: None,
             })
             .expect("Quote should succeed");
@@ -910,6 +1102,7 @@ mod tests {
             },
             meta: 32,
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
         });
 
@@ -959,6 +1152,7 @@ mod tests {
                 },
                 meta: 32,
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 override_state: None,
             })
             .expect("swap succeeds");
@@ -1006,6 +1200,7 @@ mod tests {
             },
             meta: 32,
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
         });
 
@@ -1046,6 +1241,7 @@ mod tests {
                 token: TOKEN1,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -1087,6 +1283,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -1128,6 +1325,7 @@ mod tests {
                 token: TOKEN1,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -1169,6 +1367,7 @@ mod tests {
                 token: TOKEN1,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -1210,6 +1409,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -1251,6 +1451,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -1292,6 +1493,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -1305,6 +1507,11 @@ mod tests {
             quote.execution_resources.virtual_order_delta_times_crossed,
             1
         );
+        assert_eq!(
+            quote.execution_resources.virtual_order_intervals_executed,
+            2
+        );
+        assert_eq!(quote.execution_resources.virtual_order_swaps_executed, 1);
     }
 
     #[test]
@@ -1340,6 +1547,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             meta: 32,
             override_state: None,
         });
@@ -1355,6 +1563,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn max_resources_estimate_bounds_quote() {
+        let pool = TwammPool::new(
+            TOKEN0,
+            TOKEN1,
+            0u64,
+            U256::from(1u8),
+            to_sqrt_ratio(1i32).unwrap(),
+            100_000u128,
+            0u64,
+            1 << 32u128,
+            1 << 32u128,
+            vec![
+                TwammSaleRateDelta {
+                    sale_rate_delta0: 2i128.pow(32),
+                    sale_rate_delta1: 2i128.pow(32),
+                    time: 16u64,
+                },
+                TwammSaleRateDelta {
+                    time: u64::MAX,
+                    sale_rate_delta0: -(1 << 33),
+                    sale_rate_delta1: -(1 << 33),
+                },
+            ],
+        )
+        .expect("Pool creation should succeed");
+
+        let estimate = pool.max_resources_estimate(1000);
+        assert_eq!(estimate.virtual_order_delta_times_crossed, 2);
+        assert_eq!(estimate.virtual_order_swaps_executed, 3);
+
+        let resources = pool
+            .quote(QuoteParams {
+                token_amount: TokenAmount {
+                    amount: 1000,
+                    token: TOKEN0,
+                },
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                meta: 32,
+                override_state: None,
+            })
+            .expect("Quote should succeed")
+            .execution_resources;
+
+        assert!(
+            resources.full_range_pool_resources.no_override_price_change
+                <= estimate.full_range_pool_resources.no_override_price_change
+        );
+        assert!(
+            resources.virtual_order_seconds_executed <= estimate.virtual_order_seconds_executed
+        );
+        assert!(
+            resources.virtual_order_delta_times_crossed
+                <= estimate.virtual_order_delta_times_crossed
+        );
+        assert!(resources.virtual_orders_executed <= estimate.virtual_orders_executed);
+        assert!(
+            resources.virtual_order_intervals_executed <= estimate.virtual_order_intervals_executed
+        );
+        assert!(resources.virtual_order_swaps_executed <= estimate.virtual_order_swaps_executed);
+    }
+
     #[test]
     fn price_after_no_swap() {
         let pool = TwammPool::new(
@@ -1383,6 +1654,7 @@ mod tests {
                     token: TOKEN0,
                 },
                 sqrt_ratio_limit: Some(to_sqrt_ratio(693147i32).unwrap()),
+                max_ticks_crossed: None,
                 meta: 43_200,
                 override_state: None,
             })
@@ -1395,6 +1667,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: Some(to_sqrt_ratio(693147).unwrap()),
+            max_ticks_crossed: None,
             meta: 86_400,
             override_state: None,
         })
@@ -1407,6 +1680,7 @@ mod tests {
                 token: TOKEN0,
             },
             sqrt_ratio_limit: Some(to_sqrt_ratio(693147).unwrap()),
+            max_ticks_crossed: None,
             meta: 86_400,
             override_state: Some(first.state_after),
         })
@@ -1441,6 +1715,7 @@ mod tests {
             },
             meta: 60,
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
         })
         .expect("quote after 60 seconds");
@@ -1452,6 +1727,7 @@ mod tests {
             },
             meta: 90,
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             override_state: None,
         })
         .expect("quote after 90 seconds");
@@ -1464,6 +1740,7 @@ mod tests {
                 },
                 meta: 120,
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 override_state: None,
             })
             .expect("quote after 120 seconds");
@@ -1478,6 +1755,7 @@ mod tests {
                 },
                 meta: 120,
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 override_state: Some(state_after_fully_executed),
             })
             .expect("quote with override");
@@ -1493,6 +1771,7 @@ mod tests {
                     meta: (),
                     override_state: Some(state_after_fully_executed.full_range_pool_state),
                     sqrt_ratio_limit: None,
+                    max_ticks_crossed: None,
                 })
                 .expect("base pool quote")
                 .calculated_amount
@@ -1505,6 +1784,7 @@ mod tests {
                     token: TOKEN1,
                 },
                 sqrt_ratio_limit: Some(to_sqrt_ratio(693147).unwrap()),
+                max_ticks_crossed: None,
                 meta: 120,
                 override_state: Some(state_after_fully_executed),
             })
@@ -1522,6 +1802,7 @@ mod tests {
                     meta: (),
                     override_state: Some(fully_executed_twamm.state_after.full_range_pool_state),
                     sqrt_ratio_limit: Some(to_sqrt_ratio(693147).unwrap()),
+                    max_ticks_crossed: None,
                 })
                 .unwrap()
                 .calculated_amount
@@ -1557,6 +1838,7 @@ mod tests {
                 },
                 meta: 2_040,
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 override_state: None,
             })
             .expect("first swap succeeds");
@@ -1585,6 +1867,7 @@ mod tests {
                 },
                 meta: 2_100,
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 override_state: Some(first_swap.state_after),
             })
             .expect("second swap succeeds");
@@ -1633,6 +1916,7 @@ mod tests {
                     token: TOKEN0,
                 },
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
                 meta: 2_040,
                 override_state: None,
             })
@@ -1645,6 +1929,7 @@ mod tests {
                 token: TOKEN1,
             },
             sqrt_ratio_limit: None,
+            max_ticks_crossed: None,
             meta: 2_100,
             override_state: Some(first_swap.state_after),
         })
@@ -1697,10 +1982,55 @@ mod tests {
                 meta: 1743783660,
                 override_state: None,
                 sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
             })
             .unwrap();
 
         assert_eq!(result.consumed_amount, 50000000000000000);
         assert_eq!(result.calculated_amount, 126983565);
     }
+
+    #[test]
+    fn test_diff() {
+        let pool = |sale_rate: u128, end_time: u64| {
+            TwammPool::new(
+                TOKEN0,
+                TOKEN1,
+                0,
+                U256::zero(),
+                U256::one() << 128,
+                1_000_000,
+                0,
+                sale_rate,
+                0,
+                vec![TwammSaleRateDelta {
+                    time: end_time,
+                    sale_rate_delta0: -(sale_rate as i128),
+                    sale_rate_delta1: 0,
+                }],
+            )
+            .unwrap()
+        };
+
+        assert!(pool(100, 16).diff(&pool(100, 16)).is_empty());
+        assert_eq!(
+            pool(100, 16).diff(&pool(200, 32)),
+            PoolDiff {
+                token0_sale_rate: FieldDiff::of(100, 200),
+                sale_rate_deltas: vec![
+                    EntryDiff::Removed(TwammSaleRateDelta {
+                        time: 16,
+                        sale_rate_delta0: -100,
+                        sale_rate_delta1: 0,
+                    }),
+                    EntryDiff::Added(TwammSaleRateDelta {
+                        time: 32,
+                        sale_rate_delta0: -200,
+                        sale_rate_delta1: 0,
+                    }),
+                ],
+                ..PoolDiff::default()
+            }
+        );
+    }
 }
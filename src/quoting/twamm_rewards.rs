@@ -0,0 +1,191 @@
+use crate::math::muldiv::muldiv;
+use crate::math::uint::U256;
+use crate::quoting::twamm_pool::{
+    TwammPool, TwammPoolQuoteError, TwammPoolState, VirtualOrderInterval,
+};
+use crate::quoting::types::BlockTimestamp;
+use alloc::vec::Vec;
+
+// The reward rates of a TWAMM pool, i.e. the cumulative amount of the bought token per unit of sale rate, as 128.128
+// fixed point numbers. Like the contract, the rates wrap on overflow, so only the difference between two rates is
+// meaningful.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RewardRates {
+    // The token1 received per unit of token0 sale rate
+    pub reward_rate0: U256,
+    // The token0 received per unit of token1 sale rate
+    pub reward_rate1: U256,
+}
+
+impl RewardRates {
+    // Accumulates the proceeds of the interval, split over the sale rates that earned them
+    pub fn accumulate(&mut self, interval: &VirtualOrderInterval) {
+        self.reward_rate0 = self
+            .reward_rate0
+            .overflowing_add(reward_rate_delta(
+                interval.proceeds0,
+                interval.token0_sale_rate,
+            ))
+            .0;
+        self.reward_rate1 = self
+            .reward_rate1
+            .overflowing_add(reward_rate_delta(
+                interval.proceeds1,
+                interval.token1_sale_rate,
+            ))
+            .0;
+    }
+}
+
+// The reward rates accumulated since the start of the execution window at the end of an interval
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RewardRateCheckpoint {
+    pub time: BlockTimestamp,
+    pub sqrt_ratio: U256,
+    pub reward_rates: RewardRates,
+}
+
+// Returns the increase of the reward rate for the proceeds of a sale rate, which is zero if nothing is sold
+pub fn reward_rate_delta(proceeds: u128, sale_rate: u128) -> U256 {
+    if sale_rate == 0 {
+        return U256::zero();
+    }
+    (U256::from(proceeds) << 128) / U256::from(sale_rate)
+}
+
+// Returns the proceeds of an order with the sale rate that was active while the reward rate increased from the start to
+// the end, rounded down
+pub fn order_proceeds(sale_rate: u128, reward_rate_start: U256, reward_rate_end: U256) -> u128 {
+    let delta = reward_rate_end.overflowing_sub(reward_rate_start).0;
+    muldiv(delta, U256::from(sale_rate), U256::one() << 128, false)
+        .ok()
+        .and_then(|proceeds| proceeds.try_into().ok())
+        .unwrap_or(u128::MAX)
+}
+
+/// Computes the evolution of the reward rates of the pool while its virtual orders are executed up to the time.
+///
+/// The virtual orders are executed from the state of the pool, or the override state, exactly as they are before a
+/// quote, so the proceeds of orders can be computed without waiting for the contract to checkpoint the reward rates.
+/// The rates of each checkpoint are relative to the start of the window, i.e. the last execution time.
+pub fn reward_rate_path(
+    pool: &TwammPool,
+    override_state: Option<TwammPoolState>,
+    time: BlockTimestamp,
+) -> Result<Vec<RewardRateCheckpoint>, TwammPoolQuoteError> {
+    let mut reward_rates = RewardRates::default();
+    let mut checkpoints = Vec::new();
+
+    pool.execute_virtual_orders(override_state, time, |interval| {
+        reward_rates.accumulate(interval);
+        checkpoints.push(RewardRateCheckpoint {
+            time: interval.end_time,
+            sqrt_ratio: interval.sqrt_ratio_after,
+            reward_rates,
+        });
+    })?;
+
+    Ok(checkpoints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::twamm_pool::TwammSaleRateDelta;
+    use crate::quoting::types::{Address, Pool, QuoteParams, TokenAmount};
+    use alloc::vec;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn pool(token0_sale_rate: u128, token1_sale_rate: u128) -> TwammPool {
+        TwammPool::new(
+            TOKEN0,
+            TOKEN1,
+            0,
+            U256::from(3),
+            U256::one() << 128,
+            1_000_000_000,
+            0,
+            token0_sale_rate,
+            token1_sale_rate,
+            vec![
+                TwammSaleRateDelta {
+                    time: 16,
+                    sale_rate_delta0: token0_sale_rate as i128,
+                    sale_rate_delta1: 0,
+                },
+                TwammSaleRateDelta {
+                    time: 32,
+                    sale_rate_delta0: -2 * token0_sale_rate as i128,
+                    sale_rate_delta1: -(token1_sale_rate as i128),
+                },
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_reward_rate_path() {
+        let pool = pool(1000 << 32, 500 << 32);
+        let path = reward_rate_path(&pool, None, 40).unwrap();
+
+        assert_eq!(
+            path.iter()
+                .map(|checkpoint| checkpoint.time)
+                .collect::<Vec<_>>(),
+            [16, 32, 40]
+        );
+        // nothing is sold after the orders end
+        assert_eq!(path[2].reward_rates, path[1].reward_rates);
+        // the price after the last interval is the price the quote starts from
+        let quote = pool
+            .quote(QuoteParams {
+                token_amount: TokenAmount {
+                    token: TOKEN0,
+                    amount: 0,
+                },
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: None,
+                meta: 40,
+            })
+            .unwrap();
+        assert_eq!(
+            path[2].sqrt_ratio,
+            quote.state_after.full_range_pool_state.sqrt_ratio
+        );
+    }
+
+    #[test]
+    fn test_order_proceeds_add_up() {
+        let pool = pool(1000 << 32, 500 << 32);
+        let mut intervals = Vec::new();
+        pool.execute_virtual_orders(None, 32, |interval| intervals.push(*interval))
+            .unwrap();
+        let path = reward_rate_path(&pool, None, 32).unwrap();
+
+        // an order with a quarter of the sale rate in the first interval earns a quarter of its proceeds
+        assert_eq!(
+            order_proceeds(
+                250 << 32,
+                RewardRates::default().reward_rate0,
+                path[0].reward_rates.reward_rate0
+            ),
+            intervals[0].proceeds0 / 4
+        );
+        // the whole sale rate earns all the proceeds, up to rounding
+        let total = order_proceeds(500 << 32, U256::zero(), path[1].reward_rates.reward_rate1);
+        let expected = intervals[0].proceeds1 + intervals[1].proceeds1;
+        assert!(total <= expected && total + 2 >= expected);
+        assert!(intervals[1].proceeds0 > intervals[0].proceeds0);
+    }
+
+    #[test]
+    fn test_reward_rates_wrap() {
+        let start = U256::MAX - U256::from(1u128 << 127) + 1;
+        let end = U256::from(1u128 << 127);
+        assert_eq!(order_proceeds(2, start, end), 2);
+        assert_eq!(reward_rate_delta(100, 0), U256::zero());
+    }
+}
@@ -0,0 +1,232 @@
+use crate::math::uint::U256;
+use crate::quoting::twamm_pool::{
+    TwammPool, TwammPoolQuoteError, TwammPoolResources, TwammPoolState, VirtualOrderInterval,
+};
+use crate::quoting::twamm_rewards::RewardRates;
+use crate::quoting::types::{BlockTimestamp, Pool, Quote, QuoteParams, TokenAmount};
+use alloc::vec::Vec;
+
+// A spot swap against the pool at a time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SpotSwap {
+    pub time: BlockTimestamp,
+    pub token_amount: TokenAmount,
+    pub sqrt_ratio_limit: Option<U256>,
+}
+
+// The outcome of an interaction with the pool, which executes the virtual orders up to its time before it swaps
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SimulatedInteraction {
+    pub time: BlockTimestamp,
+    // The virtual order intervals executed before the swap, in the order they were executed
+    pub intervals: Vec<VirtualOrderInterval>,
+    // The quote of the swap, or None if the interaction only executed the virtual orders
+    pub quote: Option<Quote<TwammPoolResources, TwammPoolState>>,
+    pub state_after: TwammPoolState,
+}
+
+/// Simulates a TWAMM pool over time with spot swaps interleaved with the execution of its virtual orders.
+///
+/// Like the contract, every interaction first executes the virtual orders up to its time and then swaps against the
+/// resulting state, so the simulation can be used to test strategies and keepers against the order in which the
+/// contract applies them.
+#[derive(Clone, Debug)]
+pub struct TwammSimulator<'a> {
+    pool: &'a TwammPool,
+    state: TwammPoolState,
+    // The reward rates accumulated since the start of the simulation
+    reward_rates: RewardRates,
+}
+
+impl<'a> TwammSimulator<'a> {
+    pub fn new(pool: &'a TwammPool) -> Self {
+        Self {
+            pool,
+            state: pool.get_state(),
+            reward_rates: RewardRates::default(),
+        }
+    }
+
+    pub fn state(&self) -> TwammPoolState {
+        self.state
+    }
+
+    pub fn reward_rates(&self) -> RewardRates {
+        self.reward_rates
+    }
+
+    // Executes the virtual orders up to the time without swapping, like a keeper does
+    pub fn advance(
+        &mut self,
+        time: BlockTimestamp,
+    ) -> Result<SimulatedInteraction, TwammPoolQuoteError> {
+        self.interact(time, None)
+    }
+
+    // Executes the virtual orders up to the time of the swap and then applies the swap
+    pub fn swap(&mut self, swap: SpotSwap) -> Result<SimulatedInteraction, TwammPoolQuoteError> {
+        self.interact(swap.time, Some(swap))
+    }
+
+    // Applies the swaps in order, stopping at the first that fails
+    pub fn run(
+        &mut self,
+        swaps: &[SpotSwap],
+    ) -> Result<Vec<SimulatedInteraction>, TwammPoolQuoteError> {
+        swaps.iter().map(|swap| self.swap(*swap)).collect()
+    }
+
+    fn interact(
+        &mut self,
+        time: BlockTimestamp,
+        swap: Option<SpotSwap>,
+    ) -> Result<SimulatedInteraction, TwammPoolQuoteError> {
+        let mut intervals = Vec::new();
+        let mut reward_rates = self.reward_rates;
+        self.pool
+            .execute_virtual_orders(Some(self.state), time, |interval| {
+                reward_rates.accumulate(interval);
+                intervals.push(*interval);
+            })?;
+
+        let quote = self.pool.quote(QuoteParams {
+            token_amount: swap.map_or(
+                TokenAmount {
+                    token: self.pool.get_key().token0,
+                    amount: 0,
+                },
+                |swap| swap.token_amount,
+            ),
+            sqrt_ratio_limit: swap.and_then(|swap| swap.sqrt_ratio_limit),
+            max_ticks_crossed: None,
+            override_state: Some(self.state),
+            meta: time,
+        })?;
+
+        self.state = quote.state_after;
+        self.reward_rates = reward_rates;
+
+        Ok(SimulatedInteraction {
+            time,
+            intervals,
+            quote: swap.map(|_| quote),
+            state_after: quote.state_after,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quoting::twamm_pool::TwammSaleRateDelta;
+    use crate::quoting::types::Address;
+    use alloc::vec;
+
+    const TOKEN0: Address = Address::from_raw(U256([1, 0, 0, 0]));
+    const TOKEN1: Address = Address::from_raw(U256([2, 0, 0, 0]));
+
+    fn pool() -> TwammPool {
+        TwammPool::new(
+            TOKEN0,
+            TOKEN1,
+            0,
+            U256::from(3),
+            U256::one() << 128,
+            1_000_000_000,
+            0,
+            1000 << 32,
+            0,
+            vec![TwammSaleRateDelta {
+                time: 100,
+                sale_rate_delta0: -(1000 << 32),
+                sale_rate_delta1: 0,
+            }],
+        )
+        .unwrap()
+    }
+
+    fn swap(time: BlockTimestamp, token: Address, amount: i128) -> SpotSwap {
+        SpotSwap {
+            time,
+            token_amount: TokenAmount { token, amount },
+            sqrt_ratio_limit: None,
+        }
+    }
+
+    #[test]
+    fn test_virtual_orders_execute_before_each_swap() {
+        let pool = pool();
+        let mut simulator = TwammSimulator::new(&pool);
+        let interactions = simulator
+            .run(&[swap(10, TOKEN1, 1000), swap(50, TOKEN0, 1000)])
+            .unwrap();
+
+        assert_eq!(interactions[0].intervals.len(), 1);
+        assert_eq!(interactions[0].intervals[0].start_time, 0);
+        assert_eq!(interactions[0].intervals[0].end_time, 10);
+        assert_eq!(interactions[1].intervals[0].start_time, 10);
+        assert_eq!(interactions[1].intervals[0].end_time, 50);
+        assert_eq!(simulator.state().last_execution_time, 50);
+
+        // the same as quoting each swap against the state after the previous one
+        let first = pool
+            .quote(QuoteParams {
+                token_amount: swap(10, TOKEN1, 1000).token_amount,
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: None,
+                meta: 10,
+            })
+            .unwrap();
+        assert_eq!(
+            interactions[0].quote.unwrap().calculated_amount,
+            first.calculated_amount
+        );
+        let second = pool
+            .quote(QuoteParams {
+                token_amount: swap(50, TOKEN0, 1000).token_amount,
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: Some(first.state_after),
+                meta: 50,
+            })
+            .unwrap();
+        assert_eq!(interactions[1].quote, Some(second));
+
+        // the virtual orders sold token0 before the first swap, so it received more than it would have at time zero
+        let at_start = pool
+            .quote(QuoteParams {
+                token_amount: swap(0, TOKEN1, 1000).token_amount,
+                sqrt_ratio_limit: None,
+                max_ticks_crossed: None,
+                override_state: None,
+                meta: 0,
+            })
+            .unwrap();
+        assert!(first.calculated_amount > at_start.calculated_amount);
+    }
+
+    #[test]
+    fn test_advance_crosses_sale_rate_deltas() {
+        let pool = pool();
+        let mut simulator = TwammSimulator::new(&pool);
+        let interaction = simulator.advance(150).unwrap();
+
+        assert_eq!(interaction.quote, None);
+        assert_eq!(
+            interaction
+                .intervals
+                .iter()
+                .map(|interval| (interval.end_time, interval.token0_sale_rate))
+                .collect::<Vec<_>>(),
+            [(100, 1000 << 32), (150, 0)]
+        );
+        assert_eq!(simulator.state().token0_sale_rate, 0);
+        assert!(simulator.reward_rates().reward_rate0 > U256::zero());
+
+        assert_eq!(
+            simulator.swap(swap(100, TOKEN0, 1)),
+            Err(TwammPoolQuoteError::ExecutionTimeExceedsBlockTime)
+        );
+    }
+}
@@ -1,27 +1,341 @@
+use crate::math::tick::{MAX_SQRT_RATIO, MAX_TICK_SPACING, MIN_SQRT_RATIO};
 use crate::math::uint::U256;
 use core::fmt::Debug;
 use core::ops::{Add, Sub};
+use num_traits::Zero;
 
 // Unique key identifying the pool.
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodeKey {
-    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
-    pub token0: U256,
-    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
-    pub token1: U256,
+    pub token0: Address,
+    pub token1: Address,
     pub config: Config,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Config {
+    pub fee: Fee,
+    pub tick_spacing: TickSpacing,
+    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
+    pub extension: U256,
+}
+
+impl Config {
+    pub const fn new(fee: Fee, tick_spacing: TickSpacing, extension: U256) -> Self {
+        Self {
+            fee,
+            tick_spacing,
+            extension,
+        }
+    }
+
+    // Creates a config from the raw onchain values, validating the tick spacing
+    pub const fn from_raw(
+        fee: u64,
+        tick_spacing: u32,
+        extension: U256,
+    ) -> Result<Self, ConfigError> {
+        match TickSpacing::new(tick_spacing) {
+            Ok(tick_spacing) => Ok(Self::new(Fee::from_raw(fee), tick_spacing, extension)),
+            Err(err) => Err(err),
+        }
+    }
+
+    // Creates a config from the raw onchain values without validating them
+    pub const fn from_raw_unchecked(fee: u64, tick_spacing: u32, extension: U256) -> Self {
+        Self::new(
+            Fee::from_raw(fee),
+            TickSpacing::from_raw_unchecked(tick_spacing),
+            extension,
+        )
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConfigError {
+    /// The fee must be less than 10000 basis points.
+    FeeTooLarge,
+    /// Tick spacing must be less than or equal to max tick spacing.
+    TickSpacingTooLarge,
+}
+
+// The fee of a pool as a 0.64 fixed point fraction of the amount swapped
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Fee(u64);
+
+impl Fee {
+    pub const ZERO: Fee = Fee(0);
+
+    // Creates a fee from its 0.64 fixed point representation as stored onchain. Every such fee is below 100%, so
+    // there is nothing to validate
+    pub const fn from_raw(raw: u64) -> Self {
+        Self(raw)
+    }
+
+    // Creates a fee from basis points, rounded down to the nearest representable fee
+    pub const fn from_bps(bps: u32) -> Result<Self, ConfigError> {
+        if bps >= 10_000 {
+            return Err(ConfigError::FeeTooLarge);
+        }
+        Ok(Self((((bps as u128) << 64) / 10_000) as u64))
+    }
+
+    pub const fn raw(self) -> u64 {
+        self.0
+    }
+}
+
+impl From<Fee> for u64 {
+    fn from(value: Fee) -> Self {
+        value.raw()
+    }
+}
+
+// The spacing of initializable ticks of a pool, where zero means the pool only has full range liquidity
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct TickSpacing(u32);
+
+impl TickSpacing {
+    pub const FULL_RANGE: TickSpacing = TickSpacing(0);
+
+    pub const fn new(tick_spacing: u32) -> Result<Self, ConfigError> {
+        if tick_spacing > MAX_TICK_SPACING {
+            return Err(ConfigError::TickSpacingTooLarge);
+        }
+        Ok(Self(tick_spacing))
+    }
+
+    // Creates a tick spacing from the raw onchain value without validating it
+    pub const fn from_raw_unchecked(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+
+    pub const fn is_full_range(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl TryFrom<u32> for TickSpacing {
+    type Error = ConfigError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TickSpacing {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value: u32 = serde::Deserialize::deserialize(deserializer)?;
+        TickSpacing::new(value).map_err(|err| serde::de::Error::custom(alloc::format!("{:?}", err)))
+    }
+}
+
+impl From<TickSpacing> for u32 {
+    fn from(value: TickSpacing) -> Self {
+        value.raw()
+    }
+}
+
+// The address of a token or contract, which must fit in 20 bytes
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct Address(U256);
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum AddressError {
+    /// The value does not fit in 20 bytes.
+    TooLarge,
+    /// The string is not a hex encoded address.
+    InvalidHex,
+    /// The string is mixed case but does not match the checksummed encoding of the address.
+    InvalidChecksum,
+}
+
+impl Address {
+    pub const ZERO: Address = Address(U256([0, 0, 0, 0]));
+
+    pub fn new(value: U256) -> Result<Self, AddressError> {
+        if value.bits() > 160 {
+            return Err(AddressError::TooLarge);
+        }
+        Ok(Self(value))
+    }
+
+    // Creates an address from the low 20 bytes of a word, dropping the higher bytes. Use new or TryFrom to reject
+    // words that do not fit
+    pub const fn from_raw(value: U256) -> Self {
+        Self(U256([value.0[0], value.0[1], value.0[2] & 0xffff_ffff, 0]))
+    }
+
+    pub const fn raw(self) -> U256 {
+        self.0
+    }
+
+    pub fn from_bytes(bytes: [u8; 20]) -> Self {
+        Self(U256::from_big_endian(&bytes))
+    }
+
+    // Returns the low 20 bytes of the address in big endian order
+    pub fn to_bytes(self) -> [u8; 20] {
+        let word = self.0.to_big_endian();
+        let mut bytes = [0; 20];
+        bytes.copy_from_slice(&word[12..]);
+        bytes
+    }
+
+    // Encodes the address as 0x prefixed hex with the mixed case checksum of EIP-55
+    pub fn to_checksum_string(self) -> alloc::string::String {
+        let lower = alloc::format!("{:040x}", self.0);
+        let mut hash = [0u8; 32];
+        let mut keccak = tiny_keccak::Keccak::v256();
+        tiny_keccak::Hasher::update(&mut keccak, lower.as_bytes());
+        tiny_keccak::Hasher::finalize(keccak, &mut hash);
+
+        let mut checksummed = alloc::string::String::with_capacity(42);
+        checksummed.push_str("0x");
+        for (i, c) in lower.chars().enumerate() {
+            let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0xf;
+            checksummed.push(if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            });
+        }
+        checksummed
+    }
+
+    // Parses hex with or without the 0x prefix, validating the checksum if the digits are mixed case
+    pub fn parse(value: &str) -> Result<Self, AddressError> {
+        let digits = value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+            .unwrap_or(value);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(AddressError::InvalidHex);
+        }
+        let address =
+            Self::new(U256::from_str_radix(digits, 16).map_err(|_| AddressError::InvalidHex)?)?;
+
+        let has_upper = digits.bytes().any(|b| b.is_ascii_uppercase());
+        let has_lower = digits.bytes().any(|b| b.is_ascii_lowercase());
+        if has_upper
+            && has_lower
+            && (digits.len() != 40 || address.to_checksum_string()[2..] != *digits)
+        {
+            return Err(AddressError::InvalidChecksum);
+        }
+        Ok(address)
+    }
+}
+
+impl TryFrom<U256> for Address {
+    type Error = AddressError;
+
+    fn try_from(value: U256) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Address> for U256 {
+    fn from(value: Address) -> Self {
+        value.raw()
+    }
+}
+
+impl PartialEq<U256> for Address {
+    fn eq(&self, other: &U256) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<Address> for U256 {
+    fn eq(&self, other: &Address) -> bool {
+        *self == other.0
+    }
+}
+
+impl core::fmt::Display for Address {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.to_checksum_string())
+    }
+}
+
+impl core::fmt::LowerHex for Address {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl core::str::FromStr for Address {
+    type Err = AddressError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::parse(value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Address {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_checksum_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Address {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value: alloc::borrow::Cow<'de, str> = serde::Deserialize::deserialize(deserializer)?;
+        Address::parse(&value).map_err(|err| serde::de::Error::custom(alloc::format!("{:?}", err)))
+    }
+}
+
+// The config with raw fee and tick spacing fields, for code written against the previous layout
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct RawConfig {
     pub fee: u64,
     pub tick_spacing: u32,
-    #[cfg_attr(feature = "serde", serde(with = "crate::quoting::types::serde_u256"))]
     pub extension: U256,
 }
 
+impl TryFrom<RawConfig> for Config {
+    type Error = ConfigError;
+
+    fn try_from(value: RawConfig) -> Result<Config, ConfigError> {
+        Config::from_raw(value.fee, value.tick_spacing, value.extension)
+    }
+}
+
+impl From<Config> for RawConfig {
+    fn from(value: Config) -> RawConfig {
+        RawConfig {
+            fee: value.fee.raw(),
+            tick_spacing: value.tick_spacing.raw(),
+            extension: value.extension,
+        }
+    }
+}
+
 #[cfg(feature = "serde")]
 pub mod serde_u256 {
     use super::*;
@@ -45,20 +359,44 @@ pub mod serde_u256 {
     }
 }
 
-impl From<U256> for Config {
-    fn from(value: U256) -> Config {
-        Config {
-            tick_spacing: (value % U256([4294967296, 0, 0, 0])).as_u32(),
-            fee: ((value >> 32) % U256([0, 1, 0, 0])).as_u64(),
-            extension: value >> 96,
-        }
+// An address in the unprefixed lowercase hex encoding of serde_u256, for formats that predate the checksummed one
+#[cfg(feature = "serde")]
+pub mod serde_address_hex {
+    use super::*;
+    use serde::Serializer;
+
+    pub fn serialize<S>(value: &Address, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serde_u256::serialize(&value.raw(), serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Address, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Address::new(serde_u256::deserialize(deserializer)?)
+            .map_err(|err| serde::de::Error::custom(alloc::format!("{:?}", err)))
+    }
+}
+
+impl TryFrom<U256> for Config {
+    type Error = ConfigError;
+
+    fn try_from(value: U256) -> Result<Config, ConfigError> {
+        Config::from_raw(
+            ((value >> 32) % U256([0, 1, 0, 0])).as_u64(),
+            (value % U256([4294967296, 0, 0, 0])).as_u32(),
+            value >> 96,
+        )
     }
 }
 
 impl From<Config> for U256 {
     fn from(value: Config) -> U256 {
-        U256::from(value.tick_spacing)
-            + (U256::from(value.fee) << 32)
+        U256::from(value.tick_spacing.raw())
+            + (U256::from(value.fee.raw()) << 32)
             + (U256::from(value.extension) << 96)
     }
 }
@@ -74,7 +412,7 @@ pub struct Tick {
 // Amount and token information.
 #[derive(Clone, Copy, PartialEq, Debug, Ord, PartialOrd, Eq)]
 pub struct TokenAmount {
-    pub token: U256,
+    pub token: Address,
     pub amount: i128,
 }
 
@@ -83,12 +421,144 @@ pub struct TokenAmount {
 pub struct QuoteParams<S, M> {
     pub token_amount: TokenAmount,
     pub sqrt_ratio_limit: Option<U256>,
+    // The maximum number of initialized ticks the swap may cross, after which pools with ticks fail the quote
+    pub max_ticks_crossed: Option<u32>,
     pub override_state: Option<S>,
     pub meta: M,
 }
 
+// The direction of a swap through a pool, which is the token that is paid to the pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SwapDirection {
+    ZeroForOne,
+    OneForZero,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SwapDirectionError {
+    /// The token is neither token0 nor token1 of the pool.
+    TokenNotInPool,
+}
+
+impl SwapDirection {
+    // Returns the direction of a swap of the token amount, where the token is paid to the pool for a non-negative
+    // amount and received from it for a negative amount
+    pub fn new(pool_key: &NodeKey, token_amount: TokenAmount) -> Result<Self, SwapDirectionError> {
+        let direction = Self::from_token_in(pool_key, token_amount.token)?;
+        Ok(if token_amount.amount < 0 {
+            direction.reverse()
+        } else {
+            direction
+        })
+    }
+
+    pub fn from_token_in(
+        pool_key: &NodeKey,
+        token_in: Address,
+    ) -> Result<Self, SwapDirectionError> {
+        if token_in == pool_key.token0 {
+            Ok(SwapDirection::ZeroForOne)
+        } else if token_in == pool_key.token1 {
+            Ok(SwapDirection::OneForZero)
+        } else {
+            Err(SwapDirectionError::TokenNotInPool)
+        }
+    }
+
+    pub fn token_in(self, pool_key: &NodeKey) -> Address {
+        match self {
+            SwapDirection::ZeroForOne => pool_key.token0,
+            SwapDirection::OneForZero => pool_key.token1,
+        }
+    }
+
+    pub fn token_out(self, pool_key: &NodeKey) -> Address {
+        match self {
+            SwapDirection::ZeroForOne => pool_key.token1,
+            SwapDirection::OneForZero => pool_key.token0,
+        }
+    }
+
+    pub fn reverse(self) -> Self {
+        match self {
+            SwapDirection::ZeroForOne => SwapDirection::OneForZero,
+            SwapDirection::OneForZero => SwapDirection::ZeroForOne,
+        }
+    }
+
+    // Paying token1 to the pool increases the price of token0 in terms of token1
+    pub fn is_price_increasing(self) -> bool {
+        self == SwapDirection::OneForZero
+    }
+}
+
+// Whether `QuoteParams::validate` accepts a zero amount, which pools quote as a swap that does nothing
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ZeroAmountPolicy {
+    #[default]
+    Allow,
+    Reject,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum QuoteParamsError {
+    /// The token is neither token0 nor token1 of the pool.
+    TokenNotInPool,
+    /// The amount is zero and the policy rejects zero amounts.
+    ZeroAmount,
+    /// The sqrt ratio limit is outside of the range of valid sqrt ratios.
+    SqrtRatioLimitOutOfRange,
+    /// The sqrt ratio limit is on the wrong side of the current price for the direction of the swap.
+    SqrtRatioLimitWrongSide { is_price_increasing: bool },
+}
+
+impl<S: SqrtRatioState, M> QuoteParams<S, M> {
+    /// Checks the params against the pool without quoting it, so that requests that the pool would reject are
+    /// rejected cheaply and with a specific error. The limit is checked against the override state if there is one.
+    pub fn validate<P: Pool<State = S, Meta = M>>(
+        &self,
+        pool: &P,
+        zero_amount: ZeroAmountPolicy,
+    ) -> Result<(), QuoteParamsError> {
+        let direction = SwapDirection::new(pool.get_key(), self.token_amount)
+            .map_err(|_| QuoteParamsError::TokenNotInPool)?;
+
+        if self.token_amount.amount == 0 {
+            return match zero_amount {
+                // pools return an empty quote for a zero amount without looking at the limit
+                ZeroAmountPolicy::Allow => Ok(()),
+                ZeroAmountPolicy::Reject => Err(QuoteParamsError::ZeroAmount),
+            };
+        }
+
+        let Some(limit) = self.sqrt_ratio_limit else {
+            return Ok(());
+        };
+        if !(MIN_SQRT_RATIO..=MAX_SQRT_RATIO).contains(&limit) {
+            return Err(QuoteParamsError::SqrtRatioLimitOutOfRange);
+        }
+
+        let is_price_increasing = direction.is_price_increasing();
+        let sqrt_ratio = self
+            .override_state
+            .as_ref()
+            .map_or_else(|| pool.get_state().sqrt_ratio(), SqrtRatioState::sqrt_ratio);
+        if (is_price_increasing && limit < sqrt_ratio)
+            || (!is_price_increasing && limit > sqrt_ratio)
+        {
+            return Err(QuoteParamsError::SqrtRatioLimitWrongSide {
+                is_price_increasing,
+            });
+        }
+
+        Ok(())
+    }
+}
+
 // The result of all pool swaps is some input and output delta
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Quote<R, S> {
     pub is_price_increasing: bool,
     pub consumed_amount: i128,
@@ -98,9 +568,91 @@ pub struct Quote<R, S> {
     pub fees_paid: u128,
 }
 
+// The token of a swap that the fees were taken from, relative to the quoted token amount
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeToken {
+    Specified,
+    Calculated,
+}
+
+// The fees paid for a quote, denominated in both tokens
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    // Fees are always taken from the input token, so this is the specified token for exact input swaps
+    pub token: FeeToken,
+    pub specified_amount: u128,
+    pub calculated_amount: u128,
+}
+
+impl<R, S> Quote<R, S> {
+    // Returns which token the fees were taken from
+    pub fn fee_token(&self) -> FeeToken {
+        if self.consumed_amount < 0 {
+            FeeToken::Calculated
+        } else {
+            FeeToken::Specified
+        }
+    }
+
+    // Returns the fees paid in both tokens. The amount in the token the fees were not taken from is valued at the
+    // average execution price of the quote and rounded down.
+    pub fn fee_breakdown(&self) -> FeeBreakdown {
+        let consumed_amount = self.consumed_amount.unsigned_abs();
+        let convert = |amount: u128, numerator: u128, denominator: u128| -> u128 {
+            if denominator.is_zero() {
+                0
+            } else {
+                (U256::from(amount) * U256::from(numerator) / U256::from(denominator)).low_u128()
+            }
+        };
+
+        match self.fee_token() {
+            FeeToken::Specified => FeeBreakdown {
+                token: FeeToken::Specified,
+                specified_amount: self.fees_paid,
+                calculated_amount: convert(self.fees_paid, self.calculated_amount, consumed_amount),
+            },
+            FeeToken::Calculated => FeeBreakdown {
+                token: FeeToken::Calculated,
+                specified_amount: convert(self.fees_paid, consumed_amount, self.calculated_amount),
+                calculated_amount: self.fees_paid,
+            },
+        }
+    }
+}
+
 // Commonly used as meta
 pub type BlockTimestamp = u64;
 
+// The block a quote is computed for, which can be converted to the meta of any pool
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockContext {
+    pub number: u64,
+    pub timestamp: BlockTimestamp,
+}
+
+// Meta types that can be derived from the block context, so generic code can quote pools with different meta types
+pub trait FromBlockContext {
+    fn from_block_context(context: BlockContext) -> Self;
+}
+
+impl FromBlockContext for () {
+    fn from_block_context(_context: BlockContext) -> Self {}
+}
+
+impl FromBlockContext for BlockTimestamp {
+    fn from_block_context(context: BlockContext) -> Self {
+        context.timestamp
+    }
+}
+
+impl FromBlockContext for BlockContext {
+    fn from_block_context(context: BlockContext) -> Self {
+        context
+    }
+}
+
 pub trait Pool: Send + Sync + Debug + Clone + PartialEq + Eq {
     type Resources: Add + Sub + Debug + Default + Copy + PartialEq + Eq;
     type State: Debug + Copy + PartialEq + Eq;
@@ -117,6 +669,38 @@ pub trait Pool: Send + Sync + Debug + Clone + PartialEq + Eq {
         params: QuoteParams<Self::State, Self::Meta>,
     ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError>;
 
+    // Quotes with a borrowed override state, which pools that wrap other pools pass on to the wrapped pool as a borrow
+    // of the inner state, so the override is copied once by the innermost pool instead of once per level of nesting
+    fn quote_borrowed(
+        &self,
+        params: QuoteParams<&Self::State, Self::Meta>,
+    ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError> {
+        self.quote(QuoteParams {
+            token_amount: params.token_amount,
+            sqrt_ratio_limit: params.sqrt_ratio_limit,
+            max_ticks_crossed: params.max_ticks_crossed,
+            override_state: params.override_state.copied(),
+            meta: params.meta,
+        })
+    }
+
+    // Quotes with the meta derived from the given block context
+    fn quote_with_block_context(
+        &self,
+        params: QuoteParams<Self::State, BlockContext>,
+    ) -> Result<Quote<Self::Resources, Self::State>, Self::QuoteError>
+    where
+        Self::Meta: FromBlockContext,
+    {
+        self.quote(QuoteParams {
+            token_amount: params.token_amount,
+            sqrt_ratio_limit: params.sqrt_ratio_limit,
+            max_ticks_crossed: params.max_ticks_crossed,
+            override_state: params.override_state,
+            meta: Self::Meta::from_block_context(params.meta),
+        })
+    }
+
     fn has_liquidity(&self) -> bool;
 
     // Returns the greatest tick with non-zero liquidity in the pool
@@ -128,83 +712,125 @@ pub trait Pool: Send + Sync + Debug + Clone + PartialEq + Eq {
     fn is_path_dependent(&self) -> bool;
 }
 
+// Pool states that include the current price of the pool
+pub trait SqrtRatioState {
+    fn sqrt_ratio(&self) -> U256;
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::math::tick::MAX_TICK_SPACING;
     use crate::math::uint::U256;
-    use crate::quoting::types::{Config, TokenAmount};
+    use crate::quoting::types::{
+        Address, AddressError, Config, ConfigError, Fee, FeeBreakdown, FeeToken, Quote, RawConfig,
+        TickSpacing, TokenAmount,
+    };
 
     #[test]
     fn test_ordering_token_amount() {
         assert!(
             TokenAmount {
-                token: U256::one(),
+                token: Address::from_raw(U256::one()),
                 amount: 0,
             } > TokenAmount {
-                token: U256::zero(),
+                token: Address::from_raw(U256::zero()),
                 amount: 1,
             }
         );
         assert_eq!(
             TokenAmount {
-                token: U256::zero(),
+                token: Address::from_raw(U256::zero()),
                 amount: 0,
             },
             TokenAmount {
-                token: U256::zero(),
+                token: Address::from_raw(U256::zero()),
                 amount: 0,
             }
         );
         assert!(
             TokenAmount {
-                token: U256::zero(),
+                token: Address::from_raw(U256::zero()),
                 amount: 0,
             } > TokenAmount {
-                token: U256::zero(),
+                token: Address::from_raw(U256::zero()),
                 amount: -1,
             }
         );
         assert!(
             TokenAmount {
-                token: U256::zero(),
+                token: Address::from_raw(U256::zero()),
                 amount: 0,
             } < TokenAmount {
-                token: U256::one(),
+                token: Address::from_raw(U256::one()),
                 amount: -1,
             }
         );
         assert!(
             TokenAmount {
-                token: U256::zero(),
+                token: Address::from_raw(U256::zero()),
                 amount: 0,
             } < TokenAmount {
-                token: U256::zero(),
+                token: Address::from_raw(U256::zero()),
                 amount: 1,
             }
         );
     }
 
     #[test]
-    fn test_config_from_u256() {
-        let c: Config = U256::from_str_radix("9784678070511645692802677866596", 10)
-            .unwrap()
-            .into();
-        assert_eq!(
-            c,
-            Config {
-                tick_spacing: 100,
-                fee: 1 << 63,
-                extension: U256::from(123)
+    fn test_fee_breakdown() {
+        let quote = |consumed_amount: i128, calculated_amount: u128, fees_paid: u128| Quote {
+            is_price_increasing: false,
+            consumed_amount,
+            calculated_amount,
+            execution_resources: (),
+            state_after: (),
+            fees_paid,
+        };
+
+        // exact input, fees are taken from the specified token
+        assert_eq!(
+            quote(1000, 1990, 10).fee_breakdown(),
+            FeeBreakdown {
+                token: FeeToken::Specified,
+                specified_amount: 10,
+                calculated_amount: 19,
+            }
+        );
+
+        // exact output, fees are taken from the calculated token
+        assert_eq!(
+            quote(-1000, 505, 5).fee_breakdown(),
+            FeeBreakdown {
+                token: FeeToken::Calculated,
+                specified_amount: 9,
+                calculated_amount: 5,
+            }
+        );
+
+        assert_eq!(
+            quote(0, 0, 0).fee_breakdown(),
+            FeeBreakdown {
+                token: FeeToken::Specified,
+                specified_amount: 0,
+                calculated_amount: 0,
             }
         );
     }
 
+    #[test]
+    fn test_config_from_u256() {
+        let c =
+            Config::try_from(U256::from_str_radix("9784678070511645692802677866596", 10).unwrap());
+        assert_eq!(c, Config::from_raw(1 << 63, 100, U256::from(123)));
+        assert_eq!(
+            Config::try_from(U256::from(MAX_TICK_SPACING + 1)),
+            Err(ConfigError::TickSpacingTooLarge)
+        );
+    }
+
     #[test]
     fn test_u256_from_config() {
-        let c: Config = Config {
-            tick_spacing: 100,
-            fee: 1 << 63,
-            extension: U256::from(123),
-        };
+        let c: Config = Config::from_raw_unchecked(1 << 63, 100, U256::from(123));
         let v: U256 = c.into();
         assert_eq!(
             v,
@@ -212,25 +838,261 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fee_from_bps() {
+        assert_eq!(Fee::from_bps(0), Ok(Fee::ZERO));
+        assert_eq!(Fee::from_bps(1), Ok(Fee::from_raw(1844674407370955)));
+        assert_eq!(Fee::from_bps(5_000), Ok(Fee::from_raw(1 << 63)));
+        assert_eq!(
+            Fee::from_bps(9_999),
+            Ok(Fee::from_raw(18444899399302180660))
+        );
+        assert_eq!(Fee::from_bps(10_000), Err(ConfigError::FeeTooLarge));
+    }
+
+    #[test]
+    fn test_tick_spacing_new() {
+        assert_eq!(TickSpacing::new(0), Ok(TickSpacing::FULL_RANGE));
+        assert!(TickSpacing::FULL_RANGE.is_full_range());
+        assert_eq!(TickSpacing::new(100).unwrap().raw(), 100);
+        assert!(TickSpacing::new(MAX_TICK_SPACING).is_ok());
+        assert_eq!(
+            TickSpacing::new(MAX_TICK_SPACING + 1),
+            Err(ConfigError::TickSpacingTooLarge)
+        );
+        // the raw constructor does not validate
+        assert_eq!(
+            TickSpacing::from_raw_unchecked(MAX_TICK_SPACING + 1).raw(),
+            MAX_TICK_SPACING + 1
+        );
+        assert_eq!(
+            TickSpacing::try_from(MAX_TICK_SPACING + 1),
+            Err(ConfigError::TickSpacingTooLarge)
+        );
+        assert_eq!(
+            Config::from_raw(0, MAX_TICK_SPACING + 1, U256::zero()),
+            Err(ConfigError::TickSpacingTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_raw_config_round_trip() {
+        let raw = RawConfig {
+            fee: 1 << 63,
+            tick_spacing: 100,
+            extension: U256::from(123),
+        };
+        let config = Config::try_from(raw).unwrap();
+        assert_eq!(
+            config,
+            Config::new(
+                Fee::from_bps(5_000).unwrap(),
+                TickSpacing::new(100).unwrap(),
+                U256::from(123)
+            )
+        );
+        assert_eq!(RawConfig::from(config), raw);
+        assert_eq!(
+            Config::try_from(RawConfig {
+                tick_spacing: MAX_TICK_SPACING + 1,
+                ..raw
+            }),
+            Err(ConfigError::TickSpacingTooLarge)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_config_serde_layout_unchanged() {
+        let config = Config::from_raw_unchecked(1 << 63, 100, U256::from(123));
+        let json = serde_json::to_value(config).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"fee": 9223372036854775808u64, "tick_spacing": 100, "extension": "7b"})
+        );
+        assert_eq!(serde_json::from_value::<Config>(json).unwrap(), config);
+    }
+
+    #[test]
+    fn test_swap_direction() {
+        use crate::quoting::types::{NodeKey, SwapDirection, SwapDirectionError};
+
+        let key = NodeKey {
+            token0: Address::from_raw(U256::one()),
+            token1: Address::from_raw(U256::from(2)),
+            config: Config::from_raw_unchecked(0, 0, U256::zero()),
+        };
+        let direction = |token: u64, amount: i128| {
+            SwapDirection::new(
+                &key,
+                TokenAmount {
+                    token: Address::from_raw(U256::from(token)),
+                    amount,
+                },
+            )
+        };
+
+        assert_eq!(direction(1, 100), Ok(SwapDirection::ZeroForOne));
+        assert_eq!(direction(2, 100), Ok(SwapDirection::OneForZero));
+        assert_eq!(direction(1, -100), Ok(SwapDirection::OneForZero));
+        assert_eq!(direction(2, -100), Ok(SwapDirection::ZeroForOne));
+        assert_eq!(direction(3, 100), Err(SwapDirectionError::TokenNotInPool));
+
+        let direction = SwapDirection::OneForZero;
+        assert!(direction.is_price_increasing());
+        assert!(!direction.reverse().is_price_increasing());
+        assert_eq!(direction.token_in(&key), key.token1);
+        assert_eq!(direction.token_out(&key), key.token0);
+        assert_eq!(
+            SwapDirection::from_token_in(&key, key.token0),
+            Ok(SwapDirection::ZeroForOne)
+        );
+    }
+
+    #[test]
+    fn test_quote_params_validate() {
+        use crate::quoting::full_range_pool::{FullRangePool, FullRangePoolState};
+        use crate::quoting::types::{
+            NodeKey, Pool, QuoteParams, QuoteParamsError, ZeroAmountPolicy,
+        };
+
+        let pool = FullRangePool::new(
+            NodeKey {
+                token0: Address::from_raw(U256::one()),
+                token1: Address::from_raw(U256::from(2)),
+                config: Config::from_raw_unchecked(0, 0, U256::zero()),
+            },
+            FullRangePoolState {
+                sqrt_ratio: U256::one() << 128,
+                liquidity: 1_000_000,
+            },
+        )
+        .unwrap();
+        let params = |token: u64, amount: i128, sqrt_ratio_limit: Option<U256>| QuoteParams {
+            token_amount: TokenAmount {
+                token: Address::from_raw(U256::from(token)),
+                amount,
+            },
+            sqrt_ratio_limit,
+            max_ticks_crossed: None,
+            override_state: None,
+            meta: (),
+        };
+        let validate = |params: QuoteParams<_, _>, policy| {
+            let result = params.validate(&pool, policy);
+            // params that pass validation must be quotable
+            if result.is_ok() {
+                pool.quote(params).unwrap();
+            }
+            result
+        };
+
+        assert_eq!(
+            validate(
+                params(1, 100, Some(U256::one() << 127)),
+                ZeroAmountPolicy::Allow
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            validate(params(3, 100, None), ZeroAmountPolicy::Allow),
+            Err(QuoteParamsError::TokenNotInPool)
+        );
+        assert_eq!(
+            validate(params(1, 0, Some(U256::MAX)), ZeroAmountPolicy::Allow),
+            Ok(())
+        );
+        assert_eq!(
+            validate(params(1, 0, None), ZeroAmountPolicy::Reject),
+            Err(QuoteParamsError::ZeroAmount)
+        );
+        assert_eq!(
+            validate(params(2, 100, Some(U256::MAX)), ZeroAmountPolicy::Allow),
+            Err(QuoteParamsError::SqrtRatioLimitOutOfRange)
+        );
+        assert_eq!(
+            validate(
+                params(2, 100, Some(U256::one() << 127)),
+                ZeroAmountPolicy::Allow
+            ),
+            Err(QuoteParamsError::SqrtRatioLimitWrongSide {
+                is_price_increasing: true
+            })
+        );
+        assert_eq!(
+            validate(
+                params(2, -100, Some(U256::one() << 129)),
+                ZeroAmountPolicy::Allow
+            ),
+            Err(QuoteParamsError::SqrtRatioLimitWrongSide {
+                is_price_increasing: false
+            })
+        );
+
+        let mut overridden = params(1, 100, Some(U256::one() << 129));
+        assert!(validate(overridden, ZeroAmountPolicy::Allow).is_err());
+        overridden.override_state = Some(FullRangePoolState {
+            sqrt_ratio: U256::MAX >> 90,
+            liquidity: 1_000_000,
+        });
+        assert_eq!(validate(overridden, ZeroAmountPolicy::Allow), Ok(()));
+    }
+
+    #[test]
+    fn test_address_checksum() {
+        let checksummed = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let address: Address = checksummed.parse().unwrap();
+
+        assert_eq!(address.to_checksum_string(), checksummed);
+        assert_eq!(Address::parse(&checksummed.to_lowercase()), Ok(address));
+        assert_eq!(Address::parse(&checksummed[2..]), Ok(address));
+        assert_eq!(Address::from_bytes(address.to_bytes()), address);
+        assert_eq!(
+            Address::parse("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            Err(AddressError::InvalidChecksum)
+        );
+        assert_eq!(Address::parse("0x"), Err(AddressError::InvalidHex));
+        assert_eq!(
+            Address::new(U256::one() << 160),
+            Err(AddressError::TooLarge)
+        );
+        assert_eq!(Address::try_from(U256::from(7)).unwrap(), U256::from(7));
+        assert_eq!(Address::try_from(U256::MAX), Err(AddressError::TooLarge));
+        // the raw constructor keeps the low 20 bytes
+        assert_eq!(Address::from_raw(U256::MAX), (U256::one() << 160) - 1);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_address_serde_accepts_legacy_hex() {
+        let address = Address::from_raw(U256::from(0xab));
+
+        assert_eq!(
+            serde_json::to_string(&address).unwrap(),
+            "\"0x00000000000000000000000000000000000000AB\""
+        );
+        assert_eq!(serde_json::from_str::<Address>("\"ab\"").unwrap(), address);
+        assert_eq!(
+            serde_json::to_string(&Address::from_raw(U256::MAX)).unwrap(),
+            "\"0xFFfFfFffFFfffFFfFFfFFFFFffFFFffffFfFFFfF\""
+        );
+    }
+
     #[test]
     #[cfg(feature = "serde")]
     fn test_node_key() {
         use crate::quoting::types::NodeKey;
 
         let key = NodeKey {
-            token0: U256::from(1),
-            token1: U256::from(2),
-            config: Config {
-                tick_spacing: 100,
-                fee: 1 << 63,
-                extension: U256::from(123),
-            },
+            token0: Address::from_raw(U256::from(1)),
+            token1: Address::from_raw(U256::from(2)),
+            config: Config::from_raw_unchecked(1 << 63, 100, U256::from(123)),
         };
 
         let serialized = serde_json::to_string(&key).unwrap();
         let expected = serde_json::json!({
-            "token0": "1",
-            "token1": "2",
+            "token0": "0x0000000000000000000000000000000000000001",
+            "token1": "0x0000000000000000000000000000000000000002",
             "config": {
                 "tick_spacing": 100,
                 "fee": "9223372036854775808",
@@ -239,5 +1101,6 @@ mod tests {
         });
 
         assert_eq!(serde_json::from_str::<NodeKey>(&serialized).unwrap(), key);
+        assert!(serde_json::from_value::<TickSpacing>((MAX_TICK_SPACING + 1).into()).is_err());
     }
 }
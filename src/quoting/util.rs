@@ -2,6 +2,7 @@ use crate::math::tick::{MAX_TICK, MIN_TICK};
 use crate::math::uint::{u256_to_float_base_x128, U256};
 use crate::quoting::base_pool::MAX_TICK_SPACING;
 use crate::quoting::types::Tick;
+use alloc::string::String;
 use alloc::vec::Vec;
 use num_traits::Zero;
 
@@ -75,6 +76,66 @@ pub fn construct_sorted_ticks(
     liquidity: u128,
     current_tick: i32,
 ) -> Result<Vec<Tick>, ConstructSortedTicksError> {
+    construct_sorted_ticks_with_report(
+        partial_ticks,
+        min_tick_searched,
+        max_tick_searched,
+        tick_spacing,
+        liquidity,
+        current_tick,
+    )
+    .map(|reconstructed| reconstructed.sorted_ticks)
+}
+
+// A boundary tick that existed in the partial data and whose liquidity delta was changed
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TickAdjustment {
+    pub index: i32,
+    pub liquidity_delta_before: i128,
+    pub liquidity_delta_after: i128,
+}
+
+// Everything that construct_sorted_ticks changed to make a partial view of the ticks consistent. The liquidity outside
+// of the searched range is the liquidity of the positions whose ticks were not returned, so it measures how much of
+// the pool the partial view is missing.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReconstructionReport {
+    pub partial_tick_count: usize,
+    // Boundary ticks that were not in the partial data, with the liquidity delta they were given
+    pub synthesized_ticks: Vec<Tick>,
+    pub adjusted_ticks: Vec<TickAdjustment>,
+    // The liquidity delta attributed to the lower boundary for positions starting below the searched range
+    pub liquidity_below_range: i128,
+    // The liquidity delta attributed to the upper boundary for positions ending above the searched range
+    pub liquidity_above_range: i128,
+}
+
+impl ReconstructionReport {
+    // Returns true if the partial data was already consistent and nothing was synthesized or adjusted
+    pub fn is_empty(&self) -> bool {
+        self.synthesized_ticks.is_empty() && self.adjusted_ticks.is_empty()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReconstructedTicks {
+    pub sorted_ticks: Vec<Tick>,
+    pub report: ReconstructionReport,
+}
+
+/// Same as [`construct_sorted_ticks`], but also reports the ticks that were synthesized or adjusted at the
+/// boundaries of the searched range.
+pub fn construct_sorted_ticks_with_report(
+    partial_ticks: Vec<Tick>,
+    min_tick_searched: i32,
+    max_tick_searched: i32,
+    tick_spacing: u32,
+    liquidity: u128,
+    current_tick: i32,
+) -> Result<ReconstructedTicks, ConstructSortedTicksError> {
     if current_tick < min_tick_searched || current_tick > max_tick_searched {
         return Err(ConstructSortedTicksError::CurrentTickOutsideSearchedRange);
     }
@@ -117,21 +178,34 @@ pub fn construct_sorted_ticks(
     let all_delta_sum: i128 = result.iter().map(|t| t.liquidity_delta).sum();
     let max_tick_liquidity_delta = -(min_tick_liquidity_delta + all_delta_sum);
 
+    let mut report = ReconstructionReport {
+        partial_tick_count: partial_ticks.len(),
+        liquidity_below_range: min_tick_liquidity_delta,
+        liquidity_above_range: max_tick_liquidity_delta,
+        ..Default::default()
+    };
+
     if min_tick_liquidity_delta != 0 {
         // Check if we already have min/max boundary ticks
         let has_min_tick = result.first().map_or(false, |t| t.index == valid_min_tick);
 
         // Add or update min boundary tick
         if has_min_tick {
-            result.first_mut().unwrap().liquidity_delta += min_tick_liquidity_delta;
+            let tick = result.first_mut().unwrap();
+            let liquidity_delta_before = tick.liquidity_delta;
+            tick.liquidity_delta += min_tick_liquidity_delta;
+            report.adjusted_ticks.push(TickAdjustment {
+                index: tick.index,
+                liquidity_delta_before,
+                liquidity_delta_after: tick.liquidity_delta,
+            });
         } else {
-            result.insert(
-                0,
-                Tick {
-                    index: valid_min_tick,
-                    liquidity_delta: min_tick_liquidity_delta,
-                },
-            );
+            let tick = Tick {
+                index: valid_min_tick,
+                liquidity_delta: min_tick_liquidity_delta,
+            };
+            result.insert(0, tick);
+            report.synthesized_ticks.push(tick);
         }
     }
 
@@ -141,17 +215,183 @@ pub fn construct_sorted_ticks(
         // Add or update max boundary tick
         if has_max_tick {
             // Update existing tick
-            result.last_mut().unwrap().liquidity_delta += max_tick_liquidity_delta;
+            let tick = result.last_mut().unwrap();
+            let liquidity_delta_before = tick.liquidity_delta;
+            tick.liquidity_delta += max_tick_liquidity_delta;
+            report.adjusted_ticks.push(TickAdjustment {
+                index: tick.index,
+                liquidity_delta_before,
+                liquidity_delta_after: tick.liquidity_delta,
+            });
         } else {
             // Add new max boundary tick
-            result.push(Tick {
+            let tick = Tick {
                 index: valid_max_tick,
                 liquidity_delta: max_tick_liquidity_delta,
-            });
+            };
+            result.push(tick);
+            report.synthesized_ticks.push(tick);
         }
     }
 
-    Ok(result)
+    Ok(ReconstructedTicks {
+        sorted_ticks: result,
+        report,
+    })
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ParseAmountError {
+    /// The string is not a decimal number, e.g. "1.5" or "-0.25".
+    InvalidFormat,
+    /// The number has non-zero digits beyond the decimals of the token.
+    TooManyDecimals,
+    /// The amount in the smallest unit of the token does not fit in an i128.
+    Overflow,
+}
+
+// Parses a decimal string, e.g. "1.5", into an amount in the smallest unit of a token with the given decimals
+pub fn parse_amount(value: &str, decimals: u8) -> Result<i128, ParseAmountError> {
+    let (negative, unsigned) = match value.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, value),
+    };
+    let (integer, fraction) = match unsigned.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (unsigned, None),
+    };
+
+    let is_digits = |digits: &str| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit());
+    if !is_digits(integer) || !fraction.is_none_or(is_digits) {
+        return Err(ParseAmountError::InvalidFormat);
+    }
+    let fraction = fraction.unwrap_or("");
+
+    if fraction.bytes().skip(decimals as usize).any(|b| b != b'0') {
+        return Err(ParseAmountError::TooManyDecimals);
+    }
+
+    // accumulate towards the sign of the result so that i128::MIN can be parsed
+    let mut amount: i128 = 0;
+    let digits = integer
+        .bytes()
+        .chain(fraction.bytes().take(decimals as usize))
+        .chain(core::iter::repeat_n(
+            b'0',
+            (decimals as usize).saturating_sub(fraction.len()),
+        ));
+    for digit in digits {
+        let digit = (digit - b'0') as i128;
+        amount = amount
+            .checked_mul(10)
+            .and_then(|amount| {
+                if negative {
+                    amount.checked_sub(digit)
+                } else {
+                    amount.checked_add(digit)
+                }
+            })
+            .ok_or(ParseAmountError::Overflow)?;
+    }
+
+    Ok(amount)
+}
+
+// Formats an amount in the smallest unit of a token with the given decimals as a decimal string without trailing zeros
+pub fn format_amount(amount: i128, decimals: u8) -> String {
+    let digits = alloc::format!(
+        "{:0>width$}",
+        amount.unsigned_abs(),
+        width = decimals as usize + 1
+    );
+    let (integer, fraction) = digits.split_at(digits.len() - decimals as usize);
+    let fraction = fraction.trim_end_matches('0');
+
+    let mut result = String::with_capacity(digits.len() + 2);
+    if amount < 0 {
+        result.push('-');
+    }
+    result.push_str(integer);
+    if !fraction.is_empty() {
+        result.push('.');
+        result.push_str(fraction);
+    }
+    result
+}
+
+// How display and approximation helpers round, which is independent of the rounding of the core math that must match
+// the contracts. Floor and ceil round toward negative and positive infinity respectively.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum RoundingPolicy {
+    #[default]
+    Floor,
+    Ceil,
+    // Rounds to the nearest value and ties to the even one, i.e. banker's rounding
+    HalfEven,
+}
+
+impl RoundingPolicy {
+    // Divides the numerator by the denominator, or returns None if the denominator is zero or the result overflows
+    pub fn divide(self, numerator: U256, denominator: U256) -> Option<U256> {
+        self.divide_magnitude(numerator, denominator, false)
+    }
+
+    // Divides the magnitude of a value with the sign, rounding the magnitude so that the signed value is rounded
+    fn divide_magnitude(self, magnitude: U256, denominator: U256, negative: bool) -> Option<U256> {
+        if denominator.is_zero() {
+            return None;
+        }
+
+        let (quotient, remainder) = magnitude.div_mod(denominator);
+        let round_up = !remainder.is_zero()
+            && match self {
+                RoundingPolicy::Floor => negative,
+                RoundingPolicy::Ceil => !negative,
+                RoundingPolicy::HalfEven => {
+                    let rest = denominator - remainder;
+                    remainder > rest || (remainder == rest && quotient.bit(0))
+                }
+            };
+
+        if round_up {
+            quotient.checked_add(U256::one())
+        } else {
+            Some(quotient)
+        }
+    }
+}
+
+// Formats an amount like format_amount, rounded to at most the given number of decimals for display
+pub fn format_amount_rounded(
+    amount: i128,
+    decimals: u8,
+    precision: u8,
+    rounding: RoundingPolicy,
+) -> String {
+    if precision >= decimals {
+        return format_amount(amount, decimals);
+    }
+
+    // every amount is less than 10**39, so larger divisors round the same way
+    let divisor = U256::exp10(((decimals - precision) as usize).min(39));
+    let negative = amount < 0;
+    let magnitude = rounding
+        .divide_magnitude(U256::from(amount.unsigned_abs()), divisor, negative)
+        .unwrap()
+        .as_u128();
+
+    // the rounded magnitude is at most a tenth of the magnitude of any amount plus one, so it fits
+    let rounded = if negative {
+        -(magnitude as i128)
+    } else {
+        magnitude as i128
+    };
+    format_amount(rounded, precision)
 }
 
 #[cfg(test)]
@@ -161,8 +401,9 @@ mod tests {
     use crate::quoting::types::Tick;
     use crate::quoting::util::find_nearest_initialized_tick_index;
     use crate::quoting::util::{
-        approximate_number_of_tick_spacings_crossed, construct_sorted_ticks,
-        u256_to_float_base_x128,
+        approximate_number_of_tick_spacings_crossed, construct_sorted_ticks, format_amount,
+        format_amount_rounded, parse_amount, u256_to_float_base_x128, ParseAmountError,
+        RoundingPolicy,
     };
     use alloc::vec;
 
@@ -373,7 +614,10 @@ mod tests {
 
     mod construct_sorted_ticks_tests {
         use super::*;
-        use crate::quoting::util::ConstructSortedTicksError;
+        use crate::quoting::util::{
+            construct_sorted_ticks_with_report, ConstructSortedTicksError, ReconstructionReport,
+            TickAdjustment,
+        };
 
         #[test]
         fn test_empty_ticks() {
@@ -602,5 +846,189 @@ mod tests {
 
             assert_eq!(active_liquidity, 1000);
         }
+
+        #[test]
+        fn test_report_of_synthesized_and_adjusted_ticks() {
+            let partial_ticks = vec![
+                Tick {
+                    index: -50,
+                    liquidity_delta: 100,
+                },
+                Tick {
+                    index: 0,
+                    liquidity_delta: 500,
+                },
+                Tick {
+                    index: 100,
+                    liquidity_delta: -200,
+                },
+            ];
+
+            let reconstructed =
+                construct_sorted_ticks_with_report(partial_ticks, -45, 145, 10, 750, 52).unwrap();
+
+            assert_eq!(
+                reconstructed.report,
+                ReconstructionReport {
+                    partial_tick_count: 3,
+                    synthesized_ticks: vec![Tick {
+                        index: 150,
+                        liquidity_delta: -550
+                    }],
+                    adjusted_ticks: vec![TickAdjustment {
+                        index: -50,
+                        liquidity_delta_before: 100,
+                        liquidity_delta_after: 250
+                    }],
+                    liquidity_below_range: 150,
+                    liquidity_above_range: -550,
+                }
+            );
+            assert_eq!(reconstructed.sorted_ticks.len(), 4);
+            assert_eq!(
+                reconstructed.sorted_ticks[0],
+                Tick {
+                    index: -50,
+                    liquidity_delta: 250
+                }
+            );
+        }
+
+        #[test]
+        fn test_report_of_consistent_ticks_is_empty() {
+            let partial_ticks = vec![
+                Tick {
+                    index: -10,
+                    liquidity_delta: 100,
+                },
+                Tick {
+                    index: 10,
+                    liquidity_delta: -100,
+                },
+            ];
+
+            let reconstructed =
+                construct_sorted_ticks_with_report(partial_ticks.clone(), -100, 100, 10, 100, 0)
+                    .unwrap();
+
+            assert!(reconstructed.report.is_empty());
+            assert_eq!(reconstructed.report.partial_tick_count, 2);
+            assert_eq!(reconstructed.sorted_ticks, partial_ticks);
+        }
+    }
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("1.5", 18), Ok(1_500_000_000_000_000_000));
+        assert_eq!(parse_amount("-0.25", 6), Ok(-250_000));
+        assert_eq!(parse_amount("42", 0), Ok(42));
+        assert_eq!(parse_amount("0.000001", 6), Ok(1));
+        assert_eq!(parse_amount("1.500", 1), Ok(15));
+        assert_eq!(parse_amount("007", 2), Ok(700));
+        assert_eq!(
+            parse_amount("-170141183460469231731687303715884105728", 0),
+            Ok(i128::MIN)
+        );
+    }
+
+    #[test]
+    fn test_parse_amount_errors() {
+        for invalid in ["", "-", ".5", "1.", "1.2.3", "1e18", "+1", " 1", "1,5"] {
+            assert_eq!(
+                parse_amount(invalid, 18),
+                Err(ParseAmountError::InvalidFormat),
+                "{invalid}"
+            );
+        }
+        assert_eq!(
+            parse_amount("0.0000001", 6),
+            Err(ParseAmountError::TooManyDecimals)
+        );
+        assert_eq!(
+            parse_amount("170141183460469231731687303715884105728", 0),
+            Err(ParseAmountError::Overflow)
+        );
+        assert_eq!(parse_amount("1", 39), Err(ParseAmountError::Overflow));
+        assert_eq!(parse_amount("0", 255), Ok(0));
+    }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(1_500_000_000_000_000_000, 18), "1.5");
+        assert_eq!(format_amount(-250_000, 6), "-0.25");
+        assert_eq!(format_amount(1, 6), "0.000001");
+        assert_eq!(format_amount(0, 18), "0");
+        assert_eq!(format_amount(42, 0), "42");
+        assert_eq!(
+            format_amount(i128::MIN, 18),
+            "-170141183460469231731.687303715884105728"
+        );
+    }
+
+    #[test]
+    fn test_amount_round_trip() {
+        for (amount, decimals) in [(1, 18), (-123_456_789, 6), (i128::MAX, 38), (i128::MIN, 0)] {
+            assert_eq!(
+                parse_amount(&format_amount(amount, decimals), decimals),
+                Ok(amount)
+            );
+        }
+    }
+
+    #[test]
+    fn test_rounding_policy() {
+        let divide = |policy: RoundingPolicy, numerator: u64, denominator: u64| {
+            policy.divide(U256::from(numerator), U256::from(denominator))
+        };
+        assert_eq!(divide(RoundingPolicy::Floor, 7, 2), Some(U256::from(3)));
+        assert_eq!(divide(RoundingPolicy::Ceil, 7, 2), Some(U256::from(4)));
+        assert_eq!(divide(RoundingPolicy::HalfEven, 5, 2), Some(U256::from(2)));
+        assert_eq!(divide(RoundingPolicy::HalfEven, 7, 2), Some(U256::from(4)));
+        assert_eq!(divide(RoundingPolicy::HalfEven, 8, 3), Some(U256::from(3)));
+        assert_eq!(divide(RoundingPolicy::Ceil, 6, 3), Some(U256::from(2)));
+        assert_eq!(divide(RoundingPolicy::Floor, 1, 0), None);
+        assert_eq!(
+            RoundingPolicy::Ceil.divide(U256::MAX, U256::from(2)),
+            Some((U256::MAX >> 1) + 1)
+        );
+    }
+
+    #[test]
+    fn test_format_amount_rounded() {
+        let amount = 1_234_500_000_000_000_000;
+        assert_eq!(
+            format_amount_rounded(amount, 18, 3, RoundingPolicy::Floor),
+            "1.234"
+        );
+        assert_eq!(
+            format_amount_rounded(amount, 18, 3, RoundingPolicy::Ceil),
+            "1.235"
+        );
+        assert_eq!(
+            format_amount_rounded(amount, 18, 3, RoundingPolicy::HalfEven),
+            "1.234"
+        );
+        assert_eq!(
+            format_amount_rounded(-amount, 18, 3, RoundingPolicy::Floor),
+            "-1.235"
+        );
+        assert_eq!(
+            format_amount_rounded(-amount, 18, 3, RoundingPolicy::Ceil),
+            "-1.234"
+        );
+        assert_eq!(
+            format_amount_rounded(1_999_999, 6, 2, RoundingPolicy::HalfEven),
+            "2"
+        );
+        assert_eq!(
+            format_amount_rounded(15, 6, 8, RoundingPolicy::Ceil),
+            "0.000015"
+        );
+        assert_eq!(format_amount_rounded(1, 255, 0, RoundingPolicy::Ceil), "1");
+        assert_eq!(format_amount_rounded(-1, 255, 0, RoundingPolicy::Ceil), "0");
+        assert_eq!(
+            format_amount_rounded(i128::MIN, 0, 0, RoundingPolicy::Floor),
+            format_amount(i128::MIN, 0)
+        );
     }
 }
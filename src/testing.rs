@@ -0,0 +1,86 @@
+// Helpers for tests that compare amounts computed by the SDK against amounts from elsewhere, e.g. onchain values, which
+// may differ by a few wei because of rounding
+
+use crate::math::uint::U256;
+
+// Amounts whose distance from each other can be measured in wei
+pub trait AmountDistance: Copy {
+    fn distance(self, other: Self) -> U256;
+}
+
+impl AmountDistance for u128 {
+    fn distance(self, other: Self) -> U256 {
+        U256::from(self.abs_diff(other))
+    }
+}
+
+impl AmountDistance for u64 {
+    fn distance(self, other: Self) -> U256 {
+        U256::from(self.abs_diff(other))
+    }
+}
+
+impl AmountDistance for i128 {
+    fn distance(self, other: Self) -> U256 {
+        U256::from(self.abs_diff(other))
+    }
+}
+
+impl AmountDistance for U256 {
+    fn distance(self, other: Self) -> U256 {
+        self.abs_diff(other)
+    }
+}
+
+// Returns whether the amounts differ by at most the tolerance in wei
+pub fn amount_within<T: AmountDistance>(actual: T, expected: T, tolerance: u128) -> bool {
+    actual.distance(expected) <= U256::from(tolerance)
+}
+
+/// Asserts that two amounts differ by at most a tolerance in wei, printing both amounts and their distance otherwise.
+///
+/// ```
+/// # use evm_ekubo_sdk::assert_amount_within;
+/// assert_amount_within!(999_999u128, 1_000_000u128, 1);
+/// assert_amount_within!(-5i128, -4i128, 1, "rounded toward negative infinity");
+/// ```
+#[macro_export]
+macro_rules! assert_amount_within {
+    ($actual:expr, $expected:expr, $tolerance:expr $(,)?) => {
+        $crate::assert_amount_within!($actual, $expected, $tolerance, "")
+    };
+    ($actual:expr, $expected:expr, $tolerance:expr, $($message:tt)+) => {{
+        let (actual, expected) = ($actual, $expected);
+        let tolerance: u128 = $tolerance;
+        if !$crate::testing::amount_within(actual, expected, tolerance) {
+            panic!(
+                "amounts differ by {} wei, more than the tolerance of {} wei\n  actual: {:?}\nexpected: {:?}\n{}",
+                $crate::testing::AmountDistance::distance(actual, expected),
+                tolerance,
+                actual,
+                expected,
+                format_args!($($message)+)
+            );
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amount_within() {
+        assert!(amount_within(10u128, 11u128, 1));
+        assert!(!amount_within(10u128, 12u128, 1));
+        assert!(amount_within(i128::MIN, i128::MIN + 1, 1));
+        assert!(amount_within(U256::MAX, U256::MAX - 3, 3));
+        assert!(amount_within(u64::MAX, 0, u64::MAX as u128));
+    }
+
+    #[test]
+    #[should_panic(expected = "amounts differ by 2 wei")]
+    fn test_assert_amount_within_panics() {
+        assert_amount_within!(10u128, 12u128, 1);
+    }
+}